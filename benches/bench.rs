@@ -38,10 +38,11 @@ use halo2_ecc::bigint::{
 };
 use halo2_rsa::{
     impl_pkcs1v15_basic_circuit, AssignedBigUint, AssignedRSAPubE, AssignedRSAPublicKey,
-    AssignedRSASignature, BigUintConfig, BigUintInstructions, Fresh, RSAConfig, RSAInstructions,
-    RSAPubE, RSAPublicKey, RSASignature, RSASignatureVerifier,
+    AssignedRSASignature, BigUintConfig, BigUintInstructions, DkimVerifier, Fresh, ModPowStrategy,
+    MultiOpenScheme, RSAConfig, RSAInstructions, RSAPubE, RSAPublicKey, RSASignature,
+    RSASignatureVerifier, MOD_POW_WINDOW_CROSSOVER_BITS,
 };
-use num_bigint::{BigInt, BigUint, Sign};
+use num_bigint::{BigInt, BigUint, RandomBits, Sign};
 use num_traits::{One, Signed, Zero};
 use rand::rngs::OsRng;
 use std::marker::PhantomData;
@@ -95,7 +96,7 @@ impl_pkcs1v15_basic_circuit!(
     1,
     16,
     1,
-    19,
+    18,
     true
 );
 
@@ -170,6 +171,351 @@ impl_pkcs1v15_basic_circuit!(
     false
 );
 
+/// A DKIM circuit hashing the header and the body with two SHA256 chips if `PARALLEL` is true, or sequentially with one chip otherwise.
+#[derive(Clone)]
+struct DkimBenchCircuit<const PARALLEL: bool> {
+    signature: RSASignature<Fr>,
+    public_key: RSAPublicKey<Fr>,
+    header: Vec<u8>,
+    body: Vec<u8>,
+}
+
+impl<const PARALLEL: bool> DkimBenchCircuit<PARALLEL> {
+    const BITS_LEN: usize = 2048;
+    const MAX_HEADER_LEN: usize = 512;
+    const MAX_BODY_LEN: usize = 1024;
+    const K: u32 = 16;
+}
+
+impl<const PARALLEL: bool> Default for DkimBenchCircuit<PARALLEL> {
+    fn default() -> Self {
+        Self {
+            signature: RSASignature::new(Value::unknown()),
+            public_key: RSAPublicKey::new(Value::unknown(), RSAPubE::Fix(BigUint::from(65537u32))),
+            header: vec![],
+            body: vec![],
+        }
+    }
+}
+
+impl<const PARALLEL: bool> Circuit<Fr> for DkimBenchCircuit<PARALLEL> {
+    type Config = DkimVerifier<Fr>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let range_config =
+            RangeConfig::configure(meta, Vertical, &[40], &[8], 1, 12, 0, Self::K as usize);
+        let biguint_config = BigUintConfig::construct(range_config.clone(), 64);
+        let rsa_config = RSAConfig::construct(biguint_config, Self::BITS_LEN, 5);
+        if PARALLEL {
+            let header_sha256_config = Sha256DynamicConfig::configure(
+                meta,
+                vec![Self::MAX_HEADER_LEN],
+                range_config.clone(),
+                8,
+                8,
+                true,
+            );
+            let body_sha256_config = Sha256DynamicConfig::configure(
+                meta,
+                vec![Self::MAX_BODY_LEN],
+                range_config,
+                8,
+                8,
+                true,
+            );
+            DkimVerifier::new(rsa_config, header_sha256_config, body_sha256_config)
+        } else {
+            let sha256_config = Sha256DynamicConfig::configure(
+                meta,
+                vec![Self::MAX_BODY_LEN, Self::MAX_HEADER_LEN],
+                range_config,
+                8,
+                8,
+                true,
+            );
+            DkimVerifier::new_sequential(rsa_config, sha256_config)
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.load(&mut layouter)?;
+        let rsa_config = config.rsa_config().clone();
+        let mut first_pass = SKIP_FIRST_PASS;
+        layouter.assign_region(
+            || "dkim signature",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(());
+                }
+                let mut aux = rsa_config.new_context(region);
+                let ctx = &mut aux;
+                let signature = rsa_config.assign_signature(ctx, self.signature.clone())?;
+                let public_key = rsa_config.assign_public_key(ctx, self.public_key.clone())?;
+                let mut verifier = config.clone();
+                let result = verifier.verify_pkcs1v15_dkim(
+                    ctx,
+                    &public_key,
+                    &self.header,
+                    &self.body,
+                    &signature,
+                )?;
+                rsa_config
+                    .gate()
+                    .assert_is_const(ctx, &result.is_valid, Fr::from(1u64));
+                rsa_config.range().finalize(ctx);
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+}
+
+fn setup_dkim<const PARALLEL: bool>() -> (ParamsKZG<Bn256>, ProvingKey<G1Affine>) {
+    let circuit = DkimBenchCircuit::<PARALLEL>::default();
+    let params = ParamsKZG::<Bn256>::setup(DkimBenchCircuit::<PARALLEL>::K, OsRng);
+    let vk = keygen_vk(&params, &circuit).unwrap();
+    let pk = keygen_pk(&params, vk, &circuit).unwrap();
+    (params, pk)
+}
+
+fn dkim_circuit<const PARALLEL: bool>() -> DkimBenchCircuit<PARALLEL> {
+    let mut rng = thread_rng();
+    let private_key = RsaPrivateKey::new(&mut rng, DkimBenchCircuit::<PARALLEL>::BITS_LEN)
+        .expect("failed to generate a key");
+    let header = (0..DkimBenchCircuit::<PARALLEL>::MAX_HEADER_LEN - 9)
+        .map(|_| rng.gen())
+        .collect::<Vec<u8>>();
+    let body = (0..DkimBenchCircuit::<PARALLEL>::MAX_BODY_LEN - 9)
+        .map(|_| rng.gen())
+        .collect::<Vec<u8>>();
+    let signing_key = SigningKey::<rsa::sha2::Sha256>::new(private_key.clone());
+    let sign = signing_key.sign(&header).to_vec();
+    let n_big = BigUint::from_bytes_be(&private_key.n().to_bytes_be());
+    DkimBenchCircuit {
+        signature: RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+        public_key: RSAPublicKey::new(Value::known(n_big), RSAPubE::Fix(BigUint::from(65537u32))),
+        header,
+        body,
+    }
+}
+
+fn prove_dkim<const PARALLEL: bool>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: DkimBenchCircuit<PARALLEL>,
+) {
+    let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+    create_proof::<KZGCommitmentScheme<Bn256>, ProverGWC<'_, Bn256>, _, _, _, _>(
+        params,
+        pk,
+        &[circuit],
+        &[&[]],
+        OsRng,
+        &mut transcript,
+    )
+    .unwrap();
+    black_box(transcript.finalize());
+}
+
+fn bench_dkim_sha256_chips(c: &mut Criterion) {
+    let mut group =
+        c.benchmark_group("dkim, 2048 bit public key, header 512 bytes, body 1024 bytes");
+    group.sample_size(10);
+    let (params, pk) = setup_dkim::<true>();
+    group.bench_function("two sha256 chips", |b| {
+        b.iter_batched(
+            dkim_circuit::<true>,
+            |circuit| prove_dkim(&params, &pk, circuit),
+            criterion::BatchSize::LargeInput,
+        )
+    });
+    let (params, pk) = setup_dkim::<false>();
+    group.bench_function("one sequential sha256 chip", |b| {
+        b.iter_batched(
+            dkim_circuit::<false>,
+            |circuit| prove_dkim(&params, &pk, circuit),
+            criterion::BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+/// A circuit computing a modular power of a 1024 bits modulus with a fixed exponent by `strategy`, or by [`BigUintConfig::mod_pow_unchecked`] if `unchecked` is true.
+struct ModPowBenchCircuit {
+    a: BigUint,
+    n: BigUint,
+    e: BigUint,
+    strategy: ModPowStrategy,
+    unchecked: bool,
+}
+
+impl ModPowBenchCircuit {
+    const BITS_LEN: usize = 1024;
+    const K: u32 = 16;
+
+    fn sample(e_bits: u64, strategy: ModPowStrategy) -> Self {
+        let mut rng = thread_rng();
+        let n = rng.sample::<BigUint, _>(RandomBits::new(Self::BITS_LEN as u64))
+            | (BigUint::one() << (Self::BITS_LEN - 1));
+        let a = rng.sample::<BigUint, _>(RandomBits::new(Self::BITS_LEN as u64)) % &n;
+        let e =
+            rng.sample::<BigUint, _>(RandomBits::new(e_bits)) | (BigUint::one() << (e_bits - 1));
+        Self {
+            a,
+            n,
+            e,
+            strategy,
+            unchecked: false,
+        }
+    }
+
+    fn sample_unchecked(e_bits: u64) -> Self {
+        Self {
+            unchecked: true,
+            ..Self::sample(e_bits, ModPowStrategy::SquareAndMultiply)
+        }
+    }
+}
+
+impl Circuit<Fr> for ModPowBenchCircuit {
+    type Config = BigUintConfig<Fr>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        unimplemented!();
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let range_config =
+            RangeConfig::configure(meta, Vertical, &[50], &[4], 1, 15, 0, Self::K as usize);
+        BigUintConfig::construct(range_config, 64)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.range().load_lookup_table(&mut layouter)?;
+        let mut first_pass = SKIP_FIRST_PASS;
+        layouter.assign_region(
+            || "mod_pow strategy",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(());
+                }
+                let mut aux = config.new_context(region);
+                let ctx = &mut aux;
+                let a = config.assign_integer(ctx, Value::known(self.a.clone()), Self::BITS_LEN)?;
+                let n = config.assign_integer(ctx, Value::known(self.n.clone()), Self::BITS_LEN)?;
+                let start = ctx.total_advice;
+                let (powed, method) = if self.unchecked {
+                    (
+                        config.mod_pow_unchecked(ctx, &a, &self.e, &n)?,
+                        "mod_pow_unchecked".to_string(),
+                    )
+                } else {
+                    (
+                        config.pow_mod_fixed_exp_with_strategy(
+                            ctx,
+                            &a,
+                            &self.e,
+                            &n,
+                            self.strategy,
+                        )?,
+                        format!("{:?}", self.strategy),
+                    )
+                };
+                println!(
+                    "{} bits exponent, {}: {} advice cells",
+                    self.e.bits(),
+                    method,
+                    ctx.total_advice - start
+                );
+                let ans = config.assign_constant(ctx, self.a.modpow(&self.e, &self.n))?;
+                config.assert_equal_fresh(ctx, &powed, &ans)?;
+                config.range().finalize(ctx);
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+}
+
+/// Compares the strategies of the modular power with a fixed exponent around [`MOD_POW_WINDOW_CROSSOVER_BITS`].
+///
+/// The printed advice cells justify the crossover: windows save a few multiplications for short exponents but many for long ones.
+fn bench_mod_pow_strategies(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mod_pow, 1024 bit modulus");
+    group.sample_size(10);
+    for e_bits in [17, MOD_POW_WINDOW_CROSSOVER_BITS as u64, 64, 128] {
+        for strategy in [
+            ModPowStrategy::SquareAndMultiply,
+            ModPowStrategy::Windowed(4),
+            ModPowStrategy::Auto,
+        ] {
+            group.bench_function(format!("{} bits exponent, {:?}", e_bits, strategy), |b| {
+                b.iter_batched(
+                    || ModPowBenchCircuit::sample(e_bits, strategy),
+                    |circuit| {
+                        MockProver::run(ModPowBenchCircuit::K, &circuit, vec![])
+                            .unwrap()
+                            .assert_satisfied()
+                    },
+                    criterion::BatchSize::LargeInput,
+                )
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Compares [`BigUintConfig::mod_pow_unchecked`] with [`BigUintInstructions::pow_mod_fixed_exp`] by square-and-multiply, which computes the same modular multiplications with the checks it relaxes.
+///
+/// The printed advice cells show the savings of skipping the range checks of the modulus and the equality bits of the carries in every step.
+fn bench_mod_pow_unchecked(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mod_pow_unchecked, 1024 bit modulus");
+    group.sample_size(10);
+    for e_bits in [17, 64] {
+        for unchecked in [false, true] {
+            let name = if unchecked {
+                "mod_pow_unchecked"
+            } else {
+                "pow_mod_fixed_exp"
+            };
+            group.bench_function(format!("{} bits exponent, {}", e_bits, name), |b| {
+                b.iter_batched(
+                    || {
+                        if unchecked {
+                            ModPowBenchCircuit::sample_unchecked(e_bits)
+                        } else {
+                            ModPowBenchCircuit::sample(e_bits, ModPowStrategy::SquareAndMultiply)
+                        }
+                    },
+                    |circuit| {
+                        MockProver::run(ModPowBenchCircuit::K, &circuit, vec![])
+                            .unwrap()
+                            .assert_satisfied()
+                    },
+                    criterion::BatchSize::LargeInput,
+                )
+            });
+        }
+    }
+    group.finish();
+}
+
 fn save_params_pk_and_vk(
     params_filename: &str,
     pk_filename: &str,
@@ -207,7 +553,7 @@ fn bench_pkcs1v15_1024_enabled(c: &mut Criterion) {
     //     &vk,
     // );
     // group.bench_function("message 64 bytes", |b| {
-    //     b.iter(|| prove_pkcs1v15_1024_64_enabled(&params, &vk, &pk))
+    //     b.iter(|| prove_pkcs1v15_1024_64_enabled(&params, &vk, &pk, MultiOpenScheme::Gwc))
     // });
     // let (params, vk, pk) = setup_pkcs1v15_1024_128_enabled();
     // save_params_pk_and_vk(
@@ -219,7 +565,7 @@ fn bench_pkcs1v15_1024_enabled(c: &mut Criterion) {
     //     &vk,
     // );
     // group.bench_function("message 128 bytes", |b| {
-    //     b.iter(|| prove_pkcs1v15_1024_128_enabled(&params, &vk, &pk))
+    //     b.iter(|| prove_pkcs1v15_1024_128_enabled(&params, &vk, &pk, MultiOpenScheme::Gwc))
     // });
     let (params, vk, pk) = setup_pkcs1v15_1024_1024_enabled();
     save_params_pk_and_vk(
@@ -231,7 +577,10 @@ fn bench_pkcs1v15_1024_enabled(c: &mut Criterion) {
         &vk,
     );
     group.bench_function("message 1024 bytes", |b| {
-        b.iter(|| prove_pkcs1v15_1024_1024_enabled(&params, &vk, &pk))
+        b.iter(|| prove_pkcs1v15_1024_1024_enabled(&params, &vk, &pk, MultiOpenScheme::Gwc))
+    });
+    group.bench_function("message 1024 bytes, shplonk", |b| {
+        b.iter(|| prove_pkcs1v15_1024_1024_enabled(&params, &vk, &pk, MultiOpenScheme::Shplonk))
     });
     group.finish();
 }
@@ -249,7 +598,7 @@ fn bench_pkcs1v15_2048_enabled(c: &mut Criterion) {
     //     &vk,
     // );
     // group.bench_function("message 64 bytes", |b| {
-    //     b.iter(|| prove_pkcs1v15_2048_64_enabled(&params, &vk, &pk))
+    //     b.iter(|| prove_pkcs1v15_2048_64_enabled(&params, &vk, &pk, MultiOpenScheme::Gwc))
     // });
     // let (params, vk, pk) = setup_pkcs1v15_2048_128_enabled();
     // save_params_pk_and_vk(
@@ -261,7 +610,7 @@ fn bench_pkcs1v15_2048_enabled(c: &mut Criterion) {
     //     &vk,
     // );
     // group.bench_function("message 128 bytes", |b| {
-    //     b.iter(|| prove_pkcs1v15_2048_128_enabled(&params, &vk, &pk))
+    //     b.iter(|| prove_pkcs1v15_2048_128_enabled(&params, &vk, &pk, MultiOpenScheme::Gwc))
     // });
     let (params, vk, pk) = setup_pkcs1v15_2048_1024_enabled();
     save_params_pk_and_vk(
@@ -273,7 +622,7 @@ fn bench_pkcs1v15_2048_enabled(c: &mut Criterion) {
         &vk,
     );
     group.bench_function("message 1024 bytes", |b| {
-        b.iter(|| prove_pkcs1v15_2048_1024_enabled(&params, &vk, &pk))
+        b.iter(|| prove_pkcs1v15_2048_1024_enabled(&params, &vk, &pk, MultiOpenScheme::Gwc))
     });
     let (params, vk, pk) = setup_pkcs1v15_2048_10240_enabled();
     save_params_pk_and_vk(
@@ -285,7 +634,7 @@ fn bench_pkcs1v15_2048_enabled(c: &mut Criterion) {
         &vk,
     );
     group.bench_function("message 10240 bytes", |b| {
-        b.iter(|| prove_pkcs1v15_2048_10240_enabled(&params, &vk, &pk))
+        b.iter(|| prove_pkcs1v15_2048_10240_enabled(&params, &vk, &pk, MultiOpenScheme::Gwc))
     });
     group.finish();
 }
@@ -303,7 +652,7 @@ fn bench_pkcs1v15_2048_disabled(c: &mut Criterion) {
         &vk,
     );
     group.bench_function("message 1024 bytes", |b| {
-        b.iter(|| prove_pkcs1v15_2048_1024_disabled(&params, &vk, &pk))
+        b.iter(|| prove_pkcs1v15_2048_1024_disabled(&params, &vk, &pk, MultiOpenScheme::Gwc))
     });
     group.finish();
 }
@@ -312,6 +661,9 @@ criterion_group!(
     benches,
     bench_pkcs1v15_1024_enabled,
     bench_pkcs1v15_2048_enabled,
-    bench_pkcs1v15_2048_disabled
+    bench_pkcs1v15_2048_disabled,
+    bench_dkim_sha256_chips,
+    bench_mod_pow_strategies,
+    bench_mod_pow_unchecked
 );
 criterion_main!(benches);
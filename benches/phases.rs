@@ -0,0 +1,459 @@
+//! Benchmarks of the proving phases of the pkcs1v15 circuits and of the modular arithmetic of [`BigUintConfig`].
+//!
+//! Run them with `cargo bench --bench phases`.
+//! The circuits of the wasm build have k = 18 or 19, so each phase takes from seconds to minutes.
+//! Set `HALO2_RSA_BENCH_INSECURE_PARAMS=1` to benchmark the same signatures with wider layouts of smaller degrees and params sampled from a fixed seed instead, e.g., on CI.
+//! The trapdoor of those params is public, so they must never be used outside the benchmarks.
+//!
+//! Criterion only measures the wall time, so the degree, the advice columns, the used rows, and the advice cells of each benchmarked circuit are printed and written to `pkcs1v15-costs.csv` and `biguint-costs.csv` in `target/criterion` next to its reports.
+//! The cells of the pkcs1v15 circuits are the upper bounds of [`estimate_advice_cells`], and those of the modular arithmetic are measured while synthesizing.
+//! The witness generation of the 1024_1024 circuit is also benchmarked on one thread and on all the cores, whose speedup is printed after the group.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use halo2_base::halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    dev::MockProver,
+    halo2curves::bn256::{Bn256, Fr},
+    plonk::{keygen_pk, keygen_vk, Circuit, ConstraintSystem, Error},
+    poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG},
+};
+use halo2_base::{
+    gates::{range::RangeConfig, range::RangeStrategy::Vertical, GateInstructions},
+    SKIP_FIRST_PASS,
+};
+use halo2_rsa::{
+    create_proof_with, estimate_advice_cells, verify_proof_with, BigUintConfig,
+    BigUintInstructions, MultiOpenScheme, Pkcs1v15Circuit, Pkcs1v15Layout,
+    Pkcs1v15_1024_1024EnabledBenchConfig, Pkcs1v15_1024_64EnabledBenchConfig, RSAPubE,
+    RSAPublicKey, RSASignature, TranscriptKind,
+};
+use num_bigint::{BigUint, RandomBits};
+use num_traits::One;
+use rand::rngs::OsRng;
+use rand::{thread_rng, Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use rsa::{
+    pkcs1v15::SigningKey,
+    signature::{SignatureEncoding, Signer},
+    traits::PublicKeyParts,
+    RsaPrivateKey,
+};
+use std::cell::Cell;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// The environment variable selecting the reduced circuits and the insecure params.
+const INSECURE_PARAMS_VAR: &str = "HALO2_RSA_BENCH_INSECURE_PARAMS";
+/// The seed of the insecure params.
+const INSECURE_PARAMS_SEED: u64 = 0;
+
+/// Defines a layout with the same knobs as the presets except for 12 flex advice columns, 3 range lookup advice columns, and a SHA256 lookup table of 8 bits, so that the circuit fits in `$k` rows.
+macro_rules! impl_reduced_layout {
+    ($layout_name:ident, $k:expr) => {
+        #[derive(Clone, Copy, Debug, Default)]
+        struct $layout_name;
+
+        impl Pkcs1v15Layout for $layout_name {
+            const NUM_ADVICE: usize = 12;
+            const NUM_LOOKUP_ADVICE: usize = 3;
+            const SHA256_LOOKUP_BITS: usize = 8;
+            const SHA256_LOOKUP_ADVICE: usize = 8;
+            const K: Option<u32> = Some($k);
+        }
+    };
+}
+
+impl_reduced_layout!(Reduced1024_64Layout, 15);
+impl_reduced_layout!(Reduced1024_1024Layout, 17);
+
+/// The benchmarked pkcs1v15 circuits for 1024 bits keys with the SHA256 chip.
+type BenchCircuit<const MSG_LEN: usize, L> = Pkcs1v15Circuit<Fr, 1024, MSG_LEN, true, L>;
+
+/// The costs of a benchmarked circuit.
+struct CircuitCost {
+    name: String,
+    k: u32,
+    num_advice_columns: usize,
+    advice_cells: usize,
+    measured: bool,
+}
+
+impl CircuitCost {
+    /// Returns the number of the rows used by the advice cells, assuming that they are spread evenly over the flex advice columns.
+    fn used_rows(&self, num_flex_advice: usize) -> usize {
+        (self.advice_cells + num_flex_advice - 1) / num_flex_advice
+    }
+}
+
+/// Collects the costs of the benchmarked circuits and reports them along with the wall times of criterion.
+#[derive(Default)]
+struct CostReport {
+    lines: Vec<String>,
+}
+
+impl CostReport {
+    const HEADER: &'static str = "circuit,k,rows,advice_columns,used_rows,advice_cells,cells";
+
+    fn push(&mut self, cost: CircuitCost, num_flex_advice: usize) {
+        let source = if cost.measured {
+            "measured"
+        } else {
+            "estimated"
+        };
+        println!(
+            "{}: k = {}, {} advice columns, {} of {} rows, {} advice cells ({})",
+            cost.name,
+            cost.k,
+            cost.num_advice_columns,
+            cost.used_rows(num_flex_advice),
+            1usize << cost.k,
+            cost.advice_cells,
+            source
+        );
+        self.lines.push(format!(
+            "{},{},{},{},{},{},{}",
+            cost.name,
+            cost.k,
+            1usize << cost.k,
+            cost.num_advice_columns,
+            cost.used_rows(num_flex_advice),
+            cost.advice_cells,
+            source
+        ));
+    }
+
+    /// Writes the costs to `file_name` in the output directory of criterion.
+    fn write(&self, file_name: &str) {
+        let dir = std::env::var_os("CARGO_TARGET_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("target"))
+            .join("criterion");
+        fs::create_dir_all(&dir).unwrap();
+        let mut csv = Self::HEADER.to_string();
+        for line in &self.lines {
+            csv.push('\n');
+            csv.push_str(line);
+        }
+        csv.push('\n');
+        fs::write(dir.join(file_name), csv).unwrap();
+    }
+}
+
+fn insecure_params() -> bool {
+    std::env::var_os(INSECURE_PARAMS_VAR).map_or(false, |value| value != "0")
+}
+
+fn num_advice_columns<C: Circuit<Fr>>() -> usize {
+    let mut cs = ConstraintSystem::default();
+    C::configure(&mut cs);
+    cs.num_advice_columns()
+}
+
+/// Samples a 1024 bits key and signs a uniformly sampled message of `msg_len - 9` bytes.
+fn sample_signature(msg_len: usize) -> (RSASignature<Fr>, RSAPublicKey<Fr>, Vec<u8>) {
+    let mut rng = thread_rng();
+    let private_key = RsaPrivateKey::new(&mut rng, 1024).expect("failed to generate a key");
+    let msg = (0..msg_len - 9).map(|_| rng.gen()).collect::<Vec<u8>>();
+    let signature = SigningKey::<rsa::sha2::Sha256>::new(private_key.clone())
+        .sign(&msg)
+        .to_vec();
+    let n = BigUint::from_bytes_be(&private_key.n().to_bytes_be());
+    (
+        RSASignature::new(Value::known(BigUint::from_bytes_be(&signature))),
+        RSAPublicKey::new(Value::known(n), RSAPubE::Fix(BigUint::from(65537u32))),
+        msg,
+    )
+}
+
+/// Benchmarks the witness generation, the key generation, the proving, and the verification of a 1024 bits pkcs1v15 circuit for messages of `MSG_LEN` bytes with the layout `L`.
+fn bench_pkcs1v15_phases<const MSG_LEN: usize, L: Pkcs1v15Layout>(
+    c: &mut Criterion,
+    report: &mut CostReport,
+    mut params_rng: impl RngCore,
+) {
+    let name = format!("pkcs1v15 1024_{}", MSG_LEN);
+    report.push(
+        CircuitCost {
+            name: name.clone(),
+            k: BenchCircuit::<MSG_LEN, L>::K,
+            num_advice_columns: num_advice_columns::<BenchCircuit<MSG_LEN, L>>(),
+            advice_cells: estimate_advice_cells(1024, MSG_LEN),
+            measured: false,
+        },
+        L::NUM_ADVICE,
+    );
+
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10);
+    let (signature, public_key, msg) = sample_signature(MSG_LEN);
+    let circuit =
+        || BenchCircuit::<MSG_LEN, L>::new(signature.clone(), public_key.clone(), msg.clone());
+    group.bench_function("witness generation", |b| {
+        b.iter_batched(
+            circuit,
+            |circuit| MockProver::run(BenchCircuit::<MSG_LEN, L>::K, &circuit, vec![]).unwrap(),
+            BatchSize::LargeInput,
+        )
+    });
+
+    let params = ParamsKZG::<Bn256>::setup(BenchCircuit::<MSG_LEN, L>::K, &mut params_rng);
+    group.bench_function("keygen", |b| {
+        b.iter(|| {
+            let empty_circuit = BenchCircuit::<MSG_LEN, L>::default();
+            let vk = keygen_vk(&params, &empty_circuit).unwrap();
+            keygen_pk(&params, vk, &empty_circuit).unwrap()
+        })
+    });
+
+    let empty_circuit = BenchCircuit::<MSG_LEN, L>::default();
+    let vk = keygen_vk(&params, &empty_circuit).unwrap();
+    let pk = keygen_pk(&params, vk.clone(), &empty_circuit).unwrap();
+    let prove = |circuit: BenchCircuit<MSG_LEN, L>| {
+        create_proof_with(
+            &params,
+            &pk,
+            circuit,
+            &[],
+            TranscriptKind::Blake2b,
+            MultiOpenScheme::Gwc,
+        )
+        .unwrap()
+    };
+    group.bench_function("create_proof", |b| {
+        b.iter_batched(circuit, prove, BatchSize::LargeInput)
+    });
+
+    let proof = prove(circuit());
+    group.bench_function("verify_proof", |b| {
+        b.iter(|| {
+            verify_proof_with(
+                &params,
+                &vk,
+                black_box(&proof),
+                &[],
+                TranscriptKind::Blake2b,
+                MultiOpenScheme::Gwc,
+            )
+            .unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn bench_pkcs1v15(c: &mut Criterion) {
+    let mut report = CostReport::default();
+    if insecure_params() {
+        let mut rng = ChaCha20Rng::seed_from_u64(INSECURE_PARAMS_SEED);
+        bench_pkcs1v15_phases::<64, Reduced1024_64Layout>(c, &mut report, &mut rng);
+        bench_pkcs1v15_phases::<1024, Reduced1024_1024Layout>(c, &mut report, &mut rng);
+    } else {
+        bench_pkcs1v15_phases::<64, Pkcs1v15_1024_64EnabledBenchConfig>(c, &mut report, OsRng);
+        bench_pkcs1v15_phases::<1024, Pkcs1v15_1024_1024EnabledBenchConfig>(c, &mut report, OsRng);
+    }
+    report.write("pkcs1v15-costs.csv");
+}
+
+/// Benchmarks the witness generation of the 1024 bits pkcs1v15 circuit for messages of 1024 bytes with the layout `L` on a rayon pool of one thread and on a pool of all the cores, and prints the speedup of precomputing the witnesses of [`BigUintConfig`] in parallel.
+fn bench_parallel_witness_of<L: Pkcs1v15Layout>(c: &mut Criterion) {
+    const NUM_RUNS: u32 = 5;
+    let (signature, public_key, msg) = sample_signature(1024);
+    let circuit =
+        || BenchCircuit::<1024, L>::new(signature.clone(), public_key.clone(), msg.clone());
+    let witness = |circuit: BenchCircuit<1024, L>| {
+        MockProver::run(BenchCircuit::<1024, L>::K, &circuit, vec![]).unwrap()
+    };
+
+    let mut group = c.benchmark_group("pkcs1v15 1024_1024 parallel witness");
+    group.sample_size(10);
+    let mut mean_times = vec![];
+    for num_threads in [1, num_cpus()] {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .unwrap();
+        group.bench_function(format!("{} threads", num_threads), |b| {
+            b.iter_batched(
+                circuit,
+                |circuit| pool.install(|| witness(circuit)),
+                BatchSize::LargeInput,
+            )
+        });
+        let start = Instant::now();
+        for _ in 0..NUM_RUNS {
+            black_box(pool.install(|| witness(circuit())));
+        }
+        mean_times.push((num_threads, start.elapsed() / NUM_RUNS));
+    }
+    group.finish();
+    let (_, single) = mean_times[0];
+    let (num_threads, parallel) = mean_times[1];
+    println!(
+        "witness generation of pkcs1v15 1024_1024: {:?} with 1 thread, {:?} with {} threads ({:.2}x)",
+        single,
+        parallel,
+        num_threads,
+        single.as_secs_f64() / parallel.as_secs_f64()
+    );
+}
+
+fn num_cpus() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+fn bench_parallel_witness(c: &mut Criterion) {
+    if insecure_params() {
+        bench_parallel_witness_of::<Reduced1024_1024Layout>(c);
+    } else {
+        bench_parallel_witness_of::<Pkcs1v15_1024_1024EnabledBenchConfig>(c);
+    }
+}
+
+/// A modular operation of [`BigUintInstructions`] benchmarked by [`ModularBenchCircuit`].
+#[derive(Clone, Copy, Debug)]
+enum ModularOp {
+    MulMod,
+    /// The modular power with a variable exponent of 17 bits, i.e., as many as `65537`.
+    PowMod,
+}
+
+/// A circuit computing `op` of `bits_len` bits integers, which records the advice cells used by `op` in `cells`.
+struct ModularBenchCircuit {
+    op: ModularOp,
+    bits_len: usize,
+    a: BigUint,
+    b: BigUint,
+    n: BigUint,
+    cells: Cell<usize>,
+}
+
+impl ModularBenchCircuit {
+    const K: u32 = 16;
+    const NUM_ADVICE: usize = 20;
+    const LIMB_BITS: usize = 64;
+    const EXP_BITS: usize = 17;
+
+    fn sample(op: ModularOp, bits_len: usize) -> Self {
+        let mut rng = thread_rng();
+        let n = rng.sample::<BigUint, _>(RandomBits::new(bits_len as u64))
+            | (BigUint::one() << (bits_len - 1));
+        let a = rng.sample::<BigUint, _>(RandomBits::new(bits_len as u64)) % &n;
+        let b = rng.sample::<BigUint, _>(RandomBits::new(bits_len as u64)) % &n;
+        Self {
+            op,
+            bits_len,
+            a,
+            b,
+            n,
+            cells: Cell::new(0),
+        }
+    }
+}
+
+impl Circuit<Fr> for ModularBenchCircuit {
+    type Config = BigUintConfig<Fr>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        unimplemented!();
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let range_config = RangeConfig::configure(
+            meta,
+            Vertical,
+            &[Self::NUM_ADVICE],
+            &[4],
+            1,
+            Self::K as usize - 1,
+            0,
+            Self::K as usize,
+        );
+        BigUintConfig::construct(range_config, Self::LIMB_BITS)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.range().load_lookup_table(&mut layouter)?;
+        let mut first_pass = SKIP_FIRST_PASS;
+        layouter.assign_region(
+            || "modular operation",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(());
+                }
+                let mut aux = config.new_context(region);
+                let ctx = &mut aux;
+                let a = config.assign_integer(ctx, Value::known(self.a.clone()), self.bits_len)?;
+                let n = config.assign_integer(ctx, Value::known(self.n.clone()), self.bits_len)?;
+                let start = ctx.total_advice;
+                match self.op {
+                    ModularOp::MulMod => {
+                        let b = config.assign_integer(
+                            ctx,
+                            Value::known(self.b.clone()),
+                            self.bits_len,
+                        )?;
+                        black_box(config.mul_mod(ctx, &a, &b, &n)?);
+                    }
+                    ModularOp::PowMod => {
+                        let e = config
+                            .gate()
+                            .load_witness(ctx, Value::known(Fr::from(65537)));
+                        black_box(config.pow_mod(ctx, &a, &e, &n, Self::EXP_BITS)?);
+                    }
+                }
+                self.cells.set(ctx.total_advice - start);
+                config.range().finalize(ctx);
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+}
+
+fn bench_modular_ops(c: &mut Criterion) {
+    let mut report = CostReport::default();
+    let mut group = c.benchmark_group("biguint");
+    group.sample_size(10);
+    for op in [ModularOp::MulMod, ModularOp::PowMod] {
+        for bits_len in [1024, 2048] {
+            let name = format!("{:?} {} bits", op, bits_len);
+            let circuit = ModularBenchCircuit::sample(op, bits_len);
+            MockProver::run(ModularBenchCircuit::K, &circuit, vec![])
+                .unwrap()
+                .assert_satisfied();
+            report.push(
+                CircuitCost {
+                    name: format!("biguint {}", name),
+                    k: ModularBenchCircuit::K,
+                    num_advice_columns: num_advice_columns::<ModularBenchCircuit>(),
+                    advice_cells: circuit.cells.get(),
+                    measured: true,
+                },
+                ModularBenchCircuit::NUM_ADVICE,
+            );
+            group.bench_function(name, |b| {
+                b.iter_batched(
+                    || ModularBenchCircuit::sample(op, bits_len),
+                    |circuit| MockProver::run(ModularBenchCircuit::K, &circuit, vec![]).unwrap(),
+                    BatchSize::LargeInput,
+                )
+            });
+        }
+    }
+    group.finish();
+    report.write("biguint-costs.csv");
+}
+
+criterion_group!(
+    benches,
+    bench_pkcs1v15,
+    bench_parallel_witness,
+    bench_modular_ops
+);
+criterion_main!(benches);
@@ -0,0 +1,120 @@
+//! The following example circuit multiplies two 2048-bit integers with [`BigUintConfig`] and checks the product against `num_bigint`.
+//! It walks through the lifecycle of [`AssignedBigUint`]:
+//! 1. [`BigUintInstructions::assign_integer`] returns a [`Fresh`] integer, whose limbs are range-checked to `limb_bits` bits.
+//! 2. [`BigUintInstructions::mul`] returns a [`Muled`] integer, whose `2 * num_limbs - 1` limbs are the sums of products of the input limbs and exceed `limb_bits` bits.
+//! 3. [`BigUintInstructions::refresh`] carries the overflowing limbs with [`RefreshAux`] and returns a [`Fresh`] integer again.
+//!    [`RefreshAux`] must be created with the numbers of limbs of the two multiplied integers.
+use halo2_base::halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    dev::MockProver,
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use halo2_base::{
+    gates::{
+        range::{RangeConfig, RangeStrategy::Vertical},
+        GateInstructions, RangeInstructions,
+    },
+    utils::PrimeField,
+    SKIP_FIRST_PASS,
+};
+use halo2_rsa::{AssignedBigUint, BigUintConfig, BigUintInstructions, Fresh, Muled, RefreshAux};
+use num_bigint::{BigUint, RandomBits};
+use rand::{thread_rng, Rng};
+use std::marker::PhantomData;
+
+struct BigUintMulExample<F: PrimeField> {
+    a: BigUint,
+    b: BigUint,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> BigUintMulExample<F> {
+    const BITS_LEN: usize = 2048;
+    const LIMB_BITS: usize = 64;
+    const NUM_ADVICE: usize = 50;
+    const NUM_FIXED: usize = 1;
+    const NUM_LOOKUP_ADVICE: usize = 4;
+    const LOOKUP_BITS: usize = 12;
+    const K: usize = 13;
+}
+
+impl<F: PrimeField> Circuit<F> for BigUintMulExample<F> {
+    type Config = BigUintConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        unimplemented!();
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let range_config = RangeConfig::configure(
+            meta,
+            Vertical,
+            &[Self::NUM_ADVICE],
+            &[Self::NUM_LOOKUP_ADVICE],
+            Self::NUM_FIXED,
+            Self::LOOKUP_BITS,
+            0,
+            Self::K,
+        );
+        BigUintConfig::construct(range_config, Self::LIMB_BITS)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.range().load_lookup_table(&mut layouter)?;
+        let mut first_pass = SKIP_FIRST_PASS;
+        layouter.assign_region(
+            || "mul of 2048 bits integers",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(());
+                }
+
+                let mut aux = config.new_context(region);
+                let ctx = &mut aux;
+                // 1. Assign `a` and `b` as `Fresh` integers of 2048 / 64 = 32 limbs.
+                let a: AssignedBigUint<F, Fresh> =
+                    config.assign_integer(ctx, Value::known(self.a.clone()), Self::BITS_LEN)?;
+                let b: AssignedBigUint<F, Fresh> =
+                    config.assign_integer(ctx, Value::known(self.b.clone()), Self::BITS_LEN)?;
+                // 2. Multiply them into a `Muled` integer of 63 overflowing limbs.
+                let ab: AssignedBigUint<F, Muled> = config.mul(ctx, &a, &b)?;
+                // 3. Refresh it into a `Fresh` integer of 64 limbs, which holds the full 4096 bits product.
+                let aux = RefreshAux::new(Self::LIMB_BITS, a.num_limbs(), b.num_limbs());
+                let ab: AssignedBigUint<F, Fresh> = config.refresh(ctx, &ab, &aux)?;
+                // 4. Constrain it to be equal to the product computed by `num_bigint`.
+                let expected = config.assign_constant(ctx, &self.a * &self.b)?;
+                let zero = config.gate().load_zero(ctx);
+                let expected = expected.extend_limbs(ab.num_limbs() - expected.num_limbs(), zero);
+                config.assert_equal_fresh(ctx, &ab, &expected)?;
+                config.range().finalize(ctx);
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+}
+
+fn main() {
+    let mut rng = thread_rng();
+    let bits_len = BigUintMulExample::<Fr>::BITS_LEN as u64;
+    let a = rng.sample::<BigUint, _>(RandomBits::new(bits_len));
+    let b = rng.sample::<BigUint, _>(RandomBits::new(bits_len));
+    let circuit = BigUintMulExample::<Fr> {
+        a,
+        b,
+        _f: PhantomData,
+    };
+    let prover = match MockProver::run(BigUintMulExample::<Fr>::K as u32, &circuit, vec![]) {
+        Ok(prover) => prover,
+        Err(e) => panic!("{:#?}", e),
+    };
+    prover.verify().unwrap();
+    println!("the product of two 2048 bits integers is verified");
+}
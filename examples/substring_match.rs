@@ -0,0 +1,228 @@
+//! The following example circuit verifies a pkcs1v15 signature over a message and proves that the message contains a substring, e.g., the domain of the sender.
+//! [`RSASignatureVerifier::verify_pkcs1v15_and_consume`] hands the assigned bytes of the verified message to a [`MessageConsumer`].
+//! Here a trivial substring matcher plays the role of the regex-matching chip, and its match bit is exposed as a public input.
+use halo2_base::halo2_proofs::{
+    circuit::{Cell, Layouter, SimpleFloorPlanner, Value},
+    dev::MockProver,
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+use halo2_base::{
+    gates::{
+        range::{RangeConfig, RangeStrategy::Vertical},
+        GateInstructions, RangeInstructions,
+    },
+    utils::PrimeField,
+    AssignedValue, Context, QuantumCell, SKIP_FIRST_PASS,
+};
+use halo2_rsa::{
+    halo2_dynamic_sha256::Sha256DynamicConfig, BigUintConfig, MessageConsumer, RSAConfig,
+    RSAInstructions, RSAPubE, RSAPublicKey, RSASignature, RSASignatureVerifier,
+};
+use num_bigint::BigUint;
+use rand::thread_rng;
+use rsa::{
+    pkcs1v15::SigningKey, signature::SignatureEncoding, signature::Signer, traits::PublicKeyParts,
+    RsaPrivateKey,
+};
+use std::marker::PhantomData;
+
+/// Outputs one if the message contains `pattern` at any position, and zero otherwise.
+struct SubstringMatcher<F: PrimeField> {
+    range: RangeConfig<F>,
+    pattern: Vec<u8>,
+}
+
+impl<F: PrimeField> MessageConsumer<F> for SubstringMatcher<F> {
+    fn consume<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        msg: &[AssignedValue<'v, F>],
+    ) -> Result<Vec<AssignedValue<'v, F>>, Error> {
+        let gate = self.range.gate();
+        let mut is_found = gate.load_zero(ctx);
+        for offset in 0..=(msg.len() - self.pattern.len()) {
+            let mut is_matched = gate.load_constant(ctx, F::one());
+            for (byte, expected) in msg[offset..].iter().zip(self.pattern.iter()) {
+                let is_eq = gate.is_equal(
+                    ctx,
+                    QuantumCell::Existing(byte),
+                    QuantumCell::Constant(F::from(*expected as u64)),
+                );
+                is_matched = gate.and(
+                    ctx,
+                    QuantumCell::Existing(&is_matched),
+                    QuantumCell::Existing(&is_eq),
+                );
+            }
+            is_found = gate.or(
+                ctx,
+                QuantumCell::Existing(&is_found),
+                QuantumCell::Existing(&is_matched),
+            );
+        }
+        Ok(vec![is_found])
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SubstringMatchConfig<F: PrimeField> {
+    rsa_config: RSAConfig<F>,
+    sha256_config: Sha256DynamicConfig<F>,
+    instance: Column<Instance>,
+}
+
+struct SubstringMatchExample<F: PrimeField> {
+    private_key: RsaPrivateKey,
+    msg: Vec<u8>,
+    pattern: Vec<u8>,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> SubstringMatchExample<F> {
+    const BITS_LEN: usize = 2048;
+    const MSG_LEN: usize = 1024;
+    const EXP_LIMB_BITS: usize = 5;
+    const DEFAULT_E: u128 = 65537;
+    const NUM_ADVICE: usize = 80;
+    const NUM_FIXED: usize = 1;
+    const NUM_LOOKUP_ADVICE: usize = 16;
+    const LOOKUP_BITS: usize = 12;
+    const SHA256_LOOKUP_BITS: usize = 8;
+    const SHA256_LOOKUP_ADVICE: usize = 8;
+    const K: usize = 15;
+}
+
+impl<F: PrimeField> Circuit<F> for SubstringMatchExample<F> {
+    type Config = SubstringMatchConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        unimplemented!();
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let range_config = RangeConfig::configure(
+            meta,
+            Vertical,
+            &[Self::NUM_ADVICE],
+            &[Self::NUM_LOOKUP_ADVICE],
+            Self::NUM_FIXED,
+            Self::LOOKUP_BITS,
+            0,
+            Self::K,
+        );
+        let biguint_config = BigUintConfig::construct(range_config.clone(), 64);
+        let rsa_config = RSAConfig::construct(biguint_config, Self::BITS_LEN, Self::EXP_LIMB_BITS);
+        let sha256_config = Sha256DynamicConfig::configure(
+            meta,
+            vec![Self::MSG_LEN],
+            range_config,
+            Self::SHA256_LOOKUP_BITS,
+            Self::SHA256_LOOKUP_ADVICE,
+            true,
+        );
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        Self::Config {
+            rsa_config,
+            sha256_config,
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let biguint_config = config.rsa_config.biguint_config();
+        config.sha256_config.load(&mut layouter)?;
+        biguint_config.range().load_lookup_table(&mut layouter)?;
+        let mut first_pass = SKIP_FIRST_PASS;
+        let output_cells = layouter.assign_region(
+            || "signature verification with a substring match",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(vec![]);
+                }
+
+                let mut aux = biguint_config.new_context(region);
+                let ctx = &mut aux;
+                // 1. Assign the signature and the public key.
+                let signing_key = SigningKey::<rsa::sha2::Sha256>::new(self.private_key.clone());
+                let sign = signing_key.sign(&self.msg).to_vec();
+                let sign = config.rsa_config.assign_signature(
+                    ctx,
+                    RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+                )?;
+                let n_big = BigUint::from_bytes_be(&self.private_key.n().to_bytes_be());
+                let e_fix = RSAPubE::Fix(BigUint::from(Self::DEFAULT_E));
+                let public_key = config
+                    .rsa_config
+                    .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                // 2. Verify the signature and hand the verified message to the matcher.
+                let matcher = SubstringMatcher {
+                    range: biguint_config.range().clone(),
+                    pattern: self.pattern.clone(),
+                };
+                let mut verifier = RSASignatureVerifier::new(
+                    config.rsa_config.clone(),
+                    config.sha256_config.clone(),
+                );
+                let (is_valid, outputs, _) = verifier.verify_pkcs1v15_and_consume(
+                    ctx,
+                    &public_key,
+                    &self.msg,
+                    &sign,
+                    &matcher,
+                )?;
+                // 3. The signature must be valid, whereas the match bit is left to the public input.
+                biguint_config
+                    .gate()
+                    .assert_is_const(ctx, &is_valid, F::one());
+                biguint_config.range().finalize(ctx);
+                Ok(outputs.into_iter().map(|v| v.cell()).collect::<Vec<Cell>>())
+            },
+        )?;
+        for (i, cell) in output_cells.into_iter().enumerate() {
+            layouter.constrain_instance(cell, config.instance, i)?;
+        }
+        Ok(())
+    }
+}
+
+fn main() {
+    let mut rng = thread_rng();
+    let private_key = RsaPrivateKey::new(&mut rng, SubstringMatchExample::<Fr>::BITS_LEN)
+        .expect("failed to generate a key");
+    let msg = b"from: alice <alice@example.com>\r\nsubject: hello\r\n".to_vec();
+    for (pattern, is_found) in [(&b"@example.com"[..], true), (&b"@example.org"[..], false)] {
+        let circuit = SubstringMatchExample::<Fr> {
+            private_key: private_key.clone(),
+            msg: msg.clone(),
+            pattern: pattern.to_vec(),
+            _f: PhantomData,
+        };
+        let public_inputs = vec![vec![Fr::from(is_found as u64)]];
+        let prover = match MockProver::run(
+            SubstringMatchExample::<Fr>::K as u32,
+            &circuit,
+            public_inputs,
+        ) {
+            Ok(prover) => prover,
+            Err(e) => panic!("{:#?}", e),
+        };
+        prover.verify().unwrap();
+        println!(
+            "the signed message {} {}",
+            if is_found {
+                "contains"
+            } else {
+                "does not contain"
+            },
+            String::from_utf8_lossy(pattern)
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! Writes the seeds generated from real artifacts to `corpus/<target>/` of the fuzz crate.
+//!
+//! ```text
+//! cargo run --release --example gen_corpus
+//! ```
+//!
+//! The params, the keys, the proofs, and the RSA keys are sampled from fixed seeds, so every run writes the same seeds.
+//! The keys and the proofs are of [`FuzzKeyCircuit`], whose keys are small enough to be mutated by the fuzzer.
+use halo2_base::halo2_proofs::{poly::commitment::Params, SerdeFormat};
+use halo2_rsa::{
+    compress_key, compress_params, convert_key_format, create_proof_bundle, encode_base64,
+    encode_hex, extract_verifier_params, gen_params_seeded, keygen_versioned, read_pk_checked,
+    KeyFormat, MultiOpenScheme, TranscriptKind,
+};
+use halo2_rsa_fuzz::{CircuitValuesInput, FuzzKeyCircuit, FUZZ_KEY_K};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use rsa::{
+    pkcs1v15::SigningKey, signature::SignatureEncoding, signature::Signer, traits::PublicKeyParts,
+    RsaPrivateKey,
+};
+use std::fs;
+use std::path::Path;
+
+/// The degree of the params written for the target `read_params`.
+const PARAMS_K: u32 = 4;
+
+fn write_seed(target: &str, name: &str, bytes: &[u8]) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("corpus")
+        .join(target);
+    fs::create_dir_all(&dir).expect("failed to create the corpus directory");
+    fs::write(dir.join(name), bytes).expect("failed to write a seed");
+}
+
+fn main() {
+    let params = gen_params_seeded(PARAMS_K, 0);
+    let mut raw = vec![];
+    params.write(&mut raw).unwrap();
+    let mut processed = vec![];
+    params
+        .write_custom(&mut processed, SerdeFormat::Processed)
+        .unwrap();
+    let trimmed = extract_verifier_params(&raw, 1).unwrap();
+    write_seed("read_params", "generated_raw", &raw);
+    write_seed("read_params", "generated_srs_processed", &processed);
+    write_seed(
+        "read_params",
+        "generated_compressed",
+        &compress_params(&raw).unwrap(),
+    );
+    write_seed("read_params", "generated_trimmed", &trimmed);
+    write_seed(
+        "read_params",
+        "generated_trimmed_compressed",
+        &compress_params(&trimmed).unwrap(),
+    );
+
+    let params = gen_params_seeded(FUZZ_KEY_K, 0);
+    let (pk, vk) = keygen_versioned(&params, &FuzzKeyCircuit).unwrap();
+    for (name, key) in [("pk", &pk), ("vk", &vk)] {
+        let processed =
+            convert_key_format::<FuzzKeyCircuit>(key, KeyFormat::RawBytes, KeyFormat::Processed)
+                .unwrap();
+        write_seed("read_keys", &format!("generated_{}_raw", name), key);
+        write_seed(
+            "read_keys",
+            &format!("generated_{}_processed", name),
+            &processed,
+        );
+        write_seed(
+            "read_keys",
+            &format!("generated_{}_compressed", name),
+            &compress_key(key).unwrap(),
+        );
+    }
+
+    let pk = read_pk_checked::<FuzzKeyCircuit>(&pk).unwrap();
+    for (name, transcript, scheme) in [
+        ("blake2b_gwc", TranscriptKind::Blake2b, MultiOpenScheme::Gwc),
+        (
+            "keccak256_shplonk",
+            TranscriptKind::Keccak256,
+            MultiOpenScheme::Shplonk,
+        ),
+    ] {
+        let bundle =
+            create_proof_bundle(&params, &pk, FuzzKeyCircuit, vec![], transcript, scheme).unwrap();
+        write_seed(
+            "proof_bundle",
+            &format!("generated_{}", name),
+            &bundle.to_bytes().unwrap(),
+        );
+        write_seed(
+            "proof_bundle",
+            &format!("generated_{}_json", name),
+            &serde_json::to_vec(&bundle).unwrap(),
+        );
+    }
+
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    for bits_len in [1024, 2048] {
+        let private_key = RsaPrivateKey::new(&mut rng, bits_len).expect("failed to generate a key");
+        let signature = SigningKey::<rsa::sha2::Sha256>::new(private_key.clone())
+            .sign(b"hello world")
+            .to_vec();
+        let n = private_key.n().to_bytes_be();
+        let e = private_key.e().to_bytes_be();
+        let input = CircuitValuesInput {
+            bits_len,
+            e: Some(&e),
+            n: &n,
+            signature: &signature,
+        };
+        write_seed(
+            "circuit_values",
+            &format!("generated_{}", bits_len),
+            &input.to_bytes(),
+        );
+        let jwk = format!(
+            r#"{{"kty":"RSA","n":"{}","e":"{}","alg":"RS256"}}"#,
+            encode_base64(&n, true),
+            encode_base64(&e, true)
+        );
+        write_seed(
+            "encoded_inputs",
+            &format!("generated_jwk_{}", bits_len),
+            jwk.as_bytes(),
+        );
+        write_seed(
+            "encoded_inputs",
+            &format!("generated_signature_hex_{}", bits_len),
+            format!("0x{}", encode_hex(&signature)).as_bytes(),
+        );
+        write_seed(
+            "encoded_inputs",
+            &format!("generated_signature_base64_{}", bits_len),
+            encode_base64(&signature, false).as_bytes(),
+        );
+    }
+}
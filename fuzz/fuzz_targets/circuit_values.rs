@@ -0,0 +1,32 @@
+//! Converts an untrusted public key and signature into the values of the pkcs1v15 circuits, as the prove functions of the wasm build do for their JS arguments.
+#![no_main]
+use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+use halo2_rsa::{
+    gen_circuit_values, normalize_signature_bytes, Pkcs1v15_1024_64EnabledBenchCircuit,
+};
+use halo2_rsa_fuzz::{assert_no_panic, CircuitValuesInput};
+use libfuzzer_sys::fuzz_target;
+use rsa::{BigUint, RsaPublicKey};
+
+fuzz_target!(|data: &[u8]| {
+    assert_no_panic(|| {
+        let input = match CircuitValuesInput::parse(data) {
+            Some(input) => input,
+            None => return,
+        };
+        let _ = normalize_signature_bytes(input.signature, input.bits_len);
+        // The rsa crate rejects the keys the JS side cannot deserialize either, e.g., those of an out-of-range `e`.
+        let e = BigUint::from_bytes_be(input.e.unwrap_or(&[1, 0, 1]));
+        let public_key = match RsaPublicKey::new(BigUint::from_bytes_be(input.n), e) {
+            Ok(public_key) => public_key,
+            Err(_) => return,
+        };
+        let _ = gen_circuit_values::<Fr>(
+            &public_key,
+            input.e,
+            Pkcs1v15_1024_64EnabledBenchCircuit::<Fr>::DEFAULT_E,
+            input.signature,
+            input.bits_len,
+        );
+    });
+});
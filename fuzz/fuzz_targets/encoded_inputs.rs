@@ -0,0 +1,17 @@
+//! Decodes untrusted strings as the JWKs, the hex strings, and the base64 strings accepted by the wasm build.
+#![no_main]
+use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+use halo2_rsa::{decode_base64, decode_hex, decode_proof_hex, parse_jwk, RSAPublicKey};
+use halo2_rsa_fuzz::assert_no_panic;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    assert_no_panic(|| {
+        let _ = parse_jwk(data);
+        let _ = RSAPublicKey::<Fr>::from_jwk(data, 16, 64);
+        let _ = decode_hex(data);
+        let _ = decode_proof_hex(data);
+        let _ = decode_base64(data, None);
+        let _ = decode_base64(data, Some(128));
+    });
+});
@@ -0,0 +1,27 @@
+//! Decodes untrusted bytes as a [`ProofBundle`] in both encodings and as the instances of a proof.
+#![no_main]
+use halo2_rsa::{parse_instance, parse_instances_json, ProofBundle};
+use halo2_rsa_fuzz::assert_no_panic;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    assert_no_panic(|| {
+        // A decoded bundle is encoded back to the same bytes, so no field is silently truncated.
+        if let Ok(bundle) = ProofBundle::from_bytes(data) {
+            assert_eq!(bundle.to_bytes().unwrap(), data);
+        }
+        if let Ok(bundle) = serde_json::from_slice::<ProofBundle>(data) {
+            let json = serde_json::to_vec(&bundle).unwrap();
+            assert_eq!(
+                serde_json::from_slice::<ProofBundle>(&json).unwrap(),
+                bundle
+            );
+        }
+        if let Ok(json) = std::str::from_utf8(data) {
+            let _ = parse_instance(json);
+            for num_instances in [0, 1, 17] {
+                let _ = parse_instances_json(json, num_instances);
+            }
+        }
+    });
+});
@@ -0,0 +1,26 @@
+//! Reads untrusted bytes as the versioned keys of [`FuzzKeyCircuit`] in every `KeyFormat`, as the wasm build reads the keys given to `load_proving_key` and the verify functions.
+#![no_main]
+use halo2_rsa::{
+    convert_key_format, decompress_key, extract_vk_bytes, read_pk_with_format, read_vk_with_format,
+    KeyFormat,
+};
+use halo2_rsa_fuzz::{assert_no_panic, FuzzKeyCircuit};
+use libfuzzer_sys::fuzz_target;
+
+const FORMATS: [KeyFormat; 3] = [
+    KeyFormat::RawBytes,
+    KeyFormat::RawBytesUnchecked,
+    KeyFormat::Processed,
+];
+
+fuzz_target!(|data: &[u8]| {
+    assert_no_panic(|| {
+        let _ = decompress_key(data);
+        let _ = extract_vk_bytes::<FuzzKeyCircuit>(data);
+        for format in FORMATS {
+            let _ = read_vk_with_format::<FuzzKeyCircuit>(data, format);
+            let _ = read_pk_with_format::<FuzzKeyCircuit>(data, format);
+            let _ = convert_key_format::<FuzzKeyCircuit>(data, format, KeyFormat::Processed);
+        }
+    });
+});
@@ -0,0 +1,20 @@
+//! Reads untrusted bytes as params by every read function of params, e.g., those given to `load_params` of the wasm build.
+#![no_main]
+use halo2_rsa::{
+    compress_params, decompress_params, extract_verifier_params, params_max_k, read_params,
+    read_srs, read_verifier_params,
+};
+use halo2_rsa_fuzz::assert_no_panic;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    assert_no_panic(|| {
+        let _ = params_max_k(data);
+        let _ = read_params(data);
+        let _ = read_srs(data);
+        let _ = decompress_params(data);
+        let _ = compress_params(data);
+        let _ = extract_verifier_params(data, 1);
+        let _ = read_verifier_params(data);
+    });
+});
@@ -0,0 +1,129 @@
+//! Helpers shared by the fuzz targets of halo2-rsa and the corpus generator in `examples/gen_corpus.rs`.
+//!
+//! Every target feeds the untrusted bytes to the deserializers of the crate and asserts that they return errors instead of panicking.
+use halo2_base::halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use halo2_base::{
+    gates::range::{RangeConfig, RangeStrategy::Vertical},
+    gates::RangeInstructions,
+    SKIP_FIRST_PASS,
+};
+use halo2_rsa::{BigUintConfig, BigUintInstructions};
+use num_bigint::BigUint;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+/// The degree of [`FuzzKeyCircuit`], which keeps its keys small enough to be mutated by the fuzzer.
+pub const FUZZ_KEY_K: u32 = 8;
+
+/// Runs `f` on an input of a fuzz target and aborts if a panic escapes it.
+///
+/// libfuzzer-sys installs a panic hook that aborts on every panic, even one caught by the crate, e.g., a panic of halo2 on a malformed key converted into an error.
+/// The hook is therefore replaced with the default one, and only the panics escaping `f` are reported as crashes.
+pub fn assert_no_panic<F: FnOnce()>(f: F) {
+    static RESET_HOOK: Once = Once::new();
+    RESET_HOOK.call_once(|| {
+        let _ = panic::take_hook();
+    });
+    if panic::catch_unwind(AssertUnwindSafe(f)).is_err() {
+        std::process::abort();
+    }
+}
+
+/// A circuit of degree [`FUZZ_KEY_K`] with the same kinds of columns as the pkcs1v15 circuits, whose keys are read by the target `read_keys`.
+///
+/// The keys of the presets are megabytes long, but the deserializers of the keys are generic over the circuit, so the small keys reach the same code paths.
+#[derive(Default)]
+pub struct FuzzKeyCircuit;
+
+impl Circuit<Fr> for FuzzKeyCircuit {
+    type Config = BigUintConfig<Fr>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let k = FUZZ_KEY_K as usize;
+        let range_config = RangeConfig::configure(meta, Vertical, &[1], &[1], 1, k - 1, 0, k);
+        BigUintConfig::construct(range_config, 64)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.range().load_lookup_table(&mut layouter)?;
+        let mut first_pass = SKIP_FIRST_PASS;
+        layouter.assign_region(
+            || "assign an integer",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(());
+                }
+                let mut aux = config.new_context(region);
+                let ctx = &mut aux;
+                config.assign_integer(ctx, Value::known(BigUint::from(3u64)), 64)?;
+                config.range().finalize(ctx);
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+}
+
+/// The inputs of [`halo2_rsa::gen_circuit_values`] decoded from the bytes of the target `circuit_values`.
+///
+/// The bytes are the bit length of the modulus, `1024` if the first byte is even and `2048` otherwise,
+/// the length of the fixed exponent `e` in one byte followed by its big-endian bytes, where the length zero gives no `e`,
+/// the length of the modulus in two big-endian bytes followed by its big-endian bytes, and the signature in the rest.
+#[derive(Debug)]
+pub struct CircuitValuesInput<'a> {
+    pub bits_len: usize,
+    pub e: Option<&'a [u8]>,
+    pub n: &'a [u8],
+    pub signature: &'a [u8],
+}
+
+impl<'a> CircuitValuesInput<'a> {
+    /// Splits `bytes` into the inputs, or returns `None` if they are too short for the lengths they declare.
+    pub fn parse(bytes: &'a [u8]) -> Option<Self> {
+        let (&selector, rest) = bytes.split_first()?;
+        let bits_len = if selector % 2 == 0 { 1024 } else { 2048 };
+        let (&e_len, rest) = rest.split_first()?;
+        let (e, rest) = split_at_checked(rest, e_len as usize)?;
+        let (n_len, rest) = split_at_checked(rest, 2)?;
+        let (n, signature) =
+            split_at_checked(rest, u16::from_be_bytes([n_len[0], n_len[1]]) as usize)?;
+        Some(Self {
+            bits_len,
+            e: if e.is_empty() { None } else { Some(e) },
+            n,
+            signature,
+        })
+    }
+
+    /// Encodes the inputs in the layout read by [`CircuitValuesInput::parse`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let e = self.e.unwrap_or_default();
+        let mut bytes = vec![(self.bits_len == 2048) as u8, e.len() as u8];
+        bytes.extend_from_slice(e);
+        bytes.extend_from_slice(&(self.n.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(self.n);
+        bytes.extend_from_slice(self.signature);
+        bytes
+    }
+}
+
+fn split_at_checked(bytes: &[u8], mid: usize) -> Option<(&[u8], &[u8])> {
+    if mid > bytes.len() {
+        return None;
+    }
+    Some(bytes.split_at(mid))
+}
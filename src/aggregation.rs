@@ -0,0 +1,618 @@
+use crate::{create_proof_with, verify_proof_with, MultiOpenScheme, TranscriptKind, VerifyError};
+use halo2_base::halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    halo2curves::{
+        bn256::{Bn256, Fq, Fr, G1Affine},
+        CurveAffine,
+    },
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+    plonk::{ProvingKey, VerifyingKey},
+    poly::{
+        commitment::{Params, ParamsProver},
+        kzg::commitment::ParamsKZG,
+    },
+    transcript::TranscriptWriterBuffer,
+};
+use halo2_base::{
+    gates::range::RangeConfig, utils::modulus, Context, ContextParams, SKIP_FIRST_PASS,
+};
+use halo2_ecc::{
+    ecc::{BaseFieldEccChip, EccChip},
+    fields::fp::{FpConfig, FpStrategy},
+};
+use rand::rngs::OsRng;
+use snark_verifier::{
+    loader::{self, native::NativeLoader},
+    pcs::{
+        kzg::{Gwc19, KzgAccumulator, KzgAs, KzgDecidingKey, KzgSuccinctVerifyingKey},
+        AccumulationDecider, AccumulationScheme, AccumulationSchemeProver,
+    },
+    system::halo2::Config,
+    system::halo2::{compile, transcript::halo2::PoseidonTranscript as Halo2PoseidonTranscript},
+    util::arithmetic::{fe_from_limbs, fe_to_limbs},
+    verifier::{self, plonk::PlonkProtocol, SnarkVerifier},
+};
+use std::io;
+use std::rc::Rc;
+
+/// The bit width of the limbs of a base field element exposed in the instances of [`AggregationCircuit`].
+pub const AGGREGATION_LIMB_BITS: usize = 88;
+/// The number of the limbs of a base field element exposed in the instances of [`AggregationCircuit`].
+pub const AGGREGATION_NUM_LIMBS: usize = 3;
+/// The number of the instances of [`AggregationCircuit`] encoding the KZG accumulator, which precede the instances of the aggregated proofs.
+pub const ACCUMULATOR_NUM_INSTANCES: usize = 4 * AGGREGATION_NUM_LIMBS;
+
+const T: usize = 3;
+const RATE: usize = 2;
+const R_F: usize = 8;
+const R_P: usize = 57;
+
+type As = KzgAs<Bn256, Gwc19>;
+type PlonkSuccinctVerifier = verifier::plonk::PlonkSuccinctVerifier<As>;
+type Halo2Loader<'a> = loader::halo2::Halo2Loader<'a, G1Affine, BaseFieldEccChip<G1Affine>>;
+/// The Poseidon transcript whose challenges the aggregation circuit recomputes cheaply, unlike the Blake2b and Keccak256 transcripts.
+///
+/// It is the transcript of [`TranscriptKind::Poseidon`].
+pub(crate) type PoseidonTranscript<L, S> =
+    Halo2PoseidonTranscript<G1Affine, L, S, T, RATE, R_F, R_P>;
+
+/// The parameters of the columns of [`AggregationCircuit`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AggregationConfigParams {
+    /// The degree of the circuit, i.e., the minimum degree of its params.
+    pub degree: u32,
+    /// The number of the flex advice columns.
+    pub num_advice: usize,
+    /// The number of the advice columns of the range lookup.
+    pub num_lookup_advice: usize,
+    /// The number of the fixed columns.
+    pub num_fixed: usize,
+    /// The number of the bits of the range lookup table.
+    pub lookup_bits: usize,
+}
+
+/// The columns of [`AggregationCircuit`], which fit two proofs of the pkcs1v15 circuits in `2^21` rows.
+pub const AGGREGATION_CONFIG_PARAMS: AggregationConfigParams = AggregationConfigParams {
+    degree: 21,
+    num_advice: 8,
+    num_lookup_advice: 1,
+    num_fixed: 1,
+    lookup_bits: 20,
+};
+
+/// A proof to be aggregated with the protocol of its circuit and its instances.
+#[derive(Clone, Debug)]
+pub struct Snark {
+    protocol: PlonkProtocol<G1Affine>,
+    instances: Vec<Vec<Fr>>,
+    proof: Vec<u8>,
+}
+
+impl Snark {
+    /// Returns the values of the instance columns of the proof.
+    pub fn instances(&self) -> &[Vec<Fr>] {
+        &self.instances
+    }
+
+    /// Returns the bytes of the proof created with the Poseidon transcript.
+    pub fn proof(&self) -> &[u8] {
+        &self.proof
+    }
+}
+
+/// Creates a proof of `circuit` that [`AggregationCircuit`] can aggregate.
+///
+/// The aggregation circuit recomputes the Fiat-Shamir challenges of the proof, so the proof is created with [`TranscriptKind::Poseidon`] and the GWC multi-open scheme.
+/// It is the same proof as the one of [`crate::create_proof_with`] with them, and is verified by [`crate::verify_proof_with`] with them.
+pub fn create_snark<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: Vec<Vec<Fr>>,
+) -> Result<Snark, Error> {
+    let protocol = compile_protocol(params, pk.get_vk(), &instances);
+    let instance_refs = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    let proof = create_proof_with(
+        params,
+        pk,
+        circuit,
+        &instance_refs,
+        TranscriptKind::Poseidon,
+        MultiOpenScheme::Gwc,
+    )?;
+    Ok(Snark {
+        protocol,
+        instances,
+        proof,
+    })
+}
+
+/// Returns the circuit aggregating `proofs`, all of which were created by [`create_snark`] for the circuit of `vk_inner`.
+///
+/// The proofs are checked natively and folded into one KZG accumulator, which the circuit checks again together with the proofs.
+/// The outer proof of the circuit is created by [`create_aggregation_proof`] and verified by [`verify_aggregation_proof`].
+///
+/// # Arguments
+/// * params_inner - the params of the aggregated proofs.
+/// * vk_inner - the verifying key of the aggregated proofs.
+/// * proofs - the proofs created by [`create_snark`].
+/// * instances - the values of the instance columns of each proof.
+/// * params_outer - the params to prove the aggregation circuit, which may be generated by another setup than `params_inner`.
+///
+/// # Errors
+/// Returns [`VerifyError::InvalidParams`] if `params_outer` has fewer rows than [`AGGREGATION_CONFIG_PARAMS`], [`VerifyError::InvalidInstances`] if the numbers of proofs and instances differ, and [`VerifyError::ConstraintCheckFailed`] if a proof is invalid.
+pub fn aggregate_proofs(
+    params_inner: &ParamsKZG<Bn256>,
+    vk_inner: &VerifyingKey<G1Affine>,
+    proofs: &[Vec<u8>],
+    instances: &[Vec<Vec<Fr>>],
+    params_outer: &ParamsKZG<Bn256>,
+) -> Result<AggregationCircuit, VerifyError> {
+    if params_outer.k() < AGGREGATION_CONFIG_PARAMS.degree {
+        return Err(VerifyError::InvalidParams(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "the aggregation circuit needs params of k >= {}, but k = {}",
+                AGGREGATION_CONFIG_PARAMS.degree,
+                params_outer.k()
+            ),
+        )));
+    }
+    if proofs.is_empty() || proofs.len() != instances.len() {
+        return Err(VerifyError::InvalidInstances);
+    }
+    let snarks = proofs
+        .iter()
+        .zip(instances)
+        .map(|(proof, instances)| Snark {
+            protocol: compile_protocol(params_inner, vk_inner, instances),
+            instances: instances.clone(),
+            proof: proof.clone(),
+        })
+        .collect::<Vec<_>>();
+    AggregationCircuit::new(params_inner, snarks)
+}
+
+/// Creates the outer proof of `circuit` with the Blake2b transcript and the GWC multi-open scheme.
+pub fn create_aggregation_proof(
+    params_outer: &ParamsKZG<Bn256>,
+    pk_outer: &ProvingKey<G1Affine>,
+    circuit: AggregationCircuit,
+) -> Result<Vec<u8>, Error> {
+    let instances = circuit.instances();
+    create_proof_with(
+        params_outer,
+        pk_outer,
+        circuit,
+        &[&instances],
+        TranscriptKind::Blake2b,
+        MultiOpenScheme::Gwc,
+    )
+}
+
+/// Verifies a proof created by [`create_aggregation_proof`] and decides its KZG accumulator with the pairing of `params_inner`.
+///
+/// # Arguments
+/// * instances - the instances returned by [`AggregationCircuit::instances`], i.e., the accumulator followed by the instances of the aggregated proofs.
+pub fn verify_aggregation_proof(
+    params_inner: &ParamsKZG<Bn256>,
+    params_outer: &ParamsKZG<Bn256>,
+    vk_outer: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[Fr],
+) -> Result<(), VerifyError> {
+    if instances.len() < ACCUMULATOR_NUM_INSTANCES {
+        return Err(VerifyError::InvalidInstances);
+    }
+    verify_proof_with(
+        params_outer,
+        vk_outer,
+        proof,
+        &[instances],
+        TranscriptKind::Blake2b,
+        MultiOpenScheme::Gwc,
+    )?;
+    let accumulator = accumulator_from_instances(&instances[..ACCUMULATOR_NUM_INSTANCES])
+        .ok_or(VerifyError::ConstraintCheckFailed)?;
+    let dk: KzgDecidingKey<Bn256> = (
+        params_inner.get_g()[0],
+        params_inner.g2(),
+        params_inner.s_g2(),
+    )
+        .into();
+    As::decide(&dk, accumulator).map_err(|_| VerifyError::ConstraintCheckFailed)
+}
+
+fn compile_protocol(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    instances: &[Vec<Fr>],
+) -> PlonkProtocol<G1Affine> {
+    compile(
+        params,
+        vk,
+        Config::kzg().with_num_instance(instances.iter().map(Vec::len).collect()),
+    )
+}
+
+fn accumulator_from_instances(limbs: &[Fr]) -> Option<KzgAccumulator<G1Affine, NativeLoader>> {
+    let [lhs_x, lhs_y, rhs_x, rhs_y] = [0, 1, 2, 3].map(|i| {
+        let limbs: [Fr; AGGREGATION_NUM_LIMBS] = limbs
+            [i * AGGREGATION_NUM_LIMBS..(i + 1) * AGGREGATION_NUM_LIMBS]
+            .try_into()
+            .unwrap();
+        fe_from_limbs::<Fq, Fr, AGGREGATION_NUM_LIMBS, AGGREGATION_LIMB_BITS>(limbs)
+    });
+    let lhs = Option::from(G1Affine::from_xy(lhs_x, lhs_y))?;
+    let rhs = Option::from(G1Affine::from_xy(rhs_x, rhs_y))?;
+    Some(KzgAccumulator::new(lhs, rhs))
+}
+
+/// The columns of [`AggregationCircuit`].
+#[derive(Clone, Debug)]
+pub struct AggregationConfig {
+    base_field_config: FpConfig<Fr, Fq>,
+    instance: Column<Instance>,
+}
+
+impl AggregationConfig {
+    /// Configures the columns of `params` with the base field chip of BN254 and an instance column.
+    pub fn configure(meta: &mut ConstraintSystem<Fr>, params: AggregationConfigParams) -> Self {
+        let base_field_config = FpConfig::configure(
+            meta,
+            FpStrategy::Simple,
+            &[params.num_advice],
+            &[params.num_lookup_advice],
+            params.num_fixed,
+            params.lookup_bits,
+            AGGREGATION_LIMB_BITS,
+            AGGREGATION_NUM_LIMBS,
+            modulus::<Fq>(),
+            0,
+            params.degree as usize,
+        );
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        Self {
+            base_field_config,
+            instance,
+        }
+    }
+
+    /// Returns the range chip shared with the base field chip.
+    pub fn range(&self) -> &RangeConfig<Fr> {
+        &self.base_field_config.range
+    }
+
+    fn ecc_chip(&self) -> BaseFieldEccChip<G1Affine> {
+        EccChip::construct(self.base_field_config.clone())
+    }
+}
+
+/// A circuit verifying the proofs of another circuit and exposing their instances.
+///
+/// Its instance column has the KZG accumulator of the proofs as [`ACCUMULATOR_NUM_INSTANCES`] limbs, followed by the instances of each proof in order.
+/// The pairing check of the accumulator is deferred to the verifier of the outer proof, e.g., [`verify_aggregation_proof`].
+#[derive(Clone, Debug)]
+pub struct AggregationCircuit {
+    svk: KzgSuccinctVerifyingKey<G1Affine>,
+    snarks: Vec<Snark>,
+    accumulator: Vec<Fr>,
+    as_proof: Vec<u8>,
+}
+
+impl AggregationCircuit {
+    /// Checks `snarks` natively and folds them into one KZG accumulator.
+    ///
+    /// # Errors
+    /// Returns [`VerifyError::ConstraintCheckFailed`] if a proof is invalid.
+    pub fn new(params_inner: &ParamsKZG<Bn256>, snarks: Vec<Snark>) -> Result<Self, VerifyError> {
+        let svk: KzgSuccinctVerifyingKey<G1Affine> = params_inner.get_g()[0].into();
+        let mut accumulators = vec![];
+        for snark in snarks.iter() {
+            let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(snark.proof.as_slice());
+            let proof = PlonkSuccinctVerifier::read_proof(
+                &svk,
+                &snark.protocol,
+                &snark.instances,
+                &mut transcript,
+            )
+            .map_err(|_| VerifyError::ConstraintCheckFailed)?;
+            let accumulator =
+                PlonkSuccinctVerifier::verify(&svk, &snark.protocol, &snark.instances, &proof)
+                    .map_err(|_| VerifyError::ConstraintCheckFailed)?;
+            accumulators.extend(accumulator);
+        }
+
+        let mut transcript = PoseidonTranscript::<NativeLoader, _>::init(vec![]);
+        let KzgAccumulator { lhs, rhs } =
+            As::create_proof(&Default::default(), &accumulators, &mut transcript, OsRng)
+                .map_err(|_| VerifyError::ConstraintCheckFailed)?;
+        let accumulator = [lhs.x, lhs.y, rhs.x, rhs.y]
+            .map(fe_to_limbs::<Fq, Fr, AGGREGATION_NUM_LIMBS, AGGREGATION_LIMB_BITS>)
+            .concat();
+        Ok(Self {
+            svk,
+            snarks,
+            accumulator,
+            as_proof: transcript.finalize(),
+        })
+    }
+
+    /// Returns the values of the instance column, i.e., the accumulator followed by the instances of the aggregated proofs.
+    pub fn instances(&self) -> Vec<Fr> {
+        let mut instances = self.accumulator.clone();
+        for snark in self.snarks.iter() {
+            instances.extend(snark.instances.iter().flatten());
+        }
+        instances
+    }
+}
+
+impl Circuit<Fr> for AggregationCircuit {
+    type Config = AggregationConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        AggregationConfig::configure(meta, AGGREGATION_CONFIG_PARAMS)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.range().load_lookup_table(&mut layouter)?;
+        let mut first_pass = SKIP_FIRST_PASS;
+        let instance_cells = layouter.assign_region(
+            || "aggregate proofs",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(vec![]);
+                }
+
+                let ctx = Context::new(
+                    region,
+                    ContextParams {
+                        max_rows: config.range().gate.max_rows,
+                        num_context_ids: 1,
+                        fixed_columns: config.range().gate.constants.clone(),
+                    },
+                );
+                let loader = Halo2Loader::new(config.ecc_chip(), ctx);
+                let mut accumulators = vec![];
+                let mut instance_cells = vec![];
+                for snark in self.snarks.iter() {
+                    let protocol = snark.protocol.loaded(&loader);
+                    let instances = snark
+                        .instances
+                        .iter()
+                        .map(|column| {
+                            column
+                                .iter()
+                                .map(|value| loader.assign_scalar(Value::known(*value)))
+                                .collect::<Vec<_>>()
+                        })
+                        .collect::<Vec<_>>();
+                    let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, _>::new(
+                        &loader,
+                        Value::known(snark.proof.as_slice()),
+                    );
+                    let proof = PlonkSuccinctVerifier::read_proof(
+                        &self.svk,
+                        &protocol,
+                        &instances,
+                        &mut transcript,
+                    )
+                    .map_err(|_| Error::Synthesis)?;
+                    accumulators.extend(
+                        PlonkSuccinctVerifier::verify(&self.svk, &protocol, &instances, &proof)
+                            .map_err(|_| Error::Synthesis)?,
+                    );
+                    instance_cells.extend(
+                        instances
+                            .iter()
+                            .flatten()
+                            .map(|instance| instance.assigned().cell()),
+                    );
+                }
+
+                let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, _>::new(
+                    &loader,
+                    Value::known(self.as_proof.as_slice()),
+                );
+                let as_proof = As::read_proof(&Default::default(), &accumulators, &mut transcript)
+                    .map_err(|_| Error::Synthesis)?;
+                let KzgAccumulator { lhs, rhs } =
+                    As::verify(&Default::default(), &accumulators, &as_proof)
+                        .map_err(|_| Error::Synthesis)?;
+                let (lhs, rhs) = (lhs.assigned(), rhs.assigned());
+                config.base_field_config.finalize(&mut loader.ctx_mut());
+
+                let accumulator_cells = [&lhs.x, &lhs.y, &rhs.x, &rhs.y]
+                    .into_iter()
+                    .flat_map(|coordinate| coordinate.truncation.limbs.iter())
+                    .map(|limb| limb.cell());
+                Ok(accumulator_cells.chain(instance_cells).collect::<Vec<_>>())
+            },
+        )?;
+        for (i, cell) in instance_cells.into_iter().enumerate() {
+            layouter.constrain_instance(cell, config.instance, i)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{impl_pkcs1v15_basic_circuit, RSAPubE, RSAPublicKey, RSASignature};
+    use halo2_base::halo2_proofs::{
+        dev::MockProver,
+        plonk::{keygen_pk, keygen_vk},
+    };
+    use num_bigint::BigUint;
+    use rand::thread_rng;
+    use rsa::{
+        pkcs1v15::SigningKey, signature::SignatureEncoding, signature::Signer,
+        traits::PublicKeyParts, RsaPrivateKey, RsaPublicKey,
+    };
+    use std::marker::PhantomData;
+
+    // The same circuit as `1024_64` of the wasm module.
+    impl_pkcs1v15_basic_circuit!(
+        Pkcs1v15_1024_64Config,
+        Pkcs1v15_1024_64Circuit,
+        setup_pkcs1v15_1024_64,
+        prove_pkcs1v15_1024_64,
+        1024,
+        64,
+        3,
+        1,
+        16,
+        1,
+        19,
+        true
+    );
+
+    fn sample_circuit(msg: &[u8]) -> Pkcs1v15_1024_64Circuit<Fr> {
+        let mut rng = thread_rng();
+        let private_key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let public_key = RsaPublicKey::from(&private_key);
+        let signing_key = SigningKey::<rsa::sha2::Sha256>::new(private_key);
+        let sign = signing_key.sign(msg).to_vec();
+        Pkcs1v15_1024_64Circuit {
+            signature: RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+            public_key: RSAPublicKey::new(
+                Value::known(BigUint::from_bytes_be(&public_key.n().to_bytes_be())),
+                RSAPubE::Fix(BigUint::from(Pkcs1v15_1024_64Circuit::<Fr>::DEFAULT_E)),
+            ),
+            msg: msg.to_vec(),
+            _f: PhantomData,
+        }
+    }
+
+    #[test]
+    fn test_poseidon_transcript_proof_1024_64() {
+        let (params, vk, pk) = setup_pkcs1v15_1024_64();
+        let prove = |transcript| {
+            create_proof_with(
+                &params,
+                &pk,
+                sample_circuit(b"recursive email"),
+                &[],
+                transcript,
+                MultiOpenScheme::Gwc,
+            )
+            .unwrap()
+        };
+        let verify = |proof: &[u8], transcript| {
+            verify_proof_with(&params, &vk, proof, &[], transcript, MultiOpenScheme::Gwc)
+        };
+        let proof = prove(TranscriptKind::Poseidon);
+        verify(&proof, TranscriptKind::Poseidon).unwrap();
+        for kind in [TranscriptKind::Blake2b, TranscriptKind::Keccak256] {
+            assert!(matches!(
+                verify(&proof, kind),
+                Err(VerifyError::TranscriptMismatch { expected, actual })
+                    if expected == kind && actual == TranscriptKind::Poseidon
+            ));
+            let other_proof = prove(kind);
+            verify(&other_proof, kind).unwrap();
+            assert!(matches!(
+                verify(&other_proof, TranscriptKind::Poseidon),
+                Err(VerifyError::TranscriptMismatch { expected, actual })
+                    if expected == TranscriptKind::Poseidon && actual == kind
+            ));
+        }
+
+        // The proof is accepted by the aggregation circuit as it is.
+        let snark = Snark {
+            protocol: compile_protocol(&params, &vk, &[]),
+            instances: vec![],
+            proof,
+        };
+        AggregationCircuit::new(&params, vec![snark]).unwrap();
+    }
+
+    #[test]
+    fn test_aggregate_two_pkcs1v15_proofs() {
+        let (params_inner, vk_inner, pk_inner) = setup_pkcs1v15_1024_64();
+        prove_pkcs1v15_1024_64(&params_inner, &vk_inner, &pk_inner, MultiOpenScheme::Gwc);
+        let params_outer = ParamsKZG::<Bn256>::setup(AGGREGATION_CONFIG_PARAMS.degree, OsRng);
+
+        let snarks = [b"first email".to_vec(), b"second email".to_vec()]
+            .iter()
+            .map(|msg| create_snark(&params_inner, &pk_inner, sample_circuit(msg), vec![]).unwrap())
+            .collect::<Vec<_>>();
+        let proofs = snarks
+            .iter()
+            .map(|snark| snark.proof().to_vec())
+            .collect::<Vec<_>>();
+        let instances = snarks
+            .iter()
+            .map(|snark| snark.instances().to_vec())
+            .collect::<Vec<_>>();
+        let circuit =
+            aggregate_proofs(&params_inner, &vk_inner, &proofs, &instances, &params_outer).unwrap();
+        let outer_instances = circuit.instances();
+        // The 1024_64 circuit has no instances, so only the accumulator is exposed.
+        assert_eq!(outer_instances.len(), ACCUMULATOR_NUM_INSTANCES);
+        MockProver::run(
+            AGGREGATION_CONFIG_PARAMS.degree,
+            &circuit,
+            vec![outer_instances.clone()],
+        )
+        .unwrap()
+        .assert_satisfied();
+
+        let vk_outer = keygen_vk(&params_outer, &circuit).unwrap();
+        let pk_outer = keygen_pk(&params_outer, vk_outer.clone(), &circuit).unwrap();
+        let proof = create_aggregation_proof(&params_outer, &pk_outer, circuit).unwrap();
+        verify_aggregation_proof(
+            &params_inner,
+            &params_outer,
+            &vk_outer,
+            &proof,
+            &outer_instances,
+        )
+        .unwrap();
+        let mut tampered = outer_instances.clone();
+        tampered[0] += Fr::from(1);
+        assert!(verify_aggregation_proof(
+            &params_inner,
+            &params_outer,
+            &vk_outer,
+            &proof,
+            &tampered
+        )
+        .is_err());
+
+        let mut corrupted = proofs[0].clone();
+        corrupted[0] ^= 1;
+        assert!(aggregate_proofs(
+            &params_inner,
+            &vk_inner,
+            &[corrupted],
+            &instances[..1],
+            &params_outer
+        )
+        .is_err());
+        assert!(aggregate_proofs(
+            &params_inner,
+            &vk_inner,
+            &proofs,
+            &instances[..1],
+            &params_outer
+        )
+        .is_err());
+        assert!(
+            aggregate_proofs(&params_inner, &vk_inner, &proofs, &instances, &params_inner).is_err()
+        );
+    }
+}
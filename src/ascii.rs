@@ -0,0 +1,241 @@
+//! Constraints on the bytes of a signed message to be printable ASCII, as required by some attestation formats.
+use crate::cost_report::{begin_cost_region, end_cost_region};
+#[cfg(feature = "sha256")]
+use halo2_base::halo2_proofs::plonk::Error;
+use halo2_base::QuantumCell;
+use halo2_base::{
+    gates::{range::RangeConfig, GateInstructions, RangeInstructions},
+    utils::PrimeField,
+    AssignedValue, Context,
+};
+
+/// The smallest printable ASCII byte, i.e., the space.
+pub const PRINTABLE_ASCII_MIN: u8 = 0x20;
+/// The largest printable ASCII byte, i.e., '~'.
+pub const PRINTABLE_ASCII_MAX: u8 = 0x7e;
+
+/// Returns whether every byte of `bytes` is printable ASCII, i.e., in `PRINTABLE_ASCII_MIN..=PRINTABLE_ASCII_MAX`.
+pub fn is_printable_ascii(bytes: &[u8]) -> bool {
+    bytes
+        .iter()
+        .all(|byte| (PRINTABLE_ASCII_MIN..=PRINTABLE_ASCII_MAX).contains(byte))
+}
+
+/// Chip to constrain assigned bytes to be printable ASCII in the circuit.
+///
+/// It implements [`crate::MessageConsumer`], so it is composed with the signature verification by [`crate::RSASignatureVerifier::verify_pkcs1v15_and_consume`], which makes the circuit unsatisfiable for a signed message with a control or a non-ASCII byte.
+#[derive(Clone, Debug)]
+pub struct PrintableAsciiChip<F: PrimeField> {
+    range: RangeConfig<F>,
+}
+
+impl<F: PrimeField> PrintableAsciiChip<F> {
+    /// Creates a new [`PrintableAsciiChip`] from the range config.
+    pub fn new(range: RangeConfig<F>) -> Self {
+        Self { range }
+    }
+
+    /// Getter for [`RangeConfig`].
+    pub fn range(&self) -> &RangeConfig<F> {
+        &self.range
+    }
+
+    /// Constrains every assigned byte of `bytes` to be printable ASCII.
+    ///
+    /// The bytes need not be range-checked beforehand: `byte - 0x20` and `0x7e - byte` are both range-checked to 7 bits, which holds only for `byte` in `0x20..=0x7e`.
+    pub fn assert_printable_ascii<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        bytes: &[AssignedValue<'v, F>],
+    ) {
+        let gate = self.range.gate();
+        begin_cost_region(ctx, "printable ascii");
+        for byte in bytes.iter() {
+            let above_min = gate.sub(
+                ctx,
+                QuantumCell::Existing(byte),
+                QuantumCell::Constant(F::from(PRINTABLE_ASCII_MIN as u64)),
+            );
+            self.range.range_check(ctx, &above_min, 7);
+            let below_max = gate.sub(
+                ctx,
+                QuantumCell::Constant(F::from(PRINTABLE_ASCII_MAX as u64)),
+                QuantumCell::Existing(byte),
+            );
+            self.range.range_check(ctx, &below_max, 7);
+        }
+        end_cost_region(ctx);
+    }
+}
+
+#[cfg(feature = "sha256")]
+impl<F: PrimeField> crate::MessageConsumer<F> for PrintableAsciiChip<F> {
+    /// Constrains the message to be printable ASCII, and outputs nothing.
+    fn consume<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        msg: &[AssignedValue<'v, F>],
+    ) -> Result<Vec<AssignedValue<'v, F>>, Error> {
+        self.assert_printable_ascii(ctx, msg);
+        Ok(vec![])
+    }
+}
+
+#[cfg(all(test, feature = "sha256"))]
+mod test {
+    use super::*;
+    use crate::{
+        BigUintConfig, RSAConfig, RSAInstructions, RSAPubE, RSAPublicKey, RSASignature,
+        RSASignatureVerifier,
+    };
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem},
+    };
+    use halo2_base::{gates::range::RangeStrategy::Vertical, SKIP_FIRST_PASS};
+    use halo2_dynamic_sha256::Sha256DynamicConfig;
+    use num_bigint::BigUint;
+    use rand::thread_rng;
+    use rsa::{
+        pkcs1v15::SigningKey, signature::SignatureEncoding, signature::Signer,
+        traits::PublicKeyParts, RsaPrivateKey,
+    };
+    use std::marker::PhantomData;
+
+    #[derive(Debug, Clone)]
+    struct TestPrintableAsciiConfig<F: PrimeField> {
+        rsa_config: RSAConfig<F>,
+        sha256_config: Sha256DynamicConfig<F>,
+        ascii_chip: PrintableAsciiChip<F>,
+    }
+
+    struct TestPrintableAsciiCircuit<F: PrimeField> {
+        private_key: RsaPrivateKey,
+        msg: Vec<u8>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestPrintableAsciiCircuit<F> {
+        const BITS_LEN: usize = 1024;
+        const K: usize = 15;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestPrintableAsciiCircuit<F> {
+        type Config = TestPrintableAsciiConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config =
+                RangeConfig::configure(meta, Vertical, &[80], &[16], 1, 12, 0, Self::K);
+            let biguint_config = BigUintConfig::construct(range_config.clone(), 64);
+            let rsa_config = RSAConfig::construct(biguint_config, Self::BITS_LEN, 5);
+            let sha256_config =
+                Sha256DynamicConfig::configure(meta, vec![64], range_config.clone(), 8, 8, true);
+            Self::Config {
+                rsa_config,
+                sha256_config,
+                ascii_chip: PrintableAsciiChip::new(range_config),
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let biguint_config = config.rsa_config.biguint_config();
+            config.sha256_config.load(&mut layouter)?;
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "rsa signature of a printable ascii message",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    let signing_key =
+                        SigningKey::<rsa::sha2::Sha256>::new(self.private_key.clone());
+                    let sign = signing_key.sign(&self.msg).to_vec();
+                    let sign = config.rsa_config.assign_signature(
+                        ctx,
+                        RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+                    )?;
+                    let n_big = BigUint::from_bytes_be(&self.private_key.n().to_bytes_be());
+                    let e_fix = RSAPubE::Fix(BigUint::from(65537u32));
+                    let public_key = config
+                        .rsa_config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                    let mut verifier = RSASignatureVerifier::new(
+                        config.rsa_config.clone(),
+                        config.sha256_config.clone(),
+                    );
+                    let (is_valid, outputs, _) = verifier.verify_pkcs1v15_and_consume(
+                        ctx,
+                        &public_key,
+                        &self.msg,
+                        &sign,
+                        &config.ascii_chip,
+                    )?;
+                    assert!(outputs.is_empty());
+                    biguint_config
+                        .gate()
+                        .assert_is_const(ctx, &is_valid, F::one());
+                    biguint_config.range().finalize(ctx);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn run_printable_ascii(msg: &[u8]) -> bool {
+        let private_key =
+            RsaPrivateKey::new(&mut thread_rng(), 1024).expect("failed to generate a key");
+        let circuit = TestPrintableAsciiCircuit::<Fr> {
+            private_key,
+            msg: msg.to_vec(),
+            _f: PhantomData,
+        };
+        let prover =
+            match MockProver::run(TestPrintableAsciiCircuit::<Fr>::K as u32, &circuit, vec![]) {
+                Ok(prover) => prover,
+                Err(e) => panic!("{:#?}", e),
+            };
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_printable_ascii_message() {
+        // The space and '~' are the bounds of the printable bytes.
+        assert!(run_printable_ascii(b" attestation: {\"ok\": true}~"));
+    }
+
+    #[test]
+    fn test_non_printable_ascii_message() {
+        // A NUL byte injected into the signed message makes the circuit unsatisfiable although the signature is valid.
+        assert!(!run_printable_ascii(b"attestation\x00: ok"));
+        assert!(!run_printable_ascii(b"attestation\x1f: ok"));
+        assert!(!run_printable_ascii(b"attestation\x7f: ok"));
+        assert!(!run_printable_ascii("attestation: \u{e9}".as_bytes()));
+    }
+
+    #[test]
+    fn test_is_printable_ascii() {
+        assert!(is_printable_ascii(b""));
+        assert!(is_printable_ascii(b" ~"));
+        assert!(!is_printable_ascii(b"\x00"));
+        assert!(!is_printable_ascii(b"\x1f"));
+        assert!(!is_printable_ascii(b"\x7f"));
+        assert!(!is_printable_ascii(b"\xff"));
+    }
+}
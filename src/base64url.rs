@@ -0,0 +1,357 @@
+//! Decoding of base64url strings (RFC 4648, section 5) without padding in the circuit, as used in the segments of JWTs and the members of JWKs.
+use crate::cost_report::{begin_cost_region, end_cost_region};
+use halo2_base::halo2_proofs::{circuit::Value, plonk::Error};
+use halo2_base::QuantumCell;
+use halo2_base::{
+    gates::{range::RangeConfig, GateInstructions, RangeInstructions},
+    utils::{fe_to_biguint, PrimeField},
+    AssignedValue, Context,
+};
+use num_traits::ToPrimitive;
+
+/// The alphabet of base64url, whose `i`-th character encodes the sextet `i`.
+pub const BASE64URL_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Returns the sextet encoded by the base64url character `c`, or `None` if `c` is not in [`BASE64URL_ALPHABET`].
+pub fn base64url_sextet(c: u8) -> Option<u8> {
+    match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'-' => Some(62),
+        b'_' => Some(63),
+        _ => None,
+    }
+}
+
+/// Returns the byte length of the decoding of an unpadded base64url string of `encoded_len` characters, or `None` if no string has that length.
+pub fn base64url_decoded_len(encoded_len: usize) -> Option<usize> {
+    match encoded_len % 4 {
+        1 => None,
+        rem => Some(encoded_len / 4 * 3 + rem.saturating_sub(1)),
+    }
+}
+
+/// Chip to decode base64url strings in the circuit.
+///
+/// Each character is constrained to [`BASE64URL_ALPHABET`], and the unused bits of the last character are constrained to zero, so that exactly one string decodes to each byte string.
+/// The decoded bytes can then be fed to the other chips, e.g., the big-endian bytes of the modulus in the member `n` of a JWK.
+#[derive(Clone, Debug)]
+pub struct Base64UrlChip<F: PrimeField> {
+    range: RangeConfig<F>,
+}
+
+impl<F: PrimeField> Base64UrlChip<F> {
+    /// Creates a new [`Base64UrlChip`] from the range config.
+    pub fn new(range: RangeConfig<F>) -> Self {
+        Self { range }
+    }
+
+    /// Getter for [`RangeConfig`].
+    pub fn range(&self) -> &RangeConfig<F> {
+        &self.range
+    }
+
+    /// Assigns the characters of `encoded` as witnesses.
+    ///
+    /// They are constrained only by [`Base64UrlChip::decode`] or the chip consuming them, e.g., the SHA256 chip hashing the signing input of a JWT.
+    pub fn assign_encoded<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        encoded: &[u8],
+    ) -> Vec<AssignedValue<'v, F>> {
+        let gate = self.range.gate();
+        encoded
+            .iter()
+            .map(|c| gate.load_witness(ctx, Value::known(F::from(*c as u64))))
+            .collect()
+    }
+
+    /// Given the assigned characters of an unpadded base64url string, returns the assigned bytes of its decoding.
+    ///
+    /// The constraints are not satisfied if a character is not in [`BASE64URL_ALPHABET`] or the unused bits of the last character are not zero.
+    ///
+    /// # Panics
+    /// Panics if the length of `encoded` modulo 4 is 1, which is not the length of any base64url string.
+    pub fn decode<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        encoded: &[AssignedValue<'v, F>],
+    ) -> Result<Vec<AssignedValue<'v, F>>, Error> {
+        assert!(
+            base64url_decoded_len(encoded.len()).is_some(),
+            "{} characters are not the length of a base64url string",
+            encoded.len()
+        );
+        begin_cost_region(ctx, "base64url decode");
+        let sextets = encoded
+            .iter()
+            .map(|c| self.assign_sextet(ctx, c))
+            .collect::<Vec<AssignedValue<F>>>();
+        let mut decoded = vec![];
+        for group in sextets.chunks(4) {
+            decoded.append(&mut self.pack_sextets(ctx, group));
+        }
+        end_cost_region(ctx);
+        Ok(decoded)
+    }
+
+    /// Assigns the sextet encoded by the character `c` and constrains `c` to be its character in [`BASE64URL_ALPHABET`].
+    fn assign_sextet<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        c: &AssignedValue<'v, F>,
+    ) -> AssignedValue<'v, F> {
+        let gate = self.range.gate();
+        // A character out of the alphabet is assigned the sextet 0, whose character 'A' differs from it.
+        let sextet = c.value().map(|c| {
+            let sextet = fe_to_biguint(c)
+                .to_u8()
+                .and_then(base64url_sextet)
+                .unwrap_or(0);
+            F::from(sextet as u64)
+        });
+        let sextet = gate.load_witness(ctx, sextet);
+        self.range.range_check(ctx, &sextet, 6);
+        let is_less = |ctx: &mut Context<'v, F>, bound: u64| {
+            self.range.is_less_than(
+                ctx,
+                QuantumCell::Existing(&sextet),
+                QuantumCell::Constant(F::from(bound)),
+                6,
+            )
+        };
+        let is_upper = is_less(ctx, 26);
+        let is_letter = is_less(ctx, 52);
+        let is_alnum = is_less(ctx, 62);
+        let is_hyphen = gate.is_equal(
+            ctx,
+            QuantumCell::Existing(&sextet),
+            QuantumCell::Constant(F::from(62)),
+        );
+        // The character is `sextet + 65` for 'A'-'Z', `sextet + 71` for 'a'-'z', `sextet - 4` for '0'-'9', `sextet - 17` for '-', and `sextet + 32` for '_'.
+        // Since the sextet is less than 64, the last case is the one in which all the flags are zero, so the offset is a linear combination of the flags.
+        let expected = gate.inner_product(
+            ctx,
+            vec![
+                QuantumCell::Existing(&sextet),
+                QuantumCell::Existing(&is_upper),
+                QuantumCell::Existing(&is_letter),
+                QuantumCell::Existing(&is_alnum),
+                QuantumCell::Existing(&is_hyphen),
+                QuantumCell::Constant(F::one()),
+            ],
+            [1i64, -6, 75, -36, -49, 32]
+                .into_iter()
+                .map(|coeff| {
+                    let abs = F::from(coeff.unsigned_abs());
+                    QuantumCell::Constant(if coeff < 0 { -abs } else { abs })
+                })
+                .collect(),
+        );
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(&expected),
+            QuantumCell::Existing(c),
+        );
+        sextet
+    }
+
+    /// Packs a group of 2 to 4 sextets into the bytes of their big-endian bits, whose trailing bits are constrained to zero.
+    fn pack_sextets<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        sextets: &[AssignedValue<'v, F>],
+    ) -> Vec<AssignedValue<'v, F>> {
+        let gate = self.range.gate();
+        let num_bytes = sextets.len() - 1;
+        let unused_bits = 6 * sextets.len() - 8 * num_bytes;
+        let bits = sextets
+            .iter()
+            .fold(Value::known(0u64), |acc, sextet| {
+                acc.zip(sextet.value())
+                    .map(|(acc, sextet)| (acc << 6) + fe_to_biguint(sextet).to_u64().unwrap_or(0))
+            })
+            .map(|bits| bits >> unused_bits);
+        let bytes = (0..num_bytes)
+            .map(|i| {
+                let shift = 8 * (num_bytes - 1 - i);
+                let byte = gate.load_witness(ctx, bits.map(|bits| F::from((bits >> shift) & 0xff)));
+                self.range.range_check(ctx, &byte, 8);
+                byte
+            })
+            .collect::<Vec<AssignedValue<F>>>();
+        let packed_sextets = gate.inner_product(
+            ctx,
+            sextets.iter().map(QuantumCell::Existing).collect(),
+            (0..sextets.len())
+                .map(|i| QuantumCell::Constant(F::from(1u64 << (6 * (sextets.len() - 1 - i)))))
+                .collect(),
+        );
+        let packed_bytes = gate.inner_product(
+            ctx,
+            bytes.iter().map(QuantumCell::Existing).collect(),
+            (0..num_bytes)
+                .map(|i| {
+                    QuantumCell::Constant(F::from(1u64 << (unused_bits + 8 * (num_bytes - 1 - i))))
+                })
+                .collect(),
+        );
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(&packed_sextets),
+            QuantumCell::Existing(&packed_bytes),
+        );
+        bytes
+    }
+}
+
+#[cfg(all(test, feature = "sha256"))]
+mod test {
+    use super::*;
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem},
+    };
+    use halo2_base::{gates::range::RangeStrategy::Vertical, ContextParams, SKIP_FIRST_PASS};
+    use halo2_dynamic_sha256::Sha256DynamicConfig;
+    use sha2::{Digest, Sha256};
+    use std::marker::PhantomData;
+
+    #[derive(Clone, Debug)]
+    struct TestBase64UrlConfig<F: PrimeField> {
+        base64url_chip: Base64UrlChip<F>,
+        sha256_config: Sha256DynamicConfig<F>,
+    }
+
+    struct TestBase64UrlCircuit<F: PrimeField> {
+        encoded: Vec<u8>,
+        decoded: Vec<u8>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestBase64UrlCircuit<F> {
+        const K: usize = 15;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestBase64UrlCircuit<F> {
+        type Config = TestBase64UrlConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config =
+                RangeConfig::configure(meta, Vertical, &[8], &[1], 1, 12, 0, Self::K);
+            let sha256_config =
+                Sha256DynamicConfig::configure(meta, vec![64], range_config.clone(), 8, 8, true);
+            Self::Config {
+                base64url_chip: Base64UrlChip::new(range_config),
+                sha256_config,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let range = config.base64url_chip.range().clone();
+            config.sha256_config.load(&mut layouter)?;
+            range.load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "base64url decoding hashed with sha256",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = Context::new(
+                        region,
+                        ContextParams {
+                            max_rows: range.gate.max_rows,
+                            num_context_ids: 1,
+                            fixed_columns: range.gate.constants.clone(),
+                        },
+                    );
+                    let ctx = &mut aux;
+                    let gate = range.gate();
+                    let encoded = config.base64url_chip.assign_encoded(ctx, &self.encoded);
+                    let decoded = config.base64url_chip.decode(ctx, &encoded)?;
+                    assert_eq!(decoded.len(), self.decoded.len());
+                    for (byte, expected) in decoded.iter().zip(self.decoded.iter()) {
+                        gate.assert_is_const(ctx, byte, F::from(*expected as u64));
+                    }
+                    let result = config.sha256_config.digest(ctx, &self.decoded, None)?;
+                    for (input, byte) in result.input_bytes.iter().zip(decoded.iter()) {
+                        gate.assert_equal(
+                            ctx,
+                            QuantumCell::Existing(input),
+                            QuantumCell::Existing(byte),
+                        );
+                    }
+                    let digest = Sha256::digest(&self.decoded);
+                    for (byte, expected) in result.output_bytes.iter().zip(digest.iter()) {
+                        gate.assert_is_const(ctx, byte, F::from(*expected as u64));
+                    }
+                    range.finalize(ctx);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn run_decode(encoded: &[u8], decoded: &[u8]) -> bool {
+        let circuit = TestBase64UrlCircuit::<Fr> {
+            encoded: encoded.to_vec(),
+            decoded: decoded.to_vec(),
+            _f: PhantomData,
+        };
+        let prover = match MockProver::run(TestBase64UrlCircuit::<Fr>::K as u32, &circuit, vec![]) {
+            Ok(prover) => prover,
+            Err(e) => panic!("{:#?}", e),
+        };
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_base64url_decode_and_hash() {
+        assert!(run_decode(b"aGVsbG8gd29ybGQ", b"hello world"));
+        assert!(run_decode(b"aGVsbG8gd29ybA", b"hello worl"));
+        assert!(run_decode(b"aGVsbG8gd29y", b"hello wor"));
+        // The characters '-' and '_' encode the sextets 62 and 63.
+        assert!(run_decode(b"-_8A", &[0xfb, 0xff, 0x00]));
+    }
+
+    #[test]
+    fn test_base64url_decode_invalid() {
+        // '+' and '/' are the characters of base64, not base64url.
+        assert!(!run_decode(b"+_8A", &[0xfb, 0xff, 0x00]));
+        assert!(!run_decode(b"-/8A", &[0xfb, 0xff, 0x00]));
+        // 'R' leaves a non-zero unused bit in the last character.
+        assert!(!run_decode(b"aGVsbG8gd29ybGR", b"hello world"));
+        // The decoding differs from the given bytes.
+        assert!(!run_decode(b"aGVsbG8gd29ybGQ", b"hello worle"));
+    }
+
+    #[test]
+    fn test_base64url_native() {
+        for (i, c) in BASE64URL_ALPHABET.iter().enumerate() {
+            assert_eq!(base64url_sextet(*c), Some(i as u8));
+        }
+        assert_eq!(base64url_sextet(b'+'), None);
+        assert_eq!(base64url_sextet(b'='), None);
+        assert_eq!(base64url_decoded_len(15), Some(11));
+        assert_eq!(base64url_decoded_len(16), Some(12));
+        assert_eq!(base64url_decoded_len(14), Some(10));
+        assert_eq!(base64url_decoded_len(13), None);
+    }
+}
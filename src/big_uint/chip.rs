@@ -1,7 +1,12 @@
+use std::iter;
 use std::marker::PhantomData;
 
-use super::utils::{decompose_bigint, decompose_biguint};
-use crate::{AssignedBigUint, BigUintInstructions, Fresh, Muled, RangeType, RefreshAux};
+use super::utils::{big_mod_inverse, decompose_bigint, decompose_biguint};
+use super::window_digits;
+use crate::cost_report::{begin_cost_region, end_cost_region};
+use crate::{
+    AssignedBigUint, BigUintInstructions, Fresh, ModPowStrategy, Muled, RangeType, RefreshAux,
+};
 use halo2_base::halo2_proofs::{circuit::Region, circuit::Value, plonk::Error};
 use halo2_base::utils::fe_to_bigint;
 use halo2_base::ContextParams;
@@ -12,11 +17,12 @@ use halo2_base::{
     AssignedValue, Context,
 };
 use halo2_ecc::bigint::{
-    big_is_equal, big_is_zero, big_less_than, carry_mod, mul_no_carry, negative, select, sub,
-    CRTInteger, FixedCRTInteger, FixedOverflowInteger, OverflowInteger,
+    big_is_equal, big_is_zero, big_less_than, carry_mod, negative, select, sub, CRTInteger,
+    FixedCRTInteger, FixedOverflowInteger, OverflowInteger,
 };
 use num_bigint::{BigInt, BigUint, Sign};
 use num_traits::{One, Signed, Zero};
+use rayon::prelude::*;
 
 #[derive(Clone, Debug)]
 pub struct BigUintConfig<F: PrimeField> {
@@ -47,6 +53,7 @@ impl<F: PrimeField> BigUintInstructions<F> for BigUintConfig<F> {
         bit_len: usize,
     ) -> Result<AssignedBigUint<'v, F, Fresh>, Error> {
         assert_eq!(bit_len % self.limb_bits, 0);
+        begin_cost_region(ctx, "bigint assign");
         let num_limbs = bit_len / self.limb_bits;
         let gate = self.gate();
         let range = self.range();
@@ -62,6 +69,7 @@ impl<F: PrimeField> BigUintInstructions<F> for BigUintConfig<F> {
         for limb in assigned_limbs.iter() {
             range.range_check(ctx, &limb, self.limb_bits);
         }
+        end_cost_region(ctx);
         let int = OverflowInteger::construct(assigned_limbs, self.limb_bits);
         Ok(AssignedBigUint::new(int, value))
     }
@@ -103,6 +111,7 @@ impl<F: PrimeField> BigUintInstructions<F> for BigUintConfig<F> {
         assert_eq!(a.num_limbs(), num_limbs_l + num_limbs_r - 1);
         let num_limbs_fresh = increased_limbs_vec.len();
 
+        begin_cost_region(ctx, "bigint refresh");
         let gate = self.gate();
         let mut refreshed_limbs = Vec::with_capacity(num_limbs_fresh);
         let zero_assigned = gate.load_zero(ctx);
@@ -114,13 +123,15 @@ impl<F: PrimeField> BigUintInstructions<F> for BigUintConfig<F> {
             refreshed_limbs.push(zero_assigned.clone());
         }
         let limb_max = BigInt::from(1u64) << self.limb_bits;
+        let witnesses = self.refresh_witnesses(a_limbs, &increased_limbs_vec, &limb_max);
         for i in 0..num_limbs_fresh {
             // `i`-th overflowing limb value.
             let mut limb = refreshed_limbs[i].clone();
             for j in 0..(increased_limbs_vec[i] + 1) {
                 // `n` is lower `self.limb_width` bits of `limb`.
                 // `q` is any other upper bits.
-                let (q, n) = self.div_mod_unsafe(ctx, &limb, &limb_max);
+                let (q_val, n_val) = witnesses[i][j];
+                let (q, n) = self.assign_div_mod(ctx, &limb, &limb_max, q_val, n_val);
                 if j == 0 {
                     // When `j=0`, `n` is a new `i`-th limb value.
                     refreshed_limbs[i] = n;
@@ -142,6 +153,7 @@ impl<F: PrimeField> BigUintInstructions<F> for BigUintConfig<F> {
         for limb in refreshed_limbs.iter() {
             range.range_check(ctx, &limb, self.limb_bits);
         }
+        end_cost_region(ctx);
         let int = OverflowInteger::construct(refreshed_limbs, self.limb_bits);
         let new_assigned_int = AssignedBigUint::new(int, a.value());
         Ok(new_assigned_int)
@@ -282,6 +294,7 @@ impl<F: PrimeField> BigUintInstructions<F> for BigUintConfig<F> {
         a: &AssignedBigUint<'v, F, Fresh>,
         b: &AssignedBigUint<'v, F, Fresh>,
     ) -> Result<AssignedBigUint<'v, F, Muled>, Error> {
+        begin_cost_region(ctx, "bigint mul");
         let gate = self.gate();
         let n1 = a.num_limbs();
         let n2 = b.num_limbs();
@@ -290,7 +303,8 @@ impl<F: PrimeField> BigUintInstructions<F> for BigUintConfig<F> {
         let a = a.extend_limbs(num_limbs - n1, zero_value.clone());
         let b = b.extend_limbs(num_limbs - n2, zero_value.clone());
         let num_limbs_log2_ceil = (num_limbs as f32).log2().ceil() as usize;
-        let int = mul_no_carry::truncate(self.gate(), ctx, &a.int, &b.int, num_limbs_log2_ceil);
+        let int = self.mul_no_carry(ctx, &a.int, &b.int, num_limbs_log2_ceil);
+        end_cost_region(ctx);
         let value = a.value.zip(b.value).map(|(a, b)| a * b);
         Ok(AssignedBigUint::new(int, value))
     }
@@ -365,6 +379,7 @@ impl<F: PrimeField> BigUintInstructions<F> for BigUintConfig<F> {
         // The following constraints are designed with reference to AsymmetricMultiplierReducer template in https://github.com/jacksoom/circom-bigint/blob/master/circuits/mult.circom.
         // However, we do not regroup multiple limbs like the circom-bigint implementation because addition is not free, i.e., it makes constraints as well as multiplication, in the Plonk constraints system.
         // Besides, we use lookup tables to optimize range checks.
+        begin_cost_region(ctx, "bigint mul_mod");
         let limb_bits = self.limb_bits;
         let n1 = a.num_limbs();
         let n2 = b.num_limbs();
@@ -412,6 +427,7 @@ impl<F: PrimeField> BigUintInstructions<F> for BigUintConfig<F> {
         };
         let is_eq = self.is_equal_muled(ctx, &ab, &qn_prod, n1, n2)?;
         gate.assert_is_const(ctx, &is_eq, F::one());
+        end_cost_region(ctx);
         Ok(assign_prod)
     }
 
@@ -453,7 +469,7 @@ impl<F: PrimeField> BigUintInstructions<F> for BigUintConfig<F> {
         Ok(acc)
     }
 
-    /// Given a base `a`, a fixed exponent `e`, and a modulus `n`, performs the modular power `a^e mod n`.
+    /// Given a base `a`, a fixed exponent `e`, and a modulus `n`, performs the modular power `a^e mod n` with the method picked by [`ModPowStrategy::Auto`].
     fn pow_mod_fixed_exp<'v>(
         &self,
         ctx: &mut Context<'v, F>,
@@ -461,35 +477,7 @@ impl<F: PrimeField> BigUintInstructions<F> for BigUintConfig<F> {
         e: &BigUint,
         n: &AssignedBigUint<'v, F, Fresh>,
     ) -> Result<AssignedBigUint<'v, F, Fresh>, Error> {
-        let num_limbs = a.num_limbs();
-        assert_eq!(num_limbs, n.num_limbs());
-        let num_e_bits = Self::bits_size(&BigInt::from_biguint(Sign::Plus, e.clone()));
-        // Decompose `e` into bits.
-        let e_bits = e
-            .to_bytes_le()
-            .into_iter()
-            .flat_map(|v| {
-                (0..8)
-                    .map(|i: u8| (v >> i) & 1u8 == 1u8)
-                    .collect::<Vec<bool>>()
-            })
-            .collect::<Vec<bool>>();
-        let e_bits = e_bits[0..num_e_bits].to_vec();
-        let mut acc = self.assign_constant(ctx, BigUint::from(1usize))?;
-        let zero = self.gate().load_zero(ctx);
-        acc = acc.extend_limbs(num_limbs - acc.num_limbs(), zero);
-        let mut squared: AssignedBigUint<'v, F, Fresh> = a.clone();
-        for e_bit in e_bits.into_iter() {
-            let cur_sq = squared;
-            // Square `squared`.
-            squared = self.square_mod(ctx, &cur_sq, n)?;
-            if !e_bit {
-                continue;
-            }
-            // If `e_bit = 1`, update `acc` to `acc * cur_sq`.
-            acc = self.mul_mod(ctx, &acc, &cur_sq, n)?;
-        }
-        Ok(acc)
+        self.pow_mod_fixed_exp_with_strategy(ctx, a, e, n, ModPowStrategy::Auto)
     }
 
     /// Returns an assigned bit representing whether `a` is zero or not.
@@ -521,95 +509,10 @@ impl<F: PrimeField> BigUintInstructions<F> for BigUintConfig<F> {
         num_limbs_l: usize,
         num_limbs_r: usize,
     ) -> Result<AssignedValue<'v, F>, Error> {
-        // The following constraints are designed with reference to EqualWhenCarried template in https://github.com/jacksoom/circom-bigint/blob/master/circuits/mult.circom.
-        // We use lookup tables to optimize range checks.
-        let min_n = if num_limbs_r >= num_limbs_l {
-            num_limbs_l
-        } else {
-            num_limbs_r
-        };
-        // Each limb of `a` and `b` is less than `min_n * (1^(limb_bits) - 1)^2  + (1^(limb_bits) - 1)`.
-        let muled_limb_max = Self::compute_muled_limb_max(self.limb_bits, min_n);
-        let muled_limb_max_fe = bigint_to_fe::<F>(&muled_limb_max);
-        let num_limbs = num_limbs_l + num_limbs_r - 1;
-        let muled_limb_max_bits = Self::bits_size(&(&muled_limb_max * 2u32));
-        let carry_bits = muled_limb_max_bits - self.limb_bits;
         let gate = self.gate();
-        let range = self.range();
-
-        // The naive approach is to subtract the two integers limb by limb and:
-        //  a. Verify that they sum to zero along the way while
-        //  b. Propagating carries
-        // but this doesn't work because early sums might be negative.
-        // So instead we verify that `a - b + word_max = word_max`.
-        let limb_max = BigInt::from(1) << self.limb_bits;
-        let zero = gate.load_constant(ctx, F::zero());
-        let mut accumulated_extra = zero.clone();
-        let mut carry = Vec::with_capacity(num_limbs);
-        let mut cs = Vec::with_capacity(num_limbs);
-        carry.push(zero.clone());
-        let mut eq_bit = gate.load_constant(ctx, F::one());
-        let a_limbs = a.limbs();
-        let b_limbs = b.limbs();
-        for i in 0..num_limbs {
-            // `sum = a - b + word_max`
-            let a_b_sub = gate.sub(
-                ctx,
-                QuantumCell::Existing(&a_limbs[i]),
-                QuantumCell::Existing(&b_limbs[i]),
-            );
-            let sum = gate.sum(
-                ctx,
-                vec![
-                    QuantumCell::Existing(&a_b_sub),
-                    QuantumCell::Existing(&carry[i]),
-                    QuantumCell::Constant(muled_limb_max_fe),
-                ],
-            );
-            // `c` is lower `self.limb_width` bits of `sum`.
-            // `new_carry` is any other upper bits.
-            let (new_carry, c) = self.div_mod_unsafe(ctx, &sum, &limb_max);
-            carry.push(new_carry);
-            cs.push(c);
-
-            // `accumulated_extra` is the sum of `word_max`.
-            accumulated_extra = gate.add(
-                ctx,
-                QuantumCell::Existing(&accumulated_extra),
-                QuantumCell::Constant(muled_limb_max_fe),
-            );
-            let (q_acc, mod_acc) = self.div_mod_unsafe(ctx, &accumulated_extra, &limb_max);
-            // If and only if `a` is equal to `b`, lower `self.limb_width` bits of `sum` and `accumulated_extra` are the same.
-            let cs_acc_eq = gate.is_equal(
-                ctx,
-                QuantumCell::Existing(&cs[i]),
-                QuantumCell::Existing(&mod_acc),
-            );
-            eq_bit = gate.and(
-                ctx,
-                QuantumCell::Existing(&eq_bit),
-                QuantumCell::Existing(&cs_acc_eq),
-            );
-            accumulated_extra = q_acc;
-
-            if i < num_limbs - 1 {
-                // Assert that each carry fits in `carry_bits` bits.
-                range.range_check(ctx, &carry[i + 1], carry_bits);
-            } else {
-                // The final carry should match the `accumulated_extra`.
-                let final_carry_eq = gate.is_equal(
-                    ctx,
-                    QuantumCell::Existing(&carry[i + 1]),
-                    QuantumCell::Existing(&accumulated_extra),
-                );
-                eq_bit = gate.and(
-                    ctx,
-                    QuantumCell::Existing(&eq_bit),
-                    QuantumCell::Existing(&final_carry_eq),
-                );
-            }
-        }
-        Ok(eq_bit)
+        self.is_equal_muled_with(ctx, a, b, num_limbs_l, num_limbs_r, |ctx, x, y| {
+            gate.is_equal(ctx, QuantumCell::Existing(x), QuantumCell::Existing(y))
+        })
     }
 
     /// Returns an assigned bit representing whether `a` is less than `b` (`a<b`).
@@ -739,77 +642,705 @@ impl<F: PrimeField> BigUintConfig<F> {
         )
     }
 
-    /// Returns the fewest bits necessary to express the [`BigUint`].
-    fn bits_size(val: &BigInt) -> usize {
-        val.bits() as usize
+    /// Given a base `a`, a fixed exponent `e`, and a modulus `n`, performs the modular power `a^e mod n` with fewer constraints than [`BigUintInstructions::pow_mod_fixed_exp`].
+    ///
+    /// # Soundness
+    /// Every step relaxes two checks of [`BigUintInstructions::mul_mod`].
+    /// First, [`BigUintInstructions::mul_mod`] assigns the limbs of the modulus again from its value and range-checks them.
+    /// This function instead uses `n` itself as the modulus of all the steps, which skips those range checks because `n` is already [`Fresh`].
+    /// Second, [`BigUintInstructions::mul_mod`] computes the bit of [`BigUintInstructions::is_equal_muled`] for `x * y = q * n + r` and then asserts it, whose equality bits and carry divisions take most of the cells of a step.
+    /// Since no step needs the bit, the carries are instead constrained directly to their values for equal integers, which is equivalent to asserting the bit as argued for the private `assert_equal_muled_unchecked`.
+    /// The quotient and the remainder of every step are still range-checked, so the limbs of the [`Muled`] products do not overflow the field and `x * y = q * n + r` holds over the integers.
+    /// No step checks that its remainder is smaller than `n`, but each remainder is congruent to the exact intermediate value modulo `n`, so the final result is congruent to `a^e`.
+    /// Finally, the result is constrained to be smaller than `n`, which makes it equal to `a^e mod n`.
+    ///
+    /// The caller must ensure that `n` is the modulus intended to be used, e.g., the modulus of an assigned public key.
+    /// Unlike [`BigUintInstructions::pow_mod_fixed_exp`], the circuit is unsatisfiable if `a^e mod n` cannot be computed, e.g., if `n` is zero.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `a` - an assigned base integer.
+    /// * `e` - a fixed exponent.
+    /// * `n` - an assigned modulus.
+    ///
+    /// # Return values
+    /// Returns the assigned integer `a^e mod n`.
+    pub fn mod_pow_unchecked<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedBigUint<'v, F, Fresh>,
+        e: &BigUint,
+        n: &AssignedBigUint<'v, F, Fresh>,
+    ) -> Result<AssignedBigUint<'v, F, Fresh>, Error> {
+        let num_limbs = a.num_limbs();
+        assert_eq!(num_limbs, n.num_limbs());
+        let mut acc = self.assign_constant(ctx, BigUint::from(1usize))?;
+        let zero = self.gate().load_zero(ctx);
+        acc = acc.extend_limbs(num_limbs - acc.num_limbs(), zero);
+        let mut squared: AssignedBigUint<'v, F, Fresh> = a.clone();
+        for i in 0..(e.bits()) {
+            let cur_sq = squared;
+            squared = self.mul_mod_with_fresh_modulus(ctx, &cur_sq, &cur_sq, n)?;
+            if e.bit(i) {
+                acc = self.mul_mod_with_fresh_modulus(ctx, &acc, &cur_sq, n)?;
+            }
+        }
+        let is_reduced = self.is_less_than(ctx, &acc, n)?;
+        self.gate().assert_is_const(ctx, &is_reduced, F::one());
+        Ok(acc)
     }
 
-    fn num_limbs(&self, val: &BigInt) -> usize {
-        let bits = Self::bits_size(&val);
-        let num_limbs = if bits % self.limb_bits == 0 {
-            bits / self.limb_bits
-        } else {
-            bits / self.limb_bits + 1
-        };
-        num_limbs
+    /// Given a base `a`, a fixed exponent `e`, and a modulus `n`, performs the modular power `a^e mod n` by `strategy`.
+    ///
+    /// Every strategy constrains the result in the same way as [`BigUintInstructions::mul_mod`], so they differ only in the number of modular multiplications, which [`ModPowStrategy::num_mul_mods`] returns.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `a` - an assigned base integer smaller than `n`.
+    /// * `e` - a fixed exponent.
+    /// * `n` - an assigned modulus.
+    /// * `strategy` - the method to compute the power.
+    ///
+    /// # Return values
+    /// Returns the assigned integer `a^e mod n`.
+    pub fn pow_mod_fixed_exp_with_strategy<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedBigUint<'v, F, Fresh>,
+        e: &BigUint,
+        n: &AssignedBigUint<'v, F, Fresh>,
+        strategy: ModPowStrategy,
+    ) -> Result<AssignedBigUint<'v, F, Fresh>, Error> {
+        match strategy.resolve(e) {
+            ModPowStrategy::Windowed(window_bits) if !e.is_zero() => {
+                self.pow_mod_fixed_exp_windowed(ctx, a, e, n, window_bits)
+            }
+            _ => self.pow_mod_fixed_exp_square_and_multiply(ctx, a, e, n),
+        }
     }
 
-    // fn native_modulus_uint() -> BigUint {
-    //     modulus::<F>()
-    // }
-
-    // fn native_modulus_int() -> BigInt {
-    //     BigInt::from_biguint(Sign::Plus, modulus::<F>())
-    // }
-
-    // fn compute_max_mul(&self, num_limbs_l: usize, num_limbs_r: usize) -> BigInt {
-    //     // let one = BigInt::from(1u64);
-    //     // let l_max = &(BigInt::from(1u64) << (self.limb_bits * num_limbs_l)) - &one;
-    //     // let r_max = &(BigInt::from(1u64) << (self.limb_bits * num_limbs_r)) - &one;
-    //     // l_max * r_max + one
-    //     BigInt::from(1u64) << (self.limb_bits * (num_limbs_l + num_limbs_r))
-    // }
-
-    /// Returns the maximum limb size of [`Muled`] type integers.
-    fn compute_muled_limb_max(limb_width: usize, min_n: usize) -> BigInt {
-        let one = BigInt::from(1usize);
-        let out_base = BigInt::from(1usize) << limb_width;
-        BigInt::from(min_n) * (&out_base - &one) * (&out_base - &one) + (&out_base - &one)
+    fn pow_mod_fixed_exp_square_and_multiply<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedBigUint<'v, F, Fresh>,
+        e: &BigUint,
+        n: &AssignedBigUint<'v, F, Fresh>,
+    ) -> Result<AssignedBigUint<'v, F, Fresh>, Error> {
+        let num_limbs = a.num_limbs();
+        assert_eq!(num_limbs, n.num_limbs());
+        let num_e_bits = Self::bits_size(&BigInt::from_biguint(Sign::Plus, e.clone()));
+        // Decompose `e` into bits.
+        let e_bits = e
+            .to_bytes_le()
+            .into_iter()
+            .flat_map(|v| {
+                (0..8)
+                    .map(|i: u8| (v >> i) & 1u8 == 1u8)
+                    .collect::<Vec<bool>>()
+            })
+            .collect::<Vec<bool>>();
+        let e_bits = e_bits[0..num_e_bits].to_vec();
+        let mut acc = self.assign_constant(ctx, BigUint::from(1usize))?;
+        let zero = self.gate().load_zero(ctx);
+        acc = acc.extend_limbs(num_limbs - acc.num_limbs(), zero);
+        let mut squared: AssignedBigUint<'v, F, Fresh> = a.clone();
+        for e_bit in e_bits.into_iter() {
+            let cur_sq = squared;
+            // Square `squared`.
+            squared = self.square_mod(ctx, &cur_sq, n)?;
+            if !e_bit {
+                continue;
+            }
+            // If `e_bit = 1`, update `acc` to `acc * cur_sq`.
+            acc = self.mul_mod(ctx, &acc, &cur_sq, n)?;
+        }
+        Ok(acc)
     }
 
-    /// Given a integer `a` and a divisor `n`, performs `a/n` and `a mod n`.
-    /// # Panics
-    /// Panics if `n=0`.
-    fn div_mod_unsafe<'v>(
+    fn pow_mod_fixed_exp_windowed<'v>(
         &self,
         ctx: &mut Context<'v, F>,
-        a: &AssignedValue<'v, F>,
-        b: &BigInt,
-    ) -> (AssignedValue<'v, F>, AssignedValue<'v, F>) {
-        let gate = self.gate();
-        let (q_val, n_val) = a
-            .value
-            .map(|a| {
-                let a = fe_to_bigint(&a);
-                let (q_val, n_val) = (&a / b, &a % b);
-                (bigint_to_fe(&q_val), bigint_to_fe(&n_val))
-            })
-            .unzip();
-        let q = gate.load_witness(ctx, q_val);
-        let n = gate.load_witness(ctx, n_val);
-        let prod = gate.mul(
-            ctx,
-            QuantumCell::Existing(&q),
-            QuantumCell::Constant(bigint_to_fe(b)),
-        );
-        let a_prod_sub = gate.sub(ctx, QuantumCell::Existing(&a), QuantumCell::Existing(&prod));
-        let is_eq = gate.is_equal(
-            ctx,
-            QuantumCell::Existing(&n),
-            QuantumCell::Existing(&a_prod_sub),
-        );
-        gate.assert_is_const(ctx, &is_eq, F::one());
-        (q, n)
+        a: &AssignedBigUint<'v, F, Fresh>,
+        e: &BigUint,
+        n: &AssignedBigUint<'v, F, Fresh>,
+        window_bits: usize,
+    ) -> Result<AssignedBigUint<'v, F, Fresh>, Error> {
+        assert_eq!(a.num_limbs(), n.num_limbs());
+        let digits = window_digits(e, window_bits);
+        let max_digit = digits.iter().copied().max().unwrap_or(0);
+        // The `i`-th entry of `table` is `a^(i+1)`, computed only up to the largest window.
+        let mut table = vec![a.clone()];
+        while table.len() < max_digit {
+            let next = self.mul_mod(ctx, &table[table.len() - 1], a, n)?;
+            table.push(next);
+        }
+        // The first window is nonzero because it contains the most significant bit of `e`.
+        let mut acc = table[digits[0] - 1].clone();
+        for digit in digits.into_iter().skip(1) {
+            for _ in 0..window_bits {
+                acc = self.square_mod(ctx, &acc, n)?;
+            }
+            if digit != 0 {
+                acc = self.mul_mod(ctx, &acc, &table[digit - 1], n)?;
+            }
+        }
+        Ok(acc)
+    }
+
+    /// Given an integer `a` and a modulus `n`, asserts that they are coprime, i.e., `gcd(a, n) = 1`.
+    ///
+    /// # Soundness
+    /// The Bézout coefficient `u` with `a * u ≡ 1 (mod n)`, i.e., the modular inverse of `a`, is assigned as a witness.
+    /// Then `a * u = q * n + 1` is constrained over the integers in the same way as [`BigUintConfig::mod_pow_unchecked`], which uses `n` itself as the modulus.
+    /// Any common divisor of `a` and `n` divides `a * u - q * n = 1`, so no witness satisfies the circuit if they are not coprime.
+    /// Neither `a` nor `u` needs to be smaller than `n` for this.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `a` - an assigned integer with the same number of limbs as `n`, e.g., a signature.
+    /// * `n` - an assigned modulus greater than one, e.g., the modulus of a public key.
+    pub fn assert_coprime<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedBigUint<'v, F, Fresh>,
+        n: &AssignedBigUint<'v, F, Fresh>,
+    ) -> Result<(), Error> {
+        let num_limbs = n.num_limbs();
+        // If `a` and `n` are not coprime, the zero witness makes the circuit unsatisfiable as any other witness does.
+        let u_big = a
+            .value()
+            .zip(n.value())
+            .map(|(a, n)| big_mod_inverse(&a, &n).unwrap_or_default());
+        let u = self.assign_integer(ctx, u_big, num_limbs * self.limb_bits)?;
+        let remainder = self.mul_mod_with_fresh_modulus(ctx, a, &u, n)?;
+        let one = self.assign_constant(ctx, BigUint::one())?;
+        let zero = self.gate().load_zero(ctx);
+        let one = one.extend_limbs(num_limbs - one.num_limbs(), zero);
+        self.assert_equal_fresh(ctx, &remainder, &one)
+    }
+
+    /// Re-decomposes an integer `a` into limbs of `new_limb_bits` bits, e.g., to pass it to a chip of another crate with a different limb width.
+    ///
+    /// # Soundness
+    /// The limbs of `a` are split into chunks of `gcd(limb_bits, new_limb_bits)` bits, which are range-checked and constrained to recompose the limbs.
+    /// The new limbs are the inner products of the consecutive chunks with their powers of two, so they represent the same integer as `a` and each of them is smaller than `2^new_limb_bits`.
+    /// The chunks are skipped where they are the limbs themselves, i.e., the limbs of `a` are not split if `limb_bits` divides `new_limb_bits`.
+    ///
+    /// The returned integer is [`Fresh`] for a chip whose limb width is `new_limb_bits`, so it must not be given to this chip unless the widths are equal.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `a` - an assigned integer whose limbs have the width of this chip.
+    /// * `new_limb_bits` - the width of the new limbs, which must be less than the capacity of the field.
+    ///
+    /// # Return values
+    /// Returns the assigned integer equal to `a` with `ceil(num_limbs * limb_bits / new_limb_bits)` limbs, where `num_limbs` is the number of the limbs of `a`.
+    pub fn recompose_limbs<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedBigUint<'v, F, Fresh>,
+        new_limb_bits: usize,
+    ) -> Result<AssignedBigUint<'v, F, Fresh>, Error> {
+        assert!(
+            new_limb_bits > 0 && new_limb_bits < F::CAPACITY as usize,
+            "the new limb width must be in [1, {})",
+            F::CAPACITY
+        );
+        let limb_bits = self.limb_bits;
+        if new_limb_bits == limb_bits {
+            return Ok(a.clone());
+        }
+        let chunk_bits = {
+            let (mut x, mut y) = (limb_bits, new_limb_bits);
+            while y != 0 {
+                (x, y) = (y, x % y);
+            }
+            x
+        };
+        let gate = self.gate();
+        let range = self.range();
+        let bases = |num_chunks: usize| {
+            (0..num_chunks)
+                .map(|i| {
+                    QuantumCell::Constant(biguint_to_fe(&(BigUint::one() << (chunk_bits * i))))
+                })
+                .collect::<Vec<QuantumCell<F>>>()
+        };
+
+        let chunks = if chunk_bits == limb_bits {
+            a.limbs().to_vec()
+        } else {
+            let chunks_per_limb = limb_bits / chunk_bits;
+            let limb_bases = bases(chunks_per_limb);
+            let mut chunks = Vec::with_capacity(a.num_limbs() * chunks_per_limb);
+            for limb in a.limbs() {
+                let limb_chunks = limb
+                    .value()
+                    .map(|v| decompose_biguint::<F>(&fe_to_biguint(v), chunks_per_limb, chunk_bits))
+                    .transpose_vec(chunks_per_limb)
+                    .into_iter()
+                    .map(|chunk| {
+                        let chunk = gate.load_witness(ctx, chunk);
+                        range.range_check(ctx, &chunk, chunk_bits);
+                        chunk
+                    })
+                    .collect::<Vec<AssignedValue<'v, F>>>();
+                let composed = gate.inner_product(
+                    ctx,
+                    limb_chunks.iter().map(QuantumCell::Existing),
+                    limb_bases.clone(),
+                );
+                gate.assert_equal(
+                    ctx,
+                    QuantumCell::Existing(limb),
+                    QuantumCell::Existing(&composed),
+                );
+                chunks.extend(limb_chunks);
+            }
+            chunks
+        };
+
+        let chunks_per_new_limb = new_limb_bits / chunk_bits;
+        let new_limbs = if chunks_per_new_limb == 1 {
+            chunks
+        } else {
+            chunks
+                .chunks(chunks_per_new_limb)
+                .map(|group| {
+                    gate.inner_product(
+                        ctx,
+                        group.iter().map(QuantumCell::Existing),
+                        bases(group.len()),
+                    )
+                })
+                .collect()
+        };
+        let int = OverflowInteger::construct(new_limbs, new_limb_bits);
+        Ok(AssignedBigUint::new(int, a.value()))
+    }
+
+    /// Computes [`BigUintInstructions::is_equal_muled`] with `limb_is_equal` in place of [`GateInstructions::is_equal`] for the lower bits and the final carry, e.g., to test it with forged hints of the equality bits.
+    fn is_equal_muled_with<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedBigUint<'v, F, Muled>,
+        b: &AssignedBigUint<'v, F, Muled>,
+        num_limbs_l: usize,
+        num_limbs_r: usize,
+        mut limb_is_equal: impl FnMut(
+            &mut Context<'v, F>,
+            &AssignedValue<'v, F>,
+            &AssignedValue<'v, F>,
+        ) -> AssignedValue<'v, F>,
+    ) -> Result<AssignedValue<'v, F>, Error> {
+        // The following constraints are designed with reference to EqualWhenCarried template in https://github.com/jacksoom/circom-bigint/blob/master/circuits/mult.circom.
+        // We use lookup tables to optimize range checks.
+        begin_cost_region(ctx, "bigint carry check");
+        let min_n = if num_limbs_r >= num_limbs_l {
+            num_limbs_l
+        } else {
+            num_limbs_r
+        };
+        // Each limb of `a` and `b` is less than `min_n * (1^(limb_bits) - 1)^2  + (1^(limb_bits) - 1)`.
+        let muled_limb_max = Self::compute_muled_limb_max(self.limb_bits, min_n);
+        let muled_limb_max_fe = bigint_to_fe::<F>(&muled_limb_max);
+        let num_limbs = num_limbs_l + num_limbs_r - 1;
+        let muled_limb_max_bits = Self::bits_size(&(&muled_limb_max * 2u32));
+        let carry_bits = muled_limb_max_bits - self.limb_bits;
+        let gate = self.gate();
+        let range = self.range();
+
+        // The naive approach is to subtract the two integers limb by limb and:
+        //  a. Verify that they sum to zero along the way while
+        //  b. Propagating carries
+        // but this doesn't work because early sums might be negative.
+        // So instead we verify that `a - b + word_max = word_max`.
+        let limb_max = BigInt::from(1) << self.limb_bits;
+        let zero = gate.load_constant(ctx, F::zero());
+        let mut accumulated_extra = zero.clone();
+        let mut carry = Vec::with_capacity(num_limbs);
+        let mut cs = Vec::with_capacity(num_limbs);
+        carry.push(zero.clone());
+        let mut eq_bit = gate.load_constant(ctx, F::one());
+        let a_limbs = a.limbs();
+        let b_limbs = b.limbs();
+        for i in 0..num_limbs {
+            // `sum = a - b + word_max`
+            let a_b_sub = gate.sub(
+                ctx,
+                QuantumCell::Existing(&a_limbs[i]),
+                QuantumCell::Existing(&b_limbs[i]),
+            );
+            let sum = gate.sum(
+                ctx,
+                vec![
+                    QuantumCell::Existing(&a_b_sub),
+                    QuantumCell::Existing(&carry[i]),
+                    QuantumCell::Constant(muled_limb_max_fe),
+                ],
+            );
+            // `c` is lower `self.limb_width` bits of `sum`.
+            // `new_carry` is any other upper bits.
+            let (new_carry, c) = self.div_mod_unsafe(ctx, &sum, &limb_max);
+            carry.push(new_carry);
+            cs.push(c);
+
+            // `accumulated_extra` is the sum of `word_max`.
+            accumulated_extra = gate.add(
+                ctx,
+                QuantumCell::Existing(&accumulated_extra),
+                QuantumCell::Constant(muled_limb_max_fe),
+            );
+            let (q_acc, mod_acc) = self.div_mod_unsafe(ctx, &accumulated_extra, &limb_max);
+            // If and only if `a` is equal to `b`, lower `self.limb_width` bits of `sum` and `accumulated_extra` are the same.
+            let cs_acc_eq = limb_is_equal(ctx, &cs[i], &mod_acc);
+            eq_bit = gate.and(
+                ctx,
+                QuantumCell::Existing(&eq_bit),
+                QuantumCell::Existing(&cs_acc_eq),
+            );
+            accumulated_extra = q_acc;
+
+            if i < num_limbs - 1 {
+                // Assert that each carry fits in `carry_bits` bits.
+                range.range_check(ctx, &carry[i + 1], carry_bits);
+            } else {
+                // The final carry should match the `accumulated_extra`.
+                let final_carry_eq = limb_is_equal(ctx, &carry[i + 1], &accumulated_extra);
+                eq_bit = gate.and(
+                    ctx,
+                    QuantumCell::Existing(&eq_bit),
+                    QuantumCell::Existing(&final_carry_eq),
+                );
+            }
+        }
+        end_cost_region(ctx);
+        Ok(eq_bit)
+    }
+
+    /// Performs the modular multiplication `a * b mod n` in the same way as [`BigUintInstructions::mul_mod`] except that `n` is used without being assigned again.
+    fn mul_mod_with_fresh_modulus<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedBigUint<'v, F, Fresh>,
+        b: &AssignedBigUint<'v, F, Fresh>,
+        n: &AssignedBigUint<'v, F, Fresh>,
+    ) -> Result<AssignedBigUint<'v, F, Fresh>, Error> {
+        let limb_bits = self.limb_bits;
+        let n1 = a.num_limbs();
+        let n2 = b.num_limbs();
+        assert_eq!(n1, n.num_limbs());
+        let full_prod_big = a.value() * b.value();
+        let (q_big, prod_big) = full_prod_big
+            .zip(n.value())
+            .map(|(full_prod, n)| (&full_prod / &n, &full_prod % &n))
+            .unzip();
+        let assign_q = self.assign_integer(ctx, q_big, n2 * limb_bits)?;
+        let assign_prod = self.assign_integer(ctx, prod_big, n1 * limb_bits)?;
+        let ab = self.mul(ctx, a, b)?;
+        let qn = self.mul(ctx, &assign_q, n)?;
+        let gate = self.gate();
+        let qn_prod = {
+            let value = qn
+                .value
+                .as_ref()
+                .zip(assign_prod.value.as_ref())
+                .map(|(a, b)| a + b);
+            let mut limbs = Vec::with_capacity(n1 + n2 - 1);
+            let qn_limbs = qn.limbs();
+            let prod_limbs = assign_prod.limbs();
+            for i in 0..(n1 + n2 - 1) {
+                if i < n1 {
+                    limbs.push(gate.add(
+                        ctx,
+                        QuantumCell::Existing(&qn_limbs[i]),
+                        QuantumCell::Existing(&prod_limbs[i]),
+                    ));
+                } else {
+                    limbs.push(qn_limbs[i].clone());
+                }
+            }
+            let int = OverflowInteger::construct(limbs, self.limb_bits);
+            AssignedBigUint::<F, Muled>::new(int, value)
+        };
+        self.assert_equal_muled_unchecked(ctx, &ab, &qn_prod, n1, n2)?;
+        Ok(assign_prod)
+    }
+
+    /// Asserts that `a` and `b` are equivalent in the same way as [`BigUintInstructions::assert_equal_muled`] but with fewer constraints.
+    ///
+    /// # Soundness
+    /// [`BigUintInstructions::is_equal_muled`] adds `a - b + word_max` limb by limb with carries, and its bit is one iff the lower `limb_bits` bits of every sum are those of the accumulated `word_max` and the final carry is the rest of it.
+    /// The accumulated `word_max` depends only on the limb width and the numbers of limbs, so this function computes its lower bits and its final rest outside the circuit.
+    /// Asserting the bit to be one is then equivalent to constraining every sum to be `carry * 2^limb_bits + low` with the constant lower bits `low` and the final carry to be the constant rest, which this function does directly.
+    /// The carries are range-checked to the same bits as in [`BigUintInstructions::is_equal_muled`], so the sums do not wrap around the field, and the constraints hold iff `a = b` over the integers.
+    /// It skips the equality bit of every limb, the witness of the lower bits, and the division of the accumulated `word_max` in the circuit, but it cannot return a bit for unequal integers.
+    fn assert_equal_muled_unchecked<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedBigUint<'v, F, Muled>,
+        b: &AssignedBigUint<'v, F, Muled>,
+        num_limbs_l: usize,
+        num_limbs_r: usize,
+    ) -> Result<(), Error> {
+        begin_cost_region(ctx, "bigint carry check");
+        let min_n = num_limbs_l.min(num_limbs_r);
+        let muled_limb_max = Self::compute_muled_limb_max(self.limb_bits, min_n);
+        let muled_limb_max_fe = bigint_to_fe::<F>(&muled_limb_max);
+        let num_limbs = num_limbs_l + num_limbs_r - 1;
+        let carry_bits = Self::bits_size(&(&muled_limb_max * 2u32)) - self.limb_bits;
+        let limb_max = BigInt::from(1) << self.limb_bits;
+        let limb_max_fe = bigint_to_fe::<F>(&limb_max);
+        let gate = self.gate();
+        let range = self.range();
+        let a_limbs = a.limbs();
+        let b_limbs = b.limbs();
+        let mut accumulated_extra = BigInt::zero();
+        let mut carry = gate.load_zero(ctx);
+        for i in 0..num_limbs {
+            // `sum = a - b + word_max`
+            let a_b_sub = gate.sub(
+                ctx,
+                QuantumCell::Existing(&a_limbs[i]),
+                QuantumCell::Existing(&b_limbs[i]),
+            );
+            let sum = gate.sum(
+                ctx,
+                vec![
+                    QuantumCell::Existing(&a_b_sub),
+                    QuantumCell::Existing(&carry),
+                    QuantumCell::Constant(muled_limb_max_fe),
+                ],
+            );
+            accumulated_extra += &muled_limb_max;
+            let low = &accumulated_extra % &limb_max;
+            accumulated_extra /= &limb_max;
+            // `new_carry` is the upper bits of `sum`, whose lower `self.limb_width` bits must be `low`.
+            let new_carry = gate.load_witness(
+                ctx,
+                sum.value
+                    .map(|sum| bigint_to_fe::<F>(&((fe_to_bigint(&sum) - &low) / &limb_max))),
+            );
+            let carried = gate.mul_add(
+                ctx,
+                QuantumCell::Existing(&new_carry),
+                QuantumCell::Constant(limb_max_fe),
+                QuantumCell::Constant(bigint_to_fe(&low)),
+            );
+            gate.assert_equal(
+                ctx,
+                QuantumCell::Existing(&sum),
+                QuantumCell::Existing(&carried),
+            );
+            if i < num_limbs - 1 {
+                // Assert that each carry fits in `carry_bits` bits.
+                range.range_check(ctx, &new_carry, carry_bits);
+            } else {
+                // The final carry should match the `accumulated_extra`.
+                gate.assert_is_const(ctx, &new_carry, bigint_to_fe(&accumulated_extra));
+            }
+            carry = new_carry;
+        }
+        end_cost_region(ctx);
+        Ok(())
+    }
+
+    /// Returns the fewest bits necessary to express the [`BigUint`].
+    fn bits_size(val: &BigInt) -> usize {
+        val.bits() as usize
+    }
+
+    fn num_limbs(&self, val: &BigInt) -> usize {
+        let bits = Self::bits_size(&val);
+        let num_limbs = if bits % self.limb_bits == 0 {
+            bits / self.limb_bits
+        } else {
+            bits / self.limb_bits + 1
+        };
+        num_limbs
+    }
+
+    // fn native_modulus_uint() -> BigUint {
+    //     modulus::<F>()
+    // }
+
+    // fn native_modulus_int() -> BigInt {
+    //     BigInt::from_biguint(Sign::Plus, modulus::<F>())
+    // }
+
+    // fn compute_max_mul(&self, num_limbs_l: usize, num_limbs_r: usize) -> BigInt {
+    //     // let one = BigInt::from(1u64);
+    //     // let l_max = &(BigInt::from(1u64) << (self.limb_bits * num_limbs_l)) - &one;
+    //     // let r_max = &(BigInt::from(1u64) << (self.limb_bits * num_limbs_r)) - &one;
+    //     // l_max * r_max + one
+    //     BigInt::from(1u64) << (self.limb_bits * (num_limbs_l + num_limbs_r))
+    // }
+
+    /// Returns the maximum limb size of [`Muled`] type integers.
+    fn compute_muled_limb_max(limb_width: usize, min_n: usize) -> BigInt {
+        let one = BigInt::from(1usize);
+        let out_base = BigInt::from(1usize) << limb_width;
+        BigInt::from(min_n) * (&out_base - &one) * (&out_base - &one) + (&out_base - &one)
+    }
+
+    /// Given a integer `a` and a divisor `n`, performs `a/n` and `a mod n`.
+    /// # Panics
+    /// Panics if `n=0`.
+    fn div_mod_unsafe<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedValue<'v, F>,
+        b: &BigInt,
+    ) -> (AssignedValue<'v, F>, AssignedValue<'v, F>) {
+        let (q_val, n_val) = a
+            .value
+            .map(|a| {
+                let a = fe_to_bigint(&a);
+                let (q_val, n_val) = (&a / b, &a % b);
+                (bigint_to_fe(&q_val), bigint_to_fe(&n_val))
+            })
+            .unzip();
+        self.assign_div_mod(ctx, a, b, q_val, n_val)
+    }
+
+    /// Assigns the quotient `q_val` and the remainder `n_val` of `a` divided by `b` computed in advance, and constrains `a = q * b + n`.
+    fn assign_div_mod<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedValue<'v, F>,
+        b: &BigInt,
+        q_val: Value<F>,
+        n_val: Value<F>,
+    ) -> (AssignedValue<'v, F>, AssignedValue<'v, F>) {
+        let gate = self.gate();
+        let q = gate.load_witness(ctx, q_val);
+        let n = gate.load_witness(ctx, n_val);
+        let prod = gate.mul(
+            ctx,
+            QuantumCell::Existing(&q),
+            QuantumCell::Constant(bigint_to_fe(b)),
+        );
+        let a_prod_sub = gate.sub(ctx, QuantumCell::Existing(&a), QuantumCell::Existing(&prod));
+        let is_eq = gate.is_equal(
+            ctx,
+            QuantumCell::Existing(&n),
+            QuantumCell::Existing(&a_prod_sub),
+        );
+        gate.assert_is_const(ctx, &is_eq, F::one());
+        (q, n)
+    }
+
+    /// Returns the quotients and the remainders assigned by [`BigUintInstructions::refresh`] when it decomposes the `i`-th limb of `a_limbs` and the carries into it for the `j`-th time, indexed by `[i][j]`.
+    ///
+    /// Only the carries are propagated sequentially on the integers, and the limbs are converted from and to the field elements in parallel, so that the witnesses are not computed one by one while assigning them.
+    fn refresh_witnesses(
+        &self,
+        a_limbs: &[AssignedValue<F>],
+        increased_limbs_vec: &[usize],
+        limb_max: &BigInt,
+    ) -> Vec<Vec<(Value<F>, Value<F>)>> {
+        let num_limbs_fresh = increased_limbs_vec.len();
+        let values = a_limbs
+            .iter()
+            .map(|limb| limb.value)
+            .collect::<Value<Vec<F>>>();
+        let witnesses = values.map(|values| {
+            let mut limbs = values.par_iter().map(fe_to_bigint).collect::<Vec<BigInt>>();
+            limbs.resize(num_limbs_fresh, BigInt::zero());
+            let mut quotients_and_remainders = Vec::with_capacity(num_limbs_fresh);
+            for i in 0..num_limbs_fresh {
+                let mut limb = limbs[i].clone();
+                let mut divided = Vec::with_capacity(increased_limbs_vec[i] + 1);
+                for j in 0..(increased_limbs_vec[i] + 1) {
+                    let (q, n) = (&limb / limb_max, &limb % limb_max);
+                    if j > 0 {
+                        limbs[i + j] += &n;
+                    }
+                    divided.push((q.clone(), n));
+                    limb = q;
+                }
+                quotients_and_remainders.push(divided);
+            }
+            quotients_and_remainders
+                .into_par_iter()
+                .map(|divided| {
+                    divided
+                        .iter()
+                        .map(|(q, n)| (bigint_to_fe::<F>(q), bigint_to_fe::<F>(n)))
+                        .collect::<Vec<(F, F)>>()
+                })
+                .collect::<Vec<Vec<(F, F)>>>()
+        });
+        (0..num_limbs_fresh)
+            .map(|i| {
+                (0..(increased_limbs_vec[i] + 1))
+                    .map(|j| witnesses.as_ref().map(|w| w[i][j]).unzip())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the limbs of the product of `a` and `b` without carries, which are assigned in the same cells as [`halo2_ecc::bigint::mul_no_carry::truncate`] assigns.
+    ///
+    /// The `i`-th limb is the inner product of the first `i+1` limbs of `a` and those of `b` in the reverse order, whose partial sums are computed for all the limbs in parallel before they are assigned.
+    fn mul_no_carry<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &OverflowInteger<'v, F>,
+        b: &OverflowInteger<'v, F>,
+        num_limbs_log2_ceil: usize,
+    ) -> OverflowInteger<'v, F> {
+        let num_limbs = a.limbs.len();
+        assert!(num_limbs > 0);
+        assert_eq!(num_limbs, b.limbs.len());
+        let a_values = a
+            .limbs
+            .iter()
+            .map(|limb| limb.value)
+            .collect::<Vec<Value<F>>>();
+        let b_values = b
+            .limbs
+            .iter()
+            .map(|limb| limb.value)
+            .collect::<Vec<Value<F>>>();
+        let partial_sums = (0..num_limbs)
+            .into_par_iter()
+            .map(|i| {
+                let mut sum = Value::known(F::zero());
+                (0..=i)
+                    .map(|j| {
+                        sum = sum + a_values[j] * b_values[i - j];
+                        sum
+                    })
+                    .collect::<Vec<Value<F>>>()
+            })
+            .collect::<Vec<Vec<Value<F>>>>();
+        let gate = self.gate();
+        let limbs = partial_sums
+            .into_iter()
+            .enumerate()
+            .map(|(i, sums)| {
+                // The cells of [`GateInstructions::inner_product`], i.e., `0, a_0, b_i, s_0, a_1, b_{i-1}, s_1, ...` with the partial sums `s_j`.
+                let cells = iter::once(QuantumCell::Constant(F::zero()))
+                    .chain(sums.into_iter().enumerate().flat_map(|(j, sum)| {
+                        [
+                            QuantumCell::Existing(&a.limbs[j]),
+                            QuantumCell::Existing(&b.limbs[i - j]),
+                            QuantumCell::Witness(sum),
+                        ]
+                    }))
+                    .collect::<Vec<QuantumCell<F>>>();
+                let gate_offsets = (0..=i)
+                    .map(|j| (3 * j as isize, None))
+                    .collect::<Vec<(isize, Option<[F; 3]>)>>();
+                let assigned = gate.assign_region(ctx, cells, gate_offsets);
+                assigned.last().unwrap().clone()
+            })
+            .collect();
+        OverflowInteger::construct(
+            limbs,
+            num_limbs_log2_ceil + a.max_limb_bits + b.max_limb_bits,
+        )
     }
 
     // fn carry_mod<'v>(
@@ -838,7 +1369,7 @@ mod test {
     use std::str::FromStr;
 
     use super::*;
-    use crate::big_pow_mod;
+    use crate::{big_pow_mod, MAX_MOD_POW_WINDOW_BITS};
     use halo2_base::halo2_proofs::{
         circuit::{Layouter, SimpleFloorPlanner},
         dev::MockProver,
@@ -1345,7 +1876,106 @@ mod test {
             config.range().load_lookup_table(&mut layouter)?;
             let mut first_pass = SKIP_FIRST_PASS;
             layouter.assign_region(
-                || "random refresh test",
+                || "random refresh test",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    let a_assigned =
+                        config.assign_integer(ctx, Value::known(self.a.clone()), Self::BITS_LEN)?;
+                    let b_assigned =
+                        config.assign_integer(ctx, Value::known(self.b.clone()), Self::BITS_LEN)?;
+                    let ab = config.mul(ctx, &a_assigned, &b_assigned)?;
+                    let zero = config.assign_constant(ctx, BigUint::zero())?;
+                    let num_limbs = Self::BITS_LEN / Self::LIMB_WIDTH;
+                    let zero_value = config.gate().load_zero(ctx);
+                    let zero = zero
+                        .extend_limbs(ab.num_limbs() - zero.num_limbs(), zero_value)
+                        .to_muled();
+                    config.assert_equal_muled(ctx, &ab, &zero, num_limbs, num_limbs)?;
+                    config.range().finalize(ctx);
+                    {
+                        println!("total advice cells: {}", ctx.total_advice);
+                        let const_rows = ctx.total_fixed + 1;
+                        println!("maximum rows used by a fixed column: {const_rows}");
+                        println!("lookup cells used: {}", ctx.cells_to_lookup.len());
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    );
+
+    impl_bigint_test_circuit!(
+        TestRefreshCircuit,
+        test_refresh_circuit,
+        64,
+        2048,
+        13,
+        false,
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "random refresh test",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    let a_assigned =
+                        config.assign_integer(ctx, Value::known(self.a.clone()), Self::BITS_LEN)?;
+                    let b_assigned =
+                        config.assign_integer(ctx, Value::known(self.b.clone()), Self::BITS_LEN)?;
+                    let ab = config.mul(ctx, &a_assigned, &b_assigned)?;
+                    let ba = config.mul(ctx, &b_assigned, &a_assigned)?;
+                    let num_limbs = Self::BITS_LEN / Self::LIMB_WIDTH;
+                    let aux = RefreshAux::new(Self::LIMB_WIDTH, num_limbs, num_limbs);
+                    let ab_refreshed = config.refresh(ctx, &ab, &aux)?;
+                    let ba_refreshed = config.refresh(ctx, &ba, &aux)?;
+                    config.assert_equal_fresh(ctx, &ab_refreshed, &ba_refreshed)?;
+                    config.range().finalize(ctx);
+                    {
+                        println!("total advice cells: {}", ctx.total_advice);
+                        let const_rows = ctx.total_fixed + 1;
+                        println!("maximum rows used by a fixed column: {const_rows}");
+                        println!("lookup cells used: {}", ctx.cells_to_lookup.len());
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    );
+
+    impl_bigint_test_circuit!(
+        TestRefreshProductCircuit,
+        test_refresh_product_circuit,
+        64,
+        2048,
+        13,
+        false,
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "random refresh product test",
                 |region| {
                     if first_pass {
                         first_pass = false;
@@ -1354,18 +1984,21 @@ mod test {
 
                     let mut aux = config.new_context(region);
                     let ctx = &mut aux;
+                    let a = self.a.clone();
+                    let b = self.b.clone();
                     let a_assigned =
-                        config.assign_integer(ctx, Value::known(self.a.clone()), Self::BITS_LEN)?;
+                        config.assign_integer(ctx, Value::known(a.clone()), Self::BITS_LEN)?;
                     let b_assigned =
-                        config.assign_integer(ctx, Value::known(self.b.clone()), Self::BITS_LEN)?;
+                        config.assign_integer(ctx, Value::known(b.clone()), Self::BITS_LEN)?;
                     let ab = config.mul(ctx, &a_assigned, &b_assigned)?;
-                    let zero = config.assign_constant(ctx, BigUint::zero())?;
                     let num_limbs = Self::BITS_LEN / Self::LIMB_WIDTH;
-                    let zero_value = config.gate().load_zero(ctx);
-                    let zero = zero
-                        .extend_limbs(ab.num_limbs() - zero.num_limbs(), zero_value)
-                        .to_muled();
-                    config.assert_equal_muled(ctx, &ab, &zero, num_limbs, num_limbs)?;
+                    let aux = RefreshAux::new(Self::LIMB_WIDTH, num_limbs, num_limbs);
+                    let ab_refreshed = config.refresh(ctx, &ab, &aux)?;
+                    let zero = config.gate().load_zero(ctx);
+                    let ab_expected = config.assign_constant(ctx, &a * &b)?;
+                    let ab_expected = ab_expected
+                        .extend_limbs(ab_refreshed.num_limbs() - ab_expected.num_limbs(), zero);
+                    config.assert_equal_fresh(ctx, &ab_refreshed, &ab_expected)?;
                     config.range().finalize(ctx);
                     {
                         println!("total advice cells: {}", ctx.total_advice);
@@ -1381,8 +2014,8 @@ mod test {
     );
 
     impl_bigint_test_circuit!(
-        TestRefreshCircuit,
-        test_refresh_circuit,
+        TestRefreshFullWidthProductCircuit,
+        test_refresh_full_width_product_circuit,
         64,
         2048,
         13,
@@ -1395,7 +2028,7 @@ mod test {
             config.range().load_lookup_table(&mut layouter)?;
             let mut first_pass = SKIP_FIRST_PASS;
             layouter.assign_region(
-                || "random refresh test",
+                || "full width refresh product test",
                 |region| {
                     if first_pass {
                         first_pass = false;
@@ -1404,17 +2037,23 @@ mod test {
 
                     let mut aux = config.new_context(region);
                     let ctx = &mut aux;
+                    // The product of the maximum values needs all of the `2 * num_limbs` limbs.
+                    let max = (BigUint::from(1u64) << Self::BITS_LEN) - BigUint::from(1u64);
+                    let a = max.clone();
+                    let b = max;
                     let a_assigned =
-                        config.assign_integer(ctx, Value::known(self.a.clone()), Self::BITS_LEN)?;
+                        config.assign_integer(ctx, Value::known(a.clone()), Self::BITS_LEN)?;
                     let b_assigned =
-                        config.assign_integer(ctx, Value::known(self.b.clone()), Self::BITS_LEN)?;
+                        config.assign_integer(ctx, Value::known(b.clone()), Self::BITS_LEN)?;
                     let ab = config.mul(ctx, &a_assigned, &b_assigned)?;
-                    let ba = config.mul(ctx, &b_assigned, &a_assigned)?;
                     let num_limbs = Self::BITS_LEN / Self::LIMB_WIDTH;
                     let aux = RefreshAux::new(Self::LIMB_WIDTH, num_limbs, num_limbs);
                     let ab_refreshed = config.refresh(ctx, &ab, &aux)?;
-                    let ba_refreshed = config.refresh(ctx, &ba, &aux)?;
-                    config.assert_equal_fresh(ctx, &ab_refreshed, &ba_refreshed)?;
+                    let zero = config.gate().load_zero(ctx);
+                    let ab_expected = config.assign_constant(ctx, &a * &b)?;
+                    let ab_expected = ab_expected
+                        .extend_limbs(ab_refreshed.num_limbs() - ab_expected.num_limbs(), zero);
+                    config.assert_equal_fresh(ctx, &ab_refreshed, &ab_expected)?;
                     config.range().finalize(ctx);
                     {
                         println!("total advice cells: {}", ctx.total_advice);
@@ -1704,11 +2343,292 @@ mod test {
     // );
 
     impl_bigint_test_circuit!(
-        TestMulModCircuit,
-        test_mul_mod_circuit,
+        TestMulModCircuit,
+        test_mul_mod_circuit,
+        64,
+        2048,
+        13,
+        false,
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "random refresh test",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    let a_assigned =
+                        config.assign_integer(ctx, Value::known(self.a.clone()), Self::BITS_LEN)?;
+                    let b_assigned =
+                        config.assign_integer(ctx, Value::known(self.b.clone()), Self::BITS_LEN)?;
+                    let n_assigned =
+                        config.assign_integer(ctx, Value::known(self.n.clone()), Self::BITS_LEN)?;
+                    let ab = config.mul_mod(ctx, &a_assigned, &b_assigned, &n_assigned)?;
+                    let ba = config.mul_mod(ctx, &b_assigned, &a_assigned, &n_assigned)?;
+                    let num_limbs = Self::BITS_LEN / Self::LIMB_WIDTH;
+                    config.assert_equal_fresh(ctx, &ab, &ba)?;
+                    config.range().finalize(ctx);
+                    {
+                        println!("total advice cells: {}", ctx.total_advice);
+                        let const_rows = ctx.total_fixed + 1;
+                        println!("maximum rows used by a fixed column: {const_rows}");
+                        println!("lookup cells used: {}", ctx.cells_to_lookup.len());
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    );
+
+    impl_bigint_test_circuit!(
+        TestBadMulModCircuit,
+        test_bad_mul_mod_circuit,
+        64,
+        2048,
+        13,
+        true,
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "random refresh test",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    let a_assigned =
+                        config.assign_integer(ctx, Value::known(self.a.clone()), Self::BITS_LEN)?;
+                    let b_assigned =
+                        config.assign_integer(ctx, Value::known(self.b.clone()), Self::BITS_LEN)?;
+                    let n_assigned =
+                        config.assign_integer(ctx, Value::known(self.n.clone()), Self::BITS_LEN)?;
+                    let ab = config.mul_mod(ctx, &a_assigned, &b_assigned, &n_assigned)?;
+                    let zero = config.assign_constant(ctx, BigUint::zero())?;
+                    let zero_value = config.gate().load_zero(ctx);
+                    let zero = zero.extend_limbs(ab.num_limbs() - zero.num_limbs(), zero_value);
+                    config.assert_equal_fresh(ctx, &ab, &zero)?;
+                    config.range().finalize(ctx);
+                    {
+                        println!("total advice cells: {}", ctx.total_advice);
+                        let const_rows = ctx.total_fixed + 1;
+                        println!("maximum rows used by a fixed column: {const_rows}");
+                        println!("lookup cells used: {}", ctx.cells_to_lookup.len());
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    );
+
+    impl_bigint_test_circuit!(
+        TestPowModFixedExpCircuit,
+        test_pow_mod_fixed_exp_circuit,
+        64,
+        2048,
+        13,
+        false,
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "random pow_mod test",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    let a_assigned =
+                        config.assign_integer(ctx, Value::known(self.a.clone()), Self::BITS_LEN)?;
+                    let n_assigned =
+                        config.assign_integer(ctx, Value::known(self.n.clone()), Self::BITS_LEN)?;
+                    let e = BigUint::from_u64(65537).unwrap();
+                    let powed = config.pow_mod_fixed_exp(ctx, &a_assigned, &e, &n_assigned)?;
+                    let ans_big = big_pow_mod(&self.a, &e, &self.n);
+                    let ans_assigned = config.assign_constant(ctx, ans_big)?;
+                    config.assert_equal_fresh(ctx, &powed, &ans_assigned)?;
+                    config.range().finalize(ctx);
+                    {
+                        println!("total advice cells: {}", ctx.total_advice);
+                        let const_rows = ctx.total_fixed + 1;
+                        println!("maximum rows used by a fixed column: {const_rows}");
+                        println!("lookup cells used: {}", ctx.cells_to_lookup.len());
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    );
+
+    impl_bigint_test_circuit!(
+        TestBadPowModFixedExpCircuit,
+        test_bad_pow_mod_fixed_exp_circuit,
+        64,
+        2048,
+        13,
+        true,
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "random pow_mod test",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    let a_assigned =
+                        config.assign_integer(ctx, Value::known(self.a.clone()), Self::BITS_LEN)?;
+                    let n_assigned =
+                        config.assign_integer(ctx, Value::known(self.n.clone()), Self::BITS_LEN)?;
+                    let e = BigUint::from_u64(65537).unwrap();
+                    let powed = config.pow_mod_fixed_exp(ctx, &a_assigned, &e, &n_assigned)?;
+                    let zero = config.assign_constant(ctx, BigUint::zero())?;
+                    let zero_value = config.gate().load_zero(ctx);
+                    let zero = zero.extend_limbs(powed.num_limbs() - zero.num_limbs(), zero_value);
+                    config.assert_equal_fresh(ctx, &powed, &zero)?;
+                    config.range().finalize(ctx);
+                    {
+                        println!("total advice cells: {}", ctx.total_advice);
+                        let const_rows = ctx.total_fixed + 1;
+                        println!("maximum rows used by a fixed column: {const_rows}");
+                        println!("lookup cells used: {}", ctx.cells_to_lookup.len());
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    );
+
+    impl_bigint_test_circuit!(
+        TestPowModStrategiesCircuit,
+        test_pow_mod_strategies_circuit,
+        64,
+        1024,
+        15,
+        false,
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "random pow_mod strategies test",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    let a_assigned =
+                        config.assign_integer(ctx, Value::known(self.a.clone()), Self::BITS_LEN)?;
+                    let n_assigned =
+                        config.assign_integer(ctx, Value::known(self.n.clone()), Self::BITS_LEN)?;
+                    // A 40 bits exponent taken from the random `b`.
+                    let random_e = (&self.b >> (Self::BITS_LEN - 40)) | (BigUint::one() << 39);
+                    for e in [BigUint::from(3u64), BigUint::from(65537u64), random_e] {
+                        let ans_big = big_pow_mod(&self.a, &e, &self.n);
+                        let ans_assigned = config.assign_constant(ctx, ans_big)?;
+                        for strategy in [
+                            ModPowStrategy::SquareAndMultiply,
+                            ModPowStrategy::Windowed(1),
+                            ModPowStrategy::Windowed(4),
+                            ModPowStrategy::Auto,
+                        ] {
+                            let powed = config.pow_mod_fixed_exp_with_strategy(
+                                ctx,
+                                &a_assigned,
+                                &e,
+                                &n_assigned,
+                                strategy,
+                            )?;
+                            config.assert_equal_fresh(ctx, &powed, &ans_assigned)?;
+                        }
+                    }
+                    config.range().finalize(ctx);
+                    {
+                        println!("total advice cells: {}", ctx.total_advice);
+                        let const_rows = ctx.total_fixed + 1;
+                        println!("maximum rows used by a fixed column: {const_rows}");
+                        println!("lookup cells used: {}", ctx.cells_to_lookup.len());
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    );
+
+    #[test]
+    fn test_mod_pow_strategy_selection() {
+        let e = BigUint::from(65537u64);
+        assert_eq!(
+            ModPowStrategy::Auto.resolve(&e),
+            ModPowStrategy::SquareAndMultiply
+        );
+        assert_eq!(ModPowStrategy::default(), ModPowStrategy::Auto);
+        assert_eq!(ModPowStrategy::SquareAndMultiply.num_mul_mods(&e), 19);
+        assert_eq!(ModPowStrategy::Windowed(4).num_mul_mods(&e), 17);
+
+        let e = (BigUint::one() << 2047) - BigUint::one();
+        let resolved = ModPowStrategy::Auto.resolve(&e);
+        assert!(matches!(resolved, ModPowStrategy::Windowed(_)));
+        assert!(resolved.num_mul_mods(&e) < ModPowStrategy::SquareAndMultiply.num_mul_mods(&e));
+        for window_bits in 1..=MAX_MOD_POW_WINDOW_BITS {
+            assert!(
+                resolved.num_mul_mods(&e) <= ModPowStrategy::Windowed(window_bits).num_mul_mods(&e)
+            );
+        }
+        assert_eq!(
+            window_digits(&BigUint::from(0b101_0011u64), 3),
+            vec![1, 2, 3]
+        );
+    }
+
+    impl_bigint_test_circuit!(
+        TestModPowUncheckedCircuit,
+        test_mod_pow_unchecked_circuit,
         64,
         2048,
-        13,
+        14,
         false,
         fn synthesize(
             &self,
@@ -1718,7 +2638,7 @@ mod test {
             config.range().load_lookup_table(&mut layouter)?;
             let mut first_pass = SKIP_FIRST_PASS;
             layouter.assign_region(
-                || "random refresh test",
+                || "random mod_pow_unchecked test",
                 |region| {
                     if first_pass {
                         first_pass = false;
@@ -1729,14 +2649,25 @@ mod test {
                     let ctx = &mut aux;
                     let a_assigned =
                         config.assign_integer(ctx, Value::known(self.a.clone()), Self::BITS_LEN)?;
-                    let b_assigned =
-                        config.assign_integer(ctx, Value::known(self.b.clone()), Self::BITS_LEN)?;
                     let n_assigned =
                         config.assign_integer(ctx, Value::known(self.n.clone()), Self::BITS_LEN)?;
-                    let ab = config.mul_mod(ctx, &a_assigned, &b_assigned, &n_assigned)?;
-                    let ba = config.mul_mod(ctx, &b_assigned, &a_assigned, &n_assigned)?;
-                    let num_limbs = Self::BITS_LEN / Self::LIMB_WIDTH;
-                    config.assert_equal_fresh(ctx, &ab, &ba)?;
+                    let e = BigUint::from_u64(65537).unwrap();
+                    let ans_big = big_pow_mod(&self.a, &e, &self.n);
+                    let ans_assigned = config.assign_constant(ctx, ans_big)?;
+
+                    let start = ctx.total_advice;
+                    let powed = config.pow_mod_fixed_exp(ctx, &a_assigned, &e, &n_assigned)?;
+                    let checked_cells = ctx.total_advice - start;
+                    config.assert_equal_fresh(ctx, &powed, &ans_assigned)?;
+
+                    let start = ctx.total_advice;
+                    let powed = config.mod_pow_unchecked(ctx, &a_assigned, &e, &n_assigned)?;
+                    let unchecked_cells = ctx.total_advice - start;
+                    config.assert_equal_fresh(ctx, &powed, &ans_assigned)?;
+
+                    println!("advice cells of pow_mod_fixed_exp: {checked_cells}");
+                    println!("advice cells of mod_pow_unchecked: {unchecked_cells}");
+                    assert!(unchecked_cells < checked_cells);
                     config.range().finalize(ctx);
                     {
                         println!("total advice cells: {}", ctx.total_advice);
@@ -1752,8 +2683,8 @@ mod test {
     );
 
     impl_bigint_test_circuit!(
-        TestBadMulModCircuit,
-        test_bad_mul_mod_circuit,
+        TestBadModPowUncheckedCircuit,
+        test_bad_mod_pow_unchecked_circuit,
         64,
         2048,
         13,
@@ -1766,7 +2697,7 @@ mod test {
             config.range().load_lookup_table(&mut layouter)?;
             let mut first_pass = SKIP_FIRST_PASS;
             layouter.assign_region(
-                || "random refresh test",
+                || "tampered mod_pow_unchecked test",
                 |region| {
                     if first_pass {
                         first_pass = false;
@@ -1775,17 +2706,17 @@ mod test {
 
                     let mut aux = config.new_context(region);
                     let ctx = &mut aux;
+                    // Flip the lowest bit of the base as a tampered signature.
+                    let tampered = &self.a ^ BigUint::from(1u64);
                     let a_assigned =
-                        config.assign_integer(ctx, Value::known(self.a.clone()), Self::BITS_LEN)?;
-                    let b_assigned =
-                        config.assign_integer(ctx, Value::known(self.b.clone()), Self::BITS_LEN)?;
+                        config.assign_integer(ctx, Value::known(tampered), Self::BITS_LEN)?;
                     let n_assigned =
                         config.assign_integer(ctx, Value::known(self.n.clone()), Self::BITS_LEN)?;
-                    let ab = config.mul_mod(ctx, &a_assigned, &b_assigned, &n_assigned)?;
-                    let zero = config.assign_constant(ctx, BigUint::zero())?;
-                    let zero_value = config.gate().load_zero(ctx);
-                    let zero = zero.extend_limbs(ab.num_limbs() - zero.num_limbs(), zero_value);
-                    config.assert_equal_fresh(ctx, &ab, &zero)?;
+                    let e = BigUint::from_u64(65537).unwrap();
+                    let powed = config.mod_pow_unchecked(ctx, &a_assigned, &e, &n_assigned)?;
+                    let ans_big = big_pow_mod(&self.a, &e, &self.n);
+                    let ans_assigned = config.assign_constant(ctx, ans_big)?;
+                    config.assert_equal_fresh(ctx, &powed, &ans_assigned)?;
                     config.range().finalize(ctx);
                     {
                         println!("total advice cells: {}", ctx.total_advice);
@@ -1800,9 +2731,81 @@ mod test {
         }
     );
 
+    macro_rules! impl_assert_equal_muled_unchecked_test_circuit {
+        ($circuit_name:ident, $test_fn_name:ident, $offset:expr, $should_be_error:expr) => {
+            impl_bigint_test_circuit!(
+                $circuit_name,
+                $test_fn_name,
+                64,
+                2048,
+                13,
+                $should_be_error,
+                fn synthesize(
+                    &self,
+                    config: Self::Config,
+                    mut layouter: impl Layouter<F>,
+                ) -> Result<(), Error> {
+                    config.range().load_lookup_table(&mut layouter)?;
+                    let mut first_pass = SKIP_FIRST_PASS;
+                    layouter.assign_region(
+                        || "assert_equal_muled_unchecked test",
+                        |region| {
+                            if first_pass {
+                                first_pass = false;
+                                return Ok(());
+                            }
+
+                            let mut aux = config.new_context(region);
+                            let ctx = &mut aux;
+                            let a_assigned = config.assign_integer(
+                                ctx,
+                                Value::known(self.a.clone()),
+                                Self::BITS_LEN,
+                            )?;
+                            let b_assigned = config.assign_integer(
+                                ctx,
+                                Value::known(self.b.clone()),
+                                Self::BITS_LEN,
+                            )?;
+                            // `b + offset` fits in `BITS_LEN` bits because `b < n`.
+                            let c_assigned = config.assign_integer(
+                                ctx,
+                                Value::known(&self.b + BigUint::from($offset as u64)),
+                                Self::BITS_LEN,
+                            )?;
+                            let num_limbs = a_assigned.num_limbs();
+                            let ab = config.mul(ctx, &a_assigned, &b_assigned)?;
+                            let ac = config.mul(ctx, &a_assigned, &c_assigned)?;
+                            config.assert_equal_muled_unchecked(
+                                ctx, &ab, &ac, num_limbs, num_limbs,
+                            )?;
+                            config.range().finalize(ctx);
+                            Ok(())
+                        },
+                    )?;
+                    Ok(())
+                }
+            );
+        };
+    }
+
+    impl_assert_equal_muled_unchecked_test_circuit!(
+        TestAssertEqualMuledUncheckedCircuit,
+        test_assert_equal_muled_unchecked_circuit,
+        0,
+        false
+    );
+
+    impl_assert_equal_muled_unchecked_test_circuit!(
+        TestBadAssertEqualMuledUncheckedCircuit,
+        test_bad_assert_equal_muled_unchecked_circuit,
+        1,
+        true
+    );
+
     impl_bigint_test_circuit!(
-        TestPowModFixedExpCircuit,
-        test_pow_mod_fixed_exp_circuit,
+        TestCoprimeCircuit,
+        test_coprime_circuit,
         64,
         2048,
         13,
@@ -1815,7 +2818,7 @@ mod test {
             config.range().load_lookup_table(&mut layouter)?;
             let mut first_pass = SKIP_FIRST_PASS;
             layouter.assign_region(
-                || "random pow_mod test",
+                || "random coprime test",
                 |region| {
                     if first_pass {
                         first_pass = false;
@@ -1824,22 +2827,13 @@ mod test {
 
                     let mut aux = config.new_context(region);
                     let ctx = &mut aux;
-                    let a_assigned =
-                        config.assign_integer(ctx, Value::known(self.a.clone()), Self::BITS_LEN)?;
-                    let n_assigned =
-                        config.assign_integer(ctx, Value::known(self.n.clone()), Self::BITS_LEN)?;
-                    let e = BigUint::from_u64(65537).unwrap();
-                    let powed = config.pow_mod_fixed_exp(ctx, &a_assigned, &e, &n_assigned)?;
-                    let ans_big = big_pow_mod(&self.a, &e, &self.n);
-                    let ans_assigned = config.assign_constant(ctx, ans_big)?;
-                    config.assert_equal_fresh(ctx, &powed, &ans_assigned)?;
+                    // Any power of two is coprime to an odd modulus.
+                    let n = &self.n | BigUint::one();
+                    let a = big_pow_mod(&BigUint::from(2u64), &self.a, &n);
+                    let a_assigned = config.assign_integer(ctx, Value::known(a), Self::BITS_LEN)?;
+                    let n_assigned = config.assign_integer(ctx, Value::known(n), Self::BITS_LEN)?;
+                    config.assert_coprime(ctx, &a_assigned, &n_assigned)?;
                     config.range().finalize(ctx);
-                    {
-                        println!("total advice cells: {}", ctx.total_advice);
-                        let const_rows = ctx.total_fixed + 1;
-                        println!("maximum rows used by a fixed column: {const_rows}");
-                        println!("lookup cells used: {}", ctx.cells_to_lookup.len());
-                    }
                     Ok(())
                 },
             )?;
@@ -1848,8 +2842,8 @@ mod test {
     );
 
     impl_bigint_test_circuit!(
-        TestBadPowModFixedExpCircuit,
-        test_bad_pow_mod_fixed_exp_circuit,
+        TestNotCoprimeCircuit,
+        test_not_coprime_circuit,
         64,
         2048,
         13,
@@ -1862,7 +2856,7 @@ mod test {
             config.range().load_lookup_table(&mut layouter)?;
             let mut first_pass = SKIP_FIRST_PASS;
             layouter.assign_region(
-                || "random pow_mod test",
+                || "random not coprime test",
                 |region| {
                     if first_pass {
                         first_pass = false;
@@ -1871,23 +2865,52 @@ mod test {
 
                     let mut aux = config.new_context(region);
                     let ctx = &mut aux;
-                    let a_assigned =
-                        config.assign_integer(ctx, Value::known(self.a.clone()), Self::BITS_LEN)?;
-                    let n_assigned =
-                        config.assign_integer(ctx, Value::known(self.n.clone()), Self::BITS_LEN)?;
-                    let e = BigUint::from_u64(65537).unwrap();
-                    let powed = config.pow_mod_fixed_exp(ctx, &a_assigned, &e, &n_assigned)?;
-                    let zero = config.assign_constant(ctx, BigUint::zero())?;
-                    let zero_value = config.gate().load_zero(ctx);
-                    let zero = zero.extend_limbs(powed.num_limbs() - zero.num_limbs(), zero_value);
-                    config.assert_equal_fresh(ctx, &powed, &zero)?;
+                    // Both integers are even.
+                    let n = &self.n - (&self.n & BigUint::one());
+                    let a = &self.a - (&self.a & BigUint::one());
+                    let a_assigned = config.assign_integer(ctx, Value::known(a), Self::BITS_LEN)?;
+                    let n_assigned = config.assign_integer(ctx, Value::known(n), Self::BITS_LEN)?;
+                    config.assert_coprime(ctx, &a_assigned, &n_assigned)?;
                     config.range().finalize(ctx);
-                    {
-                        println!("total advice cells: {}", ctx.total_advice);
-                        let const_rows = ctx.total_fixed + 1;
-                        println!("maximum rows used by a fixed column: {const_rows}");
-                        println!("lookup cells used: {}", ctx.cells_to_lookup.len());
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    );
+
+    impl_bigint_test_circuit!(
+        TestNotCoprimeOddCircuit,
+        test_not_coprime_odd_circuit,
+        64,
+        2048,
+        13,
+        true,
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "random odd not coprime test",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
                     }
+
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    // Both integers are odd multiples of three.
+                    let three = BigUint::from(3u64);
+                    let n = (&self.n >> 2usize | BigUint::one()) * &three;
+                    let a = (&self.a >> 2usize | BigUint::one()) * &three;
+                    let a_assigned = config.assign_integer(ctx, Value::known(a), Self::BITS_LEN)?;
+                    let n_assigned = config.assign_integer(ctx, Value::known(n), Self::BITS_LEN)?;
+                    config.assert_coprime(ctx, &a_assigned, &n_assigned)?;
+                    config.range().finalize(ctx);
                     Ok(())
                 },
             )?;
@@ -2796,4 +3819,499 @@ mod test {
     //         Ok(())
     //     }
     // );
+
+    /// Returns the overflowing limbs of the product of `a` and `b` as computed by [`BigUintInstructions::mul`].
+    fn muled_limbs(a: &BigUint, b: &BigUint, limb_bits: usize, num_limbs: usize) -> Vec<BigUint> {
+        let limb_max = BigUint::from(1u64) << limb_bits;
+        let decompose = |x: &BigUint| {
+            (0..num_limbs)
+                .map(|i| (x >> (limb_bits * i)) % &limb_max)
+                .collect::<Vec<BigUint>>()
+        };
+        let (a_limbs, b_limbs) = (decompose(a), decompose(b));
+        let mut muled = vec![BigUint::from(0u64); 2 * num_limbs - 1];
+        for (i, a_limb) in a_limbs.iter().enumerate() {
+            for (j, b_limb) in b_limbs.iter().enumerate() {
+                muled[i + j] += a_limb * b_limb;
+            }
+        }
+        muled
+    }
+
+    #[test]
+    fn test_refresh_aux_validate() {
+        let max = (BigUint::from(1u64) << 2048) - BigUint::from(1u64);
+        let product_limbs = muled_limbs(&max, &max, 64, 32);
+        RefreshAux::new(64, 32, 32)
+            .validate(&product_limbs)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_refresh_aux_validate_wrong_num_limbs() {
+        let max = (BigUint::from(1u64) << 2048) - BigUint::from(1u64);
+        let product_limbs = muled_limbs(&max, &max, 64, 32);
+        let err = RefreshAux::new(64, 16, 16)
+            .validate(&product_limbs)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            RefreshAuxError::NumLimbsMismatch {
+                expected: 31,
+                actual: 63
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "RefreshAux expects a product of 31 limbs, but the product has 63 limbs"
+        );
+    }
+
+    #[test]
+    fn test_refresh_aux_validate_wrong_limb_bits() {
+        let max = (BigUint::from(1u64) << 2048) - BigUint::from(1u64);
+        let product_limbs = muled_limbs(&max, &max, 64, 32);
+        // The aux data for 32 bits limbs records too few carries for 64 bits limbs.
+        let err = RefreshAux::new(32, 32, 32)
+            .validate(&product_limbs)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            RefreshAuxError::CarryOverflow { limb_index: 0, .. }
+        ));
+        assert!(err.to_string().contains("cannot carry the 0-th limb"));
+    }
+
+    /// A circuit whose prover witnesses the output of `is_equal_fresh` or `is_equal_muled` as `forged` instead of the computed bit.
+    struct TestForgedIsEqualCircuit<F: PrimeField> {
+        a: BigUint,
+        b: BigUint,
+        muled: bool,
+        forged: Option<u64>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestForgedIsEqualCircuit<F> {
+        const LIMB_WIDTH: usize = 64;
+        const BITS_LEN: usize = 256;
+        const K: usize = 13;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestForgedIsEqualCircuit<F> {
+        type Config = BigUintConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config =
+                RangeConfig::configure(meta, Vertical, &[10], &[1], 1, 12, 0, Self::K);
+            BigUintConfig::construct(range_config, Self::LIMB_WIDTH)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "forged is_equal test",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    let a_assigned =
+                        config.assign_integer(ctx, Value::known(self.a.clone()), Self::BITS_LEN)?;
+                    let b_assigned =
+                        config.assign_integer(ctx, Value::known(self.b.clone()), Self::BITS_LEN)?;
+                    let num_limbs = Self::BITS_LEN / Self::LIMB_WIDTH;
+                    // `a * a` and `a * b` are equal if and only if `a` and `b` are.
+                    let aa = config.mul(ctx, &a_assigned, &a_assigned)?;
+                    let ab = config.mul(ctx, &a_assigned, &b_assigned)?;
+                    let gate = config.gate();
+                    // Every limb comparison is assigned by `is_equal_with_hint`, whose cells are consistent with the hints of `self.forged`.
+                    let is_eq = if self.muled {
+                        config.is_equal_muled_with(
+                            ctx,
+                            &aa,
+                            &ab,
+                            num_limbs,
+                            num_limbs,
+                            |ctx, x, y| is_equal_with_hint(gate, ctx, x, y, self.forged),
+                        )?
+                    } else {
+                        // The same cells as `big_is_equal::assign` of `is_equal_fresh`.
+                        let mut limbs = a_assigned.limbs().iter().zip(b_assigned.limbs());
+                        let (a_limb, b_limb) = limbs.next().unwrap();
+                        let mut partial =
+                            is_equal_with_hint(gate, ctx, a_limb, b_limb, self.forged);
+                        for (a_limb, b_limb) in limbs {
+                            let eq_limb =
+                                is_equal_with_hint(gate, ctx, a_limb, b_limb, self.forged);
+                            partial = gate.and(
+                                ctx,
+                                QuantumCell::Existing(&eq_limb),
+                                QuantumCell::Existing(&partial),
+                            );
+                        }
+                        partial
+                    };
+                    let expected = match self.forged {
+                        Some(forged) => F::from(forged),
+                        None => {
+                            // With the honest hints, the bits are those of the gadgets themselves.
+                            let honest = if self.muled {
+                                config.is_equal_muled(ctx, &aa, &ab, num_limbs, num_limbs)?
+                            } else {
+                                config.is_equal_fresh(ctx, &a_assigned, &b_assigned)?
+                            };
+                            let expected = F::from((self.a == self.b) as u64);
+                            gate.assert_is_const(ctx, &honest, expected);
+                            expected
+                        }
+                    };
+                    gate.assert_is_const(ctx, &is_eq, expected);
+                    config.range().finalize(ctx);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    /// Assigns the cells of [`GateInstructions::is_equal`], i.e., `a - b` and the cells of `is_zero` for it, with the output bit `forged` if it is given.
+    ///
+    /// `is_zero` constrains `out + diff * inv = 1` and `diff * out = 0` with the hint `inv`.
+    /// A forged `out` of one takes the hint zero, which satisfies the first constraint, and a forged `out` of zero takes the honest hint, so only the constraints violated by the forged bit itself fail.
+    fn is_equal_with_hint<'v, F: PrimeField>(
+        gate: &FlexGateConfig<F>,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedValue<'v, F>,
+        b: &AssignedValue<'v, F>,
+        forged: Option<u64>,
+    ) -> AssignedValue<'v, F> {
+        let diff = gate.sub(ctx, QuantumCell::Existing(a), QuantumCell::Existing(b));
+        let (out, inv) = diff
+            .value
+            .map(|diff| {
+                let honest_inv = diff.invert().unwrap_or(F::one());
+                match forged {
+                    Some(1) => (F::one(), F::zero()),
+                    Some(forged) => (F::from(forged), honest_inv),
+                    None => (F::from(bool::from(diff.is_zero()) as u64), honest_inv),
+                }
+            })
+            .unzip();
+        let cells = vec![
+            QuantumCell::Witness(out),
+            QuantumCell::Existing(&diff),
+            QuantumCell::Witness(inv),
+            QuantumCell::Constant(F::one()),
+            QuantumCell::Constant(F::zero()),
+            QuantumCell::Existing(&diff),
+            QuantumCell::Witness(out),
+            QuantumCell::Constant(F::zero()),
+        ];
+        let assigned = gate.assign_region_smart(ctx, cells, vec![0, 4], vec![(0, 6)], vec![]);
+        assigned.into_iter().next().unwrap()
+    }
+
+    fn run_forged_is_equal(a: u64, b: u64, muled: bool, forged: Option<u64>) -> bool {
+        let circuit = TestForgedIsEqualCircuit::<Fr> {
+            a: BigUint::from(a) << 100,
+            b: BigUint::from(b) << 100,
+            muled,
+            forged,
+            _f: PhantomData,
+        };
+        let prover =
+            match MockProver::run(TestForgedIsEqualCircuit::<Fr>::K as u32, &circuit, vec![]) {
+                Ok(prover) => prover,
+                Err(e) => panic!("{:#?}", e),
+            };
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_is_equal_fresh_rejects_forged_output() {
+        assert!(run_forged_is_equal(7, 7, false, None));
+        assert!(run_forged_is_equal(7, 8, false, None));
+        assert!(!run_forged_is_equal(7, 7, false, Some(0)));
+        assert!(!run_forged_is_equal(7, 8, false, Some(1)));
+    }
+
+    #[test]
+    fn test_is_equal_muled_rejects_forged_output() {
+        assert!(run_forged_is_equal(7, 7, true, None));
+        assert!(run_forged_is_equal(7, 8, true, None));
+        assert!(!run_forged_is_equal(7, 7, true, Some(0)));
+        assert!(!run_forged_is_equal(7, 8, true, Some(1)));
+    }
+
+    impl_bigint_test_circuit!(
+        TestRecomposeLimbsCircuit,
+        test_recompose_limbs_circuit,
+        64,
+        2048,
+        13,
+        false,
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "recompose limbs test",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    let a_assigned =
+                        config.assign_integer(ctx, Value::known(self.a.clone()), Self::BITS_LEN)?;
+                    for new_limb_bits in [32, 48] {
+                        let recomposed = config.recompose_limbs(ctx, &a_assigned, new_limb_bits)?;
+                        let expected_limbs = decompose_biguint::<F>(
+                            &self.a,
+                            (Self::BITS_LEN + new_limb_bits - 1) / new_limb_bits,
+                            new_limb_bits,
+                        );
+                        assert_eq!(recomposed.num_limbs(), expected_limbs.len());
+                        for (limb, expected) in recomposed.limbs().iter().zip(expected_limbs) {
+                            config.gate().assert_equal(
+                                ctx,
+                                QuantumCell::Existing(limb),
+                                QuantumCell::Constant(expected),
+                            );
+                        }
+                    }
+                    let a_32 = config.recompose_limbs(ctx, &a_assigned, 32)?;
+                    let a_64 = BigUintConfig::construct(config.range().clone(), 32)
+                        .recompose_limbs(ctx, &a_32, 64)?;
+                    config.assert_equal_fresh(ctx, &a_64, &a_assigned)?;
+                    config.range().finalize(ctx);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    );
+
+    /// Boundary cases of the reductions of `Muled` products, i.e., [`BigUintInstructions::refresh`] with [`RefreshAux`] and the modular reductions of [`BigUintInstructions::mul_mod`] and `mul_mod_with_fresh_modulus`.
+    mod reduction {
+        use super::*;
+        use num_bigint::RandomBits;
+        use rand::{thread_rng, Rng};
+
+        /// A circuit asserting that the refreshed product `a * b` and its remainders modulo `n` equal those computed by `num_bigint`.
+        struct TestReductionCircuit<F: PrimeField> {
+            a: BigUint,
+            b: BigUint,
+            n: BigUint,
+            _f: PhantomData<F>,
+        }
+
+        impl<F: PrimeField> TestReductionCircuit<F> {
+            const LIMB_WIDTH: usize = 64;
+            const BITS_LEN: usize = 256;
+            const K: usize = 13;
+        }
+
+        impl<F: PrimeField> Circuit<F> for TestReductionCircuit<F> {
+            type Config = BigUintConfig<F>;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                unimplemented!();
+            }
+
+            fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+                let range_config =
+                    RangeConfig::configure(meta, Vertical, &[20], &[2], 1, 12, 0, Self::K);
+                BigUintConfig::construct(range_config, Self::LIMB_WIDTH)
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<F>,
+            ) -> Result<(), Error> {
+                config.range().load_lookup_table(&mut layouter)?;
+                let mut first_pass = SKIP_FIRST_PASS;
+                layouter.assign_region(
+                    || "reduction boundary test",
+                    |region| {
+                        if first_pass {
+                            first_pass = false;
+                            return Ok(());
+                        }
+
+                        let mut aux = config.new_context(region);
+                        let ctx = &mut aux;
+                        let a_assigned = config.assign_integer(
+                            ctx,
+                            Value::known(self.a.clone()),
+                            Self::BITS_LEN,
+                        )?;
+                        let b_assigned = config.assign_integer(
+                            ctx,
+                            Value::known(self.b.clone()),
+                            Self::BITS_LEN,
+                        )?;
+                        let n_assigned = config.assign_integer(
+                            ctx,
+                            Value::known(self.n.clone()),
+                            Self::BITS_LEN,
+                        )?;
+                        let zero = config.gate().load_zero(ctx);
+                        let product = &self.a * &self.b;
+                        let remainder = &product % &self.n;
+
+                        // 1. The refreshed product equals `a * b` without any reduction.
+                        let ab = config.mul(ctx, &a_assigned, &b_assigned)?;
+                        let num_limbs = Self::BITS_LEN / Self::LIMB_WIDTH;
+                        let refresh_aux = RefreshAux::new(Self::LIMB_WIDTH, num_limbs, num_limbs);
+                        let ab_refreshed = config.refresh(ctx, &ab, &refresh_aux)?;
+                        let ab_expected = config.assign_constant(ctx, product)?;
+                        let ab_expected = ab_expected.extend_limbs(
+                            ab_refreshed.num_limbs() - ab_expected.num_limbs(),
+                            zero.clone(),
+                        );
+                        config.assert_equal_fresh(ctx, &ab_refreshed, &ab_expected)?;
+
+                        // 2. Both modular reductions equal `a * b mod n`.
+                        let reduced = config.mul_mod(ctx, &a_assigned, &b_assigned, &n_assigned)?;
+                        let reduced_with_fresh_modulus = config.mul_mod_with_fresh_modulus(
+                            ctx,
+                            &a_assigned,
+                            &b_assigned,
+                            &n_assigned,
+                        )?;
+                        let expected = config.assign_constant(ctx, remainder)?;
+                        let expected =
+                            expected.extend_limbs(num_limbs - expected.num_limbs(), zero);
+                        config.assert_equal_fresh(ctx, &reduced, &expected)?;
+                        config.assert_equal_fresh(ctx, &reduced_with_fresh_modulus, &expected)?;
+                        config.range().finalize(ctx);
+                        Ok(())
+                    },
+                )?;
+                Ok(())
+            }
+        }
+
+        fn run_reduction(a: BigUint, b: BigUint, n: BigUint) -> bool {
+            assert!(a < n && b < n, "the operands must be reduced");
+            let circuit = TestReductionCircuit::<Fr> {
+                a,
+                b,
+                n,
+                _f: PhantomData,
+            };
+            let prover =
+                match MockProver::run(TestReductionCircuit::<Fr>::K as u32, &circuit, vec![]) {
+                    Ok(prover) => prover,
+                    Err(e) => panic!("{:#?}", e),
+                };
+            prover.verify().is_ok()
+        }
+
+        /// Samples an integer of exactly `bits` bits.
+        fn sample_exact_bits(bits: u64) -> BigUint {
+            let mut rng = thread_rng();
+            let mut x = BigUint::default();
+            while x.bits() != bits {
+                x = rng.sample(RandomBits::new(bits));
+            }
+            x
+        }
+
+        /// Runs the reduction of `a * b` modulo `n = a * b + delta`, i.e., a product just below, equal to, or just above the modulus.
+        fn run_product_near_modulus(a: &BigUint, b: &BigUint, delta: i64) -> bool {
+            let product = a * b;
+            let n = if delta >= 0 {
+                &product + BigUint::from(delta as u64)
+            } else {
+                &product - BigUint::from(delta.unsigned_abs())
+            };
+            run_reduction(a.clone(), b.clone(), n)
+        }
+
+        #[test]
+        fn test_reduction_product_near_modulus() {
+            let half_bits = (TestReductionCircuit::<Fr>::BITS_LEN / 2) as u64;
+            let a = sample_exact_bits(half_bits);
+            let b = sample_exact_bits(half_bits);
+            // The product is just below `n`, exactly `n`, and just above `n`, so the quotient is 0, 1, and 1 with the remainders `a * b`, 0, and 1.
+            assert!(run_product_near_modulus(&a, &b, 1));
+            assert!(run_product_near_modulus(&a, &b, 0));
+            assert!(run_product_near_modulus(&a, &b, -1));
+        }
+
+        #[test]
+        fn test_reduction_product_near_modulus_fuzz() {
+            let mut rng = thread_rng();
+            let bits_len = TestReductionCircuit::<Fr>::BITS_LEN as u64;
+            for _ in 0..8 {
+                // Unbalanced operands move the boundary across the limbs.
+                let a_bits = rng.gen_range(2..bits_len - 1);
+                let a = sample_exact_bits(a_bits);
+                let b = sample_exact_bits(bits_len - a_bits);
+                let delta = rng.gen_range(-3i64..=3);
+                if delta > 0 && (&a * &b + BigUint::from(delta as u64)).bits() > bits_len {
+                    // `n` would not fit in `BITS_LEN` bits.
+                    continue;
+                }
+                assert!(
+                    run_product_near_modulus(&a, &b, delta),
+                    "a = {}, b = {}, delta = {}",
+                    a,
+                    b,
+                    delta
+                );
+            }
+        }
+
+        #[test]
+        fn test_reduction_near_double_width_max() {
+            let bits_len = TestReductionCircuit::<Fr>::BITS_LEN;
+            // The largest odd modulus and operands, whose product has all but the lowest limb close to the maximum.
+            let n = (BigUint::one() << bits_len) - BigUint::one();
+            let max = &n - BigUint::one();
+            assert!(run_reduction(max.clone(), max.clone(), n.clone()));
+            assert!(run_reduction(max.clone(), BigUint::one(), n.clone()));
+            assert!(run_reduction(max, BigUint::zero(), n));
+        }
+
+        #[test]
+        fn test_reduction_near_double_width_max_fuzz() {
+            let mut rng = thread_rng();
+            let bits_len = TestReductionCircuit::<Fr>::BITS_LEN as u64;
+            for _ in 0..8 {
+                let n = sample_exact_bits(bits_len) | BigUint::one();
+                let a = &n - BigUint::from(rng.gen_range(1u64..=4));
+                let b = &n - BigUint::from(rng.gen_range(1u64..=4));
+                assert!(
+                    run_reduction(a.clone(), b.clone(), n.clone()),
+                    "a = {}, b = {}, n = {}",
+                    a,
+                    b,
+                    n
+                );
+            }
+        }
+    }
 }
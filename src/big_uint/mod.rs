@@ -6,6 +6,8 @@ mod utils;
 pub use chip::*;
 pub use instructions::*;
 pub use utils::*;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 
 use halo2_base::{halo2_proofs::circuit::Value, utils::PrimeField, AssignedValue};
 use halo2_ecc::bigint::{CRTInteger, OverflowInteger};
@@ -189,4 +191,168 @@ impl RefreshAux {
             increased_limbs_vec,
         }
     }
+
+    /// Checks off-circuit that [`BigUintInstructions::refresh`] with this [`RefreshAux`] can carry the limbs of `product`.
+    ///
+    /// Refreshing reduces every limb modulo `2^limb_bits` and carries the upper bits to the following limbs as many times as recorded in this [`RefreshAux`].
+    /// If this [`RefreshAux`] was created for other numbers of limbs or another `limb_bits`, some carry remains and the circuit becomes unsatisfiable without pointing to the cause.
+    /// Calling this function before the assignment turns such a failure into a [`RefreshAuxError`].
+    ///
+    /// # Arguments
+    /// * `product_limbs` - the limb values of the [`Muled`] integer to be refreshed, i.e., the product of two integers of `num_limbs_l` and `num_limbs_r` limbs.
+    pub fn validate(&self, product_limbs: &[BigUint]) -> Result<(), RefreshAuxError> {
+        let expected = self.num_limbs_l + self.num_limbs_r - 1;
+        if product_limbs.len() != expected {
+            return Err(RefreshAuxError::NumLimbsMismatch {
+                expected,
+                actual: product_limbs.len(),
+            });
+        }
+        let limb_max = BigUint::from(1u64) << self.limb_bits;
+        let num_limbs_fresh = self.increased_limbs_vec.len();
+        let mut refreshed_limbs = product_limbs.to_vec();
+        refreshed_limbs.resize(num_limbs_fresh, BigUint::from(0u64));
+        for i in 0..num_limbs_fresh {
+            let mut limb = refreshed_limbs[i].clone();
+            for j in 0..(self.increased_limbs_vec[i] + 1) {
+                let n = &limb % &limb_max;
+                if j == 0 {
+                    refreshed_limbs[i] = n;
+                } else if i + j < num_limbs_fresh {
+                    refreshed_limbs[i + j] += n;
+                } else if n != BigUint::from(0u64) {
+                    return Err(RefreshAuxError::CarryOverflow {
+                        limb_index: i,
+                        remaining_bits: limb.bits() as usize,
+                    });
+                }
+                limb >>= self.limb_bits;
+            }
+            if limb != BigUint::from(0u64) {
+                return Err(RefreshAuxError::CarryOverflow {
+                    limb_index: i,
+                    remaining_bits: limb.bits() as usize,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An error returned by [`RefreshAux::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RefreshAuxError {
+    /// The product does not have `num_limbs_l + num_limbs_r - 1` limbs.
+    NumLimbsMismatch { expected: usize, actual: usize },
+    /// The `limb_index`-th limb still has a carry of `remaining_bits` bits after the carries recorded in [`RefreshAux`].
+    CarryOverflow {
+        limb_index: usize,
+        remaining_bits: usize,
+    },
+}
+
+impl std::fmt::Display for RefreshAuxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NumLimbsMismatch { expected, actual } => write!(
+                f,
+                "RefreshAux expects a product of {} limbs, but the product has {} limbs",
+                expected, actual
+            ),
+            Self::CarryOverflow {
+                limb_index,
+                remaining_bits,
+            } => write!(
+                f,
+                "RefreshAux cannot carry the {}-th limb of the product, which has {} bits left; it was likely created with other limb_bits or numbers of limbs",
+                limb_index, remaining_bits
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RefreshAuxError {}
+
+/// The exponent bit length up to which [`ModPowStrategy::Auto`] always picks [`ModPowStrategy::SquareAndMultiply`].
+///
+/// Below it, windows save only a few modular multiplications, e.g., two for `e = 65537`, and square-and-multiply keeps the verifying keys of the circuits with such exponents.
+/// The `mod_pow` benchmark compares the advice cells of both methods around it.
+pub const MOD_POW_WINDOW_CROSSOVER_BITS: usize = 32;
+/// The maximum window width tried by [`ModPowStrategy::Auto`], whose table takes up to `2^MAX_MOD_POW_WINDOW_BITS - 2` modular multiplications.
+pub const MAX_MOD_POW_WINDOW_BITS: usize = 6;
+
+/// The method of [`BigUintConfig::pow_mod_fixed_exp_with_strategy`] to compute a modular power with a fixed exponent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ModPowStrategy {
+    /// Picks [`Self::SquareAndMultiply`] for exponents of at most [`MOD_POW_WINDOW_CROSSOVER_BITS`] bits, and otherwise the method with the fewest modular multiplications by [`Self::num_mul_mods`].
+    #[default]
+    Auto,
+    /// Squares the base for every bit of the exponent and multiplies the accumulator by it for every set bit.
+    SquareAndMultiply,
+    /// Splits the exponent into windows of the given bit width from the most significant one, and multiplies the accumulator by an entry of a table of the powers of the base for every nonzero window.
+    Windowed(usize),
+}
+
+impl ModPowStrategy {
+    /// Returns the strategy that [`Self::Auto`] picks for `e`, or this strategy itself otherwise.
+    pub fn resolve(self, e: &BigUint) -> Self {
+        if self != Self::Auto {
+            return self;
+        }
+        if e.bits() as usize <= MOD_POW_WINDOW_CROSSOVER_BITS {
+            return Self::SquareAndMultiply;
+        }
+        (1..=MAX_MOD_POW_WINDOW_BITS).map(Self::Windowed).fold(
+            Self::SquareAndMultiply,
+            |best, strategy| {
+                if strategy.num_mul_mods(e) < best.num_mul_mods(e) {
+                    strategy
+                } else {
+                    best
+                }
+            },
+        )
+    }
+
+    /// Returns the number of the modular multiplications, including the squares, to compute a power with the fixed exponent `e` by this strategy.
+    ///
+    /// # Panics
+    /// Panics if the window width of [`Self::Windowed`] is zero.
+    pub fn num_mul_mods(self, e: &BigUint) -> usize {
+        match self.resolve(e) {
+            Self::SquareAndMultiply => {
+                let num_bits = e.bits() as usize;
+                num_bits + (0..e.bits()).filter(|i| e.bit(*i)).count()
+            }
+            Self::Windowed(window_bits) => {
+                let digits = window_digits(e, window_bits);
+                if digits.is_empty() {
+                    return 0;
+                }
+                let max_digit = digits.iter().copied().max().unwrap_or(0);
+                let num_nonzero = digits.iter().filter(|digit| **digit != 0).count();
+                max_digit.saturating_sub(1) + (digits.len() - 1) * window_bits + num_nonzero - 1
+            }
+            Self::Auto => unreachable!(),
+        }
+    }
+}
+
+/// Splits `e` into the values of its `window_bits` bits windows from the most significant one, whose first window is the only partial one.
+pub(crate) fn window_digits(e: &BigUint, window_bits: usize) -> Vec<usize> {
+    assert!(
+        (1..usize::BITS as usize).contains(&window_bits),
+        "the window width must be positive and smaller than the bits of usize"
+    );
+    let num_bits = e.bits() as usize;
+    let num_digits = (num_bits + window_bits - 1) / window_bits;
+    (0..num_digits)
+        .rev()
+        .map(|i| {
+            (0..window_bits)
+                .filter(|j| e.bit((i * window_bits + j) as u64))
+                .map(|j| 1 << j)
+                .sum()
+        })
+        .collect()
 }
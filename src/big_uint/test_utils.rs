@@ -0,0 +1,433 @@
+//! A harness circuit and [`proptest`] generators to check the operations of [`BigUintConfig`] against the [`num_bigint`] reference.
+//!
+//! The module is enabled by the `test-utils` feature, so that crates building on [`BigUintConfig`] can run the same checks in their tests.
+//! A [`BigUintCase`] holds its operands as little-endian vectors of 64 bits limbs, which [`proptest`] shrinks to a minimal failing case.
+use super::utils::decompose_biguint;
+use crate::{BigUintConfig, BigUintInstructions, RefreshAux};
+use halo2_base::halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    dev::MockProver,
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+use halo2_base::{
+    gates::{range::RangeConfig, range::RangeStrategy::Vertical, GateInstructions},
+    utils::{biguint_to_fe, PrimeField},
+    AssignedValue, SKIP_FIRST_PASS,
+};
+use num_bigint::BigUint;
+use num_traits::Zero;
+use proptest::{collection::vec, prelude::*};
+use std::marker::PhantomData;
+
+/// The bit length of a limb of the harness.
+pub const HARNESS_LIMB_BITS: usize = 64;
+/// The maximum number of limbs of an operand generated by [`biguint_case`].
+pub const HARNESS_MAX_LIMBS: usize = 4;
+/// The bit length of the exponent of [`BigUintOp::PowMod`].
+pub const HARNESS_EXP_BITS: usize = 8;
+/// The degree of [`BigUintHarnessCircuit`], which fits [`BigUintOp::PowMod`] of [`HARNESS_MAX_LIMBS`] limbs.
+pub const HARNESS_K: u32 = 13;
+
+/// An operation of [`BigUintInstructions`] checked by [`BigUintHarnessCircuit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BigUintOp {
+    /// [`BigUintInstructions::add`] of `a` and `b`.
+    Add,
+    /// [`BigUintInstructions::sub_unsafe`] of `a` and `b`, including its overflow bit.
+    SubUnsafe,
+    /// [`BigUintInstructions::mul`] of `a` and `b`, whose limbs are not carried.
+    Mul,
+    /// [`BigUintInstructions::mul_mod`] of `a` and `b` modulo `n`.
+    MulMod,
+    /// [`BigUintInstructions::pow_mod`] of `a` to the [`HARNESS_EXP_BITS`] bits exponent `e` modulo `n`.
+    PowMod,
+    /// [`BigUintInstructions::refresh`] of the product of `a` and `b`.
+    Refresh,
+}
+
+impl BigUintOp {
+    /// All the operations checked by the harness.
+    pub const ALL: [BigUintOp; 6] = [
+        BigUintOp::Add,
+        BigUintOp::SubUnsafe,
+        BigUintOp::Mul,
+        BigUintOp::MulMod,
+        BigUintOp::PowMod,
+        BigUintOp::Refresh,
+    ];
+
+    /// Returns whether the operation is modulo `n`.
+    pub fn is_modular(&self) -> bool {
+        matches!(self, BigUintOp::MulMod | BigUintOp::PowMod)
+    }
+}
+
+/// Inputs of a [`BigUintOp`], where `a`, `b`, and `n` have the same number of limbs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigUintCase {
+    pub op: BigUintOp,
+    pub a: Vec<u64>,
+    pub b: Vec<u64>,
+    pub n: Vec<u64>,
+    pub e: u64,
+}
+
+impl BigUintCase {
+    /// Returns the number of limbs of the operands.
+    pub fn num_limbs(&self) -> usize {
+        self.a.len()
+    }
+
+    /// Returns the operands `(a, b, n)` given to the chip.
+    ///
+    /// For a modular operation, `n` is raised to 2 if smaller, and `a` and `b` are reduced modulo `n` as [`BigUintInstructions::mul_mod`] requires.
+    /// A modulus of 1 is excluded because [`BigUintInstructions::pow_mod`] returns the unreduced 1 for the zero exponent.
+    pub fn operands(&self) -> (BigUint, BigUint, BigUint) {
+        let (a, b, n) = (
+            limbs_to_biguint(&self.a),
+            limbs_to_biguint(&self.b),
+            limbs_to_biguint(&self.n),
+        );
+        if self.op.is_modular() {
+            let n = n.max(BigUint::from(2u8));
+            (a % &n, b % &n, n)
+        } else {
+            (a, b, n)
+        }
+    }
+
+    /// Returns the instances of [`BigUintHarnessCircuit`], i.e., the limbs of the result computed by [`num_bigint`].
+    pub fn expected_instances<F: PrimeField>(&self) -> Vec<F> {
+        let num_limbs = self.num_limbs();
+        let (a, b, n) = self.operands();
+        match self.op {
+            BigUintOp::Add => decompose_biguint(&(a + b), num_limbs + 1, HARNESS_LIMB_BITS),
+            BigUintOp::SubUnsafe => {
+                // The limbs wrap around modulo `2^(64 * num_limbs)` when `a < b`, which the overflow bit reports.
+                let modulus = BigUint::from(1u8) << (HARNESS_LIMB_BITS * num_limbs);
+                let is_overflow = a < b;
+                let diff = (&modulus + &a - &b) % &modulus;
+                let mut instances = decompose_biguint(&diff, num_limbs, HARNESS_LIMB_BITS);
+                instances.push(F::from(is_overflow as u64));
+                instances
+            }
+            BigUintOp::Mul => {
+                // The product is not carried, so its i-th limb is the i-th coefficient of the product of the limb polynomials.
+                let a_limbs = padded_limbs(&a, num_limbs);
+                let b_limbs = padded_limbs(&b, num_limbs);
+                let mut coeffs = vec![BigUint::zero(); 2 * num_limbs - 1];
+                for (i, a_limb) in a_limbs.iter().enumerate() {
+                    for (j, b_limb) in b_limbs.iter().enumerate() {
+                        coeffs[i + j] += BigUint::from(*a_limb) * *b_limb;
+                    }
+                }
+                debug_assert_eq!(
+                    coeffs
+                        .iter()
+                        .rev()
+                        .fold(BigUint::zero(), |acc, c| (acc << HARNESS_LIMB_BITS) + c),
+                    &a * &b
+                );
+                coeffs.iter().map(biguint_to_fe).collect()
+            }
+            BigUintOp::MulMod => decompose_biguint(&(a * b % n), num_limbs, HARNESS_LIMB_BITS),
+            BigUintOp::PowMod => decompose_biguint(
+                &a.modpow(&BigUint::from(self.e), &n),
+                num_limbs,
+                HARNESS_LIMB_BITS,
+            ),
+            BigUintOp::Refresh => decompose_biguint(&(a * b), 2 * num_limbs, HARNESS_LIMB_BITS),
+        }
+    }
+}
+
+/// Returns the integer whose little-endian 64 bits limbs are `limbs`.
+pub fn limbs_to_biguint(limbs: &[u64]) -> BigUint {
+    limbs
+        .iter()
+        .rev()
+        .fold(BigUint::zero(), |acc, limb| (acc << 64) + *limb)
+}
+
+fn padded_limbs(value: &BigUint, num_limbs: usize) -> Vec<u64> {
+    let mut limbs = value.to_u64_digits();
+    limbs.resize(num_limbs, 0);
+    limbs
+}
+
+/// Returns a strategy of 64 bits limbs biased to the edge values 0, 1, and `2^64 - 1`.
+pub fn limb() -> impl Strategy<Value = u64> {
+    prop_oneof![
+        1 => Just(0u64),
+        1 => Just(1u64),
+        1 => Just(u64::MAX),
+        3 => any::<u64>(),
+    ]
+}
+
+/// Returns a strategy of limb vectors of `num_limbs` limbs, including the adversarial shapes of zero, a single nonzero limb, and all limbs `2^64 - 1`.
+pub fn limbs(num_limbs: usize) -> impl Strategy<Value = Vec<u64>> {
+    prop_oneof![
+        1 => Just(vec![0u64; num_limbs]),
+        1 => limb().prop_map(move |low| {
+            let mut limbs = vec![0u64; num_limbs];
+            limbs[0] = low;
+            limbs
+        }),
+        1 => Just(vec![u64::MAX; num_limbs]),
+        5 => vec(limb(), num_limbs),
+    ]
+}
+
+/// Returns a strategy of [`BigUintCase`] for `op` whose operands have 1 to [`HARNESS_MAX_LIMBS`] limbs.
+pub fn biguint_case(op: BigUintOp) -> impl Strategy<Value = BigUintCase> {
+    (1..=HARNESS_MAX_LIMBS).prop_flat_map(move |num_limbs| {
+        (
+            limbs(num_limbs),
+            limbs(num_limbs),
+            limbs(num_limbs),
+            prop_oneof![
+                Just(0u64),
+                Just((1u64 << HARNESS_EXP_BITS) - 1),
+                0..(1u64 << HARNESS_EXP_BITS)
+            ],
+        )
+            .prop_map(move |(a, b, n, e)| BigUintCase { op, a, b, n, e })
+    })
+}
+
+/// The configuration of [`BigUintHarnessCircuit`].
+#[derive(Debug, Clone)]
+pub struct BigUintHarnessConfig<F: PrimeField> {
+    biguint_config: BigUintConfig<F>,
+    instance: Column<Instance>,
+}
+
+/// A circuit applying the operation of a [`BigUintCase`] and constraining the limbs of its result to the instances.
+///
+/// The instances are given by [`BigUintCase::expected_instances`], so the circuit is satisfied iff the chip agrees with [`num_bigint`].
+#[derive(Debug, Clone)]
+pub struct BigUintHarnessCircuit<F: PrimeField> {
+    case: BigUintCase,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> BigUintHarnessCircuit<F> {
+    const NUM_ADVICE: usize = 8;
+    const NUM_LOOKUP_ADVICE: usize = 2;
+    const LOOKUP_BITS: usize = 12;
+
+    /// Creates a new [`BigUintHarnessCircuit`] for `case`.
+    pub fn new(case: BigUintCase) -> Self {
+        Self {
+            case,
+            _f: PhantomData,
+        }
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for BigUintHarnessCircuit<F> {
+    type Config = BigUintHarnessConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        unimplemented!();
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let range_config = RangeConfig::configure(
+            meta,
+            Vertical,
+            &[Self::NUM_ADVICE],
+            &[Self::NUM_LOOKUP_ADVICE],
+            1,
+            Self::LOOKUP_BITS,
+            0,
+            HARNESS_K as usize,
+        );
+        let biguint_config = BigUintConfig::construct(range_config, HARNESS_LIMB_BITS);
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        BigUintHarnessConfig {
+            biguint_config,
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let biguint_config = &config.biguint_config;
+        biguint_config.range().load_lookup_table(&mut layouter)?;
+        let case = &self.case;
+        let bit_len = case.num_limbs() * HARNESS_LIMB_BITS;
+        let (a, b, n) = case.operands();
+        let mut first_pass = SKIP_FIRST_PASS;
+        let instance_cells = layouter.assign_region(
+            || "biguint harness",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(vec![]);
+                }
+
+                let mut aux = biguint_config.new_context(region);
+                let ctx = &mut aux;
+                let a_assigned =
+                    biguint_config.assign_integer(ctx, Value::known(a.clone()), bit_len)?;
+                let b_assigned =
+                    biguint_config.assign_integer(ctx, Value::known(b.clone()), bit_len)?;
+                let n_assigned =
+                    biguint_config.assign_integer(ctx, Value::known(n.clone()), bit_len)?;
+                let outputs: Vec<AssignedValue<F>> = match case.op {
+                    BigUintOp::Add => biguint_config
+                        .add(ctx, &a_assigned, &b_assigned)?
+                        .limbs()
+                        .to_vec(),
+                    BigUintOp::SubUnsafe => {
+                        let (diff, is_overflow) =
+                            biguint_config.sub_unsafe(ctx, &a_assigned, &b_assigned)?;
+                        let mut outputs = diff.limbs().to_vec();
+                        outputs.push(is_overflow);
+                        outputs
+                    }
+                    BigUintOp::Mul => biguint_config
+                        .mul(ctx, &a_assigned, &b_assigned)?
+                        .limbs()
+                        .to_vec(),
+                    BigUintOp::MulMod => biguint_config
+                        .mul_mod(ctx, &a_assigned, &b_assigned, &n_assigned)?
+                        .limbs()
+                        .to_vec(),
+                    BigUintOp::PowMod => {
+                        let e_assigned = biguint_config
+                            .gate()
+                            .load_witness(ctx, Value::known(F::from(case.e)));
+                        biguint_config
+                            .pow_mod(ctx, &a_assigned, &e_assigned, &n_assigned, HARNESS_EXP_BITS)?
+                            .limbs()
+                            .to_vec()
+                    }
+                    BigUintOp::Refresh => {
+                        let muled = biguint_config.mul(ctx, &a_assigned, &b_assigned)?;
+                        let aux =
+                            RefreshAux::new(HARNESS_LIMB_BITS, case.num_limbs(), case.num_limbs());
+                        biguint_config.refresh(ctx, &muled, &aux)?.limbs().to_vec()
+                    }
+                };
+                biguint_config.range().finalize(ctx);
+                Ok(outputs.iter().map(|limb| limb.cell()).collect::<Vec<_>>())
+            },
+        )?;
+        for (i, cell) in instance_cells.into_iter().enumerate() {
+            layouter.constrain_instance(cell, config.instance, i)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs [`BigUintHarnessCircuit`] for `case` with [`MockProver`], and returns an error describing the unsatisfied constraints if the chip disagrees with [`num_bigint`].
+///
+/// A mismatch in the number of result limbs also fails the check, since the extra cells or instances are left unconstrained to zero.
+pub fn check_biguint_case(case: &BigUintCase) -> Result<(), String> {
+    let instances = case.expected_instances::<Fr>();
+    let circuit = BigUintHarnessCircuit::<Fr>::new(case.clone());
+    let prover = MockProver::run(HARNESS_K, &circuit, vec![instances])
+        .map_err(|e| format!("the synthesis failed: {:?}", e))?;
+    prover
+        .verify()
+        .map_err(|failures| format!("{} constraints failed: {:?}", failures.len(), failures))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_case(case: BigUintCase) {
+        if let Err(e) = check_biguint_case(&case) {
+            panic!("{:?}: {}", case, e);
+        }
+    }
+
+    #[test]
+    fn test_adversarial_shapes() {
+        let shapes = |num_limbs: usize| {
+            let mut single = vec![0u64; num_limbs];
+            single[0] = u64::MAX;
+            vec![vec![0u64; num_limbs], single, vec![u64::MAX; num_limbs]]
+        };
+        for op in BigUintOp::ALL {
+            for num_limbs in [1, HARNESS_MAX_LIMBS] {
+                for a in shapes(num_limbs) {
+                    for b in shapes(num_limbs) {
+                        assert_case(BigUintCase {
+                            op,
+                            a: a.clone(),
+                            b,
+                            n: vec![u64::MAX; num_limbs],
+                            e: (1 << HARNESS_EXP_BITS) - 1,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_wrong_instance_is_rejected() {
+        let case = BigUintCase {
+            op: BigUintOp::MulMod,
+            a: vec![3, 1],
+            b: vec![5, 2],
+            n: vec![7, 9],
+            e: 0,
+        };
+        let mut instances = case.expected_instances::<Fr>();
+        instances[0] += Fr::from(1);
+        let circuit = BigUintHarnessCircuit::<Fr>::new(case);
+        let prover = MockProver::run(HARNESS_K, &circuit, vec![instances]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(256))]
+
+        #[test]
+        fn test_add_matches_num_bigint(case in biguint_case(BigUintOp::Add)) {
+            prop_assert_eq!(check_biguint_case(&case), Ok(()));
+        }
+
+        #[test]
+        fn test_sub_unsafe_matches_num_bigint(case in biguint_case(BigUintOp::SubUnsafe)) {
+            prop_assert_eq!(check_biguint_case(&case), Ok(()));
+        }
+
+        #[test]
+        fn test_mul_matches_num_bigint(case in biguint_case(BigUintOp::Mul)) {
+            prop_assert_eq!(check_biguint_case(&case), Ok(()));
+        }
+
+        #[test]
+        fn test_mul_mod_matches_num_bigint(case in biguint_case(BigUintOp::MulMod)) {
+            prop_assert_eq!(check_biguint_case(&case), Ok(()));
+        }
+
+        #[test]
+        fn test_refresh_matches_num_bigint(case in biguint_case(BigUintOp::Refresh)) {
+            prop_assert_eq!(check_biguint_case(&case), Ok(()));
+        }
+    }
+
+    proptest! {
+        // Each case of pow_mod costs `2 * HARNESS_EXP_BITS` modular multiplications.
+        #![proptest_config(ProptestConfig::with_cases(128))]
+
+        #[test]
+        fn test_pow_mod_matches_num_bigint(case in biguint_case(BigUintOp::PowMod)) {
+            prop_assert_eq!(check_biguint_case(&case), Ok(()));
+        }
+    }
+}
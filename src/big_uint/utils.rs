@@ -6,7 +6,7 @@ use halo2_base::{
     },
 };
 use num_bigint::{BigInt, BigUint, Sign};
-use num_traits::{One, Signed};
+use num_traits::{One, Signed, Zero};
 
 pub fn decompose_bigint<F: PrimeField>(
     e: &BigInt,
@@ -92,6 +92,27 @@ pub(crate) fn big_pow_mod(a: &BigUint, b: &BigUint, n: &BigUint) -> BigUint {
     }
 }
 
+/// Returns the modular inverse of `a` modulo `n`, or `None` if `a` and `n` are not coprime or `n` is zero.
+pub(crate) fn big_mod_inverse(a: &BigUint, n: &BigUint) -> Option<BigUint> {
+    if n.is_zero() {
+        return None;
+    }
+    // The extended Euclidean algorithm keeping only the coefficients of `a`.
+    let n_int = BigInt::from_biguint(Sign::Plus, n.clone());
+    let (mut r0, mut r1) = (n_int.clone(), BigInt::from_biguint(Sign::Plus, a % n));
+    let (mut u0, mut u1) = (BigInt::zero(), BigInt::one());
+    while !r1.is_zero() {
+        let q = &r0 / &r1;
+        (r0, r1) = (r1.clone(), &r0 - &q * &r1);
+        (u0, u1) = (u1.clone(), &u0 - &q * &u1);
+    }
+    if !r0.is_one() {
+        return None;
+    }
+    let u = ((u0 % &n_int) + &n_int) % &n_int;
+    u.to_biguint()
+}
+
 // pub(crate) struct CarryModParams<F: PrimeField> {
 //     pub limb_bits: usize,
 //     pub num_limbs: usize,
@@ -0,0 +1,398 @@
+//! Sets up, proves, and verifies the pkcs1v15 circuits named in [`PKCS1V15_CIRCUITS`] from the command line.
+//!
+//! ```text
+//! halo2-rsa setup --circuit 1024_64 --out-dir ./artifacts [--params params.bin]
+//! halo2-rsa prove --circuit 1024_64 --key key.pem --msg msg.bin --sig sig.bin --out proof.bin [--instances-out instances.json]
+//! halo2-rsa verify --circuit 1024_64 --proof proof.bin [--instances instances.json]
+//! ```
+//!
+//! `setup` writes `params.bin`, `pk.bin`, and `vk.bin` to `--out-dir` and prints the SHA256 digest of `vk.bin`, which is the `vkDigest` of the setup functions of the wasm build.
+//! `--params` may be a ceremony file of a larger degree, e.g., an `.srs` file of the Axiom or the PSE ceremony, which is downsized to the degree of the circuit instead of sampling insecure params.
+//! `prove` and `verify` read them from `--artifacts-dir`, `./artifacts` by default, unless `--params`, `--pk`, or `--vk` is given.
+//! The circuits and the helpers are the same as those of the wasm build, so the keys and the proofs of either are accepted by the other.
+//!
+//! `--key` is a PEM-encoded RSA public key in either the SPKI or the PKCS#1 format, `--msg` is the signed message, or its SHA256 digest for the circuits without the SHA256 chip, and `--sig` is the raw pkcs1v15 signature.
+//! The instances are a JSON array of `0x`-prefixed hex or decimal strings, or an array of one such array as written by `--instances-out`.
+//!
+//! The exit code is 0 on success, 1 if `verify` rejects the proof, 2 for invalid arguments, and 3 for unreadable or invalid inputs, in which case a line starting with `error: ` is written to stderr.
+
+use halo2_base::halo2_proofs::{
+    circuit::Value,
+    dev::MockProver,
+    halo2curves::bn256::{Bn256, Fr},
+    poly::{
+        commitment::{Params, ParamsProver},
+        kzg::commitment::ParamsKZG,
+    },
+};
+use halo2_rsa::{
+    check_params_k, create_proof_with, instances_to_json, keygen_versioned, parse_instances,
+    parse_instances_json, pkcs1v15_circuit_info, preflight_signature, read_params, read_pk_checked,
+    read_srs, verify_proof_bytes, DiagnosticsScope, MultiOpenScheme, Pkcs1v15Circuit,
+    Pkcs1v15Layout, Pkcs1v15_1024_1024EnabledBenchCircuit, Pkcs1v15_1024_128EnabledBenchCircuit,
+    Pkcs1v15_1024_64EnabledBenchCircuit, Pkcs1v15_2048_1024DisabledBenchCircuit,
+    Pkcs1v15_2048_1024EnabledBenchCircuit, Pkcs1v15_2048_128EnabledBenchCircuit,
+    Pkcs1v15_2048_64EnabledBenchCircuit, RSAPubE, RSAPublicKey, RSASignature, TranscriptKind,
+    VerifyError, PKCS1V15_CIRCUITS,
+};
+use num_bigint::BigUint;
+use rand::rngs::OsRng;
+use rsa::{
+    pkcs1::DecodeRsaPublicKey, pkcs8::DecodePublicKey, traits::PublicKeyParts, RsaPublicKey,
+};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+const USAGE: &str = "\
+usage:
+    halo2-rsa setup --circuit <name> --out-dir <dir> [--params <file>]
+    halo2-rsa prove --circuit <name> --key <pem> --msg <file> --sig <file> --out <file>
+                    [--instances-out <file>] [--artifacts-dir <dir>] [--params <file>] [--pk <file>]
+    halo2-rsa verify --circuit <name> --proof <file> [--instances <file>]
+                     [--artifacts-dir <dir>] [--params <file>] [--vk <file>]
+";
+
+/// The artifacts directory read by `prove` and `verify` if `--artifacts-dir` is not given.
+const DEFAULT_ARTIFACTS_DIR: &str = "artifacts";
+
+/// An error of a command, which determines the exit code.
+#[derive(Debug)]
+enum CliError {
+    /// The arguments are invalid.
+    Usage(String),
+    /// An input file cannot be read or is invalid for the circuit.
+    Input(String),
+    /// The proof is rejected by the verifier.
+    Rejected(VerifyError),
+}
+
+impl CliError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Rejected(_) => 1,
+            CliError::Usage(_) => 2,
+            CliError::Input(_) => 3,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(message) => write!(f, "{}", message),
+            CliError::Input(message) => write!(f, "{}", message),
+            CliError::Rejected(e) => write!(f, "the proof is invalid: {}", e),
+        }
+    }
+}
+
+/// The `--name value` options of a command.
+struct Options {
+    values: Vec<(String, String)>,
+}
+
+impl Options {
+    /// Parses `args` as pairs of an option in `allowed` and its value.
+    fn parse(args: &[String], allowed: &[&str]) -> Result<Self, CliError> {
+        let mut values: Vec<(String, String)> = vec![];
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let name = arg
+                .strip_prefix("--")
+                .filter(|name| allowed.contains(name))
+                .ok_or_else(|| CliError::Usage(format!("unexpected argument {:?}", arg)))?;
+            if values.iter().any(|(given, _)| given == name) {
+                return Err(CliError::Usage(format!("--{} was given twice", name)));
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| CliError::Usage(format!("--{} needs a value", name)))?;
+            values.push((name.to_string(), value.clone()));
+        }
+        Ok(Self { values })
+    }
+
+    fn optional(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(given, _)| given == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn required(&self, name: &str) -> Result<&str, CliError> {
+        self.optional(name)
+            .ok_or_else(|| CliError::Usage(format!("--{} is required", name)))
+    }
+
+    /// Returns the path of `name` if given, and otherwise the file `file_name` in the artifacts directory.
+    fn artifact(&self, name: &str, file_name: &str) -> PathBuf {
+        let artifacts_dir = self
+            .optional("artifacts-dir")
+            .unwrap_or(DEFAULT_ARTIFACTS_DIR);
+        match self.optional(name) {
+            Some(path) => PathBuf::from(path),
+            None => Path::new(artifacts_dir).join(file_name),
+        }
+    }
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, CliError> {
+    fs::read(path).map_err(|e| CliError::Input(format!("failed to read {}: {}", path.display(), e)))
+}
+
+fn write_file(path: &Path, bytes: &[u8]) -> Result<(), CliError> {
+    fs::write(path, bytes)
+        .map_err(|e| CliError::Input(format!("failed to write {}: {}", path.display(), e)))
+}
+
+/// The commands run on each circuit of [`PKCS1V15_CIRCUITS`].
+trait CircuitCommands {
+    fn run_setup(options: &Options) -> Result<(), CliError>;
+    fn run_prove(options: &Options) -> Result<(), CliError>;
+    fn run_verify(options: &Options) -> Result<(), CliError>;
+}
+
+impl<const BITS_LEN: usize, const MSG_LEN: usize, const SHA2: bool, L: Pkcs1v15Layout>
+    CircuitCommands for Pkcs1v15Circuit<Fr, BITS_LEN, MSG_LEN, SHA2, L>
+{
+    fn run_setup(options: &Options) -> Result<(), CliError> {
+        let out_dir = PathBuf::from(options.required("out-dir")?);
+        let params = match options.optional("params") {
+            Some(path) => {
+                let path = Path::new(path);
+                let mut params = read_srs(&read_file(path)?).map_err(|e| {
+                    CliError::Input(format!("invalid params {}: {}", path.display(), e))
+                })?;
+                if params.k() < Self::min_k() {
+                    return Err(CliError::Input(format!(
+                        "params for k = {} were given, but the circuit requires k >= {}",
+                        params.k(),
+                        Self::min_k()
+                    )));
+                }
+                if params.k() > Self::min_k() {
+                    params.downsize(Self::min_k());
+                }
+                params
+            }
+            None => ParamsKZG::<Bn256>::setup(Self::min_k(), OsRng),
+        };
+        let mut params_bytes = vec![];
+        params
+            .write(&mut params_bytes)
+            .map_err(|e| CliError::Input(format!("failed to serialize params: {}", e)))?;
+        let (pk_bytes, vk_bytes) = keygen_versioned(&params, &Self::default())
+            .map_err(|e| CliError::Input(e.to_string()))?;
+
+        fs::create_dir_all(&out_dir).map_err(|e| {
+            CliError::Input(format!("failed to create {}: {}", out_dir.display(), e))
+        })?;
+        write_file(&out_dir.join("params.bin"), &params_bytes)?;
+        write_file(&out_dir.join("pk.bin"), &pk_bytes)?;
+        write_file(&out_dir.join("vk.bin"), &vk_bytes)?;
+        let vk_digest = Sha256::digest(&vk_bytes)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+        println!("{}", vk_digest);
+        Ok(())
+    }
+
+    fn run_prove(options: &Options) -> Result<(), CliError> {
+        let key_path = Path::new(options.required("key")?);
+        let msg_path = Path::new(options.required("msg")?);
+        let sig_path = Path::new(options.required("sig")?);
+        let out_path = Path::new(options.required("out")?);
+
+        let pem = String::from_utf8(read_file(key_path)?)
+            .map_err(|_| CliError::Input(format!("{} is not a PEM file", key_path.display())))?;
+        let public_key = RsaPublicKey::from_public_key_pem(&pem)
+            .or_else(|_| RsaPublicKey::from_pkcs1_pem(&pem))
+            .map_err(|e| {
+                CliError::Input(format!("invalid public key {}: {}", key_path.display(), e))
+            })?;
+        // A modulus with leading zero bits, e.g., 2047 bits, is accepted as long as its signatures have the same length.
+        if public_key.n().bits() > BITS_LEN || public_key.size() != BITS_LEN / 8 {
+            return Err(CliError::Input(format!(
+                "the public key must be a {} bits key, but a {} bits key was given",
+                BITS_LEN,
+                public_key.n().bits()
+            )));
+        }
+        let key_e = BigUint::from_bytes_be(&public_key.e().to_bytes_be());
+        if key_e != BigUint::from(Self::DEFAULT_E) {
+            return Err(CliError::Input(format!(
+                "the public key has the exponent {}, but the circuit uses {}",
+                key_e,
+                Self::DEFAULT_E
+            )));
+        }
+        let msg = read_file(msg_path)?;
+        Self::INFO
+            .check_msg_len(&msg)
+            .map_err(|e| CliError::Input(e.to_string()))?;
+        let signature = read_file(sig_path)?;
+        preflight_signature(&public_key, &msg, SHA2, &signature).map_err(CliError::Input)?;
+
+        let params_path = options.artifact("params", "params.bin");
+        let params = read_params(&read_file(&params_path)?).map_err(|e| {
+            CliError::Input(format!("invalid params {}: {}", params_path.display(), e))
+        })?;
+        let pk_path = options.artifact("pk", "pk.bin");
+        let pk = read_pk_checked::<Self>(&read_file(&pk_path)?).map_err(|e| {
+            CliError::Input(format!("invalid proving key {}: {}", pk_path.display(), e))
+        })?;
+        check_params_k(&params, pk.get_vk(), Self::min_k())
+            .map_err(|e| CliError::Input(e.to_string()))?;
+
+        let n = BigUint::from_bytes_be(&public_key.n().to_bytes_be());
+        let public_key = RSAPublicKey::new(
+            Value::known(n),
+            RSAPubE::Fix(BigUint::from(Self::DEFAULT_E)),
+        );
+        let signature = RSASignature::from_be_bytes(&signature, BITS_LEN)
+            .expect("the length of the signature was checked by preflight_signature");
+        let instances = Self::instances(&msg);
+        let circuit = Self::new(signature, public_key, msg);
+        let diagnostics = DiagnosticsScope::enter();
+        let prover = MockProver::run(Self::K, &circuit, instances.clone())
+            .map_err(|e| CliError::Input(format!("failed to synthesize the circuit: {:?}", e)))?;
+        if prover.verify().is_err() {
+            return Err(CliError::Input(format!(
+                "the constraints are not satisfied: {}",
+                diagnostics.diagnose_failure(&prover)
+            )));
+        }
+        drop(diagnostics);
+        let proof = create_proof_with(
+            &params,
+            &pk,
+            circuit,
+            &instances.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+            TranscriptKind::Blake2b,
+            MultiOpenScheme::Gwc,
+        )
+        .map_err(|e| CliError::Input(format!("failed to create a proof: {:?}", e)))?;
+        write_file(out_path, &proof)?;
+        if let Some(path) = options.optional("instances-out") {
+            write_file(Path::new(path), instances_to_json(&instances).as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn run_verify(options: &Options) -> Result<(), CliError> {
+        let proof = read_file(Path::new(options.required("proof")?))?;
+        let num_instances = Self::INFO.num_instances;
+        let instances = match options.optional("instances") {
+            Some(path) => {
+                let json = String::from_utf8(read_file(Path::new(path))?)
+                    .map_err(|_| CliError::Input(format!("{} is not a JSON file", path)))?;
+                parse_instances_json(&json, num_instances)
+            }
+            None => parse_instances::<&str>(&[], num_instances),
+        }
+        .map_err(|e| CliError::Input(format!("invalid instances: {}", e)))?;
+
+        let params = read_file(&options.artifact("params", "params.bin"))?;
+        let vk = read_file(&options.artifact("vk", "vk.bin"))?;
+        match verify_proof_bytes::<Self>(
+            &params,
+            &vk,
+            Self::min_k(),
+            &proof,
+            &instances.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+            TranscriptKind::Blake2b,
+            MultiOpenScheme::Gwc,
+        ) {
+            Ok(()) => {
+                println!("valid");
+                Ok(())
+            }
+            Err(e @ (VerifyError::InvalidParams(_) | VerifyError::InvalidVerifyingKey(_))) => {
+                Err(CliError::Input(e.to_string()))
+            }
+            Err(e) => Err(CliError::Rejected(e)),
+        }
+    }
+}
+
+/// Calls `$command` of the circuit named `$circuit_name` in [`PKCS1V15_CIRCUITS`].
+macro_rules! dispatch {
+    ($circuit_name:expr, $command:ident, $options:expr) => {
+        match $circuit_name {
+            "1024_64" => Pkcs1v15_1024_64EnabledBenchCircuit::<Fr>::$command($options),
+            "1024_128" => Pkcs1v15_1024_128EnabledBenchCircuit::<Fr>::$command($options),
+            "1024_1024" => Pkcs1v15_1024_1024EnabledBenchCircuit::<Fr>::$command($options),
+            "2048_64" => Pkcs1v15_2048_64EnabledBenchCircuit::<Fr>::$command($options),
+            "2048_128" => Pkcs1v15_2048_128EnabledBenchCircuit::<Fr>::$command($options),
+            "2048_1024" => Pkcs1v15_2048_1024EnabledBenchCircuit::<Fr>::$command($options),
+            "no_sha2_2048_1024" => Pkcs1v15_2048_1024DisabledBenchCircuit::<Fr>::$command($options),
+            _ => unreachable!("the circuit name was checked"),
+        }
+    };
+}
+
+fn run(args: &[String]) -> Result<(), CliError> {
+    let (command, args) = args
+        .split_first()
+        .ok_or_else(|| CliError::Usage("no command was given".to_string()))?;
+    let allowed: &[&str] = match command.as_str() {
+        "-h" | "--help" | "help" => {
+            print!("{}", USAGE);
+            return Ok(());
+        }
+        "setup" => &["circuit", "out-dir", "params"],
+        "prove" => &[
+            "circuit",
+            "key",
+            "msg",
+            "sig",
+            "out",
+            "instances-out",
+            "artifacts-dir",
+            "params",
+            "pk",
+        ],
+        "verify" => &[
+            "circuit",
+            "proof",
+            "instances",
+            "artifacts-dir",
+            "params",
+            "vk",
+        ],
+        _ => return Err(CliError::Usage(format!("unknown command {:?}", command))),
+    };
+    let options = Options::parse(args, allowed)?;
+    let circuit_name = options.required("circuit")?;
+    if pkcs1v15_circuit_info(circuit_name).is_none() {
+        let names = PKCS1V15_CIRCUITS
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>();
+        return Err(CliError::Usage(format!(
+            "unknown circuit {:?}, expected one of {}",
+            circuit_name,
+            names.join(", ")
+        )));
+    }
+    match command.as_str() {
+        "setup" => dispatch!(circuit_name, run_setup, &options),
+        "prove" => dispatch!(circuit_name, run_prove, &options),
+        _ => dispatch!(circuit_name, run_verify, &options),
+    }
+}
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if let Err(e) = run(&args) {
+        eprintln!("error: {}", e);
+        if let CliError::Usage(_) = e {
+            eprint!("{}", USAGE);
+        }
+        process::exit(e.exit_code());
+    }
+}
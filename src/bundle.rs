@@ -0,0 +1,714 @@
+use crate::{
+    create_proof_with, decode_hex, encode_hex, verify_proof_with, vk_fingerprint_of,
+    MultiOpenScheme, TranscriptKind, VerifyError,
+};
+use base64::engine::{general_purpose::STANDARD, Engine};
+use halo2_base::halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{Circuit, Error, ProvingKey, VerifyingKey},
+    poly::kzg::commitment::ParamsKZG,
+};
+use halo2_base::utils::{biguint_to_fe, fe_to_biguint, modulus};
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read, Write};
+
+/// The magic bytes at the head of the proof bundles encoded by [`ProofBundle::to_bytes`].
+pub const PROOF_BUNDLE_MAGIC: [u8; 4] = *b"HRPB";
+/// The version of the proof bundles written by this crate. [`ProofBundle::from_bytes`] and [`verify_bundle`] reject the other versions.
+///
+/// The bundles of the version `1` lacked [`ProofBundle::vk_fingerprint`].
+pub const PROOF_BUNDLE_VERSION: u32 = 2;
+
+/// A self-describing proof, which carries the circuit, the verifying key, the transcript, the multi-open scheme, and the instances it was created with.
+///
+/// A relayer can pass it around as a whole and the verifier needs only the params and the verifying key besides it.
+/// It is serialized with serde, where the instances are `0x`-prefixed hex strings and the proof is base64, or in the compact binary encoding of [`ProofBundle::to_bytes`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofBundle {
+    /// The version of the bundle format, [`PROOF_BUNDLE_VERSION`] for the bundles created by this crate.
+    pub version: u32,
+    /// The identifier of the circuit returned by [`ProofBundle::circuit_id_of`].
+    pub circuit_id: String,
+    /// The fingerprint of the verifying key of the proof given by [`crate::vk_fingerprint`], which is serialized as a hex string.
+    #[serde(with = "hex_fingerprint")]
+    pub vk_fingerprint: [u8; 32],
+    /// The kind of transcript the proof was created with.
+    pub transcript: TranscriptKind,
+    /// The multi-open scheme the proof was created with.
+    pub scheme: MultiOpenScheme,
+    /// The values of the instance columns.
+    #[serde(with = "hex_instances")]
+    pub instances: Vec<Vec<Fr>>,
+    /// The bytes of the proof, which the legacy verify functions, e.g., [`verify_proof_with`], accept as they are.
+    #[serde(with = "base64_bytes")]
+    pub proof: Vec<u8>,
+}
+
+impl ProofBundle {
+    /// Returns the identifier of the circuit `C`, which is its type name as recorded in the header of the versioned keys.
+    pub fn circuit_id_of<C: Circuit<Fr>>() -> &'static str {
+        std::any::type_name::<C>()
+    }
+
+    /// Encodes the bundle into bytes.
+    ///
+    /// The encoding is [`PROOF_BUNDLE_MAGIC`], the version as a 4 bytes little-endian integer, the circuit id prefixed with its 1 byte length, the 32 bytes of the fingerprint of the verifying key, the transcript and the scheme as 1 byte each,
+    /// the number of the instance columns, and for each column the number of its rows followed by the 32 bytes little-endian values, and the proof prefixed with its length.
+    /// Every count and length is a 4 bytes little-endian integer unless otherwise noted.
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut bytes = PROOF_BUNDLE_MAGIC.to_vec();
+        bytes.write_all(&self.version.to_le_bytes())?;
+        let circuit_id_len = u8::try_from(self.circuit_id.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too long circuit id"))?;
+        bytes.write_all(&[circuit_id_len])?;
+        bytes.write_all(self.circuit_id.as_bytes())?;
+        bytes.write_all(&self.vk_fingerprint)?;
+        bytes.write_all(&[self.transcript as u8, self.scheme as u8])?;
+        write_len(&mut bytes, self.instances.len())?;
+        for column in self.instances.iter() {
+            write_len(&mut bytes, column.len())?;
+            for value in column.iter() {
+                let mut repr = fe_to_biguint(value).to_bytes_le();
+                repr.resize(32, 0);
+                bytes.write_all(&repr)?;
+            }
+        }
+        write_len(&mut bytes, self.proof.len())?;
+        bytes.write_all(&self.proof)?;
+        Ok(bytes)
+    }
+
+    /// Decodes a bundle encoded by [`ProofBundle::to_bytes`].
+    ///
+    /// Returns an [`io::ErrorKind::InvalidData`] error if the bytes lack [`PROOF_BUNDLE_MAGIC`], the version is not [`PROOF_BUNDLE_VERSION`], a field is out of range, or bytes remain after the proof.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut reader = bytes;
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != PROOF_BUNDLE_MAGIC {
+            return Err(invalid_data("the bytes are not a proof bundle".to_string()));
+        }
+        let version = read_u32(&mut reader)?;
+        if version != PROOF_BUNDLE_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported proof bundle version {}, expected {}",
+                version, PROOF_BUNDLE_VERSION
+            )));
+        }
+        let mut circuit_id_len = [0u8; 1];
+        reader.read_exact(&mut circuit_id_len)?;
+        let mut circuit_id = vec![0u8; circuit_id_len[0] as usize];
+        reader.read_exact(&mut circuit_id)?;
+        let circuit_id = String::from_utf8(circuit_id)
+            .map_err(|_| invalid_data("the circuit id is not UTF-8".to_string()))?;
+        let mut vk_fingerprint = [0u8; 32];
+        reader.read_exact(&mut vk_fingerprint)?;
+        let mut kinds = [0u8; 2];
+        reader.read_exact(&mut kinds)?;
+        let transcript = match kinds[0] {
+            0 => TranscriptKind::Blake2b,
+            1 => TranscriptKind::Keccak256,
+            #[cfg(feature = "aggregation")]
+            2 => TranscriptKind::Poseidon,
+            kind => return Err(invalid_data(format!("unknown transcript kind {}", kind))),
+        };
+        let scheme = match kinds[1] {
+            0 => MultiOpenScheme::Gwc,
+            1 => MultiOpenScheme::Shplonk,
+            scheme => {
+                return Err(invalid_data(format!(
+                    "unknown multi-open scheme {}",
+                    scheme
+                )))
+            }
+        };
+        let num_columns = read_len(&mut reader)?;
+        let mut instances = vec![];
+        for _ in 0..num_columns {
+            let num_rows = read_len(&mut reader)?;
+            let mut column = vec![];
+            for _ in 0..num_rows {
+                let mut repr = [0u8; 32];
+                reader.read_exact(&mut repr)?;
+                column.push(fr_from_biguint(BigUint::from_bytes_le(&repr))?);
+            }
+            instances.push(column);
+        }
+        let proof_len = read_len(&mut reader)?;
+        let mut proof = vec![0u8; proof_len];
+        reader.read_exact(&mut proof)?;
+        if !reader.is_empty() {
+            return Err(invalid_data(format!(
+                "{} bytes remain after the proof bundle",
+                reader.len()
+            )));
+        }
+        Ok(Self {
+            version,
+            circuit_id,
+            vk_fingerprint,
+            transcript,
+            scheme,
+            instances,
+            proof,
+        })
+    }
+}
+
+/// Creates a proof of `circuit` in the same way as [`create_proof_with`] and bundles it with the circuit id, the fingerprint of the verifying key of `pk`, `transcript`, `scheme`, and `instances`.
+pub fn create_proof_bundle<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: Vec<Vec<Fr>>,
+    transcript: TranscriptKind,
+    scheme: MultiOpenScheme,
+) -> Result<ProofBundle, Error> {
+    let instance_refs = instances
+        .iter()
+        .map(|column| &column[..])
+        .collect::<Vec<_>>();
+    let vk_fingerprint = vk_fingerprint_of::<C>(pk.get_vk()).map_err(Error::Transcript)?;
+    let proof = create_proof_with(params, pk, circuit, &instance_refs, transcript, scheme)?;
+    Ok(ProofBundle {
+        version: PROOF_BUNDLE_VERSION,
+        circuit_id: ProofBundle::circuit_id_of::<C>().to_string(),
+        vk_fingerprint,
+        transcript,
+        scheme,
+        instances,
+        proof,
+    })
+}
+
+/// Verifies the proof in `bundle` for the circuit `C` with the transcript, the scheme, and the instances recorded in `bundle`.
+///
+/// # Return values
+/// Returns [`VerifyError::UnsupportedBundleVersion`] if the version of `bundle` is not [`PROOF_BUNDLE_VERSION`], [`VerifyError::CircuitMismatch`] if it was created for another circuit,
+/// [`VerifyError::VerifyingKeyMismatch`] if it was created with a verifying key other than `vk`, e.g., one generated for other params or another size of the circuit,
+/// and otherwise the result of [`verify_proof_with`], e.g., [`VerifyError::ConstraintCheckFailed`] if the instances were tampered with.
+/// The version, the circuit, and the verifying key are checked before the proof is read, so a mismatched bundle costs no pairing.
+pub fn verify_bundle<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    bundle: &ProofBundle,
+) -> Result<(), VerifyError> {
+    if bundle.version != PROOF_BUNDLE_VERSION {
+        return Err(VerifyError::UnsupportedBundleVersion(bundle.version));
+    }
+    let expected = ProofBundle::circuit_id_of::<C>();
+    if bundle.circuit_id != expected {
+        return Err(VerifyError::CircuitMismatch {
+            expected: expected.to_string(),
+            actual: bundle.circuit_id.clone(),
+        });
+    }
+    let expected = vk_fingerprint_of::<C>(vk).map_err(VerifyError::InvalidVerifyingKey)?;
+    if bundle.vk_fingerprint != expected {
+        return Err(VerifyError::VerifyingKeyMismatch {
+            expected,
+            actual: bundle.vk_fingerprint,
+        });
+    }
+    let instances = bundle
+        .instances
+        .iter()
+        .map(|column| &column[..])
+        .collect::<Vec<_>>();
+    verify_proof_with(
+        params,
+        vk,
+        &bundle.proof,
+        &instances,
+        bundle.transcript,
+        bundle.scheme,
+    )
+}
+
+/// Parses an instance given as a `0x`-prefixed hex string or a decimal string, e.g., one of the strings serialized in a [`ProofBundle`].
+///
+/// Returns an error if `value` is not a number or is not less than the field modulus.
+pub fn parse_instance(value: &str) -> io::Result<Fr> {
+    let parsed = match value.strip_prefix("0x") {
+        Some(digits) => BigUint::parse_bytes(digits.as_bytes(), 16),
+        None => BigUint::parse_bytes(value.as_bytes(), 10),
+    };
+    let value = parsed.ok_or_else(|| {
+        invalid_data(format!(
+            "the instance {:?} is neither a 0x-prefixed hex nor a decimal number",
+            value
+        ))
+    })?;
+    fr_from_biguint(value)
+}
+
+/// Formats an instance as the `0x`-prefixed hex string of its 32 bytes big-endian value, which [`parse_instance`] reads back.
+pub fn format_instance(value: &Fr) -> String {
+    format!("0x{:064x}", fe_to_biguint(value))
+}
+
+/// Parses the public instances of a circuit with `num_instances` instances in one column, each of which is given as a string accepted by [`parse_instance`].
+///
+/// Returns the values of the instance columns, which are none if `num_instances` is zero.
+/// Returns an error if the number of `values` differs from `num_instances` or a value cannot be parsed.
+pub fn parse_instances<S: AsRef<str>>(
+    values: &[S],
+    num_instances: usize,
+) -> io::Result<Vec<Vec<Fr>>> {
+    if values.len() != num_instances {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} public instances are expected, but {} were given",
+                num_instances,
+                values.len()
+            ),
+        ));
+    }
+    let column = values
+        .iter()
+        .map(|value| parse_instance(value.as_ref()))
+        .collect::<io::Result<Vec<_>>>()?;
+    Ok(if num_instances == 0 {
+        vec![]
+    } else {
+        vec![column]
+    })
+}
+
+/// Parses the public instances in the same way as [`parse_instances`] from a JSON array of strings, or an array of one such array as written by [`instances_to_json`].
+pub fn parse_instances_json(json: &str, num_instances: usize) -> io::Result<Vec<Vec<Fr>>> {
+    let values = match serde_json::from_str::<Vec<Vec<String>>>(json) {
+        Ok(columns) if columns.len() <= 1 => columns.into_iter().flatten().collect(),
+        Ok(columns) => {
+            return Err(invalid_data(format!(
+                "the instances must be in one column, but {} columns were given",
+                columns.len()
+            )))
+        }
+        Err(_) => serde_json::from_str::<Vec<String>>(json).map_err(|e| {
+            invalid_data(format!(
+                "the instances must be a JSON array of hex or decimal strings: {}",
+                e
+            ))
+        })?,
+    };
+    parse_instances(&values, num_instances)
+}
+
+/// Serializes the values of the instance columns as a JSON array of arrays of the strings given by [`format_instance`], which [`parse_instances_json`] reads back.
+pub fn instances_to_json(instances: &[Vec<Fr>]) -> String {
+    let columns = instances
+        .iter()
+        .map(|column| column.iter().map(format_instance).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    serde_json::to_string(&columns).expect("strings are always serializable")
+}
+
+fn write_len<W: Write>(writer: &mut W, len: usize) -> io::Result<()> {
+    let len = u32::try_from(len)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too long proof bundle field"))?;
+    writer.write_all(&len.to_le_bytes())
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_len(reader: &mut &[u8]) -> io::Result<usize> {
+    let len = read_u32(reader)? as usize;
+    // Every count is followed by at least one byte per item, so a larger count is malformed rather than a reason to allocate.
+    if len > reader.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "the proof bundle is truncated",
+        ));
+    }
+    Ok(len)
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn fr_from_biguint(value: BigUint) -> io::Result<Fr> {
+    if value >= modulus::<Fr>() {
+        return Err(invalid_data(format!(
+            "the instance 0x{:x} is not less than the field modulus",
+            value
+        )));
+    }
+    Ok(biguint_to_fe(&value))
+}
+
+/// Serializes the instances as `0x`-prefixed hex strings of 32 bytes big-endian field elements.
+mod hex_instances {
+    use super::*;
+    use serde::{de::Error as _, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        instances: &[Vec<Fr>],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        instances
+            .iter()
+            .map(|column| column.iter().map(format_instance).collect::<Vec<_>>())
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Vec<Fr>>, D::Error> {
+        let instances = Vec::<Vec<String>>::deserialize(deserializer)?;
+        instances
+            .iter()
+            .map(|column| {
+                column
+                    .iter()
+                    .map(|hex| {
+                        let digits = hex.strip_prefix("0x").unwrap_or(hex);
+                        let value =
+                            BigUint::parse_bytes(digits.as_bytes(), 16).ok_or_else(|| {
+                                D::Error::custom(format!("the instance {:?} is not hex", hex))
+                            })?;
+                        fr_from_biguint(value).map_err(D::Error::custom)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Serializes the fingerprint of the verifying key as a hex string of 32 bytes.
+mod hex_fingerprint {
+    use super::*;
+    use serde::{de::Error as _, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        fingerprint: &[u8; 32],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&encode_hex(fingerprint))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 32], D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        let bytes = decode_hex(&hex).map_err(D::Error::custom)?;
+        <[u8; 32]>::try_from(bytes).map_err(|bytes| {
+            D::Error::custom(format!(
+                "the fingerprint of a verifying key is 32 bytes, but {} bytes were given",
+                bytes.len()
+            ))
+        })
+    }
+}
+
+/// Serializes the proof as a base64 string.
+mod base64_bytes {
+    use super::*;
+    use serde::{de::Error as _, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        STANDARD.decode(encoded).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{verify_proof_with_transcript, BigUintConfig, BigUintInstructions};
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{keygen_pk, keygen_vk, Column, ConstraintSystem, Instance},
+        poly::commitment::ParamsProver,
+    };
+    use halo2_base::{
+        gates::range::{RangeConfig, RangeStrategy::Vertical},
+        gates::{GateInstructions, RangeInstructions},
+        utils::PrimeField,
+        QuantumCell::Existing,
+        SKIP_FIRST_PASS,
+    };
+    use rand::rngs::OsRng;
+
+    const K: u32 = 10;
+
+    #[derive(Clone)]
+    struct TestProductConfig<F: PrimeField> {
+        biguint_config: BigUintConfig<F>,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes the product of `a` and `b` as the only instance.
+    #[derive(Default)]
+    struct TestProductCircuit {
+        a: u64,
+        b: u64,
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestProductCircuit {
+        type Config = TestProductConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config = RangeConfig::configure(
+                meta,
+                Vertical,
+                &[1],
+                &[1],
+                1,
+                K as usize - 1,
+                0,
+                K as usize,
+            );
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            TestProductConfig {
+                biguint_config: BigUintConfig::construct(range_config, 64),
+                instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let biguint_config = config.biguint_config;
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let cell = layouter.assign_region(
+                || "product",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(None);
+                    }
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    let gate = biguint_config.gate();
+                    let a = gate.load_witness(ctx, Value::known(F::from(self.a)));
+                    let b = gate.load_witness(ctx, Value::known(F::from(self.b)));
+                    let product = gate.mul(ctx, Existing(&a), Existing(&b));
+                    biguint_config.range().finalize(ctx);
+                    Ok(Some(product.cell()))
+                },
+            )?;
+            if let Some(cell) = cell {
+                layouter.constrain_instance(cell, config.instance, 0)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn setup() -> (
+        ParamsKZG<Bn256>,
+        ProvingKey<G1Affine>,
+        VerifyingKey<G1Affine>,
+    ) {
+        let params = ParamsKZG::<Bn256>::setup(K, OsRng);
+        let vk = keygen_vk(&params, &TestProductCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &TestProductCircuit::default()).unwrap();
+        (params, pk, vk)
+    }
+
+    fn prove(
+        params: &ParamsKZG<Bn256>,
+        pk: &ProvingKey<G1Affine>,
+        transcript: TranscriptKind,
+        scheme: MultiOpenScheme,
+    ) -> ProofBundle {
+        let circuit = TestProductCircuit { a: 6, b: 7 };
+        create_proof_bundle(
+            params,
+            pk,
+            circuit,
+            vec![vec![Fr::from(42)]],
+            transcript,
+            scheme,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_proof_bundle_roundtrip() {
+        let (params, pk, vk) = setup();
+        for (transcript, scheme) in [
+            (TranscriptKind::Blake2b, MultiOpenScheme::Gwc),
+            (TranscriptKind::Keccak256, MultiOpenScheme::Shplonk),
+        ] {
+            let bundle = prove(&params, &pk, transcript, scheme);
+            assert_eq!(bundle.version, PROOF_BUNDLE_VERSION);
+            assert_eq!(
+                bundle.circuit_id,
+                ProofBundle::circuit_id_of::<TestProductCircuit>()
+            );
+            verify_bundle::<TestProductCircuit>(&params, &vk, &bundle).unwrap();
+
+            let bytes = bundle.to_bytes().unwrap();
+            assert!(bytes.starts_with(&PROOF_BUNDLE_MAGIC));
+            let decoded = ProofBundle::from_bytes(&bytes).unwrap();
+            assert_eq!(decoded, bundle);
+            verify_bundle::<TestProductCircuit>(&params, &vk, &decoded).unwrap();
+
+            let json = serde_json::to_string(&bundle).unwrap();
+            assert!(json.contains(&format!("\"0x{:064x}\"", 42)));
+            assert_eq!(serde_json::from_str::<ProofBundle>(&json).unwrap(), bundle);
+
+            assert!(ProofBundle::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+            let mut trailing = bytes.clone();
+            trailing.push(0);
+            assert!(ProofBundle::from_bytes(&trailing).is_err());
+        }
+    }
+
+    #[test]
+    fn test_proof_bundle_rejects_tampering() {
+        let (params, pk, vk) = setup();
+        let bundle = prove(&params, &pk, TranscriptKind::Blake2b, MultiOpenScheme::Gwc);
+
+        let mut tampered = bundle.clone();
+        tampered.instances[0][0] = Fr::from(43);
+        assert!(matches!(
+            verify_bundle::<TestProductCircuit>(&params, &vk, &tampered),
+            Err(VerifyError::ConstraintCheckFailed)
+        ));
+        // The tampered instance survives the binary encoding and is still rejected.
+        let decoded = ProofBundle::from_bytes(&tampered.to_bytes().unwrap()).unwrap();
+        assert!(verify_bundle::<TestProductCircuit>(&params, &vk, &decoded).is_err());
+
+        let mut other_circuit = bundle.clone();
+        other_circuit.circuit_id = "another circuit".to_string();
+        let err = verify_bundle::<TestProductCircuit>(&params, &vk, &other_circuit).unwrap_err();
+        assert!(matches!(err, VerifyError::CircuitMismatch { .. }));
+        assert_eq!(err.code(), "circuit_mismatch");
+
+        let mut future = bundle.clone();
+        future.version = PROOF_BUNDLE_VERSION + 1;
+        assert!(matches!(
+            verify_bundle::<TestProductCircuit>(&params, &vk, &future),
+            Err(VerifyError::UnsupportedBundleVersion(version)) if version == PROOF_BUNDLE_VERSION + 1
+        ));
+        let err = ProofBundle::from_bytes(&future.to_bytes().unwrap()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_proof_bundle_rejects_other_vk() {
+        let (params, pk, vk) = setup();
+        let bundle = prove(&params, &pk, TranscriptKind::Blake2b, MultiOpenScheme::Gwc);
+        assert_eq!(
+            bundle.vk_fingerprint,
+            vk_fingerprint_of::<TestProductCircuit>(&vk).unwrap()
+        );
+        let json = serde_json::to_string(&bundle).unwrap();
+        assert!(json.contains(&format!("\"{}\"", encode_hex(&bundle.vk_fingerprint))));
+
+        // The keys generated for other params are another verifying key of the same circuit.
+        let (other_params, _, other_vk) = setup();
+        let err =
+            verify_bundle::<TestProductCircuit>(&other_params, &other_vk, &bundle).unwrap_err();
+        assert!(matches!(err, VerifyError::VerifyingKeyMismatch { .. }));
+        assert_eq!(err.code(), "verifying_key_mismatch");
+
+        let mut tampered = bundle.clone();
+        tampered.vk_fingerprint[0] ^= 1;
+        let decoded = ProofBundle::from_bytes(&tampered.to_bytes().unwrap()).unwrap();
+        assert!(matches!(
+            verify_bundle::<TestProductCircuit>(&params, &vk, &decoded),
+            Err(VerifyError::VerifyingKeyMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_bare_proof_still_verifies() {
+        let (params, pk, vk) = setup();
+        let bundle = prove(&params, &pk, TranscriptKind::Blake2b, MultiOpenScheme::Gwc);
+        let instances = [Fr::from(42)];
+        verify_proof_with_transcript(
+            &params,
+            &vk,
+            &bundle.proof,
+            &[&instances],
+            TranscriptKind::Blake2b,
+        )
+        .unwrap();
+
+        let bare = crate::create_proof_with_transcript(
+            &params,
+            &pk,
+            TestProductCircuit { a: 6, b: 7 },
+            &[&instances],
+            TranscriptKind::Blake2b,
+        )
+        .unwrap();
+        verify_proof_with_transcript(&params, &vk, &bare, &[&instances], TranscriptKind::Blake2b)
+            .unwrap();
+        assert!(ProofBundle::from_bytes(&bare).is_err());
+    }
+
+    #[test]
+    fn test_parsed_instances_must_match_proof() {
+        let (params, pk, vk) = setup();
+        let bundle = prove(&params, &pk, TranscriptKind::Blake2b, MultiOpenScheme::Gwc);
+        let verify = |value: &str| {
+            let instances = [parse_instance(value).unwrap()];
+            verify_proof_with(
+                &params,
+                &vk,
+                &bundle.proof,
+                &[&instances],
+                TranscriptKind::Blake2b,
+                MultiOpenScheme::Gwc,
+            )
+        };
+        verify("42").unwrap();
+        verify(&format!("0x{:064x}", 42)).unwrap();
+        verify("0x2a").unwrap();
+        assert!(matches!(
+            verify("43"),
+            Err(VerifyError::ConstraintCheckFailed)
+        ));
+
+        assert!(parse_instance("0x").is_err());
+        assert!(parse_instance("forty-two").is_err());
+        assert!(parse_instance("-42").is_err());
+        assert!(parse_instance(&modulus::<Fr>().to_string()).is_err());
+        assert_eq!(
+            parse_instance(&(modulus::<Fr>() - 1u32).to_string()).unwrap(),
+            -Fr::from(1)
+        );
+    }
+
+    #[test]
+    fn test_instances_json_roundtrip() {
+        let instances = vec![vec![Fr::from(42), -Fr::from(1)]];
+        let json = instances_to_json(&instances);
+        assert_eq!(parse_instances_json(&json, 2).unwrap(), instances);
+        assert_eq!(
+            parse_instances_json(r#"["42", "0x2a"]"#, 2).unwrap(),
+            vec![vec![Fr::from(42), Fr::from(42)]]
+        );
+        assert_eq!(instances_to_json(&[]), "[]");
+        assert!(parse_instances_json("[]", 0).unwrap().is_empty());
+
+        assert!(parse_instances_json(&json, 1).is_err());
+        assert!(parse_instances_json(r#"[["42"], ["43"]]"#, 2).is_err());
+        assert!(parse_instances_json(r#"[42]"#, 1).is_err());
+        assert!(parse_instances_json("not json", 0).is_err());
+    }
+}
@@ -1,7 +1,11 @@
 use crate::big_uint::BigUintInstructions;
+use crate::codec::encode_hex;
+use crate::cost_report::{begin_cost_region, end_cost_region};
+use crate::diagnostics::record_failed_check;
 use crate::{
-    AssignedBigUint, AssignedRSAPubE, AssignedRSAPublicKey, AssignedRSASignature, BigUintConfig,
-    Fresh, RSAInstructions, RSAPubE, RSAPublicKey, RSASignature,
+    AssignedBigUint, AssignedDigestInfo, AssignedRSAPubE, AssignedRSAPublicKey,
+    AssignedRSASignature, BigUintConfig, Fresh, ModPowStrategy, RSAInstructions, RSAPubE,
+    RSAPublicKey, RSASignature,
 };
 use halo2_base::halo2_proofs::{circuit::Region, circuit::Value, plonk::Error};
 use halo2_base::utils::fe_to_bigint;
@@ -16,6 +20,74 @@ use halo2_base::{
 use num_bigint::BigUint;
 use std::marker::PhantomData;
 
+/// The byte length of the algorithm OIDs in [`AssignedDigestInfo`].
+pub const DIGEST_INFO_OID_LEN: usize = 9;
+/// The byte length of the digests in [`AssignedDigestInfo`], i.e., that of the longest supported digest.
+pub const DIGEST_INFO_MAX_DIGEST_LEN: usize = 64;
+/// The DER-encoded OID of SHA256 (2.16.840.1.101.3.4.2.1).
+pub const SHA256_OID: [u8; DIGEST_INFO_OID_LEN] =
+    [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+/// The DER-encoded OID of SHA384 (2.16.840.1.101.3.4.2.2).
+pub const SHA384_OID: [u8; DIGEST_INFO_OID_LEN] =
+    [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02];
+/// The DER-encoded OID of SHA512 (2.16.840.1.101.3.4.2.3).
+pub const SHA512_OID: [u8; DIGEST_INFO_OID_LEN] =
+    [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03];
+/// The hash algorithms supported by [`RSAInstructions::decode_pkcs1v15_digest_info`] and their digest lengths.
+const DIGEST_INFO_ALGORITHMS: [([u8; DIGEST_INFO_OID_LEN], usize); 3] =
+    [(SHA256_OID, 32), (SHA384_OID, 48), (SHA512_OID, 64)];
+
+/// Returns the DER encoding of DigestInfo without the digest, i.e., the bytes preceding the digest in a pkcs1v15 padded block.
+fn digest_info_prefix(oid: &[u8; DIGEST_INFO_OID_LEN], digest_len: usize) -> Vec<u8> {
+    let mut prefix = vec![
+        0x30,
+        (8 + DIGEST_INFO_OID_LEN + digest_len) as u8,
+        0x30,
+        (4 + DIGEST_INFO_OID_LEN) as u8,
+        0x06,
+        DIGEST_INFO_OID_LEN as u8,
+    ];
+    prefix.extend_from_slice(oid);
+    prefix.extend_from_slice(&[0x05, 0x00, 0x04, digest_len as u8]);
+    prefix
+}
+
+/// Records the checks of [`RSAInstructions::verify_pkcs1v15_signature`] that fail for the recovered block `em = s^e mod n` and the SHA256 digest `hashed_msg` for [`crate::DiagnosticsScope::diagnose_failure`].
+fn record_pkcs1v15_failures(em: &BigUint, hashed_msg: &BigUint, bits_len: usize) {
+    let to_bytes = |v: &BigUint, len: usize| {
+        let bytes = v.to_bytes_be();
+        let mut padded = vec![0u8; len.saturating_sub(bytes.len())];
+        padded.extend(bytes);
+        padded
+    };
+    let em_len = bits_len / 8;
+    let em = to_bytes(em, em_len);
+    let digest = to_bytes(hashed_msg, 32);
+    let prefix = digest_info_prefix(&SHA256_OID, 32);
+    let mut expected = vec![0x00, 0x01];
+    expected.resize(em_len - digest.len() - prefix.len() - 1, 0xff);
+    expected.push(0x00);
+    expected.extend(prefix);
+    let mismatch = em
+        .iter()
+        .zip(expected.iter())
+        .enumerate()
+        .find(|(_, (found, expected))| found != expected);
+    if let Some((i, (found, expected))) = mismatch {
+        record_failed_check(format!(
+            "padding mismatch at byte {} of the recovered block: expected {:#04x}, found {:#04x}",
+            i, expected, found
+        ));
+    }
+    if em[em_len - digest.len()..] != digest[..] {
+        record_failed_check(format!(
+            "s^e mod n != expected: the recovered block ends with the digest {}, but the hashed message is {}",
+            encode_hex(&em[em_len - digest.len()..]),
+            encode_hex(&digest)
+        ));
+    }
+}
+
 /// Configuration for [`RSAConfig`].
 #[derive(Clone, Debug)]
 pub struct RSAConfig<F: PrimeField> {
@@ -25,6 +97,8 @@ pub struct RSAConfig<F: PrimeField> {
     default_bits: usize,
     /// The bit length of exponents.
     exp_bits: usize,
+    /// Whether a fixed exponent computed by square-and-multiply uses [`BigUintConfig::mod_pow_unchecked`].
+    unchecked_mod_pow: bool,
 }
 
 impl<F: PrimeField> RSAInstructions<F> for RSAConfig<F> {
@@ -36,13 +110,50 @@ impl<F: PrimeField> RSAInstructions<F> for RSAConfig<F> {
     ///
     /// # Return values
     /// Returns a new [`AssignedRSAPublicKey`].
+    /// The modulus `n` may have fewer significant bits than the default bit length, e.g., a 2047 bits modulus in the 2048 bits space, in which case its high limbs are assigned as zeros.
+    /// Returns [`Error::Synthesis`] if `n` does not fit in the default bit length.
+    /// The least significant bit of `n` is constrained to 1, so an even modulus makes the circuit unsatisfiable.
     fn assign_public_key<'v>(
         &self,
         ctx: &mut Context<'v, F>,
         public_key: RSAPublicKey<F>,
     ) -> Result<AssignedRSAPublicKey<'v, F>, Error> {
+        let mut is_fit = true;
+        public_key
+            .n
+            .as_ref()
+            .map(|n| is_fit = n.bits() as usize <= self.default_bits);
+        if !is_fit {
+            return Err(Error::Synthesis);
+        }
+        public_key.n.as_ref().map(|n| {
+            if !n.bit(0) {
+                record_failed_check(
+                    "modulus LSB not 1: the modulus n of the public key is even".to_string(),
+                );
+            }
+        });
         let biguint_config = self.biguint_config();
         let n = biguint_config.assign_integer(ctx, public_key.n, self.default_bits)?;
+        // An RSA modulus is a product of two odd primes, so constrain `n[0] = 2 * half + 1` for a `half` of `limb_bits - 1` bits.
+        let n_low = &n.limbs()[0];
+        let half = n_low
+            .value()
+            .map(|v| biguint_to_fe::<F>(&(fe_to_biguint(v) >> 1usize)));
+        let half = self.gate().load_witness(ctx, half);
+        self.range()
+            .range_check(ctx, &half, biguint_config.limb_bits() - 1);
+        let odd = self.gate().mul_add(
+            ctx,
+            QuantumCell::Existing(&half),
+            QuantumCell::Constant(F::from(2)),
+            QuantumCell::Constant(F::one()),
+        );
+        self.gate().assert_equal(
+            ctx,
+            QuantumCell::Existing(n_low),
+            QuantumCell::Existing(&odd),
+        );
         let e = match public_key.e {
             RSAPubE::Var(e) => {
                 let assigned = self.gate().load_witness(ctx, e.map(|v| biguint_to_fe(&v)));
@@ -74,6 +185,8 @@ impl<F: PrimeField> RSAInstructions<F> for RSAConfig<F> {
 
     /// Given a base `x`, a RSA public key (e,n), performs the modular power `x^e mod n`.
     ///
+    /// If the config is created with [`RSAConfig::with_unchecked_mod_pow`], a fixed exponent picked as [`ModPowStrategy::SquareAndMultiply`] by [`ModPowStrategy::Auto`], e.g., 65537, is computed by [`BigUintConfig::mod_pow_unchecked`], which skips the range checks of the already assigned `n` in every step.
+    ///
     /// # Arguments
     /// * `ctx` - a region context.
     /// * `x` - a base integer.
@@ -88,13 +201,21 @@ impl<F: PrimeField> RSAInstructions<F> for RSAConfig<F> {
         public_key: &AssignedRSAPublicKey<'v, F>,
     ) -> Result<AssignedBigUint<'v, F, Fresh>, Error> {
         let biguint_config = self.biguint_config();
+        begin_cost_region(ctx, "rsa modpow");
         biguint_config.assert_in_field(ctx, x, &public_key.n)?;
         let powed = match &public_key.e {
             AssignedRSAPubE::Var(e) => {
                 biguint_config.pow_mod(ctx, x, e, &public_key.n, self.exp_bits)
             }
+            AssignedRSAPubE::Fix(e)
+                if self.unchecked_mod_pow
+                    && ModPowStrategy::Auto.resolve(e) == ModPowStrategy::SquareAndMultiply =>
+            {
+                biguint_config.mod_pow_unchecked(ctx, x, e, &public_key.n)
+            }
             AssignedRSAPubE::Fix(e) => biguint_config.pow_mod_fixed_exp(ctx, x, e, &public_key.n),
         }?;
+        end_cost_region(ctx);
         Ok(powed)
     }
 
@@ -121,8 +242,18 @@ impl<F: PrimeField> RSAInstructions<F> for RSAConfig<F> {
         let gate = self.gate();
         let mut is_eq = gate.load_constant(ctx, F::one());
         let powed = self.modpow_public_key(ctx, &signature.c, public_key)?;
+        begin_cost_region(ctx, "pkcs1v15 padding check");
         let hash_len = hashed_msg.len();
         assert_eq!(hash_len, 4);
+        let mut hashed_value = Value::known(BigUint::default());
+        for limb in hashed_msg.iter().rev() {
+            hashed_value = hashed_value
+                .zip(limb.value())
+                .map(|(acc, limb)| (acc << 64usize) + fe_to_biguint(limb));
+        }
+        powed.value().zip(hashed_value).map(|(em, hashed_value)| {
+            record_pkcs1v15_failures(&em, &hashed_value, self.default_bits)
+        });
         // 1. Check hashed data
         // 64 * 4 = 256 bit, that is the first 4 numbers.
         for (limb, hash) in powed.limbs()[0..hash_len].iter().zip(hashed_msg.iter()) {
@@ -235,8 +366,150 @@ impl<F: PrimeField> RSAInstructions<F> for RSAConfig<F> {
             QuantumCell::Existing(&is_eq),
             QuantumCell::Existing(&is_last_em_eq),
         );
+        end_cost_region(ctx);
         Ok(is_eq.clone())
     }
+
+    /// Given a RSA public key, a pkcs1v15 signature, and the big-endian bytes of a SHA256 digest computed outside the circuit, verifies the signature for the digest.
+    ///
+    /// This is the primitive under [`RSASignatureVerifier`], which computes the digest with the SHA256 chip instead.
+    /// The caller decides whether to trust an externally computed digest or to constrain it by another chip.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `public_key` - an assigned RSA public key.
+    /// * `signature` - an assigned pkcs1v15 signature.
+    /// * `digest` - the assigned bytes of the SHA256 digest in big-endian order. Each byte is range-checked to 8 bits.
+    ///
+    /// # Return values
+    /// Returns the assigned bit as `AssignedValue<F>`.
+    /// If `signature` is valid for `public_key` and `digest`, the bit is equivalent to one.
+    /// Otherwise, the bit is equivalent to zero.
+    fn verify_pkcs1v15_digest<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        public_key: &AssignedRSAPublicKey<'v, F>,
+        signature: &AssignedRSASignature<'v, F>,
+        digest: &[AssignedValue<'v, F>; 32],
+    ) -> Result<AssignedValue<'v, F>, Error> {
+        let gate = self.gate();
+        let range = self.range();
+        let limb_bytes = self.biguint_config.limb_bits() / 8;
+        let bases = (0..limb_bytes)
+            .map(|i| QuantumCell::Constant(F::from(1u64 << (8 * i))))
+            .collect::<Vec<QuantumCell<F>>>();
+        // Pack the bytes into 64 bit limbs from the least significant one.
+        let hashed_u64s = digest
+            .iter()
+            .rev()
+            .collect::<Vec<_>>()
+            .chunks(limb_bytes)
+            .map(|bytes| {
+                for byte in bytes.iter() {
+                    range.range_check(ctx, byte, 8);
+                }
+                let bytes = bytes
+                    .iter()
+                    .map(|byte| QuantumCell::Existing(*byte))
+                    .collect::<Vec<QuantumCell<F>>>();
+                gate.inner_product(ctx, bytes, bases.clone())
+            })
+            .collect::<Vec<AssignedValue<F>>>();
+        self.verify_pkcs1v15_signature(ctx, public_key, &hashed_u64s, signature)
+    }
+
+    /// Given a RSA public key and a pkcs1v15 signature, decodes the DigestInfo in the recovered padded block and returns its algorithm OID and digest.
+    ///
+    /// The padded block is checked against the layout `0x00 || 0x01 || 0xff... || 0x00 || DigestInfo` for each of SHA256, SHA384, and SHA512.
+    /// Since the OID is determined in the circuit, a verifier can distinguish the hash algorithm without trusting the prover.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `public_key` - an assigned RSA public key.
+    /// * `signature` - an assigned pkcs1v15 signature.
+    ///
+    /// # Return values
+    /// Returns [`AssignedDigestInfo`], whose `oid` is one of [`SHA256_OID`], [`SHA384_OID`], and [`SHA512_OID`] if `is_valid` is one.
+    /// Otherwise, `is_valid` is zero and `oid` and `digest` are all zero.
+    fn decode_pkcs1v15_digest_info<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        public_key: &AssignedRSAPublicKey<'v, F>,
+        signature: &AssignedRSASignature<'v, F>,
+    ) -> Result<AssignedDigestInfo<'v, F>, Error> {
+        let gate = self.gate();
+        let powed = self.modpow_public_key(ctx, &signature.c, public_key)?;
+        begin_cost_region(ctx, "pkcs1v15 padding check");
+        // 1. Decompose the padded block into bytes from the least significant one.
+        let em_bytes = self.decompose_bytes(ctx, &powed);
+        let em_len = em_bytes.len();
+        // 0x00 || 0x01 || at least 8 bytes of 0xff || 0x00 || DigestInfo
+        assert!(em_len >= 11 + 10 + DIGEST_INFO_OID_LEN + DIGEST_INFO_MAX_DIGEST_LEN);
+
+        // 2. Check the layout of the padded block for each hash algorithm.
+        let mut is_algs = vec![];
+        for (oid, digest_len) in DIGEST_INFO_ALGORITHMS.iter() {
+            let prefix = digest_info_prefix(oid, *digest_len);
+            let mut expected = vec![0x00, 0x01];
+            expected.resize(em_len - digest_len - prefix.len() - 1, 0xff);
+            expected.push(0x00);
+            expected.extend(prefix);
+            let mut is_alg = gate.load_constant(ctx, F::one());
+            for (byte, expected) in em_bytes[*digest_len..].iter().rev().zip(expected) {
+                let is_byte_eq = gate.is_equal(
+                    ctx,
+                    QuantumCell::Existing(byte),
+                    QuantumCell::Constant(F::from(expected as u64)),
+                );
+                is_alg = gate.and(
+                    ctx,
+                    QuantumCell::Existing(&is_alg),
+                    QuantumCell::Existing(&is_byte_eq),
+                );
+            }
+            is_algs.push(is_alg);
+        }
+        // The layouts conflict with each other at the bytes of 0x00 after the padding, so at most one of `is_algs` is one.
+        let is_valid = gate.sum(ctx, is_algs.iter().map(QuantumCell::Existing));
+
+        // 3. Select the OID of the matched algorithm.
+        let oid = (0..DIGEST_INFO_OID_LEN)
+            .map(|i| {
+                gate.inner_product(
+                    ctx,
+                    is_algs.iter().map(QuantumCell::Existing),
+                    DIGEST_INFO_ALGORITHMS
+                        .iter()
+                        .map(|(oid, _)| QuantumCell::Constant(F::from(oid[i] as u64))),
+                )
+            })
+            .collect::<Vec<AssignedValue<'v, F>>>();
+
+        // 4. Keep the bytes of the digest and zero the others.
+        let mut digest = vec![];
+        for i in (0..DIGEST_INFO_MAX_DIGEST_LEN).rev() {
+            let is_digest_byte = gate.sum(
+                ctx,
+                is_algs
+                    .iter()
+                    .zip(DIGEST_INFO_ALGORITHMS.iter())
+                    .filter(|(_, (_, digest_len))| i < *digest_len)
+                    .map(|(is_alg, _)| QuantumCell::Existing(is_alg)),
+            );
+            let byte = gate.mul(
+                ctx,
+                QuantumCell::Existing(&em_bytes[i]),
+                QuantumCell::Existing(&is_digest_byte),
+            );
+            digest.push(byte);
+        }
+        end_cost_region(ctx);
+        Ok(AssignedDigestInfo {
+            is_valid,
+            oid,
+            digest,
+        })
+    }
 }
 
 impl<F: PrimeField> RSAConfig<F> {
@@ -258,9 +531,18 @@ impl<F: PrimeField> RSAConfig<F> {
             biguint_config,
             default_bits,
             exp_bits,
+            unchecked_mod_pow: false,
         }
     }
 
+    /// Sets whether [`RSAInstructions::modpow_public_key`] computes a fixed exponent like 65537 by [`BigUintConfig::mod_pow_unchecked`], which takes fewer advice cells than [`BigUintInstructions::pow_mod_fixed_exp`].
+    ///
+    /// It is disabled by default. Enabling it changes the layout of the circuit, so the keys generated without it cannot prove or verify the circuit.
+    pub fn with_unchecked_mod_pow(mut self, enabled: bool) -> Self {
+        self.unchecked_mod_pow = enabled;
+        self
+    }
+
     /// Return [`Context<F>`]
     pub fn new_context<'a, 'b>(&'b self, region: Region<'a, F>) -> Context<'a, F> {
         self.biguint_config.new_context(region)
@@ -280,6 +562,45 @@ impl<F: PrimeField> RSAConfig<F> {
     pub fn range(&self) -> &RangeConfig<F> {
         &self.biguint_config.range()
     }
+
+    /// Decomposes the limbs of `a` into range-checked bytes from the least significant one.
+    pub(crate) fn decompose_bytes<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedBigUint<'v, F, Fresh>,
+    ) -> Vec<AssignedValue<'v, F>> {
+        let gate = self.gate();
+        let range = self.range();
+        let limb_bytes = self.biguint_config.limb_bits() / 8;
+        let bases = (0..limb_bytes)
+            .map(|i| QuantumCell::Constant(biguint_to_fe(&(BigUint::from(1u64) << (8 * i)))))
+            .collect::<Vec<QuantumCell<F>>>();
+        let mut bytes = vec![];
+        for limb in a.limbs() {
+            let limb_le_bytes = (0..limb_bytes)
+                .map(|i| {
+                    let byte = limb
+                        .value()
+                        .map(|v| biguint_to_fe::<F>(&((fe_to_biguint(v) >> (8 * i)) % 256u32)));
+                    let byte = gate.load_witness(ctx, byte);
+                    range.range_check(ctx, &byte, 8);
+                    byte
+                })
+                .collect::<Vec<AssignedValue<'v, F>>>();
+            let composed = gate.inner_product(
+                ctx,
+                limb_le_bytes.iter().map(QuantumCell::Existing),
+                bases.clone(),
+            );
+            gate.assert_equal(
+                ctx,
+                QuantumCell::Existing(limb),
+                QuantumCell::Existing(&composed),
+            );
+            bytes.extend(limb_le_bytes);
+        }
+        bytes
+    }
 }
 
 #[cfg(test)]
@@ -288,17 +609,23 @@ mod test {
 
     use super::*;
     use crate::big_uint::decompose_biguint;
+    use crate::normalize_signature_bytes;
     use halo2_base::halo2_proofs::{
-        circuit::{Layouter, SimpleFloorPlanner},
+        circuit::{Cell, Layouter, SimpleFloorPlanner},
         dev::MockProver,
         halo2curves::bn256::Fr,
-        plonk::{Circuit, ConstraintSystem},
+        plonk::{Circuit, Column, ConstraintSystem, Instance},
     };
     use halo2_base::{gates::range::RangeStrategy::Vertical, ContextParams, SKIP_FIRST_PASS};
 
     use num_bigint::RandomBits;
     use num_traits::FromPrimitive;
     use rand::{thread_rng, Rng};
+    use rsa::{
+        sha2::{Digest, Sha256, Sha384},
+        traits::PublicKeyParts,
+        Pkcs1v15Sign, RsaPrivateKey, RsaPublicKey,
+    };
 
     macro_rules! impl_rsa_modpow_test_circuit {
         ($circuit_name:ident, $test_fn_name:ident, $bits_len:expr, $limb_bits:expr, $k:expr, $should_be_error:expr, $( $synth:tt )*) => {
@@ -347,6 +674,8 @@ mod test {
                     while n.bits() != bits_len {
                         n = rng.sample(RandomBits::new(bits_len));
                     }
+                    // The modulus of a public key must be odd.
+                    n |= BigUint::from(1u8);
                     let e = rng.sample::<BigUint, _>(RandomBits::new($circuit_name::<F>::EXP_LIMB_BITS as u64)) % &n;
                     let x = rng.sample::<BigUint, _>(RandomBits::new(bits_len)) % &n;
                     let circuit = $circuit_name::<F> {
@@ -609,6 +938,132 @@ mod test {
         }
     );
 
+    impl_rsa_modpow_test_circuit!(
+        TestRSAModPowLargeE1024Circuit,
+        test_rsa_modpow_large_e_1024_circuit,
+        1024,
+        64,
+        15,
+        false,
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let biguint_config = config.biguint_config();
+
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "rsa modpow test with an 80 bits fixed exponent",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    // e = 2^79 + 2^40 + 0xb3, which does not fit in u64.
+                    let mut e_bytes = vec![0u8; 10];
+                    e_bytes[0] = 0x80;
+                    e_bytes[4] = 0x01;
+                    e_bytes[9] = 0xb3;
+                    let e_fix = RSAPubE::fix_from_be_bytes(&e_bytes).unwrap();
+                    let e = match &e_fix {
+                        RSAPubE::Fix(e) => e.clone(),
+                        RSAPubE::Var(_) => unreachable!(),
+                    };
+                    assert_eq!(e.bits(), 80);
+                    let public_key_fix = RSAPublicKey::new(Value::known(self.n.clone()), e_fix);
+                    let public_key_fix = config.assign_public_key(ctx, public_key_fix)?;
+                    let x_assigned = biguint_config.assign_integer(
+                        ctx,
+                        Value::known(self.x.clone()),
+                        Self::BITS_LEN,
+                    )?;
+                    let powed_fix = config.modpow_public_key(ctx, &x_assigned, &public_key_fix)?;
+                    let valid_powed_fix = big_pow_mod(&self.x, &e, &self.n);
+                    let valid_powed_fix = biguint_config.assign_constant(ctx, valid_powed_fix)?;
+                    biguint_config.assert_equal_fresh(ctx, &powed_fix, &valid_powed_fix)?;
+                    config.range().finalize(ctx);
+                    {
+                        println!("total advice cells: {}", ctx.total_advice);
+                        let const_rows = ctx.total_fixed + 1;
+                        println!("maximum rows used by a fixed column: {const_rows}");
+                        println!("lookup cells used: {}", ctx.cells_to_lookup.len());
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    );
+
+    impl_rsa_modpow_test_circuit!(
+        TestRSAModPowUnchecked1024Circuit,
+        test_rsa_modpow_unchecked_1024_circuit,
+        1024,
+        64,
+        14,
+        false,
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let biguint_config = config.biguint_config();
+            let unchecked_config = config.clone().with_unchecked_mod_pow(true);
+
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "rsa modpow test with and without the unchecked modular power",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    let e_fix = RSAPubE::Fix(BigUint::from_u128(Self::DEFAULT_E).unwrap());
+                    let public_key_fix = RSAPublicKey::new(Value::known(self.n.clone()), e_fix);
+                    let public_key_fix = config.assign_public_key(ctx, public_key_fix)?;
+                    let x_assigned = biguint_config.assign_integer(
+                        ctx,
+                        Value::known(self.x.clone()),
+                        Self::BITS_LEN,
+                    )?;
+                    let before = ctx.total_advice;
+                    let powed = config.modpow_public_key(ctx, &x_assigned, &public_key_fix)?;
+                    let checked_cells = ctx.total_advice - before;
+                    let before = ctx.total_advice;
+                    let powed_unchecked =
+                        unchecked_config.modpow_public_key(ctx, &x_assigned, &public_key_fix)?;
+                    let unchecked_cells = ctx.total_advice - before;
+                    // The default layout is kept, and only the opted-in config relaxes the checks.
+                    assert!(unchecked_cells < checked_cells);
+                    let valid_powed =
+                        big_pow_mod(&self.x, &BigUint::from(Self::DEFAULT_E), &self.n);
+                    let valid_powed = biguint_config.assign_constant(ctx, valid_powed)?;
+                    biguint_config.assert_equal_fresh(ctx, &powed, &valid_powed)?;
+                    biguint_config.assert_equal_fresh(ctx, &powed_unchecked, &valid_powed)?;
+                    config.range().finalize(ctx);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    );
+
+    #[test]
+    fn test_rsa_pub_e_from_be_bytes() {
+        assert!(RSAPubE::fix_from_be_bytes(&[0x01, 0x00, 0x01]).is_some());
+        assert!(RSAPubE::fix_from_be_bytes(&[0x01, 0x00, 0x02]).is_none());
+        assert!(RSAPubE::fix_from_be_bytes(&[]).is_none());
+    }
+
     macro_rules! impl_rsa_signature_test_circuit {
         ($circuit_name:ident, $test_fn_name:ident, $bits_len:expr, $limb_bits:expr, $k:expr, $should_be_error:expr, $( $synth:tt )*) => {
             struct $circuit_name<F: PrimeField> {
@@ -714,6 +1169,43 @@ mod test {
         }
     );
 
+    impl_rsa_signature_test_circuit!(
+        TestRSAEvenModulusCircuit,
+        test_rsa_even_modulus_circuit,
+        2048,
+        64,
+        13,
+        true,
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "rsa public key with an even modulus",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    let e_fix = RSAPubE::Fix(BigUint::from(Self::DEFAULT_E));
+                    let n_big = BigUint::from_str("27333278531038650284292446400685983964543820405055158402397263907659995327446166369388984969315774410223081038389734916442552953312548988147687296936649645550823280957757266695625382122565413076484125874545818286099364801140117875853249691189224238587206753225612046406534868213180954324992542640955526040556053150097561640564120642863954208763490114707326811013163227280580130702236406906684353048490731840275232065153721031968704703853746667518350717957685569289022049487955447803273805415754478723962939325870164033644600353029240991739641247820015852898600430315191986948597672794286676575642204004244219381500407").unwrap();
+                    let n_even = n_big + 1u32;
+                    let public_key = RSAPublicKey::new(Value::known(n_even), e_fix);
+                    config.assign_public_key(ctx, public_key)?;
+                    config.range().finalize(ctx);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    );
+
     impl_rsa_signature_test_circuit!(
         TestRSASignatureCircuit2,
         test_rsa_signature_circuit2,
@@ -814,6 +1306,195 @@ mod test {
         }
     );
 
+    impl_rsa_signature_test_circuit!(
+        TestRSASignatureDigestCircuit,
+        test_rsa_signature_digest_circuit,
+        2048,
+        64,
+        13,
+        false,
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "rsa signature test with a digest computed off-circuit",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    let mut rng = thread_rng();
+                    let private_key = RsaPrivateKey::new(&mut rng, Self::BITS_LEN)
+                        .expect("failed to generate a key");
+                    let digest = Sha256::digest(b"hello world");
+                    let sign = private_key
+                        .sign(Pkcs1v15Sign::new::<Sha256>(), &digest)
+                        .unwrap();
+                    let e_fix = RSAPubE::Fix(BigUint::from(Self::DEFAULT_E));
+                    let n_big = BigUint::from_bytes_be(&private_key.n().to_bytes_be());
+                    let public_key = config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                    let sign = config.assign_signature(
+                        ctx,
+                        RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+                    )?;
+                    let digest_assigned: [AssignedValue<F>; 32] = std::array::from_fn(|i| {
+                        config
+                            .gate()
+                            .load_witness(ctx, Value::known(F::from(digest[i] as u64)))
+                    });
+                    let is_valid =
+                        config.verify_pkcs1v15_digest(ctx, &public_key, &sign, &digest_assigned)?;
+                    config.gate().assert_is_const(ctx, &is_valid, F::one());
+                    config.range().finalize(ctx);
+                    {
+                        println!("total advice cells: {}", ctx.total_advice);
+                        let const_rows = ctx.total_fixed + 1;
+                        println!("maximum rows used by a fixed column: {const_rows}");
+                        println!("lookup cells used: {}", ctx.cells_to_lookup.len());
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    );
+
+    impl_rsa_signature_test_circuit!(
+        TestBadRSASignatureDigestCircuit,
+        test_bad_rsa_signature_digest_circuit,
+        2048,
+        64,
+        13,
+        true,
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "rsa signature test with a digest computed off-circuit",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    let mut rng = thread_rng();
+                    let private_key = RsaPrivateKey::new(&mut rng, Self::BITS_LEN)
+                        .expect("failed to generate a key");
+                    let digest = Sha256::digest(b"hello world");
+                    let sign = private_key
+                        .sign(Pkcs1v15Sign::new::<Sha256>(), &digest)
+                        .unwrap();
+                    // The digest of another message.
+                    let digest = Sha256::digest(b"hello world!");
+                    let e_fix = RSAPubE::Fix(BigUint::from(Self::DEFAULT_E));
+                    let n_big = BigUint::from_bytes_be(&private_key.n().to_bytes_be());
+                    let public_key = config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                    let sign = config.assign_signature(
+                        ctx,
+                        RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+                    )?;
+                    let digest_assigned: [AssignedValue<F>; 32] = std::array::from_fn(|i| {
+                        config
+                            .gate()
+                            .load_witness(ctx, Value::known(F::from(digest[i] as u64)))
+                    });
+                    let is_valid =
+                        config.verify_pkcs1v15_digest(ctx, &public_key, &sign, &digest_assigned)?;
+                    config.gate().assert_is_const(ctx, &is_valid, F::one());
+                    config.range().finalize(ctx);
+                    {
+                        println!("total advice cells: {}", ctx.total_advice);
+                        let const_rows = ctx.total_fixed + 1;
+                        println!("maximum rows used by a fixed column: {const_rows}");
+                        println!("lookup cells used: {}", ctx.cells_to_lookup.len());
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    );
+
+    impl_rsa_signature_test_circuit!(
+        TestRSASignatureShortModulusCircuit,
+        test_rsa_signature_short_modulus_circuit,
+        2048,
+        64,
+        13,
+        false,
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "rsa signature test with a 2047 bits modulus",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    let mut rng = thread_rng();
+                    // The top bit of the 2048 bits space is zero, while the signature is still 256 bytes long.
+                    let private_key = RsaPrivateKey::new(&mut rng, Self::BITS_LEN - 1)
+                        .expect("failed to generate a key");
+                    assert_eq!(private_key.n().bits(), Self::BITS_LEN - 1);
+                    let digest = Sha256::digest(b"hello world");
+                    let sign = private_key
+                        .sign(Pkcs1v15Sign::new::<Sha256>(), &digest)
+                        .unwrap();
+                    assert_eq!(sign.len(), Self::BITS_LEN / 8);
+                    let e_fix = RSAPubE::Fix(BigUint::from(Self::DEFAULT_E));
+                    let n_big = BigUint::from_bytes_be(&private_key.n().to_bytes_be());
+                    let public_key = config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                    assert_eq!(public_key.n.limbs().len(), Self::BITS_LEN / Self::LIMB_BITS);
+                    let sign = config.assign_signature(
+                        ctx,
+                        RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+                    )?;
+                    let digest_assigned: [AssignedValue<F>; 32] = std::array::from_fn(|i| {
+                        config
+                            .gate()
+                            .load_witness(ctx, Value::known(F::from(digest[i] as u64)))
+                    });
+                    let is_valid =
+                        config.verify_pkcs1v15_digest(ctx, &public_key, &sign, &digest_assigned)?;
+                    config.gate().assert_is_const(ctx, &is_valid, F::one());
+                    config.range().finalize(ctx);
+                    {
+                        println!("total advice cells: {}", ctx.total_advice);
+                        let const_rows = ctx.total_fixed + 1;
+                        println!("maximum rows used by a fixed column: {const_rows}");
+                        println!("lookup cells used: {}", ctx.cells_to_lookup.len());
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    );
+
     impl_rsa_signature_test_circuit!(
         TestBadRSASignatureCircuit2,
         test_bad_rsa_signature_circuit2,
@@ -863,4 +1544,231 @@ mod test {
             Ok(())
         }
     );
+
+    #[derive(Clone, Debug)]
+    struct TestDigestInfoConfig<F: PrimeField> {
+        rsa_config: RSAConfig<F>,
+        digest_info_instance: Column<Instance>,
+    }
+
+    struct TestDigestInfoCircuit<F: PrimeField> {
+        n: BigUint,
+        signature: RSASignature<F>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestDigestInfoCircuit<F> {
+        const BITS_LEN: usize = 1024;
+        const LIMB_BITS: usize = 64;
+        const EXP_LIMB_BITS: usize = 5;
+        const DEFAULT_E: u128 = 65537;
+        const NUM_ADVICE: usize = 50;
+        const NUM_FIXED: usize = 1;
+        const NUM_LOOKUP_ADVICE: usize = 4;
+        const LOOKUP_BITS: usize = 12;
+        const K: u32 = 14;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestDigestInfoCircuit<F> {
+        type Config = TestDigestInfoConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config = RangeConfig::configure(
+                meta,
+                Vertical,
+                &[Self::NUM_ADVICE],
+                &[Self::NUM_LOOKUP_ADVICE],
+                Self::NUM_FIXED,
+                Self::LOOKUP_BITS,
+                0,
+                Self::K as usize,
+            );
+            let bigint_config = BigUintConfig::construct(range_config, Self::LIMB_BITS);
+            let rsa_config =
+                RSAConfig::construct(bigint_config, Self::BITS_LEN, Self::EXP_LIMB_BITS);
+            let digest_info_instance = meta.instance_column();
+            meta.enable_equality(digest_info_instance);
+            Self::Config {
+                rsa_config,
+                digest_info_instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let rsa_config = config.rsa_config;
+            rsa_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let digest_info_cells = layouter.assign_region(
+                || "decode digest info with 1024 bits public keys",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(vec![]);
+                    }
+
+                    let mut aux = rsa_config.new_context(region);
+                    let ctx = &mut aux;
+                    let e_fix = RSAPubE::Fix(BigUint::from(Self::DEFAULT_E));
+                    let public_key = RSAPublicKey::new(Value::known(self.n.clone()), e_fix);
+                    let public_key = rsa_config.assign_public_key(ctx, public_key)?;
+                    let sign = rsa_config.assign_signature(ctx, self.signature.clone())?;
+                    let digest_info =
+                        rsa_config.decode_pkcs1v15_digest_info(ctx, &public_key, &sign)?;
+                    rsa_config
+                        .gate()
+                        .assert_is_const(ctx, &digest_info.is_valid, F::one());
+                    rsa_config.range().finalize(ctx);
+                    {
+                        println!("total advice cells: {}", ctx.total_advice);
+                        let const_rows = ctx.total_fixed + 1;
+                        println!("maximum rows used by a fixed column: {const_rows}");
+                        println!("lookup cells used: {}", ctx.cells_to_lookup.len());
+                    }
+                    let cells = digest_info
+                        .oid
+                        .iter()
+                        .chain(digest_info.digest.iter())
+                        .map(|v| v.cell())
+                        .collect::<Vec<Cell>>();
+                    Ok(cells)
+                },
+            )?;
+            for (i, cell) in digest_info_cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell, config.digest_info_instance, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn run_digest_info_test(
+        padding: Pkcs1v15Sign,
+        hash: fn(&[u8]) -> Vec<u8>,
+        claimed_oid: &[u8; DIGEST_INFO_OID_LEN],
+        should_be_error: bool,
+    ) {
+        let mut rng = thread_rng();
+        let bits_len = TestDigestInfoCircuit::<Fr>::BITS_LEN;
+        let private_key = RsaPrivateKey::new(&mut rng, bits_len).expect("failed to generate a key");
+        let public_key = RsaPublicKey::from(&private_key);
+        let mut msg = [0u8; 128];
+        rng.fill(&mut msg[..]);
+        let digest = hash(&msg);
+        let sign = private_key
+            .sign(padding, &digest)
+            .expect("fail to sign a hashed message.");
+        let n = BigUint::from_bytes_be(&public_key.n().to_bytes_be());
+        let signature = RSASignature::new(Value::known(BigUint::from_bytes_be(&sign)));
+        assert_eq!(
+            run_digest_info_circuit(n, signature, claimed_oid, digest),
+            should_be_error
+        );
+    }
+
+    /// Runs [`TestDigestInfoCircuit`] and returns whether it fails for the claimed OID and digest.
+    fn run_digest_info_circuit(
+        n: BigUint,
+        signature: RSASignature<Fr>,
+        claimed_oid: &[u8; DIGEST_INFO_OID_LEN],
+        digest: Vec<u8>,
+    ) -> bool {
+        let circuit = TestDigestInfoCircuit::<Fr> {
+            n,
+            signature,
+            _f: PhantomData,
+        };
+
+        let mut padded_digest = vec![0u8; DIGEST_INFO_MAX_DIGEST_LEN - digest.len()];
+        padded_digest.extend(digest);
+        let public_inputs = claimed_oid
+            .iter()
+            .chain(padded_digest.iter())
+            .map(|byte| Fr::from(*byte as u64))
+            .collect::<Vec<Fr>>();
+        let prover = match MockProver::run(
+            TestDigestInfoCircuit::<Fr>::K,
+            &circuit,
+            vec![public_inputs],
+        ) {
+            Ok(prover) => prover,
+            Err(e) => panic!("{:#?}", e),
+        };
+        prover.verify().is_err()
+    }
+
+    #[test]
+    fn test_decode_digest_info_sha256() {
+        run_digest_info_test(
+            Pkcs1v15Sign::new::<Sha256>(),
+            |msg| Sha256::digest(msg).to_vec(),
+            &SHA256_OID,
+            false,
+        );
+    }
+
+    #[test]
+    fn test_decode_digest_info_sha384() {
+        run_digest_info_test(
+            Pkcs1v15Sign::new::<Sha384>(),
+            |msg| Sha384::digest(msg).to_vec(),
+            &SHA384_OID,
+            false,
+        );
+    }
+
+    #[test]
+    fn test_signature_without_leading_zero_byte() {
+        let mut rng = thread_rng();
+        let bits_len = TestDigestInfoCircuit::<Fr>::BITS_LEN;
+        let private_key = RsaPrivateKey::new(&mut rng, bits_len).expect("failed to generate a key");
+        let public_key = RsaPublicKey::from(&private_key);
+        let n = BigUint::from_bytes_be(&public_key.n().to_bytes_be());
+        // About one in 256 signatures starts with a zero byte.
+        let (digest, sign) = loop {
+            let digest = Sha256::digest(rng.gen::<[u8; 32]>()).to_vec();
+            let sign = private_key
+                .sign(Pkcs1v15Sign::new::<Sha256>(), &digest)
+                .expect("fail to sign a hashed message.");
+            if sign[0] == 0 {
+                break (digest, sign);
+            }
+        };
+        let stripped = &sign[1..];
+        assert_eq!(stripped.len(), bits_len / 8 - 1);
+        assert_eq!(normalize_signature_bytes(stripped, bits_len).unwrap(), sign);
+        assert_eq!(normalize_signature_bytes(&sign, bits_len).unwrap(), sign);
+
+        for bytes in [&sign[..], stripped] {
+            let signature = RSASignature::from_be_bytes(bytes, bits_len).unwrap();
+            assert!(!run_digest_info_circuit(
+                n.clone(),
+                signature,
+                &SHA256_OID,
+                digest.clone()
+            ));
+        }
+
+        let mut too_long = vec![0];
+        too_long.extend_from_slice(&sign);
+        assert!(normalize_signature_bytes(&too_long, bits_len).is_none());
+        assert!(RSASignature::<Fr>::from_be_bytes(&too_long, bits_len).is_none());
+    }
+
+    #[test]
+    fn test_decode_digest_info_wrong_oid() {
+        run_digest_info_test(
+            Pkcs1v15Sign::new::<Sha384>(),
+            |msg| Sha384::digest(msg).to_vec(),
+            &SHA256_OID,
+            true,
+        );
+    }
 }
@@ -0,0 +1,853 @@
+use crate::{
+    create_proof_with, digest_instances, verify_proof_with, BigUintConfig, BigUintInstructions,
+    CircuitInfo, CircuitShape, Exposure, MsgLenError, MultiOpenScheme, Pkcs1v15CircuitBuilder,
+    RSAConfig, RSAInstructions, RSAPubE, RSAPublicKey, RSASignature, RSASignatureVerifier,
+    TranscriptKind, RECOMMEND_K_NUM_ADVICE,
+};
+use halo2_base::halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    dev::MockProver,
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{keygen_pk, keygen_vk, Circuit, Column, ConstraintSystem, Error, Instance},
+    plonk::{ProvingKey, VerifyingKey},
+    poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG},
+};
+use halo2_base::{
+    gates::{range::RangeConfig, range::RangeStrategy::Vertical, GateInstructions},
+    utils::PrimeField,
+    AssignedValue, SKIP_FIRST_PASS,
+};
+use halo2_dynamic_sha256::Sha256DynamicConfig;
+use num_bigint::BigUint;
+use rand::{rngs::OsRng, thread_rng, Rng};
+use rsa::{
+    pkcs1v15::SigningKey, signature::SignatureEncoding, signature::Signer, traits::PublicKeyParts,
+    Pkcs1v15Sign, RsaPrivateKey, RsaPublicKey,
+};
+use sha2::{Digest, Sha256};
+use std::marker::PhantomData;
+
+/// The configuration knobs of a [`Pkcs1v15Circuit`] other than the sizes of the modulus and the message.
+///
+/// Every constant has a default, so an empty implementation such as [`DefaultPkcs1v15Layout`] takes the recommended ones.
+/// [`crate::impl_pkcs1v15_basic_circuit`] implements this trait with all of its arguments, and [`crate::impl_pkcs1v15_layout`] with a validated [`CircuitShape`].
+pub trait Pkcs1v15Layout {
+    /// The number of flex advice columns.
+    const NUM_ADVICE: usize = RECOMMEND_K_NUM_ADVICE;
+    /// The number of advice columns of the range lookup.
+    const NUM_LOOKUP_ADVICE: usize = 1;
+    /// The lookup bits of the SHA256 chip.
+    const SHA256_LOOKUP_BITS: usize = crate::SHA256_LOOKUP_BITS as usize;
+    /// The number of lookup advice columns of the SHA256 chip.
+    const SHA256_LOOKUP_ADVICE: usize = 1;
+    /// Whether the SHA256 digest given instead of the message is exposed as the public instances returned by [`Pkcs1v15Circuit::instances`].
+    /// It requires the SHA256 chip to be disabled.
+    const PUBLIC_DIGEST: bool = false;
+    /// Whether the validity of the signature is output as a public 0/1 instance following the digest, if any, instead of being constrained to be valid.
+    /// A proof is then created for an invalid signature as well, and the verifier must check the instance.
+    const PUBLIC_VALIDITY: bool = false;
+    /// The degree of the circuit, or `None` to take the minimum one computed by [`Pkcs1v15CircuitBuilder::build`].
+    const K: Option<u32> = None;
+}
+
+/// The [`Pkcs1v15Layout`] with the recommended knobs.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultPkcs1v15Layout;
+
+impl Pkcs1v15Layout for DefaultPkcs1v15Layout {}
+
+/// The bit width of the limbs of the integers in [`Pkcs1v15Circuit`].
+const LIMB_WIDTH: usize = 64;
+/// The bit width of the limbs of the exponent in [`Pkcs1v15Circuit`].
+const EXP_LIMB_BITS: usize = 5;
+/// The fixed exponent of the keys of [`Pkcs1v15Circuit::default`].
+const DEFAULT_E: u128 = 65537;
+const NUM_FIXED: usize = 1;
+
+/// The configuration of [`Pkcs1v15Circuit`].
+#[derive(Debug, Clone)]
+pub struct Pkcs1v15CircuitConfig<F: PrimeField> {
+    rsa_config: RSAConfig<F>,
+    sha256_config: Option<Sha256DynamicConfig<F>>,
+    instance: Option<Column<Instance>>,
+    public_digest: bool,
+    public_validity: bool,
+}
+
+/// A circuit verifying a pkcs1v15 signature of a `BITS_LEN` bits RSA modulus with the fixed exponent.
+///
+/// If `SHA2` is true, the message of at most `MSG_LEN - 9` bytes is hashed in the circuit, where `MSG_LEN` includes the 9 bytes of the SHA256 padding.
+/// Otherwise, the 32 bytes SHA256 digest is given instead of the message.
+/// The other knobs are given by `L`, e.g., a 2048 bits circuit for 256 bytes messages with the recommended knobs is `Pkcs1v15Circuit<Fr, 2048, 256, true>`.
+pub struct Pkcs1v15Circuit<
+    F: PrimeField,
+    const BITS_LEN: usize,
+    const MSG_LEN: usize,
+    const SHA2: bool,
+    L: Pkcs1v15Layout = DefaultPkcs1v15Layout,
+> {
+    /// The signature to verify.
+    pub signature: RSASignature<F>,
+    /// The public key of the signature.
+    pub public_key: RSAPublicKey<F>,
+    /// The signed message, or its SHA256 digest if `SHA2` is false.
+    pub msg: Vec<u8>,
+    #[doc(hidden)]
+    pub _f: PhantomData<(F, L)>,
+}
+
+impl<
+        F: PrimeField,
+        const BITS_LEN: usize,
+        const MSG_LEN: usize,
+        const SHA2: bool,
+        L: Pkcs1v15Layout,
+    > Pkcs1v15Circuit<F, BITS_LEN, MSG_LEN, SHA2, L>
+{
+    pub const BITS_LEN: usize = BITS_LEN;
+    pub const MSG_LEN: usize = MSG_LEN;
+    pub const LIMB_WIDTH: usize = LIMB_WIDTH;
+    pub const EXP_LIMB_BITS: usize = EXP_LIMB_BITS;
+    pub const DEFAULT_E: u128 = DEFAULT_E;
+    pub const NUM_ADVICE: usize = L::NUM_ADVICE;
+    /// The shape of this circuit validated by [`Pkcs1v15CircuitBuilder::build`], which fails to compile for an invalid combination of the knobs.
+    pub const SHAPE: CircuitShape = match Pkcs1v15CircuitBuilder::from_layout::<L>()
+        .bits_len(BITS_LEN)
+        .msg_len(MSG_LEN)
+        .sha2_chip_enabled(SHA2)
+        .build()
+    {
+        Ok(shape) => shape,
+        Err(_) => panic!("invalid combination of the knobs of the pkcs1v15 circuit"),
+    };
+    /// The degree of this circuit, which is [`Pkcs1v15Layout::K`] if any.
+    pub const K: u32 = Self::SHAPE.k;
+    pub const LOOKUP_BITS: usize = Self::K as usize - 1;
+    /// The constants of this circuit exposed to the frontends.
+    pub const INFO: CircuitInfo = Self::SHAPE.info();
+    /// The maximum byte length of the message, which is `MSG_LEN - 9` if `SHA2` is true, and 32 for the SHA256 digest otherwise.
+    ///
+    /// A longer message does not fit in the circuit, so [`Self::try_new`] rejects it instead of failing to synthesize.
+    pub const MAX_MSG_LEN: usize = Self::INFO.max_msg_len();
+
+    /// Creates a circuit verifying `signature` for `msg` with `public_key`.
+    ///
+    /// # Arguments
+    /// * signature - the signature to verify.
+    /// * public_key - the public key of the signature.
+    /// * msg - the signed message, or its SHA256 digest if `SHA2` is false.
+    pub fn new(signature: RSASignature<F>, public_key: RSAPublicKey<F>, msg: Vec<u8>) -> Self {
+        Self {
+            signature,
+            public_key,
+            msg,
+            _f: PhantomData,
+        }
+    }
+
+    /// Creates a circuit in the same way as [`Self::new`] after checking that `msg` fits in it by [`CircuitInfo::check_msg_len`].
+    pub fn try_new(
+        signature: RSASignature<F>,
+        public_key: RSAPublicKey<F>,
+        msg: Vec<u8>,
+    ) -> Result<Self, MsgLenError> {
+        Self::INFO.check_msg_len(&msg)?;
+        Ok(Self::new(signature, public_key, msg))
+    }
+
+    /// Returns the minimum degree of params to prove and verify this circuit.
+    pub const fn min_k() -> u32 {
+        Self::K
+    }
+
+    /// Returns a circuit without witnesses for the fixed exponent `e`.
+    ///
+    /// The constraints depend on the bits of `e`, so the proving and verifying keys must be generated from this circuit when `e` is not [`Self::DEFAULT_E`].
+    pub fn without_witness_with_e(e: BigUint) -> Self {
+        let signature = RSASignature::without_witness();
+        let public_key = RSAPublicKey::without_witness(e);
+        let msg = if SHA2 {
+            vec![0; MSG_LEN - 9]
+        } else {
+            vec![0; 32]
+        };
+        Self::new(signature, public_key, msg)
+    }
+
+    /// Returns the values of the instance columns for `msg` and a valid signature, which are the limbs given by `digest_instances` if the digest is public, and none otherwise.
+    ///
+    /// If [`Pkcs1v15Layout::PUBLIC_VALIDITY`] is true, they are followed by the validity 1. See [`Self::instances_with_validity`] for an invalid signature.
+    pub fn instances(msg: &[u8]) -> Vec<Vec<F>> {
+        Self::instances_with_validity(msg, true)
+    }
+
+    /// Returns the values of the instance columns for `msg` and a signature whose validity is `is_valid`.
+    ///
+    /// `is_valid` is ignored unless [`Pkcs1v15Layout::PUBLIC_VALIDITY`] is true, since an invalid signature cannot be proven otherwise.
+    pub fn instances_with_validity(msg: &[u8], is_valid: bool) -> Vec<Vec<F>> {
+        shape_instances(&Self::SHAPE, msg, is_valid)
+    }
+}
+
+impl<const BITS_LEN: usize, const MSG_LEN: usize, const SHA2: bool, L: Pkcs1v15Layout>
+    Pkcs1v15Circuit<Fr, BITS_LEN, MSG_LEN, SHA2, L>
+{
+    /// Samples params of degree [`Self::K`] and generates the proving and verifying keys of this circuit for them.
+    pub fn setup() -> (
+        ParamsKZG<Bn256>,
+        VerifyingKey<G1Affine>,
+        ProvingKey<G1Affine>,
+    ) {
+        let circuit = Self::default();
+        let params = ParamsKZG::<Bn256>::setup(Self::K, OsRng);
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &circuit).unwrap();
+        (params, vk, pk)
+    }
+
+    /// Proves and verifies a signature of a uniformly sampled key and message with the keys of [`Self::setup`] for the tests and the benchmarks.
+    ///
+    /// # Panics
+    /// Panics if the constraints are not satisfied or the proof is rejected.
+    pub fn prove_sampled(
+        params: &ParamsKZG<Bn256>,
+        vk: &VerifyingKey<G1Affine>,
+        pk: &ProvingKey<G1Affine>,
+        scheme: MultiOpenScheme,
+    ) {
+        let (circuit, instances) = Self::sampled();
+        let instance_refs = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        let prover = match MockProver::run(Self::K, &circuit, instances.clone()) {
+            Ok(prover) => prover,
+            Err(e) => panic!("{:#?}", e),
+        };
+        prover.verify().unwrap();
+
+        // 7. Generate a proof.
+        let proof = create_proof_with(
+            params,
+            pk,
+            circuit,
+            &instance_refs,
+            TranscriptKind::Blake2b,
+            scheme,
+        )
+        .unwrap();
+        // 8. Verify the proof.
+        verify_proof_with(
+            params,
+            vk,
+            &proof,
+            &instance_refs,
+            TranscriptKind::Blake2b,
+            scheme,
+        )
+        .unwrap();
+    }
+
+    /// Returns a circuit verifying a signature of a uniformly sampled key and message, and its public instances.
+    pub(crate) fn sampled() -> (Self, Vec<Vec<Fr>>) {
+        let (signature, public_key, msg) = sample_witness(BITS_LEN, MSG_LEN, SHA2);
+        let instances = Self::instances(&msg);
+        (Self::new(signature, public_key, msg), instances)
+    }
+}
+
+/// Returns the values of the instance columns of a circuit of `shape` for `msg` and a signature whose validity is `is_valid`.
+fn shape_instances<F: PrimeField>(shape: &CircuitShape, msg: &[u8], is_valid: bool) -> Vec<Vec<F>> {
+    let mut column = match shape.exposure {
+        Exposure::Digest => digest_instances(msg),
+        Exposure::None => vec![],
+    };
+    if shape.public_validity {
+        column.push(F::from(is_valid as u64));
+    }
+    if column.is_empty() {
+        vec![]
+    } else {
+        vec![column]
+    }
+}
+
+/// Samples a RSA key pair of `bits_len` bits and a message of `msg_len - 9` bytes, and returns the pkcs1v15 signature, the public key with [`DEFAULT_E`], and the message, or its SHA256 digest if `sha2_chip_enabled` is false.
+fn sample_witness<F: PrimeField>(
+    bits_len: usize,
+    msg_len: usize,
+    sha2_chip_enabled: bool,
+) -> (RSASignature<F>, RSAPublicKey<F>, Vec<u8>) {
+    // 1. Uniformly sample a RSA key pair.
+    let mut rng = thread_rng();
+    let private_key = RsaPrivateKey::new(&mut rng, bits_len).expect("failed to generate a key");
+    let public_key = RsaPublicKey::from(&private_key);
+    // 2. Uniformly sample a message.
+    // 3. Compute the SHA256 hash of `msg`.
+    let (msg, hashed_msg) = if sha2_chip_enabled {
+        let msg = (0..msg_len - 9).map(|_| rng.gen()).collect::<Vec<u8>>();
+        let hashed_msg = Sha256::digest(&msg).to_vec();
+        (msg, hashed_msg)
+    } else {
+        let msg = rng.gen::<[u8; 32]>();
+        let hashed_msg = Sha256::digest(msg).to_vec();
+        (hashed_msg.clone(), hashed_msg)
+    };
+
+    // 4. Generate a pkcs1v15 signature.
+    // Without the SHA256 chip, `msg` is already the digest, so it is signed without hashing it again.
+    let sign = if sha2_chip_enabled {
+        let signing_key = SigningKey::<rsa::sha2::Sha256>::new(private_key.clone());
+        signing_key.sign(&msg).to_vec()
+    } else {
+        private_key
+            .sign(Pkcs1v15Sign::new::<rsa::sha2::Sha256>(), &hashed_msg)
+            .expect("failed to sign the digest")
+    };
+    let sign_big = BigUint::from_bytes_be(&sign);
+    let signature = RSASignature::new(Value::known(sign_big));
+
+    // 5. Construct `RSAPublicKey` from `n` of `public_key` and fixed `e`.
+    let n_big = BigUint::from_bytes_be(&public_key.n().to_bytes_be());
+    let e_fix = RSAPubE::Fix(BigUint::from(DEFAULT_E));
+    let public_key = RSAPublicKey::new(Value::known(n_big), e_fix);
+    (signature, public_key, msg)
+}
+
+impl<
+        F: PrimeField,
+        const BITS_LEN: usize,
+        const MSG_LEN: usize,
+        const SHA2: bool,
+        L: Pkcs1v15Layout,
+    > Default for Pkcs1v15Circuit<F, BITS_LEN, MSG_LEN, SHA2, L>
+{
+    fn default() -> Self {
+        Self::without_witness_with_e(BigUint::from(Self::DEFAULT_E))
+    }
+}
+
+impl<
+        F: PrimeField,
+        const BITS_LEN: usize,
+        const MSG_LEN: usize,
+        const SHA2: bool,
+        L: Pkcs1v15Layout,
+    > Circuit<F> for Pkcs1v15Circuit<F, BITS_LEN, MSG_LEN, SHA2, L>
+{
+    type Config = Pkcs1v15CircuitConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        unimplemented!();
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        Pkcs1v15CircuitConfig::configure(meta, &Self::SHAPE)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        config.synthesize(layouter, &self.signature, &self.public_key, &self.msg)
+    }
+}
+
+impl<F: PrimeField> Pkcs1v15CircuitConfig<F> {
+    /// Configures the chips of a pkcs1v15 circuit of `shape`.
+    fn configure(meta: &mut ConstraintSystem<F>, shape: &CircuitShape) -> Self {
+        let range_config = RangeConfig::configure(
+            meta,
+            Vertical,
+            &[shape.num_advice],
+            &[shape.num_lookup_advice],
+            NUM_FIXED,
+            shape.k as usize - 1,
+            0,
+            shape.k as usize,
+        );
+        let bigint_config = BigUintConfig::construct(range_config.clone(), LIMB_WIDTH);
+        let rsa_config = RSAConfig::construct(bigint_config, shape.bits_len, EXP_LIMB_BITS);
+        let sha256_config = if shape.sha2_chip_enabled {
+            let sha256_config = Sha256DynamicConfig::configure(
+                meta,
+                vec![shape.msg_len],
+                range_config,
+                shape.sha256_lookup_bits,
+                shape.sha256_lookup_advice,
+                true,
+            );
+            Some(sha256_config)
+        } else {
+            None
+        };
+        let public_digest = matches!(shape.exposure, Exposure::Digest);
+        let instance = if public_digest || shape.public_validity {
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            Some(instance)
+        } else {
+            None
+        };
+
+        Self {
+            rsa_config,
+            sha256_config,
+            instance,
+            public_digest,
+            public_validity: shape.public_validity,
+        }
+    }
+
+    /// Verifies `signature` for `msg`, or for the SHA256 digest `msg` if the SHA256 chip is disabled, with `public_key`.
+    ///
+    /// The signature is constrained to be valid unless its validity is public, in which case the output of the verification is constrained to the instance after the digest, if any.
+    fn synthesize(
+        &self,
+        mut layouter: impl Layouter<F>,
+        signature: &RSASignature<F>,
+        public_key: &RSAPublicKey<F>,
+        msg: &[u8],
+    ) -> Result<(), Error> {
+        let biguint_config = self.rsa_config.biguint_config();
+        let limb_bits = LIMB_WIDTH;
+        if let Some(sha256_config) = self.sha256_config.as_ref() {
+            sha256_config.load(&mut layouter)?;
+        }
+        biguint_config.range().load_lookup_table(&mut layouter)?;
+        let mut first_pass = SKIP_FIRST_PASS;
+        let instance_cells = layouter.assign_region(
+            || "pkcs1v15 signature verification",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(vec![]);
+                }
+
+                let mut aux = biguint_config.new_context(region);
+                let ctx = &mut aux;
+                let sign = self.rsa_config.assign_signature(ctx, signature.clone())?;
+                let public_key = self.rsa_config.assign_public_key(ctx, public_key.clone())?;
+                let mut instance_cells = vec![];
+                let is_valid = if let Some(sha256_config) = self.sha256_config.as_ref() {
+                    let mut verifier =
+                        RSASignatureVerifier::new(self.rsa_config.clone(), sha256_config.clone());
+                    let (is_valid, _) =
+                        verifier.verify_pkcs1v15_signature(ctx, &public_key, msg, &sign)?;
+                    is_valid
+                } else {
+                    let gate = self.rsa_config.gate();
+                    let mut msg = msg.to_vec();
+                    msg.reverse();
+                    let hash_u64s = msg.chunks(limb_bits / 8).map(|limbs| {
+                        let mut sum = 0u64;
+                        for (i, limb) in limbs.iter().enumerate() {
+                            sum += (*limb as u64) << (8 * i);
+                        }
+                        F::from(sum)
+                    });
+                    let assigned_msg = hash_u64s
+                        .map(|v| gate.load_witness(ctx, Value::known(v)))
+                        .collect::<Vec<AssignedValue<F>>>();
+                    if self.public_digest {
+                        instance_cells.extend(assigned_msg.iter().map(|limb| limb.cell()));
+                    }
+                    self.rsa_config.verify_pkcs1v15_signature(
+                        ctx,
+                        &public_key,
+                        &assigned_msg,
+                        &sign,
+                    )?
+                };
+                if self.public_validity {
+                    instance_cells.push(is_valid.cell());
+                } else {
+                    biguint_config
+                        .gate()
+                        .assert_is_const(ctx, &is_valid, F::one());
+                }
+                biguint_config.range().finalize(ctx);
+                {
+                    println!("total advice cells: {}", ctx.total_advice);
+                    let const_rows = ctx.total_fixed + 1;
+                    println!("maximum rows used by a fixed column: {const_rows}");
+                    println!("lookup cells used: {}", ctx.cells_to_lookup.len());
+                }
+                Ok(instance_cells)
+            },
+        )?;
+        if let Some(instance) = self.instance {
+            for (i, cell) in instance_cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell, instance, i)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+thread_local! {
+    /// The shape configured by [`MeasuredPkcs1v15Circuit`], which is given at runtime unlike that of [`Pkcs1v15Circuit`].
+    static MEASURED_SHAPE: std::cell::Cell<Option<CircuitShape>> = std::cell::Cell::new(None);
+}
+
+/// A pkcs1v15 circuit of the shape in [`MEASURED_SHAPE`], which [`min_k_for`] synthesizes with degrees chosen at runtime.
+#[cfg(not(target_arch = "wasm32"))]
+struct MeasuredPkcs1v15Circuit<F: PrimeField> {
+    signature: RSASignature<F>,
+    public_key: RSAPublicKey<F>,
+    msg: Vec<u8>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<F: PrimeField> Circuit<F> for MeasuredPkcs1v15Circuit<F> {
+    type Config = Pkcs1v15CircuitConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        unimplemented!();
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let shape = MEASURED_SHAPE
+            .with(|shape| shape.get())
+            .expect("no shape is given to measure");
+        Pkcs1v15CircuitConfig::configure(meta, &shape)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        config.synthesize(layouter, &self.signature, &self.public_key, &self.msg)
+    }
+}
+
+/// Returns the minimum degree of a pkcs1v15 circuit for `bits_len` bits moduli and messages of `msg_len` bytes including the SHA256 padding, measured by synthesizing the circuit.
+///
+/// The other knobs are taken from `options`, whose degree is ignored.
+/// Unlike [`Pkcs1v15CircuitBuilder::min_k`], which sums the upper bounds of the costs in [`crate::PKCS1V15_ADVICE_CELLS`], it binary-searches the smallest degree for which [`MockProver`] accepts a sampled signature.
+/// It therefore takes from seconds to minutes and is meant for the tests and for choosing [`Pkcs1v15Layout::K`] by hand.
+/// The flex gate panics when its advice columns overflow, so the panic messages of the too small degrees are printed while searching.
+///
+/// The degree of a [`Pkcs1v15Circuit`] is a constant of its type, so a layout without [`Pkcs1v15Layout::K`] still takes the degree of [`Pkcs1v15CircuitBuilder::min_k`], which is never smaller than this one.
+///
+/// # Panics
+/// Panics if the knobs are invalid. See [`crate::ShapeError`].
+#[cfg(not(target_arch = "wasm32"))]
+pub fn min_k_for(bits_len: usize, msg_len: usize, options: Pkcs1v15CircuitBuilder) -> u32 {
+    let builder = options.bits_len(bits_len).msg_len(msg_len);
+    let estimated = builder.min_k().unwrap_or_else(|e| panic!("{}", e));
+    let shape = builder
+        .k(estimated)
+        .build()
+        .unwrap_or_else(|e| panic!("{}", e));
+    let (signature, public_key, msg) =
+        sample_witness::<Fr>(shape.bits_len, shape.msg_len, shape.sha2_chip_enabled);
+    let instances = shape_instances(&shape, &msg, true);
+    let circuit = MeasuredPkcs1v15Circuit {
+        signature,
+        public_key,
+        msg,
+    };
+    let fits = |k: u32| {
+        MEASURED_SHAPE.with(|measured| measured.set(Some(CircuitShape { k, ..shape })));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            MockProver::run(k, &circuit, instances.clone()).map(|prover| prover.verify().is_ok())
+        }));
+        matches!(result, Ok(Ok(true)))
+    };
+
+    // The lookup table of the SHA256 chip needs `2^sha256_lookup_bits` rows besides the reserved ones.
+    let mut lower = if shape.sha2_chip_enabled {
+        shape.sha256_lookup_bits as u32 + 1
+    } else {
+        1
+    };
+    let mut upper = estimated;
+    while !fits(upper) {
+        assert!(upper < MAX_MEASURED_K, "no degree fits the circuit");
+        upper += 1;
+    }
+    while lower < upper {
+        let k = (lower + upper) / 2;
+        if fits(k) {
+            upper = k;
+        } else {
+            lower = k + 1;
+        }
+    }
+    MEASURED_SHAPE.with(|measured| measured.set(None));
+    upper
+}
+
+/// The maximum degree tried by [`min_k_for`].
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_MEASURED_K: u32 = 26;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pkcs1v15_circuit_2048_256() {
+        type Circuit2048_256 = Pkcs1v15Circuit<Fr, 2048, 256, true>;
+        assert_eq!(Circuit2048_256::K, crate::recommend_k(2048, 256));
+        assert_eq!(Circuit2048_256::INFO.instance_layout(), Vec::<usize>::new());
+        let (params, vk, pk) = Circuit2048_256::setup();
+        Circuit2048_256::prove_sampled(&params, &vk, &pk, MultiOpenScheme::Gwc);
+    }
+
+    #[test]
+    fn test_pkcs1v15_circuit_public_digest() {
+        struct PublicDigestLayout;
+        impl Pkcs1v15Layout for PublicDigestLayout {
+            const NUM_ADVICE: usize = 4;
+            const PUBLIC_DIGEST: bool = true;
+            const K: Option<u32> = Some(15);
+        }
+        type Circuit1024 = Pkcs1v15Circuit<Fr, 1024, 64, false, PublicDigestLayout>;
+        assert_eq!(Circuit1024::min_k(), 15);
+        assert_eq!(Circuit1024::INFO.instance_layout(), vec![4]);
+        let (params, vk, pk) = Circuit1024::setup();
+        Circuit1024::prove_sampled(&params, &vk, &pk, MultiOpenScheme::Shplonk);
+    }
+
+    #[test]
+    fn test_pkcs1v15_circuit_1024_1024_preset() {
+        // The preset of the wasm build fits in 2^18 rows, which halves the params and the memory of the browser provers.
+        type Circuit1024_1024 = crate::Pkcs1v15_1024_1024EnabledBenchCircuit<Fr>;
+        assert_eq!(Circuit1024_1024::K, 18);
+        assert_eq!(Circuit1024_1024::LOOKUP_BITS, 17);
+        // The degree is the measured one of the default layout, which checks the modulus in every step of the modular power.
+        let options = Pkcs1v15CircuitBuilder::new().num_advice(3);
+        let min_k = min_k_for(1024, 1024, options);
+        assert!(min_k <= 18 && 18 <= min_k + 1, "measured k = {}", min_k);
+        let (params, vk, pk) = Circuit1024_1024::setup();
+        assert_eq!(vk.get_domain().k(), 18);
+        Circuit1024_1024::prove_sampled(&params, &vk, &pk, MultiOpenScheme::Gwc);
+    }
+
+    #[test]
+    fn test_pkcs1v15_circuit_rejects_long_msg() {
+        type Circuit1024 = Pkcs1v15Circuit<Fr, 1024, 128, true>;
+        assert_eq!(Circuit1024::MAX_MSG_LEN, 119);
+        let (signature, public_key, msg) = sample_witness::<Fr>(1024, 128, true);
+        let circuit =
+            Circuit1024::try_new(signature.clone(), public_key.clone(), vec![0; 119]).unwrap();
+        assert_eq!(circuit.msg.len(), 119);
+        let result = Circuit1024::try_new(signature.clone(), public_key.clone(), vec![0; 120]);
+        assert_eq!(
+            result.err(),
+            Some(MsgLenError::TooLong {
+                len: 120,
+                max_len: 119
+            })
+        );
+
+        type Digest1024 = Pkcs1v15Circuit<Fr, 1024, 128, false>;
+        assert_eq!(Digest1024::MAX_MSG_LEN, 32);
+        let result = Digest1024::try_new(signature, public_key, msg);
+        assert_eq!(result.err(), Some(MsgLenError::NotDigest { len: 119 }));
+    }
+
+    #[test]
+    fn test_pkcs1v15_circuit_public_validity() {
+        struct PublicValidityLayout;
+        impl Pkcs1v15Layout for PublicValidityLayout {
+            const NUM_ADVICE: usize = 4;
+            const PUBLIC_DIGEST: bool = true;
+            const PUBLIC_VALIDITY: bool = true;
+            const K: Option<u32> = Some(15);
+        }
+        type Circuit1024 = Pkcs1v15Circuit<Fr, 1024, 64, false, PublicValidityLayout>;
+        assert_eq!(Circuit1024::INFO.instance_layout(), vec![5]);
+        let (signature, public_key, mut msg) = sample_witness::<Fr>(1024, 64, false);
+        // The signature does not verify for the tampered digest.
+        msg[0] ^= 1;
+        let invalid = Circuit1024::instances_with_validity(&msg, false);
+        let valid = Circuit1024::instances(&msg);
+        assert_eq!(invalid[0][4], Fr::from(0));
+        assert_eq!(valid[0][4], Fr::from(1));
+        let circuit = Circuit1024::new(signature, public_key, msg);
+        let prover = MockProver::run(Circuit1024::K, &circuit, valid.clone()).unwrap();
+        assert!(prover.verify().is_err());
+
+        let (params, vk, pk) = Circuit1024::setup();
+        let instance_refs =
+            |instances: &[Vec<Fr>]| instances.iter().map(Vec::as_slice).collect::<Vec<&[Fr]>>();
+        let proof = create_proof_with(
+            &params,
+            &pk,
+            circuit,
+            &instance_refs(&invalid),
+            TranscriptKind::Blake2b,
+            MultiOpenScheme::Gwc,
+        )
+        .unwrap();
+        let verify = |instances: &[Vec<Fr>]| {
+            verify_proof_with(
+                &params,
+                &vk,
+                &proof,
+                &instance_refs(instances),
+                TranscriptKind::Blake2b,
+                MultiOpenScheme::Gwc,
+            )
+        };
+        verify(&invalid).unwrap();
+        assert!(verify(&valid).is_err());
+    }
+
+    crate::impl_pkcs1v15_layout!(
+        Shape1024_64Layout,
+        Shape1024_64Circuit,
+        Pkcs1v15CircuitBuilder::new()
+            .bits_len(1024)
+            .msg_len(64)
+            .build()
+    );
+
+    crate::impl_pkcs1v15_layout!(
+        Shape2048_256Layout,
+        Shape2048_256Circuit,
+        Pkcs1v15CircuitBuilder::new()
+            .bits_len(2048)
+            .msg_len(256)
+            .num_advice(6)
+            .build()
+    );
+
+    crate::impl_pkcs1v15_layout!(
+        Shape1024DigestLayout,
+        Shape1024DigestCircuit,
+        Pkcs1v15CircuitBuilder::new()
+            .bits_len(1024)
+            .sha2_chip_enabled(false)
+            .expose(crate::Exposure::Digest)
+            .num_advice(4)
+            .build()
+    );
+
+    fn mock_prove_sampled<
+        const BITS_LEN: usize,
+        const MSG_LEN: usize,
+        const SHA2: bool,
+        L: Pkcs1v15Layout,
+    >() {
+        let (circuit, instances) = Pkcs1v15Circuit::<Fr, BITS_LEN, MSG_LEN, SHA2, L>::sampled();
+        let k = Pkcs1v15Circuit::<Fr, BITS_LEN, MSG_LEN, SHA2, L>::K;
+        let prover = match MockProver::run(k, &circuit, instances) {
+            Ok(prover) => prover,
+            Err(e) => panic!("{:#?}", e),
+        };
+        prover.verify().unwrap();
+    }
+
+    #[test]
+    fn test_pkcs1v15_circuit_builder_1024_64() {
+        assert_eq!(Shape1024_64Layout::SHAPE.k, 17);
+        assert_eq!(
+            Shape1024_64Circuit::<Fr>::INFO,
+            Shape1024_64Layout::SHAPE.info()
+        );
+        mock_prove_sampled::<1024, 64, true, Shape1024_64Layout>();
+    }
+
+    #[test]
+    fn test_pkcs1v15_circuit_builder_2048_256() {
+        assert_eq!(Shape2048_256Layout::SHAPE.k, 17);
+        assert_eq!(Shape2048_256Circuit::<Fr>::NUM_ADVICE, 6);
+        mock_prove_sampled::<2048, 256, true, Shape2048_256Layout>();
+    }
+
+    #[test]
+    fn test_pkcs1v15_circuit_builder_1024_digest() {
+        assert_eq!(Shape1024DigestLayout::SHAPE.k, 14);
+        assert_eq!(
+            Shape1024DigestCircuit::<Fr>::INFO.instance_layout(),
+            vec![4]
+        );
+        mock_prove_sampled::<1024, 1024, false, Shape1024DigestLayout>();
+    }
+
+    #[test]
+    fn test_min_k_for_macro_configs() {
+        // (bits_len, msg_len, num_flex_advice, sha2_chip_enabled, k) of the macro calls in the wasm bindings.
+        let macro_configs = [
+            (1024, 64, 3, true, 19),
+            (1024, 128, 3, true, 19),
+            (1024, 1024, 3, true, 18),
+            (2048, 64, 3, true, 19),
+            (2048, 128, 3, true, 19),
+            (2048, 1024, 3, true, 19),
+            (2048, 1024, 1, false, 19),
+        ];
+        // The circuits kept at k = 19 so that the shared params of the demo and the keys already published for them stay valid, although their measured k may be smaller by more than one.
+        let shared_params = [
+            (1024, 64, true),
+            (1024, 128, true),
+            (2048, 64, true),
+            (2048, 128, true),
+            (2048, 1024, true),
+            (2048, 1024, false),
+        ];
+        for (bits_len, msg_len, num_advice, sha2_chip_enabled, k) in macro_configs {
+            let options = Pkcs1v15CircuitBuilder::new()
+                .num_advice(num_advice)
+                .sha2_chip_enabled(sha2_chip_enabled);
+            let min_k = min_k_for(bits_len, msg_len, options);
+            assert!(
+                min_k <= k,
+                "{} bits, {} bytes: k = {} is smaller than the measured k = {}",
+                bits_len,
+                msg_len,
+                k,
+                min_k
+            );
+            if !shared_params.contains(&(bits_len, msg_len, sha2_chip_enabled)) {
+                assert!(
+                    k <= min_k + 1,
+                    "{} bits, {} bytes: k = {} is wastefully larger than the measured k = {}",
+                    bits_len,
+                    msg_len,
+                    k,
+                    min_k
+                );
+            }
+            assert!(min_k <= options.bits_len(bits_len).msg_len(msg_len).min_k().unwrap());
+        }
+
+        // The circuits with their own params, e.g., `Pkcs1v15_1024_64WasmNoSha2Circuit`, must not be wastefully large either.
+        let options = Pkcs1v15CircuitBuilder::new()
+            .num_advice(4)
+            .sha2_chip_enabled(false)
+            .expose(crate::Exposure::Digest);
+        let min_k = min_k_for(1024, 64, options);
+        assert!(min_k <= 15 && 15 <= min_k + 1, "measured k = {}", min_k);
+    }
+
+    #[test]
+    fn test_pkcs1v15_circuit_builder_errors() {
+        let builder = Pkcs1v15CircuitBuilder::new();
+        assert_eq!(
+            builder.expose(crate::Exposure::Digest).build(),
+            Err(crate::ShapeError::DigestExposureWithSha2)
+        );
+        assert_eq!(
+            builder.bits_len(4096).build(),
+            Err(crate::ShapeError::UnsupportedBitsLen(4096))
+        );
+        assert_eq!(
+            builder.msg_len(100).build(),
+            Err(crate::ShapeError::InvalidMsgLen(100))
+        );
+        assert_eq!(
+            builder.num_advice(0).build(),
+            Err(crate::ShapeError::NoAdviceColumns)
+        );
+        let min_k = builder.min_k().unwrap();
+        assert_eq!(
+            builder.k(min_k - 1).build(),
+            Err(crate::ShapeError::KTooSmall {
+                k: min_k - 1,
+                min_k
+            })
+        );
+        assert_eq!(builder.k(min_k + 1).build().unwrap().k, min_k + 1);
+        // A longer message needs a larger degree.
+        assert!(builder.msg_len(4096).min_k().unwrap() > min_k);
+    }
+}
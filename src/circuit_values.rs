@@ -0,0 +1,157 @@
+use crate::{normalize_signature_bytes, RSAPubE, RSAPublicKey, RSASignature};
+use halo2_base::halo2_proofs::circuit::Value;
+use halo2_base::utils::PrimeField;
+use num_bigint::BigUint;
+use rsa::{traits::PublicKeyParts, RsaPublicKey};
+use std::fmt;
+
+/// An error returned by [`gen_circuit_values`] for a public key or a signature that cannot be given to a circuit.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CircuitValuesError {
+    /// The fixed exponent `e` of the circuit is even.
+    EvenExponent,
+    /// The modulus `n` is longer than the circuit supports.
+    ModulusTooLarge {
+        /// The bit length of `n`.
+        bits: u64,
+        /// The bit length of the modulus of the circuit.
+        max_bits: usize,
+    },
+    /// The signature has more bytes than the modulus of the circuit.
+    SignatureTooLong {
+        /// The byte length of the signature.
+        len: usize,
+        /// The byte length of the modulus of the circuit.
+        max_len: usize,
+    },
+}
+
+impl fmt::Display for CircuitValuesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitValuesError::EvenExponent => write!(f, "e must be odd"),
+            CircuitValuesError::ModulusTooLarge { bits, max_bits } => write!(
+                f,
+                "the modulus n has {} bits, but at most {} bits are supported",
+                bits, max_bits
+            ),
+            CircuitValuesError::SignatureTooLong { len, max_len } => write!(
+                f,
+                "the signature must be at most {} bytes, but {} bytes were given",
+                max_len, len
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CircuitValuesError {}
+
+impl<F: PrimeField> RSAPublicKey<F> {
+    /// Creates new [`RSAPublicKey`] with the fixed exponent from a public key of the rsa crate, e.g., one deserialized from JS.
+    ///
+    /// # Arguments
+    /// * public_key - the public key, whose modulus is used.
+    /// * e - the big-endian bytes of the fixed exponent of the circuit, which defaults to `default_e`.
+    /// * default_e - the exponent used if `e` is `None`, e.g., [`crate::Pkcs1v15Circuit::DEFAULT_E`].
+    ///
+    /// # Return values
+    /// Returns new [`RSAPublicKey`], or [`CircuitValuesError::EvenExponent`] if `e` is even.
+    pub fn from_rsa_key(
+        public_key: &RsaPublicKey,
+        e: Option<&[u8]>,
+        default_e: u128,
+    ) -> Result<Self, CircuitValuesError> {
+        let n = BigUint::from_bytes_be(&public_key.n().to_bytes_be());
+        let e = match e {
+            Some(e) => RSAPubE::fix_from_be_bytes(e).ok_or(CircuitValuesError::EvenExponent)?,
+            None => RSAPubE::Fix(BigUint::from(default_e)),
+        };
+        Ok(Self::new(Value::known(n), e))
+    }
+}
+
+impl<F: PrimeField> RSASignature<F> {
+    /// Creates new [`RSASignature`] in the same way as [`RSASignature::from_be_bytes`], but returns [`CircuitValuesError::SignatureTooLong`] instead of `None`.
+    pub fn try_from_be_bytes(bytes: &[u8], bits_len: usize) -> Result<Self, CircuitValuesError> {
+        let bytes = normalize_signature_bytes(bytes, bits_len).ok_or(
+            CircuitValuesError::SignatureTooLong {
+                len: bytes.len(),
+                max_len: bits_len / 8,
+            },
+        )?;
+        Ok(Self::new(Value::known(BigUint::from_bytes_be(&bytes))))
+    }
+}
+
+/// Converts the public key and the signature given to a prove function into the values of a circuit of `bits_len` bits modulus.
+///
+/// See [`RSAPublicKey::from_rsa_key`] for `public_key`, `e`, and `default_e`, and [`RSASignature::try_from_be_bytes`] for `signature`.
+///
+/// # Return values
+/// Returns the values for the circuit, or an error if they do not fit in it, which would otherwise surface as a failed synthesis.
+pub fn gen_circuit_values<F: PrimeField>(
+    public_key: &RsaPublicKey,
+    e: Option<&[u8]>,
+    default_e: u128,
+    signature: &[u8],
+    bits_len: usize,
+) -> Result<(RSAPublicKey<F>, RSASignature<F>), CircuitValuesError> {
+    let bits = public_key.n().bits() as u64;
+    if bits > bits_len as u64 {
+        return Err(CircuitValuesError::ModulusTooLarge {
+            bits,
+            max_bits: bits_len,
+        });
+    }
+    Ok((
+        RSAPublicKey::from_rsa_key(public_key, e, default_e)?,
+        RSASignature::try_from_be_bytes(signature, bits_len)?,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+    use rand::thread_rng;
+    use rsa::RsaPrivateKey;
+
+    #[test]
+    fn test_gen_circuit_values() {
+        let private_key =
+            RsaPrivateKey::new(&mut thread_rng(), 1024).expect("failed to generate a key");
+        let public_key = RsaPublicKey::from(&private_key);
+        let n = BigUint::from_bytes_be(&public_key.n().to_bytes_be());
+
+        // The leading zero bytes of the signature may be stripped.
+        let (key, signature) =
+            gen_circuit_values::<Fr>(&public_key, None, 65537, &[1, 2, 3], 1024).unwrap();
+        key.n.map(|key_n| assert_eq!(key_n, n));
+        assert!(matches!(key.e, RSAPubE::Fix(e) if e == BigUint::from(65537u32)));
+        signature
+            .c
+            .map(|c| assert_eq!(c, BigUint::from(0x010203u32)));
+        let (key, _) =
+            gen_circuit_values::<Fr>(&public_key, Some(&[3]), 65537, &[0; 128], 1024).unwrap();
+        assert!(matches!(key.e, RSAPubE::Fix(e) if e == BigUint::from(3u32)));
+
+        assert_eq!(
+            gen_circuit_values::<Fr>(&public_key, Some(&[1, 0]), 65537, &[], 1024).unwrap_err(),
+            CircuitValuesError::EvenExponent
+        );
+        assert_eq!(
+            gen_circuit_values::<Fr>(&public_key, None, 65537, &[0; 129], 1024).unwrap_err(),
+            CircuitValuesError::SignatureTooLong {
+                len: 129,
+                max_len: 128
+            }
+        );
+        assert_eq!(
+            gen_circuit_values::<Fr>(&public_key, None, 65537, &[], 512).unwrap_err(),
+            CircuitValuesError::ModulusTooLarge {
+                bits: 1024,
+                max_bits: 512
+            }
+        );
+    }
+}
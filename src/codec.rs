@@ -0,0 +1,251 @@
+use crate::cost::TRANSCRIPT_ELEMENT_BYTES;
+use base64::{
+    alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+    Engine,
+};
+use std::fmt;
+
+/// An error returned by the decode functions of the hex and base64 strings, e.g., [`decode_hex`].
+#[derive(Debug)]
+pub enum CodecError {
+    /// The string contains whitespace at the byte offset, e.g., a line break of a copied string.
+    Whitespace(usize),
+    /// The hex string without the `0x` prefix has an odd number of digits.
+    OddHexLength(usize),
+    /// The character at the byte offset is not a hex digit.
+    InvalidHexDigit {
+        /// The byte offset of the character.
+        position: usize,
+        /// The character.
+        digit: char,
+    },
+    /// The base64 string contains the characters of both the standard and the URL-safe alphabets.
+    MixedBase64Alphabets,
+    /// The string is not base64.
+    InvalidBase64(base64::DecodeError),
+    /// The decoded bytes do not have the expected length.
+    UnexpectedLength {
+        /// The expected byte length.
+        expected: usize,
+        /// The byte length of the decoded bytes.
+        actual: usize,
+    },
+    /// The decoded bytes are not a sequence of the 32 bytes elements of a transcript, so they cannot be a proof.
+    InvalidProofLength(usize),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::Whitespace(position) => {
+                write!(f, "the string contains whitespace at byte {}", position)
+            }
+            CodecError::OddHexLength(len) => {
+                write!(f, "the hex string has an odd number of digits, {}", len)
+            }
+            CodecError::InvalidHexDigit { position, digit } => {
+                write!(f, "invalid hex digit {:?} at byte {}", digit, position)
+            }
+            CodecError::MixedBase64Alphabets => write!(
+                f,
+                "the base64 string mixes the standard alphabet (+/) and the URL-safe alphabet (-_)"
+            ),
+            CodecError::InvalidBase64(e) => write!(f, "invalid base64: {}", e),
+            CodecError::UnexpectedLength { expected, actual } => write!(
+                f,
+                "expected {} bytes, but the string encodes {} bytes",
+                expected, actual
+            ),
+            CodecError::InvalidProofLength(len) => write!(
+                f,
+                "a proof is a non-empty sequence of {} bytes elements, but got {} bytes",
+                TRANSCRIPT_ELEMENT_BYTES, len
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {}
+
+/// Encodes `bytes` as a lowercase hex string without the `0x` prefix.
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Decodes a hex string in the same order as the bytes, i.e., without reversing them.
+///
+/// Both lowercase and uppercase digits and an optional `0x` prefix are accepted, but whitespace is rejected rather than skipped.
+pub fn decode_hex(hex: &str) -> Result<Vec<u8>, CodecError> {
+    check_no_whitespace(hex)?;
+    let offset = if hex.starts_with("0x") || hex.starts_with("0X") {
+        2
+    } else {
+        0
+    };
+    let mut bytes = Vec::with_capacity((hex.len() - offset) / 2);
+    let mut high = None;
+    for (i, c) in hex[offset..].char_indices() {
+        let digit = c.to_digit(16).ok_or(CodecError::InvalidHexDigit {
+            position: offset + i,
+            digit: c,
+        })? as u8;
+        match high.take() {
+            None => high = Some(digit),
+            Some(high) => bytes.push(high << 4 | digit),
+        }
+    }
+    if high.is_some() {
+        return Err(CodecError::OddHexLength(hex.len() - offset));
+    }
+    Ok(bytes)
+}
+
+/// Decodes a proof encoded by [`encode_hex`], checking that it consists of whole transcript elements.
+pub fn decode_proof_hex(hex: &str) -> Result<Vec<u8>, CodecError> {
+    let proof = decode_hex(hex)?;
+    if proof.is_empty() || proof.len() % TRANSCRIPT_ELEMENT_BYTES != 0 {
+        return Err(CodecError::InvalidProofLength(proof.len()));
+    }
+    Ok(proof)
+}
+
+/// Encodes `bytes` in base64 with the standard alphabet and padding, or with the URL-safe alphabet and without padding if `url_safe` is true.
+pub fn encode_base64(bytes: &[u8], url_safe: bool) -> String {
+    if url_safe {
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+    } else {
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    }
+}
+
+/// Decodes a base64 string in either the standard or the URL-safe alphabet, with or without padding.
+///
+/// Whitespace, a string mixing the two alphabets, and non-zero trailing bits are rejected.
+/// If `expected_len` is given, e.g., the byte length of the modulus for a signature, the decoded bytes must have that length.
+pub fn decode_base64(encoded: &str, expected_len: Option<usize>) -> Result<Vec<u8>, CodecError> {
+    check_no_whitespace(encoded)?;
+    let is_url_safe = encoded.contains(['-', '_']);
+    if is_url_safe && encoded.contains(['+', '/']) {
+        return Err(CodecError::MixedBase64Alphabets);
+    }
+    let alphabet = if is_url_safe {
+        &alphabet::URL_SAFE
+    } else {
+        &alphabet::STANDARD
+    };
+    let config =
+        GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+    let bytes = GeneralPurpose::new(alphabet, config)
+        .decode(encoded)
+        .map_err(CodecError::InvalidBase64)?;
+    match expected_len {
+        Some(expected) if bytes.len() != expected => Err(CodecError::UnexpectedLength {
+            expected,
+            actual: bytes.len(),
+        }),
+        _ => Ok(bytes),
+    }
+}
+
+fn check_no_whitespace(s: &str) -> Result<(), CodecError> {
+    match s.find(char::is_whitespace) {
+        Some(position) => Err(CodecError::Whitespace(position)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_hex_roundtrip() {
+        let mut bytes = vec![0u8; 96];
+        thread_rng().fill(&mut bytes[..]);
+        let hex = encode_hex(&bytes);
+        assert_eq!(hex, hex.to_lowercase());
+        assert_eq!(decode_hex(&hex).unwrap(), bytes);
+        assert_eq!(decode_hex(&hex.to_uppercase()).unwrap(), bytes);
+        assert_eq!(decode_hex(&format!("0x{}", hex)).unwrap(), bytes);
+        assert_eq!(decode_proof_hex(&hex).unwrap(), bytes);
+        assert_eq!(decode_hex("0x").unwrap(), Vec::<u8>::new());
+        assert_eq!(decode_hex("00ff10").unwrap(), vec![0x00, 0xff, 0x10]);
+    }
+
+    #[test]
+    fn test_invalid_hex() {
+        assert!(matches!(
+            decode_hex("abc"),
+            Err(CodecError::OddHexLength(3))
+        ));
+        assert!(matches!(
+            decode_hex("0xab\ncd"),
+            Err(CodecError::Whitespace(4))
+        ));
+        assert!(matches!(
+            decode_hex("abcg"),
+            Err(CodecError::InvalidHexDigit {
+                position: 3,
+                digit: 'g'
+            })
+        ));
+        assert!(matches!(
+            decode_proof_hex(&encode_hex(&[0u8; 33])),
+            Err(CodecError::InvalidProofLength(33))
+        ));
+        assert!(matches!(
+            decode_proof_hex(""),
+            Err(CodecError::InvalidProofLength(0))
+        ));
+    }
+
+    #[test]
+    fn test_base64_roundtrip() {
+        for len in [0, 1, 2, 3, 128, 256] {
+            let mut bytes = vec![0u8; len];
+            thread_rng().fill(&mut bytes[..]);
+            for url_safe in [false, true] {
+                let encoded = encode_base64(&bytes, url_safe);
+                assert_eq!(!url_safe && len % 3 != 0, encoded.ends_with('='));
+                assert_eq!(decode_base64(&encoded, None).unwrap(), bytes);
+                assert_eq!(decode_base64(&encoded, Some(len)).unwrap(), bytes);
+            }
+        }
+        // `0xfb 0xff` is `+/8=` in the standard alphabet and `-_8` in the URL-safe one.
+        assert_eq!(encode_base64(&[0xfb, 0xff], false), "+/8=");
+        assert_eq!(encode_base64(&[0xfb, 0xff], true), "-_8");
+        for encoded in ["+/8=", "+/8", "-_8", "-_8="] {
+            assert_eq!(decode_base64(encoded, None).unwrap(), vec![0xfb, 0xff]);
+        }
+    }
+
+    #[test]
+    fn test_invalid_base64() {
+        assert!(matches!(
+            decode_base64("AQ AB", None),
+            Err(CodecError::Whitespace(2))
+        ));
+        assert!(matches!(
+            decode_base64("+_8=", None),
+            Err(CodecError::MixedBase64Alphabets)
+        ));
+        assert!(matches!(
+            decode_base64("A!AB", None),
+            Err(CodecError::InvalidBase64(_))
+        ));
+        // The last digit of a 1 byte encoding must not have set trailing bits.
+        assert!(matches!(
+            decode_base64("AR==", None),
+            Err(CodecError::InvalidBase64(_))
+        ));
+        assert!(matches!(
+            decode_base64("AQAB", Some(4)),
+            Err(CodecError::UnexpectedLength {
+                expected: 4,
+                actual: 3
+            })
+        ));
+    }
+}
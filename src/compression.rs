@@ -0,0 +1,157 @@
+use crate::keys::catch_read_panic;
+use crate::params::{
+    check_params_len, G1_COMPRESSED_BYTES, G1_RAW_BYTES, G2_COMPRESSED_BYTES, G2_RAW_BYTES,
+};
+use crate::VERIFIER_PARAMS_MAGIC;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use halo2_base::halo2_proofs::{
+    halo2curves::bn256::Bn256,
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+    SerdeFormat,
+};
+use std::borrow::Cow;
+use std::io::{self, BufReader, Read, Write};
+
+/// The magic bytes at the head of gzip streams, by which the read functions of this crate detect compressed artifacts.
+pub const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+/// The magic bytes at the head of the decompressed stream of [`compress_params`], followed by the params with compressed points.
+pub(crate) const COMPRESSED_PARAMS_MAGIC: [u8; 4] = *b"HRCP";
+
+/// Compresses serialized [`ParamsKZG`] for transfer.
+///
+/// The G1 and G2 points are coordinates of random-looking field elements, so gzip alone barely shrinks them.
+/// The full params are therefore re-encoded with compressed points, which halves them, before gzip.
+/// The trimmed params produced by [`crate::extract_verifier_params`] are gzipped as they are.
+///
+/// # Arguments
+/// * params - bytes of either [`ParamsKZG`] written by [`Params::write`] or the trimmed params.
+///
+/// # Return values
+/// Returns the compressed params, which the read functions of this crate, e.g., [`crate::read_verifier_params`], accept as they are.
+pub fn compress_params(params: &[u8]) -> io::Result<Vec<u8>> {
+    if params.starts_with(&VERIFIER_PARAMS_MAGIC) {
+        return gzip(params);
+    }
+    check_params_len(params, G1_RAW_BYTES, G2_RAW_BYTES)?;
+    let params = catch_read_panic("params", || {
+        ParamsKZG::<Bn256>::read(&mut BufReader::new(params))
+    })?;
+    let mut processed = COMPRESSED_PARAMS_MAGIC.to_vec();
+    params.write_custom(&mut processed, SerdeFormat::Processed)?;
+    gzip(&processed)
+}
+
+/// Restores the params compressed by [`compress_params`].
+///
+/// Decompressing the points takes a square root per point, so this is slower than reading the uncompressed params.
+/// Bytes without [`GZIP_MAGIC`] are returned as they are, and gzipped params compressed by other tools are just gunzipped.
+pub fn decompress_params(bytes: &[u8]) -> io::Result<Vec<u8>> {
+    let bytes = gunzip_if_compressed(bytes)?;
+    if !bytes.starts_with(&COMPRESSED_PARAMS_MAGIC) {
+        return Ok(bytes.into_owned());
+    }
+    let mut reader = &bytes[COMPRESSED_PARAMS_MAGIC.len()..];
+    check_params_len(reader, G1_COMPRESSED_BYTES, G2_COMPRESSED_BYTES)?;
+    let params = catch_read_panic("params", || {
+        ParamsKZG::<Bn256>::read_custom(&mut reader, SerdeFormat::Processed)
+    })?;
+    let mut raw = vec![];
+    params.write_custom(&mut raw, SerdeFormat::RawBytes)?;
+    Ok(raw)
+}
+
+/// Compresses a proving key or a verifying key written by [`crate::write_pk_versioned`] or [`crate::write_vk_versioned`] with gzip.
+///
+/// The read functions of the keys, e.g., [`crate::read_pk_checked`], accept the compressed keys as they are.
+pub fn compress_key(key: &[u8]) -> io::Result<Vec<u8>> {
+    gzip(key)
+}
+
+/// Restores the key compressed by [`compress_key`]. Bytes without [`GZIP_MAGIC`] are returned as they are.
+pub fn decompress_key(bytes: &[u8]) -> io::Result<Vec<u8>> {
+    Ok(gunzip_if_compressed(bytes)?.into_owned())
+}
+
+/// Gunzips `bytes` if they start with [`GZIP_MAGIC`] and borrows them otherwise.
+pub(crate) fn gunzip_if_compressed(bytes: &[u8]) -> io::Result<Cow<[u8]>> {
+    if !bytes.starts_with(&GZIP_MAGIC) {
+        return Ok(Cow::Borrowed(bytes));
+    }
+    let mut decompressed = vec![];
+    GzDecoder::new(bytes).read_to_end(&mut decompressed)?;
+    Ok(Cow::Owned(decompressed))
+}
+
+/// Returns at most the first `len` bytes of `bytes` gunzipped as [`gunzip_if_compressed`] does, without decompressing the rest.
+pub(crate) fn gunzip_prefix(bytes: &[u8], len: usize) -> io::Result<Cow<[u8]>> {
+    if !bytes.starts_with(&GZIP_MAGIC) {
+        return Ok(Cow::Borrowed(&bytes[..len.min(bytes.len())]));
+    }
+    let mut prefix = vec![];
+    GzDecoder::new(bytes)
+        .take(len as u64)
+        .read_to_end(&mut prefix)?;
+    Ok(Cow::Owned(prefix))
+}
+
+fn gzip(bytes: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    encoder.write_all(bytes)?;
+    encoder.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{extract_verifier_params, gen_params_seeded, read_params, read_verifier_params};
+
+    const K: u32 = 8;
+
+    fn raw_params() -> Vec<u8> {
+        let params = gen_params_seeded(K, 0);
+        let mut bytes = vec![];
+        params.write(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_compress_params_roundtrip() {
+        let raw = raw_params();
+        let compressed = compress_params(&raw).unwrap();
+        assert!(compressed.starts_with(&GZIP_MAGIC));
+        assert!(compressed.len() < raw.len() * 2 / 3);
+        assert_eq!(decompress_params(&compressed).unwrap(), raw);
+        assert_eq!(decompress_params(&raw).unwrap(), raw);
+
+        let params = read_params(&compressed).unwrap();
+        let mut rewritten = vec![];
+        params.write(&mut rewritten).unwrap();
+        assert_eq!(rewritten, raw);
+    }
+
+    #[test]
+    fn test_compress_verifier_params_roundtrip() {
+        let raw = raw_params();
+        let trimmed = extract_verifier_params(&raw, 1).unwrap();
+        let compressed = compress_params(&trimmed).unwrap();
+        assert_eq!(decompress_params(&compressed).unwrap(), trimmed);
+        assert_eq!(
+            read_verifier_params(&compressed).unwrap().k(),
+            read_verifier_params(&trimmed).unwrap().k()
+        );
+        // The full params compressed by compress_params are also accepted.
+        let compressed_full = compress_params(&raw).unwrap();
+        assert_eq!(
+            extract_verifier_params(&compressed_full, 1).unwrap(),
+            trimmed
+        );
+    }
+
+    #[test]
+    fn test_decompress_params_gzipped_by_other_tools() {
+        let raw = raw_params();
+        let gzipped = gzip(&raw).unwrap();
+        assert_eq!(decompress_params(&gzipped).unwrap(), raw);
+        assert!(decompress_params(&GZIP_MAGIC).is_err());
+    }
+}
@@ -0,0 +1,595 @@
+use halo2_base::halo2_proofs::{
+    halo2curves::bn256::{Fr, G1Affine},
+    plonk::{Circuit, ConstraintSystem},
+    poly::Rotation,
+};
+use std::collections::BTreeSet;
+
+/// Upper bounds of the advice cells used by [`crate::RSAInstructions::verify_pkcs1v15_signature`] for each bit length of the RSA modulus.
+///
+/// They are measured with 64 bits limbs and `e = 65537`, i.e., [`crate::RSAPubE::Fix`], and the test suite checks them against [`halo2_base::halo2_proofs::dev::MockProver`].
+pub const PKCS1V15_ADVICE_CELLS: [(usize, usize); 2] = [(1024, 64_000), (2048, 192_000)];
+/// An upper bound of the advice cells used by the SHA256 chip of the pkcs1v15 circuits per 64 bytes block of the maximum message length.
+pub const SHA256_ADVICE_CELLS_PER_BLOCK: usize = 24_000;
+/// The lookup bits of the SHA256 chip of the pkcs1v15 circuits, whose lookup table needs `2^SHA256_LOOKUP_BITS` rows.
+pub const SHA256_LOOKUP_BITS: u32 = 16;
+/// The number of flex advice columns of the pkcs1v15 circuits with the SHA256 chip shipped by this crate.
+pub const RECOMMEND_K_NUM_ADVICE: usize = 3;
+/// The number of rows at the bottom of the circuit reserved for the blinding factors.
+const RESERVED_ROWS: usize = 16;
+
+/// Estimates the number of advice cells of a pkcs1v15 circuit from [`PKCS1V15_ADVICE_CELLS`] and [`SHA256_ADVICE_CELLS_PER_BLOCK`].
+///
+/// # Arguments
+/// * bits_len - the bit length of the RSA modulus.
+/// * msg_len - the maximum byte length of the message including the 9 bytes of the SHA256 padding, which is the same as `MSG_LEN` of [`crate::Pkcs1v15Circuit`].
+///
+/// # Panics
+/// Panics if no cost is measured for `bits_len`.
+pub fn estimate_advice_cells(bits_len: usize, msg_len: usize) -> usize {
+    let rsa_cells = pkcs1v15_advice_cells(bits_len)
+        .unwrap_or_else(|| panic!("no cost is measured for {} bits moduli", bits_len));
+    rsa_cells + sha256_advice_cells(msg_len)
+}
+
+/// Returns the entry of [`PKCS1V15_ADVICE_CELLS`] for `bits_len`, looked up without iterators to be usable in constants.
+pub(crate) const fn pkcs1v15_advice_cells(bits_len: usize) -> Option<usize> {
+    let mut i = 0;
+    while i < PKCS1V15_ADVICE_CELLS.len() {
+        if PKCS1V15_ADVICE_CELLS[i].0 == bits_len {
+            return Some(PKCS1V15_ADVICE_CELLS[i].1);
+        }
+        i += 1;
+    }
+    None
+}
+
+pub(crate) const fn sha256_advice_cells(msg_len: usize) -> usize {
+    let num_blocks = (msg_len + 63) / 64;
+    num_blocks * SHA256_ADVICE_CELLS_PER_BLOCK
+}
+
+/// Returns the minimal degree `k` of a pkcs1v15 circuit with [`RECOMMEND_K_NUM_ADVICE`] flex advice columns.
+///
+/// Unlike probing with [`halo2_base::halo2_proofs::dev::MockProver`], it only sums the costs in [`estimate_advice_cells`], so it is instant for any message length.
+/// See [`estimate_advice_cells`] for the arguments.
+pub fn recommend_k(bits_len: usize, msg_len: usize) -> u32 {
+    recommend_k_with_advice(bits_len, msg_len, RECOMMEND_K_NUM_ADVICE)
+}
+
+/// Returns the minimal degree `k` of a pkcs1v15 circuit with `num_advice` flex advice columns.
+pub fn recommend_k_with_advice(bits_len: usize, msg_len: usize, num_advice: usize) -> u32 {
+    assert!(num_advice > 0);
+    let cells = estimate_advice_cells(bits_len, msg_len);
+    k_of_advice_cells(cells, num_advice).max(SHA256_LOOKUP_BITS + 1)
+}
+
+/// Returns the same degree as [`recommend_k_with_advice`], or `None` if no cost is measured for `bits_len` or `num_advice` is zero.
+///
+/// It is a `const fn`, so it determines the degree of a circuit at compile time.
+pub const fn try_recommend_k_with_advice(
+    bits_len: usize,
+    msg_len: usize,
+    num_advice: usize,
+) -> Option<u32> {
+    if num_advice == 0 {
+        return None;
+    }
+    match pkcs1v15_advice_cells(bits_len) {
+        Some(rsa_cells) => {
+            let k = k_of_advice_cells(rsa_cells + sha256_advice_cells(msg_len), num_advice);
+            if k > SHA256_LOOKUP_BITS {
+                Some(k)
+            } else {
+                Some(SHA256_LOOKUP_BITS + 1)
+            }
+        }
+        None => None,
+    }
+}
+
+/// Returns the minimal degree `k` whose rows hold `cells` advice cells in `num_advice` columns besides the reserved rows.
+pub(crate) const fn k_of_advice_cells(cells: usize, num_advice: usize) -> u32 {
+    let rows = (cells + num_advice - 1) / num_advice + RESERVED_ROWS;
+    usize::BITS - (rows - 1).leading_zeros()
+}
+
+/// The constants of a pkcs1v15 circuit, which [`crate::Pkcs1v15Circuit`] registers as its `INFO`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitInfo {
+    /// The degree of the circuit, i.e., the minimum degree of its params.
+    pub k: u32,
+    /// The bit length of the RSA modulus.
+    pub bits_len: usize,
+    /// The maximum byte length of the message including the 9 bytes of the SHA256 padding, or 32 for the SHA256 digest if the SHA256 chip is disabled.
+    pub msg_len: usize,
+    /// The number of public instances.
+    pub num_instances: usize,
+    /// Whether the message is hashed in the circuit.
+    pub sha2_chip_enabled: bool,
+}
+
+impl CircuitInfo {
+    /// Returns the number of the rows of each instance column, which is one column of [`Self::num_instances`] rows if the circuit has public instances, and no column otherwise.
+    pub fn instance_layout(&self) -> Vec<usize> {
+        if self.num_instances > 0 {
+            vec![self.num_instances]
+        } else {
+            vec![]
+        }
+    }
+
+    /// Returns the maximum byte length of the message given to the circuit, which is [`Self::msg_len`] minus the 9 bytes of the SHA256 padding, or 32 for the SHA256 digest if the SHA256 chip is disabled.
+    pub const fn max_msg_len(&self) -> usize {
+        if self.sha2_chip_enabled {
+            self.msg_len - 9
+        } else {
+            32
+        }
+    }
+
+    /// Checks that `msg` fits in the circuit, i.e., it is at most [`Self::max_msg_len`] bytes, or exactly a 32 bytes digest if the SHA256 chip is disabled.
+    pub fn check_msg_len(&self, msg: &[u8]) -> Result<(), MsgLenError> {
+        if !self.sha2_chip_enabled && msg.len() != 32 {
+            return Err(MsgLenError::NotDigest { len: msg.len() });
+        }
+        let max_len = self.max_msg_len();
+        if msg.len() > max_len {
+            return Err(MsgLenError::TooLong {
+                len: msg.len(),
+                max_len,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// An error returned by [`CircuitInfo::check_msg_len`] for a message that does not fit in the circuit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MsgLenError {
+    /// The message hashed in the circuit is longer than [`CircuitInfo::max_msg_len`].
+    TooLong { len: usize, max_len: usize },
+    /// The SHA256 chip is disabled, but the message is not a 32 bytes SHA256 digest.
+    NotDigest { len: usize },
+}
+
+impl std::fmt::Display for MsgLenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooLong { len, max_len } => write!(
+                f,
+                "the message must be at most {} bytes, but {} bytes were given",
+                max_len, len
+            ),
+            Self::NotDigest { len } => write!(
+                f,
+                "the message must be a 32 bytes SHA256 digest, but {} bytes were given",
+                len
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MsgLenError {}
+
+/// The byte size of a compressed G1 point and of a scalar in the transcript.
+pub(crate) const TRANSCRIPT_ELEMENT_BYTES: usize = 32;
+
+/// Estimates the byte size of a proof of the circuit `C` with the GWC multi-open scheme from the columns, queries, and arguments of its constraint system.
+///
+/// The proof consists of the commitments to the advice columns, the lookup and permutation arguments, and the quotient polynomial, the evaluations at the queried rotations, and one opening commitment per distinct rotation.
+/// The SHPLONK multi-open scheme replaces the opening commitments with two commitments, so its proofs are usually a few elements smaller.
+pub fn estimate_proof_size<C: Circuit<Fr>>() -> usize {
+    let cs = constraint_system::<C>();
+    let num_lookups = cs.lookups().len();
+    let num_permutation_chunks = num_permutation_chunks(&cs);
+    let num_commitments = num_commitments(&cs);
+    let num_evaluations = cs.advice_queries().len()
+        + cs.fixed_queries().len()
+        + 1
+        + cs.permutation().get_columns().len()
+        + (3 * num_permutation_chunks).saturating_sub(1)
+        + 5 * num_lookups;
+    (num_commitments + num_evaluations + num_opening_points(&cs)) * TRANSCRIPT_ELEMENT_BYTES
+}
+
+/// Estimates the peak bytes of the memory used to prove the circuit `C` with params of degree `k`.
+///
+/// It counts the params, the fixed and permutation polynomials of the proving key in the Lagrange and monomial bases and over the extended domain, and the advice and lookup polynomials of the witness including their extended evaluations.
+/// The vanishing argument is evaluated over the extended domain, whose size is `2^k` times the degree of the quotient polynomial rounded up to a power of two, so it dominates for circuits of high degree.
+pub fn estimate_proving_memory<C: Circuit<Fr>>(k: u32) -> usize {
+    let cs = constraint_system::<C>();
+    let n = 1usize << k;
+    let extended_n = extended_domain_size(&cs, k);
+    let witness_columns =
+        cs.num_advice_columns() + 3 * cs.lookups().len() + num_permutation_chunks(&cs);
+    let witness_scalars = witness_columns * (2 * n + extended_n);
+    params_bytes(k) + (proving_key_scalars(&cs, k) + witness_scalars) * std::mem::size_of::<Fr>()
+}
+
+/// Estimates the peak bytes of the memory used to generate and serialize the keys of the circuit `C` with params of degree `k`.
+///
+/// It counts the params, the proving key as in [`estimate_proving_memory`], and its serialization, which is written while the proving key is still alive.
+/// The witness polynomials are not allocated by the key generation, so the peak is below that of the proving, but the proving key alone is several times larger than the params.
+pub fn estimate_keygen_memory<C: Circuit<Fr>>(k: u32) -> usize {
+    let cs = constraint_system::<C>();
+    params_bytes(k) + 2 * proving_key_scalars(&cs, k) * std::mem::size_of::<Fr>()
+}
+
+/// Returns the bytes of the params of degree `k`, i.e., the `2^k` G1 points of both the monomial and the Lagrange bases.
+pub(crate) fn params_bytes(k: u32) -> usize {
+    2 * (1usize << k) * std::mem::size_of::<G1Affine>()
+}
+
+/// Returns the size of the extended domain, which is `2^k` times the degree of the quotient polynomial rounded up to a power of two.
+fn extended_domain_size(cs: &ConstraintSystem<Fr>, k: u32) -> usize {
+    (1usize << k) * (cs.degree() - 1).next_power_of_two()
+}
+
+/// Returns the number of the scalars held by the proving key for params of degree `k`.
+fn proving_key_scalars(cs: &ConstraintSystem<Fr>, k: u32) -> usize {
+    let n = 1usize << k;
+    let extended_n = extended_domain_size(cs, k);
+    let num_key_columns = cs.num_fixed_columns() + cs.permutation().get_columns().len();
+    // The values and coefficients over the domain and the evaluations over the extended domain of each column, and those of l_0, l_last, and l_active_row.
+    num_key_columns * (2 * n + extended_n) + 3 * extended_n
+}
+
+/// Returns the byte size of the commitments at the head of a proof of the circuit `C`, which the prover writes before the evaluations and the opening proof.
+pub fn proof_commitments_size<C: Circuit<Fr>>() -> usize {
+    num_commitments(&constraint_system::<C>()) * TRANSCRIPT_ELEMENT_BYTES
+}
+
+pub(crate) fn constraint_system<C: Circuit<Fr>>() -> ConstraintSystem<Fr> {
+    let mut cs = ConstraintSystem::default();
+    C::configure(&mut cs);
+    cs
+}
+
+/// Returns the number of the grand product polynomials of the permutation argument, each of which covers `degree - 2` columns.
+pub(crate) fn num_permutation_chunks(cs: &ConstraintSystem<Fr>) -> usize {
+    let chunk_len = cs.degree() - 2;
+    (cs.permutation().get_columns().len() + chunk_len - 1) / chunk_len
+}
+
+/// Returns the number of the commitments of the advice columns, the lookup and permutation arguments, and the quotient polynomial.
+pub(crate) fn num_commitments(cs: &ConstraintSystem<Fr>) -> usize {
+    cs.num_advice_columns()
+        + 3 * cs.lookups().len()
+        + num_permutation_chunks(cs)
+        // The random polynomial and the pieces of the quotient polynomial.
+        + 1
+        + (cs.degree() - 1)
+}
+
+/// Returns the number of the distinct rotations at which the polynomials are opened, each of which takes one opening commitment with the GWC multi-open scheme.
+pub(crate) fn num_opening_points(cs: &ConstraintSystem<Fr>) -> usize {
+    let mut rotations = cs
+        .advice_queries()
+        .iter()
+        .map(|(_, rotation)| rotation.0)
+        .chain(cs.fixed_queries().iter().map(|(_, rotation)| rotation.0))
+        .chain([Rotation::cur().0, Rotation::next().0])
+        .collect::<BTreeSet<i32>>();
+    if !cs.lookups().is_empty() {
+        rotations.insert(Rotation::prev().0);
+    }
+    if num_permutation_chunks(cs) > 1 {
+        rotations.insert(-(cs.blinding_factors() as i32 + 1));
+    }
+    rotations.len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::big_uint::decompose_biguint;
+    use crate::{BigUintConfig, RSAConfig, RSAInstructions, RSAPubE, RSAPublicKey, RSASignature};
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use halo2_base::{
+        gates::range::{RangeConfig, RangeStrategy::Vertical},
+        gates::{GateInstructions, RangeInstructions},
+        utils::PrimeField,
+        AssignedValue, SKIP_FIRST_PASS,
+    };
+    use num_bigint::BigUint;
+    use rand::thread_rng;
+    use rsa::{traits::PublicKeyParts, Pkcs1v15Sign, RsaPrivateKey};
+    use sha2::{Digest, Sha256};
+    use std::cell::Cell;
+    use std::marker::PhantomData;
+
+    const K: usize = 14;
+
+    struct TestMeasurePkcs1v15Circuit<F: PrimeField, const BITS_LEN: usize> {
+        measured: Cell<usize>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField, const BITS_LEN: usize> Circuit<F> for TestMeasurePkcs1v15Circuit<F, BITS_LEN> {
+        type Config = RSAConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config = RangeConfig::configure(meta, Vertical, &[50], &[4], 1, K - 1, 0, K);
+            let biguint_config = BigUintConfig::construct(range_config, 64);
+            RSAConfig::construct(biguint_config, BITS_LEN, 5)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "measure the pkcs1v15 signature verification",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    let mut rng = thread_rng();
+                    let private_key =
+                        RsaPrivateKey::new(&mut rng, BITS_LEN).expect("failed to generate a key");
+                    let digest = Sha256::digest(b"hello world");
+                    let sign = private_key
+                        .sign(Pkcs1v15Sign::new::<Sha256>(), &digest)
+                        .unwrap();
+                    let n_big = BigUint::from_bytes_be(&private_key.n().to_bytes_be());
+                    let e_fix = RSAPubE::Fix(BigUint::from(65537u32));
+                    let public_key = config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                    let sign = config.assign_signature(
+                        ctx,
+                        RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+                    )?;
+                    let hashed_msg_limbs =
+                        decompose_biguint::<F>(&BigUint::from_bytes_be(&digest), 4, 64);
+                    let hashed_msg_assigned = hashed_msg_limbs
+                        .into_iter()
+                        .map(|limb| config.gate().load_witness(ctx, Value::known(limb)))
+                        .collect::<Vec<AssignedValue<F>>>();
+                    let cells_before = ctx.total_advice;
+                    let is_valid = config.verify_pkcs1v15_signature(
+                        ctx,
+                        &public_key,
+                        &hashed_msg_assigned,
+                        &sign,
+                    )?;
+                    self.measured.set(ctx.total_advice - cells_before);
+                    config.gate().assert_is_const(ctx, &is_valid, F::one());
+                    config.range().finalize(ctx);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn measure_pkcs1v15<const BITS_LEN: usize>() -> usize {
+        let circuit = TestMeasurePkcs1v15Circuit::<Fr, BITS_LEN> {
+            measured: Cell::new(0),
+            _f: PhantomData,
+        };
+        let prover = match MockProver::run(K as u32, &circuit, vec![]) {
+            Ok(prover) => prover,
+            Err(e) => panic!("{:#?}", e),
+        };
+        prover.verify().unwrap();
+        circuit.measured.get()
+    }
+
+    fn assert_bounds(bits_len: usize, measured: usize) {
+        let (_, table) = PKCS1V15_ADVICE_CELLS
+            .iter()
+            .find(|(bits, _)| *bits == bits_len)
+            .unwrap();
+        println!(
+            "{} bits: measured {} advice cells, table {}",
+            bits_len, measured, table
+        );
+        // The table must be an upper bound, but not so loose that the recommended k is too large.
+        assert!(measured <= *table);
+        assert!(*table <= 2 * measured);
+    }
+
+    #[test]
+    fn test_pkcs1v15_cost_table_1024() {
+        assert_bounds(1024, measure_pkcs1v15::<1024>());
+    }
+
+    #[test]
+    fn test_pkcs1v15_cost_table_2048() {
+        assert_bounds(2048, measure_pkcs1v15::<2048>());
+    }
+
+    #[test]
+    fn test_recommend_k_matches_shipped_configs() {
+        // (bits_len, msg_len, num_flex_advice, k) of the circuits in the wasm bindings and the benchmarks.
+        let shipped = [
+            (1024, 64, 3, 19),
+            (1024, 128, 3, 19),
+            (1024, 1024, 3, 18),
+            (2048, 64, 3, 19),
+            (2048, 128, 3, 19),
+            (2048, 1024, 3, 19),
+            (2048, 10240, 21, 19),
+        ];
+        for (bits_len, msg_len, num_advice, k) in shipped {
+            let recommended = recommend_k_with_advice(bits_len, msg_len, num_advice);
+            assert!(
+                recommended <= k,
+                "{} bits, {} bytes: recommended k = {} exceeds the shipped k = {}",
+                bits_len,
+                msg_len,
+                recommended,
+                k
+            );
+            assert!(recommended > SHA256_LOOKUP_BITS);
+        }
+        assert_eq!(
+            recommend_k(2048, 1024),
+            recommend_k_with_advice(2048, 1024, 3)
+        );
+        assert_eq!(
+            try_recommend_k_with_advice(2048, 10240, 21),
+            Some(recommend_k_with_advice(2048, 10240, 21))
+        );
+        assert_eq!(try_recommend_k_with_advice(4096, 1024, 3), None);
+        assert_eq!(try_recommend_k_with_advice(2048, 1024, 0), None);
+    }
+
+    #[test]
+    fn test_recommend_k_is_monotone() {
+        let mut prev = 0;
+        for msg_len in (64..=16384).step_by(64) {
+            let k = recommend_k(2048, msg_len);
+            assert!(k >= prev);
+            prev = k;
+        }
+        assert!(recommend_k(1024, 1024) <= recommend_k(2048, 1024));
+    }
+
+    #[test]
+    #[should_panic(expected = "no cost is measured for 4096 bits moduli")]
+    fn test_recommend_k_unknown_bits_len() {
+        recommend_k(4096, 1024);
+    }
+
+    #[test]
+    fn test_keygen_memory_doubles_with_k() {
+        let k = K as u32;
+        let keygen = estimate_keygen_memory::<TestMeasurePkcs1v15Circuit<Fr, 1024>>(k);
+        // The proving key and its serialization take more than the params.
+        assert!(keygen > 2 * params_bytes(k));
+        assert_eq!(
+            estimate_keygen_memory::<TestMeasurePkcs1v15Circuit<Fr, 1024>>(k + 1),
+            2 * keygen
+        );
+    }
+
+    #[cfg(feature = "sha256")]
+    mod sha256 {
+        use super::*;
+        use halo2_dynamic_sha256::Sha256DynamicConfig;
+
+        const SHA256_K: usize = SHA256_LOOKUP_BITS as usize + 1;
+
+        #[derive(Debug, Clone)]
+        struct TestMeasureSha256Config<F: PrimeField> {
+            biguint_config: BigUintConfig<F>,
+            sha256_config: Sha256DynamicConfig<F>,
+        }
+
+        struct TestMeasureSha256Circuit<F: PrimeField, const MSG_LEN: usize> {
+            measured: Cell<usize>,
+            _f: PhantomData<F>,
+        }
+
+        impl<F: PrimeField, const MSG_LEN: usize> Circuit<F> for TestMeasureSha256Circuit<F, MSG_LEN> {
+            type Config = TestMeasureSha256Config<F>;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                unimplemented!();
+            }
+
+            fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+                let range_config = RangeConfig::configure(
+                    meta,
+                    Vertical,
+                    &[RECOMMEND_K_NUM_ADVICE],
+                    &[1],
+                    1,
+                    SHA256_K - 1,
+                    0,
+                    SHA256_K,
+                );
+                let biguint_config = BigUintConfig::construct(range_config.clone(), 64);
+                let sha256_config = Sha256DynamicConfig::configure(
+                    meta,
+                    vec![MSG_LEN],
+                    range_config,
+                    SHA256_LOOKUP_BITS as usize,
+                    1,
+                    true,
+                );
+                Self::Config {
+                    biguint_config,
+                    sha256_config,
+                }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<F>,
+            ) -> Result<(), Error> {
+                config.sha256_config.load(&mut layouter)?;
+                config
+                    .biguint_config
+                    .range()
+                    .load_lookup_table(&mut layouter)?;
+                let mut first_pass = SKIP_FIRST_PASS;
+                layouter.assign_region(
+                    || "measure the sha256 chip",
+                    |region| {
+                        if first_pass {
+                            first_pass = false;
+                            return Ok(());
+                        }
+
+                        let mut aux = config.biguint_config.new_context(region);
+                        let ctx = &mut aux;
+                        let mut sha256_config = config.sha256_config.clone();
+                        let cells_before = ctx.total_advice;
+                        sha256_config.digest(ctx, b"hello world", None)?;
+                        self.measured.set(ctx.total_advice - cells_before);
+                        config.biguint_config.range().finalize(ctx);
+                        Ok(())
+                    },
+                )?;
+                Ok(())
+            }
+        }
+
+        fn measure_sha256<const MSG_LEN: usize>() -> usize {
+            let circuit = TestMeasureSha256Circuit::<Fr, MSG_LEN> {
+                measured: Cell::new(0),
+                _f: PhantomData,
+            };
+            let prover = match MockProver::run(SHA256_K as u32, &circuit, vec![]) {
+                Ok(prover) => prover,
+                Err(e) => panic!("{:#?}", e),
+            };
+            prover.verify().unwrap();
+            circuit.measured.get()
+        }
+
+        #[test]
+        fn test_sha256_cost_table() {
+            // The cost depends on the maximum message length rather than the actual one.
+            let per_block = (measure_sha256::<256>() - measure_sha256::<128>()) / 2;
+            println!(
+                "sha256: measured {} advice cells per block, table {}",
+                per_block, SHA256_ADVICE_CELLS_PER_BLOCK
+            );
+            assert!(per_block <= SHA256_ADVICE_CELLS_PER_BLOCK);
+            assert!(SHA256_ADVICE_CELLS_PER_BLOCK <= 2 * per_block);
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use halo2_base::halo2_proofs::{
+    dev::MockProver,
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use halo2_base::{utils::PrimeField, Context};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::fmt;
+
+/// The name of the region of [`CircuitCostReport::regions`] holding the cells assigned outside the named regions of the chips.
+pub const UNNAMED_COST_REGION: &str = "other";
+
+thread_local! {
+    static RECORDER: RefCell<Option<RegionRecorder>> = RefCell::new(None);
+}
+
+/// The cells assigned in a named region of the chips, e.g., `"bigint mul"`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegionCost {
+    /// The name of the region.
+    pub name: String,
+    /// The advice cells assigned in the region, excluding those of the regions nested in it.
+    pub advice_cells: usize,
+    /// The cells range-checked with the lookup table in the region, excluding those of the regions nested in it.
+    pub lookup_cells: usize,
+    /// The number of times the region was entered.
+    pub count: usize,
+}
+
+/// The columns, the lookups, and the cells of a circuit measured by [`CircuitCostReport::measure`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CircuitCostReport {
+    /// The degree of the circuit.
+    pub k: u32,
+    pub advice_columns: usize,
+    pub fixed_columns: usize,
+    pub instance_columns: usize,
+    /// The number of the lookup arguments.
+    pub lookups: usize,
+    /// The advice cells assigned by the chips of this crate through the context of halo2-base.
+    pub advice_cells: usize,
+    /// The cells range-checked with the lookup table of halo2-base.
+    pub lookup_cells: usize,
+    /// The rows filled by the advice and lookup cells if they were spread evenly over the advice columns, i.e., a lower bound of the used rows.
+    pub rows: usize,
+    /// The cells of each named region in the order of their first assignment, followed by [`UNNAMED_COST_REGION`].
+    pub regions: Vec<RegionCost>,
+}
+
+impl CircuitCostReport {
+    /// Synthesizes `circuit` with [`MockProver`] for params of degree `k` and reports its costs.
+    ///
+    /// The columns and the lookups are taken from the constraint system of `C`, and the cells are recorded in the regions named by the chips of this crate, i.e., `"bigint assign"`, `"bigint mul"`, `"bigint refresh"`, `"bigint mul_mod"`, `"bigint carry check"`, `"rsa modpow"`, `"pkcs1v15 padding check"`, `"sha256 rounds"`, `"base64url decode"`, `"dkim relaxed canonicalization"`, `"email domain"`, `"sha256 padding"`, and `"printable ascii"`.
+    /// The constraints are not checked, and the public instances are filled with zeros.
+    ///
+    /// # Errors
+    /// Returns the error of [`MockProver::run`], e.g., if the circuit does not fit in `2^k` rows.
+    pub fn measure<C: Circuit<Fr>>(k: u32, circuit: &C) -> Result<Self, Error> {
+        let mut cs = ConstraintSystem::<Fr>::default();
+        C::configure(&mut cs);
+        let instances = vec![vec![]; cs.num_instance_columns()];
+        let _guard = RecorderGuard::start();
+        MockProver::run(k, circuit, instances)?;
+        let recorder = RECORDER
+            .with(|recorder| recorder.borrow_mut().take())
+            .unwrap_or_default();
+
+        let mut regions = recorder.regions;
+        // The unnamed region is created by the first top-level region, so it is moved to the end.
+        if let Some(index) = regions
+            .iter()
+            .position(|region| region.name == UNNAMED_COST_REGION)
+        {
+            let unnamed = regions.remove(index);
+            regions.push(unnamed);
+        }
+        let advice_cells = regions
+            .iter()
+            .map(|region| region.advice_cells)
+            .sum::<usize>();
+        let lookup_cells = regions
+            .iter()
+            .map(|region| region.lookup_cells)
+            .sum::<usize>();
+        let advice_columns = cs.num_advice_columns();
+        Ok(Self {
+            k,
+            advice_columns,
+            fixed_columns: cs.num_fixed_columns(),
+            instance_columns: cs.num_instance_columns(),
+            lookups: cs.lookups().len(),
+            advice_cells,
+            lookup_cells,
+            rows: (advice_cells + lookup_cells + advice_columns - 1) / advice_columns.max(1),
+            regions,
+        })
+    }
+
+    /// Returns the region named `name`, if any.
+    pub fn region(&self, name: &str) -> Option<&RegionCost> {
+        self.regions.iter().find(|region| region.name == name)
+    }
+
+    /// Serializes the report into a JSON object with the camelCase names of the fields.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("the report is always serializable")
+    }
+}
+
+impl fmt::Display for CircuitCostReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "k = {}: {} advice columns, {} fixed columns, {} instance columns, {} lookups",
+            self.k, self.advice_columns, self.fixed_columns, self.instance_columns, self.lookups
+        )?;
+        writeln!(
+            f,
+            "{} advice cells and {} lookup cells in at least {} of {} rows",
+            self.advice_cells,
+            self.lookup_cells,
+            self.rows,
+            1usize << self.k
+        )?;
+        write!(
+            f,
+            "{:<24} {:>12} {:>12} {:>8}",
+            "region", "advice cells", "lookup cells", "count"
+        )?;
+        for region in &self.regions {
+            write!(
+                f,
+                "\n{:<24} {:>12} {:>12} {:>8}",
+                region.name, region.advice_cells, region.lookup_cells, region.count
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Records the cells of the named regions on the calling thread while [`CircuitCostReport::measure`] runs.
+#[derive(Default)]
+struct RegionRecorder {
+    regions: Vec<RegionCost>,
+    stack: Vec<OpenRegion>,
+    /// The cells of the context at the end of the last top-level region.
+    last_cells: (usize, usize),
+}
+
+struct OpenRegion {
+    index: usize,
+    start_cells: (usize, usize),
+    nested_cells: (usize, usize),
+}
+
+impl RegionRecorder {
+    fn add(&mut self, name: &str, cells: (usize, usize), count: usize) -> usize {
+        let index = match self.regions.iter().position(|region| region.name == name) {
+            Some(index) => index,
+            None => {
+                self.regions.push(RegionCost {
+                    name: name.to_string(),
+                    advice_cells: 0,
+                    lookup_cells: 0,
+                    count: 0,
+                });
+                self.regions.len() - 1
+            }
+        };
+        let region = &mut self.regions[index];
+        region.advice_cells += cells.0;
+        region.lookup_cells += cells.1;
+        region.count += count;
+        index
+    }
+}
+
+/// Stops recording when [`CircuitCostReport::measure`] returns or panics, e.g., when the flex gate overflows.
+struct RecorderGuard;
+
+impl RecorderGuard {
+    fn start() -> Self {
+        RECORDER.with(|recorder| *recorder.borrow_mut() = Some(RegionRecorder::default()));
+        Self
+    }
+}
+
+impl Drop for RecorderGuard {
+    fn drop(&mut self) {
+        RECORDER.with(|recorder| recorder.borrow_mut().take());
+    }
+}
+
+fn context_cells<F: PrimeField>(ctx: &Context<'_, F>) -> (usize, usize) {
+    (ctx.total_advice, ctx.cells_to_lookup.len())
+}
+
+/// Opens the region `name` of [`CircuitCostReport`], which is closed by [`end_cost_region`].
+///
+/// It does nothing unless [`CircuitCostReport::measure`] runs on the calling thread, so the chips call it unconditionally.
+pub(crate) fn begin_cost_region<F: PrimeField>(ctx: &Context<'_, F>, name: &str) {
+    RECORDER.with(|recorder| {
+        let mut recorder = recorder.borrow_mut();
+        let recorder = match recorder.as_mut() {
+            Some(recorder) => recorder,
+            None => return,
+        };
+        let cells = context_cells(ctx);
+        if recorder.stack.is_empty() {
+            // The cells of a new context start from zero.
+            let last = if cells.0 >= recorder.last_cells.0 {
+                recorder.last_cells
+            } else {
+                (0, 0)
+            };
+            recorder.add(
+                UNNAMED_COST_REGION,
+                (cells.0 - last.0, cells.1.saturating_sub(last.1)),
+                0,
+            );
+        }
+        let index = recorder.add(name, (0, 0), 0);
+        recorder.stack.push(OpenRegion {
+            index,
+            start_cells: cells,
+            nested_cells: (0, 0),
+        });
+    });
+}
+
+/// Closes the region opened last by [`begin_cost_region`].
+pub(crate) fn end_cost_region<F: PrimeField>(ctx: &Context<'_, F>) {
+    RECORDER.with(|recorder| {
+        let mut recorder = recorder.borrow_mut();
+        let recorder = match recorder.as_mut() {
+            Some(recorder) => recorder,
+            None => return,
+        };
+        let open = match recorder.stack.pop() {
+            Some(open) => open,
+            None => return,
+        };
+        let cells = context_cells(ctx);
+        let total = (
+            cells.0 - open.start_cells.0,
+            cells.1.saturating_sub(open.start_cells.1),
+        );
+        let region = &mut recorder.regions[open.index];
+        region.advice_cells += total.0 - open.nested_cells.0;
+        region.lookup_cells += total.1.saturating_sub(open.nested_cells.1);
+        region.count += 1;
+        match recorder.stack.last_mut() {
+            Some(parent) => {
+                parent.nested_cells.0 += total.0;
+                parent.nested_cells.1 += total.1;
+            }
+            None => recorder.last_cells = cells,
+        }
+    });
+}
+
+#[cfg(all(test, feature = "sha256"))]
+mod test {
+    use super::*;
+    use crate::Pkcs1v15_1024_64EnabledBenchCircuit;
+
+    #[test]
+    fn test_cost_report_1024_64() {
+        type Circuit1024 = Pkcs1v15_1024_64EnabledBenchCircuit<Fr>;
+        let (circuit, _) = Circuit1024::sampled();
+        let report = CircuitCostReport::measure(Circuit1024::K, &circuit).unwrap();
+        println!("{}", report);
+        assert!(report.rows <= 1 << 17);
+        assert_eq!(report.instance_columns, 0);
+        for name in [
+            "bigint assign",
+            "bigint mul",
+            "bigint mul_mod",
+            "bigint carry check",
+            "rsa modpow",
+            "pkcs1v15 padding check",
+            "sha256 rounds",
+        ] {
+            let region = report.region(name).unwrap();
+            assert!(region.advice_cells > 0, "{}", name);
+        }
+        assert_eq!(
+            report
+                .regions
+                .iter()
+                .map(|region| region.advice_cells)
+                .sum::<usize>(),
+            report.advice_cells
+        );
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["k"], Circuit1024::K);
+        assert_eq!(
+            json["regions"].as_array().unwrap().len(),
+            report.regions.len()
+        );
+    }
+}
@@ -0,0 +1,279 @@
+use halo2_base::halo2_proofs::dev::{MockProver, VerifyFailure};
+use halo2_base::utils::PrimeField;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+
+/// The maximum number of failed checks kept in a [`DiagnosticsScope`], which bounds the memory when a circuit is synthesized more than once in the scope.
+const MAX_FAILED_CHECKS: usize = 64;
+
+thread_local! {
+    // The checks recorded in the active scope, or `None` outside of any scope.
+    static FAILED_CHECKS: RefCell<Option<Vec<String>>> = RefCell::new(None);
+}
+
+/// Records a check of a chip that fails for the assigned witness, e.g., `"padding mismatch at byte 10"`, so that [`DiagnosticsScope::diagnose_failure`] can explain the failed constraints.
+///
+/// The chips call it while synthesizing if the values of the witness are known, but the message is only kept inside a [`DiagnosticsScope`] on the calling thread.
+pub(crate) fn record_failed_check(message: String) {
+    FAILED_CHECKS.with(|checks| {
+        if let Some(checks) = checks.borrow_mut().as_mut() {
+            // The synthesis may run more than once for the same witness.
+            if checks.len() < MAX_FAILED_CHECKS && !checks.contains(&message) {
+                checks.push(message);
+            }
+        }
+    });
+}
+
+/// A scope on the calling thread in which the chips of this crate record the checks failing for the assigned witness.
+///
+/// Entering the scope discards the checks of any previous scope and dropping it stops the recording, so a diagnosis never explains a circuit synthesized before the scope or on another thread.
+/// Enter it right before [`MockProver::run`], e.g.,
+/// ```ignore
+/// let diagnostics = DiagnosticsScope::enter();
+/// let prover = MockProver::run(k, &circuit, instances)?;
+/// println!("{}", diagnostics.diagnose_failure(&prover));
+/// ```
+#[derive(Debug)]
+pub struct DiagnosticsScope {
+    // The records are thread local, so the scope must stay on the thread that entered it.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl DiagnosticsScope {
+    /// Starts recording the failed checks on the calling thread, discarding the checks recorded before.
+    pub fn enter() -> Self {
+        FAILED_CHECKS.with(|checks| *checks.borrow_mut() = Some(vec![]));
+        Self {
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Explains why `prover` fails in plain English, e.g., `"padding mismatch at byte 10 of the recovered block: expected 0xff, found 0xfe"`, and ends the scope.
+    ///
+    /// The chips of this crate fold their checks into a single bit, e.g., `is_valid` of [`crate::RSAInstructions::verify_pkcs1v15_signature`], so the failures of [`MockProver::verify`] only point at the cell of the bit.
+    /// This function therefore prefixes them with the checks recorded by the chips while `prover` was created by [`MockProver::run`] in this scope:
+    /// * `"padding mismatch at byte N ..."` if the pkcs1v15 padding or the DigestInfo prefix of `s^e mod n` differs at the byte `N` from the most significant one;
+    /// * `"s^e mod n != expected ..."` if the digest in `s^e mod n` differs from the hashed message;
+    /// * `"modulus LSB not 1 ..."` if the modulus of the public key is even.
+    ///
+    /// # Return values
+    /// Returns the recorded checks followed by a line for each failure of [`MockProver::verify`], or `"all constraints are satisfied"` if `prover` verifies.
+    pub fn diagnose_failure<F: PrimeField>(self, prover: &MockProver<F>) -> String {
+        let checks = FAILED_CHECKS.with(|checks| checks.borrow_mut().take().unwrap_or_default());
+        let failures = match prover.verify() {
+            Ok(()) => return "all constraints are satisfied".to_string(),
+            Err(failures) => failures,
+        };
+        let mut lines = checks;
+        lines.push(format!("{} failed constraints:", failures.len()));
+        lines.extend(
+            failures
+                .iter()
+                .map(|failure| format!("- {}", describe_failure(failure))),
+        );
+        lines.join("\n")
+    }
+}
+
+impl Drop for DiagnosticsScope {
+    fn drop(&mut self) {
+        FAILED_CHECKS.with(|checks| *checks.borrow_mut() = None);
+    }
+}
+
+fn describe_failure(failure: &VerifyFailure) -> String {
+    match failure {
+        VerifyFailure::Lookup { location, .. } => {
+            format!(
+                "a range-checked value does not fit in its bits {}",
+                location
+            )
+        }
+        VerifyFailure::Permutation { location, .. } => format!(
+            "a cell differs from the cell or the constant it must equal, e.g., an asserted bit {}",
+            location
+        ),
+        VerifyFailure::ConstraintNotSatisfied {
+            constraint,
+            location,
+            ..
+        } => format!("{} is not satisfied {}", constraint, location),
+        failure => failure.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        BigUintConfig, RSAConfig, RSAInstructions, RSAPubE, RSAPublicKey, RSASignature, SHA256_OID,
+    };
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use halo2_base::{
+        gates::{
+            range::{RangeConfig, RangeStrategy::Vertical},
+            GateInstructions,
+        },
+        AssignedValue, SKIP_FIRST_PASS,
+    };
+    use num_bigint::BigUint;
+    use rand::thread_rng;
+    use rsa::{
+        traits::{PrivateKeyParts, PublicKeyParts},
+        RsaPrivateKey,
+    };
+    use sha2::{Digest, Sha256};
+    use std::marker::PhantomData;
+
+    struct TestDiagnoseCircuit<F: PrimeField> {
+        n: BigUint,
+        signature: BigUint,
+        digest: Vec<u8>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestDiagnoseCircuit<F> {
+        const BITS_LEN: usize = 1024;
+        const K: usize = 13;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestDiagnoseCircuit<F> {
+        type Config = RSAConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config =
+                RangeConfig::configure(meta, Vertical, &[50], &[4], 1, 12, 0, Self::K);
+            let biguint_config = BigUintConfig::construct(range_config, 64);
+            RSAConfig::construct(biguint_config, Self::BITS_LEN, 5)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "rsa signature to diagnose",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    let e_fix = RSAPubE::Fix(BigUint::from(65537u32));
+                    let public_key = config.assign_public_key(
+                        ctx,
+                        RSAPublicKey::new(Value::known(self.n.clone()), e_fix),
+                    )?;
+                    let signature = config.assign_signature(
+                        ctx,
+                        RSASignature::new(Value::known(self.signature.clone())),
+                    )?;
+                    let digest: [AssignedValue<F>; 32] = std::array::from_fn(|i| {
+                        config
+                            .gate()
+                            .load_witness(ctx, Value::known(F::from(self.digest[i] as u64)))
+                    });
+                    let is_valid =
+                        config.verify_pkcs1v15_digest(ctx, &public_key, &signature, &digest)?;
+                    config.gate().assert_is_const(ctx, &is_valid, F::one());
+                    config.range().finalize(ctx);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    /// Signs the pkcs1v15 block of `digest` with the byte `tampered` from the most significant one flipped, if any.
+    fn sign_block(private_key: &RsaPrivateKey, digest: &[u8], tampered: Option<usize>) -> BigUint {
+        let em_len = TestDiagnoseCircuit::<Fr>::BITS_LEN / 8;
+        let mut prefix = vec![0x30, 0x31, 0x30, 0x0d, 0x06, 0x09];
+        prefix.extend_from_slice(&SHA256_OID);
+        prefix.extend_from_slice(&[0x05, 0x00, 0x04, 0x20]);
+        let mut em = vec![0x00, 0x01];
+        em.resize(em_len - prefix.len() - digest.len() - 1, 0xff);
+        em.push(0x00);
+        em.extend(prefix);
+        em.extend_from_slice(digest);
+        if let Some(tampered) = tampered {
+            em[tampered] ^= 0x01;
+        }
+        let n = BigUint::from_bytes_be(&private_key.n().to_bytes_be());
+        let d = BigUint::from_bytes_be(&private_key.d().to_bytes_be());
+        BigUint::from_bytes_be(&em).modpow(&d, &n)
+    }
+
+    #[test]
+    fn test_diagnose_tampered_padding() {
+        let private_key =
+            RsaPrivateKey::new(&mut thread_rng(), 1024).expect("failed to generate a key");
+        let n = BigUint::from_bytes_be(&private_key.n().to_bytes_be());
+        let digest = Sha256::digest(b"hello world").to_vec();
+        let k = TestDiagnoseCircuit::<Fr>::K as u32;
+        let run = |signature: BigUint, digest: &[u8]| {
+            let circuit = TestDiagnoseCircuit::<Fr> {
+                n: n.clone(),
+                signature,
+                digest: digest.to_vec(),
+                _f: PhantomData,
+            };
+            let diagnostics = DiagnosticsScope::enter();
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            diagnostics.diagnose_failure(&prover)
+        };
+
+        let signature = sign_block(&private_key, &digest, None);
+        assert_eq!(
+            run(signature.clone(), &digest),
+            "all constraints are satisfied"
+        );
+
+        // The byte 10 is in the 0xff padding.
+        let diagnostic = run(sign_block(&private_key, &digest, Some(10)), &digest);
+        assert!(
+            diagnostic.starts_with(
+                "padding mismatch at byte 10 of the recovered block: expected 0xff, found 0xfe\n"
+            ),
+            "{}",
+            diagnostic
+        );
+        assert!(!diagnostic.contains("s^e mod n != expected"));
+        assert!(diagnostic.contains("failed constraints:"));
+
+        // The padding is valid, but the digest is of another message.
+        let other_digest = Sha256::digest(b"hello world!").to_vec();
+        let diagnostic = run(signature.clone(), &other_digest);
+        assert!(
+            diagnostic.starts_with("s^e mod n != expected"),
+            "{}",
+            diagnostic
+        );
+        assert!(!diagnostic.contains("padding mismatch"));
+
+        // The checks recorded outside of a scope do not leak into the diagnosis of a later one.
+        let circuit = TestDiagnoseCircuit::<Fr> {
+            n: n.clone(),
+            signature: sign_block(&private_key, &digest, Some(10)),
+            digest: digest.clone(),
+            _f: PhantomData,
+        };
+        MockProver::run(k, &circuit, vec![]).unwrap();
+        let diagnostic = run(signature, &other_digest);
+        assert!(!diagnostic.contains("padding mismatch"), "{}", diagnostic);
+    }
+}
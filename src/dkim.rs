@@ -0,0 +1,418 @@
+use crate::cost_report::{begin_cost_region, end_cost_region};
+use crate::{
+    canonicalize_header_relaxed, AssignedRSAPublicKey, AssignedRSASignature, DkimCanonChip,
+    RSAConfig, RSASignatureVerifier,
+};
+use halo2_base::halo2_proofs::{circuit::Layouter, plonk::Error};
+use halo2_base::{gates::GateInstructions, utils::PrimeField, AssignedValue, Context, QuantumCell};
+use halo2_dynamic_sha256::Sha256DynamicConfig;
+
+/// The assigned values returned by [`DkimVerifier::verify_pkcs1v15_dkim`].
+#[derive(Clone, Debug)]
+pub struct AssignedDkimResult<'v, F: PrimeField> {
+    /// the bit indicating whether the signature is valid for the SHA256 hash of the header.
+    pub is_valid: AssignedValue<'v, F>,
+    /// the assigned bytes of the header hashed by SHA256, e.g., for matching the `bh=` tag of the `DKIM-Signature` header.
+    pub header_bytes: Vec<AssignedValue<'v, F>>,
+    /// the assigned bytes of the SHA256 hash of the header.
+    pub header_hash: Vec<AssignedValue<'v, F>>,
+    /// the assigned bytes of the SHA256 hash of the body.
+    pub body_hash: Vec<AssignedValue<'v, F>>,
+}
+
+/// A circuit implementation to verify the pkcs1v15 signature of a DKIM header and to hash the body of the email.
+///
+/// The header and body hashes are independent, so [`DkimVerifier::new`] takes one [`Sha256DynamicConfig`] for each of them.
+/// The two configs are configured with the same [`halo2_base::gates::range::RangeConfig`], so they assign their SHA256 rounds to their own advice columns but share the range lookup table, which [`DkimVerifier::load`] loads once.
+/// If the columns of a second SHA256 chip do not fit the budget of the circuit, [`DkimVerifier::new_sequential`] hashes both with a single chip instead.
+#[derive(Clone, Debug)]
+pub struct DkimVerifier<F: PrimeField> {
+    header_verifier: RSASignatureVerifier<F>,
+    body_sha256_config: Option<Sha256DynamicConfig<F>>,
+}
+
+impl<F: PrimeField> DkimVerifier<F> {
+    /// Creates new [`DkimVerifier`] hashing the header and the body with separate SHA256 chips.
+    ///
+    /// # Arguments
+    /// * rsa_config - a [`RSAConfig`].
+    /// * header_sha256_config - a [`Sha256DynamicConfig`] whose maximum byte size is that of the header.
+    /// * body_sha256_config - a [`Sha256DynamicConfig`] whose maximum byte size is that of the body, configured with the same range config as `header_sha256_config`.
+    ///
+    /// # Return values
+    /// Returns new [`DkimVerifier`].
+    pub fn new(
+        rsa_config: RSAConfig<F>,
+        header_sha256_config: Sha256DynamicConfig<F>,
+        body_sha256_config: Sha256DynamicConfig<F>,
+    ) -> Self {
+        Self {
+            header_verifier: RSASignatureVerifier::new(rsa_config, header_sha256_config),
+            body_sha256_config: Some(body_sha256_config),
+        }
+    }
+
+    /// Creates new [`DkimVerifier`] hashing the body and then the header with a single SHA256 chip.
+    ///
+    /// The maximum byte sizes of `sha256_config` must be those of the body and the header in this order.
+    pub fn new_sequential(rsa_config: RSAConfig<F>, sha256_config: Sha256DynamicConfig<F>) -> Self {
+        Self {
+            header_verifier: RSASignatureVerifier::new(rsa_config, sha256_config),
+            body_sha256_config: None,
+        }
+    }
+
+    /// Getter for [`RSAConfig`].
+    pub fn rsa_config(&self) -> &RSAConfig<F> {
+        &self.header_verifier.rsa_config
+    }
+
+    /// Loads the lookup tables of the SHA256 chips and the range lookup table shared by them.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.header_verifier.sha256_config.load(layouter)?;
+        if let Some(body_sha256_config) = self.body_sha256_config.as_ref() {
+            body_sha256_config.load(layouter)?;
+        }
+        self.header_verifier
+            .rsa_config
+            .range()
+            .load_lookup_table(layouter)
+    }
+
+    /// Given a RSA public key, the canonicalized header and body of an email, and a pkcs1v15 signature, verifies the signature for the SHA256 hash of the header and hashes the body.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * public_key - an assigned public key used for the verification.
+    /// * header - the signed header bytes, including the `DKIM-Signature` header without its `b=` value.
+    /// * body - the body bytes, whose hash is signed through the `bh=` tag of the header.
+    /// * signature - a pkcs1v15 signature to be verified.
+    ///
+    /// # Return values
+    /// Returns [`AssignedDkimResult`].
+    /// If `signature` is valid for `public_key` and `header`, its `is_valid` is equivalent to one.
+    /// Otherwise, it is equivalent to zero.
+    /// The signature only covers the body through the `bh=` tag, so the caller must constrain the returned `body_hash` to the tag in `header_bytes` or expose both as public inputs.
+    pub fn verify_pkcs1v15_dkim<'a, 'b: 'a>(
+        &'a mut self,
+        ctx: &mut Context<'b, F>,
+        public_key: &AssignedRSAPublicKey<'b, F>,
+        header: &'a [u8],
+        body: &'a [u8],
+        signature: &AssignedRSASignature<'b, F>,
+    ) -> Result<AssignedDkimResult<'b, F>, Error> {
+        begin_cost_region(ctx, "sha256 rounds");
+        let body_result = match self.body_sha256_config.as_mut() {
+            Some(body_sha256_config) => body_sha256_config.digest(ctx, body, None)?,
+            None => self.header_verifier.sha256_config.digest(ctx, body, None)?,
+        };
+        let header_result = self
+            .header_verifier
+            .sha256_config
+            .digest(ctx, header, None)?;
+        end_cost_region(ctx);
+        let header_hash = header_result.output_bytes;
+        let is_valid = self.header_verifier.verify_hashed_bytes(
+            ctx,
+            public_key,
+            header_hash.clone(),
+            signature,
+        )?;
+        Ok(AssignedDkimResult {
+            is_valid,
+            header_bytes: header_result.input_bytes[0..header.len()].to_vec(),
+            header_hash,
+            body_hash: body_result.output_bytes,
+        })
+    }
+
+    /// Given a RSA public key, the raw header and the canonicalized body of an email, and a pkcs1v15 signature, canonicalizes the header with the relaxed header canonicalization, verifies the signature for the SHA256 hash of the canonicalized header, and hashes the body.
+    ///
+    /// Unlike [`DkimVerifier::verify_pkcs1v15_dkim`], the hashed header bytes are constrained to the output of [`DkimCanonChip::canonicalize_relaxed`] for the witnessed raw header, so the prover cannot hash a header canonicalized differently from `raw_header`.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * canon_chip - a [`DkimCanonChip`] whose maximum header size is at least the length of `raw_header`.
+    /// * public_key - an assigned public key used for the verification.
+    /// * raw_header - the signed header fields as they appear in the email, including the `DKIM-Signature` header without its `b=` value.
+    /// * body - the body bytes, whose hash is signed through the `bh=` tag of the header.
+    /// * signature - a pkcs1v15 signature to be verified.
+    ///
+    /// # Return values
+    /// Returns [`AssignedDkimResult`] as [`DkimVerifier::verify_pkcs1v15_dkim`] does, whose `header_bytes` are the canonicalized header.
+    /// Like `header_bytes`, the constraints binding the canonicalized header depend on its length.
+    pub fn verify_pkcs1v15_dkim_relaxed<'a, 'b: 'a>(
+        &'a mut self,
+        ctx: &mut Context<'b, F>,
+        canon_chip: &DkimCanonChip<F>,
+        public_key: &AssignedRSAPublicKey<'b, F>,
+        raw_header: &[u8],
+        body: &'a [u8],
+        signature: &AssignedRSASignature<'b, F>,
+    ) -> Result<AssignedDkimResult<'b, F>, Error> {
+        let header = canonicalize_header_relaxed(raw_header);
+        let result = self.verify_pkcs1v15_dkim(ctx, public_key, &header, body, signature)?;
+        let raw_header = canon_chip.assign_header(ctx, raw_header);
+        let canonicalized = canon_chip.canonicalize_relaxed(ctx, &raw_header)?;
+        let gate = self.header_verifier.rsa_config.gate();
+        for (i, byte) in canonicalized.bytes.iter().enumerate() {
+            match result.header_bytes.get(i) {
+                Some(hashed) => gate.assert_equal(
+                    ctx,
+                    QuantumCell::Existing(hashed),
+                    QuantumCell::Existing(byte),
+                ),
+                None => gate.assert_is_const(ctx, byte, F::zero()),
+            }
+        }
+        gate.assert_is_const(ctx, &canonicalized.len, F::from(header.len() as u64));
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{BigUintConfig, RSAInstructions, RSAPubE, RSAPublicKey, RSASignature};
+    use halo2_base::halo2_proofs::{
+        circuit::{Cell, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, Column, ConstraintSystem, Instance},
+    };
+    use halo2_base::{
+        gates::{
+            range::{RangeConfig, RangeStrategy::Vertical},
+            GateInstructions,
+        },
+        SKIP_FIRST_PASS,
+    };
+    use num_bigint::BigUint;
+    use rand::thread_rng;
+    use rsa::{
+        pkcs1v15::SigningKey, signature::SignatureEncoding, signature::Signer,
+        traits::PublicKeyParts, RsaPrivateKey,
+    };
+    use sha2::{Digest, Sha256};
+    use std::marker::PhantomData;
+
+    const HEADER: &[u8] = b"from:alice@example.com\r\nto:bob@example.com\r\nsubject:hello\r\ndkim-signature:v=1; a=rsa-sha256; d=example.com; s=selector; bh=VyqV/unA8yADB4nkiDcHr/4SSC+7HqBLPqgmfIeokPs=; b=";
+    const BODY: &[u8] = b"hello world\r\n";
+    /// The header fields of [`HEADER`] as they appear in the email, which are canonicalized to [`HEADER`].
+    const RAW_HEADER: &[u8] = b"From: alice@example.com\r\nTo:  bob@example.com \r\nSubject: hello\r\nDKIM-Signature: v=1; a=rsa-sha256; d=example.com;\r\n\ts=selector; bh=VyqV/unA8yADB4nkiDcHr/4SSC+7HqBLPqgmfIeokPs=; b=";
+
+    #[derive(Debug, Clone)]
+    struct TestDkimConfig<F: PrimeField> {
+        verifier: DkimVerifier<F>,
+        canon_chip: DkimCanonChip<F>,
+        hash_instance: Column<Instance>,
+    }
+
+    struct TestDkimCircuit<F: PrimeField, const PARALLEL: bool> {
+        private_key: RsaPrivateKey,
+        header: Vec<u8>,
+        /// whether `header` is the raw header canonicalized in the circuit.
+        is_relaxed: bool,
+        body: Vec<u8>,
+        signature: Vec<u8>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField, const PARALLEL: bool> TestDkimCircuit<F, PARALLEL> {
+        const BITS_LEN: usize = 1024;
+        const MAX_HEADER_LEN: usize = 256;
+        const MAX_BODY_LEN: usize = 128;
+        const K: usize = 15;
+    }
+
+    impl<F: PrimeField, const PARALLEL: bool> Circuit<F> for TestDkimCircuit<F, PARALLEL> {
+        type Config = TestDkimConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config =
+                RangeConfig::configure(meta, Vertical, &[80], &[16], 1, 12, 0, Self::K);
+            let biguint_config = BigUintConfig::construct(range_config.clone(), 64);
+            let rsa_config = RSAConfig::construct(biguint_config, Self::BITS_LEN, 5);
+            let canon_chip = DkimCanonChip::new(range_config.clone(), Self::MAX_HEADER_LEN);
+            let verifier = if PARALLEL {
+                let header_sha256_config = Sha256DynamicConfig::configure(
+                    meta,
+                    vec![Self::MAX_HEADER_LEN],
+                    range_config.clone(),
+                    8,
+                    8,
+                    true,
+                );
+                let body_sha256_config = Sha256DynamicConfig::configure(
+                    meta,
+                    vec![Self::MAX_BODY_LEN],
+                    range_config,
+                    8,
+                    8,
+                    true,
+                );
+                DkimVerifier::new(rsa_config, header_sha256_config, body_sha256_config)
+            } else {
+                let sha256_config = Sha256DynamicConfig::configure(
+                    meta,
+                    vec![Self::MAX_BODY_LEN, Self::MAX_HEADER_LEN],
+                    range_config,
+                    8,
+                    8,
+                    true,
+                );
+                DkimVerifier::new_sequential(rsa_config, sha256_config)
+            };
+            let hash_instance = meta.instance_column();
+            meta.enable_equality(hash_instance);
+            Self::Config {
+                verifier,
+                canon_chip,
+                hash_instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.verifier.load(&mut layouter)?;
+            let rsa_config = config.verifier.rsa_config().clone();
+            let mut first_pass = SKIP_FIRST_PASS;
+            let hash_cells = layouter.assign_region(
+                || "dkim signature",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(vec![]);
+                    }
+
+                    let mut aux = rsa_config.new_context(region);
+                    let ctx = &mut aux;
+                    let signature = rsa_config.assign_signature(
+                        ctx,
+                        RSASignature::new(Value::known(BigUint::from_bytes_be(&self.signature))),
+                    )?;
+                    let n_big = BigUint::from_bytes_be(&self.private_key.n().to_bytes_be());
+                    let e_fix = RSAPubE::Fix(BigUint::from(65537u32));
+                    let public_key = rsa_config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                    let mut verifier = config.verifier.clone();
+                    let result = if self.is_relaxed {
+                        verifier.verify_pkcs1v15_dkim_relaxed(
+                            ctx,
+                            &config.canon_chip,
+                            &public_key,
+                            &self.header,
+                            &self.body,
+                            &signature,
+                        )?
+                    } else {
+                        verifier.verify_pkcs1v15_dkim(
+                            ctx,
+                            &public_key,
+                            &self.header,
+                            &self.body,
+                            &signature,
+                        )?
+                    };
+                    // A canonicalized header is canonicalized to itself.
+                    assert_eq!(
+                        result.header_bytes.len(),
+                        canonicalize_header_relaxed(&self.header).len()
+                    );
+                    rsa_config
+                        .gate()
+                        .assert_is_const(ctx, &result.is_valid, F::one());
+                    rsa_config.range().finalize(ctx);
+                    Ok(result
+                        .header_hash
+                        .iter()
+                        .chain(result.body_hash.iter())
+                        .map(|v| v.cell())
+                        .collect::<Vec<Cell>>())
+                },
+            )?;
+            for (i, cell) in hash_cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell, config.hash_instance, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn run_dkim<const PARALLEL: bool>(body: &[u8], tamper_signature: bool) -> bool {
+        run_dkim_header::<PARALLEL>(HEADER, false, body, tamper_signature)
+    }
+
+    fn run_dkim_header<const PARALLEL: bool>(
+        header: &[u8],
+        is_relaxed: bool,
+        body: &[u8],
+        tamper_signature: bool,
+    ) -> bool {
+        let private_key =
+            RsaPrivateKey::new(&mut thread_rng(), 1024).expect("failed to generate a key");
+        let signing_key = SigningKey::<rsa::sha2::Sha256>::new(private_key.clone());
+        let mut signature = signing_key.sign(HEADER).to_vec();
+        if tamper_signature {
+            signature[64] ^= 0x01;
+        }
+        let circuit = TestDkimCircuit::<Fr, PARALLEL> {
+            private_key,
+            header: header.to_vec(),
+            is_relaxed,
+            body: body.to_vec(),
+            signature,
+            _f: PhantomData,
+        };
+        let hashes = [Sha256::digest(HEADER), Sha256::digest(BODY)]
+            .concat()
+            .iter()
+            .map(|byte| Fr::from(*byte as u64))
+            .collect::<Vec<Fr>>();
+        let prover = match MockProver::run(
+            TestDkimCircuit::<Fr, PARALLEL>::K as u32,
+            &circuit,
+            vec![hashes],
+        ) {
+            Ok(prover) => prover,
+            Err(e) => panic!("{:#?}", e),
+        };
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_dkim_two_sha256_chips() {
+        assert!(run_dkim::<true>(BODY, false));
+        assert!(!run_dkim::<true>(BODY, true));
+        assert!(!run_dkim::<true>(b"goodbye world\r\n", false));
+    }
+
+    #[test]
+    fn test_dkim_sequential_sha256_chip() {
+        assert!(run_dkim::<false>(BODY, false));
+        assert!(!run_dkim::<false>(b"goodbye world\r\n", false));
+    }
+
+    #[test]
+    fn test_dkim_relaxed_header() {
+        assert!(run_dkim_header::<true>(RAW_HEADER, true, BODY, false));
+        assert!(!run_dkim_header::<true>(RAW_HEADER, true, BODY, true));
+        // The case of the values is kept, so the header is not the signed one.
+        let other_header = String::from_utf8(RAW_HEADER.to_vec())
+            .unwrap()
+            .replace("Subject: hello", "Subject: Hello");
+        assert!(!run_dkim_header::<true>(
+            other_header.as_bytes(),
+            true,
+            BODY,
+            false
+        ));
+    }
+}
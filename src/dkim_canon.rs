@@ -0,0 +1,692 @@
+//! Relaxed header canonicalization of DKIM (RFC 6376, section 3.4.2) in the circuit, so that the hashed header is bound to the raw header bytes instead of being canonicalized by the prover.
+use crate::cost_report::{begin_cost_region, end_cost_region};
+use halo2_base::halo2_proofs::{circuit::Value, plonk::Error};
+use halo2_base::QuantumCell;
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, range::RangeConfig, GateInstructions, RangeInstructions},
+    utils::PrimeField,
+    AssignedValue, Context,
+};
+
+/// Canonicalizes `header` with the relaxed header canonicalization of DKIM as OpenDKIM does.
+///
+/// `header` is the concatenation of the signed header fields in the order of the `h=` tag, each ending with CRLF except possibly the last one, e.g., the `DKIM-Signature` header without its `b=` value.
+/// A field ends at a CRLF not followed by a space or a tab.
+/// The name of each field, i.e., the bytes before its first colon, is lowercased and stripped of whitespace, the whitespace after the colon and at the end of the value is deleted, and the other runs of whitespace, including the folding CRLFs, are replaced by a single space.
+/// As in OpenDKIM, the whitespace is that of C's `isspace`, i.e., a space, `\t`, `\n`, `\v`, `\f`, or `\r`.
+pub fn canonicalize_header_relaxed(header: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(header.len());
+    let mut start = 0;
+    while start < header.len() {
+        let mut end = start;
+        while end < header.len() && !is_field_end(header, end) {
+            end += 1;
+        }
+        canonicalize_field(&mut out, &header[start..end]);
+        if end < header.len() {
+            out.extend_from_slice(b"\r\n");
+            end += 2;
+        }
+        start = end;
+    }
+    out
+}
+
+fn is_dkim_space(c: u8) -> bool {
+    c == b' ' || (b'\t'..=b'\r').contains(&c)
+}
+
+/// Returns whether the CRLF at `i` ends a field.
+fn is_field_end(header: &[u8], i: usize) -> bool {
+    header[i] == b'\r'
+        && header.get(i + 1) == Some(&b'\n')
+        && !matches!(header.get(i + 2), Some(b' ' | b'\t'))
+}
+
+fn canonicalize_field(out: &mut Vec<u8>, field: &[u8]) {
+    let colon = field.iter().position(|c| *c == b':').unwrap_or(field.len());
+    out.extend(
+        field[..colon]
+            .iter()
+            .filter(|c| !is_dkim_space(**c))
+            .map(|c| c.to_ascii_lowercase()),
+    );
+    if colon == field.len() {
+        return;
+    }
+    out.push(b':');
+    let mut is_started = false;
+    let mut is_space = false;
+    for &c in &field[colon + 1..] {
+        if is_dkim_space(c) {
+            // The whitespace before the first word is deleted.
+            is_space = is_started;
+        } else {
+            if is_space {
+                out.push(b' ');
+                is_space = false;
+            }
+            out.push(c);
+            is_started = true;
+        }
+    }
+}
+
+/// Returns the bits indicating whether each index less than `max_len` is less than `len`, and constrains `len` to be at most `max_len`.
+pub(crate) fn assign_in_range<'v, F: PrimeField>(
+    gate: &FlexGateConfig<F>,
+    ctx: &mut Context<'v, F>,
+    len: &AssignedValue<'v, F>,
+    max_len: usize,
+) -> Vec<AssignedValue<'v, F>> {
+    let is_len = (0..=max_len)
+        .map(|i| {
+            gate.is_equal(
+                ctx,
+                QuantumCell::Existing(len),
+                QuantumCell::Constant(F::from(i as u64)),
+            )
+        })
+        .collect::<Vec<AssignedValue<F>>>();
+    let is_valid_len = gate.sum(ctx, is_len.iter().map(QuantumCell::Existing));
+    gate.assert_is_const(ctx, &is_valid_len, F::one());
+    let mut in_range = vec![gate.not(ctx, QuantumCell::Existing(&is_len[0]))];
+    for is_len in is_len[1..max_len].iter() {
+        let prev = in_range.last().unwrap();
+        let next = gate.sub(
+            ctx,
+            QuantumCell::Existing(prev),
+            QuantumCell::Existing(is_len),
+        );
+        in_range.push(next);
+    }
+    in_range
+}
+
+/// The assigned bytes of a header of at most [`DkimCanonChip::max_header_len`] bytes.
+#[derive(Clone, Debug)]
+pub struct AssignedHeaderBytes<'v, F: PrimeField> {
+    /// the bytes of the header followed by zeros up to the maximum header size.
+    pub bytes: Vec<AssignedValue<'v, F>>,
+    /// the byte length of the header.
+    pub len: AssignedValue<'v, F>,
+}
+
+/// The classes of a raw header byte used by [`DkimCanonChip::canonicalize_relaxed`], which are zero beyond the length of the header except `colon` and `upper`.
+struct ByteClass<'v, F: PrimeField> {
+    cr: AssignedValue<'v, F>,
+    lf: AssignedValue<'v, F>,
+    /// a space or a tab, which continues a folded field after a CRLF.
+    lwsp: AssignedValue<'v, F>,
+    /// the whitespace of C's `isspace`.
+    space: AssignedValue<'v, F>,
+    colon: AssignedValue<'v, F>,
+    upper: AssignedValue<'v, F>,
+}
+
+/// A byte moving to its position in the canonicalized header.
+struct Slot<'v, F: PrimeField> {
+    is_kept: AssignedValue<'v, F>,
+    byte: AssignedValue<'v, F>,
+    /// the little-endian bits of the number of the deleted bytes before it, from the bit of the current stage.
+    shift_bits: Vec<AssignedValue<'v, F>>,
+}
+
+/// Chip to canonicalize DKIM headers with the relaxed header canonicalization in the circuit.
+///
+/// [`DkimCanonChip::canonicalize_relaxed`] constrains the canonicalized bytes to those of [`canonicalize_header_relaxed`] for the witnessed raw header, so the prover cannot hash a header canonicalized differently from the raw bytes.
+/// Each raw byte is kept or deleted depending on its neighbors and the whitespace up to the end of its field, and the kept bytes are moved to the left by the number of the deleted bytes before them in `log2(max_header_len)` stages.
+/// Its cost is `O(n log^2 n)` cells for the maximum header size `n`, i.e., several hundred cells per byte of a 1 KB header.
+#[derive(Clone, Debug)]
+pub struct DkimCanonChip<F: PrimeField> {
+    range: RangeConfig<F>,
+    max_header_len: usize,
+}
+
+impl<F: PrimeField> DkimCanonChip<F> {
+    /// Creates a new [`DkimCanonChip`] from the range config and the maximum byte size of the raw header.
+    ///
+    /// The lookup bits of `range` must be at least 8 to range-check the header bytes in one lookup.
+    pub fn new(range: RangeConfig<F>, max_header_len: usize) -> Self {
+        assert!(
+            max_header_len > 0,
+            "the maximum header size must be positive"
+        );
+        Self {
+            range,
+            max_header_len,
+        }
+    }
+
+    /// Getter for [`RangeConfig`].
+    pub fn range(&self) -> &RangeConfig<F> {
+        &self.range
+    }
+
+    /// Getter for the maximum byte size of the raw header.
+    pub fn max_header_len(&self) -> usize {
+        self.max_header_len
+    }
+
+    /// Assigns the raw header bytes and their length as witnesses, padding the bytes with zeros up to [`DkimCanonChip::max_header_len`].
+    ///
+    /// # Panics
+    /// Panics if `header` is longer than [`DkimCanonChip::max_header_len`].
+    pub fn assign_header<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        header: &[u8],
+    ) -> AssignedHeaderBytes<'v, F> {
+        assert!(
+            header.len() <= self.max_header_len,
+            "the header of {} bytes exceeds the maximum size {}",
+            header.len(),
+            self.max_header_len
+        );
+        let gate = self.range.gate();
+        let bytes = (0..self.max_header_len)
+            .map(|i| {
+                let byte = header.get(i).copied().unwrap_or(0);
+                gate.load_witness(ctx, Value::known(F::from(byte as u64)))
+            })
+            .collect();
+        let len = gate.load_witness(ctx, Value::known(F::from(header.len() as u64)));
+        AssignedHeaderBytes { bytes, len }
+    }
+
+    /// Given the assigned raw header, returns the assigned bytes of its relaxed canonicalization by [`canonicalize_header_relaxed`].
+    ///
+    /// The returned bytes are followed by zeros up to [`DkimCanonChip::max_header_len`], since the canonicalization never lengthens the header.
+    /// The constraints are not satisfied if a raw byte, including the padding, is not a byte or the length exceeds [`DkimCanonChip::max_header_len`].
+    /// The values of the padding bytes do not affect the canonicalized bytes.
+    ///
+    /// # Panics
+    /// Panics if the number of the raw bytes is not [`DkimCanonChip::max_header_len`].
+    pub fn canonicalize_relaxed<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        header: &AssignedHeaderBytes<'v, F>,
+    ) -> Result<AssignedHeaderBytes<'v, F>, Error> {
+        assert_eq!(
+            header.bytes.len(),
+            self.max_header_len,
+            "the raw header must be padded to the maximum size"
+        );
+        begin_cost_region(ctx, "dkim relaxed canonicalization");
+        let gate = self.range.gate();
+        let n = self.max_header_len;
+        let in_range = assign_in_range(gate, ctx, &header.len, n);
+        let classes = header
+            .bytes
+            .iter()
+            .zip(in_range.iter())
+            .map(|(byte, in_range)| self.classify(ctx, byte, in_range))
+            .collect::<Vec<ByteClass<F>>>();
+
+        // 1. A CRLF ends a field unless it is followed by a space or a tab, and the other whitespace is folded.
+        let zero = gate.load_zero(ctx);
+        let mut is_eol_cr = vec![];
+        for i in 0..n {
+            let lf_next = classes.get(i + 1).map_or(&zero, |class| &class.lf);
+            let lwsp_next = classes.get(i + 2).map_or(&zero, |class| &class.lwsp);
+            let is_crlf = gate.and(
+                ctx,
+                QuantumCell::Existing(&classes[i].cr),
+                QuantumCell::Existing(lf_next),
+            );
+            let is_not_folded = gate.not(ctx, QuantumCell::Existing(lwsp_next));
+            is_eol_cr.push(gate.and(
+                ctx,
+                QuantumCell::Existing(&is_crlf),
+                QuantumCell::Existing(&is_not_folded),
+            ));
+        }
+        let mut is_eol_lf = vec![zero.clone()];
+        is_eol_lf.extend(is_eol_cr[..n - 1].iter().cloned());
+        let mut is_sp = vec![];
+        for i in 0..n {
+            let is_eol = gate.add(
+                ctx,
+                QuantumCell::Existing(&is_eol_cr[i]),
+                QuantumCell::Existing(&is_eol_lf[i]),
+            );
+            // The bytes of the CRLFs ending the fields are also whitespace.
+            is_sp.push(gate.sub(
+                ctx,
+                QuantumCell::Existing(&classes[i].space),
+                QuantumCell::Existing(&is_eol),
+            ));
+        }
+
+        // 2. Whether each byte is in the name of its field or in the whitespace right after the colon.
+        let mut in_name = vec![gate.load_constant(ctx, F::one())];
+        let mut after_colon = vec![zero.clone()];
+        for i in 0..n - 1 {
+            let is_separator = gate.and(
+                ctx,
+                QuantumCell::Existing(&in_name[i]),
+                QuantumCell::Existing(&classes[i].colon),
+            );
+            let in_same_name = gate.sub(
+                ctx,
+                QuantumCell::Existing(&in_name[i]),
+                QuantumCell::Existing(&is_separator),
+            );
+            in_name.push(gate.select(
+                ctx,
+                QuantumCell::Constant(F::one()),
+                QuantumCell::Existing(&in_same_name),
+                QuantumCell::Existing(&is_eol_lf[i]),
+            ));
+            after_colon.push(gate.mul_add(
+                ctx,
+                QuantumCell::Existing(&is_sp[i]),
+                QuantumCell::Existing(&after_colon[i]),
+                QuantumCell::Existing(&is_separator),
+            ));
+        }
+
+        // 3. Whether only whitespace follows each byte up to the end of its field or the header.
+        let mut before_eol = vec![gate.load_constant(ctx, F::one()); n + 1];
+        for i in (0..n).rev() {
+            let is_end = gate.sub(
+                ctx,
+                QuantumCell::Existing(&is_eol_cr[i]),
+                QuantumCell::Existing(&in_range[i]),
+            );
+            let is_end = gate.add(
+                ctx,
+                QuantumCell::Existing(&is_end),
+                QuantumCell::Constant(F::one()),
+            );
+            before_eol[i] = gate.mul_add(
+                ctx,
+                QuantumCell::Existing(&is_sp[i]),
+                QuantumCell::Existing(&before_eol[i + 1]),
+                QuantumCell::Existing(&is_end),
+            );
+        }
+
+        // 4. A whitespace byte is kept as a space only if it starts a run in the middle of a value, and the other bytes are kept with the names lowercased.
+        let mut slots = vec![];
+        let mut num_kept = zero.clone();
+        let shift_bits_len = (usize::BITS - (n - 1).leading_zeros()).max(1) as usize;
+        for i in 0..n {
+            let is_sp_prev = if i == 0 { &zero } else { &is_sp[i - 1] };
+            let num_deleting = gate.sum(
+                ctx,
+                [&in_name[i], &after_colon[i], &before_eol[i], is_sp_prev]
+                    .into_iter()
+                    .map(QuantumCell::Existing),
+            );
+            let is_run_start = gate.is_zero(ctx, &num_deleting);
+            let is_kept_sp = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_sp[i]),
+                QuantumCell::Existing(&is_run_start),
+            );
+            let is_deleted = gate.sub(
+                ctx,
+                QuantumCell::Existing(&is_sp[i]),
+                QuantumCell::Existing(&is_kept_sp),
+            );
+            let is_kept = gate.sub(
+                ctx,
+                QuantumCell::Existing(&in_range[i]),
+                QuantumCell::Existing(&is_deleted),
+            );
+            let is_lowered = gate.and(
+                ctx,
+                QuantumCell::Existing(&in_name[i]),
+                QuantumCell::Existing(&classes[i].upper),
+            );
+            let lowered = gate.mul_add(
+                ctx,
+                QuantumCell::Existing(&is_lowered),
+                QuantumCell::Constant(F::from(32)),
+                QuantumCell::Existing(&header.bytes[i]),
+            );
+            let byte = gate.select(
+                ctx,
+                QuantumCell::Constant(F::from(b' ' as u64)),
+                QuantumCell::Existing(&lowered),
+                QuantumCell::Existing(&is_sp[i]),
+            );
+            let byte = gate.mul(
+                ctx,
+                QuantumCell::Existing(&is_kept),
+                QuantumCell::Existing(&byte),
+            );
+            let shift = gate.sub(
+                ctx,
+                QuantumCell::Constant(F::from(i as u64)),
+                QuantumCell::Existing(&num_kept),
+            );
+            let shift_bits = gate.num_to_bits(ctx, &shift, shift_bits_len);
+            num_kept = gate.add(
+                ctx,
+                QuantumCell::Existing(&num_kept),
+                QuantumCell::Existing(&is_kept),
+            );
+            slots.push(Slot {
+                is_kept,
+                byte,
+                shift_bits,
+            });
+        }
+
+        // 5. The kept bytes move to the left by the bits of their shifts from the lowest one.
+        // Since the shifts of the kept bytes increase by at most the distances between them, no two kept bytes ever land on the same slot.
+        for stage in 0..shift_bits_len {
+            let offset = 1 << stage;
+            let mut stays = vec![];
+            let mut moves = vec![];
+            for slot in slots.iter() {
+                let not_bit = gate.not(ctx, QuantumCell::Existing(&slot.shift_bits[0]));
+                stays.push(gate.and(
+                    ctx,
+                    QuantumCell::Existing(&slot.is_kept),
+                    QuantumCell::Existing(&not_bit),
+                ));
+                moves.push(gate.and(
+                    ctx,
+                    QuantumCell::Existing(&slot.is_kept),
+                    QuantumCell::Existing(&slot.shift_bits[0]),
+                ));
+            }
+            let mut next_slots = vec![];
+            for s in 0..n {
+                let stay = &stays[s];
+                let slot = &slots[s];
+                let next_slot = match slots.get(s + offset) {
+                    Some(incoming) => {
+                        let mv = &moves[s + offset];
+                        Slot {
+                            is_kept: gate.add(
+                                ctx,
+                                QuantumCell::Existing(stay),
+                                QuantumCell::Existing(mv),
+                            ),
+                            byte: self.merge(ctx, stay, &slot.byte, mv, &incoming.byte),
+                            shift_bits: slot.shift_bits[1..]
+                                .iter()
+                                .zip(incoming.shift_bits[1..].iter())
+                                .map(|(own, other)| self.merge(ctx, stay, own, mv, other))
+                                .collect(),
+                        }
+                    }
+                    None => Slot {
+                        is_kept: stay.clone(),
+                        byte: gate.mul(
+                            ctx,
+                            QuantumCell::Existing(stay),
+                            QuantumCell::Existing(&slot.byte),
+                        ),
+                        shift_bits: slot.shift_bits[1..]
+                            .iter()
+                            .map(|own| {
+                                gate.mul(
+                                    ctx,
+                                    QuantumCell::Existing(stay),
+                                    QuantumCell::Existing(own),
+                                )
+                            })
+                            .collect(),
+                    },
+                };
+                next_slots.push(next_slot);
+            }
+            slots = next_slots;
+        }
+        end_cost_region(ctx);
+        Ok(AssignedHeaderBytes {
+            bytes: slots.into_iter().map(|slot| slot.byte).collect(),
+            len: num_kept,
+        })
+    }
+
+    /// Range-checks `byte` and returns its [`ByteClass`].
+    fn classify<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        byte: &AssignedValue<'v, F>,
+        in_range: &AssignedValue<'v, F>,
+    ) -> ByteClass<'v, F> {
+        let gate = self.range.gate();
+        self.range.range_check(ctx, byte, 8);
+        let is_byte = |ctx: &mut Context<'v, F>, c: u8| {
+            gate.is_equal(
+                ctx,
+                QuantumCell::Existing(byte),
+                QuantumCell::Constant(F::from(c as u64)),
+            )
+        };
+        let is_less = |ctx: &mut Context<'v, F>, bound: u8| {
+            self.range.is_less_than(
+                ctx,
+                QuantumCell::Existing(byte),
+                QuantumCell::Constant(F::from(bound as u64)),
+                8,
+            )
+        };
+        let mask = |ctx: &mut Context<'v, F>, flag: AssignedValue<'v, F>| {
+            gate.and(
+                ctx,
+                QuantumCell::Existing(&flag),
+                QuantumCell::Existing(in_range),
+            )
+        };
+        let is_cr = is_byte(ctx, b'\r');
+        let is_lf = is_byte(ctx, b'\n');
+        let is_tab = is_byte(ctx, b'\t');
+        let is_space = is_byte(ctx, b' ');
+        let is_colon = is_byte(ctx, b':');
+        let is_lwsp = gate.add(
+            ctx,
+            QuantumCell::Existing(&is_tab),
+            QuantumCell::Existing(&is_space),
+        );
+        // `\t` to `\r` are the bytes from 9 to 13.
+        let is_less_9 = is_less(ctx, 9);
+        let is_less_14 = is_less(ctx, 14);
+        let is_control_space = gate.sub(
+            ctx,
+            QuantumCell::Existing(&is_less_14),
+            QuantumCell::Existing(&is_less_9),
+        );
+        let is_any_space = gate.add(
+            ctx,
+            QuantumCell::Existing(&is_control_space),
+            QuantumCell::Existing(&is_space),
+        );
+        let is_less_65 = is_less(ctx, b'A');
+        let is_less_91 = is_less(ctx, b'Z' + 1);
+        let is_upper = gate.sub(
+            ctx,
+            QuantumCell::Existing(&is_less_91),
+            QuantumCell::Existing(&is_less_65),
+        );
+        ByteClass {
+            cr: mask(ctx, is_cr),
+            lf: mask(ctx, is_lf),
+            lwsp: mask(ctx, is_lwsp),
+            space: mask(ctx, is_any_space),
+            colon: is_colon,
+            upper: is_upper,
+        }
+    }
+
+    /// Returns `stay * own + mv * other`, where at most one of `stay` and `mv` is one.
+    fn merge<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        stay: &AssignedValue<'v, F>,
+        own: &AssignedValue<'v, F>,
+        mv: &AssignedValue<'v, F>,
+        other: &AssignedValue<'v, F>,
+    ) -> AssignedValue<'v, F> {
+        let gate = self.range.gate();
+        let kept = gate.mul(ctx, QuantumCell::Existing(stay), QuantumCell::Existing(own));
+        gate.mul_add(
+            ctx,
+            QuantumCell::Existing(mv),
+            QuantumCell::Existing(other),
+            QuantumCell::Existing(&kept),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem},
+    };
+    use halo2_base::{gates::range::RangeStrategy::Vertical, ContextParams, SKIP_FIRST_PASS};
+    use std::marker::PhantomData;
+
+    /// The raw headers and their relaxed canonicalizations by OpenDKIM.
+    /// The first one is the example of RFC 6376, section 3.4.5.
+    const VECTORS: &[(&[u8], &[u8])] = &[
+        (b"A: X\r\nB : Y\t\r\n\tZ  \r\n", b"a:X\r\nb:Y Z\r\n"),
+        (b"Subject:  Hello   World \r\n", b"subject:Hello World\r\n"),
+        (
+            b"To:\r\n Bob <bob@EXAMPLE.com>\r\nX-Empty:   \r\n",
+            b"to:Bob <bob@EXAMPLE.com>\r\nx-empty:\r\n",
+        ),
+        (
+            b"Date: Mon, 1 Jan 2024 10:00:00 +0000\r\n",
+            b"date:Mon, 1 Jan 2024 10:00:00 +0000\r\n",
+        ),
+        (
+            b"DKIM-Signature: v=1; a=rsa-sha256;\r\n\td=example.com; s=sel;\r\n  b=",
+            b"dkim-signature:v=1; a=rsa-sha256; d=example.com; s=sel; b=",
+        ),
+        (b"Subject: hi \t", b"subject:hi"),
+    ];
+
+    struct TestDkimCanonCircuit<F: PrimeField> {
+        raw: Vec<u8>,
+        canonicalized: Vec<u8>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestDkimCanonCircuit<F> {
+        const K: usize = 15;
+        const MAX_HEADER_LEN: usize = 96;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestDkimCanonCircuit<F> {
+        type Config = DkimCanonChip<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config =
+                RangeConfig::configure(meta, Vertical, &[8], &[1], 1, 12, 0, Self::K);
+            DkimCanonChip::new(range_config, Self::MAX_HEADER_LEN)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let range = config.range().clone();
+            range.load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "dkim relaxed canonicalization",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = Context::new(
+                        region,
+                        ContextParams {
+                            max_rows: range.gate.max_rows,
+                            num_context_ids: 1,
+                            fixed_columns: range.gate.constants.clone(),
+                        },
+                    );
+                    let ctx = &mut aux;
+                    let gate = range.gate();
+                    let raw = config.assign_header(ctx, &self.raw);
+                    let canonicalized = config.canonicalize_relaxed(ctx, &raw)?;
+                    for (i, byte) in canonicalized.bytes.iter().enumerate() {
+                        let expected = self.canonicalized.get(i).copied().unwrap_or(0);
+                        gate.assert_is_const(ctx, byte, F::from(expected as u64));
+                    }
+                    gate.assert_is_const(
+                        ctx,
+                        &canonicalized.len,
+                        F::from(self.canonicalized.len() as u64),
+                    );
+                    range.finalize(ctx);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn run_canonicalize(raw: &[u8], canonicalized: &[u8]) -> bool {
+        let circuit = TestDkimCanonCircuit::<Fr> {
+            raw: raw.to_vec(),
+            canonicalized: canonicalized.to_vec(),
+            _f: PhantomData,
+        };
+        let prover = match MockProver::run(TestDkimCanonCircuit::<Fr>::K as u32, &circuit, vec![]) {
+            Ok(prover) => prover,
+            Err(e) => panic!("{:#?}", e),
+        };
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_canonicalize_header_relaxed_native() {
+        for (raw, canonicalized) in VECTORS {
+            assert_eq!(
+                canonicalize_header_relaxed(raw),
+                *canonicalized,
+                "{}",
+                String::from_utf8_lossy(raw)
+            );
+        }
+        // The canonicalization is idempotent.
+        for (_, canonicalized) in VECTORS {
+            assert_eq!(canonicalize_header_relaxed(canonicalized), *canonicalized);
+        }
+    }
+
+    #[test]
+    fn test_dkim_canon_chip() {
+        for (raw, canonicalized) in VECTORS {
+            assert!(
+                run_canonicalize(raw, canonicalized),
+                "{}",
+                String::from_utf8_lossy(raw)
+            );
+        }
+        assert!(run_canonicalize(b"", b""));
+    }
+
+    #[test]
+    fn test_dkim_canon_chip_rejects_other_canonicalizations() {
+        let (raw, _) = VECTORS[0];
+        // The simple canonicalization, the name not lowercased, and the whitespace not folded.
+        assert!(!run_canonicalize(raw, raw));
+        assert!(!run_canonicalize(raw, b"A:X\r\nb:Y Z\r\n"));
+        assert!(!run_canonicalize(raw, b"a:X\r\nb:Y  Z\r\n"));
+        // The trailing CRLF dropped.
+        assert!(!run_canonicalize(raw, b"a:X\r\nb:Y Z"));
+    }
+}
@@ -0,0 +1,554 @@
+//! Extraction of the domain of the sender address from the `From` header of an email in the circuit, e.g., to expose it as a public input after verifying the DKIM signature of the header.
+use crate::cost_report::{begin_cost_region, end_cost_region};
+use crate::dkim_canon::assign_in_range;
+use crate::AssignedHeaderBytes;
+use halo2_base::halo2_proofs::plonk::Error;
+use halo2_base::QuantumCell;
+use halo2_base::{
+    gates::{range::RangeConfig, GateInstructions, RangeInstructions},
+    utils::PrimeField,
+    AssignedValue, Context,
+};
+
+/// Returns the lowercased domain of the sender address in the `From` header `from`, or `None` if it does not have exactly one address with a non-empty domain.
+///
+/// `from` is either the whole header field, e.g., `From: "Doe, John" <john@example.com>\r\n`, or only its value.
+/// The quoted strings, e.g., the display name or a quoted local part, are skipped with their escaped characters, so an `@` or `<` in them is not taken for the address.
+/// If an angle bracket `<` is out of the quoted strings, the address is the one in the angle brackets.
+/// The domain follows the `@` of the address and ends at `>`, whitespace, or the end of `from`.
+/// The comments in parentheses are not recognized, so a header with an `@` in a comment is rejected.
+pub fn email_domain_of(from: &[u8]) -> Option<Vec<u8>> {
+    let mut in_quote = false;
+    let mut is_escaped = false;
+    let mut ats = vec![];
+    let mut has_angle = false;
+    let mut in_angle = false;
+    for (i, &c) in from.iter().enumerate() {
+        if in_quote {
+            if is_escaped {
+                is_escaped = false;
+            } else if c == b'\\' {
+                is_escaped = true;
+            } else if c == b'"' {
+                in_quote = false;
+            }
+            continue;
+        }
+        match c {
+            b'"' => in_quote = true,
+            b'<' => {
+                has_angle = true;
+                in_angle = true;
+            }
+            b'>' => in_angle = false,
+            b'@' => ats.push((i, in_angle)),
+            _ => {}
+        }
+    }
+    let ats = ats
+        .into_iter()
+        .filter(|(_, in_angle)| *in_angle || !has_angle)
+        .collect::<Vec<_>>();
+    if ats.len() != 1 {
+        return None;
+    }
+    let domain = from[ats[0].0 + 1..]
+        .iter()
+        .take_while(|c| !is_domain_end(**c))
+        .map(|c| c.to_ascii_lowercase())
+        .collect::<Vec<u8>>();
+    if domain.is_empty() {
+        return None;
+    }
+    Some(domain)
+}
+
+/// Returns the public instances of a domain extracted by [`EmailDomainChip::extract_email_domain`], i.e., its bytes followed by zeros up to `max_domain_len`.
+///
+/// # Panics
+/// Panics if `domain` is longer than `max_domain_len`.
+pub fn email_domain_instances<F: PrimeField>(domain: &[u8], max_domain_len: usize) -> Vec<F> {
+    assert!(
+        domain.len() <= max_domain_len,
+        "the domain of {} bytes exceeds the maximum length {}",
+        domain.len(),
+        max_domain_len
+    );
+    (0..max_domain_len)
+        .map(|i| F::from(domain.get(i).copied().unwrap_or(0) as u64))
+        .collect()
+}
+
+fn is_domain_end(c: u8) -> bool {
+    matches!(c, b'>' | b' ' | b'\t' | b'\r' | b'\n')
+}
+
+/// The assigned domain returned by [`EmailDomainChip::extract_email_domain`].
+#[derive(Clone, Debug)]
+pub struct AssignedEmailDomain<'v, F: PrimeField> {
+    /// the lowercased bytes of the domain followed by zeros up to the maximum domain length, which can be exposed as public inputs in the order of [`email_domain_instances`].
+    pub bytes: Vec<AssignedValue<'v, F>>,
+    /// the byte length of the domain.
+    pub len: AssignedValue<'v, F>,
+}
+
+/// Chip to extract the domain of the sender address from the `From` header in the circuit, as [`email_domain_of`] does.
+///
+/// With the header bytes verified by [`crate::DkimVerifier`], the exposed domain lets a verifier enforce that the email was sent by someone at the domain.
+#[derive(Clone, Debug)]
+pub struct EmailDomainChip<F: PrimeField> {
+    range: RangeConfig<F>,
+    max_domain_len: usize,
+}
+
+impl<F: PrimeField> EmailDomainChip<F> {
+    /// Creates a new [`EmailDomainChip`] from the range config and the maximum byte length of the domains.
+    ///
+    /// The lookup bits of `range` must be at least 8 to range-check the header bytes in one lookup.
+    pub fn new(range: RangeConfig<F>, max_domain_len: usize) -> Self {
+        assert!(
+            max_domain_len > 0,
+            "the maximum domain length must be positive"
+        );
+        Self {
+            range,
+            max_domain_len,
+        }
+    }
+
+    /// Getter for [`RangeConfig`].
+    pub fn range(&self) -> &RangeConfig<F> {
+        &self.range
+    }
+
+    /// Getter for the maximum byte length of the domains.
+    pub fn max_domain_len(&self) -> usize {
+        self.max_domain_len
+    }
+
+    /// Given the assigned bytes of a `From` header, e.g., those assigned by [`crate::DkimCanonChip::assign_header`], returns the assigned domain of its sender address.
+    ///
+    /// The constraints are not satisfied if [`email_domain_of`] returns `None` for the header or the domain is longer than [`EmailDomainChip::max_domain_len`].
+    /// The bytes of `from` beyond its length are ignored, but they must be bytes as well.
+    pub fn extract_email_domain<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        from: &AssignedHeaderBytes<'v, F>,
+    ) -> Result<AssignedEmailDomain<'v, F>, Error> {
+        begin_cost_region(ctx, "email domain");
+        let gate = self.range.gate();
+        let n = from.bytes.len();
+        let in_range = assign_in_range(gate, ctx, &from.len, n);
+
+        // 1. The states of the quoted strings and the angle brackets before each byte.
+        let mut in_quote = gate.load_zero(ctx);
+        let mut is_escaped = gate.load_zero(ctx);
+        let mut in_angle = gate.load_zero(ctx);
+        let mut ats = vec![];
+        let mut in_angles = vec![];
+        let mut num_angles = gate.load_zero(ctx);
+        let mut is_ends = vec![];
+        let mut lowered_bytes = vec![];
+        for (byte, in_range) in from.bytes.iter().zip(in_range.iter()) {
+            self.range.range_check(ctx, byte, 8);
+            let is_byte = |ctx: &mut Context<'v, F>, c: u8| {
+                gate.is_equal(
+                    ctx,
+                    QuantumCell::Existing(byte),
+                    QuantumCell::Constant(F::from(c as u64)),
+                )
+            };
+            let is_quote = is_byte(ctx, b'"');
+            let is_backslash = is_byte(ctx, b'\\');
+            let is_lt = is_byte(ctx, b'<');
+            let is_gt = is_byte(ctx, b'>');
+            let is_at = is_byte(ctx, b'@');
+            let mut is_end = gate.not(ctx, QuantumCell::Existing(in_range));
+            for c in [b' ', b'\t', b'\r', b'\n'] {
+                let is_space = is_byte(ctx, c);
+                is_end = gate.or(
+                    ctx,
+                    QuantumCell::Existing(&is_end),
+                    QuantumCell::Existing(&is_space),
+                );
+            }
+            is_end = gate.or(
+                ctx,
+                QuantumCell::Existing(&is_end),
+                QuantumCell::Existing(&is_gt),
+            );
+            is_ends.push(is_end);
+            let is_less_65 = self.range.is_less_than(
+                ctx,
+                QuantumCell::Existing(byte),
+                QuantumCell::Constant(F::from(b'A' as u64)),
+                8,
+            );
+            let is_less_91 = self.range.is_less_than(
+                ctx,
+                QuantumCell::Existing(byte),
+                QuantumCell::Constant(F::from(b'Z' as u64 + 1)),
+                8,
+            );
+            let is_upper = gate.sub(
+                ctx,
+                QuantumCell::Existing(&is_less_91),
+                QuantumCell::Existing(&is_less_65),
+            );
+            lowered_bytes.push(gate.mul_add(
+                ctx,
+                QuantumCell::Existing(&is_upper),
+                QuantumCell::Constant(F::from(32)),
+                QuantumCell::Existing(byte),
+            ));
+
+            // The bytes out of the quoted strings and the length.
+            let not_quoted = gate.not(ctx, QuantumCell::Existing(&in_quote));
+            let is_unquoted = gate.and(
+                ctx,
+                QuantumCell::Existing(in_range),
+                QuantumCell::Existing(&not_quoted),
+            );
+            let is_opening = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_unquoted),
+                QuantumCell::Existing(&is_lt),
+            );
+            let is_closing = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_unquoted),
+                QuantumCell::Existing(&is_gt),
+            );
+            ats.push(gate.and(
+                ctx,
+                QuantumCell::Existing(&is_unquoted),
+                QuantumCell::Existing(&is_at),
+            ));
+            in_angles.push(in_angle.clone());
+            num_angles = gate.add(
+                ctx,
+                QuantumCell::Existing(&num_angles),
+                QuantumCell::Existing(&is_opening),
+            );
+            let not_closing = gate.not(ctx, QuantumCell::Existing(&is_closing));
+            in_angle = gate.select(
+                ctx,
+                QuantumCell::Existing(&not_closing),
+                QuantumCell::Existing(&is_opening),
+                QuantumCell::Existing(&in_angle),
+            );
+
+            // A quote toggles the quoted string unless it is escaped in the quoted string.
+            let not_escaped = gate.not(ctx, QuantumCell::Existing(&is_escaped));
+            let is_toggle = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_quote),
+                QuantumCell::Existing(&not_escaped),
+            );
+            let is_toggle = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_toggle),
+                QuantumCell::Existing(in_range),
+            );
+            let is_escaping = gate.and(
+                ctx,
+                QuantumCell::Existing(&in_quote),
+                QuantumCell::Existing(&is_backslash),
+            );
+            is_escaped = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_escaping),
+                QuantumCell::Existing(&not_escaped),
+            );
+            let both = gate.mul(
+                ctx,
+                QuantumCell::Existing(&in_quote),
+                QuantumCell::Existing(&is_toggle),
+            );
+            let toggled = gate.add(
+                ctx,
+                QuantumCell::Existing(&in_quote),
+                QuantumCell::Existing(&is_toggle),
+            );
+            in_quote = gate.mul_add(
+                ctx,
+                QuantumCell::Existing(&both),
+                QuantumCell::Constant(-F::from(2)),
+                QuantumCell::Existing(&toggled),
+            );
+        }
+
+        // 2. The `@` of the address is the only one in the angle brackets, or the only one if there are no angle brackets.
+        let has_no_angle = gate.is_zero(ctx, &num_angles);
+        let mut address_ats = vec![];
+        for (at, in_angle) in ats.iter().zip(in_angles.iter()) {
+            let is_addressed = gate.add(
+                ctx,
+                QuantumCell::Existing(in_angle),
+                QuantumCell::Existing(&has_no_angle),
+            );
+            // An `@` in the angle brackets is not counted twice, since `has_no_angle` is then zero.
+            address_ats.push(gate.and(
+                ctx,
+                QuantumCell::Existing(at),
+                QuantumCell::Existing(&is_addressed),
+            ));
+        }
+        let num_address_ats = gate.sum(ctx, address_ats.iter().map(QuantumCell::Existing));
+        gate.assert_is_const(ctx, &num_address_ats, F::one());
+
+        // 3. The domain runs from the byte after the `@` to the first end.
+        let mut in_domain = vec![];
+        let mut prev = gate.load_zero(ctx);
+        for (at, is_end) in address_ats.iter().zip(is_ends.iter()) {
+            let not_end = gate.not(ctx, QuantumCell::Existing(is_end));
+            let is_domain = gate.and(
+                ctx,
+                QuantumCell::Existing(&prev),
+                QuantumCell::Existing(&not_end),
+            );
+            in_domain.push(is_domain.clone());
+            prev = gate.add(
+                ctx,
+                QuantumCell::Existing(&is_domain),
+                QuantumCell::Existing(at),
+            );
+        }
+        let len = gate.sum(ctx, in_domain.iter().map(QuantumCell::Existing));
+        let is_empty = gate.is_zero(ctx, &len);
+        gate.assert_is_const(ctx, &is_empty, F::zero());
+        // Both the length and the position of the domain are at most `n`.
+        let len_bits = (usize::BITS - n.max(self.max_domain_len + 1).leading_zeros()) as usize;
+        let is_short = self.range.is_less_than(
+            ctx,
+            QuantumCell::Existing(&len),
+            QuantumCell::Constant(F::from(self.max_domain_len as u64 + 1)),
+            len_bits,
+        );
+        gate.assert_is_const(ctx, &is_short, F::one());
+
+        // 4. The domain bytes are shifted to the head by the position after the `@`.
+        let start = gate.inner_product(
+            ctx,
+            address_ats.iter().map(QuantumCell::Existing).collect(),
+            (1..=n)
+                .map(|i| QuantumCell::Constant(F::from(i as u64)))
+                .collect(),
+        );
+        let mut bytes = in_domain
+            .iter()
+            .zip(lowered_bytes.iter())
+            .map(|(is_domain, byte)| {
+                gate.mul(
+                    ctx,
+                    QuantumCell::Existing(is_domain),
+                    QuantumCell::Existing(byte),
+                )
+            })
+            .collect::<Vec<AssignedValue<F>>>();
+        let zero = gate.load_zero(ctx);
+        for (stage, bit) in gate.num_to_bits(ctx, &start, len_bits).iter().enumerate() {
+            let offset = 1 << stage;
+            bytes = (0..n)
+                .map(|i| {
+                    let shifted = bytes.get(i + offset).unwrap_or(&zero);
+                    gate.select(
+                        ctx,
+                        QuantumCell::Existing(shifted),
+                        QuantumCell::Existing(&bytes[i]),
+                        QuantumCell::Existing(bit),
+                    )
+                })
+                .collect();
+        }
+        bytes.resize(self.max_domain_len, zero);
+        end_cost_region(ctx);
+        Ok(AssignedEmailDomain { bytes, len })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::DkimCanonChip;
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, Column, ConstraintSystem, Instance},
+    };
+    use halo2_base::{gates::range::RangeStrategy::Vertical, ContextParams, SKIP_FIRST_PASS};
+    use std::marker::PhantomData;
+
+    /// The `From` headers and the domains of their sender addresses.
+    const VECTORS: &[(&[u8], &[u8])] = &[
+        (
+            b"From: \"Doe, John \\\"JD\\\" <jd@evil.com>\" <John.Doe@Example.COM>\r\n",
+            b"example.com",
+        ),
+        (b"From: alice@example.org\r\n", b"example.org"),
+        (b"from:Bob <bob@mail.example.net>", b"mail.example.net"),
+        (b"From: \"bob@evil.com\"@example.com\r\n", b"example.com"),
+        (b"From: <carol@a.io> (Carol)\r\n", b"a.io"),
+    ];
+
+    /// The `From` headers without exactly one sender address with a non-empty domain.
+    const INVALID_HEADERS: &[&[u8]] = &[
+        b"From: undisclosed-recipients:;\r\n",
+        b"From: alice@example.org, bob@example.net\r\n",
+        b"From: Alice <alice@>\r\n",
+        b"From: \"alice@example.org\" <alice>\r\n",
+        b"From: Eve <eve@evil.com> <alice@example.org>\r\n",
+    ];
+
+    #[derive(Clone)]
+    struct TestEmailDomainConfig<F: PrimeField> {
+        canon_chip: DkimCanonChip<F>,
+        domain_chip: EmailDomainChip<F>,
+        instance: Column<Instance>,
+    }
+
+    struct TestEmailDomainCircuit<F: PrimeField> {
+        from: Vec<u8>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestEmailDomainCircuit<F> {
+        const K: usize = 15;
+        const MAX_HEADER_LEN: usize = 96;
+        const MAX_DOMAIN_LEN: usize = 16;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestEmailDomainCircuit<F> {
+        type Config = TestEmailDomainConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config =
+                RangeConfig::configure(meta, Vertical, &[8], &[1], 1, 12, 0, Self::K);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            TestEmailDomainConfig {
+                canon_chip: DkimCanonChip::new(range_config.clone(), Self::MAX_HEADER_LEN),
+                domain_chip: EmailDomainChip::new(range_config, Self::MAX_DOMAIN_LEN),
+                instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let range = config.domain_chip.range().clone();
+            range.load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let mut cells = vec![];
+            layouter.assign_region(
+                || "email domain",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = Context::new(
+                        region,
+                        ContextParams {
+                            max_rows: range.gate.max_rows,
+                            num_context_ids: 1,
+                            fixed_columns: range.gate.constants.clone(),
+                        },
+                    );
+                    let ctx = &mut aux;
+                    let from = config.canon_chip.assign_header(ctx, &self.from);
+                    let domain = config.domain_chip.extract_email_domain(ctx, &from)?;
+                    range.finalize(ctx);
+                    cells = domain.bytes.iter().map(|byte| byte.cell()).collect();
+                    Ok(())
+                },
+            )?;
+            for (i, cell) in cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell, config.instance, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn run_extract(from: &[u8], domain: &[u8]) -> bool {
+        let circuit = TestEmailDomainCircuit::<Fr> {
+            from: from.to_vec(),
+            _f: PhantomData,
+        };
+        let instances =
+            email_domain_instances(domain, TestEmailDomainCircuit::<Fr>::MAX_DOMAIN_LEN);
+        let prover = match MockProver::run(
+            TestEmailDomainCircuit::<Fr>::K as u32,
+            &circuit,
+            vec![instances],
+        ) {
+            Ok(prover) => prover,
+            Err(e) => panic!("{:#?}", e),
+        };
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_email_domain_of() {
+        for (from, domain) in VECTORS {
+            assert_eq!(
+                email_domain_of(from).as_deref(),
+                Some(*domain),
+                "{}",
+                String::from_utf8_lossy(from)
+            );
+        }
+        for from in INVALID_HEADERS {
+            assert_eq!(
+                email_domain_of(from),
+                None,
+                "{}",
+                String::from_utf8_lossy(from)
+            );
+        }
+    }
+
+    #[test]
+    fn test_email_domain_chip() {
+        for (from, domain) in VECTORS {
+            assert!(
+                run_extract(from, domain),
+                "{}",
+                String::from_utf8_lossy(from)
+            );
+        }
+    }
+
+    #[test]
+    fn test_email_domain_chip_rejects_wrong_domains() {
+        let (from, _) = VECTORS[0];
+        // The domain in the display name, the domain not lowercased, and a truncated domain.
+        assert!(!run_extract(from, b"evil.com"));
+        assert!(!run_extract(from, b"Example.COM"));
+        assert!(!run_extract(from, b"example"));
+        for from in INVALID_HEADERS {
+            assert!(!run_extract(from, b""), "{}", String::from_utf8_lossy(from));
+            assert!(
+                !run_extract(from, b"example.org"),
+                "{}",
+                String::from_utf8_lossy(from)
+            );
+        }
+        // The domain longer than the maximum length.
+        let from = b"From: alice@mail.example-long.org\r\n";
+        assert_eq!(
+            email_domain_of(from).as_deref(),
+            Some(&b"mail.example-long.org"[..])
+        );
+        assert!(!run_extract(from, b"mail.example-lon"));
+    }
+}
@@ -0,0 +1,176 @@
+use halo2_base::halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fq, Fr, G1Affine},
+    plonk::{create_proof, Circuit, Error, ProvingKey, VerifyingKey},
+    poly::{
+        commitment::ParamsProver,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::ProverGWC,
+        },
+    },
+    transcript::TranscriptWriterBuffer,
+};
+use rand::rngs::OsRng;
+use snark_verifier::{
+    loader::evm::{self, encode_calldata, Address, EvmLoader, ExecutorBuilder},
+    pcs::kzg::{Gwc19, KzgAs},
+    system::halo2::{compile, transcript::evm::EvmTranscript, Config},
+    verifier::{self, SnarkVerifier},
+};
+use std::rc::Rc;
+
+type PlonkVerifier = verifier::plonk::PlonkVerifier<KzgAs<Bn256, Gwc19>>;
+
+/// Generates the Yul source of a contract verifying the proofs of the circuit of `vk` created by [`create_evm_proof`].
+///
+/// # Arguments
+/// * params - the params used to generate `vk`. Only `g[0]`, `g2`, and `s_g2` are embedded in the contract.
+/// * vk - the verifying key of the circuit.
+/// * num_instances - the number of the rows of each instance column, e.g., [`crate::CircuitInfo::instance_layout`] for the pkcs1v15 circuits.
+///
+/// # Panics
+/// Panics if the length of `num_instances` differs from the number of the instance columns of `vk`.
+pub fn generate_evm_verifier_yul(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instances: Vec<usize>,
+) -> String {
+    let protocol = compile(
+        params,
+        vk,
+        Config::kzg().with_num_instance(num_instances.clone()),
+    );
+    let svk = (params.get_g()[0], params.g2(), params.s_g2()).into();
+
+    let loader = EvmLoader::new::<Fq, Fr>();
+    let protocol = protocol.loaded(&loader);
+    let mut transcript = EvmTranscript::<_, Rc<EvmLoader>, _, _>::new(&loader);
+    let instances = transcript.load_instances(num_instances);
+    let proof = PlonkVerifier::read_proof(&svk, &protocol, &instances, &mut transcript).unwrap();
+    PlonkVerifier::verify(&svk, &protocol, &instances, &proof).unwrap();
+    loader.yul_code()
+}
+
+/// Generates the deployment bytecode of a contract verifying the proofs of the circuit of `vk` created by [`create_evm_proof`].
+///
+/// The contract takes the instances followed by the proof as its calldata, which [`evm_verify`] encodes, and reverts if the proof is invalid.
+/// See [`generate_evm_verifier_yul`] for the arguments.
+///
+/// # Panics
+/// In addition to the cases of [`generate_evm_verifier_yul`], panics if `solc` is not found in `PATH` to compile the Yul source.
+pub fn generate_evm_verifier(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instances: Vec<usize>,
+) -> Vec<u8> {
+    evm::compile_yul(&generate_evm_verifier_yul(params, vk, num_instances))
+}
+
+/// Creates a proof of `circuit` that the contract generated by [`generate_evm_verifier`] verifies.
+///
+/// The proof uses the Keccak256 transcript of snark-verifier and the GWC multi-open scheme.
+/// It is not a proof of [`crate::TranscriptKind::Keccak256`], whose transcript hashes the points differently, so it cannot be verified by [`crate::verify_proof_with`].
+pub fn create_evm_proof<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[&[Fr]],
+) -> Result<Vec<u8>, Error> {
+    let mut transcript = EvmTranscript::<_, _, _, _>::init(vec![]);
+    create_proof::<KZGCommitmentScheme<Bn256>, ProverGWC<_>, _, _, _, _>(
+        params,
+        pk,
+        &[circuit],
+        &[instances],
+        OsRng,
+        &mut transcript,
+    )?;
+    Ok(transcript.finalize())
+}
+
+/// Deploys `deployment_code` generated by [`generate_evm_verifier`] to an in-memory EVM and returns whether the contract accepts `proof` for `instances`.
+pub fn evm_verify(deployment_code: Vec<u8>, instances: &[Vec<Fr>], proof: &[u8]) -> bool {
+    let calldata = encode_calldata(instances, proof);
+    let mut evm = ExecutorBuilder::default()
+        .with_gas_limit(u64::MAX.into())
+        .build();
+    let caller = Address::from_low_u64_be(0xfe);
+    let verifier = match evm.deploy(caller, deployment_code.into(), 0.into()).address {
+        Some(verifier) => verifier,
+        None => return false,
+    };
+    let result = evm.call_raw(caller, verifier, calldata.into(), 0.into());
+    !result.reverted
+}
+
+#[cfg(all(test, feature = "sha256"))]
+mod test {
+    use super::*;
+    use crate::{impl_pkcs1v15_basic_circuit, RSAPubE, RSAPublicKey, RSASignature};
+    use halo2_base::halo2_proofs::{circuit::Value, poly::commitment::Params};
+    use num_bigint::BigUint;
+    use rand::{thread_rng, Rng};
+    use rsa::{traits::PublicKeyParts, Pkcs1v15Sign, RsaPrivateKey, RsaPublicKey};
+    use sha2::{Digest, Sha256};
+    use std::marker::PhantomData;
+
+    impl_pkcs1v15_basic_circuit!(
+        TestEvmConfig,
+        TestEvmCircuit,
+        setup_evm,
+        prove_evm,
+        1024,
+        64,
+        4,
+        1,
+        16,
+        1,
+        15,
+        false,
+        true
+    );
+
+    #[test]
+    fn test_evm_verifier() {
+        let (params, vk, pk) = setup_evm();
+        assert_eq!(params.k(), TestEvmCircuit::<Fr>::min_k());
+        prove_evm(&params, &vk, &pk, crate::MultiOpenScheme::Gwc);
+        let info = TestEvmCircuit::<Fr>::INFO;
+        let deployment_code = generate_evm_verifier(&params, &vk, info.instance_layout());
+
+        let mut rng = thread_rng();
+        let private_key = RsaPrivateKey::new(&mut rng, info.bits_len).unwrap();
+        let public_key = RsaPublicKey::from(&private_key);
+        let digest = Sha256::digest(rng.gen::<[u8; 32]>()).to_vec();
+        let sign = private_key
+            .sign(Pkcs1v15Sign::new::<rsa::sha2::Sha256>(), &digest)
+            .unwrap();
+        let circuit = TestEvmCircuit::<Fr> {
+            signature: RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+            public_key: RSAPublicKey::new(
+                Value::known(BigUint::from_bytes_be(&public_key.n().to_bytes_be())),
+                RSAPubE::Fix(BigUint::from(TestEvmCircuit::<Fr>::DEFAULT_E)),
+            ),
+            msg: digest.clone(),
+            _f: PhantomData,
+        };
+        let instances = TestEvmCircuit::<Fr>::instances(&digest);
+        let instance_refs = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        let proof = create_evm_proof(&params, &pk, circuit, &instance_refs).unwrap();
+        assert!(evm_verify(deployment_code.clone(), &instances, &proof));
+
+        let mut tampered = instances.clone();
+        tampered[0][0] += Fr::from(1);
+        assert!(!evm_verify(deployment_code, &tampered, &proof));
+        // The proof of snark-verifier's transcript is not the one of `TranscriptKind::Keccak256`.
+        assert!(crate::verify_proof_with(
+            &params,
+            &vk,
+            &proof,
+            &instance_refs,
+            crate::TranscriptKind::Keccak256,
+            crate::MultiOpenScheme::Gwc,
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,198 @@
+//! **UNSOUND. FOR TESTS ONLY. NEVER ENABLE THE `fast-test-hash` FEATURE IN PRODUCTION.**
+//!
+//! [`FastTestSha256Chip`] stands in for the SHA256 chip so that the tests of the RSA layer do not pay for synthesizing the 64 rounds of SHA256.
+//! It runs zero rounds in the circuit: the digest is computed outside the circuit and assigned as free witnesses, so a malicious prover can claim any digest for any message.
+//! To keep it out of production binaries, the crate refuses to compile with this feature unless debug assertions are enabled.
+
+use crate::Sha256Instructions;
+use halo2_base::halo2_proofs::{
+    circuit::{Layouter, Value},
+    plonk::Error,
+};
+use halo2_base::{
+    gates::{range::RangeConfig, GateInstructions, RangeInstructions},
+    utils::PrimeField,
+    AssignedValue, Context,
+};
+use halo2_dynamic_sha256::AssignedHashResult;
+use sha2::{Digest, Sha256};
+
+#[cfg(not(debug_assertions))]
+compile_error!("the `fast-test-hash` feature is unsound and must not be enabled in release builds");
+
+/// An UNSOUND stand-in for the SHA256 chip for the tests of the RSA layer. See the [module documentation](self).
+///
+/// It implements [`Sha256Instructions`], so it is given to [`crate::RSASignatureVerifier::new`] in place of a [`halo2_dynamic_sha256::Sha256DynamicConfig`].
+#[derive(Debug, Clone)]
+pub struct FastTestSha256Chip<F: PrimeField> {
+    range: RangeConfig<F>,
+}
+
+impl<F: PrimeField> FastTestSha256Chip<F> {
+    /// Creates new [`FastTestSha256Chip`] from a [`RangeConfig`] with lookup bits of at least 8.
+    pub fn construct(range: RangeConfig<F>) -> Self {
+        Self { range }
+    }
+
+    /// Does nothing, since the chip has no table other than the lookup table of its [`RangeConfig`], which the circuit loads.
+    ///
+    /// It exists so that the chip replaces [`halo2_dynamic_sha256::Sha256DynamicConfig`] in a circuit without other changes.
+    pub fn load(&self, _layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn assign_byte<'v>(&self, ctx: &mut Context<'v, F>, byte: u8) -> AssignedValue<'v, F> {
+        let assigned = self
+            .range
+            .gate()
+            .load_witness(ctx, Value::known(F::from(byte as u64)));
+        self.range.range_check(ctx, &assigned, 8);
+        assigned
+    }
+}
+
+impl<F: PrimeField> Sha256Instructions<F> for FastTestSha256Chip<F> {
+    /// Assigns `input`, its length, and its SHA256 digest computed outside the circuit.
+    ///
+    /// Every byte is range-checked to 8 bits, but nothing binds the length and the digest to the input.
+    /// `precomputed_input_len` is ignored.
+    fn digest<'a, 'b: 'a>(
+        &'a mut self,
+        ctx: &mut Context<'b, F>,
+        input: &'a [u8],
+        _precomputed_input_len: Option<usize>,
+    ) -> Result<AssignedHashResult<'b, F>, Error> {
+        let input_len = self
+            .range
+            .gate()
+            .load_witness(ctx, Value::known(F::from(input.len() as u64)));
+        let input_bytes = input
+            .iter()
+            .map(|byte| self.assign_byte(ctx, *byte))
+            .collect::<Vec<_>>();
+        let output_bytes = Sha256::digest(input)
+            .iter()
+            .map(|byte| self.assign_byte(ctx, *byte))
+            .collect::<Vec<_>>();
+        Ok(AssignedHashResult {
+            input_len,
+            input_bytes,
+            output_bytes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        BigUintConfig, RSAConfig, RSAInstructions, RSAPubE, RSAPublicKey, RSASignature,
+        RSASignatureVerifier,
+    };
+    use halo2_base::halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem},
+    };
+    use halo2_base::{gates::range::RangeStrategy::Vertical, SKIP_FIRST_PASS};
+    use num_bigint::BigUint;
+    use rand::thread_rng;
+    use rsa::{
+        pkcs1v15::SigningKey, signature::SignatureEncoding, signature::Signer,
+        traits::PublicKeyParts, RsaPrivateKey,
+    };
+    use std::marker::PhantomData;
+
+    const K: usize = 13;
+
+    struct TestFastHashSignatureCircuit<F: PrimeField> {
+        private_key: RsaPrivateKey,
+        msg: Vec<u8>,
+        signed_msg: Vec<u8>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestFastHashSignatureCircuit<F> {
+        type Config = RSAConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config = RangeConfig::configure(meta, Vertical, &[50], &[4], 1, 12, 0, K);
+            let biguint_config = BigUintConfig::construct(range_config, 64);
+            RSAConfig::construct(biguint_config, 2048, 5)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "rsa signature test with the fast test hash",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    let signing_key =
+                        SigningKey::<rsa::sha2::Sha256>::new(self.private_key.clone());
+                    let sign = signing_key.sign(&self.signed_msg).to_vec();
+                    let sign = config.assign_signature(
+                        ctx,
+                        RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+                    )?;
+                    let n_big = BigUint::from_bytes_be(&self.private_key.n().to_bytes_be());
+                    let e_fix = RSAPubE::Fix(BigUint::from(65537u32));
+                    let public_key = config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                    let chip = FastTestSha256Chip::construct(config.range().clone());
+                    let mut verifier = RSASignatureVerifier::new(config.clone(), chip);
+                    let (is_valid, hashed_msg) =
+                        verifier.verify_pkcs1v15_signature(ctx, &public_key, &self.msg, &sign)?;
+                    assert_eq!(hashed_msg.len(), 32);
+                    config.gate().assert_is_const(ctx, &is_valid, F::one());
+                    config.range().finalize(ctx);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn run(msg: &[u8], signed_msg: &[u8]) -> bool {
+        let private_key =
+            RsaPrivateKey::new(&mut thread_rng(), 2048).expect("failed to generate a key");
+        let circuit = TestFastHashSignatureCircuit::<Fr> {
+            private_key,
+            msg: msg.to_vec(),
+            signed_msg: signed_msg.to_vec(),
+            _f: PhantomData,
+        };
+        let prover = match MockProver::run(K as u32, &circuit, vec![]) {
+            Ok(prover) => prover,
+            Err(e) => panic!("{:#?}", e),
+        };
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_fast_hash_signature() {
+        let msg = [7u8; 1024];
+        assert!(run(&msg, &msg));
+    }
+
+    #[test]
+    fn test_fast_hash_signature_for_another_message() {
+        assert!(!run(b"hello world", b"hello again"));
+    }
+}
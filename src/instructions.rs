@@ -1,5 +1,6 @@
 use crate::{
-    AssignedBigUint, AssignedRSAPublicKey, AssignedRSASignature, Fresh, RSAPublicKey, RSASignature,
+    AssignedBigUint, AssignedDigestInfo, AssignedRSAPublicKey, AssignedRSASignature, Fresh,
+    RSAPublicKey, RSASignature,
 };
 use halo2_base::halo2_proofs::{circuit::Region, circuit::Value, plonk::Error};
 use halo2_base::utils::fe_to_bigint;
@@ -12,7 +13,7 @@ use halo2_base::{
 };
 /// Instructions for RSA operations.
 pub trait RSAInstructions<F: PrimeField> {
-    /// Assigns a [`AssignedRSAPublicKey`].
+    /// Assigns a [`AssignedRSAPublicKey`], constraining its modulus `n` to be odd.
     fn assign_public_key<'v>(
         &self,
         ctx: &mut Context<'v, F>,
@@ -42,4 +43,21 @@ pub trait RSAInstructions<F: PrimeField> {
         hashed_msg: &[AssignedValue<'v, F>],
         signature: &AssignedRSASignature<'v, F>,
     ) -> Result<AssignedValue<'v, F>, Error>;
+
+    /// Given a RSA public key, a pkcs1v15 signature, and the big-endian bytes of a SHA256 digest computed outside the circuit, verifies the signature for the digest.
+    fn verify_pkcs1v15_digest<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        public_key: &AssignedRSAPublicKey<'v, F>,
+        signature: &AssignedRSASignature<'v, F>,
+        digest: &[AssignedValue<'v, F>; 32],
+    ) -> Result<AssignedValue<'v, F>, Error>;
+
+    /// Given a RSA public key and a pkcs1v15 signature, decodes the DigestInfo in the recovered padded block and returns its algorithm OID and digest.
+    fn decode_pkcs1v15_digest_info<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        public_key: &AssignedRSAPublicKey<'v, F>,
+        signature: &AssignedRSASignature<'v, F>,
+    ) -> Result<AssignedDigestInfo<'v, F>, Error>;
 }
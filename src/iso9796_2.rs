@@ -0,0 +1,363 @@
+//! Verification of ISO 9796-2 (scheme 1) RSA signatures with partial message recovery, as used in EMV.
+//!
+//! The recovered block `s^e mod n` is laid out as `0x6A || M1 || H || T`, where `M1` is the recoverable part of the message `M = M1 || M2`, `H` is the hash of `M`, and `T` is a trailer field.
+//! The following trailer fields are supported:
+//! * `0xBC` - the implicit trailer, with which the hash algorithm is agreed in advance (e.g., SHA-1 in EMV).
+//! * `0x33CC` - the explicit trailer for SHA-1.
+//! * `0x34CC` - the explicit trailer for SHA-256.
+//!
+//! Total recovery (the header `0x4B`) and the moduli whose bit length is not a multiple of 8 are not supported.
+use crate::{AssignedRSAPublicKey, AssignedRSASignature, RSAConfig, RSAInstructions};
+use halo2_base::halo2_proofs::plonk::Error;
+use halo2_base::QuantumCell;
+use halo2_base::{gates::GateInstructions, utils::PrimeField, AssignedValue, Context};
+
+/// The header byte of the recovered block with partial message recovery.
+pub const ISO9796_2_PARTIAL_RECOVERY_HEADER: u8 = 0x6A;
+
+/// A trailer field of ISO 9796-2 signatures.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Iso9796_2Trailer {
+    /// The implicit trailer `0xBC` with the byte length of the digest of the agreed hash algorithm.
+    Implicit(usize),
+    /// The explicit trailer `0x33CC` for SHA-1.
+    Sha1,
+    /// The explicit trailer `0x34CC` for SHA-256.
+    Sha256,
+}
+
+impl Iso9796_2Trailer {
+    /// Returns the bytes of the trailer field.
+    pub fn bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Implicit(_) => vec![0xBC],
+            Self::Sha1 => vec![0x33, 0xCC],
+            Self::Sha256 => vec![0x34, 0xCC],
+        }
+    }
+
+    /// Returns the byte length of the digest in the recovered block.
+    pub fn digest_len(&self) -> usize {
+        match self {
+            Self::Implicit(digest_len) => *digest_len,
+            Self::Sha1 => 20,
+            Self::Sha256 => 32,
+        }
+    }
+}
+
+/// An assigned message recovered from an ISO 9796-2 signature.
+#[derive(Clone, Debug)]
+pub struct AssignedIso9796_2Message<'v, F: PrimeField> {
+    /// the assigned bit that is one if the header, the hash, and the trailer of the recovered block are valid.
+    pub is_valid: AssignedValue<'v, F>,
+    /// the assigned bytes of the recoverable part of the message `M1`.
+    pub recovered_msg: Vec<AssignedValue<'v, F>>,
+    /// the assigned bytes of the hash of the whole message `M1 || M2`.
+    pub digest: Vec<AssignedValue<'v, F>>,
+}
+
+impl<F: PrimeField> RSAConfig<F> {
+    /// Given a RSA public key, an ISO 9796-2 signature with partial message recovery, and the hash of the whole message, recovers the embedded part of the message.
+    ///
+    /// The hash `H` in the recovered block must be equal to `expected_digest`, which the caller computes from `M1 || M2`, e.g., with a hash chip over `recovered_msg` of the return value and `M2`, or exposes as public inputs.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `public_key` - an assigned RSA public key.
+    /// * `signature` - an assigned ISO 9796-2 signature.
+    /// * `expected_digest` - the assigned bytes of the hash of the whole message `M1 || M2`, which must be [`Iso9796_2Trailer::digest_len`] bytes.
+    /// * `trailer` - the trailer field of the signature.
+    ///
+    /// # Return values
+    /// Returns [`AssignedIso9796_2Message`] whose `recovered_msg` is `default_bits / 8 - 1 - digest_len - trailer_len` bytes.
+    /// Its `is_valid` is one if the header, the trailer, and the hash equal to `expected_digest` are recovered from `signature`, and zero otherwise.
+    pub fn verify_iso9796_2<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        public_key: &AssignedRSAPublicKey<'v, F>,
+        signature: &AssignedRSASignature<'v, F>,
+        expected_digest: &[AssignedValue<'v, F>],
+        trailer: &Iso9796_2Trailer,
+    ) -> Result<AssignedIso9796_2Message<'v, F>, Error> {
+        let gate = self.gate();
+        let powed = self.modpow_public_key(ctx, &signature.c, public_key)?;
+        let mut em_bytes = self.decompose_bytes(ctx, &powed);
+        em_bytes.reverse();
+        let em_len = em_bytes.len();
+        let trailer_bytes = trailer.bytes();
+        let trailer_len = trailer_bytes.len();
+        let digest_len = trailer.digest_len();
+        assert!(em_len > 1 + digest_len + trailer_len);
+        assert_eq!(expected_digest.len(), digest_len);
+
+        // 1. Check the header.
+        let mut is_valid = gate.is_equal(
+            ctx,
+            QuantumCell::Existing(&em_bytes[0]),
+            QuantumCell::Constant(F::from(ISO9796_2_PARTIAL_RECOVERY_HEADER as u64)),
+        );
+        // 2. Check the trailer.
+        for (byte, expected) in em_bytes[(em_len - trailer_len)..].iter().zip(trailer_bytes) {
+            let is_byte_eq = gate.is_equal(
+                ctx,
+                QuantumCell::Existing(byte),
+                QuantumCell::Constant(F::from(expected as u64)),
+            );
+            is_valid = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_valid),
+                QuantumCell::Existing(&is_byte_eq),
+            );
+        }
+        // 3. Split the rest into the recovered message and the digest, which must be the expected one.
+        let msg_end = em_len - trailer_len - digest_len;
+        let recovered_msg = em_bytes[1..msg_end].to_vec();
+        let digest = em_bytes[msg_end..(em_len - trailer_len)].to_vec();
+        for (byte, expected) in digest.iter().zip(expected_digest) {
+            let is_byte_eq = gate.is_equal(
+                ctx,
+                QuantumCell::Existing(byte),
+                QuantumCell::Existing(expected),
+            );
+            is_valid = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_valid),
+                QuantumCell::Existing(&is_byte_eq),
+            );
+        }
+        Ok(AssignedIso9796_2Message {
+            is_valid,
+            recovered_msg,
+            digest,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{decode_hex, BigUintConfig, RSAPubE, RSAPublicKey, RSASignature};
+    use halo2_base::halo2_proofs::{
+        circuit::{Cell, Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, Column, ConstraintSystem, Instance},
+    };
+    use halo2_base::{
+        gates::{
+            range::{RangeConfig, RangeStrategy::Vertical},
+            RangeInstructions,
+        },
+        SKIP_FIRST_PASS,
+    };
+    use num_bigint::BigUint;
+    use std::marker::PhantomData;
+
+    /// An issuer public key certificate in the layout of EMV Book 2, signed by a 1024 bits certification authority key with `e = 65537`.
+    ///
+    /// The recovered block is `6A || M1 || SHA-1(M1 || M2) || BC`, where `M1` is the certificate format `02`, the issuer identifier `45399912`, the expiration date `1230`,
+    /// the serial number `000001`, the hash and key algorithm indicators `01 01`, the issuer key length `60`, the exponent length `01`, and the leftmost 92 bytes of the issuer key,
+    /// and `M2` is the remainder of the issuer key followed by the issuer exponent `03`.
+    mod emv_vector {
+        pub const CA_MODULUS: &str = "bdc2840c615a4ce9b527477504b6d0cc3a64a2804f88ef6b4624b8c661954eba69a23b9f036c757450a497a3076015ea767b71aa8c377d11729a07489e2ab5f17036797ee2dd5f50b93dafddc5c1481f8a3a91e1c671faedbf317997b509c10a1e095aef18baeb9d46ca2a0693bc2a0d10d7d5cba37c18d4e5ec447d91d09297";
+        pub const CERTIFICATE: &str = "478615c112b008bfcb4b27c718edde83a46dd7a1cf920ef701569dd8161e7e6827f1fff0a27d11d1608996836015d1f346f981f82fd5072e1014965168c898dd588be4298cb1ce5b01ca61f1163c5c6c459c8c80104430a0ea44930a5b76790e96ef28fd706ed660f3fb0040f5824094f075b941dafc6df62e976a99f9da6e60";
+        pub const RECOVERED_MSG: &str = "0245399912123000000101016001c2d1ad1fa17dfa5bed4f5ac7584c0eb6689dcb9c7bcc87d8c50858db0da0809d2f51bfc64611b11a17bc07ce8a8e92022c2b217d5f8ecbf863af3f2e076d6b18f7d9b8915b63f1ba41b31b9d5fcdfac7ede91837c5d5319a823d908a";
+        pub const ISSUER_KEY_REMAINDER: &str = "b71435e6";
+        pub const ISSUER_KEY_EXPONENT: &str = "03";
+        pub const HASH: &str = "bd7ab11c1c2d177b9ff4fa7b30a2caef72c5f4fa";
+    }
+
+    #[derive(Clone, Debug)]
+    struct TestIso9796_2Config<F: PrimeField> {
+        rsa_config: RSAConfig<F>,
+        recovered_instance: Column<Instance>,
+    }
+
+    struct TestIso9796_2Circuit<F: PrimeField> {
+        n: BigUint,
+        signature: BigUint,
+        expected_digest: Vec<u8>,
+        trailer: Iso9796_2Trailer,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestIso9796_2Circuit<F> {
+        const BITS_LEN: usize = 1024;
+        const LIMB_BITS: usize = 64;
+        const EXP_LIMB_BITS: usize = 5;
+        const DEFAULT_E: u128 = 65537;
+        const NUM_ADVICE: usize = 50;
+        const NUM_FIXED: usize = 1;
+        const NUM_LOOKUP_ADVICE: usize = 4;
+        const LOOKUP_BITS: usize = 12;
+        const K: u32 = 13;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestIso9796_2Circuit<F> {
+        type Config = TestIso9796_2Config<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config = RangeConfig::configure(
+                meta,
+                Vertical,
+                &[Self::NUM_ADVICE],
+                &[Self::NUM_LOOKUP_ADVICE],
+                Self::NUM_FIXED,
+                Self::LOOKUP_BITS,
+                0,
+                Self::K as usize,
+            );
+            let bigint_config = BigUintConfig::construct(range_config, Self::LIMB_BITS);
+            let rsa_config =
+                RSAConfig::construct(bigint_config, Self::BITS_LEN, Self::EXP_LIMB_BITS);
+            let recovered_instance = meta.instance_column();
+            meta.enable_equality(recovered_instance);
+            Self::Config {
+                rsa_config,
+                recovered_instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let rsa_config = config.rsa_config;
+            rsa_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let recovered_cells = layouter.assign_region(
+                || "iso9796-2 signature test with 1024 bits public keys",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(vec![]);
+                    }
+
+                    let mut aux = rsa_config.new_context(region);
+                    let ctx = &mut aux;
+                    let e_fix = RSAPubE::Fix(BigUint::from(Self::DEFAULT_E));
+                    let public_key = RSAPublicKey::new(Value::known(self.n.clone()), e_fix);
+                    let public_key = rsa_config.assign_public_key(ctx, public_key)?;
+                    let sign = RSASignature::new(Value::known(self.signature.clone()));
+                    let sign = rsa_config.assign_signature(ctx, sign)?;
+                    let expected_digest = self
+                        .expected_digest
+                        .iter()
+                        .map(|byte| {
+                            rsa_config
+                                .gate()
+                                .load_witness(ctx, Value::known(F::from(*byte as u64)))
+                        })
+                        .collect::<Vec<AssignedValue<F>>>();
+                    let recovered = rsa_config.verify_iso9796_2(
+                        ctx,
+                        &public_key,
+                        &sign,
+                        &expected_digest,
+                        &self.trailer,
+                    )?;
+                    rsa_config
+                        .gate()
+                        .assert_is_const(ctx, &recovered.is_valid, F::one());
+                    rsa_config.range().finalize(ctx);
+                    {
+                        println!("total advice cells: {}", ctx.total_advice);
+                        let const_rows = ctx.total_fixed + 1;
+                        println!("maximum rows used by a fixed column: {const_rows}");
+                        println!("lookup cells used: {}", ctx.cells_to_lookup.len());
+                    }
+                    let cells = recovered
+                        .recovered_msg
+                        .iter()
+                        .chain(expected_digest.iter())
+                        .map(|v| v.cell())
+                        .collect::<Vec<Cell>>();
+                    Ok(cells)
+                },
+            )?;
+            for (i, cell) in recovered_cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell, config.recovered_instance, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Recovers the issuer public key certificate of [`emv_vector`] in the circuit, given the hash `expected_digest` and the trailer `trailer`, and exposes `M1` and the hash.
+    fn run_emv_vector_test(
+        expected_digest: Vec<u8>,
+        trailer: Iso9796_2Trailer,
+        should_be_error: bool,
+    ) {
+        let recovered_msg = decode_hex(emv_vector::RECOVERED_MSG).unwrap();
+        let circuit = TestIso9796_2Circuit::<Fr> {
+            n: BigUint::from_bytes_be(&decode_hex(emv_vector::CA_MODULUS).unwrap()),
+            signature: BigUint::from_bytes_be(&decode_hex(emv_vector::CERTIFICATE).unwrap()),
+            expected_digest: expected_digest.clone(),
+            trailer,
+            _f: PhantomData,
+        };
+        let public_inputs = recovered_msg
+            .iter()
+            .chain(expected_digest.iter())
+            .map(|byte| Fr::from(*byte as u64))
+            .collect::<Vec<Fr>>();
+        let prover =
+            match MockProver::run(TestIso9796_2Circuit::<Fr>::K, &circuit, vec![public_inputs]) {
+                Ok(prover) => prover,
+                Err(e) => panic!("{:#?}", e),
+            };
+        assert_eq!(prover.verify().is_err(), should_be_error);
+    }
+
+    #[test]
+    fn test_iso9796_2_emv_issuer_certificate() {
+        let hash = decode_hex(emv_vector::HASH).unwrap();
+        run_emv_vector_test(hash, Iso9796_2Trailer::Implicit(20), false);
+    }
+
+    #[test]
+    fn test_iso9796_2_wrong_digest() {
+        // A hash differing in one bit, as that of an `M2` the signer did not sign.
+        let mut hash = decode_hex(emv_vector::HASH).unwrap();
+        hash[19] ^= 1;
+        run_emv_vector_test(hash, Iso9796_2Trailer::Implicit(20), true);
+    }
+
+    #[test]
+    fn test_iso9796_2_wrong_trailer() {
+        let hash = decode_hex(emv_vector::HASH).unwrap();
+        run_emv_vector_test(hash, Iso9796_2Trailer::Sha1, true);
+    }
+
+    #[test]
+    fn test_emv_vector_layout() {
+        let recovered_msg = decode_hex(emv_vector::RECOVERED_MSG).unwrap();
+        let remainder = decode_hex(emv_vector::ISSUER_KEY_REMAINDER).unwrap();
+        let exponent = decode_hex(emv_vector::ISSUER_KEY_EXPONENT).unwrap();
+        let n = BigUint::from_bytes_be(&decode_hex(emv_vector::CA_MODULUS).unwrap());
+        let signature = BigUint::from_bytes_be(&decode_hex(emv_vector::CERTIFICATE).unwrap());
+        let recovered = signature.modpow(&BigUint::from(65537u32), &n).to_bytes_be();
+        let trailer = Iso9796_2Trailer::Implicit(20).bytes();
+        assert_eq!(
+            recovered,
+            [
+                vec![ISO9796_2_PARTIAL_RECOVERY_HEADER],
+                recovered_msg.clone(),
+                decode_hex(emv_vector::HASH).unwrap(),
+                trailer,
+            ]
+            .concat()
+        );
+        // The issuer key length and the exponent length in `M1` cover the leftmost bytes of the key in `M1` and the rest in `M2`.
+        assert_eq!(recovered_msg[11] as usize, 128 - 36 + remainder.len());
+        assert_eq!(recovered_msg[12] as usize, exponent.len());
+        assert_eq!(recovered_msg.len() - 14, 128 - 36);
+    }
+}
@@ -0,0 +1,369 @@
+use serde_json::Value;
+use std::fmt::Write;
+
+/// Serializes `value` in the JSON Canonicalization Scheme (JCS, RFC 8785), e.g., to get the bytes of a verifiable credential signed as canonical JSON.
+///
+/// The canonicalization is done outside the circuit, and the returned bytes are given to the circuit as the message to be hashed.
+/// The signature verifies only if they are exactly the bytes the signer canonicalized, so this follows RFC 8785 as follows:
+/// * the members of every object are sorted by the UTF-16 code units of their names, independently of the order kept by `serde_json`;
+/// * numbers are serialized as the IEEE 754 doubles closest to them in the format of ECMAScript's `Number.prototype.toString`, e.g., `4.50` as `4.5` and `1E30` as `1e+30`;
+/// * strings escape only `"`, `\` and the control characters, and the other characters are written as UTF-8;
+/// * no whitespace is written.
+///
+/// Integers beyond 2^53 lose their precision as in JavaScript. RFC 8785 also requires rejecting objects with duplicate names, which `serde_json` already merges when parsing, so parse the JSON with a parser rejecting them if the input is untrusted.
+pub fn prepare_jcs(value: &Value) -> Vec<u8> {
+    let mut out = String::new();
+    write_value(&mut out, value);
+    out.into_bytes()
+}
+
+fn write_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => write_number(out, n.as_f64().expect("a JSON number is a double")),
+        Value::String(s) => write_string(out, s),
+        Value::Array(values) => {
+            out.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, value);
+            }
+            out.push(']');
+        }
+        Value::Object(members) => {
+            let mut members = members.iter().collect::<Vec<_>>();
+            members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (i, (name, value)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(out, name);
+                out.push(':');
+                write_value(out, value);
+            }
+            out.push('}');
+        }
+    }
+}
+
+/// Writes `value` as ECMAScript's `Number.prototype.toString` does, which RFC 8785 uses for the numbers.
+fn write_number(out: &mut String, value: f64) {
+    assert!(value.is_finite(), "JCS does not support {}", value);
+    if value == 0.0 {
+        // Both 0 and -0.
+        out.push('0');
+        return;
+    }
+    if value < 0.0 {
+        out.push('-');
+    }
+    // The shortest digits that round-trip, e.g., "1.2345e6".
+    let sci = format!("{:e}", value.abs());
+    let (mantissa, exponent) = sci.split_once('e').unwrap();
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    // The position of the decimal point relative to the first digit.
+    let n = exponent.parse::<i32>().unwrap() + 1;
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.extend(std::iter::repeat('0').take((n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        out.push_str(&digits[..n as usize]);
+        out.push('.');
+        out.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.extend(std::iter::repeat('0').take(-n as usize));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        write!(out, "e{}{}", if n > 0 { "+" } else { "-" }, (n - 1).abs()).unwrap();
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{8}' => out.push_str("\\b"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\u{c}' => out.push_str("\\f"),
+            '\r' => out.push_str("\\r"),
+            c if c < ' ' => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn jcs(json: &str) -> String {
+        String::from_utf8(prepare_jcs(&serde_json::from_str(json).unwrap())).unwrap()
+    }
+
+    #[test]
+    fn test_rfc8785_example() {
+        // RFC 8785, Section 3.2.2.
+        let input = r#"{
+            "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+            "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+            "literals": [null, true, false]
+        }"#;
+        assert_eq!(
+            jcs(input),
+            r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+        );
+    }
+
+    #[test]
+    fn test_rfc8785_sorting() {
+        // RFC 8785, Section 3.2.3. The emoji is sorted by its surrogates, i.e., before U+FB33.
+        let input = r#"{
+            "\u20ac": "Euro Sign",
+            "\r": "Carriage Return",
+            "\ufb33": "Hebrew Letter Dalet With Dagesh",
+            "1": "One",
+            "\ud83d\ude00": "Emoji: Grinning Face",
+            "\u0080": "Control",
+            "\u00f6": "Latin Small Letter O With Diaeresis"
+        }"#;
+        assert_eq!(
+            jcs(input),
+            "{\"\\r\":\"Carriage Return\",\"1\":\"One\",\"\u{80}\":\"Control\",\"\u{f6}\":\"Latin Small Letter O With Diaeresis\",\"\u{20ac}\":\"Euro Sign\",\"\u{1f600}\":\"Emoji: Grinning Face\",\"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}"
+        );
+    }
+
+    #[test]
+    fn test_rfc8785_numbers() {
+        // RFC 8785, Appendix B.
+        for (bits, expected) in [
+            (0x0000000000000000u64, "0"),
+            (0x8000000000000000, "0"),
+            (0x0000000000000001, "5e-324"),
+            (0x8000000000000001, "-5e-324"),
+            (0x7fefffffffffffff, "1.7976931348623157e+308"),
+            (0xffefffffffffffff, "-1.7976931348623157e+308"),
+            (0x4340000000000000, "9007199254740992"),
+            (0xc340000000000000, "-9007199254740992"),
+            (0x4430000000000000, "295147905179352830000"),
+            (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+            (0x44b52d02c7e14af6, "1e+23"),
+            (0x44b52d02c7e14af7, "1.0000000000000001e+23"),
+            (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+            (0x444b1ae4d6e2ef50, "1e+21"),
+            (0x3eb0c6f7a0b5ed8d, "0.000001"),
+            (0x3eb0c6f7a0b5ed8e, "0.0000010000000000000002"),
+            (0x41b3de4355555553, "333333333.3333332"),
+            (0x41b3de4355555557, "333333333.33333343"),
+        ] {
+            let mut out = String::new();
+            write_number(&mut out, f64::from_bits(bits));
+            assert_eq!(out, expected, "{:#018x}", bits);
+        }
+        assert_eq!(
+            jcs("[-0, 1.0, 100, 1e-7, 123e-20, 18446744073709551615]"),
+            "[0,1,100,1e-7,1.23e-18,18446744073709552000]"
+        );
+    }
+
+    #[test]
+    fn test_escape_control_characters() {
+        assert_eq!(
+            jcs(r#""\u0000\u0008\u0009\u001f\u007f\u2028/""#),
+            "\"\\u0000\\b\\t\\u001f\u{7f}\u{2028}/\""
+        );
+    }
+
+    #[cfg(feature = "sha256")]
+    mod circuit {
+        use super::*;
+        use crate::{
+            BigUintConfig, BigUintInstructions, RSAConfig, RSAInstructions, RSAPubE, RSAPublicKey,
+            RSASignature, RSASignatureVerifier,
+        };
+        use halo2_base::halo2_proofs::{
+            circuit::{Layouter, SimpleFloorPlanner, Value},
+            dev::MockProver,
+            halo2curves::bn256::Fr,
+            plonk::{Circuit, ConstraintSystem, Error},
+        };
+        use halo2_base::{
+            gates::{range::RangeConfig, range::RangeStrategy::Vertical, GateInstructions},
+            utils::PrimeField,
+            SKIP_FIRST_PASS,
+        };
+        use halo2_dynamic_sha256::Sha256DynamicConfig;
+        use num_bigint::BigUint;
+        use rand::thread_rng;
+        use rsa::{
+            pkcs1v15::SigningKey, signature::SignatureEncoding, signature::Signer,
+            traits::PublicKeyParts, RsaPrivateKey,
+        };
+        use std::marker::PhantomData;
+
+        /// A verifiable credential in the example of the W3C VC Data Model, written with the members in another order and with whitespace.
+        const CREDENTIAL: &str = r#"{
+            "type": ["VerifiableCredential", "AlumniCredential"],
+            "@context": [
+                "https://www.w3.org/2018/credentials/v1",
+                "https://www.w3.org/2018/credentials/examples/v1"
+            ],
+            "issuanceDate": "2010-01-01T19:23:24Z",
+            "id": "http://example.edu/credentials/1872",
+            "credentialSubject": {
+                "alumniOf": {
+                    "name": "Example University",
+                    "id": "did:example:c276e12ec21ebfeb1f712ebc6f1"
+                },
+                "id": "did:example:ebfeb1f712ebc6f1c276e12ec21"
+            },
+            "issuer": "https://example.edu/issuers/565049"
+        }"#;
+
+        /// The bytes of [`CREDENTIAL`] signed by the issuer.
+        const CANONICAL_CREDENTIAL: &str = r#"{"@context":["https://www.w3.org/2018/credentials/v1","https://www.w3.org/2018/credentials/examples/v1"],"credentialSubject":{"alumniOf":{"id":"did:example:c276e12ec21ebfeb1f712ebc6f1","name":"Example University"},"id":"did:example:ebfeb1f712ebc6f1c276e12ec21"},"id":"http://example.edu/credentials/1872","issuanceDate":"2010-01-01T19:23:24Z","issuer":"https://example.edu/issuers/565049","type":["VerifiableCredential","AlumniCredential"]}"#;
+
+        #[derive(Debug, Clone)]
+        struct TestJcsConfig<F: PrimeField> {
+            rsa_config: RSAConfig<F>,
+            sha256_config: Sha256DynamicConfig<F>,
+        }
+
+        struct TestJcsCircuit<F: PrimeField> {
+            n: BigUint,
+            signature: BigUint,
+            msg: Vec<u8>,
+            _f: PhantomData<F>,
+        }
+
+        impl<F: PrimeField> TestJcsCircuit<F> {
+            const BITS_LEN: usize = 1024;
+            const MSG_LEN: usize = 1024;
+            const K: usize = 15;
+        }
+
+        impl<F: PrimeField> Circuit<F> for TestJcsCircuit<F> {
+            type Config = TestJcsConfig<F>;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                unimplemented!();
+            }
+
+            fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+                let range_config =
+                    RangeConfig::configure(meta, Vertical, &[80], &[16], 1, 12, 0, Self::K);
+                let biguint_config = BigUintConfig::construct(range_config.clone(), 64);
+                let rsa_config = RSAConfig::construct(biguint_config, Self::BITS_LEN, 5);
+                let sha256_config = Sha256DynamicConfig::configure(
+                    meta,
+                    vec![Self::MSG_LEN],
+                    range_config,
+                    8,
+                    8,
+                    true,
+                );
+                Self::Config {
+                    rsa_config,
+                    sha256_config,
+                }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<F>,
+            ) -> Result<(), Error> {
+                let biguint_config = config.rsa_config.biguint_config();
+                config.sha256_config.load(&mut layouter)?;
+                biguint_config.range().load_lookup_table(&mut layouter)?;
+                let mut first_pass = SKIP_FIRST_PASS;
+                layouter.assign_region(
+                    || "rsa signature over a canonical JSON",
+                    |region| {
+                        if first_pass {
+                            first_pass = false;
+                            return Ok(());
+                        }
+
+                        let mut aux = biguint_config.new_context(region);
+                        let ctx = &mut aux;
+                        let signature = config.rsa_config.assign_signature(
+                            ctx,
+                            RSASignature::new(Value::known(self.signature.clone())),
+                        )?;
+                        let e_fix = RSAPubE::Fix(BigUint::from(65537u32));
+                        let public_key = config.rsa_config.assign_public_key(
+                            ctx,
+                            RSAPublicKey::new(Value::known(self.n.clone()), e_fix),
+                        )?;
+                        let mut verifier = RSASignatureVerifier::new(
+                            config.rsa_config.clone(),
+                            config.sha256_config.clone(),
+                        );
+                        let (is_valid, _) = verifier.verify_pkcs1v15_signature(
+                            ctx,
+                            &public_key,
+                            &self.msg,
+                            &signature,
+                        )?;
+                        biguint_config
+                            .gate()
+                            .assert_is_const(ctx, &is_valid, F::one());
+                        biguint_config.range().finalize(ctx);
+                        Ok(())
+                    },
+                )?;
+                Ok(())
+            }
+        }
+
+        #[test]
+        fn test_circuit_accepts_jcs_signed_credential() {
+            let private_key =
+                RsaPrivateKey::new(&mut thread_rng(), 1024).expect("failed to generate a key");
+            let n = BigUint::from_bytes_be(&private_key.n().to_bytes_be());
+            // The issuer signs the canonical bytes.
+            let signing_key = SigningKey::<rsa::sha2::Sha256>::new(private_key);
+            let signature = signing_key.sign(CANONICAL_CREDENTIAL.as_bytes()).to_vec();
+            let signature = BigUint::from_bytes_be(&signature);
+
+            // The holder canonicalizes the credential as received.
+            let msg = prepare_jcs(&serde_json::from_str(CREDENTIAL).unwrap());
+            assert_eq!(msg, CANONICAL_CREDENTIAL.as_bytes());
+            let k = TestJcsCircuit::<Fr>::K as u32;
+            let circuit = TestJcsCircuit::<Fr> {
+                n: n.clone(),
+                signature: signature.clone(),
+                msg,
+                _f: PhantomData,
+            };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+
+            // The bytes as received are not what was signed.
+            let circuit = TestJcsCircuit::<Fr> {
+                n,
+                signature,
+                msg: CREDENTIAL.as_bytes().to_vec(),
+                _f: PhantomData,
+            };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{RSAPubE, RSAPublicKey};
+use base64::engine::{general_purpose::URL_SAFE_NO_PAD, Engine};
+use halo2_base::halo2_proofs::circuit::Value;
+use halo2_base::utils::PrimeField;
+use num_bigint::BigUint;
+use serde::Deserialize;
+use std::fmt;
+
+/// An error returned by [`parse_jwk`] and [`RSAPublicKey::from_jwk`].
+#[derive(Debug)]
+pub enum JwkError {
+    /// The JWK is not a JSON object with string members.
+    InvalidJson(serde_json::Error),
+    /// The `kty` member is not `"RSA"`.
+    UnsupportedKeyType(String),
+    /// The member `n` or `e` is missing.
+    MissingField(&'static str),
+    /// The member `n` or `e` is not base64url.
+    InvalidBase64(&'static str, base64::DecodeError),
+    /// The exponent `e` is even.
+    EvenExponent,
+    /// The modulus `n` is longer than the circuit supports.
+    ModulusTooLarge {
+        /// The bit length of `n`.
+        bits: u64,
+        /// The maximum bit length, i.e., the number of limbs times the limb width.
+        max_bits: usize,
+    },
+}
+
+impl fmt::Display for JwkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JwkError::InvalidJson(e) => write!(f, "invalid JWK: {}", e),
+            JwkError::UnsupportedKeyType(kty) => {
+                write!(
+                    f,
+                    "the JWK has kty {:?}, but only \"RSA\" is supported",
+                    kty
+                )
+            }
+            JwkError::MissingField(field) => write!(f, "the JWK has no member {:?}", field),
+            JwkError::InvalidBase64(field, e) => {
+                write!(
+                    f,
+                    "the member {:?} of the JWK is not base64url: {}",
+                    field, e
+                )
+            }
+            JwkError::EvenExponent => write!(f, "the exponent e of the JWK must be odd"),
+            JwkError::ModulusTooLarge { bits, max_bits } => write!(
+                f,
+                "the modulus n of the JWK has {} bits, but at most {} bits are supported",
+                bits, max_bits
+            ),
+        }
+    }
+}
+
+impl std::error::Error for JwkError {}
+
+/// The members of a JWK read by [`parse_jwk`]. The other members, e.g., `kid` and `alg`, are ignored.
+#[derive(Deserialize)]
+struct RsaJwk {
+    kty: String,
+    n: Option<String>,
+    e: Option<String>,
+}
+
+/// Parses an RSA public key in the JSON Web Key format (RFC 7517), e.g., an entry of the `keys` array of an OIDC provider's JWKS.
+///
+/// The members `n` and `e` are the base64url encodings of the big-endian bytes of the modulus and the exponent.
+/// Both the unpadded encoding required by RFC 7518 and the one padded with `=` are accepted.
+///
+/// # Return values
+/// Returns the modulus `n` and the exponent `e`, or an error if the JWK is not an RSA key or `e` is even.
+pub fn parse_jwk(jwk: &str) -> Result<(BigUint, BigUint), JwkError> {
+    let jwk: RsaJwk = serde_json::from_str(jwk).map_err(JwkError::InvalidJson)?;
+    if jwk.kty != "RSA" {
+        return Err(JwkError::UnsupportedKeyType(jwk.kty));
+    }
+    let decode = |field: &'static str, value: Option<String>| {
+        let value = value.ok_or(JwkError::MissingField(field))?;
+        URL_SAFE_NO_PAD
+            .decode(value.trim_end_matches('='))
+            .map(|bytes| BigUint::from_bytes_be(&bytes))
+            .map_err(|e| JwkError::InvalidBase64(field, e))
+    };
+    let n = decode("n", jwk.n)?;
+    let e = decode("e", jwk.e)?;
+    if !e.bit(0) {
+        return Err(JwkError::EvenExponent);
+    }
+    Ok((n, e))
+}
+
+impl<F: PrimeField> RSAPublicKey<F> {
+    /// Creates new [`RSAPublicKey`] with the fixed exponent from a JWK. See [`parse_jwk`] for the accepted JWK.
+    ///
+    /// # Arguments
+    /// * jwk - a JSON string of the JWK.
+    /// * num_limbs - the number of limbs of `n` in the circuit.
+    /// * limb_width - the bit width of each limb.
+    ///
+    /// # Return values
+    /// Returns new [`RSAPublicKey`], or an error if the JWK is invalid or `n` does not fit in `num_limbs` limbs.
+    pub fn from_jwk(jwk: &str, num_limbs: usize, limb_width: usize) -> Result<Self, JwkError> {
+        let (n, e) = parse_jwk(jwk)?;
+        let max_bits = num_limbs * limb_width;
+        if n.bits() > max_bits as u64 {
+            return Err(JwkError::ModulusTooLarge {
+                bits: n.bits(),
+                max_bits,
+            });
+        }
+        Ok(Self::new(Value::known(n), RSAPubE::Fix(e)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+    use rand::thread_rng;
+    use rsa::{traits::PublicKeyParts, RsaPrivateKey};
+
+    /// The RSA key of the example JWK Set in RFC 7517, Appendix A.1, in the same shape as the entries of Google's JWKS.
+    const RFC7517_JWK: &str = r#"{
+        "kty": "RSA",
+        "n": "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw",
+        "e": "AQAB",
+        "alg": "RS256",
+        "use": "sig",
+        "kid": "2011-04-29"
+    }"#;
+
+    #[test]
+    fn test_parse_rfc7517_jwk() {
+        let (n, e) = parse_jwk(RFC7517_JWK).unwrap();
+        assert_eq!(n.bits(), 2048);
+        assert_eq!(&n.to_bytes_be()[..3], &[0xd2, 0xfc, 0x7b]);
+        assert_eq!(e, BigUint::from(65537u32));
+
+        let public_key = RSAPublicKey::<Fr>::from_jwk(RFC7517_JWK, 32, 64).unwrap();
+        public_key.n.map(|key_n| assert_eq!(key_n, n));
+        assert!(matches!(public_key.e, RSAPubE::Fix(key_e) if key_e == e));
+        assert!(matches!(
+            RSAPublicKey::<Fr>::from_jwk(RFC7517_JWK, 16, 64),
+            Err(JwkError::ModulusTooLarge {
+                bits: 2048,
+                max_bits: 1024
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_generated_jwk() {
+        let private_key =
+            RsaPrivateKey::new(&mut thread_rng(), 2048).expect("failed to generate a key");
+        let n = private_key.n().to_bytes_be();
+        let e = private_key.e().to_bytes_be();
+        let unpadded = format!(
+            r#"{{"kty":"RSA","n":"{}","e":"{}"}}"#,
+            URL_SAFE_NO_PAD.encode(&n),
+            URL_SAFE_NO_PAD.encode(&e)
+        );
+        let padded = format!(
+            r#"{{"kty":"RSA","n":"{}","e":"{}"}}"#,
+            base64::engine::general_purpose::URL_SAFE.encode(&n),
+            base64::engine::general_purpose::URL_SAFE.encode(&e)
+        );
+        for jwk in [unpadded, padded] {
+            assert_eq!(
+                parse_jwk(&jwk).unwrap(),
+                (BigUint::from_bytes_be(&n), BigUint::from_bytes_be(&e))
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid_jwk() {
+        assert!(matches!(
+            parse_jwk("not json"),
+            Err(JwkError::InvalidJson(_))
+        ));
+        assert!(matches!(
+            parse_jwk(r#"{"kty":"EC","crv":"P-256"}"#),
+            Err(JwkError::UnsupportedKeyType(_))
+        ));
+        assert!(matches!(
+            parse_jwk(r#"{"kty":"RSA","e":"AQAB"}"#),
+            Err(JwkError::MissingField("n"))
+        ));
+        assert!(matches!(
+            parse_jwk(r#"{"kty":"RSA","n":"AQAB","e":"A+/B"}"#),
+            Err(JwkError::InvalidBase64("e", _))
+        ));
+        assert!(matches!(
+            parse_jwk(r#"{"kty":"RSA","n":"AQAB","e":"AQAA"}"#),
+            Err(JwkError::EvenExponent)
+        ));
+    }
+}
@@ -0,0 +1,470 @@
+use crate::cost::constraint_system;
+use crate::keys::{read_short_str, write_short_str};
+use crate::{encode_hex, read_pk_trusted, write_pk_versioned, KEY_CRATE_VERSION};
+use halo2_base::halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{keygen_pk, keygen_vk, Circuit, Error, ProvingKey, VerifyingKey},
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The magic bytes at the head of the key files written by [`KeyStore`].
+pub const KEY_STORE_MAGIC: [u8; 4] = *b"HRKC";
+
+/// An error of [`KeyStore`].
+#[derive(Debug)]
+pub enum KeyStoreError {
+    /// The directory or a key file could not be read or written.
+    Io(io::Error),
+    /// The keys could not be generated, e.g., because the params are too small for the circuit.
+    Keygen(Error),
+    /// The key file was not written by [`KeyStore`], or was written by another version of this crate or for another circuit, params, or degree.
+    Mismatch { path: PathBuf, reason: String },
+    /// The key file is truncated or does not match its checksum, e.g., because writing it was interrupted.
+    Corrupted { path: PathBuf },
+}
+
+impl fmt::Display for KeyStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to access the key store: {}", e),
+            Self::Keygen(e) => write!(f, "failed to generate the keys: {:?}", e),
+            Self::Mismatch { path, reason } => write!(
+                f,
+                "the cached keys {} do not match: {}; delete the file to regenerate the keys",
+                path.display(),
+                reason
+            ),
+            Self::Corrupted { path } => write!(
+                f,
+                "the cached keys {} are corrupted; delete the file to regenerate the keys",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for KeyStoreError {}
+
+impl From<io::Error> for KeyStoreError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Returns the hash identifying the constraint system of the circuit `C`, i.e., the SHA256 digest of its type name and its gates, lookups, and permutation columns.
+///
+/// The circuits of the same shape share the hash, whereas changing a column or a gate, e.g., by another layout of a pkcs1v15 circuit, changes it.
+pub fn circuit_shape_hash<C: Circuit<Fr>>() -> [u8; 32] {
+    let cs = constraint_system::<C>();
+    let mut hasher = Sha256::new();
+    hasher.update(std::any::type_name::<C>().as_bytes());
+    hasher.update(format!("{:?}", cs.pinned()).as_bytes());
+    hasher.finalize().into()
+}
+
+/// Returns the SHA256 digest of [`VerifyingKey::transcript_repr`] of `vk`, which commits to the fixed columns and the permutation of the circuit.
+///
+/// Unlike [`circuit_shape_hash`], it tells apart the circuits of the same shape with different constants, e.g., the pkcs1v15 circuits of different fixed exponents.
+pub fn circuit_fixed_hash(vk: &VerifyingKey<G1Affine>) -> [u8; 32] {
+    Sha256::digest(format!("{:?}", vk.transcript_repr()).as_bytes()).into()
+}
+
+/// Returns the SHA256 digest of `s_g2` of `params`, which tells apart the params of different ceremonies or setups of the same degree.
+fn params_fingerprint(params: &ParamsKZG<Bn256>) -> [u8; 32] {
+    Sha256::digest(format!("{:?}", params.s_g2()).as_bytes()).into()
+}
+
+/// A directory caching the proving keys of circuits, so that they are generated once rather than in every session.
+///
+/// Each key is written to a file named after [`circuit_shape_hash`], [`circuit_fixed_hash`], the params, and the degree, with a header recording [`KEY_STORE_MAGIC`], [`KEY_CRATE_VERSION`], both hashes, the fingerprint of the params, the degree, and the SHA256 checksum of the key.
+/// [`KeyStore::get_or_create`] refuses to load a file whose header does not match with [`KeyStoreError::Mismatch`] or [`KeyStoreError::Corrupted`], which tell the user to delete the file to regenerate the keys.
+/// The keys are read without checking that their points are on the curve as [`read_pk_trusted`] does, since the checksum already detects a corrupted file, so the directory must not be writable by others.
+#[derive(Debug)]
+pub struct KeyStore {
+    dir: PathBuf,
+    num_keygens: AtomicUsize,
+}
+
+impl KeyStore {
+    /// Opens the key store in `dir`, which is created if it does not exist.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, KeyStoreError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            num_keygens: AtomicUsize::new(0),
+        })
+    }
+
+    /// Getter for the directory of the store.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the number of the keys generated by this store so far, i.e., the calls to [`KeyStore::get_or_create`] that missed the cache.
+    pub fn num_keygens(&self) -> usize {
+        self.num_keygens.load(Ordering::Relaxed)
+    }
+
+    /// Returns the path of the file caching the keys of the circuit `C` whose verifying key is `vk` for `params`.
+    pub fn path_of<C: Circuit<Fr>>(
+        &self,
+        vk: &VerifyingKey<G1Affine>,
+        params: &ParamsKZG<Bn256>,
+    ) -> PathBuf {
+        self.dir.join(format!(
+            "{}-{}-{}-k{}.pk",
+            &encode_hex(&circuit_shape_hash::<C>())[..16],
+            &encode_hex(&circuit_fixed_hash(vk))[..16],
+            &encode_hex(&params_fingerprint(params))[..16],
+            params.k()
+        ))
+    }
+
+    /// Returns the proving key and the verifying key of `circuit` for `params`, which are read from the store if cached, or generated and written to the store otherwise.
+    ///
+    /// The verifying key is generated on every call to find the file by [`circuit_fixed_hash`], which costs a fraction of generating the proving key.
+    ///
+    /// # Arguments
+    /// * circuit - the circuit without witnesses, e.g., [`Circuit::without_witnesses`] of the circuit to be proven.
+    /// * params - the params of the keys.
+    ///
+    /// # Return values
+    /// Returns the keys, or [`KeyStoreError::Mismatch`] or [`KeyStoreError::Corrupted`] if the cached file cannot be used, [`KeyStoreError::Keygen`] if the keys cannot be generated, or [`KeyStoreError::Io`] if the file cannot be read or written.
+    pub fn get_or_create<C: Circuit<Fr>>(
+        &self,
+        circuit: &C,
+        params: &ParamsKZG<Bn256>,
+    ) -> Result<(ProvingKey<G1Affine>, VerifyingKey<G1Affine>), KeyStoreError> {
+        let vk = keygen_vk(params, circuit).map_err(KeyStoreError::Keygen)?;
+        let path = self.path_of::<C>(&vk, params);
+        match fs::read(&path) {
+            Ok(bytes) => {
+                let pk = read_cached_pk::<C>(&path, &bytes, &vk, params)?;
+                let vk = pk.get_vk().clone();
+                return Ok((pk, vk));
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        let pk = keygen_pk(params, vk.clone(), circuit).map_err(KeyStoreError::Keygen)?;
+        self.num_keygens.fetch_add(1, Ordering::Relaxed);
+        let mut key = vec![];
+        write_pk_versioned::<C, _>(&pk, &mut key)?;
+        let mut file = vec![];
+        file.extend_from_slice(&KEY_STORE_MAGIC);
+        write_short_str(&mut file, KEY_CRATE_VERSION)?;
+        file.extend_from_slice(&circuit_shape_hash::<C>());
+        file.extend_from_slice(&circuit_fixed_hash(&vk));
+        file.extend_from_slice(&params_fingerprint(params));
+        file.extend_from_slice(&params.k().to_le_bytes());
+        file.extend_from_slice(&(key.len() as u64).to_le_bytes());
+        file.extend_from_slice(&Sha256::digest(&key));
+        file.extend_from_slice(&key);
+        // The file is renamed into place, so an interrupted write never leaves a partial key under the path.
+        let tmp_path = path.with_extension(format!("pk.{}.tmp", std::process::id()));
+        fs::write(&tmp_path, &file)?;
+        fs::rename(&tmp_path, &path)?;
+        Ok((pk, vk))
+    }
+}
+
+/// Reads the proving key cached at `path` after checking its header against the circuit `C`, its verifying key `vk`, and `params`.
+fn read_cached_pk<C: Circuit<Fr>>(
+    path: &Path,
+    bytes: &[u8],
+    vk: &VerifyingKey<G1Affine>,
+    params: &ParamsKZG<Bn256>,
+) -> Result<ProvingKey<G1Affine>, KeyStoreError> {
+    let mismatch = |reason: String| KeyStoreError::Mismatch {
+        path: path.to_path_buf(),
+        reason,
+    };
+    let corrupted = || KeyStoreError::Corrupted {
+        path: path.to_path_buf(),
+    };
+    let mut reader = bytes;
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic).map_err(|_| corrupted())?;
+    if magic != KEY_STORE_MAGIC {
+        return Err(mismatch("not a key file of the key store".to_string()));
+    }
+    let version = read_short_str(&mut reader).map_err(|_| corrupted())?;
+    if version != KEY_CRATE_VERSION.as_bytes() {
+        return Err(mismatch(format!(
+            "written by version {}, expected {}",
+            String::from_utf8_lossy(&version),
+            KEY_CRATE_VERSION
+        )));
+    }
+    let mut shape_hash = [0u8; 32];
+    reader
+        .read_exact(&mut shape_hash)
+        .map_err(|_| corrupted())?;
+    if shape_hash != circuit_shape_hash::<C>() {
+        return Err(mismatch(format!(
+            "generated for another shape than the circuit {}",
+            std::any::type_name::<C>()
+        )));
+    }
+    let mut fixed_hash = [0u8; 32];
+    reader
+        .read_exact(&mut fixed_hash)
+        .map_err(|_| corrupted())?;
+    if fixed_hash != circuit_fixed_hash(vk) {
+        return Err(mismatch(
+            "generated for other fixed columns, e.g., another fixed exponent".to_string(),
+        ));
+    }
+    let mut params_hash = [0u8; 32];
+    reader
+        .read_exact(&mut params_hash)
+        .map_err(|_| corrupted())?;
+    if params_hash != params_fingerprint(params) {
+        return Err(mismatch("generated for other params".to_string()));
+    }
+    let mut k = [0u8; 4];
+    reader.read_exact(&mut k).map_err(|_| corrupted())?;
+    let k = u32::from_le_bytes(k);
+    if k != params.k() {
+        return Err(mismatch(format!(
+            "generated for k = {}, expected k = {}",
+            k,
+            params.k()
+        )));
+    }
+    let mut key_len = [0u8; 8];
+    reader.read_exact(&mut key_len).map_err(|_| corrupted())?;
+    let mut checksum = [0u8; 32];
+    reader.read_exact(&mut checksum).map_err(|_| corrupted())?;
+    if u64::from_le_bytes(key_len) != reader.len() as u64
+        || checksum != <[u8; 32]>::from(Sha256::digest(reader))
+    {
+        return Err(corrupted());
+    }
+    read_pk_trusted::<C>(reader).map_err(|_| corrupted())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{BigUintConfig, BigUintInstructions};
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::ConstraintSystem,
+    };
+    use halo2_base::{
+        gates::range::{RangeConfig, RangeStrategy::Vertical},
+        gates::{GateInstructions, RangeInstructions},
+        utils::PrimeField,
+        SKIP_FIRST_PASS,
+    };
+    use num_bigint::BigUint;
+    use rand::rngs::OsRng;
+
+    const K: u32 = 10;
+
+    /// A circuit assigning `a` and the constant `c`, which is kept by [`Circuit::without_witnesses`] as the fixed exponent of the pkcs1v15 circuits.
+    #[derive(Default)]
+    struct TestAssignCircuit<const BITS: usize> {
+        a: BigUint,
+        c: u64,
+    }
+
+    impl<F: PrimeField, const BITS: usize> Circuit<F> for TestAssignCircuit<BITS> {
+        type Config = BigUintConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                a: BigUint::default(),
+                c: self.c,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config = RangeConfig::configure(
+                meta,
+                Vertical,
+                &[1],
+                &[1],
+                1,
+                K as usize - 1,
+                0,
+                K as usize,
+            );
+            BigUintConfig::construct(range_config, 64)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "assign an integer",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    config.assign_integer(ctx, Value::known(self.a.clone()), BITS)?;
+                    config.gate().load_constant(ctx, F::from(self.c));
+                    config.range().finalize(ctx);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn temp_store(name: &str) -> KeyStore {
+        let dir = std::env::temp_dir().join(format!(
+            "halo2-rsa-key-store-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        KeyStore::open(dir).unwrap()
+    }
+
+    #[test]
+    fn test_key_store_hit_skips_keygen() {
+        let store = temp_store("hit");
+        let params = ParamsKZG::<Bn256>::setup(K, OsRng);
+        let circuit = TestAssignCircuit::<128>::default();
+        let (pk, vk) = store.get_or_create(&circuit, &params).unwrap();
+        assert_eq!(store.num_keygens(), 1);
+        assert!(store
+            .path_of::<TestAssignCircuit<128>>(&vk, &params)
+            .exists());
+
+        // A new session reads the same keys without generating them.
+        let store = KeyStore::open(store.dir()).unwrap();
+        let (cached_pk, cached_vk) = store.get_or_create(&circuit, &params).unwrap();
+        assert_eq!(store.num_keygens(), 0);
+        assert_eq!(cached_vk.transcript_repr(), vk.transcript_repr());
+        assert_eq!(
+            cached_pk.get_vk().transcript_repr(),
+            pk.get_vk().transcript_repr()
+        );
+
+        // Another circuit shape and other params are cached in their own files.
+        store
+            .get_or_create(&TestAssignCircuit::<256>::default(), &params)
+            .unwrap();
+        let other_params = ParamsKZG::<Bn256>::setup(K, OsRng);
+        store.get_or_create(&circuit, &other_params).unwrap();
+        assert_eq!(store.num_keygens(), 2);
+        assert_ne!(
+            circuit_shape_hash::<TestAssignCircuit<128>>(),
+            circuit_shape_hash::<TestAssignCircuit<256>>()
+        );
+        fs::remove_dir_all(store.dir()).unwrap();
+    }
+
+    #[test]
+    fn test_key_store_detects_corrupted_file() {
+        let store = temp_store("corrupted");
+        let params = ParamsKZG::<Bn256>::setup(K, OsRng);
+        let circuit = TestAssignCircuit::<128>::default();
+        let (_, vk) = store.get_or_create(&circuit, &params).unwrap();
+        let path = store.path_of::<TestAssignCircuit<128>>(&vk, &params);
+        let bytes = fs::read(&path).unwrap();
+
+        // A flipped bit of the key.
+        let mut corrupted = bytes.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 0x01;
+        fs::write(&path, &corrupted).unwrap();
+        assert!(matches!(
+            store.get_or_create(&circuit, &params),
+            Err(KeyStoreError::Corrupted { .. })
+        ));
+        // A truncated file.
+        fs::write(&path, &bytes[..bytes.len() / 2]).unwrap();
+        assert!(matches!(
+            store.get_or_create(&circuit, &params),
+            Err(KeyStoreError::Corrupted { .. })
+        ));
+        // A file written by another version of this crate.
+        let mut other_version = bytes.clone();
+        other_version[KEY_STORE_MAGIC.len() + 1] ^= 0xff;
+        fs::write(&path, &other_version).unwrap();
+        let err = store.get_or_create(&circuit, &params).unwrap_err();
+        assert!(matches!(err, KeyStoreError::Mismatch { .. }));
+        assert!(err
+            .to_string()
+            .contains("delete the file to regenerate the keys"));
+        // The keys of another circuit copied to the path.
+        let (_, other_vk) = store
+            .get_or_create(&TestAssignCircuit::<256>::default(), &params)
+            .unwrap();
+        fs::copy(
+            store.path_of::<TestAssignCircuit<256>>(&other_vk, &params),
+            &path,
+        )
+        .unwrap();
+        assert!(matches!(
+            store.get_or_create(&circuit, &params),
+            Err(KeyStoreError::Mismatch { .. })
+        ));
+        assert_eq!(store.num_keygens(), 2);
+
+        // The keys are generated again once the file is deleted.
+        fs::remove_file(&path).unwrap();
+        store.get_or_create(&circuit, &params).unwrap();
+        assert_eq!(store.num_keygens(), 3);
+        fs::remove_dir_all(store.dir()).unwrap();
+    }
+
+    #[test]
+    fn test_key_store_separates_fixed_constants() {
+        let store = temp_store("fixed");
+        let params = ParamsKZG::<Bn256>::setup(K, OsRng);
+        // The circuits share the shape and differ only in a constant, as the pkcs1v15 circuits of different fixed exponents do.
+        let circuit = TestAssignCircuit::<128> {
+            a: BigUint::default(),
+            c: 3,
+        };
+        let other_circuit = TestAssignCircuit::<128> {
+            a: BigUint::default(),
+            c: 65537,
+        };
+        let (_, vk) = store.get_or_create(&circuit, &params).unwrap();
+        let (_, other_vk) = store.get_or_create(&other_circuit, &params).unwrap();
+        assert_eq!(store.num_keygens(), 2);
+        assert_ne!(vk.transcript_repr(), other_vk.transcript_repr());
+        let path = store.path_of::<TestAssignCircuit<128>>(&vk, &params);
+        let other_path = store.path_of::<TestAssignCircuit<128>>(&other_vk, &params);
+        assert_ne!(path, other_path);
+
+        // Each circuit reads back its own keys.
+        let store = KeyStore::open(store.dir()).unwrap();
+        let (cached_pk, _) = store.get_or_create(&other_circuit, &params).unwrap();
+        assert_eq!(store.num_keygens(), 0);
+        assert_eq!(
+            cached_pk.get_vk().transcript_repr(),
+            other_vk.transcript_repr()
+        );
+
+        // The keys of one constant copied to the path of the other are refused.
+        fs::copy(&other_path, &path).unwrap();
+        assert!(matches!(
+            store.get_or_create(&circuit, &params),
+            Err(KeyStoreError::Mismatch { .. })
+        ));
+        fs::remove_dir_all(store.dir()).unwrap();
+    }
+}
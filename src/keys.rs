@@ -0,0 +1,773 @@
+use crate::compression::gunzip_if_compressed;
+use crate::MAX_PARAMS_K;
+use halo2_base::halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{keygen_pk, keygen_vk, Circuit, ConstraintSystem, ProvingKey, VerifyingKey},
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+    SerdeFormat,
+};
+use sha2::{Digest, Sha256};
+use std::io::{self, Read, Write};
+use std::panic::{self, AssertUnwindSafe};
+
+/// The magic bytes at the head of the keys written by [`write_pk_versioned`] and [`write_vk_versioned`].
+pub const KEY_MAGIC: [u8; 4] = *b"HRSK";
+/// The version of this crate recorded in the header of the serialized keys.
+pub const KEY_CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The serialization format of the points of a versioned key, which the key must be read in.
+///
+/// The header written by [`write_pk_versioned`] and [`write_vk_versioned`] does not record the format, so a key converted by [`convert_key_format`] must be read with the format it was converted to.
+#[cfg_attr(
+    all(target_arch = "wasm32", feature = "sha256"),
+    wasm_bindgen::prelude::wasm_bindgen
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum KeyFormat {
+    /// [`SerdeFormat::RawBytes`], the uncompressed points checked to be on the curve, which the keys have been written in so far.
+    #[default]
+    RawBytes,
+    /// The same bytes as [`KeyFormat::RawBytes`] read without checking the points. See [`read_pk_trusted`] for when it is safe.
+    RawBytesUnchecked,
+    /// [`SerdeFormat::Processed`], the compressed points, which halve the size of the points but take a square root per point to read.
+    ///
+    /// It thus pays off where fetching the key takes longer than deserializing it, unlike [`KeyFormat::RawBytesUnchecked`] which is the fastest to read.
+    Processed,
+}
+
+impl From<KeyFormat> for SerdeFormat {
+    fn from(format: KeyFormat) -> Self {
+        match format {
+            KeyFormat::RawBytes => SerdeFormat::RawBytes,
+            KeyFormat::RawBytesUnchecked => SerdeFormat::RawBytesUnchecked,
+            KeyFormat::Processed => SerdeFormat::Processed,
+        }
+    }
+}
+
+/// Writes a proving key of the circuit `C` prefixed with [`KEY_MAGIC`], [`KEY_CRATE_VERSION`], and the type name of `C`.
+///
+/// The key itself is serialized in [`SerdeFormat::RawBytes`], whose layout depends on the halo2 version pinned by this crate.
+pub fn write_pk_versioned<C: Circuit<Fr>, W: Write>(
+    pk: &ProvingKey<G1Affine>,
+    writer: &mut W,
+) -> io::Result<()> {
+    write_header::<C, _>(writer)?;
+    pk.write(writer, SerdeFormat::RawBytes)
+}
+
+/// Writes a verifying key of the circuit `C` prefixed with [`KEY_MAGIC`], [`KEY_CRATE_VERSION`], and the type name of `C`.
+pub fn write_vk_versioned<C: Circuit<Fr>, W: Write>(
+    vk: &VerifyingKey<G1Affine>,
+    writer: &mut W,
+) -> io::Result<()> {
+    write_header::<C, _>(writer)?;
+    vk.write(writer, SerdeFormat::RawBytes)
+}
+
+/// Generates the proving key and the verifying key of `circuit` for `params` and writes them by [`write_pk_versioned`] and [`write_vk_versioned`].
+///
+/// The key generation is deterministic, so the same params and circuit always yield byte-identical keys, whether in the wasm build or natively.
+///
+/// # Return values
+/// Returns the bytes of the proving key and the verifying key, or an error if the keys cannot be generated, e.g., because `params` is too small for `circuit`.
+pub fn keygen_versioned<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    circuit: &C,
+) -> io::Result<(Vec<u8>, Vec<u8>)> {
+    let vk = keygen_vk(params, circuit).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("failed to generate a verifying key: {:?}", e),
+        )
+    })?;
+    let pk = keygen_pk(params, vk.clone(), circuit).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("failed to generate a proving key: {:?}", e),
+        )
+    })?;
+    let mut pk_bytes = vec![];
+    write_pk_versioned::<C, _>(&pk, &mut pk_bytes)?;
+    let mut vk_bytes = vec![];
+    write_vk_versioned::<C, _>(&vk, &mut vk_bytes)?;
+    Ok((pk_bytes, vk_bytes))
+}
+
+/// Reads a proving key and a verifying key written by [`write_pk_versioned`] and [`write_vk_versioned`].
+///
+/// # Arguments
+/// * pk - bytes of the versioned proving key, optionally compressed by [`crate::compress_key`].
+/// * vk - bytes of the versioned verifying key, optionally compressed by [`crate::compress_key`].
+///
+/// # Return values
+/// Returns the keys for the circuit `C`.
+/// Returns an [`io::ErrorKind::InvalidData`] error if either key lacks the header, was serialized by another version of this crate, or was generated for another circuit, instead of failing somewhere in the middle of the raw bytes.
+pub fn read_keys_checked<C: Circuit<Fr>>(
+    pk: &[u8],
+    vk: &[u8],
+) -> io::Result<(ProvingKey<G1Affine>, VerifyingKey<G1Affine>)> {
+    Ok((read_pk_checked::<C>(pk)?, read_vk_checked::<C>(vk)?))
+}
+
+/// Reads a proving key written by [`write_pk_versioned`]. See [`read_keys_checked`] for the errors.
+pub fn read_pk_checked<C: Circuit<Fr>>(pk: &[u8]) -> io::Result<ProvingKey<G1Affine>> {
+    read_pk_with_format::<C>(pk, KeyFormat::RawBytes)
+}
+
+/// Reads a proving key written by [`write_pk_versioned`] in [`SerdeFormat::RawBytesUnchecked`], which skips checking that the points of the key are on the curve.
+///
+/// This cuts the deserialization time of large keys, but a corrupted or malicious key is no longer rejected and may yield invalid proofs or panics.
+/// Only use it for keys from a trusted source, e.g., those generated by the caller itself. The header is checked in the same way as [`read_pk_checked`].
+pub fn read_pk_trusted<C: Circuit<Fr>>(pk: &[u8]) -> io::Result<ProvingKey<G1Affine>> {
+    read_pk_with_format::<C>(pk, KeyFormat::RawBytesUnchecked)
+}
+
+/// Reads a verifying key written by [`write_vk_versioned`]. See [`read_keys_checked`] for the errors.
+pub fn read_vk_checked<C: Circuit<Fr>>(vk: &[u8]) -> io::Result<VerifyingKey<G1Affine>> {
+    read_vk_with_format::<C>(vk, KeyFormat::RawBytes)
+}
+
+/// Reads a proving key of the circuit `C` in `format`, e.g., one converted by [`convert_key_format`].
+///
+/// [`read_pk_checked`] and [`read_pk_trusted`] are the same as this function with [`KeyFormat::RawBytes`] and [`KeyFormat::RawBytesUnchecked`].
+/// Returns the errors of [`read_keys_checked`], or an error from the middle of the key if it is in another format.
+pub fn read_pk_with_format<C: Circuit<Fr>>(
+    pk: &[u8],
+    format: KeyFormat,
+) -> io::Result<ProvingKey<G1Affine>> {
+    let pk = gunzip_if_compressed(pk)?;
+    let mut reader = &pk[..];
+    read_header::<C, _>(&mut reader)?;
+    check_key_k::<C>(reader)?;
+    catch_read_panic("proving key", || {
+        ProvingKey::read::<_, C>(&mut reader, format.into())
+    })
+}
+
+/// Reads a verifying key of the circuit `C` in `format` in the same way as [`read_pk_with_format`].
+pub fn read_vk_with_format<C: Circuit<Fr>>(
+    vk: &[u8],
+    format: KeyFormat,
+) -> io::Result<VerifyingKey<G1Affine>> {
+    let vk = gunzip_if_compressed(vk)?;
+    let mut reader = &vk[..];
+    read_header::<C, _>(&mut reader)?;
+    check_key_k::<C>(reader)?;
+    catch_read_panic("verifying key", || {
+        VerifyingKey::read::<_, C>(&mut reader, format.into())
+    })
+}
+
+/// Converts a versioned proving key or verifying key of the circuit `C` from the format `from` to `to`, e.g., to migrate the keys written so far to [`KeyFormat::Processed`].
+///
+/// The kind of the key is detected by whether the bytes end right after the verifying key, which a proving key starts with.
+/// [`KeyFormat::RawBytesUnchecked`] is written as the same bytes as [`KeyFormat::RawBytes`], and `key` may be compressed by [`crate::compress_key`] while the converted key is not.
+///
+/// # Return values
+/// Returns the converted key with the same header, or the errors of [`read_pk_with_format`] if `key` cannot be read in `from`.
+pub fn convert_key_format<C: Circuit<Fr>>(
+    key: &[u8],
+    from: KeyFormat,
+    to: KeyFormat,
+) -> io::Result<Vec<u8>> {
+    let key = gunzip_if_compressed(key)?;
+    let mut reader = &key[..];
+    read_header::<C, _>(&mut reader)?;
+    check_key_k::<C>(reader)?;
+    let vk = catch_read_panic("verifying key", || {
+        VerifyingKey::<G1Affine>::read::<_, C>(&mut reader, from.into())
+    })?;
+    let mut converted = vec![];
+    write_header::<C, _>(&mut converted)?;
+    if reader.is_empty() {
+        vk.write(&mut converted, to.into())?;
+    } else {
+        let pk = read_pk_with_format::<C>(&key, from)?;
+        pk.write(&mut converted, to.into())?;
+    }
+    Ok(converted)
+}
+
+/// Extracts the verifying key from a proving key written by [`write_pk_versioned`], so that the holders of only the proving key can verify proofs without generating the keys again.
+///
+/// A proving key in [`SerdeFormat::RawBytes`] starts with its verifying key in the same format, so only that part of `pk` is deserialized.
+///
+/// # Arguments
+/// * pk - bytes of the versioned proving key of the circuit `C`, optionally compressed by [`crate::compress_key`].
+///
+/// # Return values
+/// Returns the verifying key written by [`write_vk_versioned`], which is byte-identical to the one written when the keys were generated.
+/// Returns the errors of [`read_keys_checked`] for `pk`.
+pub fn extract_vk_bytes<C: Circuit<Fr>>(pk: &[u8]) -> io::Result<Vec<u8>> {
+    let pk = gunzip_if_compressed(pk)?;
+    let mut reader = &pk[..];
+    read_header::<C, _>(&mut reader)?;
+    check_key_k::<C>(reader)?;
+    let vk = catch_read_panic("verifying key", || {
+        VerifyingKey::<G1Affine>::read::<_, C>(&mut reader, SerdeFormat::RawBytes)
+    })?;
+    let mut vk_bytes = vec![];
+    write_vk_versioned::<C, _>(&vk, &mut vk_bytes)?;
+    Ok(vk_bytes)
+}
+
+/// Computes the fingerprint of a verifying key, which is the SHA256 digest of its bytes written by [`write_vk_versioned`].
+///
+/// The header of the bytes records the id of the circuit given by [`crate::ProofBundle::circuit_id_of`], so the keys of the circuits of different sizes never share a fingerprint.
+/// The digest is taken over `vk_bytes` as they are, so a key compressed by [`crate::compress_key`] must be restored by [`crate::decompress_key`] first.
+pub fn vk_fingerprint(vk_bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(vk_bytes).into()
+}
+
+/// Computes the fingerprint of `vk` of the circuit `C` given by [`vk_fingerprint`] for its bytes written by [`write_vk_versioned`].
+///
+/// Returns the error of [`write_vk_versioned`], e.g., if the type name of `C` is too long for the header.
+pub fn vk_fingerprint_of<C: Circuit<Fr>>(vk: &VerifyingKey<G1Affine>) -> io::Result<[u8; 32]> {
+    let mut vk_bytes = vec![];
+    write_vk_versioned::<C, _>(vk, &mut vk_bytes)?;
+    Ok(vk_fingerprint(&vk_bytes))
+}
+
+/// Checks that `params` can be used with the keys whose verifying key is `vk`.
+///
+/// # Arguments
+/// * params - params for proving or verification.
+/// * vk - the verifying key, e.g., [`ProvingKey::get_vk`] of the proving key.
+/// * min_k - the minimum degree of the circuit of the keys.
+///
+/// # Return values
+/// Returns an [`io::ErrorKind::InvalidInput`] error if `params` is smaller than `min_k` or its degree differs from the one used to generate the keys.
+pub fn check_params_k(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    min_k: u32,
+) -> io::Result<()> {
+    let params_k = params.k();
+    if params_k < min_k {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "params for k = {} were given, but the circuit requires k >= {}",
+                params_k, min_k
+            ),
+        ));
+    }
+    let key_k = vk.get_domain().k();
+    if params_k != key_k {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "params for k = {} were given, but the keys were generated for k = {}",
+                params_k, key_k
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn write_header<C: Circuit<Fr>, W: Write>(writer: &mut W) -> io::Result<()> {
+    writer.write_all(&KEY_MAGIC)?;
+    write_short_str(writer, KEY_CRATE_VERSION)?;
+    write_short_str(writer, std::any::type_name::<C>())
+}
+
+pub(crate) fn write_short_str<W: Write>(writer: &mut W, s: &str) -> io::Result<()> {
+    let len = u8::try_from(s.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too long key header"))?;
+    writer.write_all(&[len])?;
+    writer.write_all(s.as_bytes())
+}
+
+pub(crate) fn read_short_str<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut len = [0u8; 1];
+    reader.read_exact(&mut len)?;
+    let mut s = vec![0u8; len[0] as usize];
+    reader.read_exact(&mut s)?;
+    Ok(s)
+}
+
+fn read_header<C: Circuit<Fr>, R: Read>(reader: &mut R) -> io::Result<()> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != KEY_MAGIC {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the key is not serialized in the versioned format",
+        ));
+    }
+    let version = read_short_str(reader)?;
+    if version != KEY_CRATE_VERSION.as_bytes() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "key serialized by incompatible version {}, expected {}",
+                String::from_utf8_lossy(&version),
+                KEY_CRATE_VERSION
+            ),
+        ));
+    }
+    let circuit = read_short_str(reader)?;
+    let expected_circuit = std::any::type_name::<C>();
+    if circuit != expected_circuit.as_bytes() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "key generated for the circuit {}, expected {}",
+                String::from_utf8_lossy(&circuit),
+                expected_circuit
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks `k` at the head of the halo2 key of the circuit `C` following the header.
+///
+/// halo2 allocates the `2^k` bits of every selector column of the key before reading them, and panics for a `k` larger than the scalar field supports.
+/// The selectors are stored as `2^k / 8` bytes each, so a key shorter than them is rejected here instead of allocating the memory for a forged `k`.
+fn check_key_k<C: Circuit<Fr>>(key: &[u8]) -> io::Result<()> {
+    let mut k_bytes = [0u8; 4];
+    (&key[..]).read_exact(&mut k_bytes)?;
+    let k = u32::from_be_bytes(k_bytes);
+    if k > MAX_PARAMS_K {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid k {} in the key", k),
+        ));
+    }
+    let mut cs = ConstraintSystem::<Fr>::default();
+    C::configure(&mut cs);
+    let selectors_len = cs.num_selectors() as u64 * ((1u64 << k) + 7) / 8;
+    if (key.len() as u64) < selectors_len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("the key is too short for its k {}", k),
+        ));
+    }
+    Ok(())
+}
+
+/// Runs `read` of a halo2 artifact, e.g., [`VerifyingKey::read`], and converts its panic into an [`io::ErrorKind::InvalidData`] error naming `what` was read.
+///
+/// The deserializers of halo2 assume well-formed bytes in places, so malformed bytes from an untrusted source may make them panic rather than fail.
+/// The panic is only caught where it unwinds, i.e., not in the wasm build, which aborts on a panic.
+pub(crate) fn catch_read_panic<T>(
+    what: &str,
+    read: impl FnOnce() -> io::Result<T>,
+) -> io::Result<T> {
+    panic::catch_unwind(AssertUnwindSafe(read)).unwrap_or_else(|payload| {
+        let reason = payload
+            .downcast_ref::<&str>()
+            .map(|reason| reason.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("malformed {}: {}", what, reason),
+        ))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{BigUintConfig, BigUintInstructions};
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        halo2curves::bn256::Bn256,
+        plonk::{keygen_pk, keygen_vk, ConstraintSystem, Error},
+        poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG},
+    };
+    use halo2_base::{
+        gates::range::{RangeConfig, RangeStrategy::Vertical},
+        gates::RangeInstructions,
+        utils::PrimeField,
+        SKIP_FIRST_PASS,
+    };
+    use num_bigint::BigUint;
+    use rand::rngs::OsRng;
+
+    const K: u32 = 10;
+
+    #[derive(Default)]
+    struct TestAssignCircuit<const BITS: usize> {
+        a: BigUint,
+    }
+
+    impl<F: PrimeField, const BITS: usize> Circuit<F> for TestAssignCircuit<BITS> {
+        type Config = BigUintConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config = RangeConfig::configure(
+                meta,
+                Vertical,
+                &[1],
+                &[1],
+                1,
+                K as usize - 1,
+                0,
+                K as usize,
+            );
+            BigUintConfig::construct(range_config, 64)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "assign an integer",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    config.assign_integer(ctx, Value::known(self.a.clone()), BITS)?;
+                    config.range().finalize(ctx);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn versioned_keys() -> (Vec<u8>, Vec<u8>) {
+        let params = crate::gen_params_seeded(K, 0);
+        let circuit = TestAssignCircuit::<128>::default();
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &circuit).unwrap();
+        let mut pk_bytes = vec![];
+        write_pk_versioned::<TestAssignCircuit<128>, _>(&pk, &mut pk_bytes).unwrap();
+        let mut vk_bytes = vec![];
+        write_vk_versioned::<TestAssignCircuit<128>, _>(&vk, &mut vk_bytes).unwrap();
+        (pk_bytes, vk_bytes)
+    }
+
+    #[test]
+    fn test_read_keys_checked() {
+        let (pk_bytes, vk_bytes) = versioned_keys();
+        let (pk, vk) = read_keys_checked::<TestAssignCircuit<128>>(&pk_bytes, &vk_bytes).unwrap();
+        let mut rewritten = vec![];
+        write_vk_versioned::<TestAssignCircuit<128>, _>(&vk, &mut rewritten).unwrap();
+        assert_eq!(rewritten, vk_bytes);
+        assert_eq!(pk.get_vk().transcript_repr(), vk.transcript_repr());
+    }
+
+    #[test]
+    fn test_seeded_keys_are_reproducible() {
+        // The keys are generated deterministically from the seeded params, so they are the same on every machine.
+        assert_eq!(versioned_keys(), versioned_keys());
+    }
+
+    #[test]
+    fn test_read_keys_checked_rejects_incompatible_version() {
+        let (pk_bytes, mut vk_bytes) = versioned_keys();
+        // Corrupt the first byte of the version string.
+        vk_bytes[KEY_MAGIC.len() + 1] ^= 0xff;
+        let err = read_keys_checked::<TestAssignCircuit<128>>(&pk_bytes, &vk_bytes).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err
+            .to_string()
+            .starts_with("key serialized by incompatible version"));
+    }
+
+    #[test]
+    fn test_read_keys_rejects_forged_k() {
+        let (pk_bytes, vk_bytes) = versioned_keys();
+        let mut header = vec![];
+        write_header::<TestAssignCircuit<128>, _>(&mut header).unwrap();
+        // The largest k would allocate gigabytes of selectors before reaching the end of the bytes.
+        for (k, kind) in [
+            (MAX_PARAMS_K, io::ErrorKind::UnexpectedEof),
+            (MAX_PARAMS_K + 1, io::ErrorKind::InvalidData),
+            (u32::MAX, io::ErrorKind::InvalidData),
+        ] {
+            let forge = |key: &[u8]| {
+                let mut forged = key.to_vec();
+                forged[header.len()..header.len() + 4].copy_from_slice(&k.to_be_bytes());
+                forged
+            };
+            for format in [
+                KeyFormat::RawBytes,
+                KeyFormat::RawBytesUnchecked,
+                KeyFormat::Processed,
+            ] {
+                let err = read_vk_with_format::<TestAssignCircuit<128>>(&forge(&vk_bytes), format)
+                    .unwrap_err();
+                assert_eq!(err.kind(), kind);
+                let err = read_pk_with_format::<TestAssignCircuit<128>>(&forge(&pk_bytes), format)
+                    .unwrap_err();
+                assert_eq!(err.kind(), kind);
+            }
+        }
+        // Truncated keys fail with an error rather than a panic.
+        for len in [
+            header.len(),
+            header.len() + 2,
+            vk_bytes.len() / 2,
+            vk_bytes.len() - 1,
+        ] {
+            assert!(read_vk_checked::<TestAssignCircuit<128>>(&vk_bytes[..len]).is_err());
+            assert!(read_pk_checked::<TestAssignCircuit<128>>(&pk_bytes[..len]).is_err());
+            assert!(extract_vk_bytes::<TestAssignCircuit<128>>(&pk_bytes[..len]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_read_keys_checked_rejects_raw_keys() {
+        let (pk_bytes, vk_bytes) = versioned_keys();
+        let header_len = KEY_MAGIC.len()
+            + 1
+            + KEY_CRATE_VERSION.len()
+            + 1
+            + std::any::type_name::<TestAssignCircuit<128>>().len();
+        let err = read_keys_checked::<TestAssignCircuit<128>>(&pk_bytes[header_len..], &vk_bytes)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_read_keys_checked_rejects_other_circuit() {
+        let (pk_bytes, vk_bytes) = versioned_keys();
+        let err = read_keys_checked::<TestAssignCircuit<256>>(&pk_bytes, &vk_bytes).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().starts_with("key generated for the circuit"));
+    }
+
+    #[test]
+    fn test_check_params_k() {
+        let (pk_bytes, _) = versioned_keys();
+        let pk = read_pk_checked::<TestAssignCircuit<128>>(&pk_bytes).unwrap();
+        let params = ParamsKZG::<Bn256>::setup(K, OsRng);
+        check_params_k(&params, pk.get_vk(), K).unwrap();
+
+        // The params are large enough for the circuit but do not match the keys.
+        let larger_params = ParamsKZG::<Bn256>::setup(K + 1, OsRng);
+        let err = check_params_k(&larger_params, pk.get_vk(), K).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "params for k = {} were given, but the keys were generated for k = {}",
+                K + 1,
+                K
+            )
+        );
+
+        let smaller_params = ParamsKZG::<Bn256>::setup(K - 1, OsRng);
+        let err = check_params_k(&smaller_params, pk.get_vk(), K).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "params for k = {} were given, but the circuit requires k >= {}",
+                K - 1,
+                K
+            )
+        );
+    }
+
+    #[test]
+    fn test_read_compressed_keys() {
+        let (pk_bytes, vk_bytes) = versioned_keys();
+        let compressed_pk = crate::compress_key(&pk_bytes).unwrap();
+        let compressed_vk = crate::compress_key(&vk_bytes).unwrap();
+        assert_eq!(crate::decompress_key(&compressed_pk).unwrap(), pk_bytes);
+        assert_eq!(crate::decompress_key(&compressed_vk).unwrap(), vk_bytes);
+        assert_eq!(crate::decompress_key(&vk_bytes).unwrap(), vk_bytes);
+
+        let (pk, vk) =
+            read_keys_checked::<TestAssignCircuit<128>>(&compressed_pk, &compressed_vk).unwrap();
+        let mut rewritten = vec![];
+        write_vk_versioned::<TestAssignCircuit<128>, _>(&vk, &mut rewritten).unwrap();
+        assert_eq!(rewritten, vk_bytes);
+        assert_eq!(pk.get_vk().transcript_repr(), vk.transcript_repr());
+    }
+
+    #[test]
+    fn test_extract_vk_bytes() {
+        let params = ParamsKZG::<Bn256>::setup(K, OsRng);
+        let circuit = TestAssignCircuit::<128>::default();
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &circuit).unwrap();
+        let mut pk_bytes = vec![];
+        write_pk_versioned::<TestAssignCircuit<128>, _>(&pk, &mut pk_bytes).unwrap();
+        let mut vk_bytes = vec![];
+        write_vk_versioned::<TestAssignCircuit<128>, _>(&vk, &mut vk_bytes).unwrap();
+
+        let extracted = extract_vk_bytes::<TestAssignCircuit<128>>(&pk_bytes).unwrap();
+        assert_eq!(extracted, vk_bytes);
+        let compressed_pk = crate::compress_key(&pk_bytes).unwrap();
+        assert_eq!(
+            extract_vk_bytes::<TestAssignCircuit<128>>(&compressed_pk).unwrap(),
+            vk_bytes
+        );
+
+        // The extracted key verifies a proof created with the original proving key.
+        let circuit = TestAssignCircuit::<128> {
+            a: BigUint::from(0xdeadbeefu32),
+        };
+        let proof = crate::create_proof_with_transcript(
+            &params,
+            &pk,
+            circuit,
+            &[],
+            crate::TranscriptKind::Blake2b,
+        )
+        .unwrap();
+        let extracted_vk = read_vk_checked::<TestAssignCircuit<128>>(&extracted).unwrap();
+        crate::verify_proof_with_transcript(
+            &params,
+            &extracted_vk,
+            &proof,
+            &[],
+            crate::TranscriptKind::Blake2b,
+        )
+        .unwrap();
+
+        let err = extract_vk_bytes::<TestAssignCircuit<256>>(&pk_bytes).unwrap_err();
+        assert!(err.to_string().starts_with("key generated for the circuit"));
+    }
+
+    #[test]
+    fn test_read_pk_trusted() {
+        let (pk_bytes, vk_bytes) = versioned_keys();
+        let vk = read_vk_checked::<TestAssignCircuit<128>>(&vk_bytes).unwrap();
+        for bytes in [pk_bytes.clone(), crate::compress_key(&pk_bytes).unwrap()] {
+            let pk = read_pk_trusted::<TestAssignCircuit<128>>(&bytes).unwrap();
+            assert_eq!(pk.get_vk().transcript_repr(), vk.transcript_repr());
+        }
+        let err = read_pk_trusted::<TestAssignCircuit<256>>(&pk_bytes).unwrap_err();
+        assert!(err.to_string().starts_with("key generated for the circuit"));
+    }
+
+    #[test]
+    fn test_convert_key_format() {
+        type TestCircuit = TestAssignCircuit<128>;
+        let params = ParamsKZG::<Bn256>::setup(K, OsRng);
+        let circuit = TestCircuit::default();
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &circuit).unwrap();
+        let mut pk_bytes = vec![];
+        write_pk_versioned::<TestCircuit, _>(&pk, &mut pk_bytes).unwrap();
+        let mut vk_bytes = vec![];
+        write_vk_versioned::<TestCircuit, _>(&vk, &mut vk_bytes).unwrap();
+        let prove = |pk: &ProvingKey<G1Affine>| {
+            let circuit = TestCircuit {
+                a: BigUint::from(0xdeadbeefu32),
+            };
+            crate::create_proof_with_transcript(
+                &params,
+                pk,
+                circuit,
+                &[],
+                crate::TranscriptKind::Blake2b,
+            )
+            .unwrap()
+        };
+        let verify = |vk: &VerifyingKey<G1Affine>, proof: &[u8]| {
+            crate::verify_proof_with_transcript(
+                &params,
+                vk,
+                proof,
+                &[],
+                crate::TranscriptKind::Blake2b,
+            )
+        };
+
+        for format in [
+            KeyFormat::RawBytes,
+            KeyFormat::RawBytesUnchecked,
+            KeyFormat::Processed,
+        ] {
+            let converted_pk =
+                convert_key_format::<TestCircuit>(&pk_bytes, KeyFormat::RawBytes, format).unwrap();
+            let converted_vk =
+                convert_key_format::<TestCircuit>(&vk_bytes, KeyFormat::RawBytes, format).unwrap();
+            // Only the processed keys differ from the raw bytes.
+            assert_eq!(converted_pk == pk_bytes, format != KeyFormat::Processed);
+            assert_eq!(converted_vk == vk_bytes, format != KeyFormat::Processed);
+            let read_pk = read_pk_with_format::<TestCircuit>(&converted_pk, format).unwrap();
+            let read_vk = read_vk_with_format::<TestCircuit>(&converted_vk, format).unwrap();
+            assert_eq!(read_pk.get_vk().transcript_repr(), vk.transcript_repr());
+            assert_eq!(read_vk.transcript_repr(), vk.transcript_repr());
+
+            // The proofs of the keys in either format verify with the keys in the other.
+            verify(&read_vk, &prove(&pk)).unwrap();
+            verify(&vk, &prove(&read_pk)).unwrap();
+
+            // Converting back restores the original bytes, also from a compressed key.
+            let compressed_pk = crate::compress_key(&converted_pk).unwrap();
+            assert_eq!(
+                convert_key_format::<TestCircuit>(&compressed_pk, format, KeyFormat::RawBytes)
+                    .unwrap(),
+                pk_bytes
+            );
+            assert_eq!(
+                convert_key_format::<TestCircuit>(&converted_vk, format, KeyFormat::RawBytes)
+                    .unwrap(),
+                vk_bytes
+            );
+        }
+
+        let processed_vk =
+            convert_key_format::<TestCircuit>(&vk_bytes, KeyFormat::RawBytes, KeyFormat::Processed)
+                .unwrap();
+        assert!(read_vk_checked::<TestCircuit>(&processed_vk).is_err());
+        let err = convert_key_format::<TestAssignCircuit<256>>(
+            &pk_bytes,
+            KeyFormat::RawBytes,
+            KeyFormat::Processed,
+        )
+        .unwrap_err();
+        assert!(err.to_string().starts_with("key generated for the circuit"));
+    }
+
+    /// Generates the verifying key of the circuit `C` of degree `k` and returns it with its bytes written by [`write_vk_versioned`].
+    #[cfg(feature = "sha256")]
+    fn versioned_vk_of<C: Circuit<Fr> + Default>(k: u32) -> (VerifyingKey<G1Affine>, Vec<u8>) {
+        let params = ParamsKZG::<Bn256>::setup(k, OsRng);
+        let vk = keygen_vk(&params, &C::default()).unwrap();
+        let mut vk_bytes = vec![];
+        write_vk_versioned::<C, _>(&vk, &mut vk_bytes).unwrap();
+        (vk, vk_bytes)
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_vk_fingerprint() {
+        type Circuit64 = crate::Pkcs1v15_1024_64EnabledBenchCircuit<Fr>;
+        type Circuit128 = crate::Pkcs1v15_1024_128EnabledBenchCircuit<Fr>;
+        let (vk_64, vk_bytes_64) = versioned_vk_of::<Circuit64>(Circuit64::K);
+        let (_, vk_bytes_128) = versioned_vk_of::<Circuit128>(Circuit128::K);
+        let fingerprint = vk_fingerprint(&vk_bytes_64);
+        assert_eq!(vk_fingerprint_of::<Circuit64>(&vk_64).unwrap(), fingerprint);
+        assert_ne!(vk_fingerprint(&vk_bytes_128), fingerprint);
+
+        let mut tampered = vk_bytes_64.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert_ne!(vk_fingerprint(&tampered), fingerprint);
+        // The same key labeled with another circuit has another fingerprint.
+        assert_ne!(
+            vk_fingerprint_of::<Circuit128>(&vk_64).unwrap(),
+            fingerprint
+        );
+
+        let compressed = crate::compress_key(&vk_bytes_64).unwrap();
+        assert_ne!(vk_fingerprint(&compressed), fingerprint);
+        assert_eq!(
+            vk_fingerprint(&crate::decompress_key(&compressed).unwrap()),
+            fingerprint
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use halo2_base::halo2_proofs::{dev::CircuitLayout, halo2curves::bn256::Fr, plonk::Circuit};
+use plotters::coord::Shift;
+use plotters::prelude::{
+    BitMapBackend, DrawingArea, DrawingBackend, IntoDrawingArea, SVGBackend, WHITE,
+};
+use std::fmt;
+use std::path::Path;
+
+/// The width and the height in pixels of the images written by [`render_circuit_layout`].
+pub const LAYOUT_IMAGE_SIZE: (u32, u32) = (1600, 2400);
+
+/// An error in [`render_circuit_layout`].
+#[derive(Debug)]
+pub enum LayoutRenderError {
+    /// The extension of the path is neither `png` nor `svg`.
+    UnsupportedFormat(String),
+    /// The layout could not be drawn or written, e.g., because the directory of the path does not exist.
+    Drawing(String),
+}
+
+impl fmt::Display for LayoutRenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedFormat(path) => write!(
+                f,
+                "the layout can only be rendered to a png or svg file, but {:?} was given",
+                path
+            ),
+            Self::Drawing(e) => write!(f, "failed to render the layout: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for LayoutRenderError {}
+
+/// Renders the layout of `circuit` for params of degree `k` to the image at `path`, whose extension `png` or `svg` selects the format.
+///
+/// The columns are drawn from left to right, and each region is labeled with the name given to `assign_region`.
+/// Since the chips of this crate assign their cells in a single region of halo2-base, [`crate::CircuitCostReport::measure`] breaks that region down into the cells of the named regions of the chips.
+/// The labels are drawn with the system `sans-serif` font.
+pub fn render_circuit_layout<C: Circuit<Fr>>(
+    k: u32,
+    circuit: &C,
+    path: impl AsRef<Path>,
+) -> Result<(), LayoutRenderError> {
+    let path = path.as_ref();
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("png") => draw_layout(
+            k,
+            circuit,
+            &BitMapBackend::new(path, LAYOUT_IMAGE_SIZE).into_drawing_area(),
+        ),
+        Some("svg") => draw_layout(
+            k,
+            circuit,
+            &SVGBackend::new(path, LAYOUT_IMAGE_SIZE).into_drawing_area(),
+        ),
+        _ => Err(LayoutRenderError::UnsupportedFormat(
+            path.display().to_string(),
+        )),
+    }
+}
+
+fn draw_layout<C: Circuit<Fr>, DB: DrawingBackend>(
+    k: u32,
+    circuit: &C,
+    root: &DrawingArea<DB, Shift>,
+) -> Result<(), LayoutRenderError> {
+    let drawing = |e: plotters::drawing::DrawingAreaErrorKind<DB::ErrorType>| {
+        LayoutRenderError::Drawing(e.to_string())
+    };
+    root.fill(&WHITE).map_err(drawing)?;
+    CircuitLayout::default()
+        .show_labels(true)
+        .render(k, circuit, root)
+        .map_err(drawing)?;
+    root.present().map_err(drawing)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Base64UrlChip;
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        plonk::{ConstraintSystem, Error},
+    };
+    use halo2_base::{
+        gates::{
+            range::{RangeConfig, RangeStrategy::Vertical},
+            RangeInstructions,
+        },
+        Context, ContextParams, SKIP_FIRST_PASS,
+    };
+
+    /// A circuit of `k = 10` decoding a base64url string, whose layout renders in a moment.
+    struct TestLayoutCircuit;
+
+    impl TestLayoutCircuit {
+        const K: u32 = 10;
+    }
+
+    impl Circuit<Fr> for TestLayoutCircuit {
+        type Config = Base64UrlChip<Fr>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let range_config =
+                RangeConfig::configure(meta, Vertical, &[2], &[1], 1, 8, 0, Self::K as usize);
+            Base64UrlChip::new(range_config)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let range = config.range().clone();
+            range.load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "base64url decoding",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = Context::new(
+                        region,
+                        ContextParams {
+                            max_rows: range.gate.max_rows,
+                            num_context_ids: 1,
+                            fixed_columns: range.gate.constants.clone(),
+                        },
+                    );
+                    let ctx = &mut aux;
+                    let encoded = config.assign_encoded(ctx, b"aGVsbG8gd29ybGQ");
+                    config.decode(ctx, &encoded)?;
+                    range.finalize(ctx);
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_render_circuit_layout() {
+        let dir = std::env::temp_dir();
+        for extension in ["png", "svg"] {
+            let path = dir.join(format!(
+                "halo2-rsa-layout-{}.{}",
+                std::process::id(),
+                extension
+            ));
+            render_circuit_layout(TestLayoutCircuit::K, &TestLayoutCircuit, &path).unwrap();
+            assert!(std::fs::metadata(&path).unwrap().len() > 0);
+            std::fs::remove_file(&path).unwrap();
+        }
+        assert!(matches!(
+            render_circuit_layout(
+                TestLayoutCircuit::K,
+                &TestLayoutCircuit,
+                dir.join("layout.pdf")
+            ),
+            Err(LayoutRenderError::UnsupportedFormat(_))
+        ));
+    }
+}
@@ -12,7 +12,10 @@ use std::marker::PhantomData;
 
 pub use big_uint::*;
 
-use halo2_base::halo2_proofs::{circuit::Value, plonk::Error};
+use halo2_base::halo2_proofs::{
+    circuit::{Cell, Value},
+    plonk::Error,
+};
 use halo2_base::QuantumCell;
 use halo2_base::{
     gates::{flex_gate::FlexGateConfig, range::RangeConfig, GateInstructions, RangeInstructions},
@@ -26,23 +29,113 @@ use halo2_ecc::bigint::{
 use num_bigint::{BigInt, BigUint, Sign};
 use num_traits::{One, Signed, Zero};
 
+mod ascii;
+mod base64url;
+mod bundle;
 mod chip;
+mod circuit_values;
+mod codec;
+mod compression;
+mod cost;
+mod cost_report;
+mod diagnostics;
+mod dkim_canon;
+mod email_domain;
 mod instructions;
+mod jcs;
+mod jwk;
+mod keys;
+mod params;
+mod sha256_pad;
+mod transcript;
+pub use ascii::*;
+pub use base64url::*;
+pub use bundle::*;
 pub use chip::*;
+pub use circuit_values::*;
+pub use codec::*;
+pub use compression::*;
+pub use cost::*;
+pub use cost_report::*;
+pub use diagnostics::*;
+pub use dkim_canon::*;
+pub use email_domain::*;
 pub use instructions::*;
+pub use jcs::*;
+pub use jwk::*;
+pub use keys::*;
+pub use params::*;
+pub use sha256_pad::*;
+pub use transcript::*;
+#[cfg(not(target_arch = "wasm32"))]
+mod key_store;
+#[cfg(not(target_arch = "wasm32"))]
+pub use key_store::*;
+#[cfg(feature = "aggregation")]
+mod aggregation;
+#[cfg(feature = "aggregation")]
+pub use aggregation::*;
+#[cfg(feature = "sha256")]
+mod dkim;
+#[cfg(feature = "sha256")]
+pub use dkim::*;
+#[cfg(feature = "evm")]
+mod evm;
+#[cfg(feature = "evm")]
+pub use evm::*;
+#[cfg(feature = "fast-test-hash")]
+mod fast_test_hash;
+#[cfg(feature = "fast-test-hash")]
+pub use fast_test_hash::*;
+#[cfg(feature = "iso9796_2")]
+mod iso9796_2;
+#[cfg(feature = "iso9796_2")]
+pub use iso9796_2::*;
+#[cfg(feature = "dev-graph")]
+mod layout;
+#[cfg(feature = "dev-graph")]
+pub use layout::*;
+#[cfg(feature = "memory-stats")]
+mod memory;
+#[cfg(feature = "memory-stats")]
+pub use memory::*;
+#[cfg(feature = "registry")]
+mod registry;
+#[cfg(feature = "registry")]
+pub use registry::*;
+#[cfg(feature = "prove-timing")]
+mod timing;
+#[cfg(feature = "prove-timing")]
+pub use timing::*;
+#[cfg(feature = "sha256")]
+mod circuit;
+#[cfg(feature = "sha256")]
+pub use circuit::*;
+#[cfg(feature = "sha256")]
+mod presets;
+#[cfg(feature = "sha256")]
+pub use presets::*;
+#[cfg(feature = "sha256")]
+mod shape;
+#[cfg(feature = "sha256")]
+pub use shape::*;
 #[cfg(feature = "sha256")]
 mod macros;
 #[cfg(feature = "sha256")]
+use cost_report::{begin_cost_region, end_cost_region};
+#[cfg(feature = "sha256")]
 pub use halo2_dynamic_sha256;
 #[cfg(feature = "sha256")]
 use halo2_dynamic_sha256::{AssignedHashResult, Sha256DynamicConfig};
 #[cfg(feature = "sha256")]
 pub use macros::*;
+#[cfg(feature = "sha256")]
+use sha2::{Digest, Sha256};
 
-// #[cfg(target_arch = "wasm32")]
-// mod wasm;
-// #[cfg(target_arch = "wasm32")]
-// pub use wasm::*;
+#[cfg(all(target_arch = "wasm32", feature = "sha256"))]
+mod wasm;
+#[cfg(all(target_arch = "wasm32", feature = "sha256"))]
+pub use wasm::*;
 
 /// A parameter `e` in the RSA public key that is about to be assigned.
 #[derive(Clone, Debug)]
@@ -53,6 +146,25 @@ pub enum RSAPubE {
     Fix(BigUint),
 }
 
+impl RSAPubE {
+    /// Creates a fixed parameter `e` from its big-endian bytes.
+    ///
+    /// Unlike `e` given as `u64`, `e` of any size is accepted and the modular power in [`RSAConfig`] uses all of its bits.
+    ///
+    /// # Arguments
+    /// * bytes - big-endian bytes of `e`.
+    ///
+    /// # Return values
+    /// Returns new [`RSAPubE::Fix`], or `None` if `e` is not odd.
+    pub fn fix_from_be_bytes(bytes: &[u8]) -> Option<Self> {
+        let e = BigUint::from_bytes_be(bytes);
+        if !e.bit(0) {
+            return None;
+        }
+        Some(Self::Fix(e))
+    }
+}
+
 /// A parameter `e` in the assigned RSA public key.
 #[derive(Clone, Debug)]
 pub enum AssignedRSAPubE<'v, F: PrimeField> {
@@ -147,6 +259,56 @@ impl<F: PrimeField> RSASignature<F> {
         let c = Value::unknown();
         Self { c, _f: PhantomData }
     }
+
+    /// Creates new [`RSASignature`] from its big-endian bytes, which are normalized by [`normalize_signature_bytes`].
+    ///
+    /// # Arguments
+    /// * bytes - big-endian bytes of the signature, with or without its leading zero bytes.
+    /// * bits_len - the bit length of the RSA modulus.
+    ///
+    /// # Return values
+    /// Returns new [`RSASignature`], or `None` if `bytes` is longer than `bits_len / 8` bytes.
+    pub fn from_be_bytes(bytes: &[u8], bits_len: usize) -> Option<Self> {
+        let bytes = normalize_signature_bytes(bytes, bits_len)?;
+        Some(Self::new(Value::known(BigUint::from_bytes_be(&bytes))))
+    }
+}
+
+/// Left-pads the big-endian bytes of a signature with zeros to exactly `bits_len / 8` bytes.
+///
+/// Some libraries strip the leading zero bytes of a signature, e.g., giving 127 bytes for a 1024 bits key, which the rsa crate rejects for its length.
+/// Returns `None` if `signature` is longer than `bits_len / 8` bytes, even if its extra bytes are zeros.
+pub fn normalize_signature_bytes(signature: &[u8], bits_len: usize) -> Option<Vec<u8>> {
+    let len = bits_len / 8;
+    if signature.len() > len {
+        return None;
+    }
+    let mut bytes = vec![0; len - signature.len()];
+    bytes.extend_from_slice(signature);
+    Some(bytes)
+}
+
+/// Returns the values of the instance column bound to a signature by [`RSASignatureVerifier::with_public_signature`], i.e., its `limb_bits` bits limbs from the least significant one.
+///
+/// # Arguments
+/// * signature - an integer of the signature.
+/// * bits_len - the bit length of the RSA modulus, i.e., the default bit length of the [`RSAConfig`].
+/// * limb_bits - the bit width of each limb.
+pub fn signature_instances<F: PrimeField>(
+    signature: &BigUint,
+    bits_len: usize,
+    limb_bits: usize,
+) -> Vec<F> {
+    decompose_biguint(signature, bits_len / limb_bits, limb_bits)
+}
+
+/// Returns the values of the instance column exposing a SHA256 digest in the circuits of [`Pkcs1v15Circuit`] with the public digest, i.e., its 64 bits limbs from the least significant one.
+///
+/// # Arguments
+/// * digest - the 32 bytes big-endian SHA256 digest.
+pub fn digest_instances<F: PrimeField>(digest: &[u8]) -> Vec<F> {
+    assert_eq!(digest.len(), 32, "the digest must be 32 bytes");
+    decompose_biguint(&BigUint::from_bytes_be(digest), 4, 64)
 }
 
 /// An assigned RSA signature.
@@ -169,28 +331,343 @@ impl<'v, F: PrimeField> AssignedRSASignature<'v, F> {
     }
 }
 
+/// An assigned DigestInfo decoded from a pkcs1v15 signature.
+#[derive(Clone, Debug)]
+pub struct AssignedDigestInfo<'v, F: PrimeField> {
+    /// the assigned bit that is one if the signature is a valid pkcs1v15 signature for one of the supported hash algorithms.
+    pub is_valid: AssignedValue<'v, F>,
+    /// the assigned bytes of the algorithm OID, which are all zero if `is_valid` is zero.
+    pub oid: Vec<AssignedValue<'v, F>>,
+    /// the assigned big-endian bytes of the digest, left-padded with zeros to the longest supported digest length.
+    pub digest: Vec<AssignedValue<'v, F>>,
+}
+
 #[cfg(feature = "sha256")]
 /// A circuit implementation to verify pkcs1v15 signatures.
 #[derive(Clone, Debug)]
-pub struct RSASignatureVerifier<F: PrimeField> {
+pub struct RSASignatureVerifier<F: PrimeField, H: Sha256Instructions<F> = Sha256DynamicConfig<F>> {
     rsa_config: RSAConfig<F>,
-    sha256_config: Sha256DynamicConfig<F>,
+    sha256_config: H,
+    /// Whether to record the limbs of the verified signatures for binding them to an instance column.
+    expose_signature: bool,
+    /// The cells of the limbs of the verified signatures recorded if `expose_signature` is true.
+    signature_cells: Vec<Cell>,
+}
+
+/// A SHA256 chip that [`RSASignatureVerifier`] hashes the signed messages with.
+///
+/// [`Sha256DynamicConfig`] is the default one. With the `fast-test-hash` feature, the unsound `FastTestSha256Chip` implements it for the tests of the RSA layer.
+#[cfg(feature = "sha256")]
+pub trait Sha256Instructions<F: PrimeField> {
+    /// Assigns `input` and its SHA256 digest in the same way as [`Sha256DynamicConfig::digest`].
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * input - the bytes to be hashed.
+    /// * precomputed_input_len - the number of the leading bytes of `input` whose hash state is computed outside the circuit, if any.
+    ///
+    /// # Return values
+    /// Returns the assigned length and bytes of `input` and the assigned bytes of its digest.
+    fn digest<'a, 'b: 'a>(
+        &'a mut self,
+        ctx: &mut Context<'b, F>,
+        input: &'a [u8],
+        precomputed_input_len: Option<usize>,
+    ) -> Result<AssignedHashResult<'b, F>, Error>;
+}
+
+#[cfg(feature = "sha256")]
+impl<F: PrimeField> Sha256Instructions<F> for Sha256DynamicConfig<F> {
+    fn digest<'a, 'b: 'a>(
+        &'a mut self,
+        ctx: &mut Context<'b, F>,
+        input: &'a [u8],
+        precomputed_input_len: Option<usize>,
+    ) -> Result<AssignedHashResult<'b, F>, Error> {
+        Sha256DynamicConfig::digest(self, ctx, input, precomputed_input_len)
+    }
+}
+
+/// A chip that constrains the message bytes verified by [`RSASignatureVerifier::verify_pkcs1v15_and_consume`].
+///
+/// Downstream crates, e.g., regex-matching chips, implement this trait to check that the signed message matches a pattern.
+#[cfg(feature = "sha256")]
+pub trait MessageConsumer<F: PrimeField> {
+    /// Constrains the assigned message bytes.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * msg - the assigned bytes of the verified message.
+    ///
+    /// # Return values
+    /// Returns the assigned values to be exposed as public inputs, e.g., a bit indicating whether `msg` matches a pattern.
+    fn consume<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        msg: &[AssignedValue<'v, F>],
+    ) -> Result<Vec<AssignedValue<'v, F>>, Error>;
+}
+
+/// The maximum number of message bytes packed into one field element by [`RSASignatureVerifier::extract_message_field`], which keeps the packed value below the modulus of a field of 254 bits.
+pub const MESSAGE_FIELD_BYTES_PER_ELEMENT: usize = 31;
+
+/// A [`MessageConsumer`] that exposes fixed byte ranges of the signed message, e.g., a timestamp or a nonce at a fixed offset, as packed field elements.
+///
+/// Its outputs are the elements of [`RSASignatureVerifier::extract_message_field`] for each range in order.
+#[cfg(feature = "sha256")]
+#[derive(Clone, Debug)]
+pub struct MessageFieldExtractor<F: PrimeField> {
+    gate: FlexGateConfig<F>,
+    fields: Vec<(usize, usize)>,
+}
+
+#[cfg(feature = "sha256")]
+impl<F: PrimeField> MessageFieldExtractor<F> {
+    /// Creates new [`MessageFieldExtractor`] from a [`FlexGateConfig`] and the pairs of the offset and the length of each range.
+    pub fn new(gate: FlexGateConfig<F>, fields: Vec<(usize, usize)>) -> Self {
+        Self { gate, fields }
+    }
+}
+
+#[cfg(feature = "sha256")]
+impl<F: PrimeField> MessageConsumer<F> for MessageFieldExtractor<F> {
+    fn consume<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        msg: &[AssignedValue<'v, F>],
+    ) -> Result<Vec<AssignedValue<'v, F>>, Error> {
+        let mut outputs = vec![];
+        for (offset, len) in self.fields.iter() {
+            outputs.extend(pack_message_field(&self.gate, ctx, msg, *offset, *len)?);
+        }
+        Ok(outputs)
+    }
+}
+
+/// A field of a signed message at a fixed byte range, which is either revealed as public inputs or kept hidden.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MessageField {
+    /// The index of the first byte of the field.
+    pub offset: usize,
+    /// The number of bytes of the field.
+    pub len: usize,
+    /// Whether the field is exposed as public inputs.
+    pub reveal: bool,
+}
+
+/// A schema of a signed message as the concatenation of fields, e.g., the fields of a passport, a credential, or an email header, of which only the flagged ones are revealed.
+///
+/// The fields must be in order and must not overlap. The bytes not covered by any field are hidden as well.
+/// [`RSASignatureVerifier::verify_with_schema`] packs each revealed field as [`RSASignatureVerifier::extract_message_field`] does.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FieldSchema {
+    fields: Vec<MessageField>,
+}
+
+impl FieldSchema {
+    /// Creates new [`FieldSchema`] from its fields.
+    pub fn new(fields: Vec<MessageField>) -> Self {
+        Self { fields }
+    }
+
+    /// Appends a field of `len` bytes at `offset`, which is revealed if `reveal` is true.
+    pub fn field(mut self, offset: usize, len: usize, reveal: bool) -> Self {
+        self.fields.push(MessageField {
+            offset,
+            len,
+            reveal,
+        });
+        self
+    }
+
+    /// Returns the fields in order.
+    pub fn fields(&self) -> &[MessageField] {
+        &self.fields
+    }
+
+    /// Returns the pairs of the offset and the length of the revealed fields.
+    pub fn revealed_ranges(&self) -> Vec<(usize, usize)> {
+        self.fields
+            .iter()
+            .filter(|field| field.reveal)
+            .map(|field| (field.offset, field.len))
+            .collect()
+    }
+
+    /// Returns whether the fields are non-empty, in order, non-overlapping, and within a message of `msg_len` bytes.
+    pub fn is_valid_for(&self, msg_len: usize) -> bool {
+        let mut end = 0;
+        for field in self.fields.iter() {
+            if field.len == 0 || field.offset < end {
+                return false;
+            }
+            end = match field.offset.checked_add(field.len) {
+                Some(field_end) if field_end <= msg_len => field_end,
+                _ => return false,
+            };
+        }
+        true
+    }
+
+    /// Returns the values of the public inputs exposed by [`RSASignatureVerifier::verify_with_schema`] for `msg`, i.e., the packed elements of the revealed fields in order.
+    ///
+    /// # Panics
+    /// Panics if the schema is not valid for `msg`. See [`FieldSchema::is_valid_for`].
+    pub fn instances<F: PrimeField>(&self, msg: &[u8]) -> Vec<F> {
+        assert!(
+            self.is_valid_for(msg.len()),
+            "the schema does not fit the message"
+        );
+        self.revealed_ranges()
+            .into_iter()
+            .flat_map(|(offset, len)| {
+                msg[offset..offset + len].chunks(MESSAGE_FIELD_BYTES_PER_ELEMENT)
+            })
+            .map(|chunk| biguint_to_fe(&BigUint::from_bytes_be(chunk)))
+            .collect()
+    }
+}
+
+/// The number of bytes of the big-endian epoch embedded in a message verified by [`RSASignatureVerifier::verify_pkcs1v15_with_key_epoch`].
+pub const KEY_EPOCH_BYTES: usize = 8;
+
+/// The block size of SHA256 in bytes, to which [`RSASignatureVerifier::verify_pkcs1v15_hmac`] pads the HMAC key.
+pub const HMAC_SHA256_BLOCK_LEN: usize = 64;
+
+/// The inner and the outer pads of HMAC, which are XORed with each byte of the padded key.
+#[cfg(feature = "sha256")]
+const HMAC_IPAD: u8 = 0x36;
+#[cfg(feature = "sha256")]
+const HMAC_OPAD: u8 = 0x5c;
+/// The bit length bounding the lengths of the hashed messages compared by [`RSASignatureVerifier`].
+#[cfg(feature = "sha256")]
+const INPUT_LEN_BITS: usize = 32;
+
+/// Returns the head of the SHA256 hash chain starting from `entry`, i.e., `entry` hashed `steps` times, which is `entry` itself for zero steps.
+///
+/// This is the message signed for [`RSASignatureVerifier::verify_signed_hashchain`].
+#[cfg(feature = "sha256")]
+pub fn hashchain_head(entry: &[u8], steps: usize) -> Vec<u8> {
+    (0..steps).fold(entry.to_vec(), |link, _| Sha256::digest(&link).to_vec())
+}
+
+/// A rotated RSA key allowed to sign the messages whose embedded epoch is in `[active_from, active_until)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyEpoch {
+    /// The modulus of the key.
+    pub n: BigUint,
+    /// The first epoch in which the key is active.
+    pub active_from: u64,
+    /// The first epoch in which the key has expired.
+    pub active_until: u64,
+}
+
+impl KeyEpoch {
+    /// Creates new [`KeyEpoch`] from the modulus and the validity range of a key.
+    pub fn new(n: BigUint, active_from: u64, active_until: u64) -> Self {
+        Self {
+            n,
+            active_from,
+            active_until,
+        }
+    }
+
+    /// Returns whether the key is active in `epoch`.
+    pub fn is_active_at(&self, epoch: u64) -> bool {
+        self.active_from <= epoch && epoch < self.active_until
+    }
+}
+
+/// Returns the values of the public inputs exposed by [`RSASignatureVerifier::verify_pkcs1v15_with_key_epoch`] for the key set `keys`.
+///
+/// For each key in order, they are the `limb_bits` bits limbs of its modulus from the least significant one, followed by its `active_from` and `active_until`.
+///
+/// # Arguments
+/// * keys - the allowed keys.
+/// * bits_len - the bit length of the RSA modulus, i.e., the default bit length of the [`RSAConfig`].
+/// * limb_bits - the bit width of each limb.
+pub fn key_epoch_instances<F: PrimeField>(
+    keys: &[KeyEpoch],
+    bits_len: usize,
+    limb_bits: usize,
+) -> Vec<F> {
+    keys.iter()
+        .flat_map(|key| {
+            let mut instances = decompose_biguint::<F>(&key.n, bits_len / limb_bits, limb_bits);
+            instances.push(F::from(key.active_from));
+            instances.push(F::from(key.active_until));
+            instances
+        })
+        .collect()
+}
+
+/// Packs `msg[offset..offset + len]` into field elements. See [`RSASignatureVerifier::extract_message_field`].
+#[cfg(feature = "sha256")]
+fn pack_message_field<'v, F: PrimeField>(
+    gate: &FlexGateConfig<F>,
+    ctx: &mut Context<'v, F>,
+    msg: &[AssignedValue<'v, F>],
+    offset: usize,
+    len: usize,
+) -> Result<Vec<AssignedValue<'v, F>>, Error> {
+    if len == 0 || offset.checked_add(len).map_or(true, |end| end > msg.len()) {
+        return Err(Error::Synthesis);
+    }
+    Ok(msg[offset..offset + len]
+        .chunks(MESSAGE_FIELD_BYTES_PER_ELEMENT)
+        .map(|chunk| {
+            let bases = (0..chunk.len())
+                .rev()
+                .map(|i| QuantumCell::Constant(biguint_to_fe(&(BigUint::one() << (8 * i)))))
+                .collect::<Vec<QuantumCell<F>>>();
+            gate.inner_product(
+                ctx,
+                chunk.iter().map(QuantumCell::Existing).collect(),
+                bases,
+            )
+        })
+        .collect())
 }
 
 #[cfg(feature = "sha256")]
-impl<F: PrimeField> RSASignatureVerifier<F> {
+impl<F: PrimeField, H: Sha256Instructions<F>> RSASignatureVerifier<F, H> {
     /// Creates new [`RSASignatureVerifier`] from [`RSAChip`] and [`Sha256BitChip`].
     ///
     /// # Arguments
     /// * rsa_config - a [`RSAConfig`].
-    /// * sha256_config - a [`Sha256DynamicConfig`]
+    /// * sha256_config - a [`Sha256DynamicConfig`], or another [`Sha256Instructions`].
     ///
     /// # Return values
     /// Returns new [`RSASignatureVerifier`].
-    pub fn new(rsa_config: RSAConfig<F>, sha256_config: Sha256DynamicConfig<F>) -> Self {
+    pub fn new(rsa_config: RSAConfig<F>, sha256_config: H) -> Self {
         Self {
             rsa_config,
             sha256_config,
+            expose_signature: false,
+            signature_cells: vec![],
+        }
+    }
+
+    /// Sets whether the verifier exposes the verified signatures, e.g., for an auditor who knows the signature and checks which one a proof pertains to.
+    ///
+    /// If `expose` is true, every verify function records the cells of the limbs of the signature, from the least significant one, and [`RSASignatureVerifier::signature_cells`] returns them.
+    /// The circuit then binds them to an instance column with `Layouter::constrain_instance` outside the region, whose values are [`signature_instances`] of the signatures.
+    pub fn with_public_signature(mut self, expose: bool) -> Self {
+        self.expose_signature = expose;
+        self
+    }
+
+    /// Returns the cells of the limbs of the signatures verified so far if the verifier was created with [`RSASignatureVerifier::with_public_signature`], and otherwise an empty slice.
+    ///
+    /// The limbs of each signature follow those of the previously verified one.
+    pub fn signature_cells(&self) -> &[Cell] {
+        &self.signature_cells
+    }
+
+    fn record_signature(&mut self, signature: &AssignedRSASignature<'_, F>) {
+        if self.expose_signature {
+            self.signature_cells
+                .extend(signature.c.limbs().iter().map(|limb| limb.cell()));
         }
     }
 
@@ -215,86 +692,885 @@ impl<F: PrimeField> RSASignatureVerifier<F> {
         signature: &AssignedRSASignature<'b, F>,
     ) -> Result<(AssignedValue<'b, F>, Vec<AssignedValue<'b, F>>), Error> {
         let sha256 = &mut self.sha256_config;
-        let rsa = self.rsa_config.clone();
-        let biguint = &rsa.biguint_config();
+        begin_cost_region(ctx, "sha256 rounds");
         let result = sha256.digest(ctx, msg, None)?;
-        let mut hashed_bytes = result.output_bytes;
-        hashed_bytes.reverse();
-        let bytes_bits = hashed_bytes.len() * 8;
-        let limb_bits = biguint.limb_bits();
-        let limb_bytes = limb_bits / 8;
-        let mut hashed_u64s = vec![];
-        let bases = (0..limb_bytes)
-            .map(|i| F::from((1u64 << (8 * i)) as u64))
-            .map(QuantumCell::Constant)
-            .collect::<Vec<QuantumCell<F>>>();
-        for i in 0..(bytes_bits / limb_bits) {
-            let left = hashed_bytes[limb_bytes * i..limb_bytes * (i + 1)]
-                .iter()
-                .map(QuantumCell::Existing)
-                .collect::<Vec<QuantumCell<F>>>();
-            let sum = biguint.gate().inner_product(ctx, left, bases.clone());
-            hashed_u64s.push(sum);
-        }
+        end_cost_region(ctx);
+        let hashed_bytes = result.output_bytes;
         let is_sign_valid =
-            rsa.verify_pkcs1v15_signature(ctx, public_key, &hashed_u64s, signature)?;
-
-        hashed_bytes.reverse();
+            self.verify_hashed_bytes(ctx, public_key, hashed_bytes.clone(), signature)?;
         Ok((is_sign_valid, hashed_bytes))
     }
-}
 
-#[cfg(feature = "sha256")]
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::big_uint::decompose_biguint;
-    use halo2_base::halo2_proofs::{
-        circuit::{Cell, Layouter, SimpleFloorPlanner},
-        dev::MockProver,
-        halo2curves::bn256::Fr,
-        plonk::{Circuit, Column, ConstraintSystem, Instance},
-    };
-    use halo2_base::{gates::range::RangeStrategy::Vertical, ContextParams, SKIP_FIRST_PASS};
-    use halo2_dynamic_sha256::Sha256DynamicConfig;
+    /// Given a RSA public key, a public prefix and a private suffix of the signed message, and a pkcs1v15 signature, verifies the signature over the SHA256 hash of `public_prefix || private_suffix`.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * public_key - an assigned public key used for the verification.
+    /// * signature - a pkcs1v15 signature to be verified.
+    /// * public_prefix - bytes of the message that are allowed to be revealed.
+    /// * private_suffix - bytes of the message that must stay hidden.
+    ///
+    /// # Return values
+    /// Returns the assigned bit as `AssignedValue<F>`, the assigned bytes of `public_prefix`, and the assigned bytes of the resulting hash.
+    /// The length of the hashed message is constrained to be at least `public_prefix.len()`, so the returned bytes are never the SHA256 padding of a shorter message.
+    /// If `signature` is valid for `public_key` and `public_prefix || private_suffix`, the bit is equivalent to one.
+    /// Otherwise, the bit is equivalent to zero.
+    /// Only the returned prefix bytes are meant to be exposed as public inputs; the suffix bytes are never returned.
+    pub fn verify_pkcs1v15_split<'a, 'b: 'a>(
+        &'a mut self,
+        ctx: &mut Context<'b, F>,
+        public_key: &AssignedRSAPublicKey<'b, F>,
+        signature: &AssignedRSASignature<'b, F>,
+        public_prefix: &'a [u8],
+        private_suffix: &'a [u8],
+    ) -> Result<
+        (
+            AssignedValue<'b, F>,
+            Vec<AssignedValue<'b, F>>,
+            Vec<AssignedValue<'b, F>>,
+        ),
+        Error,
+    > {
+        let msg = [public_prefix, private_suffix].concat();
+        begin_cost_region(ctx, "sha256 rounds");
+        let result = self.sha256_config.digest(ctx, &msg, None)?;
+        end_cost_region(ctx);
+        // The assigned input bytes are laid out in the order of `msg`, so the first bytes are exactly those of `public_prefix` as long as the hashed message is not shorter than it.
+        self.assert_input_len_at_least(ctx, &result.input_len, public_prefix.len());
+        let public_bytes = result.input_bytes[0..public_prefix.len()].to_vec();
+        let hashed_bytes = result.output_bytes;
+        let is_sign_valid =
+            self.verify_hashed_bytes(ctx, public_key, hashed_bytes.clone(), signature)?;
+        Ok((is_sign_valid, public_bytes, hashed_bytes))
+    }
 
-    use num_bigint::RandomBits;
-    use num_traits::FromPrimitive;
-    use rand::{rngs::OsRng, thread_rng, Rng};
-    use rsa::{
-        pkcs1v15::*, signature::SignatureEncoding, signature::Signer, traits::PaddingScheme,
-        traits::PublicKeyParts, Pkcs1v15Sign, RsaPrivateKey, RsaPublicKey,
-    };
-    use sha2::{Digest, Sha256};
+    /// Given a RSA public key, hidden message bytes, and a pkcs1v15 signature, constrains the signature to be valid for the SHA256 hash of the message and returns only the hash.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * public_key - an assigned public key used for the verification.
+    /// * msg - signed message bytes, which are only given to the SHA256 chip.
+    /// * signature - a pkcs1v15 signature to be verified.
+    ///
+    /// # Return values
+    /// Returns the assigned bytes of the resulting hash, which are meant to be the sole public output about the message.
+    /// Unlike [`RSASignatureVerifier::verify_pkcs1v15_signature`], the validity bit is asserted to be one inside, so the returned hash is always bound to a valid signature.
+    pub fn verify_pkcs1v15_hash_public<'a, 'b: 'a>(
+        &'a mut self,
+        ctx: &mut Context<'b, F>,
+        public_key: &AssignedRSAPublicKey<'b, F>,
+        msg: &'a [u8],
+        signature: &AssignedRSASignature<'b, F>,
+    ) -> Result<Vec<AssignedValue<'b, F>>, Error> {
+        let (is_sign_valid, hashed_bytes) =
+            self.verify_pkcs1v15_signature(ctx, public_key, msg, signature)?;
+        self.rsa_config
+            .biguint_config()
+            .gate()
+            .assert_is_const(ctx, &is_sign_valid, F::one());
+        Ok(hashed_bytes)
+    }
 
-    macro_rules! impl_rsa_signature_test_circuit {
-        ($config_name:ident, $circuit_name:ident, $test_fn_name:ident, $bits_len:expr, $msg_len:expr, $num_advice:expr, $num_lookup_advice:expr, $lookup_bits:expr, $k:expr, $should_be_error:expr, $( $synth:tt )*) => {
-            #[derive(Debug,Clone)]
-            struct $config_name<F:PrimeField> {
-                rsa_config: RSAConfig<F>,
-                sha256_config: Sha256DynamicConfig<F>,
-                n_instance: Column<Instance>,
-                hash_instance: Column<Instance>
-            }
+    /// Given a RSA public key, signed message bytes, and a pkcs1v15 signature, verifies the signature with SHA256 hash function and hands the assigned message bytes to `consumer`.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * public_key - an assigned public key used for the verification.
+    /// * msg - signed message bytes.
+    /// * signature - a pkcs1v15 signature to be verified.
+    /// * consumer - a [`MessageConsumer`] that constrains the verified message, e.g., a regex-matching chip.
+    ///
+    /// # Return values
+    /// Returns the assigned bit as `AssignedValue<F>`, the outputs of `consumer`, and the assigned bytes of the resulting hash.
+    /// If `signature` is valid for `public_key` and `msg`, the bit is equivalent to one.
+    /// Otherwise, the bit is equivalent to zero.
+    /// The bytes given to `consumer` are the same cells hashed by SHA256, and the length of the hashed message is constrained to be at least `msg.len()`, so its outputs are bound to the signed message and never to its SHA256 padding.
+    pub fn verify_pkcs1v15_and_consume<'a, 'b: 'a, C: MessageConsumer<F>>(
+        &'a mut self,
+        ctx: &mut Context<'b, F>,
+        public_key: &AssignedRSAPublicKey<'b, F>,
+        msg: &'a [u8],
+        signature: &AssignedRSASignature<'b, F>,
+        consumer: &C,
+    ) -> Result<
+        (
+            AssignedValue<'b, F>,
+            Vec<AssignedValue<'b, F>>,
+            Vec<AssignedValue<'b, F>>,
+        ),
+        Error,
+    > {
+        begin_cost_region(ctx, "sha256 rounds");
+        let result = self.sha256_config.digest(ctx, msg, None)?;
+        end_cost_region(ctx);
+        // The assigned input bytes are padded to the maximum message length, so only the first `msg.len()` bytes are handed to `consumer`, and the hashed message must not be shorter than them.
+        self.assert_input_len_at_least(ctx, &result.input_len, msg.len());
+        let msg_bytes = &result.input_bytes[0..msg.len()];
+        let outputs = consumer.consume(ctx, msg_bytes)?;
+        let hashed_bytes = result.output_bytes;
+        let is_sign_valid =
+            self.verify_hashed_bytes(ctx, public_key, hashed_bytes.clone(), signature)?;
+        Ok((is_sign_valid, outputs, hashed_bytes))
+    }
 
-            struct $circuit_name<F: PrimeField> {
-                private_key: RsaPrivateKey,
-                public_key: RsaPublicKey,
-                msg: Vec<u8>,
-                _f: PhantomData<F>
+    /// Given a RSA public key, signed message bytes, a pkcs1v15 signature, and a fixed set of message templates, verifies the signature for `msg` and constrains `msg` to be one of `templates` without revealing which.
+    ///
+    /// The SHA256 digests of `templates` are computed outside the circuit and fixed as constants, so the message itself is never hashed in the circuit; the witnessed digest of `msg` is instead constrained to equal one of those constants.
+    /// If `index_salt` is given, a commitment `SHA256(index || index_salt)` to the one-byte index of the chosen template is computed with the SHA256 chip, which then needs a maximum byte size of at least 64 for it.
+    /// `index_salt` must be sampled uniformly at random and kept secret, as the index takes only `templates.len()` values.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * public_key - an assigned public key used for the verification.
+    /// * msg - signed message bytes.
+    /// * signature - a pkcs1v15 signature to be verified.
+    /// * templates - at most 256 distinct candidate messages, which are part of the circuit.
+    /// * index_salt - a salt for the commitment to the chosen index, or `None` to expose nothing about the index.
+    ///
+    /// # Return values
+    /// Returns the assigned bit as `AssignedValue<F>` and, only if `index_salt` is given, the assigned bytes of the commitment.
+    /// If `signature` is valid for `public_key` and `msg` and `msg` is one of `templates`, the bit is equivalent to one.
+    /// Otherwise, the bit is equivalent to zero.
+    /// Returns [`Error::Synthesis`] if `templates` is empty, has more than 256 messages, or has duplicate messages.
+    pub fn verify_pkcs1v15_one_of<'a, 'b: 'a>(
+        &'a mut self,
+        ctx: &mut Context<'b, F>,
+        public_key: &AssignedRSAPublicKey<'b, F>,
+        msg: &'a [u8],
+        signature: &AssignedRSASignature<'b, F>,
+        templates: &[&[u8]],
+        index_salt: Option<&[u8; 31]>,
+    ) -> Result<(AssignedValue<'b, F>, Option<Vec<AssignedValue<'b, F>>>), Error> {
+        let template_digests = templates
+            .iter()
+            .map(|template| Sha256::digest(template).to_vec())
+            .collect::<Vec<Vec<u8>>>();
+        let has_duplicates = template_digests
+            .iter()
+            .enumerate()
+            .any(|(i, digest)| template_digests[..i].contains(digest));
+        if templates.is_empty() || templates.len() > 256 || has_duplicates {
+            return Err(Error::Synthesis);
+        }
+        let rsa = self.rsa_config.clone();
+        let gate = rsa.gate();
+        // 1. Assign the digest of `msg`, which `verify_pkcs1v15_digest` range-checks to bytes.
+        let digest = Sha256::digest(msg);
+        let digest: [AssignedValue<'b, F>; 32] = std::array::from_fn(|i| {
+            gate.load_witness(ctx, Value::known(F::from(digest[i] as u64)))
+        });
+        // 2. Compute the bit indicating whether the digest equals that of each template.
+        // Since the templates are distinct, at most one bit is one, so their sum is also a bit.
+        let is_chosen_bits = template_digests
+            .iter()
+            .map(|template_digest| {
+                let mut is_chosen = gate.load_constant(ctx, F::one());
+                for (byte, expected) in digest.iter().zip(template_digest.iter()) {
+                    let is_eq = gate.is_equal(
+                        ctx,
+                        QuantumCell::Existing(byte),
+                        QuantumCell::Constant(F::from(*expected as u64)),
+                    );
+                    is_chosen = gate.and(
+                        ctx,
+                        QuantumCell::Existing(&is_chosen),
+                        QuantumCell::Existing(&is_eq),
+                    );
+                }
+                is_chosen
+            })
+            .collect::<Vec<AssignedValue<F>>>();
+        let is_one_of = gate.sum(ctx, is_chosen_bits.iter().map(QuantumCell::Existing));
+        // 3. Verify the signature for the digest.
+        self.record_signature(signature);
+        let is_sign_valid = rsa.verify_pkcs1v15_digest(ctx, public_key, signature, &digest)?;
+        let is_valid = gate.and(
+            ctx,
+            QuantumCell::Existing(&is_sign_valid),
+            QuantumCell::Existing(&is_one_of),
+        );
+        // 4. Commit to the index of the chosen template if requested.
+        let commitment = match index_salt {
+            Some(index_salt) => {
+                let index = gate.inner_product(
+                    ctx,
+                    is_chosen_bits.iter().map(QuantumCell::Existing).collect(),
+                    (0..templates.len())
+                        .map(|i| QuantumCell::Constant(F::from(i as u64)))
+                        .collect(),
+                );
+                let native_index = templates
+                    .iter()
+                    .position(|template| *template == msg)
+                    .unwrap_or(0) as u8;
+                let preimage = [&[native_index][..], &index_salt[..]].concat();
+                begin_cost_region(ctx, "sha256 rounds");
+                let result = self.sha256_config.digest(ctx, &preimage, None)?;
+                end_cost_region(ctx);
+                gate.assert_equal(
+                    ctx,
+                    QuantumCell::Existing(&result.input_bytes[0]),
+                    QuantumCell::Existing(&index),
+                );
+                Some(result.output_bytes)
             }
+            None => None,
+        };
+        Ok((is_valid, commitment))
+    }
 
-            impl<F: PrimeField> $circuit_name<F> {
-                const BITS_LEN:usize = $bits_len;
-                const MSG_LEN:usize = $msg_len;
-                const EXP_LIMB_BITS:usize = 5;
-                const DEFAULT_E: u128 = 65537;
-                const NUM_ADVICE:usize = $num_advice;
-                const NUM_FIXED:usize = 1;
-                const NUM_LOOKUP_ADVICE:usize = $num_lookup_advice;
-                const LOOKUP_BITS:usize = $lookup_bits;
-                const SHA256_LOOKUP_BITS:usize = 8;
-                const SHA256_LOOKUP_ADVICE:usize = 8;
+    /// Given a RSA public key, signed message bytes, a pkcs1v15 signature, and a [`FieldSchema`] of the message, verifies the signature over the whole message and packs only the revealed fields.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * public_key - an assigned public key used for the verification.
+    /// * msg - signed message bytes.
+    /// * signature - a pkcs1v15 signature to be verified.
+    /// * schema - the fields of `msg`.
+    ///
+    /// # Return values
+    /// Returns the assigned bit as `AssignedValue<F>`, the packed elements of the revealed fields in order, which [`FieldSchema::instances`] computes natively, and the assigned bytes of the resulting hash.
+    /// If `signature` is valid for `public_key` and `msg`, the bit is equivalent to one.
+    /// Otherwise, the bit is equivalent to zero.
+    /// Returns [`Error::Synthesis`] if `schema` is not valid for `msg`. See [`FieldSchema::is_valid_for`].
+    /// Every field then ends within `msg`, whose length [`RSASignatureVerifier::verify_pkcs1v15_and_consume`] constrains the hashed length to reach, so the packed fields are never read from the SHA256 padding of a shorter message.
+    pub fn verify_with_schema<'a, 'b: 'a>(
+        &'a mut self,
+        ctx: &mut Context<'b, F>,
+        public_key: &AssignedRSAPublicKey<'b, F>,
+        msg: &'a [u8],
+        signature: &AssignedRSASignature<'b, F>,
+        schema: &FieldSchema,
+    ) -> Result<
+        (
+            AssignedValue<'b, F>,
+            Vec<AssignedValue<'b, F>>,
+            Vec<AssignedValue<'b, F>>,
+        ),
+        Error,
+    > {
+        if !schema.is_valid_for(msg.len()) {
+            return Err(Error::Synthesis);
+        }
+        let extractor =
+            MessageFieldExtractor::new(self.rsa_config.gate().clone(), schema.revealed_ranges());
+        self.verify_pkcs1v15_and_consume(ctx, public_key, msg, signature, &extractor)
+    }
+
+    /// Given a RSA public key, signed message bytes, a pkcs1v15 signature, and a set of rotated keys, verifies the signature and constrains `public_key` to be a key of the set that is active in the epoch embedded in `msg`.
+    ///
+    /// The epoch is the [`KEY_EPOCH_BYTES`] bytes big-endian integer at `epoch_offset` of the signed message, and a key is active in the epochs in `[active_from, active_until)`.
+    /// The length of the hashed message is constrained to be at least `epoch_offset + KEY_EPOCH_BYTES`, so the epoch always lies within the signed bytes.
+    /// The moduli and the validity ranges of `keys` are witnessed and returned to be exposed as public inputs, whose values are [`key_epoch_instances`] of `keys`, while the epoch and the chosen key stay hidden.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * public_key - an assigned public key used for the verification.
+    /// * msg - signed message bytes.
+    /// * signature - a pkcs1v15 signature to be verified.
+    /// * keys - the allowed keys.
+    /// * epoch_offset - the index of the first byte of the epoch in `msg`.
+    ///
+    /// # Return values
+    /// Returns the assigned bit as `AssignedValue<F>`, the assigned values of the key set, and the assigned bytes of the resulting hash.
+    /// If `signature` is valid for `public_key` and `msg` and `public_key` is a key of `keys` active in the epoch of `msg`, the bit is equivalent to one.
+    /// Otherwise, the bit is equivalent to zero.
+    /// Returns [`Error::Synthesis`] if `keys` is empty or the epoch exceeds `msg`.
+    pub fn verify_pkcs1v15_with_key_epoch<'a, 'b: 'a>(
+        &'a mut self,
+        ctx: &mut Context<'b, F>,
+        public_key: &AssignedRSAPublicKey<'b, F>,
+        msg: &'a [u8],
+        signature: &AssignedRSASignature<'b, F>,
+        keys: &[KeyEpoch],
+        epoch_offset: usize,
+    ) -> Result<
+        (
+            AssignedValue<'b, F>,
+            Vec<AssignedValue<'b, F>>,
+            Vec<AssignedValue<'b, F>>,
+        ),
+        Error,
+    > {
+        if keys.is_empty() {
+            return Err(Error::Synthesis);
+        }
+        // 1. Verify the signature and extract the epoch, which is less than 2^64 since the message bytes are range-checked by the SHA256 chip.
+        // The extractor fails unless the epoch ends within `msg`, and the hashed length is constrained to be at least `msg.len()`, so it is at least `epoch_offset + KEY_EPOCH_BYTES` and the epoch is never read from the SHA256 padding.
+        let extractor = MessageFieldExtractor::new(
+            self.rsa_config.gate().clone(),
+            vec![(epoch_offset, KEY_EPOCH_BYTES)],
+        );
+        let (is_sign_valid, epoch, hashed_bytes) =
+            self.verify_pkcs1v15_and_consume(ctx, public_key, msg, signature, &extractor)?;
+        let epoch = &epoch[0];
+        let rsa = self.rsa_config.clone();
+        let biguint = rsa.biguint_config();
+        let gate = rsa.gate();
+        let range = rsa.range();
+        let epoch_bits = 8 * KEY_EPOCH_BYTES;
+        let n_bits = public_key.n.num_limbs() * biguint.limb_bits();
+        // 2. Compute the bit indicating whether `public_key` is each key and active in the epoch.
+        let mut key_set = vec![];
+        let mut is_allowed = gate.load_constant(ctx, F::zero());
+        for key in keys.iter() {
+            let n = biguint.assign_integer(ctx, Value::known(key.n.clone()), n_bits)?;
+            let active_from = gate.load_witness(ctx, Value::known(F::from(key.active_from)));
+            let active_until = gate.load_witness(ctx, Value::known(F::from(key.active_until)));
+            range.range_check(ctx, &active_from, epoch_bits);
+            range.range_check(ctx, &active_until, epoch_bits);
+            let is_key = biguint.is_equal_fresh(ctx, &public_key.n, &n)?;
+            let is_before = range.is_less_than(
+                ctx,
+                QuantumCell::Existing(epoch),
+                QuantumCell::Existing(&active_from),
+                epoch_bits,
+            );
+            let is_started = gate.not(ctx, QuantumCell::Existing(&is_before));
+            let is_unexpired = range.is_less_than(
+                ctx,
+                QuantumCell::Existing(epoch),
+                QuantumCell::Existing(&active_until),
+                epoch_bits,
+            );
+            let is_active = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_started),
+                QuantumCell::Existing(&is_unexpired),
+            );
+            let is_active_key = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_key),
+                QuantumCell::Existing(&is_active),
+            );
+            is_allowed = gate.or(
+                ctx,
+                QuantumCell::Existing(&is_allowed),
+                QuantumCell::Existing(&is_active_key),
+            );
+            key_set.extend(n.limbs().iter().cloned());
+            key_set.push(active_from);
+            key_set.push(active_until);
+        }
+        let is_valid = gate.and(
+            ctx,
+            QuantumCell::Existing(&is_sign_valid),
+            QuantumCell::Existing(&is_allowed),
+        );
+        Ok((is_valid, key_set, hashed_bytes))
+    }
+
+    /// Packs the bytes `msg[offset..offset + len]` of the signed message into field elements to be exposed as public inputs.
+    ///
+    /// The range is split into chunks of [`MESSAGE_FIELD_BYTES_PER_ELEMENT`] bytes from `offset`, and each chunk is packed in big-endian order.
+    /// For example, an 8 bytes big-endian timestamp is packed into one element equal to the timestamp, which an on-chain verifier can compare with the current time.
+    /// The bytes are not range-checked again, so `msg` must be the assigned bytes bound to the signature, e.g., those given to a [`MessageConsumer`] by [`RSASignatureVerifier::verify_pkcs1v15_and_consume`].
+    /// Use [`MessageFieldExtractor`] to extract the ranges in that consumer.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * msg - the assigned bytes of the signed message.
+    /// * offset - the index of the first byte of the range.
+    /// * len - the number of bytes of the range.
+    ///
+    /// # Return values
+    /// Returns the packed elements in order, or [`Error::Synthesis`] if `len` is zero or the range exceeds `msg`.
+    pub fn extract_message_field<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        msg: &[AssignedValue<'v, F>],
+        offset: usize,
+        len: usize,
+    ) -> Result<Vec<AssignedValue<'v, F>>, Error> {
+        pack_message_field(self.rsa_config.gate(), ctx, msg, offset, len)
+    }
+
+    /// Given a RSA public key, a HMAC key, message bytes, and a pkcs1v15 signature, verifies the signature over the HMAC-SHA256 tag of the message, i.e., over `SHA256(HMAC-SHA256(key, msg))`.
+    ///
+    /// The tag is computed with the SHA256 chip as `SHA256((K0 ^ opad) || SHA256((K0 ^ ipad) || msg))` of RFC 2104, where `K0` is `key` right-padded with zeros to [`HMAC_SHA256_BLOCK_LEN`] bytes, or the SHA256 hash of `key` padded in the same way if `key` is longer than that.
+    /// The chip therefore hashes `key` if it is longer than [`HMAC_SHA256_BLOCK_LEN`] bytes, `64 + msg.len()` bytes, 96 bytes, and the 32 bytes of the tag in this order, and its maximum byte sizes must cover these lengths.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * public_key - an assigned public key used for the verification.
+    /// * key - the HMAC key, which is only witnessed.
+    /// * msg - message bytes authenticated by the HMAC.
+    /// * signature - a pkcs1v15 signature to be verified.
+    ///
+    /// # Return values
+    /// Returns the assigned bit as `AssignedValue<F>`, the assigned bytes of the tag, and the assigned bytes of the resulting hash.
+    /// If `signature` is valid for `public_key` and the tag of `msg` under `key`, the bit is equivalent to one.
+    /// Otherwise, the bit is equivalent to zero.
+    pub fn verify_pkcs1v15_hmac<'a, 'b: 'a>(
+        &'a mut self,
+        ctx: &mut Context<'b, F>,
+        public_key: &AssignedRSAPublicKey<'b, F>,
+        key: &'a [u8],
+        msg: &'a [u8],
+        signature: &AssignedRSASignature<'b, F>,
+    ) -> Result<
+        (
+            AssignedValue<'b, F>,
+            Vec<AssignedValue<'b, F>>,
+            Vec<AssignedValue<'b, F>>,
+        ),
+        Error,
+    > {
+        let rsa = self.rsa_config.clone();
+        let gate = rsa.gate();
+        // 1. Assign the bytes of `K0` before the zero padding, hashing `key` first if it exceeds the block.
+        let (native_key, key_bytes) = if key.len() > HMAC_SHA256_BLOCK_LEN {
+            begin_cost_region(ctx, "sha256 rounds");
+            let result = self.sha256_config.digest(ctx, key, None)?;
+            end_cost_region(ctx);
+            (Sha256::digest(key).to_vec(), result.output_bytes)
+        } else {
+            let key_bytes = key
+                .iter()
+                .map(|byte| gate.load_witness(ctx, Value::known(F::from(*byte as u64))))
+                .collect::<Vec<AssignedValue<F>>>();
+            (key.to_vec(), key_bytes)
+        };
+        // The decomposition into bits also range-checks the witnessed key bytes.
+        let key_bits = key_bytes
+            .iter()
+            .map(|byte| gate.num_to_bits(ctx, byte, 8))
+            .collect::<Vec<Vec<AssignedValue<F>>>>();
+        let padded_key = |pad: u8| {
+            (0..HMAC_SHA256_BLOCK_LEN)
+                .map(|i| native_key.get(i).copied().unwrap_or(0) ^ pad)
+                .collect::<Vec<u8>>()
+        };
+        // 2. Compute the tag with the inner and the outer hashes, binding their blocks to the key bits.
+        let inner_input = [padded_key(HMAC_IPAD), msg.to_vec()].concat();
+        begin_cost_region(ctx, "sha256 rounds");
+        let inner = self.sha256_config.digest(ctx, &inner_input, None)?;
+        end_cost_region(ctx);
+        // The inner input is exactly the key block followed by `msg`.
+        gate.assert_is_const(
+            ctx,
+            &inner.input_len,
+            F::from((HMAC_SHA256_BLOCK_LEN + msg.len()) as u64),
+        );
+        self.assert_hmac_key_block(ctx, &inner.input_bytes, &key_bits, HMAC_IPAD);
+        let outer_input = [padded_key(HMAC_OPAD), Sha256::digest(&inner_input).to_vec()].concat();
+        begin_cost_region(ctx, "sha256 rounds");
+        let outer = self.sha256_config.digest(ctx, &outer_input, None)?;
+        end_cost_region(ctx);
+        // The outer input is exactly the key block followed by the 32 bytes of the inner hash.
+        gate.assert_is_const(
+            ctx,
+            &outer.input_len,
+            F::from((HMAC_SHA256_BLOCK_LEN + 32) as u64),
+        );
+        self.assert_hmac_key_block(ctx, &outer.input_bytes, &key_bits, HMAC_OPAD);
+        for (input, inner_byte) in outer.input_bytes[HMAC_SHA256_BLOCK_LEN..]
+            .iter()
+            .zip(inner.output_bytes.iter())
+        {
+            gate.assert_equal(
+                ctx,
+                QuantumCell::Existing(input),
+                QuantumCell::Existing(inner_byte),
+            );
+        }
+        let tag = outer.output_bytes;
+        // 3. Hash the tag and verify the signature for the hash.
+        begin_cost_region(ctx, "sha256 rounds");
+        let result = self
+            .sha256_config
+            .digest(ctx, &Sha256::digest(&outer_input), None)?;
+        end_cost_region(ctx);
+        // The hashed message is exactly the 32 bytes of the tag.
+        gate.assert_is_const(ctx, &result.input_len, F::from(32));
+        for (input, tag_byte) in result.input_bytes.iter().zip(tag.iter()) {
+            gate.assert_equal(
+                ctx,
+                QuantumCell::Existing(input),
+                QuantumCell::Existing(tag_byte),
+            );
+        }
+        let hashed_bytes = result.output_bytes;
+        let is_sign_valid =
+            self.verify_hashed_bytes(ctx, public_key, hashed_bytes.clone(), signature)?;
+        Ok((is_sign_valid, tag, hashed_bytes))
+    }
+
+    /// Given a RSA public key, a pkcs1v15 signature over the head of a SHA256 hash chain, and an entry of the chain, verifies the signature and that `entry` hashes forward to the head in `steps` steps.
+    ///
+    /// The chain is `h_0 = entry` and `h_{i+1} = SHA256(h_i)`, whose head `h_steps` is the signed message, so `steps` is the position of `entry` counted back from the head.
+    /// Both `entry` and `steps` are only witnessed, while `max_steps` is fixed in the circuit: `entry` is hashed forward `max_steps` times, and the head is selected from the links by `steps`.
+    /// The constraints are not satisfied if `steps` exceeds `max_steps`, so one circuit verifies the entries at any position up to `max_steps`.
+    /// The SHA256 chip digests `max_steps + 1` messages, so it needs as many maximum byte sizes: the first one fitting `entry`, the others at least 64 for the 32 bytes links, and the last one fitting the longer of `entry` and a link.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * public_key - an assigned public key used for the verification.
+    /// * signature - a pkcs1v15 signature over the head of the chain.
+    /// * entry - an entry of the chain.
+    /// * steps - the number of hashes from `entry` to the head.
+    /// * max_steps - the maximum of `steps`.
+    ///
+    /// # Return values
+    /// Returns the assigned bit as `AssignedValue<F>`, the assigned bytes of the head, the assigned bytes of the hash of the head, and the assigned `steps`.
+    /// The head is `entry` if `steps` is zero and 32 bytes otherwise, followed by its SHA256 padding up to the longest link, i.e., the longer of `entry` and 32 bytes unless `max_steps` is zero.
+    /// If `signature` is valid for `public_key` and the head [`hashchain_head`] of `entry`, the bit is equivalent to one.
+    /// Otherwise, the bit is equivalent to zero.
+    pub fn verify_signed_hashchain<'a, 'b: 'a>(
+        &'a mut self,
+        ctx: &mut Context<'b, F>,
+        public_key: &AssignedRSAPublicKey<'b, F>,
+        signature: &AssignedRSASignature<'b, F>,
+        entry: &'a [u8],
+        steps: usize,
+        max_steps: usize,
+    ) -> Result<
+        (
+            AssignedValue<'b, F>,
+            Vec<AssignedValue<'b, F>>,
+            Vec<AssignedValue<'b, F>>,
+            AssignedValue<'b, F>,
+        ),
+        Error,
+    > {
+        let rsa = self.rsa_config.clone();
+        let gate = rsa.gate();
+        // 1. Hash `entry` forward `max_steps` times, which assigns the links `h_0` to `h_max_steps`.
+        let mut links: Vec<Vec<AssignedValue<'b, F>>> = vec![];
+        let mut link = entry.to_vec();
+        for step in 0..max_steps {
+            begin_cost_region(ctx, "sha256 rounds");
+            let result = self.sha256_config.digest(ctx, &link, None)?;
+            end_cost_region(ctx);
+            if step == 0 {
+                gate.assert_is_const(ctx, &result.input_len, F::from(entry.len() as u64));
+                links.push(result.input_bytes[0..entry.len()].to_vec());
+            } else {
+                // The input of the digest is the output of the previous one, i.e., exactly the 32 bytes of the previous link.
+                gate.assert_is_const(ctx, &result.input_len, F::from(32));
+                for (input, prev_byte) in result.input_bytes.iter().zip(links[step].iter()) {
+                    gate.assert_equal(
+                        ctx,
+                        QuantumCell::Existing(input),
+                        QuantumCell::Existing(prev_byte),
+                    );
+                }
+            }
+            links.push(result.output_bytes);
+            link = Sha256::digest(&link).to_vec();
+        }
+        if links.is_empty() {
+            // Without any step, `entry` is bound to the chain only as the head.
+            links.push(
+                entry
+                    .iter()
+                    .map(|byte| gate.load_witness(ctx, Value::known(F::from(*byte as u64))))
+                    .collect(),
+            );
+        }
+
+        // 2. Assign `steps` with the indicators of the links, whose sum is one only if `steps` is at most `max_steps`.
+        let assigned_steps = gate.load_witness(ctx, Value::known(F::from(steps as u64)));
+        let is_steps = (0..=max_steps)
+            .map(|i| {
+                gate.is_equal(
+                    ctx,
+                    QuantumCell::Existing(&assigned_steps),
+                    QuantumCell::Constant(F::from(i as u64)),
+                )
+            })
+            .collect::<Vec<AssignedValue<F>>>();
+        let is_valid_steps = gate.sum(ctx, is_steps.iter().map(QuantumCell::Existing));
+        gate.assert_is_const(ctx, &is_valid_steps, F::one());
+
+        // 3. Hash the head for the signature, constraining the input of the digest to be the link selected by the indicators.
+        begin_cost_region(ctx, "sha256 rounds");
+        let result = self
+            .sha256_config
+            .digest(ctx, &hashchain_head(entry, steps), None)?;
+        end_cost_region(ctx);
+        let head_len = gate.inner_product(
+            ctx,
+            links
+                .iter()
+                .map(|link| QuantumCell::Constant(F::from(link.len() as u64)))
+                .collect(),
+            is_steps.iter().map(QuantumCell::Existing).collect(),
+        );
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(&result.input_len),
+            QuantumCell::Existing(&head_len),
+        );
+        let max_head_len = links.iter().map(Vec::len).max().unwrap_or(0);
+        for (j, input) in result.input_bytes[0..max_head_len].iter().enumerate() {
+            let selected = gate.inner_product(
+                ctx,
+                links
+                    .iter()
+                    .map(|link| match link.get(j) {
+                        Some(byte) => QuantumCell::Existing(byte),
+                        None => QuantumCell::Constant(F::zero()),
+                    })
+                    .collect(),
+                is_steps.iter().map(QuantumCell::Existing).collect(),
+            );
+            if links.iter().all(|link| j < link.len()) {
+                gate.assert_equal(
+                    ctx,
+                    QuantumCell::Existing(input),
+                    QuantumCell::Existing(&selected),
+                );
+                continue;
+            }
+            // The byte is constrained only if the selected link is long enough to have it, and is the SHA256 padding otherwise.
+            let has_byte = gate.inner_product(
+                ctx,
+                links
+                    .iter()
+                    .map(|link| QuantumCell::Constant(F::from((j < link.len()) as u64)))
+                    .collect(),
+                is_steps.iter().map(QuantumCell::Existing).collect(),
+            );
+            let diff = gate.sub(
+                ctx,
+                QuantumCell::Existing(input),
+                QuantumCell::Existing(&selected),
+            );
+            let masked_diff = gate.mul(
+                ctx,
+                QuantumCell::Existing(&diff),
+                QuantumCell::Existing(&has_byte),
+            );
+            gate.assert_is_const(ctx, &masked_diff, F::zero());
+        }
+        let head = result.input_bytes[0..max_head_len].to_vec();
+        let hashed_bytes = result.output_bytes;
+        let is_sign_valid =
+            self.verify_hashed_bytes(ctx, public_key, hashed_bytes.clone(), signature)?;
+        Ok((is_sign_valid, head, hashed_bytes, assigned_steps))
+    }
+
+    /// Constrains the first [`HMAC_SHA256_BLOCK_LEN`] bytes of `input_bytes` to be `K0 ^ pad`, where `K0` is the key of `key_bits` padded with zeros.
+    fn assert_hmac_key_block<'b>(
+        &self,
+        ctx: &mut Context<'b, F>,
+        input_bytes: &[AssignedValue<'b, F>],
+        key_bits: &[Vec<AssignedValue<'b, F>>],
+        pad: u8,
+    ) {
+        let gate = self.rsa_config.gate();
+        for (i, input) in input_bytes[..HMAC_SHA256_BLOCK_LEN].iter().enumerate() {
+            let bits = match key_bits.get(i) {
+                Some(bits) => bits,
+                None => {
+                    gate.assert_is_const(ctx, input, F::from(pad as u64));
+                    continue;
+                }
+            };
+            // XORing a bit `b` with a constant bit `c` gives `c + (1 - 2c) * b`, so `byte ^ pad` is linear in the bits of `byte`.
+            let coeffs = (0..8)
+                .map(|j| {
+                    let coeff = F::from(1u64 << j);
+                    QuantumCell::Constant(if (pad >> j) & 1 == 1 { -coeff } else { coeff })
+                })
+                .collect();
+            let xored = gate.inner_product(
+                ctx,
+                bits.iter().map(QuantumCell::Existing).collect(),
+                coeffs,
+            );
+            let xored = gate.add(
+                ctx,
+                QuantumCell::Existing(&xored),
+                QuantumCell::Constant(F::from(pad as u64)),
+            );
+            gate.assert_equal(
+                ctx,
+                QuantumCell::Existing(input),
+                QuantumCell::Existing(&xored),
+            );
+        }
+    }
+
+    /// Constrains the length `input_len` of a message hashed by the SHA256 chip to be at least `min_len`, so that its first `min_len` assigned bytes are bytes of the signed message rather than its SHA256 padding.
+    fn assert_input_len_at_least<'b>(
+        &self,
+        ctx: &mut Context<'b, F>,
+        input_len: &AssignedValue<'b, F>,
+        min_len: usize,
+    ) {
+        if min_len == 0 {
+            return;
+        }
+        // The range check of `(min_len - 1) + 2^INPUT_LEN_BITS - input_len` fails for any `input_len` below `min_len`, even one wrapping around the field.
+        self.rsa_config.range().check_less_than(
+            ctx,
+            QuantumCell::Constant(F::from((min_len - 1) as u64)),
+            QuantumCell::Existing(input_len),
+            INPUT_LEN_BITS,
+        );
+    }
+
+    /// Packs the big-endian SHA256 hash bytes into 64 bit limbs and verifies the pkcs1v15 signature for them.
+    fn verify_hashed_bytes<'b>(
+        &mut self,
+        ctx: &mut Context<'b, F>,
+        public_key: &AssignedRSAPublicKey<'b, F>,
+        mut hashed_bytes: Vec<AssignedValue<'b, F>>,
+        signature: &AssignedRSASignature<'b, F>,
+    ) -> Result<AssignedValue<'b, F>, Error> {
+        self.record_signature(signature);
+        let rsa = self.rsa_config.clone();
+        let biguint = &rsa.biguint_config();
+        hashed_bytes.reverse();
+        let bytes_bits = hashed_bytes.len() * 8;
+        let limb_bits = biguint.limb_bits();
+        let limb_bytes = limb_bits / 8;
+        let mut hashed_u64s = vec![];
+        let bases = (0..limb_bytes)
+            .map(|i| F::from((1u64 << (8 * i)) as u64))
+            .map(QuantumCell::Constant)
+            .collect::<Vec<QuantumCell<F>>>();
+        for i in 0..(bytes_bits / limb_bits) {
+            let left = hashed_bytes[limb_bytes * i..limb_bytes * (i + 1)]
+                .iter()
+                .map(QuantumCell::Existing)
+                .collect::<Vec<QuantumCell<F>>>();
+            let sum = biguint.gate().inner_product(ctx, left, bases.clone());
+            hashed_u64s.push(sum);
+        }
+        rsa.verify_pkcs1v15_signature(ctx, public_key, &hashed_u64s, signature)
+    }
+}
+
+#[cfg(feature = "sha256")]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::big_uint::decompose_biguint;
+    use halo2_base::halo2_proofs::{
+        circuit::{Cell, Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, Column, ConstraintSystem, Instance},
+    };
+    use halo2_base::{gates::range::RangeStrategy::Vertical, ContextParams, SKIP_FIRST_PASS};
+    use halo2_dynamic_sha256::Sha256DynamicConfig;
+    use hmac::{Hmac, Mac};
+
+    use num_bigint::RandomBits;
+    use num_traits::FromPrimitive;
+    use rand::{rngs::OsRng, thread_rng, Rng};
+    use rsa::{
+        pkcs1v15::*, signature::SignatureEncoding, signature::Signer, traits::PaddingScheme,
+        traits::PublicKeyParts, Pkcs1v15Sign, RsaPrivateKey, RsaPublicKey,
+    };
+    use sha2::{Digest, Sha256};
+
+    /// The hash chip of the tests of the RSA layer, which skips the SHA256 rounds with the `fast-test-hash` feature.
+    #[cfg(feature = "fast-test-hash")]
+    type TestSha256Config<F> = FastTestSha256Chip<F>;
+    #[cfg(not(feature = "fast-test-hash"))]
+    type TestSha256Config<F> = Sha256DynamicConfig<F>;
+
+    #[cfg(feature = "fast-test-hash")]
+    fn configure_test_sha256<F: PrimeField>(
+        _meta: &mut ConstraintSystem<F>,
+        _max_byte_sizes: Vec<usize>,
+        range_config: RangeConfig<F>,
+        _lookup_bits: usize,
+        _num_advice: usize,
+    ) -> TestSha256Config<F> {
+        FastTestSha256Chip::construct(range_config)
+    }
+
+    #[cfg(not(feature = "fast-test-hash"))]
+    fn configure_test_sha256<F: PrimeField>(
+        meta: &mut ConstraintSystem<F>,
+        max_byte_sizes: Vec<usize>,
+        range_config: RangeConfig<F>,
+        lookup_bits: usize,
+        num_advice: usize,
+    ) -> TestSha256Config<F> {
+        Sha256DynamicConfig::configure(
+            meta,
+            max_byte_sizes,
+            range_config,
+            lookup_bits,
+            num_advice,
+            true,
+        )
+    }
+
+    /// A hash chip that returns a fresh witness as the length of its `call`-th digest, as a prover forging the length of a hashed message would.
+    #[derive(Debug, Clone)]
+    struct TamperedLenSha256<F: PrimeField, H: Sha256Instructions<F> = TestSha256Config<F>> {
+        inner: H,
+        gate: FlexGateConfig<F>,
+        // The index of the digest call and the forged length.
+        tampered: Option<(usize, u64)>,
+        num_calls: usize,
+    }
+
+    impl<F: PrimeField, H: Sha256Instructions<F>> TamperedLenSha256<F, H> {
+        fn new(inner: H, gate: FlexGateConfig<F>, tampered: Option<(usize, u64)>) -> Self {
+            Self {
+                inner,
+                gate,
+                tampered,
+                num_calls: 0,
+            }
+        }
+    }
+
+    impl<F: PrimeField, H: Sha256Instructions<F>> Sha256Instructions<F> for TamperedLenSha256<F, H> {
+        fn digest<'a, 'b: 'a>(
+            &'a mut self,
+            ctx: &mut Context<'b, F>,
+            input: &'a [u8],
+            precomputed_input_len: Option<usize>,
+        ) -> Result<AssignedHashResult<'b, F>, Error> {
+            let mut result = self.inner.digest(ctx, input, precomputed_input_len)?;
+            match self.tampered {
+                Some((call, len)) if call == self.num_calls => {
+                    result.input_len = self.gate.load_witness(ctx, Value::known(F::from(len)));
+                }
+                _ => {}
+            }
+            self.num_calls += 1;
+            Ok(result)
+        }
+    }
+
+    macro_rules! impl_rsa_signature_test_circuit {
+        ($config_name:ident, $circuit_name:ident, $test_fn_name:ident, $bits_len:expr, $msg_len:expr, $num_advice:expr, $num_lookup_advice:expr, $lookup_bits:expr, $k:expr, $should_be_error:expr, $( $synth:tt )*) => {
+            #[derive(Debug,Clone)]
+            struct $config_name<F:PrimeField> {
+                rsa_config: RSAConfig<F>,
+                sha256_config: TestSha256Config<F>,
+                n_instance: Column<Instance>,
+                hash_instance: Column<Instance>
+            }
+
+            struct $circuit_name<F: PrimeField> {
+                private_key: RsaPrivateKey,
+                public_key: RsaPublicKey,
+                msg: Vec<u8>,
+                _f: PhantomData<F>
+            }
+
+            impl<F: PrimeField> $circuit_name<F> {
+                const BITS_LEN:usize = $bits_len;
+                const MSG_LEN:usize = $msg_len;
+                const EXP_LIMB_BITS:usize = 5;
+                const DEFAULT_E: u128 = 65537;
+                const NUM_ADVICE:usize = $num_advice;
+                const NUM_FIXED:usize = 1;
+                const NUM_LOOKUP_ADVICE:usize = $num_lookup_advice;
+                const LOOKUP_BITS:usize = $lookup_bits;
+                const SHA256_LOOKUP_BITS:usize = 8;
+                const SHA256_LOOKUP_ADVICE:usize = 8;
             }
 
             impl<F: PrimeField> Circuit<F> for $circuit_name<F> {
@@ -309,7 +1585,7 @@ mod test {
                     let range_config = RangeConfig::configure(meta,Vertical, &[Self::NUM_ADVICE], &[Self::NUM_LOOKUP_ADVICE], Self::NUM_FIXED, Self::LOOKUP_BITS, 0, $k);
                     let bigint_config = BigUintConfig::construct(range_config.clone(), 64);
                     let rsa_config = RSAConfig::construct(bigint_config, Self::BITS_LEN, Self::EXP_LIMB_BITS);
-                    let sha256_config = Sha256DynamicConfig::configure(meta,vec![Self::MSG_LEN], range_config,Self::SHA256_LOOKUP_BITS,Self::SHA256_LOOKUP_ADVICE,true);
+                    let sha256_config = configure_test_sha256(meta,vec![Self::MSG_LEN], range_config,Self::SHA256_LOOKUP_BITS,Self::SHA256_LOOKUP_ADVICE);
                     let n_instance = meta.instance_column();
                     let hash_instance = meta.instance_column();
                     meta.enable_equality(n_instance);
@@ -541,17 +1817,50 @@ mod test {
         }
     );
 
-    impl_rsa_signature_test_circuit!(
-        TestBadRSASignatureWithHashConfig1,
-        TestBadRSASignatureWithHashCircuit,
-        test_bad_rsa_signature_with_hash_circuit1,
-        2048,
-        1024,
-        80,
-        16,
-        12,
-        15,
-        true,
+    #[derive(Debug, Clone)]
+    struct TestSplitConfig<F: PrimeField> {
+        rsa_config: RSAConfig<F>,
+        sha256_config: TestSha256Config<F>,
+        header_instance: Column<Instance>,
+    }
+
+    struct TestSplitCircuit<F: PrimeField> {
+        private_key: RsaPrivateKey,
+        msg: Vec<u8>,
+        header_len: usize,
+        // If given, the length of the hashed message is forged as `TamperedLenSha256` does.
+        tampered_len: Option<u64>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestSplitCircuit<F> {
+        const BITS_LEN: usize = 1024;
+        const K: usize = 15;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestSplitCircuit<F> {
+        type Config = TestSplitConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config =
+                RangeConfig::configure(meta, Vertical, &[80], &[16], 1, 12, 0, Self::K);
+            let biguint_config = BigUintConfig::construct(range_config.clone(), 64);
+            let rsa_config = RSAConfig::construct(biguint_config, Self::BITS_LEN, 5);
+            let sha256_config = configure_test_sha256(meta, vec![192], range_config, 8, 8);
+            let header_instance = meta.instance_column();
+            meta.enable_equality(header_instance);
+            Self::Config {
+                rsa_config,
+                sha256_config,
+                header_instance,
+            }
+        }
+
         fn synthesize(
             &self,
             config: Self::Config,
@@ -561,72 +1870,1420 @@ mod test {
             config.sha256_config.load(&mut layouter)?;
             biguint_config.range().load_lookup_table(&mut layouter)?;
             let mut first_pass = SKIP_FIRST_PASS;
-            let (public_key_cells, hashed_msg_cells) = layouter.assign_region(
-                || "random rsa modpow test with 2048 bits public keys",
+            let header_cells = layouter.assign_region(
+                || "rsa signature over a public header and a private body",
                 |region| {
                     if first_pass {
                         first_pass = false;
-                        return Ok((vec![], vec![]));
+                        return Ok(vec![]);
                     }
 
                     let mut aux = biguint_config.new_context(region);
                     let ctx = &mut aux;
-                    // let hashed_msg = Sha256::digest(&self.msg);
-                    // let padding = PaddingScheme::PKCS1v15Sign {
-                    //     hash: Some(Hash::SHA2_256),
-                    // };
-                    let invalid_msg = [0; 32];
                     let signing_key =
                         SigningKey::<rsa::sha2::Sha256>::new(self.private_key.clone());
-                    let mut sign = signing_key.sign(&invalid_msg).to_vec();
-                    sign.reverse();
-                    let sign_big = BigUint::from_bytes_le(&sign);
-                    let sign = config
-                        .rsa_config
-                        .assign_signature(ctx, RSASignature::new(Value::known(sign_big)))?;
-                    let n_big =
-                        BigUint::from_radix_le(&self.public_key.n().clone().to_radix_le(16), 16)
-                            .unwrap();
-                    let e_fix = RSAPubE::Fix(BigUint::from(Self::DEFAULT_E));
+                    let sign = signing_key.sign(&self.msg).to_vec();
+                    let sign = config.rsa_config.assign_signature(
+                        ctx,
+                        RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+                    )?;
+                    let n_big = BigUint::from_bytes_be(&self.private_key.n().to_bytes_be());
+                    let e_fix = RSAPubE::Fix(BigUint::from(65537u32));
                     let public_key = config
                         .rsa_config
                         .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
-                    let mut verifier = RSASignatureVerifier::new(
-                        config.rsa_config.clone(),
+                    let sha256 = TamperedLenSha256::new(
                         config.sha256_config.clone(),
+                        biguint_config.gate().clone(),
+                        self.tampered_len.map(|len| (0, len)),
                     );
-                    let (is_valid, hashed_msg) =
-                        verifier.verify_pkcs1v15_signature(ctx, &public_key, &self.msg, &sign)?;
+                    let mut verifier = RSASignatureVerifier::new(config.rsa_config.clone(), sha256);
+                    let (header, body) = self.msg.split_at(self.header_len);
+                    let (is_valid, public_header, _) =
+                        verifier.verify_pkcs1v15_split(ctx, &public_key, &sign, header, body)?;
+                    assert_eq!(public_header.len(), header.len());
                     biguint_config
                         .gate()
                         .assert_is_const(ctx, &is_valid, F::one());
                     biguint_config.range().finalize(ctx);
-                    {
-                        println!("total advice cells: {}", ctx.total_advice);
-                        let const_rows = ctx.total_fixed + 1;
-                        println!("maximum rows used by a fixed column: {const_rows}");
-                        println!("lookup cells used: {}", ctx.cells_to_lookup.len());
-                    }
-                    let public_key_cells = public_key
-                        .n
-                        .limbs()
-                        .into_iter()
-                        .map(|v| v.cell())
-                        .collect::<Vec<Cell>>();
-                    let hashed_msg_cells = hashed_msg
+                    Ok(public_header
                         .into_iter()
                         .map(|v| v.cell())
-                        .collect::<Vec<Cell>>();
-                    Ok((public_key_cells, hashed_msg_cells))
+                        .collect::<Vec<Cell>>())
                 },
             )?;
-            for (i, cell) in public_key_cells.into_iter().enumerate() {
-                layouter.constrain_instance(cell, config.n_instance, i)?;
-            }
-            for (i, cell) in hashed_msg_cells.into_iter().enumerate() {
-                layouter.constrain_instance(cell, config.hash_instance, i)?;
+            for (i, cell) in header_cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell, config.header_instance, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn run_split_circuit(
+        msg: &[u8],
+        header_len: usize,
+        tampered_len: Option<u64>,
+        header: &[u8],
+    ) -> bool {
+        let private_key =
+            RsaPrivateKey::new(&mut thread_rng(), 1024).expect("failed to generate a key");
+        let circuit = TestSplitCircuit::<Fr> {
+            private_key,
+            msg: msg.to_vec(),
+            header_len,
+            tampered_len,
+            _f: PhantomData,
+        };
+        let instances = header.iter().map(|byte| Fr::from(*byte as u64)).collect();
+        MockProver::run(TestSplitCircuit::<Fr>::K as u32, &circuit, vec![instances])
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    #[test]
+    fn test_rsa_signature_split_reveals_header() {
+        let msg = (0..128).map(|_| thread_rng().gen()).collect::<Vec<u8>>();
+        let (header, _) = msg.split_at(64);
+        assert!(run_split_circuit(&msg, 64, None, header));
+        let mut other_header = header.to_vec();
+        other_header[0] ^= 1;
+        assert!(!run_split_circuit(&msg, 64, None, &other_header));
+    }
+
+    #[test]
+    fn test_rsa_signature_split_rejects_short_input_len() {
+        let msg = (0..128).map(|_| thread_rng().gen()).collect::<Vec<u8>>();
+        let (header, _) = msg.split_at(64);
+        // The whole message or the header alone may be hashed, but not a message shorter than the header.
+        assert!(run_split_circuit(&msg, 64, Some(128), header));
+        assert!(run_split_circuit(&msg, 64, Some(64), header));
+        assert!(!run_split_circuit(&msg, 64, Some(63), header));
+        assert!(!run_split_circuit(&msg, 64, Some(0), header));
+    }
+
+    impl_rsa_signature_test_circuit!(
+        TestRSASignatureHashPublicConfig1,
+        TestRSASignatureHashPublicCircuit1,
+        test_rsa_signature_hash_public_circuit1,
+        2048,
+        1024,
+        80,
+        16,
+        12,
+        15,
+        false,
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let biguint_config = config.rsa_config.biguint_config();
+            config.sha256_config.load(&mut layouter)?;
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let (public_key_cells, hashed_msg_cells) = layouter.assign_region(
+                || "rsa signature over a hidden message with a public hash",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok((vec![], vec![]));
+                    }
+
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    let signing_key =
+                        SigningKey::<rsa::sha2::Sha256>::new(self.private_key.clone());
+                    let sign = signing_key.sign(&self.msg).to_vec();
+                    let sign_big = BigUint::from_bytes_be(&sign);
+                    let sign = config
+                        .rsa_config
+                        .assign_signature(ctx, RSASignature::new(Value::known(sign_big)))?;
+                    let n_big =
+                        BigUint::from_radix_le(&self.public_key.n().clone().to_radix_le(16), 16)
+                            .unwrap();
+                    let e_fix = RSAPubE::Fix(BigUint::from(Self::DEFAULT_E));
+                    let public_key = config
+                        .rsa_config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                    let mut verifier = RSASignatureVerifier::new(
+                        config.rsa_config.clone(),
+                        config.sha256_config.clone(),
+                    );
+                    let hashed_msg =
+                        verifier.verify_pkcs1v15_hash_public(ctx, &public_key, &self.msg, &sign)?;
+                    biguint_config.range().finalize(ctx);
+                    let public_key_cells = public_key
+                        .n
+                        .limbs()
+                        .into_iter()
+                        .map(|v| v.cell())
+                        .collect::<Vec<Cell>>();
+                    let hashed_msg_cells = hashed_msg
+                        .into_iter()
+                        .map(|v| v.cell())
+                        .collect::<Vec<Cell>>();
+                    Ok((public_key_cells, hashed_msg_cells))
+                },
+            )?;
+            for (i, cell) in public_key_cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell, config.n_instance, i)?;
+            }
+            for (i, cell) in hashed_msg_cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell, config.hash_instance, i)?;
+            }
+            Ok(())
+        }
+    );
+
+    impl_rsa_signature_test_circuit!(
+        TestBadRSASignatureHashPublicConfig1,
+        TestBadRSASignatureHashPublicCircuit1,
+        test_bad_rsa_signature_hash_public_circuit1,
+        2048,
+        1024,
+        80,
+        16,
+        12,
+        15,
+        true,
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let biguint_config = config.rsa_config.biguint_config();
+            config.sha256_config.load(&mut layouter)?;
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let (public_key_cells, hashed_msg_cells) = layouter.assign_region(
+                || "rsa signature over a hidden message with a public hash",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok((vec![], vec![]));
+                    }
+
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    // Sign a message different from the hidden one.
+                    let mut other_msg = self.msg.clone();
+                    other_msg[0] ^= 1;
+                    let signing_key =
+                        SigningKey::<rsa::sha2::Sha256>::new(self.private_key.clone());
+                    let sign = signing_key.sign(&other_msg).to_vec();
+                    let sign_big = BigUint::from_bytes_be(&sign);
+                    let sign = config
+                        .rsa_config
+                        .assign_signature(ctx, RSASignature::new(Value::known(sign_big)))?;
+                    let n_big =
+                        BigUint::from_radix_le(&self.public_key.n().clone().to_radix_le(16), 16)
+                            .unwrap();
+                    let e_fix = RSAPubE::Fix(BigUint::from(Self::DEFAULT_E));
+                    let public_key = config
+                        .rsa_config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                    let mut verifier = RSASignatureVerifier::new(
+                        config.rsa_config.clone(),
+                        config.sha256_config.clone(),
+                    );
+                    let hashed_msg =
+                        verifier.verify_pkcs1v15_hash_public(ctx, &public_key, &self.msg, &sign)?;
+                    biguint_config.range().finalize(ctx);
+                    let public_key_cells = public_key
+                        .n
+                        .limbs()
+                        .into_iter()
+                        .map(|v| v.cell())
+                        .collect::<Vec<Cell>>();
+                    let hashed_msg_cells = hashed_msg
+                        .into_iter()
+                        .map(|v| v.cell())
+                        .collect::<Vec<Cell>>();
+                    Ok((public_key_cells, hashed_msg_cells))
+                },
+            )?;
+            for (i, cell) in public_key_cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell, config.n_instance, i)?;
+            }
+            for (i, cell) in hashed_msg_cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell, config.hash_instance, i)?;
+            }
+            Ok(())
+        }
+    );
+
+    impl_rsa_signature_test_circuit!(
+        TestBadRSASignatureWithHashConfig1,
+        TestBadRSASignatureWithHashCircuit,
+        test_bad_rsa_signature_with_hash_circuit1,
+        2048,
+        1024,
+        80,
+        16,
+        12,
+        15,
+        true,
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let biguint_config = config.rsa_config.biguint_config();
+            config.sha256_config.load(&mut layouter)?;
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let (public_key_cells, hashed_msg_cells) = layouter.assign_region(
+                || "random rsa modpow test with 2048 bits public keys",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok((vec![], vec![]));
+                    }
+
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    // let hashed_msg = Sha256::digest(&self.msg);
+                    // let padding = PaddingScheme::PKCS1v15Sign {
+                    //     hash: Some(Hash::SHA2_256),
+                    // };
+                    let invalid_msg = [0; 32];
+                    let signing_key =
+                        SigningKey::<rsa::sha2::Sha256>::new(self.private_key.clone());
+                    let mut sign = signing_key.sign(&invalid_msg).to_vec();
+                    sign.reverse();
+                    let sign_big = BigUint::from_bytes_le(&sign);
+                    let sign = config
+                        .rsa_config
+                        .assign_signature(ctx, RSASignature::new(Value::known(sign_big)))?;
+                    let n_big =
+                        BigUint::from_radix_le(&self.public_key.n().clone().to_radix_le(16), 16)
+                            .unwrap();
+                    let e_fix = RSAPubE::Fix(BigUint::from(Self::DEFAULT_E));
+                    let public_key = config
+                        .rsa_config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                    let mut verifier = RSASignatureVerifier::new(
+                        config.rsa_config.clone(),
+                        config.sha256_config.clone(),
+                    );
+                    let (is_valid, hashed_msg) =
+                        verifier.verify_pkcs1v15_signature(ctx, &public_key, &self.msg, &sign)?;
+                    biguint_config
+                        .gate()
+                        .assert_is_const(ctx, &is_valid, F::one());
+                    biguint_config.range().finalize(ctx);
+                    {
+                        println!("total advice cells: {}", ctx.total_advice);
+                        let const_rows = ctx.total_fixed + 1;
+                        println!("maximum rows used by a fixed column: {const_rows}");
+                        println!("lookup cells used: {}", ctx.cells_to_lookup.len());
+                    }
+                    let public_key_cells = public_key
+                        .n
+                        .limbs()
+                        .into_iter()
+                        .map(|v| v.cell())
+                        .collect::<Vec<Cell>>();
+                    let hashed_msg_cells = hashed_msg
+                        .into_iter()
+                        .map(|v| v.cell())
+                        .collect::<Vec<Cell>>();
+                    Ok((public_key_cells, hashed_msg_cells))
+                },
+            )?;
+            for (i, cell) in public_key_cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell, config.n_instance, i)?;
+            }
+            for (i, cell) in hashed_msg_cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell, config.hash_instance, i)?;
             }
             Ok(())
         }
     );
+
+    const ONE_OF_TEMPLATES: [&[u8]; 3] = [b"vote: yes", b"vote: no", b"vote: abstain"];
+
+    #[derive(Debug, Clone)]
+    struct TestOneOfConfig<F: PrimeField> {
+        rsa_config: RSAConfig<F>,
+        sha256_config: TestSha256Config<F>,
+        commitment_instance: Column<Instance>,
+    }
+
+    struct TestOneOfCircuit<F: PrimeField> {
+        private_key: RsaPrivateKey,
+        msg: Vec<u8>,
+        index_salt: Option<[u8; 31]>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestOneOfCircuit<F> {
+        const BITS_LEN: usize = 1024;
+        const K: usize = 15;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestOneOfCircuit<F> {
+        type Config = TestOneOfConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config =
+                RangeConfig::configure(meta, Vertical, &[80], &[16], 1, 12, 0, Self::K);
+            let biguint_config = BigUintConfig::construct(range_config.clone(), 64);
+            let rsa_config = RSAConfig::construct(biguint_config, Self::BITS_LEN, 5);
+            let sha256_config = configure_test_sha256(meta, vec![64], range_config, 8, 8);
+            let commitment_instance = meta.instance_column();
+            meta.enable_equality(commitment_instance);
+            Self::Config {
+                rsa_config,
+                sha256_config,
+                commitment_instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let biguint_config = config.rsa_config.biguint_config();
+            config.sha256_config.load(&mut layouter)?;
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let commitment_cells = layouter.assign_region(
+                || "rsa signature for one of the templates",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(vec![]);
+                    }
+
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    let signing_key =
+                        SigningKey::<rsa::sha2::Sha256>::new(self.private_key.clone());
+                    let sign = signing_key.sign(&self.msg).to_vec();
+                    let sign = config.rsa_config.assign_signature(
+                        ctx,
+                        RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+                    )?;
+                    let n_big = BigUint::from_bytes_be(&self.private_key.n().to_bytes_be());
+                    let e_fix = RSAPubE::Fix(BigUint::from(65537u32));
+                    let public_key = config
+                        .rsa_config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                    let mut verifier = RSASignatureVerifier::new(
+                        config.rsa_config.clone(),
+                        config.sha256_config.clone(),
+                    );
+                    let (is_valid, commitment) = verifier.verify_pkcs1v15_one_of(
+                        ctx,
+                        &public_key,
+                        &self.msg,
+                        &sign,
+                        &ONE_OF_TEMPLATES,
+                        self.index_salt.as_ref(),
+                    )?;
+                    assert_eq!(commitment.is_some(), self.index_salt.is_some());
+                    biguint_config
+                        .gate()
+                        .assert_is_const(ctx, &is_valid, F::one());
+                    biguint_config.range().finalize(ctx);
+                    Ok(commitment
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|v| v.cell())
+                        .collect::<Vec<Cell>>())
+                },
+            )?;
+            for (i, cell) in commitment_cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell, config.commitment_instance, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn run_one_of(msg: &[u8], index_salt: Option<[u8; 31]>, commitment: Vec<u8>) -> bool {
+        let private_key =
+            RsaPrivateKey::new(&mut thread_rng(), 1024).expect("failed to generate a key");
+        let circuit = TestOneOfCircuit::<Fr> {
+            private_key,
+            msg: msg.to_vec(),
+            index_salt,
+            _f: PhantomData,
+        };
+        let commitment = commitment
+            .iter()
+            .map(|byte| Fr::from(*byte as u64))
+            .collect::<Vec<Fr>>();
+        let prover =
+            match MockProver::run(TestOneOfCircuit::<Fr>::K as u32, &circuit, vec![commitment]) {
+                Ok(prover) => prover,
+                Err(e) => panic!("{:#?}", e),
+            };
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_one_of_templates() {
+        for template in ONE_OF_TEMPLATES {
+            assert!(run_one_of(template, None, vec![]));
+        }
+        assert!(!run_one_of(b"vote: maybe", None, vec![]));
+    }
+
+    #[test]
+    fn test_one_of_templates_with_index_commitment() {
+        let index_salt: [u8; 31] = thread_rng().gen();
+        let commitment =
+            |index: u8| Sha256::digest([&[index][..], &index_salt[..]].concat()).to_vec();
+        assert!(run_one_of(
+            ONE_OF_TEMPLATES[1],
+            Some(index_salt),
+            commitment(1)
+        ));
+        assert!(!run_one_of(
+            ONE_OF_TEMPLATES[1],
+            Some(index_salt),
+            commitment(2)
+        ));
+    }
+
+    /// A message with an 8 bytes big-endian timestamp at [`TIMESTAMP_OFFSET`].
+    fn timestamped_msg(timestamp: u64) -> Vec<u8> {
+        [
+            &b"ts:"[..],
+            &timestamp.to_be_bytes()[..],
+            &b";msg:hello"[..],
+        ]
+        .concat()
+    }
+
+    const TIMESTAMP_OFFSET: usize = 3;
+
+    #[derive(Debug, Clone)]
+    struct TestMessageFieldConfig<F: PrimeField> {
+        rsa_config: RSAConfig<F>,
+        sha256_config: TestSha256Config<F>,
+        field_instance: Column<Instance>,
+    }
+
+    struct TestMessageFieldCircuit<F: PrimeField> {
+        private_key: RsaPrivateKey,
+        msg: Vec<u8>,
+        fields: Vec<(usize, usize)>,
+        // If given, the message is verified by `verify_with_schema` instead of extracting `fields`.
+        schema: Option<FieldSchema>,
+        // If given, the length of the hashed message is forged as `TamperedLenSha256` does.
+        tampered_len: Option<u64>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestMessageFieldCircuit<F> {
+        const BITS_LEN: usize = 1024;
+        const K: usize = 15;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestMessageFieldCircuit<F> {
+        type Config = TestMessageFieldConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config =
+                RangeConfig::configure(meta, Vertical, &[80], &[16], 1, 12, 0, Self::K);
+            let biguint_config = BigUintConfig::construct(range_config.clone(), 64);
+            let rsa_config = RSAConfig::construct(biguint_config, Self::BITS_LEN, 5);
+            let sha256_config = configure_test_sha256(meta, vec![64], range_config, 8, 8);
+            let field_instance = meta.instance_column();
+            meta.enable_equality(field_instance);
+            Self::Config {
+                rsa_config,
+                sha256_config,
+                field_instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let biguint_config = config.rsa_config.biguint_config();
+            config.sha256_config.load(&mut layouter)?;
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let field_cells = layouter.assign_region(
+                || "rsa signature with message fields",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(vec![]);
+                    }
+
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    let signing_key =
+                        SigningKey::<rsa::sha2::Sha256>::new(self.private_key.clone());
+                    let sign = signing_key.sign(&self.msg).to_vec();
+                    let sign = config.rsa_config.assign_signature(
+                        ctx,
+                        RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+                    )?;
+                    let n_big = BigUint::from_bytes_be(&self.private_key.n().to_bytes_be());
+                    let e_fix = RSAPubE::Fix(BigUint::from(65537u32));
+                    let public_key = config
+                        .rsa_config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                    let sha256 = TamperedLenSha256::new(
+                        config.sha256_config.clone(),
+                        biguint_config.gate().clone(),
+                        self.tampered_len.map(|len| (0, len)),
+                    );
+                    let mut verifier = RSASignatureVerifier::new(config.rsa_config.clone(), sha256);
+                    let (is_valid, fields, _) = match self.schema.as_ref() {
+                        Some(schema) => verifier.verify_with_schema(
+                            ctx,
+                            &public_key,
+                            &self.msg,
+                            &sign,
+                            schema,
+                        )?,
+                        None => {
+                            let extractor = MessageFieldExtractor::new(
+                                biguint_config.gate().clone(),
+                                self.fields.clone(),
+                            );
+                            verifier.verify_pkcs1v15_and_consume(
+                                ctx,
+                                &public_key,
+                                &self.msg,
+                                &sign,
+                                &extractor,
+                            )?
+                        }
+                    };
+                    biguint_config
+                        .gate()
+                        .assert_is_const(ctx, &is_valid, F::one());
+                    biguint_config.range().finalize(ctx);
+                    Ok(fields.into_iter().map(|v| v.cell()).collect::<Vec<Cell>>())
+                },
+            )?;
+            for (i, cell) in field_cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell, config.field_instance, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn run_message_field(
+        msg: &[u8],
+        fields: Vec<(usize, usize)>,
+        instances: Vec<Fr>,
+    ) -> Result<bool, Error> {
+        run_message_field_circuit(msg, fields, None, None, instances)
+    }
+
+    fn run_message_field_circuit(
+        msg: &[u8],
+        fields: Vec<(usize, usize)>,
+        schema: Option<FieldSchema>,
+        tampered_len: Option<u64>,
+        instances: Vec<Fr>,
+    ) -> Result<bool, Error> {
+        let private_key =
+            RsaPrivateKey::new(&mut thread_rng(), 1024).expect("failed to generate a key");
+        let circuit = TestMessageFieldCircuit::<Fr> {
+            private_key,
+            msg: msg.to_vec(),
+            fields,
+            schema,
+            tampered_len,
+            _f: PhantomData,
+        };
+        let prover = MockProver::run(
+            TestMessageFieldCircuit::<Fr>::K as u32,
+            &circuit,
+            vec![instances],
+        )?;
+        Ok(prover.verify().is_ok())
+    }
+
+    #[test]
+    fn test_extract_timestamp_field() {
+        let timestamp = 1_700_000_000u64;
+        let msg = timestamped_msg(timestamp);
+        let fields = vec![(TIMESTAMP_OFFSET, 8)];
+        assert!(run_message_field(&msg, fields.clone(), vec![Fr::from(timestamp)]).unwrap());
+        assert!(!run_message_field(&msg, fields, vec![Fr::from(timestamp + 1)]).unwrap());
+    }
+
+    #[test]
+    fn test_extract_long_message_field() {
+        // 40 bytes are packed into an element of 31 bytes and one of the remaining 9 bytes.
+        let msg = (0u8..40).collect::<Vec<u8>>();
+        let pack = |bytes: &[u8]| {
+            bytes.iter().fold(Fr::from(0), |acc, byte| {
+                acc * Fr::from(256) + Fr::from(*byte as u64)
+            })
+        };
+        assert!(run_message_field(
+            &msg,
+            vec![(0, 40), (38, 1)],
+            vec![pack(&msg[0..31]), pack(&msg[31..40]), Fr::from(38)]
+        )
+        .unwrap());
+    }
+
+    #[test]
+    fn test_extract_message_field_rejects_short_input_len() {
+        let timestamp = 1_700_000_000u64;
+        let msg = timestamped_msg(timestamp);
+        let fields = vec![(TIMESTAMP_OFFSET, 8)];
+        let len = msg.len() as u64;
+        let run = |tampered_len| {
+            run_message_field_circuit(
+                &msg,
+                fields.clone(),
+                None,
+                Some(tampered_len),
+                vec![Fr::from(timestamp)],
+            )
+            .unwrap()
+        };
+        assert!(run(len));
+        // The consumed bytes would include the SHA256 padding of a shorter message.
+        assert!(!run(len - 1));
+        assert!(!run(TIMESTAMP_OFFSET as u64));
+    }
+
+    #[test]
+    fn test_extract_message_field_out_of_range() {
+        let msg = timestamped_msg(0);
+        assert!(run_message_field(&msg, vec![(msg.len() - 4, 8)], vec![]).is_err());
+        assert!(run_message_field(&msg, vec![(0, 0)], vec![]).is_err());
+    }
+
+    #[test]
+    fn test_verify_with_schema() {
+        // A credential of a 4 bytes hidden id, a revealed name, and a hidden 8 bytes birth date.
+        let msg = [&[0x12, 0x34, 0x56, 0x78][..], b"alice", b"19900101"].concat();
+        let schema = FieldSchema::default()
+            .field(0, 4, false)
+            .field(4, 5, true)
+            .field(9, 8, false);
+        assert!(schema.is_valid_for(msg.len()));
+        let instances = schema.instances::<Fr>(&msg);
+        assert_eq!(
+            instances,
+            vec![biguint_to_fe::<Fr>(&BigUint::from_bytes_be(b"alice"))]
+        );
+        let run = |schema: &FieldSchema, tampered_len, instances| {
+            run_message_field_circuit(&msg, vec![], Some(schema.clone()), tampered_len, instances)
+                .unwrap()
+        };
+        assert!(run(&schema, None, instances.clone()));
+        let other_name = vec![biguint_to_fe::<Fr>(&BigUint::from_bytes_be(b"alicf"))];
+        assert!(!run(&schema, None, other_name));
+        // A hashed message shorter than the schema is rejected even if the revealed field is within it.
+        assert!(run(&schema, Some(msg.len() as u64), instances.clone()));
+        assert!(!run(&schema, Some(msg.len() as u64 - 1), instances.clone()));
+        assert!(!run(&schema, Some(9), instances));
+
+        let overlapping = FieldSchema::default().field(0, 5, true).field(4, 5, false);
+        assert!(!overlapping.is_valid_for(msg.len()));
+        assert!(run_message_field_circuit(&msg, vec![], Some(overlapping), None, vec![]).is_err());
+        assert!(!FieldSchema::default()
+            .field(9, 9, true)
+            .is_valid_for(msg.len()));
+    }
+
+    #[derive(Debug, Clone)]
+    struct TestPublicSignatureConfig<F: PrimeField> {
+        rsa_config: RSAConfig<F>,
+        sha256_config: TestSha256Config<F>,
+        signature_instance: Column<Instance>,
+    }
+
+    struct TestPublicSignatureCircuit<F: PrimeField> {
+        private_key: RsaPrivateKey,
+        msg: Vec<u8>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestPublicSignatureCircuit<F> {
+        const BITS_LEN: usize = 1024;
+        const LIMB_BITS: usize = 64;
+        const K: usize = 15;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestPublicSignatureCircuit<F> {
+        type Config = TestPublicSignatureConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config =
+                RangeConfig::configure(meta, Vertical, &[80], &[16], 1, 12, 0, Self::K);
+            let biguint_config = BigUintConfig::construct(range_config.clone(), Self::LIMB_BITS);
+            let rsa_config = RSAConfig::construct(biguint_config, Self::BITS_LEN, 5);
+            let sha256_config = configure_test_sha256(meta, vec![64], range_config, 8, 8);
+            let signature_instance = meta.instance_column();
+            meta.enable_equality(signature_instance);
+            Self::Config {
+                rsa_config,
+                sha256_config,
+                signature_instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let biguint_config = config.rsa_config.biguint_config();
+            config.sha256_config.load(&mut layouter)?;
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let signature_cells = layouter.assign_region(
+                || "rsa signature exposed as public inputs",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(vec![]);
+                    }
+
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    let signing_key =
+                        SigningKey::<rsa::sha2::Sha256>::new(self.private_key.clone());
+                    let sign = signing_key.sign(&self.msg).to_vec();
+                    let sign = config.rsa_config.assign_signature(
+                        ctx,
+                        RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+                    )?;
+                    let n_big = BigUint::from_bytes_be(&self.private_key.n().to_bytes_be());
+                    let e_fix = RSAPubE::Fix(BigUint::from(65537u32));
+                    let public_key = config
+                        .rsa_config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                    let mut verifier = RSASignatureVerifier::new(
+                        config.rsa_config.clone(),
+                        config.sha256_config.clone(),
+                    )
+                    .with_public_signature(true);
+                    let (is_valid, _) =
+                        verifier.verify_pkcs1v15_signature(ctx, &public_key, &self.msg, &sign)?;
+                    biguint_config
+                        .gate()
+                        .assert_is_const(ctx, &is_valid, F::one());
+                    biguint_config.range().finalize(ctx);
+                    Ok(verifier.signature_cells().to_vec())
+                },
+            )?;
+            for (i, cell) in signature_cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell, config.signature_instance, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_public_signature_binds_instances() {
+        let private_key =
+            RsaPrivateKey::new(&mut thread_rng(), 1024).expect("failed to generate a key");
+        let signing_key = SigningKey::<rsa::sha2::Sha256>::new(private_key.clone());
+        let instances_of = |msg: &[u8]| {
+            let sign = BigUint::from_bytes_be(&signing_key.sign(msg).to_vec());
+            signature_instances::<Fr>(
+                &sign,
+                TestPublicSignatureCircuit::<Fr>::BITS_LEN,
+                TestPublicSignatureCircuit::<Fr>::LIMB_BITS,
+            )
+        };
+        let circuit = TestPublicSignatureCircuit::<Fr> {
+            private_key: private_key.clone(),
+            msg: b"hello world".to_vec(),
+            _f: PhantomData,
+        };
+        let k = TestPublicSignatureCircuit::<Fr>::K as u32;
+        let prover = MockProver::run(k, &circuit, vec![instances_of(b"hello world")]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // The limbs of a valid signature for another message do not match the signature in the witness.
+        let swapped = instances_of(b"hello again");
+        assert_eq!(swapped.len(), 16);
+        let prover = MockProver::run(k, &circuit, vec![swapped]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    /// A message with an 8 bytes big-endian epoch at [`EPOCH_OFFSET`].
+    fn epoch_msg(epoch: u64) -> Vec<u8> {
+        [&b"epoch:"[..], &epoch.to_be_bytes()[..], &b";msg:hello"[..]].concat()
+    }
+
+    const EPOCH_OFFSET: usize = 6;
+
+    #[derive(Debug, Clone)]
+    struct TestKeyEpochConfig<F: PrimeField> {
+        rsa_config: RSAConfig<F>,
+        sha256_config: TestSha256Config<F>,
+        key_set_instance: Column<Instance>,
+    }
+
+    struct TestKeyEpochCircuit<F: PrimeField> {
+        private_key: RsaPrivateKey,
+        msg: Vec<u8>,
+        keys: Vec<KeyEpoch>,
+        // If given, the length of the hashed message is forged as `TamperedLenSha256` does.
+        tampered_len: Option<u64>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestKeyEpochCircuit<F> {
+        const BITS_LEN: usize = 1024;
+        const LIMB_BITS: usize = 64;
+        const K: usize = 15;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestKeyEpochCircuit<F> {
+        type Config = TestKeyEpochConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config =
+                RangeConfig::configure(meta, Vertical, &[80], &[16], 1, 12, 0, Self::K);
+            let biguint_config = BigUintConfig::construct(range_config.clone(), Self::LIMB_BITS);
+            let rsa_config = RSAConfig::construct(biguint_config, Self::BITS_LEN, 5);
+            let sha256_config = configure_test_sha256(meta, vec![64], range_config, 8, 8);
+            let key_set_instance = meta.instance_column();
+            meta.enable_equality(key_set_instance);
+            Self::Config {
+                rsa_config,
+                sha256_config,
+                key_set_instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let biguint_config = config.rsa_config.biguint_config();
+            config.sha256_config.load(&mut layouter)?;
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let key_set_cells = layouter.assign_region(
+                || "rsa signature with a rotated key",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(vec![]);
+                    }
+
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    let signing_key =
+                        SigningKey::<rsa::sha2::Sha256>::new(self.private_key.clone());
+                    let sign = signing_key.sign(&self.msg).to_vec();
+                    let sign = config.rsa_config.assign_signature(
+                        ctx,
+                        RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+                    )?;
+                    let n_big = BigUint::from_bytes_be(&self.private_key.n().to_bytes_be());
+                    let e_fix = RSAPubE::Fix(BigUint::from(65537u32));
+                    let public_key = config
+                        .rsa_config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                    let sha256 = TamperedLenSha256::new(
+                        config.sha256_config.clone(),
+                        biguint_config.gate().clone(),
+                        self.tampered_len.map(|len| (0, len)),
+                    );
+                    let mut verifier = RSASignatureVerifier::new(config.rsa_config.clone(), sha256);
+                    let (is_valid, key_set, _) = verifier.verify_pkcs1v15_with_key_epoch(
+                        ctx,
+                        &public_key,
+                        &self.msg,
+                        &sign,
+                        &self.keys,
+                        EPOCH_OFFSET,
+                    )?;
+                    biguint_config
+                        .gate()
+                        .assert_is_const(ctx, &is_valid, F::one());
+                    biguint_config.range().finalize(ctx);
+                    Ok(key_set.into_iter().map(|v| v.cell()).collect::<Vec<Cell>>())
+                },
+            )?;
+            for (i, cell) in key_set_cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell, config.key_set_instance, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn run_key_epoch(
+        private_key: &RsaPrivateKey,
+        epoch: u64,
+        keys: &[KeyEpoch],
+        instances: Vec<Fr>,
+    ) -> bool {
+        run_key_epoch_with_len(private_key, epoch, keys, None, instances)
+    }
+
+    fn run_key_epoch_with_len(
+        private_key: &RsaPrivateKey,
+        epoch: u64,
+        keys: &[KeyEpoch],
+        tampered_len: Option<u64>,
+        instances: Vec<Fr>,
+    ) -> bool {
+        let circuit = TestKeyEpochCircuit::<Fr> {
+            private_key: private_key.clone(),
+            msg: epoch_msg(epoch),
+            keys: keys.to_vec(),
+            tampered_len,
+            _f: PhantomData,
+        };
+        let k = TestKeyEpochCircuit::<Fr>::K as u32;
+        let prover = MockProver::run(k, &circuit, vec![instances]).unwrap();
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_verify_with_key_epoch() {
+        let mut rng = thread_rng();
+        let old_key = RsaPrivateKey::new(&mut rng, 1024).expect("failed to generate a key");
+        let new_key = RsaPrivateKey::new(&mut rng, 1024).expect("failed to generate a key");
+        let modulus = |key: &RsaPrivateKey| BigUint::from_bytes_be(&key.n().to_bytes_be());
+        // The old key expires when the new key becomes active in epoch 100.
+        let keys = vec![
+            KeyEpoch::new(modulus(&old_key), 0, 100),
+            KeyEpoch::new(modulus(&new_key), 100, 200),
+        ];
+        let instances = key_epoch_instances::<Fr>(
+            &keys,
+            TestKeyEpochCircuit::<Fr>::BITS_LEN,
+            TestKeyEpochCircuit::<Fr>::LIMB_BITS,
+        );
+        assert_eq!(instances.len(), 2 * (16 + 2));
+        assert!(run_key_epoch(&old_key, 99, &keys, instances.clone()));
+        assert!(run_key_epoch(&new_key, 100, &keys, instances.clone()));
+        assert!(run_key_epoch(&new_key, 199, &keys, instances.clone()));
+
+        // A valid signature from the expired key is rejected, as is one from the new key before it becomes active.
+        assert!(!keys[0].is_active_at(150));
+        assert!(!run_key_epoch(&old_key, 150, &keys, instances.clone()));
+        assert!(!run_key_epoch(&new_key, 99, &keys, instances.clone()));
+
+        // The key set is bound to the public inputs.
+        let mut extended = keys.clone();
+        extended[0].active_until = 200;
+        let extended_instances = key_epoch_instances::<Fr>(
+            &extended,
+            TestKeyEpochCircuit::<Fr>::BITS_LEN,
+            TestKeyEpochCircuit::<Fr>::LIMB_BITS,
+        );
+        assert!(!run_key_epoch(&old_key, 150, &keys, extended_instances));
+    }
+
+    #[test]
+    fn test_key_epoch_rejects_short_input_len() {
+        let key = RsaPrivateKey::new(&mut thread_rng(), 1024).expect("failed to generate a key");
+        let keys = vec![KeyEpoch::new(
+            BigUint::from_bytes_be(&key.n().to_bytes_be()),
+            0,
+            100,
+        )];
+        let instances = key_epoch_instances::<Fr>(
+            &keys,
+            TestKeyEpochCircuit::<Fr>::BITS_LEN,
+            TestKeyEpochCircuit::<Fr>::LIMB_BITS,
+        );
+        let msg_len = epoch_msg(0).len() as u64;
+        let epoch_end = (EPOCH_OFFSET + KEY_EPOCH_BYTES) as u64;
+        assert!(run_key_epoch_with_len(
+            &key,
+            50,
+            &keys,
+            Some(msg_len),
+            instances.clone()
+        ));
+        // The epoch would be read from the SHA256 padding of a message truncated before its end.
+        assert!(!run_key_epoch_with_len(
+            &key,
+            50,
+            &keys,
+            Some(epoch_end - 1),
+            instances.clone()
+        ));
+        assert!(!run_key_epoch_with_len(
+            &key,
+            50,
+            &keys,
+            Some(EPOCH_OFFSET as u64),
+            instances
+        ));
+    }
+
+    fn hmac_sha256(key: &[u8], msg: &[u8]) -> Vec<u8> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
+        mac.update(msg);
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    #[derive(Debug, Clone)]
+    struct TestHmacConfig<F: PrimeField> {
+        rsa_config: RSAConfig<F>,
+        sha256_config: Sha256DynamicConfig<F>,
+    }
+
+    struct TestHmacCircuit<F: PrimeField> {
+        private_key: RsaPrivateKey,
+        key: Vec<u8>,
+        msg: Vec<u8>,
+        signed_tag: Vec<u8>,
+        // If given, the length of the hashed message is forged as `TamperedLenSha256` does.
+        tampered_len: Option<(usize, u64)>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestHmacCircuit<F> {
+        const BITS_LEN: usize = 1024;
+        const K: usize = 16;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestHmacCircuit<F> {
+        type Config = TestHmacConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config =
+                RangeConfig::configure(meta, Vertical, &[80], &[16], 1, 12, 0, Self::K);
+            let biguint_config = BigUintConfig::construct(range_config.clone(), 64);
+            let rsa_config = RSAConfig::construct(biguint_config, Self::BITS_LEN, 5);
+            // The key longer than the block, the inner block with the message, the outer block, and the tag.
+            let sha256_config = Sha256DynamicConfig::configure(
+                meta,
+                vec![128, 128, 128, 64],
+                range_config,
+                8,
+                8,
+                true,
+            );
+            Self::Config {
+                rsa_config,
+                sha256_config,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let biguint_config = config.rsa_config.biguint_config();
+            config.sha256_config.load(&mut layouter)?;
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "rsa signature over a hmac tag",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    let signing_key =
+                        SigningKey::<rsa::sha2::Sha256>::new(self.private_key.clone());
+                    let sign = signing_key.sign(&self.signed_tag).to_vec();
+                    let sign = config.rsa_config.assign_signature(
+                        ctx,
+                        RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+                    )?;
+                    let n_big = BigUint::from_bytes_be(&self.private_key.n().to_bytes_be());
+                    let e_fix = RSAPubE::Fix(BigUint::from(65537u32));
+                    let public_key = config
+                        .rsa_config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                    let sha256 = TamperedLenSha256::new(
+                        config.sha256_config.clone(),
+                        biguint_config.gate().clone(),
+                        self.tampered_len,
+                    );
+                    let mut verifier = RSASignatureVerifier::new(config.rsa_config.clone(), sha256);
+                    let (is_valid, tag, _) = verifier.verify_pkcs1v15_hmac(
+                        ctx,
+                        &public_key,
+                        &self.key,
+                        &self.msg,
+                        &sign,
+                    )?;
+                    let gate = biguint_config.gate();
+                    gate.assert_is_const(ctx, &is_valid, F::one());
+                    for (byte, expected) in tag.iter().zip(hmac_sha256(&self.key, &self.msg)) {
+                        gate.assert_is_const(ctx, byte, F::from(expected as u64));
+                    }
+                    biguint_config.range().finalize(ctx);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn run_hmac(private_key: &RsaPrivateKey, key: &[u8], msg: &[u8], signed_tag: Vec<u8>) -> bool {
+        run_hmac_with_len(private_key, key, msg, signed_tag, None)
+    }
+
+    fn run_hmac_with_len(
+        private_key: &RsaPrivateKey,
+        key: &[u8],
+        msg: &[u8],
+        signed_tag: Vec<u8>,
+        tampered_len: Option<(usize, u64)>,
+    ) -> bool {
+        let circuit = TestHmacCircuit::<Fr> {
+            private_key: private_key.clone(),
+            key: key.to_vec(),
+            msg: msg.to_vec(),
+            signed_tag,
+            tampered_len,
+            _f: PhantomData,
+        };
+        let prover = MockProver::run(TestHmacCircuit::<Fr>::K as u32, &circuit, vec![]).unwrap();
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_verify_pkcs1v15_hmac() {
+        let private_key =
+            RsaPrivateKey::new(&mut thread_rng(), 1024).expect("failed to generate a key");
+        let msg = b"amount=100&to=alice";
+        // The keys shorter than, as long as, and longer than the block of SHA256.
+        for key_len in [20, HMAC_SHA256_BLOCK_LEN, 100] {
+            let key = (0..key_len).map(|i| i as u8).collect::<Vec<u8>>();
+            let tag = hmac_sha256(&key, msg);
+            assert!(run_hmac(&private_key, &key, msg, tag), "{}", key_len);
+        }
+
+        // The tag signed for another key or message is rejected.
+        let key = b"the shared hmac key".to_vec();
+        let other_key_tag = hmac_sha256(b"another hmac key", msg);
+        assert!(!run_hmac(&private_key, &key, msg, other_key_tag));
+        let other_msg_tag = hmac_sha256(&key, b"amount=900&to=alice");
+        assert!(!run_hmac(&private_key, &key, msg, other_msg_tag));
+    }
+
+    #[test]
+    fn test_verify_pkcs1v15_hmac_rejects_tampered_inner_len() {
+        let private_key =
+            RsaPrivateKey::new(&mut thread_rng(), 1024).expect("failed to generate a key");
+        let msg = b"amount=100&to=alice";
+        let inner_len = (HMAC_SHA256_BLOCK_LEN + msg.len()) as u64;
+        // The inner digest is the first one unless the key longer than the block is hashed before it.
+        for (key_len, inner_call) in [(20, 0), (100, 1)] {
+            let key = (0..key_len).map(|i| i as u8).collect::<Vec<u8>>();
+            let tag = hmac_sha256(&key, msg);
+            let run = |len| {
+                run_hmac_with_len(
+                    &private_key,
+                    &key,
+                    msg,
+                    tag.clone(),
+                    Some((inner_call, len)),
+                )
+            };
+            assert!(run(inner_len), "{}", key_len);
+            assert!(!run(inner_len - 1), "{}", key_len);
+            assert!(!run(HMAC_SHA256_BLOCK_LEN as u64), "{}", key_len);
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct TestHashchainConfig<F: PrimeField> {
+        rsa_config: RSAConfig<F>,
+        sha256_config: Sha256DynamicConfig<F>,
+    }
+
+    struct TestHashchainCircuit<F: PrimeField, const MAX_STEPS: usize> {
+        private_key: RsaPrivateKey,
+        entry: Vec<u8>,
+        steps: usize,
+        signed_head: Vec<u8>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField, const MAX_STEPS: usize> TestHashchainCircuit<F, MAX_STEPS> {
+        const BITS_LEN: usize = 1024;
+        const K: usize = 16;
+    }
+
+    impl<F: PrimeField, const MAX_STEPS: usize> Circuit<F> for TestHashchainCircuit<F, MAX_STEPS> {
+        type Config = TestHashchainConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config =
+                RangeConfig::configure(meta, Vertical, &[80], &[16], 1, 12, 0, Self::K);
+            let biguint_config = BigUintConfig::construct(range_config.clone(), 64);
+            let rsa_config = RSAConfig::construct(biguint_config, Self::BITS_LEN, 5);
+            // The entry, the links of the chain, and the head.
+            let sha256_config = Sha256DynamicConfig::configure(
+                meta,
+                vec![64; MAX_STEPS + 1],
+                range_config,
+                8,
+                8,
+                true,
+            );
+            Self::Config {
+                rsa_config,
+                sha256_config,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let biguint_config = config.rsa_config.biguint_config();
+            config.sha256_config.load(&mut layouter)?;
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "rsa signature over a hash chain head",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    let signing_key =
+                        SigningKey::<rsa::sha2::Sha256>::new(self.private_key.clone());
+                    let sign = signing_key.sign(&self.signed_head).to_vec();
+                    let sign = config.rsa_config.assign_signature(
+                        ctx,
+                        RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+                    )?;
+                    let n_big = BigUint::from_bytes_be(&self.private_key.n().to_bytes_be());
+                    let e_fix = RSAPubE::Fix(BigUint::from(65537u32));
+                    let public_key = config
+                        .rsa_config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                    let mut verifier = RSASignatureVerifier::new(
+                        config.rsa_config.clone(),
+                        config.sha256_config.clone(),
+                    );
+                    let (is_valid, head, _, steps) = verifier.verify_signed_hashchain(
+                        ctx,
+                        &public_key,
+                        &sign,
+                        &self.entry,
+                        self.steps,
+                        MAX_STEPS,
+                    )?;
+                    let gate = biguint_config.gate();
+                    gate.assert_is_const(ctx, &is_valid, F::one());
+                    gate.assert_is_const(ctx, &steps, F::from(self.steps as u64));
+                    assert_eq!(head.len(), self.entry.len().max(32));
+                    for (byte, expected) in head.iter().zip(self.signed_head.iter()) {
+                        gate.assert_is_const(ctx, byte, F::from(*expected as u64));
+                    }
+                    biguint_config.range().finalize(ctx);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn run_hashchain<const MAX_STEPS: usize>(
+        private_key: &RsaPrivateKey,
+        entry: &[u8],
+        steps: usize,
+        signed_head: &[u8],
+    ) -> bool {
+        let circuit = TestHashchainCircuit::<Fr, MAX_STEPS> {
+            private_key: private_key.clone(),
+            entry: entry.to_vec(),
+            steps,
+            signed_head: signed_head.to_vec(),
+            _f: PhantomData,
+        };
+        let prover = MockProver::run(
+            TestHashchainCircuit::<Fr, MAX_STEPS>::K as u32,
+            &circuit,
+            vec![],
+        )
+        .unwrap();
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_verify_signed_hashchain() {
+        let private_key =
+            RsaPrivateKey::new(&mut thread_rng(), 1024).expect("failed to generate a key");
+        // A chain of 4 entries, whose last entry is the signed head.
+        let chain = (0..4)
+            .map(|i| hashchain_head(b"log entry: user=alice action=login", i))
+            .collect::<Vec<Vec<u8>>>();
+        let head = &chain[3];
+        assert_eq!(&Sha256::digest(&chain[2]).to_vec(), head);
+
+        // Every entry hashes forward to the head in as many steps as its distance to the head, which the same circuit verifies for any distance up to its maximum.
+        assert!(run_hashchain::<3>(&private_key, &chain[0], 3, head));
+        assert!(run_hashchain::<3>(&private_key, &chain[1], 2, head));
+        assert!(run_hashchain::<3>(&private_key, &chain[2], 1, head));
+        assert!(run_hashchain::<3>(&private_key, &chain[3], 0, head));
+        assert!(run_hashchain::<0>(&private_key, &chain[3], 0, head));
+
+        // An entry at another position, a tampered entry, or a signature over another head is rejected.
+        assert!(!run_hashchain::<3>(&private_key, &chain[0], 2, head));
+        assert!(!run_hashchain::<3>(&private_key, &chain[1], 3, head));
+        let mut tampered = chain[0].clone();
+        tampered[0] ^= 1;
+        assert!(!run_hashchain::<3>(&private_key, &tampered, 3, head));
+        assert!(!run_hashchain::<3>(&private_key, &chain[0], 3, &chain[2]));
+        // The steps beyond the maximum are rejected even for the signed head.
+        let longer_head = hashchain_head(&chain[0], 4);
+        assert!(!run_hashchain::<3>(
+            &private_key,
+            &chain[0],
+            4,
+            &longer_head
+        ));
+    }
 }
@@ -0,0 +1,122 @@
+use std::alloc::{GlobalAlloc, Layout};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A global allocator wrapping `A` that counts the bytes currently allocated and their peak.
+///
+/// The wasm build registers it as the global allocator with the `memory-stats` feature, so that `memory_stats` can report the heap usage of the prover.
+/// Besides the peak since the start, it keeps a window peak that [`PeakAlloc::take_window_peak`] restarts, e.g., to measure each phase of the proving separately.
+pub struct PeakAlloc<A> {
+    inner: A,
+    current: AtomicUsize,
+    peak: AtomicUsize,
+    window_peak: AtomicUsize,
+}
+
+impl<A> PeakAlloc<A> {
+    /// Wraps `inner` with all counters set to zero.
+    pub const fn new(inner: A) -> Self {
+        Self {
+            inner,
+            current: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            window_peak: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the bytes currently allocated through the allocator.
+    pub fn current(&self) -> usize {
+        self.current.load(Ordering::SeqCst)
+    }
+
+    /// Returns the maximum of [`PeakAlloc::current`] since the allocator was created.
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::SeqCst)
+    }
+
+    /// Returns the maximum of [`PeakAlloc::current`] since the previous call, or since the allocator was created, and starts a new window from the current bytes.
+    pub fn take_window_peak(&self) -> usize {
+        self.window_peak.swap(self.current(), Ordering::SeqCst)
+    }
+
+    fn add(&self, size: usize) {
+        let current = self.current.fetch_add(size, Ordering::SeqCst) + size;
+        self.peak.fetch_max(current, Ordering::SeqCst);
+        self.window_peak.fetch_max(current, Ordering::SeqCst);
+    }
+
+    fn sub(&self, size: usize) {
+        self.current.fetch_sub(size, Ordering::SeqCst);
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for PeakAlloc<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc(layout);
+        if !ptr.is_null() {
+            self.add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            self.add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout);
+        self.sub(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = self.inner.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            // The peak counts the new size only, although the allocator may hold both blocks while copying.
+            if new_size > layout.size() {
+                self.add(new_size - layout.size());
+            } else {
+                self.sub(layout.size() - new_size);
+            }
+        }
+        new_ptr
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::alloc::System;
+
+    #[test]
+    fn test_peak_alloc_counts_allocations() {
+        let alloc = PeakAlloc::new(System);
+        let small = Layout::from_size_align(100, 8).unwrap();
+        let large = Layout::from_size_align(1000, 8).unwrap();
+        unsafe {
+            let a = alloc.alloc(small);
+            let b = alloc.alloc_zeroed(large);
+            assert_eq!(alloc.current(), 1100);
+            assert_eq!(alloc.peak(), 1100);
+            alloc.dealloc(b, large);
+            assert_eq!(alloc.current(), 100);
+            assert_eq!(alloc.peak(), 1100);
+            assert_eq!(alloc.take_window_peak(), 1100);
+
+            // The new window starts from the 100 bytes still allocated.
+            let a = alloc.realloc(a, small, 400);
+            assert_eq!(alloc.current(), 400);
+            let a_layout = Layout::from_size_align(400, 8).unwrap();
+            let a = alloc.realloc(a, a_layout, 50);
+            assert_eq!(alloc.current(), 50);
+            assert_eq!(alloc.take_window_peak(), 400);
+            assert_eq!(alloc.take_window_peak(), 50);
+            assert_eq!(alloc.peak(), 1100);
+
+            alloc.dealloc(a, Layout::from_size_align(50, 8).unwrap());
+            assert_eq!(alloc.current(), 0);
+        }
+    }
+}
@@ -0,0 +1,560 @@
+use crate::compression::{gunzip_prefix, COMPRESSED_PARAMS_MAGIC, GZIP_MAGIC};
+use crate::decompress_params;
+use crate::keys::catch_read_panic;
+use halo2_base::halo2_proofs::{
+    halo2curves::{
+        bn256::{Bn256, G1Affine, G2Affine},
+        serde::SerdeObject,
+    },
+    poly::{
+        commitment::{Params, ParamsProver},
+        kzg::commitment::ParamsKZG,
+    },
+    SerdeFormat,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use std::io::{self, BufReader, Read};
+
+/// The magic bytes at the head of the trimmed verifier params produced by [`extract_verifier_params`].
+pub const VERIFIER_PARAMS_MAGIC: [u8; 4] = *b"HRVP";
+/// The number of the leading bytes of params enough for [`params_max_k`], which covers the gzip header and the first deflate block of the compressed params.
+pub(crate) const PARAMS_HEADER_PEEK_BYTES: usize = 1 << 16;
+/// The byte length of a G1 point in the raw serialization of [`ParamsKZG`].
+pub(crate) const G1_RAW_BYTES: usize = 64;
+/// The byte length of a G2 point in the raw serialization of [`ParamsKZG`].
+pub(crate) const G2_RAW_BYTES: usize = 128;
+/// The byte length of a compressed G1 point in the serialization of [`ParamsKZG`] in [`SerdeFormat::Processed`].
+pub(crate) const G1_COMPRESSED_BYTES: usize = 32;
+/// The byte length of a compressed G2 point in the serialization of [`ParamsKZG`] in [`SerdeFormat::Processed`].
+pub(crate) const G2_COMPRESSED_BYTES: usize = 64;
+/// The maximum degree `k` of params accepted by the read functions of this crate.
+///
+/// The scalar field of BN254 has no root of unity of order `2^29`, so no circuit, and thus no useful params, is larger than `2^28` rows.
+pub const MAX_PARAMS_K: u32 = 28;
+
+/// Trims serialized [`ParamsKZG`] down to the part used by the verifier.
+///
+/// The verifier only needs `g[0]`, the first `num_instances` Lagrange bases used to commit the instance columns, `g2`, and `s_g2`.
+/// For the circuits without instance columns, `num_instances` can be zero and the output is a few hundred bytes regardless of `k`.
+///
+/// # Arguments
+/// * params - bytes of [`ParamsKZG`] written by [`Params::write`], or those compressed by [`crate::compress_params`].
+/// * num_instances - the maximum number of rows used in an instance column of the verified circuits.
+///
+/// # Return values
+/// Returns the trimmed params, which can be read by [`read_verifier_params`].
+pub fn extract_verifier_params(params: &[u8], num_instances: usize) -> io::Result<Vec<u8>> {
+    let params = &decompress_params(params)?[..];
+    let k = check_params_len(params, G1_RAW_BYTES, G2_RAW_BYTES)?;
+    let n = 1usize << k;
+    let num_points = num_instances.max(1);
+    if num_points > n {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} instances do not fit in 2^{} rows", num_instances, k),
+        ));
+    }
+    let g_offset = 4;
+    let g_lagrange_offset = g_offset + n * G1_RAW_BYTES;
+    let g2_offset = g_lagrange_offset + n * G1_RAW_BYTES;
+
+    let mut trimmed = Vec::with_capacity(12 + 2 * num_points * G1_RAW_BYTES + 2 * G2_RAW_BYTES);
+    trimmed.extend_from_slice(&VERIFIER_PARAMS_MAGIC);
+    trimmed.extend_from_slice(&k.to_le_bytes());
+    trimmed.extend_from_slice(&(num_points as u32).to_le_bytes());
+    trimmed.extend_from_slice(&params[g_offset..g_offset + num_points * G1_RAW_BYTES]);
+    trimmed.extend_from_slice(
+        &params[g_lagrange_offset..g_lagrange_offset + num_points * G1_RAW_BYTES],
+    );
+    trimmed.extend_from_slice(&params[g2_offset..]);
+    Ok(trimmed)
+}
+
+/// Returns the maximum degree `k` of serialized params by parsing only their header, e.g., to reject params too small for a circuit before reading them.
+///
+/// # Arguments
+/// * params - bytes of [`ParamsKZG`] written by [`Params::write`], the trimmed params produced by [`extract_verifier_params`], or either of them compressed by [`crate::compress_params`].
+///
+/// # Return values
+/// Returns `k` of the params, which support the circuits of `k` or less after [`Params::downsize`].
+/// Returns an [`io::ErrorKind::InvalidData`] error if the header is invalid or the length of the uncompressed full params does not match their `k`, e.g., those truncated by an interrupted download.
+pub fn params_max_k(params: &[u8]) -> io::Result<u32> {
+    params_prefix_max_k(params, params.len() as u64)
+}
+
+/// Returns [`params_max_k`] of the params of `params_len` bytes starting with `prefix`, which must contain their first [`PARAMS_HEADER_PEEK_BYTES`] bytes if they are longer.
+pub(crate) fn params_prefix_max_k(prefix: &[u8], params_len: u64) -> io::Result<u32> {
+    // Both magic bytes are 4 bytes long and followed by `k`.
+    let header = gunzip_prefix(prefix, VERIFIER_PARAMS_MAGIC.len() + 4)?;
+    if let Some(k) = header.strip_prefix(&VERIFIER_PARAMS_MAGIC) {
+        return read_k(k);
+    }
+    if let Some(k) = header.strip_prefix(&COMPRESSED_PARAMS_MAGIC) {
+        return read_k(k);
+    }
+    let k = read_k(&header)?;
+    if !prefix.starts_with(&GZIP_MAGIC)
+        && params_len != params_len_of(k, G1_RAW_BYTES, G2_RAW_BYTES)
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the length of params does not match its k",
+        ));
+    }
+    Ok(k)
+}
+
+/// Reads [`ParamsKZG`] written by [`Params::write`], or those compressed by [`crate::compress_params`].
+pub fn read_params(bytes: &[u8]) -> io::Result<ParamsKZG<Bn256>> {
+    let bytes = decompress_params(bytes)?;
+    check_params_len(&bytes, G1_RAW_BYTES, G2_RAW_BYTES)?;
+    catch_read_panic("params", || {
+        ParamsKZG::<Bn256>::read(&mut BufReader::new(&bytes[..]))
+    })
+}
+
+/// Reads [`ParamsKZG`] from a ceremony file, e.g., the `.srs` files of the Axiom ceremony or those converted from the perpetual powers of tau by PSE.
+///
+/// Both are the serialization of [`ParamsKZG`] without a header, but the points are either raw as written by [`Params::write`] or compressed as in [`SerdeFormat::Processed`],
+/// which is told apart by the length of the bytes for their `k`. The params compressed by [`crate::compress_params`] or gzipped by other tools are accepted as well.
+///
+/// Returns an [`io::ErrorKind::InvalidData`] error if the length matches neither layout, e.g., for a truncated download.
+pub fn read_srs(bytes: &[u8]) -> io::Result<ParamsKZG<Bn256>> {
+    let bytes = decompress_params(bytes)?;
+    let k = read_k(&bytes)?;
+    let len = bytes.len() as u64;
+    let format = if len == params_len_of(k, G1_RAW_BYTES, G2_RAW_BYTES) {
+        SerdeFormat::RawBytes
+    } else if len == params_len_of(k, G1_COMPRESSED_BYTES, G2_COMPRESSED_BYTES) {
+        SerdeFormat::Processed
+    } else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "the length of params matches neither the raw nor the compressed points for k = {}",
+                k
+            ),
+        ));
+    };
+    catch_read_panic("params", || {
+        ParamsKZG::<Bn256>::read_custom(&mut BufReader::new(&bytes[..]), format)
+    })
+}
+
+/// Downsizes the params of a larger ceremony to `target_k` by [`Params::downsize`], which keeps the powers of tau and recomputes the Lagrange bases.
+///
+/// The downsized params are as secure as the ceremony, unlike those sampled by [`ParamsKZG::setup`], and the proofs created with them verify with the params downsized from the same ceremony by anyone else.
+///
+/// # Arguments
+/// * params - a ceremony file accepted by [`read_srs`].
+/// * target_k - the degree of the downsized params, which must not exceed that of `params`.
+///
+/// # Return values
+/// Returns the downsized params written by [`Params::write`], or an [`io::ErrorKind::InvalidInput`] error if `target_k` is larger than `k` of `params`.
+pub fn downsize_params(params: &[u8], target_k: u32) -> io::Result<Vec<u8>> {
+    let mut params = read_srs(params)?;
+    if target_k > params.k() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "params for k = {} cannot be downsized to k = {}",
+                params.k(),
+                target_k
+            ),
+        ));
+    }
+    params.downsize(target_k);
+    let mut downsized = vec![];
+    params.write(&mut downsized)?;
+    Ok(downsized)
+}
+
+/// Samples [`ParamsKZG`] of degree `k` with a ChaCha20 RNG seeded by `seed`, so that the params, and the keys generated for them, are byte-identical on every machine.
+///
+/// # Security
+/// The secret of the params is derived from `seed`, so anyone knowing the seed can forge proofs.
+/// They are only for the tests and the benchmarks; use the params of a ceremony read by [`read_srs`] in production.
+pub fn gen_params_seeded(k: u32, seed: u64) -> ParamsKZG<Bn256> {
+    ParamsKZG::<Bn256>::setup(k, ChaCha20Rng::seed_from_u64(seed))
+}
+
+/// Reads [`ParamsKZG`] for verification from either full params or the trimmed params produced by [`extract_verifier_params`], compressed or not.
+///
+/// The params read from the trimmed ones hold only their bases, while `k` and `n` are those of the full params.
+/// The KZG verifiers evaluate the instance columns instead of committing them and take only `g[0]`, `g2`, and `s_g2` from the params,
+/// so the resulting params verify the same proofs as the full ones without allocating the `2^k` bases.
+/// The returned params must not be used for proving.
+pub fn read_verifier_params(bytes: &[u8]) -> io::Result<ParamsKZG<Bn256>> {
+    let bytes = &decompress_params(bytes)?[..];
+    if !bytes.starts_with(&VERIFIER_PARAMS_MAGIC) {
+        check_params_len(bytes, G1_RAW_BYTES, G2_RAW_BYTES)?;
+        return catch_read_panic("params", || {
+            ParamsKZG::<Bn256>::read(&mut BufReader::new(bytes))
+        });
+    }
+    let mut reader = &bytes[VERIFIER_PARAMS_MAGIC.len()..];
+    let mut u32_bytes = [0u8; 4];
+    reader.read_exact(&mut u32_bytes)?;
+    let k = u32::from_le_bytes(u32_bytes);
+    reader.read_exact(&mut u32_bytes)?;
+    let num_points = u32::from_le_bytes(u32_bytes) as usize;
+    if k > MAX_PARAMS_K || num_points == 0 || num_points > (1usize << k) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid header of the verifier params",
+        ));
+    }
+    if reader.len() as u64 != 2 * num_points as u64 * G1_RAW_BYTES as u64 + 2 * G2_RAW_BYTES as u64
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the length of the verifier params does not match its header",
+        ));
+    }
+    let (g, rest) = reader.split_at(num_points * G1_RAW_BYTES);
+    let (g_lagrange, g2s) = rest.split_at(num_points * G1_RAW_BYTES);
+    catch_read_panic("params", || {
+        // `ParamsKZG::from_parts` does not use its receiver, which is read as the params of `k = 0` from the first bases.
+        let mut base = 0u32.to_le_bytes().to_vec();
+        base.extend_from_slice(&g[..G1_RAW_BYTES]);
+        base.extend_from_slice(&g_lagrange[..G1_RAW_BYTES]);
+        base.extend_from_slice(g2s);
+        let base = ParamsKZG::<Bn256>::read(&mut &base[..])?;
+        let mut g2s = g2s;
+        let g2 = G2Affine::read_raw(&mut g2s)?;
+        let s_g2 = G2Affine::read_raw(&mut g2s)?;
+        Ok(base.from_parts(
+            k,
+            read_raw_g1_points(g)?,
+            Some(read_raw_g1_points(g_lagrange)?),
+            g2,
+            s_g2,
+        ))
+    })
+}
+
+/// Reads the G1 points serialized by [`Params::write`] back to back.
+fn read_raw_g1_points(mut bytes: &[u8]) -> io::Result<Vec<G1Affine>> {
+    (0..bytes.len() / G1_RAW_BYTES)
+        .map(|_| G1Affine::read_raw(&mut bytes))
+        .collect()
+}
+
+/// Returns the byte length of params of degree `k` whose G1 and G2 points take `g1_bytes` and `g2_bytes` each, i.e., `k`, `g` and `g_lagrange` of `2^k` points each, `g2`, and `s_g2`.
+fn params_len_of(k: u32, g1_bytes: usize, g2_bytes: usize) -> u64 {
+    4 + 2 * ((g1_bytes as u64) << k) + 2 * g2_bytes as u64
+}
+
+/// Returns `k` of the serialized params after checking that their length matches `k` for the points of `g1_bytes` and `g2_bytes`.
+///
+/// [`ParamsKZG::read`] allocates the `2^k` points of the `k` it reads before reading them, so a forged `k` in a few bytes would otherwise exhaust the memory instead of failing on the truncated points.
+pub(crate) fn check_params_len(params: &[u8], g1_bytes: usize, g2_bytes: usize) -> io::Result<u32> {
+    let k = read_k(params)?;
+    if params.len() as u64 != params_len_of(k, g1_bytes, g2_bytes) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the length of params does not match its k",
+        ));
+    }
+    Ok(k)
+}
+
+fn read_k(params: &[u8]) -> io::Result<u32> {
+    let mut k_bytes = [0u8; 4];
+    (&params[..]).read_exact(&mut k_bytes)?;
+    let k = u32::from_le_bytes(k_bytes);
+    if k > MAX_PARAMS_K {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid k {} in params", k),
+        ));
+    }
+    Ok(k)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{BigUintConfig, BigUintInstructions};
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        halo2curves::bn256::G1Affine,
+        plonk::{
+            create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, ConstraintSystem, Error,
+        },
+        poly::kzg::{
+            commitment::KZGCommitmentScheme,
+            multiopen::{ProverGWC, VerifierGWC},
+            strategy::SingleStrategy,
+        },
+        transcript::{
+            Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+        },
+    };
+    use halo2_base::{
+        gates::range::{RangeConfig, RangeStrategy::Vertical},
+        gates::RangeInstructions,
+        utils::PrimeField,
+        SKIP_FIRST_PASS,
+    };
+    use num_bigint::BigUint;
+    use rand::rngs::OsRng;
+
+    const K: u32 = 13;
+
+    #[derive(Default)]
+    struct TestMulCircuit {
+        a: BigUint,
+        b: BigUint,
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestMulCircuit {
+        type Config = BigUintConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config = RangeConfig::configure(
+                meta,
+                Vertical,
+                &[2],
+                &[1],
+                1,
+                K as usize - 1,
+                0,
+                K as usize,
+            );
+            BigUintConfig::construct(range_config, 64)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "mul of 256 bits integers",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    let a = config.assign_integer(ctx, Value::known(self.a.clone()), 256)?;
+                    let b = config.assign_integer(ctx, Value::known(self.b.clone()), 256)?;
+                    config.mul(ctx, &a, &b)?;
+                    config.range().finalize(ctx);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_verify_with_trimmed_params() {
+        let params = ParamsKZG::<Bn256>::setup(K, OsRng);
+        let circuit = TestMulCircuit {
+            a: BigUint::from(3u64) << 200,
+            b: BigUint::from(5u64) << 100,
+        };
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &circuit).unwrap();
+        let proof = {
+            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            create_proof::<KZGCommitmentScheme<_>, ProverGWC<_>, _, _, _, _>(
+                &params,
+                &pk,
+                &[circuit],
+                &[&[]],
+                OsRng,
+                &mut transcript,
+            )
+            .unwrap();
+            transcript.finalize()
+        };
+
+        let mut params_bytes = vec![];
+        params.write(&mut params_bytes).unwrap();
+        let trimmed = extract_verifier_params(&params_bytes, 0).unwrap();
+        assert!(trimmed.len() < 1024);
+        let verifier_params = read_verifier_params(&trimmed).unwrap();
+        assert_eq!(verifier_params.k(), K);
+        // The bases of the full params are not restored, so even the trimmed params of the largest k are read at once.
+        let mut largest = trimmed.clone();
+        largest[4..8].copy_from_slice(&MAX_PARAMS_K.to_le_bytes());
+        assert_eq!(read_verifier_params(&largest).unwrap().k(), MAX_PARAMS_K);
+
+        let strategy = SingleStrategy::new(&verifier_params);
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+        verify_proof::<_, VerifierGWC<_>, _, _, _>(
+            &verifier_params,
+            &vk,
+            strategy,
+            &[&[]],
+            &mut transcript,
+        )
+        .unwrap();
+
+        // The full params are still accepted.
+        let full_params = read_verifier_params(&params_bytes).unwrap();
+        assert_eq!(full_params.k(), K);
+    }
+
+    #[test]
+    fn test_downsize_params() {
+        const DOWNSIZED_K: u32 = 17;
+        let ceremony = ParamsKZG::<Bn256>::setup(DOWNSIZED_K + 1, OsRng);
+        let mut raw = vec![];
+        ceremony.write(&mut raw).unwrap();
+        let mut compressed = vec![];
+        ceremony
+            .write_custom(&mut compressed, SerdeFormat::Processed)
+            .unwrap();
+
+        // The prover and the verifier downsize the same ceremony from the files in either layout.
+        let prover_params = read_params(&downsize_params(&raw, DOWNSIZED_K).unwrap()).unwrap();
+        assert_eq!(prover_params.k(), DOWNSIZED_K);
+        let verifier_bytes = downsize_params(&compressed, DOWNSIZED_K).unwrap();
+        let verifier_params =
+            read_verifier_params(&extract_verifier_params(&verifier_bytes, 0).unwrap()).unwrap();
+
+        let circuit = TestMulCircuit {
+            a: BigUint::from(3u64) << 200,
+            b: BigUint::from(5u64) << 100,
+        };
+        let vk = keygen_vk(&prover_params, &circuit).unwrap();
+        let pk = keygen_pk(&prover_params, vk.clone(), &circuit).unwrap();
+        // The keys only depend on the downsized params.
+        let verifier_vk = keygen_vk(&read_params(&verifier_bytes).unwrap(), &circuit).unwrap();
+        assert_eq!(verifier_vk.transcript_repr(), vk.transcript_repr());
+        let proof = {
+            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            create_proof::<KZGCommitmentScheme<_>, ProverGWC<_>, _, _, _, _>(
+                &prover_params,
+                &pk,
+                &[circuit],
+                &[&[]],
+                OsRng,
+                &mut transcript,
+            )
+            .unwrap();
+            transcript.finalize()
+        };
+        let strategy = SingleStrategy::new(&verifier_params);
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+        verify_proof::<_, VerifierGWC<_>, _, _, _>(
+            &verifier_params,
+            &verifier_vk,
+            strategy,
+            &[&[]],
+            &mut transcript,
+        )
+        .unwrap();
+
+        assert_eq!(
+            downsize_params(&raw, DOWNSIZED_K + 2).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+        assert_eq!(
+            read_srs(&raw[..raw.len() - 1]).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn test_params_max_k() {
+        // The header of params for k = 17 followed by identity points, which is laid out as the real ones.
+        let k = 17u32;
+        let mut srs = k.to_le_bytes().to_vec();
+        srs.resize(4 + 2 * (1 << k) * G1_RAW_BYTES + 2 * G2_RAW_BYTES, 0);
+        assert_eq!(params_max_k(&srs).unwrap(), 17);
+        // A circuit of k = 20 is rejected without reading the params.
+        let target_k = 20;
+        assert!(params_max_k(&srs).unwrap() < target_k);
+        srs.pop();
+        let err = params_max_k(&srs).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(params_max_k(&[17, 0]).is_err());
+        assert!(params_max_k(&40u32.to_le_bytes()).is_err());
+
+        let params = ParamsKZG::<Bn256>::setup(K, OsRng);
+        let mut params_bytes = vec![];
+        params.write(&mut params_bytes).unwrap();
+        let trimmed = extract_verifier_params(&params_bytes, 0).unwrap();
+        for bytes in [
+            crate::compress_params(&params_bytes).unwrap(),
+            trimmed.clone(),
+            crate::compress_params(&trimmed).unwrap(),
+            params_bytes,
+        ] {
+            assert_eq!(params_max_k(&bytes).unwrap(), K);
+        }
+    }
+
+    #[test]
+    fn test_gen_params_seeded() {
+        let params_bytes = |params: ParamsKZG<Bn256>| {
+            let mut bytes = vec![];
+            params.write(&mut bytes).unwrap();
+            bytes
+        };
+        let params = params_bytes(gen_params_seeded(K, 1));
+        assert_eq!(params, params_bytes(gen_params_seeded(K, 1)));
+        assert_ne!(params, params_bytes(gen_params_seeded(K, 2)));
+        assert_eq!(read_params(&params).unwrap().k(), K);
+    }
+
+    #[test]
+    fn test_read_params_rejects_forged_k() {
+        // A few bytes declaring the largest k would make halo2 allocate gigabytes of points before reaching their end.
+        let forged = MAX_PARAMS_K.to_le_bytes();
+        assert_eq!(
+            read_params(&forged).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            read_srs(&forged).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        let mut compressed = crate::compression::COMPRESSED_PARAMS_MAGIC.to_vec();
+        compressed.extend_from_slice(&forged);
+        assert_eq!(
+            crate::decompress_params(&compressed).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            params_max_k(&(MAX_PARAMS_K + 1).to_le_bytes())
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        // The trimmed params of a k larger than the scalar field supports are rejected by their header.
+        let params = ParamsKZG::<Bn256>::setup(4, OsRng);
+        let mut params_bytes = vec![];
+        params.write(&mut params_bytes).unwrap();
+        let mut trimmed = extract_verifier_params(&params_bytes, 0).unwrap();
+        trimmed[4..8].copy_from_slice(&(MAX_PARAMS_K + 1).to_le_bytes());
+        assert_eq!(
+            read_verifier_params(&trimmed).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn test_extract_verifier_params_rejects_truncated_params() {
+        let params = ParamsKZG::<Bn256>::setup(4, OsRng);
+        let mut params_bytes = vec![];
+        params.write(&mut params_bytes).unwrap();
+        params_bytes.pop();
+        assert!(extract_verifier_params(&params_bytes, 0).is_err());
+    }
+}
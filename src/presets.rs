@@ -0,0 +1,190 @@
+use crate::{normalize_signature_bytes, CircuitInfo, Pkcs1v15Circuit, Pkcs1v15Layout};
+use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+use rsa::{traits::PublicKeyParts, Pkcs1v15Sign, RsaPublicKey};
+use sha2::{Digest, Sha256};
+
+/// Defines `$layout_name` and the public alias `$circuit_name<F>` of [`Pkcs1v15Circuit`] in the same way as [`crate::impl_pkcs1v15_basic_circuit`] with a fixed `$k`.
+macro_rules! impl_pkcs1v15_preset {
+    (
+        $layout_name:ident,
+        $circuit_name:ident,
+        $bits_len:expr,
+        $msg_len:expr,
+        $num_flex_advice:expr,
+        $num_range_advice:expr,
+        $sha256_lookup_bits:expr,
+        $sha256_lookup_advice:expr,
+        $k:expr,
+        $sha2_chip_enabled:expr
+    ) => {
+        #[derive(Clone, Copy, Debug, Default)]
+        pub struct $layout_name;
+
+        impl Pkcs1v15Layout for $layout_name {
+            const NUM_ADVICE: usize = $num_flex_advice;
+            const NUM_LOOKUP_ADVICE: usize = $num_range_advice;
+            const SHA256_LOOKUP_BITS: usize = $sha256_lookup_bits;
+            const SHA256_LOOKUP_ADVICE: usize = $sha256_lookup_advice;
+            const K: Option<u32> = Some($k);
+        }
+
+        pub type $circuit_name<F> =
+            Pkcs1v15Circuit<F, { $bits_len }, { $msg_len }, { $sha2_chip_enabled }, $layout_name>;
+    };
+}
+
+impl_pkcs1v15_preset!(
+    Pkcs1v15_1024_64EnabledBenchConfig,
+    Pkcs1v15_1024_64EnabledBenchCircuit,
+    1024,
+    64,
+    3,
+    1,
+    16,
+    1,
+    19,
+    true
+);
+
+impl_pkcs1v15_preset!(
+    Pkcs1v15_1024_128EnabledBenchConfig,
+    Pkcs1v15_1024_128EnabledBenchCircuit,
+    1024,
+    128,
+    3,
+    1,
+    16,
+    1,
+    19,
+    true
+);
+
+impl_pkcs1v15_preset!(
+    Pkcs1v15_1024_1024EnabledBenchConfig,
+    Pkcs1v15_1024_1024EnabledBenchCircuit,
+    1024,
+    1024,
+    3,
+    1,
+    16,
+    1,
+    18,
+    true
+);
+
+impl_pkcs1v15_preset!(
+    Pkcs1v15_2048_64EnabledBenchConfig,
+    Pkcs1v15_2048_64EnabledBenchCircuit,
+    2048,
+    64,
+    3,
+    1,
+    16,
+    1,
+    19,
+    true
+);
+
+impl_pkcs1v15_preset!(
+    Pkcs1v15_2048_128EnabledBenchConfig,
+    Pkcs1v15_2048_128EnabledBenchCircuit,
+    2048,
+    128,
+    3,
+    1,
+    16,
+    1,
+    19,
+    true
+);
+
+impl_pkcs1v15_preset!(
+    Pkcs1v15_2048_1024EnabledBenchConfig,
+    Pkcs1v15_2048_1024EnabledBenchCircuit,
+    2048,
+    1024,
+    3,
+    1,
+    16,
+    1,
+    19,
+    true
+);
+
+impl_pkcs1v15_preset!(
+    Pkcs1v15_2048_1024DisabledBenchConfig,
+    Pkcs1v15_2048_1024DisabledBenchCircuit,
+    2048,
+    1024,
+    1,
+    1,
+    16,
+    1,
+    19,
+    false
+);
+
+/// The named pkcs1v15 circuits with the constants registered by [`Pkcs1v15Circuit`], keyed by the names in the `Pkcs1v15CircuitName` type of the wasm build.
+///
+/// The wasm exports and the `halo2-rsa` binary share these circuit types, so the keys and the proofs of either are accepted by the other.
+/// The keys are versioned with the type names of the circuits by [`crate::write_pk_versioned`], which include the module path of their layouts.
+pub const PKCS1V15_CIRCUITS: [(&str, CircuitInfo); 7] = [
+    ("1024_64", Pkcs1v15_1024_64EnabledBenchCircuit::<Fr>::INFO),
+    ("1024_128", Pkcs1v15_1024_128EnabledBenchCircuit::<Fr>::INFO),
+    (
+        "1024_1024",
+        Pkcs1v15_1024_1024EnabledBenchCircuit::<Fr>::INFO,
+    ),
+    ("2048_64", Pkcs1v15_2048_64EnabledBenchCircuit::<Fr>::INFO),
+    ("2048_128", Pkcs1v15_2048_128EnabledBenchCircuit::<Fr>::INFO),
+    (
+        "2048_1024",
+        Pkcs1v15_2048_1024EnabledBenchCircuit::<Fr>::INFO,
+    ),
+    (
+        "no_sha2_2048_1024",
+        Pkcs1v15_2048_1024DisabledBenchCircuit::<Fr>::INFO,
+    ),
+];
+
+/// Returns the constants of the circuit named `circuit_name` in [`PKCS1V15_CIRCUITS`], or `None` if no circuit has that name.
+pub fn pkcs1v15_circuit_info(circuit_name: &str) -> Option<CircuitInfo> {
+    PKCS1V15_CIRCUITS
+        .iter()
+        .find(|(name, _)| *name == circuit_name)
+        .map(|(_, info)| *info)
+}
+
+/// Verifies a pkcs1v15 signature with the rsa crate for `msg`, which is the message hashed in the circuit if `sha2_chip_enabled` is true, and its SHA256 digest otherwise.
+///
+/// The prove functions run this check before proving, so that a signature invalid for the message, e.g., because of a typo in the message, fails immediately with the reason instead of as an unsatisfiable circuit.
+/// A signature whose leading zero bytes were stripped is left-padded to the byte length of the modulus before the check.
+pub fn preflight_signature(
+    public_key: &RsaPublicKey,
+    msg: &[u8],
+    sha2_chip_enabled: bool,
+    signature: &[u8],
+) -> Result<(), String> {
+    let digest = if sha2_chip_enabled {
+        Sha256::digest(msg).to_vec()
+    } else {
+        msg.to_vec()
+    };
+    let signature =
+        normalize_signature_bytes(signature, public_key.size() * 8).ok_or_else(|| {
+            format!(
+                "the signature must be at most {} bytes, but {} bytes were given",
+                public_key.size(),
+                signature.len()
+            )
+        })?;
+    public_key
+        .verify(Pkcs1v15Sign::new::<Sha256>(), &digest, &signature)
+        .map_err(|_| {
+            if sha2_chip_enabled {
+                "the signature does not verify for the message and the public key; check that the message is exactly the signed bytes".to_string()
+            } else {
+                "the signature does not verify for the digest and the public key; check that the digest is the SHA256 hash of the signed bytes".to_string()
+            }
+        })
+}
@@ -0,0 +1,1006 @@
+use crate::{
+    decompose_biguint, AssignedRSAPubE, AssignedRSAPublicKey, AssignedRSASignature, RSAConfig,
+    RSAInstructions,
+};
+use halo2_base::halo2_proofs::plonk::Error;
+use halo2_base::QuantumCell;
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, GateInstructions},
+    utils::{biguint_to_fe, PrimeField},
+    AssignedValue, Context,
+};
+use num_bigint::BigUint;
+use poseidon::{Poseidon, Spec};
+use std::collections::BTreeMap;
+
+/// The width of the Poseidon permutation used by the key registry.
+pub const REGISTRY_POSEIDON_T: usize = 3;
+/// The rate of the Poseidon sponge used by the key registry.
+pub const REGISTRY_POSEIDON_RATE: usize = 2;
+/// The number of the full rounds of the Poseidon permutation.
+pub const REGISTRY_POSEIDON_R_F: usize = 8;
+/// The number of the partial rounds of the Poseidon permutation.
+pub const REGISTRY_POSEIDON_R_P: usize = 57;
+
+/// Computes the Poseidon hash of `inputs` outside the circuit, as [`KeyRegistryChip::poseidon`] does in the circuit.
+pub fn registry_poseidon<F: PrimeField>(inputs: &[F]) -> F {
+    let mut hasher = Poseidon::<F, REGISTRY_POSEIDON_T, REGISTRY_POSEIDON_RATE>::new(
+        REGISTRY_POSEIDON_R_F,
+        REGISTRY_POSEIDON_R_P,
+    );
+    hasher.update(inputs);
+    hasher.squeeze()
+}
+
+/// Computes the Poseidon commitment to the RSA public key `(n, e)`, which is the leaf of the key in the registry.
+///
+/// The commitment hashes the `limb_bits`-bit limbs of `n` followed by `e`, as [`KeyRegistryChip::key_commitment`] does.
+pub fn key_commitment<F: PrimeField>(
+    n: &BigUint,
+    e: &BigUint,
+    bits_len: usize,
+    limb_bits: usize,
+) -> F {
+    let mut inputs = decompose_biguint::<F>(n, bits_len / limb_bits, limb_bits);
+    inputs.push(biguint_to_fe(e));
+    registry_poseidon(&inputs)
+}
+
+/// Computes the Poseidon commitment to the modulus `n` of an RSA public key, which a [`KeyEndorser`] binds to its endorsement of the key.
+///
+/// The commitment hashes the `limb_bits`-bit limbs of `n`, as [`KeyRegistryChip::modulus_commitment`] does.
+pub fn modulus_commitment<F: PrimeField>(n: &BigUint, bits_len: usize, limb_bits: usize) -> F {
+    registry_poseidon(&decompose_biguint::<F>(n, bits_len / limb_bits, limb_bits))
+}
+
+/// A chip that proves that an RSA public key is endorsed by another trust anchor, e.g., an ECDSA chip verifying the signature of an EC root key over the commitment to the modulus.
+///
+/// [`KeyRegistryChip::verify_pkcs1v15_endorsed`] hands it the cell of [`KeyRegistryChip::modulus_commitment`], so the endorsement is bound to the modulus that verifies the RSA signature.
+pub trait KeyEndorser<F: PrimeField> {
+    /// Constrains the endorsement of the key committed to by `modulus_commitment`.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * modulus_commitment - the assigned commitment given by [`modulus_commitment`].
+    ///
+    /// # Return values
+    /// Returns the assigned bit indicating whether the trust anchor endorsed the commitment.
+    fn endorse<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        modulus_commitment: &AssignedValue<'v, F>,
+    ) -> Result<AssignedValue<'v, F>, Error>;
+}
+
+/// The row of the key commitment in the first instance column of a circuit exposing it, e.g., the one returned by [`KeyRegistryChip::verify_pkcs1v15_committed`].
+///
+/// Proofs of such circuits are for the same public key exactly when their instances at this row are equal, which [`assert_same_key_commitment`] checks without an aggregation circuit.
+pub const KEY_COMMITMENT_INSTANCE_ROW: usize = 0;
+
+/// Returns the key commitment exposed in `instances`, the instance columns of a proof, at the row [`KEY_COMMITMENT_INSTANCE_ROW`] of the first column.
+pub fn key_commitment_instance<F: PrimeField>(instances: &[Vec<F>]) -> Option<F> {
+    instances
+        .first()
+        .and_then(|column| column.get(KEY_COMMITMENT_INSTANCE_ROW))
+        .copied()
+}
+
+/// An error returned by [`assert_same_key_commitment`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyLinkError {
+    /// The instances of the first (`0`) or the second (`1`) proof have no row [`KEY_COMMITMENT_INSTANCE_ROW`] in the first column.
+    MissingKeyCommitment { proof: usize },
+    /// The two proofs expose different key commitments.
+    DifferentKeyCommitments,
+}
+
+impl std::fmt::Display for KeyLinkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingKeyCommitment { proof } => write!(
+                f,
+                "the instances of the proof {} have no key commitment at the row {}",
+                proof, KEY_COMMITMENT_INSTANCE_ROW
+            ),
+            Self::DifferentKeyCommitments => {
+                write!(f, "the proofs expose different key commitments")
+            }
+        }
+    }
+}
+
+impl std::error::Error for KeyLinkError {}
+
+/// Checks that two proofs were generated for the same public key by comparing the key commitments exposed in their instances.
+///
+/// Each proof must be verified with its instances as well, since this only compares the public inputs and links nothing by itself.
+///
+/// # Arguments
+/// * proof_a_instances - the instance columns of the first proof.
+/// * proof_b_instances - the instance columns of the second proof.
+///
+/// # Return values
+/// Returns the shared key commitment, or [`KeyLinkError`] if either proof has no key commitment or they differ.
+pub fn assert_same_key_commitment<F: PrimeField>(
+    proof_a_instances: &[Vec<F>],
+    proof_b_instances: &[Vec<F>],
+) -> Result<F, KeyLinkError> {
+    let a = key_commitment_instance(proof_a_instances)
+        .ok_or(KeyLinkError::MissingKeyCommitment { proof: 0 })?;
+    let b = key_commitment_instance(proof_b_instances)
+        .ok_or(KeyLinkError::MissingKeyCommitment { proof: 1 })?;
+    if a != b {
+        return Err(KeyLinkError::DifferentKeyCommitments);
+    }
+    Ok(a)
+}
+
+/// A sparse Merkle tree of the public key commitments, whose nodes are hashed with [`registry_poseidon`].
+///
+/// The leaf at index `i` is the commitment of the `i`-th registered key, and the empty leaves are zero.
+/// It is the native counterpart of [`KeyRegistryChip`], which is used to compute the root and the siblings given to the circuit.
+#[derive(Clone, Debug)]
+pub struct KeyRegistryTree<F: PrimeField> {
+    depth: usize,
+    leaves: BTreeMap<u64, F>,
+    empty_nodes: Vec<F>,
+}
+
+impl<F: PrimeField> KeyRegistryTree<F> {
+    /// Creates an empty tree with `2^depth` leaves.
+    pub fn new(depth: usize) -> Self {
+        assert!(depth > 0 && depth <= 64, "the depth must be in [1, 64]");
+        let mut empty_nodes = vec![F::zero()];
+        for level in 0..depth {
+            let empty = empty_nodes[level];
+            empty_nodes.push(registry_poseidon(&[empty, empty]));
+        }
+        Self {
+            depth,
+            leaves: BTreeMap::new(),
+            empty_nodes,
+        }
+    }
+
+    /// Returns the depth of the tree.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Sets the leaf at `index` to `commitment`.
+    pub fn insert(&mut self, index: u64, commitment: F) {
+        assert!(
+            self.depth == 64 || index >> self.depth == 0,
+            "the index is out of the tree"
+        );
+        self.leaves.insert(index, commitment);
+    }
+
+    /// Returns the root of the tree.
+    pub fn root(&self) -> F {
+        let layers = self.layers();
+        self.node(&layers, self.depth, 0)
+    }
+
+    /// Returns the siblings of the path from the leaf at `index` to the root, from the leaf side.
+    pub fn siblings(&self, index: u64) -> Vec<F> {
+        let layers = self.layers();
+        (0..self.depth)
+            .map(|level| self.node(&layers, level, (index >> level) ^ 1))
+            .collect()
+    }
+
+    fn node(&self, layers: &[BTreeMap<u64, F>], level: usize, position: u64) -> F {
+        layers[level]
+            .get(&position)
+            .copied()
+            .unwrap_or(self.empty_nodes[level])
+    }
+
+    /// Returns the non-empty nodes of every level, from the leaves to the root.
+    fn layers(&self) -> Vec<BTreeMap<u64, F>> {
+        let mut layers = vec![self.leaves.clone()];
+        for level in 0..self.depth {
+            let mut parents = BTreeMap::new();
+            for position in layers[level].keys() {
+                let parent = position >> 1;
+                if parents.contains_key(&parent) {
+                    continue;
+                }
+                let left = self.node(&layers, level, parent << 1);
+                let right = self.node(&layers, level, (parent << 1) | 1);
+                parents.insert(parent, registry_poseidon(&[left, right]));
+            }
+            layers.push(parents);
+        }
+        layers
+    }
+}
+
+/// Chip to verify RSA signatures whose public key is registered in a sparse Merkle tree of Poseidon key commitments.
+///
+/// The registry is shared by the verifiers, which only need its root and the index of the key in it.
+/// [`KeyRegistryChip::verify_pkcs1v15_registered`] proves that the signature is valid for the public key and that the Poseidon commitment of the key is the leaf at the index.
+/// The caller should expose the returned index and root as public inputs, e.g., with [`halo2_base::halo2_proofs::circuit::Layouter::constrain_instance`].
+#[derive(Clone, Debug)]
+pub struct KeyRegistryChip<F: PrimeField> {
+    rsa_config: RSAConfig<F>,
+    spec: Spec<F, REGISTRY_POSEIDON_T, REGISTRY_POSEIDON_RATE>,
+    depth: usize,
+}
+
+impl<F: PrimeField> KeyRegistryChip<F> {
+    /// Creates new [`KeyRegistryChip`] for a registry with `2^depth` leaves.
+    pub fn new(rsa_config: RSAConfig<F>, depth: usize) -> Self {
+        assert!(depth > 0 && depth <= 64, "the depth must be in [1, 64]");
+        Self {
+            rsa_config,
+            spec: Spec::new(REGISTRY_POSEIDON_R_F, REGISTRY_POSEIDON_R_P),
+            depth,
+        }
+    }
+
+    /// Getter for [`RSAConfig`].
+    pub fn rsa_config(&self) -> &RSAConfig<F> {
+        &self.rsa_config
+    }
+
+    /// Returns the depth of the registry.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Verifies a pkcs1v15 signature for `hashed_msg` with a public key registered at `index` of the registry.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * public_key - an assigned public key.
+    /// * index - an assigned index of the key in the registry, which is range checked to `depth` bits.
+    /// * siblings - the assigned siblings of the path from the leaf at `index` to the root, from the leaf side.
+    /// * hashed_msg - the assigned limbs of the SHA256 digest, as [`RSAInstructions::verify_pkcs1v15_signature`] takes.
+    /// * signature - an assigned signature.
+    ///
+    /// # Return values
+    /// Returns the bit indicating whether the signature is valid and the root of the registry computed from the key commitment.
+    /// The key is registered at `index` only if the returned root equals the root of the registry.
+    pub fn verify_pkcs1v15_registered<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        public_key: &AssignedRSAPublicKey<'v, F>,
+        index: &AssignedValue<'v, F>,
+        siblings: &[AssignedValue<'v, F>],
+        hashed_msg: &[AssignedValue<'v, F>],
+        signature: &AssignedRSASignature<'v, F>,
+    ) -> Result<(AssignedValue<'v, F>, AssignedValue<'v, F>), Error> {
+        let is_valid = self
+            .rsa_config
+            .verify_pkcs1v15_signature(ctx, public_key, hashed_msg, signature)?;
+        let commitment = self.key_commitment(ctx, public_key);
+        let root = self.registry_root(ctx, &commitment, index, siblings)?;
+        Ok((is_valid, root))
+    }
+
+    /// Verifies a pkcs1v15 signature for `hashed_msg` with `public_key` and computes the commitment to the key.
+    ///
+    /// The caller should constrain the returned commitment to the row [`KEY_COMMITMENT_INSTANCE_ROW`] of its first instance column,
+    /// so that separate proofs for the same key can be linked by [`assert_same_key_commitment`].
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * public_key - an assigned public key.
+    /// * hashed_msg - the assigned limbs of the SHA256 digest, as [`RSAInstructions::verify_pkcs1v15_signature`] takes.
+    /// * signature - an assigned signature.
+    ///
+    /// # Return values
+    /// Returns the bit indicating whether the signature is valid and the commitment given by [`key_commitment`].
+    pub fn verify_pkcs1v15_committed<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        public_key: &AssignedRSAPublicKey<'v, F>,
+        hashed_msg: &[AssignedValue<'v, F>],
+        signature: &AssignedRSASignature<'v, F>,
+    ) -> Result<(AssignedValue<'v, F>, AssignedValue<'v, F>), Error> {
+        let is_valid = self
+            .rsa_config
+            .verify_pkcs1v15_signature(ctx, public_key, hashed_msg, signature)?;
+        let commitment = self.key_commitment(ctx, public_key);
+        Ok((is_valid, commitment))
+    }
+
+    /// Verifies a pkcs1v15 signature for `hashed_msg` with `public_key` and the endorsement of `public_key` by `endorser`.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * public_key - an assigned public key.
+    /// * hashed_msg - the assigned limbs of the SHA256 digest, as [`RSAInstructions::verify_pkcs1v15_signature`] takes.
+    /// * signature - an assigned signature.
+    /// * endorser - a [`KeyEndorser`] of the trust anchor.
+    ///
+    /// # Return values
+    /// Returns the bit indicating whether the signature is valid and the key is endorsed, and the commitment given by [`modulus_commitment`].
+    pub fn verify_pkcs1v15_endorsed<'v, E: KeyEndorser<F>>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        public_key: &AssignedRSAPublicKey<'v, F>,
+        hashed_msg: &[AssignedValue<'v, F>],
+        signature: &AssignedRSASignature<'v, F>,
+        endorser: &E,
+    ) -> Result<(AssignedValue<'v, F>, AssignedValue<'v, F>), Error> {
+        let is_sign_valid = self
+            .rsa_config
+            .verify_pkcs1v15_signature(ctx, public_key, hashed_msg, signature)?;
+        let commitment = self.modulus_commitment(ctx, public_key);
+        let is_endorsed = endorser.endorse(ctx, &commitment)?;
+        let is_valid = self.gate().and(
+            ctx,
+            QuantumCell::Existing(&is_sign_valid),
+            QuantumCell::Existing(&is_endorsed),
+        );
+        Ok((is_valid, commitment))
+    }
+
+    /// Computes the Poseidon commitment to the modulus of `public_key` as [`modulus_commitment`] does.
+    pub fn modulus_commitment<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        public_key: &AssignedRSAPublicKey<'v, F>,
+    ) -> AssignedValue<'v, F> {
+        self.poseidon(ctx, public_key.n.limbs())
+    }
+
+    /// Computes the Poseidon commitment to `public_key` as [`key_commitment`] does.
+    pub fn key_commitment<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        public_key: &AssignedRSAPublicKey<'v, F>,
+    ) -> AssignedValue<'v, F> {
+        let mut inputs = public_key.n.limbs().to_vec();
+        let e = match &public_key.e {
+            AssignedRSAPubE::Var(e) => e.clone(),
+            AssignedRSAPubE::Fix(e) => self.gate().load_constant(ctx, biguint_to_fe(e)),
+        };
+        inputs.push(e);
+        self.poseidon(ctx, &inputs)
+    }
+
+    /// Computes the root of the registry from the leaf at `index` and its siblings.
+    ///
+    /// Returns [`Error::Synthesis`] if the number of the siblings differs from the depth of the registry.
+    pub fn registry_root<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        leaf: &AssignedValue<'v, F>,
+        index: &AssignedValue<'v, F>,
+        siblings: &[AssignedValue<'v, F>],
+    ) -> Result<AssignedValue<'v, F>, Error> {
+        if siblings.len() != self.depth {
+            return Err(Error::Synthesis);
+        }
+        let gate = self.gate();
+        // `num_to_bits` also constrains `index` to be less than `2^depth`.
+        let index_bits = gate.num_to_bits(ctx, index, self.depth);
+        let mut node = leaf.clone();
+        for (bit, sibling) in index_bits.iter().zip(siblings.iter()) {
+            // The node is the right child if the bit is one.
+            let left = gate.select(
+                ctx,
+                QuantumCell::Existing(sibling),
+                QuantumCell::Existing(&node),
+                QuantumCell::Existing(bit),
+            );
+            let right = gate.select(
+                ctx,
+                QuantumCell::Existing(&node),
+                QuantumCell::Existing(sibling),
+                QuantumCell::Existing(bit),
+            );
+            node = self.poseidon(ctx, &[left, right]);
+        }
+        Ok(node)
+    }
+
+    /// Computes the Poseidon hash of `inputs` as [`registry_poseidon`] does.
+    pub fn poseidon<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        inputs: &[AssignedValue<'v, F>],
+    ) -> AssignedValue<'v, F> {
+        let gate = self.gate();
+        let mut state = [
+            gate.load_constant(ctx, F::from_u128(1u128 << 64)),
+            gate.load_constant(ctx, F::zero()),
+            gate.load_constant(ctx, F::zero()),
+        ];
+        // The sponge pads the inputs with one, even if they fill the last chunk.
+        let one = gate.load_constant(ctx, F::one());
+        let mut padded = inputs.to_vec();
+        let num_full_chunks = padded.len() / REGISTRY_POSEIDON_RATE;
+        padded.push(one);
+        let (full, last) = padded.split_at(num_full_chunks * REGISTRY_POSEIDON_RATE);
+        for chunk in full.chunks(REGISTRY_POSEIDON_RATE) {
+            self.permute(ctx, &mut state, chunk);
+        }
+        self.permute(ctx, &mut state, last);
+        state[1].clone()
+    }
+
+    /// Getter for [`FlexGateConfig`].
+    pub fn gate(&self) -> &FlexGateConfig<F> {
+        self.rsa_config.gate()
+    }
+
+    /// Absorbs `inputs` into `state` and permutes it, with the optimized round constants and matrices of the `poseidon` crate.
+    fn permute<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        state: &mut [AssignedValue<'v, F>; REGISTRY_POSEIDON_T],
+        inputs: &[AssignedValue<'v, F>],
+    ) {
+        let gate = self.gate();
+        let r_f = REGISTRY_POSEIDON_R_F / 2;
+        let constants = self.spec.constants();
+        let mds_matrices = self.spec.mds_matrices();
+        let mds = mds_matrices.mds().rows();
+        let pre_sparse_mds = mds_matrices.pre_sparse_mds().rows();
+
+        // 1. The first half of the full rounds, whose first constants are added along with the inputs.
+        let start = constants.start();
+        for (i, word) in state.iter_mut().enumerate() {
+            let mut terms = vec![
+                QuantumCell::Existing(&*word),
+                QuantumCell::Constant(start[0][i]),
+            ];
+            if i > 0 {
+                if let Some(input) = inputs.get(i - 1) {
+                    terms.push(QuantumCell::Existing(input));
+                }
+            }
+            *word = gate.sum(ctx, terms);
+        }
+        for round_constants in start.iter().skip(1).take(r_f - 1) {
+            self.sbox_full(ctx, state, round_constants);
+            self.apply_mds(ctx, state, &mds);
+        }
+        self.sbox_full(ctx, state, start.last().unwrap());
+        self.apply_mds(ctx, state, &pre_sparse_mds);
+
+        // 2. The partial rounds with the sparse matrices.
+        for (constant, sparse_mds) in constants
+            .partial()
+            .iter()
+            .zip(mds_matrices.sparse_matrices().iter())
+        {
+            state[0] = self.sbox(ctx, &state[0], *constant);
+            let row = sparse_mds.row();
+            let first = gate.inner_product(
+                ctx,
+                state.iter().map(QuantumCell::Existing).collect(),
+                row.iter().map(|m| QuantumCell::Constant(*m)).collect(),
+            );
+            // The other words add the first word before the multiplication times `col_hat`.
+            let (head, tail) = state.split_at_mut(1);
+            for (word, col) in tail.iter_mut().zip(sparse_mds.col_hat().iter()) {
+                *word = gate.mul_add(
+                    ctx,
+                    QuantumCell::Existing(&head[0]),
+                    QuantumCell::Constant(*col),
+                    QuantumCell::Existing(&*word),
+                );
+            }
+            head[0] = first;
+        }
+
+        // 3. The second half of the full rounds, whose last round adds no constants.
+        for round_constants in constants.end().iter() {
+            self.sbox_full(ctx, state, round_constants);
+            self.apply_mds(ctx, state, &mds);
+        }
+        self.sbox_full(ctx, state, &[F::zero(); REGISTRY_POSEIDON_T]);
+        self.apply_mds(ctx, state, &mds);
+    }
+
+    fn sbox_full<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        state: &mut [AssignedValue<'v, F>; REGISTRY_POSEIDON_T],
+        constants: &[F; REGISTRY_POSEIDON_T],
+    ) {
+        for (word, constant) in state.iter_mut().zip(constants.iter()) {
+            *word = self.sbox(ctx, word, *constant);
+        }
+    }
+
+    /// Computes `x^5 + constant`.
+    fn sbox<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        x: &AssignedValue<'v, F>,
+        constant: F,
+    ) -> AssignedValue<'v, F> {
+        let gate = self.gate();
+        let x2 = gate.mul(ctx, QuantumCell::Existing(x), QuantumCell::Existing(x));
+        let x4 = gate.mul(ctx, QuantumCell::Existing(&x2), QuantumCell::Existing(&x2));
+        gate.mul_add(
+            ctx,
+            QuantumCell::Existing(x),
+            QuantumCell::Existing(&x4),
+            QuantumCell::Constant(constant),
+        )
+    }
+
+    fn apply_mds<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        state: &mut [AssignedValue<'v, F>; REGISTRY_POSEIDON_T],
+        mds: &[[F; REGISTRY_POSEIDON_T]; REGISTRY_POSEIDON_T],
+    ) {
+        let gate = self.gate();
+        let new_state = mds.map(|row| {
+            gate.inner_product(
+                ctx,
+                state.iter().map(QuantumCell::Existing).collect(),
+                row.iter().map(|m| QuantumCell::Constant(*m)).collect(),
+            )
+        });
+        *state = new_state;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{BigUintConfig, BigUintInstructions, RSAPubE, RSAPublicKey, RSASignature};
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, Column, ConstraintSystem, Instance},
+    };
+    use halo2_base::{
+        gates::range::{RangeConfig, RangeStrategy::Vertical},
+        SKIP_FIRST_PASS,
+    };
+    use rand::thread_rng;
+    use rsa::{
+        pkcs1v15::SigningKey, signature::SignatureEncoding, signature::Signer,
+        traits::PublicKeyParts, RsaPrivateKey,
+    };
+    use sha2::{Digest, Sha256};
+    use std::marker::PhantomData;
+
+    #[derive(Debug, Clone)]
+    struct TestRegisteredKeyConfig<F: PrimeField> {
+        rsa_config: RSAConfig<F>,
+        registry_instance: Column<Instance>,
+    }
+
+    struct TestRegisteredKeyCircuit<F: PrimeField> {
+        private_key: RsaPrivateKey,
+        msg: Vec<u8>,
+        index: u64,
+        siblings: Vec<F>,
+    }
+
+    impl<F: PrimeField> TestRegisteredKeyCircuit<F> {
+        const BITS_LEN: usize = 1024;
+        const LIMB_BITS: usize = 64;
+        const DEPTH: usize = 4;
+        const K: usize = 15;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestRegisteredKeyCircuit<F> {
+        type Config = TestRegisteredKeyConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config =
+                RangeConfig::configure(meta, Vertical, &[80], &[16], 1, 12, 0, Self::K);
+            let biguint_config = BigUintConfig::construct(range_config, Self::LIMB_BITS);
+            let rsa_config = RSAConfig::construct(biguint_config, Self::BITS_LEN, 5);
+            let registry_instance = meta.instance_column();
+            meta.enable_equality(registry_instance);
+            Self::Config {
+                rsa_config,
+                registry_instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = KeyRegistryChip::new(config.rsa_config.clone(), Self::DEPTH);
+            let biguint_config = config.rsa_config.biguint_config();
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let public_cells = layouter.assign_region(
+                || "rsa signature with a registered public key",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(vec![]);
+                    }
+
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    let gate = chip.gate();
+                    let signing_key = SigningKey::<Sha256>::new(self.private_key.clone());
+                    let sign = signing_key.sign(&self.msg).to_vec();
+                    let sign = config.rsa_config.assign_signature(
+                        ctx,
+                        RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+                    )?;
+                    let n_big = BigUint::from_bytes_be(&self.private_key.n().to_bytes_be());
+                    let e_fix = RSAPubE::Fix(BigUint::from(65537u32));
+                    let public_key = config
+                        .rsa_config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                    let digest = BigUint::from_bytes_be(&Sha256::digest(&self.msg));
+                    let hashed_msg = decompose_biguint::<F>(&digest, 4, 64)
+                        .into_iter()
+                        .map(|limb| gate.load_witness(ctx, Value::known(limb)))
+                        .collect::<Vec<AssignedValue<F>>>();
+                    let index = gate.load_witness(ctx, Value::known(F::from(self.index)));
+                    let siblings = self
+                        .siblings
+                        .iter()
+                        .map(|sibling| gate.load_witness(ctx, Value::known(*sibling)))
+                        .collect::<Vec<AssignedValue<F>>>();
+                    let (is_valid, root) = chip.verify_pkcs1v15_registered(
+                        ctx,
+                        &public_key,
+                        &index,
+                        &siblings,
+                        &hashed_msg,
+                        &sign,
+                    )?;
+                    gate.assert_is_const(ctx, &is_valid, F::one());
+                    biguint_config.range().finalize(ctx);
+                    Ok(vec![index.cell(), root.cell()])
+                },
+            )?;
+            for (i, cell) in public_cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell, config.registry_instance, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    struct TestCommittedKeyCircuit<F: PrimeField> {
+        private_key: RsaPrivateKey,
+        msg: Vec<u8>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestCommittedKeyCircuit<F> {
+        type Config = TestRegisteredKeyConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            TestRegisteredKeyCircuit::<F>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = KeyRegistryChip::new(
+                config.rsa_config.clone(),
+                TestRegisteredKeyCircuit::<F>::DEPTH,
+            );
+            let biguint_config = config.rsa_config.biguint_config();
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let commitment_cell = layouter.assign_region(
+                || "rsa signature with a committed public key",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(None);
+                    }
+
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    let gate = chip.gate();
+                    let signing_key = SigningKey::<Sha256>::new(self.private_key.clone());
+                    let sign = signing_key.sign(&self.msg).to_vec();
+                    let sign = config.rsa_config.assign_signature(
+                        ctx,
+                        RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+                    )?;
+                    let n_big = BigUint::from_bytes_be(&self.private_key.n().to_bytes_be());
+                    let e_fix = RSAPubE::Fix(BigUint::from(65537u32));
+                    let public_key = config
+                        .rsa_config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                    let digest = BigUint::from_bytes_be(&Sha256::digest(&self.msg));
+                    let hashed_msg = decompose_biguint::<F>(&digest, 4, 64)
+                        .into_iter()
+                        .map(|limb| gate.load_witness(ctx, Value::known(limb)))
+                        .collect::<Vec<AssignedValue<F>>>();
+                    let (is_valid, commitment) =
+                        chip.verify_pkcs1v15_committed(ctx, &public_key, &hashed_msg, &sign)?;
+                    gate.assert_is_const(ctx, &is_valid, F::one());
+                    biguint_config.range().finalize(ctx);
+                    Ok(Some(commitment.cell()))
+                },
+            )?;
+            if let Some(cell) = commitment_cell {
+                layouter.constrain_instance(
+                    cell,
+                    config.registry_instance,
+                    KEY_COMMITMENT_INSTANCE_ROW,
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    /// A stand-in for an ECDSA chip, which would assign the commitment signed by the EC root from the message of its signature.
+    struct StubEndorser<F: PrimeField> {
+        gate: FlexGateConfig<F>,
+        endorsed: F,
+    }
+
+    impl<F: PrimeField> KeyEndorser<F> for StubEndorser<F> {
+        fn endorse<'v>(
+            &self,
+            ctx: &mut Context<'v, F>,
+            modulus_commitment: &AssignedValue<'v, F>,
+        ) -> Result<AssignedValue<'v, F>, Error> {
+            let endorsed = self.gate.load_witness(ctx, Value::known(self.endorsed));
+            Ok(self.gate.is_equal(
+                ctx,
+                QuantumCell::Existing(&endorsed),
+                QuantumCell::Existing(modulus_commitment),
+            ))
+        }
+    }
+
+    struct TestEndorsedKeyCircuit<F: PrimeField> {
+        private_key: RsaPrivateKey,
+        msg: Vec<u8>,
+        endorsed: F,
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestEndorsedKeyCircuit<F> {
+        type Config = TestRegisteredKeyConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            TestRegisteredKeyCircuit::<F>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = KeyRegistryChip::new(
+                config.rsa_config.clone(),
+                TestRegisteredKeyCircuit::<F>::DEPTH,
+            );
+            let endorser = StubEndorser {
+                gate: chip.gate().clone(),
+                endorsed: self.endorsed,
+            };
+            let biguint_config = config.rsa_config.biguint_config();
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "rsa signature with an endorsed public key",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    let gate = chip.gate();
+                    let signing_key = SigningKey::<Sha256>::new(self.private_key.clone());
+                    let sign = signing_key.sign(&self.msg).to_vec();
+                    let sign = config.rsa_config.assign_signature(
+                        ctx,
+                        RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+                    )?;
+                    let n_big = BigUint::from_bytes_be(&self.private_key.n().to_bytes_be());
+                    let e_fix = RSAPubE::Fix(BigUint::from(65537u32));
+                    let public_key = config
+                        .rsa_config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                    let digest = BigUint::from_bytes_be(&Sha256::digest(&self.msg));
+                    let hashed_msg = decompose_biguint::<F>(&digest, 4, 64)
+                        .into_iter()
+                        .map(|limb| gate.load_witness(ctx, Value::known(limb)))
+                        .collect::<Vec<AssignedValue<F>>>();
+                    let (is_valid, _) = chip.verify_pkcs1v15_endorsed(
+                        ctx,
+                        &public_key,
+                        &hashed_msg,
+                        &sign,
+                        &endorser,
+                    )?;
+                    gate.assert_is_const(ctx, &is_valid, F::one());
+                    biguint_config.range().finalize(ctx);
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_endorsed_key_binds_modulus_commitment() {
+        let bits_len = TestRegisteredKeyCircuit::<Fr>::BITS_LEN;
+        let limb_bits = TestRegisteredKeyCircuit::<Fr>::LIMB_BITS;
+        let mut rng = thread_rng();
+        let keys = (0..2)
+            .map(|_| RsaPrivateKey::new(&mut rng, bits_len).expect("failed to generate a key"))
+            .collect::<Vec<_>>();
+        let modulus_of = |key: &RsaPrivateKey| BigUint::from_bytes_be(&key.n().to_bytes_be());
+        let run = |endorsed: Fr| {
+            let circuit = TestEndorsedKeyCircuit::<Fr> {
+                private_key: keys[0].clone(),
+                msg: b"hello".to_vec(),
+                endorsed,
+            };
+            let k = TestRegisteredKeyCircuit::<Fr>::K as u32;
+            MockProver::run(k, &circuit, vec![vec![]])
+                .unwrap()
+                .verify()
+                .is_ok()
+        };
+        assert!(run(modulus_commitment(
+            &modulus_of(&keys[0]),
+            bits_len,
+            limb_bits
+        )));
+        // The endorsement of another key, or of a commitment to the whole key, does not endorse the modulus.
+        assert!(!run(modulus_commitment(
+            &modulus_of(&keys[1]),
+            bits_len,
+            limb_bits
+        )));
+        assert!(!run(key_commitment(
+            &modulus_of(&keys[0]),
+            &BigUint::from(65537u32),
+            bits_len,
+            limb_bits
+        )));
+    }
+
+    #[test]
+    fn test_same_key_commitment_links_proofs() {
+        let bits_len = TestRegisteredKeyCircuit::<Fr>::BITS_LEN;
+        let limb_bits = TestRegisteredKeyCircuit::<Fr>::LIMB_BITS;
+        let mut rng = thread_rng();
+        let keys = (0..2)
+            .map(|_| RsaPrivateKey::new(&mut rng, bits_len).expect("failed to generate a key"))
+            .collect::<Vec<_>>();
+        // Returns the instances of a proof of `msg` signed by `key` after checking that they satisfy the circuit.
+        let prove = |key: &RsaPrivateKey, msg: &[u8]| {
+            let commitment = key_commitment::<Fr>(
+                &BigUint::from_bytes_be(&key.n().to_bytes_be()),
+                &BigUint::from_bytes_be(&key.e().to_bytes_be()),
+                bits_len,
+                limb_bits,
+            );
+            let circuit = TestCommittedKeyCircuit::<Fr> {
+                private_key: key.clone(),
+                msg: msg.to_vec(),
+                _f: PhantomData,
+            };
+            let k = TestRegisteredKeyCircuit::<Fr>::K as u32;
+            let instances = vec![vec![commitment]];
+            assert_eq!(
+                MockProver::run(k, &circuit, instances.clone())
+                    .unwrap()
+                    .verify(),
+                Ok(())
+            );
+            // The exposed commitment is bound to the key.
+            let forged = vec![vec![commitment + Fr::one()]];
+            assert!(MockProver::run(k, &circuit, forged)
+                .unwrap()
+                .verify()
+                .is_err());
+            instances
+        };
+
+        let first = prove(&keys[0], b"hello");
+        let second = prove(&keys[0], b"another message");
+        let other_key = prove(&keys[1], b"hello");
+        assert_eq!(
+            assert_same_key_commitment(&first, &second),
+            Ok(first[0][KEY_COMMITMENT_INSTANCE_ROW])
+        );
+        assert_eq!(
+            assert_same_key_commitment(&first, &other_key),
+            Err(KeyLinkError::DifferentKeyCommitments)
+        );
+        assert_eq!(
+            assert_same_key_commitment(&first, &[]),
+            Err(KeyLinkError::MissingKeyCommitment { proof: 1 })
+        );
+        assert_eq!(
+            assert_same_key_commitment(&[vec![]], &first),
+            Err(KeyLinkError::MissingKeyCommitment { proof: 0 })
+        );
+    }
+
+    #[test]
+    fn test_registered_key_in_two_entry_registry() {
+        let bits_len = TestRegisteredKeyCircuit::<Fr>::BITS_LEN;
+        let limb_bits = TestRegisteredKeyCircuit::<Fr>::LIMB_BITS;
+        let mut rng = thread_rng();
+        let keys = (0..3)
+            .map(|_| RsaPrivateKey::new(&mut rng, bits_len).expect("failed to generate a key"))
+            .collect::<Vec<_>>();
+        let commitment_of = |key: &RsaPrivateKey| {
+            key_commitment::<Fr>(
+                &BigUint::from_bytes_be(&key.n().to_bytes_be()),
+                &BigUint::from_bytes_be(&key.e().to_bytes_be()),
+                bits_len,
+                limb_bits,
+            )
+        };
+        // Only the first two keys are registered, at the indexes 1 and 6.
+        let mut registry = KeyRegistryTree::<Fr>::new(TestRegisteredKeyCircuit::<Fr>::DEPTH);
+        registry.insert(1, commitment_of(&keys[0]));
+        registry.insert(6, commitment_of(&keys[1]));
+        let root = registry.root();
+        let k = TestRegisteredKeyCircuit::<Fr>::K as u32;
+        let run = |key: &RsaPrivateKey, index: u64, instances: Vec<Fr>| {
+            let circuit = TestRegisteredKeyCircuit::<Fr> {
+                private_key: key.clone(),
+                msg: b"hello registry".to_vec(),
+                index,
+                siblings: registry.siblings(index),
+            };
+            MockProver::run(k, &circuit, vec![instances])
+                .unwrap()
+                .verify()
+        };
+
+        assert_eq!(run(&keys[0], 1, vec![Fr::from(1), root]), Ok(()));
+        assert_eq!(run(&keys[1], 6, vec![Fr::from(6), root]), Ok(()));
+        // The key is not registered at another index.
+        assert!(run(&keys[0], 6, vec![Fr::from(6), root]).is_err());
+        // An unregistered key does not lead to the root, even with the siblings of a registered one.
+        assert!(run(&keys[2], 1, vec![Fr::from(1), root]).is_err());
+    }
+
+    #[test]
+    fn test_registry_tree_siblings_lead_to_root() {
+        let mut registry = KeyRegistryTree::<Fr>::new(3);
+        let empty_root = registry.root();
+        registry.insert(2, Fr::from(7));
+        registry.insert(5, Fr::from(11));
+        assert_ne!(registry.root(), empty_root);
+        for (index, leaf) in [(2u64, Fr::from(7)), (5, Fr::from(11)), (0, Fr::zero())] {
+            let mut node = leaf;
+            for (level, sibling) in registry.siblings(index).into_iter().enumerate() {
+                node = if (index >> level) & 1 == 1 {
+                    registry_poseidon(&[sibling, node])
+                } else {
+                    registry_poseidon(&[node, sibling])
+                };
+            }
+            assert_eq!(node, registry.root());
+        }
+    }
+}
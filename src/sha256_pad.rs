@@ -0,0 +1,355 @@
+//! The padding of SHA256 messages (FIPS 180-4, section 5.1.1) in the circuit, i.e., `0x80`, zeros, and the 64 bits big-endian bit length of the message up to a multiple of 64 bytes.
+use crate::cost_report::{begin_cost_region, end_cost_region};
+use crate::dkim_canon::assign_in_range;
+use halo2_base::halo2_proofs::plonk::Error;
+use halo2_base::QuantumCell;
+use halo2_base::{
+    gates::{range::RangeConfig, GateInstructions, RangeInstructions},
+    utils::{fe_to_biguint, PrimeField},
+    AssignedValue, Context,
+};
+use num_traits::ToPrimitive;
+
+/// The byte size of a SHA256 block.
+pub const SHA256_BLOCK_SIZE: usize = 64;
+
+/// Returns the number of the SHA256 blocks of a message of `msg_len` bytes after the padding.
+pub fn sha256_num_blocks(msg_len: usize) -> usize {
+    (msg_len + 9 + SHA256_BLOCK_SIZE - 1) / SHA256_BLOCK_SIZE
+}
+
+/// Returns `msg` followed by its SHA256 padding.
+pub fn sha256_padded(msg: &[u8]) -> Vec<u8> {
+    let mut padded = msg.to_vec();
+    padded.push(0x80);
+    padded.resize(sha256_num_blocks(msg.len()) * SHA256_BLOCK_SIZE - 8, 0);
+    padded.extend_from_slice(&(8 * msg.len() as u64).to_be_bytes());
+    padded
+}
+
+/// The assigned padded message returned by [`Sha256PadChip::sha256_pad`].
+#[derive(Clone, Debug)]
+pub struct AssignedSha256Padded<'v, F: PrimeField> {
+    /// the bytes of the message followed by its padding and zeros up to the blocks of the longest message.
+    pub bytes: Vec<AssignedValue<'v, F>>,
+    /// the number of the blocks of the padded message, i.e., [`sha256_num_blocks`] of the message length.
+    pub num_blocks: AssignedValue<'v, F>,
+}
+
+/// Chip to pad the messages of variable lengths for SHA256 in the circuit, as [`sha256_padded`] does.
+///
+/// The padded bytes can be fed to a SHA256 compression function block by block, e.g., to hash a message in several circuits or to skip the blocks beyond the padded message.
+#[derive(Clone, Debug)]
+pub struct Sha256PadChip<F: PrimeField> {
+    range: RangeConfig<F>,
+}
+
+impl<F: PrimeField> Sha256PadChip<F> {
+    /// Creates a new [`Sha256PadChip`] from the range config.
+    ///
+    /// The lookup bits of `range` must be at least 8 to range-check the message bytes in one lookup.
+    pub fn new(range: RangeConfig<F>) -> Self {
+        Self { range }
+    }
+
+    /// Getter for [`RangeConfig`].
+    pub fn range(&self) -> &RangeConfig<F> {
+        &self.range
+    }
+
+    /// Given the assigned bytes of a message padded with any bytes up to its maximum length and its assigned byte length `actual_len`, returns the assigned bytes of the message followed by its SHA256 padding.
+    ///
+    /// The returned bytes fill [`sha256_num_blocks`] of `msg_bytes.len()` blocks, and those after the padding are zeros.
+    /// The bit length at the tail of the padding is computed from `actual_len`, so it always matches the length of the hashed message.
+    /// The constraints are not satisfied if a byte of `msg_bytes` is not a byte or `actual_len` exceeds `msg_bytes.len()`.
+    /// The bytes of `msg_bytes` beyond `actual_len` do not affect the padded bytes.
+    ///
+    /// # Panics
+    /// Panics if `msg_bytes` is empty.
+    pub fn sha256_pad<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        msg_bytes: &[AssignedValue<'v, F>],
+        actual_len: &AssignedValue<'v, F>,
+    ) -> Result<AssignedSha256Padded<'v, F>, Error> {
+        assert!(!msg_bytes.is_empty(), "the maximum length must be positive");
+        begin_cost_region(ctx, "sha256 padding");
+        let gate = self.range.gate();
+        let max_len = msg_bytes.len();
+        let max_num_blocks = sha256_num_blocks(max_len);
+        for byte in msg_bytes.iter() {
+            self.range.range_check(ctx, byte, 8);
+        }
+        let in_range = assign_in_range(gate, ctx, actual_len, max_len);
+
+        // 1. The number of the blocks `b` satisfies `0 <= 64 * b - (actual_len + 9) < 64`.
+        let num_blocks = gate.load_witness(
+            ctx,
+            actual_len.value().map(|len| {
+                let len = fe_to_biguint(len).to_usize().unwrap_or(0);
+                F::from(sha256_num_blocks(len) as u64)
+            }),
+        );
+        let len_plus_9 = gate.add(
+            ctx,
+            QuantumCell::Existing(actual_len),
+            QuantumCell::Constant(F::from(9)),
+        );
+        let blocks_len = gate.mul(
+            ctx,
+            QuantumCell::Existing(&num_blocks),
+            QuantumCell::Constant(F::from(SHA256_BLOCK_SIZE as u64)),
+        );
+        let slack = gate.sub(
+            ctx,
+            QuantumCell::Existing(&blocks_len),
+            QuantumCell::Existing(&len_plus_9),
+        );
+        // The number is range-checked as well, so that `64 * b` does not wrap around the field.
+        let num_blocks_bits = (usize::BITS - max_num_blocks.leading_zeros()) as usize;
+        self.range.range_check(ctx, &num_blocks, num_blocks_bits);
+        self.range.range_check(ctx, &slack, 6);
+        let is_last_blocks = (0..max_num_blocks)
+            .map(|i| {
+                gate.is_equal(
+                    ctx,
+                    QuantumCell::Existing(&num_blocks),
+                    QuantumCell::Constant(F::from(i as u64 + 1)),
+                )
+            })
+            .collect::<Vec<AssignedValue<F>>>();
+
+        // 2. The big-endian bytes of the bit length.
+        let bit_len = gate.mul(
+            ctx,
+            QuantumCell::Existing(actual_len),
+            QuantumCell::Constant(F::from(8)),
+        );
+        let bits = gate.num_to_bits(ctx, &bit_len, 64);
+        let len_bytes = (0..8)
+            .map(|i| {
+                let byte_bits = &bits[8 * (7 - i)..8 * (8 - i)];
+                gate.inner_product(
+                    ctx,
+                    byte_bits.iter().map(QuantumCell::Existing).collect(),
+                    (0..8)
+                        .map(|j| QuantumCell::Constant(F::from(1u64 << j)))
+                        .collect(),
+                )
+            })
+            .collect::<Vec<AssignedValue<F>>>();
+
+        // 3. Each byte is the message byte before `actual_len`, `0x80` at `actual_len`, the byte of the bit length at the tail of the last block, and zero otherwise.
+        let one = gate.load_constant(ctx, F::one());
+        let zero = gate.load_zero(ctx);
+        // The byte is at `actual_len` if and only if the previous one is before it and this one is not.
+        let is_ends = (0..=max_len)
+            .map(|i| {
+                let is_prev_in_range = if i == 0 { &one } else { &in_range[i - 1] };
+                gate.sub(
+                    ctx,
+                    QuantumCell::Existing(is_prev_in_range),
+                    QuantumCell::Existing(in_range.get(i).unwrap_or(&zero)),
+                )
+            })
+            .collect::<Vec<AssignedValue<F>>>();
+        let mut bytes = Vec::with_capacity(max_num_blocks * SHA256_BLOCK_SIZE);
+        for i in 0..(max_num_blocks * SHA256_BLOCK_SIZE) {
+            let mut byte = zero.clone();
+            if i < max_len {
+                byte = gate.mul(
+                    ctx,
+                    QuantumCell::Existing(&in_range[i]),
+                    QuantumCell::Existing(&msg_bytes[i]),
+                );
+            }
+            if let Some(is_end) = is_ends.get(i) {
+                byte = gate.mul_add(
+                    ctx,
+                    QuantumCell::Existing(is_end),
+                    QuantumCell::Constant(F::from(0x80)),
+                    QuantumCell::Existing(&byte),
+                );
+            }
+            let offset = i % SHA256_BLOCK_SIZE;
+            if offset >= SHA256_BLOCK_SIZE - 8 {
+                byte = gate.mul_add(
+                    ctx,
+                    QuantumCell::Existing(&is_last_blocks[i / SHA256_BLOCK_SIZE]),
+                    QuantumCell::Existing(&len_bytes[offset + 8 - SHA256_BLOCK_SIZE]),
+                    QuantumCell::Existing(&byte),
+                );
+            }
+            bytes.push(byte);
+        }
+        end_cost_region(ctx);
+        Ok(AssignedSha256Padded { bytes, num_blocks })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem},
+    };
+    use halo2_base::{gates::range::RangeStrategy::Vertical, ContextParams, SKIP_FIRST_PASS};
+    use sha2::{digest::generic_array::GenericArray, Digest, Sha256};
+    use std::marker::PhantomData;
+
+    /// The initial hash value of SHA256 (FIPS 180-4, section 5.3.3).
+    const SHA256_H0: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+
+    struct TestSha256PadCircuit<F: PrimeField> {
+        msg: Vec<u8>,
+        actual_len: usize,
+        padded: Vec<u8>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestSha256PadCircuit<F> {
+        const K: usize = 12;
+        const MAX_LEN: usize = 64;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestSha256PadCircuit<F> {
+        type Config = Sha256PadChip<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config = RangeConfig::configure(meta, Vertical, &[1], &[1], 1, 8, 0, Self::K);
+            Sha256PadChip::new(range_config)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let range = config.range().clone();
+            range.load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "sha256 padding",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = Context::new(
+                        region,
+                        ContextParams {
+                            max_rows: range.gate.max_rows,
+                            num_context_ids: 1,
+                            fixed_columns: range.gate.constants.clone(),
+                        },
+                    );
+                    let ctx = &mut aux;
+                    let gate = range.gate();
+                    // The bytes beyond the length are not zeros, which must not affect the padding.
+                    let msg_bytes = (0..Self::MAX_LEN)
+                        .map(|i| {
+                            let byte = self.msg.get(i).copied().unwrap_or(0xff);
+                            gate.load_witness(ctx, Value::known(F::from(byte as u64)))
+                        })
+                        .collect::<Vec<AssignedValue<F>>>();
+                    let actual_len =
+                        gate.load_witness(ctx, Value::known(F::from(self.actual_len as u64)));
+                    let padded = config.sha256_pad(ctx, &msg_bytes, &actual_len)?;
+                    assert_eq!(
+                        padded.bytes.len(),
+                        sha256_num_blocks(Self::MAX_LEN) * SHA256_BLOCK_SIZE
+                    );
+                    for (i, byte) in padded.bytes.iter().enumerate() {
+                        let expected = self.padded.get(i).copied().unwrap_or(0);
+                        gate.assert_is_const(ctx, byte, F::from(expected as u64));
+                    }
+                    gate.assert_is_const(
+                        ctx,
+                        &padded.num_blocks,
+                        F::from((self.padded.len() / SHA256_BLOCK_SIZE) as u64),
+                    );
+                    range.finalize(ctx);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn run_pad(msg: &[u8], actual_len: usize, padded: &[u8]) -> bool {
+        let circuit = TestSha256PadCircuit::<Fr> {
+            msg: msg.to_vec(),
+            actual_len,
+            padded: padded.to_vec(),
+            _f: PhantomData,
+        };
+        let prover = match MockProver::run(TestSha256PadCircuit::<Fr>::K as u32, &circuit, vec![]) {
+            Ok(prover) => prover,
+            Err(e) => panic!("{:#?}", e),
+        };
+        prover.verify().is_ok()
+    }
+
+    fn msg_of_len(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 7 + 1) as u8).collect()
+    }
+
+    #[test]
+    fn test_sha256_padded() {
+        // 55 bytes are the longest message padded in one block.
+        assert_eq!(sha256_num_blocks(55), 1);
+        assert_eq!(sha256_num_blocks(56), 2);
+        assert_eq!(sha256_num_blocks(119), 2);
+        assert_eq!(sha256_num_blocks(120), 3);
+        for len in [0, 1, 55, 56, 63, 64, 119, 120] {
+            let msg = msg_of_len(len);
+            let padded = sha256_padded(&msg);
+            // The compression of the padded blocks is the hash of the message.
+            let mut state = SHA256_H0;
+            let blocks = padded
+                .chunks(SHA256_BLOCK_SIZE)
+                .map(GenericArray::clone_from_slice)
+                .collect::<Vec<_>>();
+            sha2::compress256(&mut state, &blocks);
+            let digest = state
+                .iter()
+                .flat_map(|word| word.to_be_bytes())
+                .collect::<Vec<u8>>();
+            assert_eq!(digest, Sha256::digest(&msg).to_vec(), "{} bytes", len);
+        }
+    }
+
+    #[test]
+    fn test_sha256_pad_chip() {
+        for len in [0, 1, 55, 56, 63, 64] {
+            let msg = msg_of_len(len);
+            assert!(run_pad(&msg, len, &sha256_padded(&msg)), "{} bytes", len);
+        }
+    }
+
+    #[test]
+    fn test_sha256_pad_chip_rejects_wrong_lengths() {
+        // The padding of the other side of the boundary, and the bit length of a longer message.
+        let msg = msg_of_len(56);
+        assert!(!run_pad(&msg, 56, &sha256_padded(&msg[..55])));
+        assert!(!run_pad(&msg[..55], 55, &sha256_padded(&msg)));
+        let mut padded = sha256_padded(&msg[..55]);
+        padded[63] += 8;
+        assert!(!run_pad(&msg[..55], 55, &padded));
+        // The length beyond the maximum length.
+        let msg = msg_of_len(64);
+        assert!(!run_pad(&msg, 65, &sha256_padded(&msg)));
+    }
+}
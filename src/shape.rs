@@ -0,0 +1,273 @@
+use crate::cost::{k_of_advice_cells, pkcs1v15_advice_cells, sha256_advice_cells};
+use crate::{CircuitInfo, DefaultPkcs1v15Layout, Pkcs1v15Layout};
+
+/// The public instances exposed by a pkcs1v15 circuit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Exposure {
+    /// No public instances.
+    None,
+    /// The four 64 bits limbs of the SHA256 digest given by [`crate::digest_instances`], which requires the SHA256 chip to be disabled.
+    Digest,
+}
+
+impl Exposure {
+    /// Returns the number of the public instances of this exposure.
+    pub const fn num_instances(&self) -> usize {
+        match self {
+            Self::None => 0,
+            Self::Digest => 4,
+        }
+    }
+}
+
+/// The validated knobs of a pkcs1v15 circuit and its minimum degree, returned by [`Pkcs1v15CircuitBuilder::build`].
+///
+/// [`crate::Pkcs1v15Circuit::SHAPE`] is the shape of each circuit type, and [`crate::impl_pkcs1v15_layout`] turns a shape into a circuit type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitShape {
+    /// The bit length of the RSA modulus.
+    pub bits_len: usize,
+    /// The maximum byte length of the message including the 9 bytes of the SHA256 padding.
+    pub msg_len: usize,
+    /// Whether the message is hashed in the circuit.
+    pub sha2_chip_enabled: bool,
+    /// The public instances of the circuit.
+    pub exposure: Exposure,
+    /// Whether the validity of the signature is a public 0/1 instance following those of [`Self::exposure`] instead of a constraint.
+    pub public_validity: bool,
+    /// The number of flex advice columns.
+    pub num_advice: usize,
+    /// The number of advice columns of the range lookup.
+    pub num_lookup_advice: usize,
+    /// The lookup bits of the SHA256 chip.
+    pub sha256_lookup_bits: usize,
+    /// The number of lookup advice columns of the SHA256 chip.
+    pub sha256_lookup_advice: usize,
+    /// The degree of the circuit.
+    pub k: u32,
+}
+
+impl CircuitShape {
+    /// Returns the constants of the circuit of this shape exposed to the frontends.
+    pub const fn info(&self) -> CircuitInfo {
+        CircuitInfo {
+            k: self.k,
+            bits_len: self.bits_len,
+            msg_len: if self.sha2_chip_enabled {
+                self.msg_len
+            } else {
+                32
+            },
+            num_instances: self.exposure.num_instances() + self.public_validity as usize,
+            sha2_chip_enabled: self.sha2_chip_enabled,
+        }
+    }
+}
+
+/// An error returned by [`Pkcs1v15CircuitBuilder::build`] for an invalid combination of the knobs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShapeError {
+    /// No cost is measured for the bit length of the modulus in [`crate::PKCS1V15_ADVICE_CELLS`].
+    UnsupportedBitsLen(usize),
+    /// The maximum message length of the SHA256 chip must be a positive multiple of 64 bytes.
+    InvalidMsgLen(usize),
+    /// The digest is public only if the SHA256 chip is disabled.
+    DigestExposureWithSha2,
+    /// Every kind of advice columns must have at least one column.
+    NoAdviceColumns,
+    /// The given degree is smaller than the minimum one for the knobs.
+    KTooSmall { k: u32, min_k: u32 },
+}
+
+impl std::fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedBitsLen(bits_len) => {
+                write!(f, "no cost is measured for {} bits moduli", bits_len)
+            }
+            Self::InvalidMsgLen(msg_len) => write!(
+                f,
+                "the maximum message length must be a positive multiple of 64 bytes, but {} bytes were given",
+                msg_len
+            ),
+            Self::DigestExposureWithSha2 => write!(
+                f,
+                "the digest is public only if the SHA256 chip is disabled"
+            ),
+            Self::NoAdviceColumns => write!(f, "every kind of advice columns must have a column"),
+            Self::KTooSmall { k, min_k } => write!(
+                f,
+                "k = {} is smaller than the minimum k = {} of the circuit",
+                k, min_k
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ShapeError {}
+
+/// A builder of the [`CircuitShape`] of a [`crate::Pkcs1v15Circuit`].
+///
+/// All the functions are `const fn`, so a shape can be built in a constant and given to [`crate::impl_pkcs1v15_layout`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pkcs1v15CircuitBuilder {
+    bits_len: usize,
+    msg_len: usize,
+    sha2_chip_enabled: bool,
+    exposure: Exposure,
+    public_validity: bool,
+    num_advice: usize,
+    num_lookup_advice: usize,
+    sha256_lookup_bits: usize,
+    sha256_lookup_advice: usize,
+    k: Option<u32>,
+}
+
+impl Default for Pkcs1v15CircuitBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pkcs1v15CircuitBuilder {
+    /// Creates a builder of a 2048 bits circuit hashing messages of up to 1024 bytes with the knobs of [`DefaultPkcs1v15Layout`].
+    pub const fn new() -> Self {
+        Self::from_layout::<DefaultPkcs1v15Layout>()
+    }
+
+    /// Creates a builder with the knobs of the layout `L`, whose sizes are those of [`Self::new`].
+    pub const fn from_layout<L: Pkcs1v15Layout>() -> Self {
+        Self {
+            bits_len: 2048,
+            msg_len: 1024,
+            sha2_chip_enabled: true,
+            exposure: if L::PUBLIC_DIGEST {
+                Exposure::Digest
+            } else {
+                Exposure::None
+            },
+            public_validity: L::PUBLIC_VALIDITY,
+            num_advice: L::NUM_ADVICE,
+            num_lookup_advice: L::NUM_LOOKUP_ADVICE,
+            sha256_lookup_bits: L::SHA256_LOOKUP_BITS,
+            sha256_lookup_advice: L::SHA256_LOOKUP_ADVICE,
+            k: L::K,
+        }
+    }
+
+    /// Sets the bit length of the RSA modulus.
+    pub const fn bits_len(mut self, bits_len: usize) -> Self {
+        self.bits_len = bits_len;
+        self
+    }
+
+    /// Sets the maximum byte length of the message including the 9 bytes of the SHA256 padding.
+    pub const fn msg_len(mut self, msg_len: usize) -> Self {
+        self.msg_len = msg_len;
+        self
+    }
+
+    /// Sets whether the message is hashed in the circuit.
+    pub const fn sha2_chip_enabled(mut self, sha2_chip_enabled: bool) -> Self {
+        self.sha2_chip_enabled = sha2_chip_enabled;
+        self
+    }
+
+    /// Sets the public instances of the circuit.
+    pub const fn expose(mut self, exposure: Exposure) -> Self {
+        self.exposure = exposure;
+        self
+    }
+
+    /// Sets whether the validity of the signature is output as a public 0/1 instance, so that a proof is created for an invalid signature as well.
+    pub const fn public_validity(mut self, public_validity: bool) -> Self {
+        self.public_validity = public_validity;
+        self
+    }
+
+    /// Sets the number of flex advice columns.
+    pub const fn num_advice(mut self, num_advice: usize) -> Self {
+        self.num_advice = num_advice;
+        self
+    }
+
+    /// Sets the number of advice columns of the range lookup.
+    pub const fn num_lookup_advice(mut self, num_lookup_advice: usize) -> Self {
+        self.num_lookup_advice = num_lookup_advice;
+        self
+    }
+
+    /// Sets the lookup bits of the SHA256 chip.
+    pub const fn sha256_lookup_bits(mut self, sha256_lookup_bits: usize) -> Self {
+        self.sha256_lookup_bits = sha256_lookup_bits;
+        self
+    }
+
+    /// Sets the number of lookup advice columns of the SHA256 chip.
+    pub const fn sha256_lookup_advice(mut self, sha256_lookup_advice: usize) -> Self {
+        self.sha256_lookup_advice = sha256_lookup_advice;
+        self
+    }
+
+    /// Sets the degree of the circuit instead of the minimum one.
+    pub const fn k(mut self, k: u32) -> Self {
+        self.k = Some(k);
+        self
+    }
+
+    /// Returns the minimum degree of the circuit, which holds the advice cells estimated by [`crate::estimate_advice_cells`] and, if the SHA256 chip is enabled, its lookup table.
+    pub const fn min_k(&self) -> Result<u32, ShapeError> {
+        let rsa_cells = match pkcs1v15_advice_cells(self.bits_len) {
+            Some(cells) => cells,
+            None => return Err(ShapeError::UnsupportedBitsLen(self.bits_len)),
+        };
+        if self.num_advice == 0 || self.num_lookup_advice == 0 {
+            return Err(ShapeError::NoAdviceColumns);
+        }
+        if !self.sha2_chip_enabled {
+            return Ok(k_of_advice_cells(rsa_cells, self.num_advice));
+        }
+        let k = k_of_advice_cells(
+            rsa_cells + sha256_advice_cells(self.msg_len),
+            self.num_advice,
+        );
+        let sha256_k = self.sha256_lookup_bits as u32 + 1;
+        Ok(if k > sha256_k { k } else { sha256_k })
+    }
+
+    /// Validates the combination of the knobs and returns the shape with the given degree, or the minimum one if not given.
+    pub const fn build(self) -> Result<CircuitShape, ShapeError> {
+        if self.sha2_chip_enabled {
+            if self.msg_len == 0 || self.msg_len % 64 != 0 {
+                return Err(ShapeError::InvalidMsgLen(self.msg_len));
+            }
+            if self.sha256_lookup_advice == 0 {
+                return Err(ShapeError::NoAdviceColumns);
+            }
+            if matches!(self.exposure, Exposure::Digest) {
+                return Err(ShapeError::DigestExposureWithSha2);
+            }
+        }
+        let min_k = match self.min_k() {
+            Ok(min_k) => min_k,
+            Err(e) => return Err(e),
+        };
+        let k = match self.k {
+            Some(k) if k < min_k => return Err(ShapeError::KTooSmall { k, min_k }),
+            Some(k) => k,
+            None => min_k,
+        };
+        Ok(CircuitShape {
+            bits_len: self.bits_len,
+            msg_len: self.msg_len,
+            sha2_chip_enabled: self.sha2_chip_enabled,
+            exposure: self.exposure,
+            public_validity: self.public_validity,
+            num_advice: self.num_advice,
+            num_lookup_advice: self.num_lookup_advice,
+            sha256_lookup_bits: self.sha256_lookup_bits,
+            sha256_lookup_advice: self.sha256_lookup_advice,
+            k,
+        })
+    }
+}
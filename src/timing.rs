@@ -0,0 +1,273 @@
+use crate::cost::{constraint_system, num_commitments, num_opening_points, num_permutation_chunks};
+use crate::{create_proof_with, MultiOpenScheme, TranscriptKind};
+use halo2_base::halo2_proofs::{
+    arithmetic::{best_fft, best_multiexp},
+    dev::MockProver,
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{Circuit, Error, ProvingKey},
+    poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG},
+};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// The split of the proving time of a proof created by [`create_proof_timed`].
+///
+/// The prover of halo2 is not instrumented, so only [`ProveTiming::total`] and [`ProveTiming::witness`] are measured as they are.
+/// The MSMs and the FFTs are timed once each for the degree of the circuit and multiplied by the numbers of them the prover runs for the columns and the arguments of the circuit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProveTiming {
+    /// The wall time of creating the proof.
+    pub total: Duration,
+    /// The time of synthesizing the circuit with its witnesses, measured by [`MockProver::run`] before the proof is created.
+    pub witness: Duration,
+    /// The estimated time of the MSMs committing to the polynomials and opening them.
+    pub msm: Duration,
+    /// The estimated time of the FFTs converting the polynomials to the coefficients and to the extended domain.
+    pub fft: Duration,
+    /// The rest of [`ProveTiming::total`], e.g., evaluating the quotient polynomial over the extended domain and the transcript, which is zero if the other parts add up to more than the total.
+    pub other: Duration,
+    /// The number of the MSMs of `2^k` points.
+    pub num_msms: usize,
+    /// The number of the FFTs of `2^k` points and over the extended domain.
+    pub num_ffts: usize,
+}
+
+impl ProveTiming {
+    /// Returns the share of `part` in [`ProveTiming::total`] in percent.
+    pub fn percent_of_total(&self, part: Duration) -> f64 {
+        if self.total.is_zero() {
+            return 0.0;
+        }
+        100.0 * part.as_secs_f64() / self.total.as_secs_f64()
+    }
+}
+
+impl fmt::Display for ProveTiming {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "total {:?}", self.total)?;
+        for (name, part) in [
+            ("witness", self.witness),
+            ("msm", self.msm),
+            ("fft", self.fft),
+            ("other", self.other),
+        ] {
+            write!(
+                f,
+                ", {} {:?} ({:.1}%)",
+                name,
+                part,
+                self.percent_of_total(part)
+            )?;
+        }
+        write!(f, " with {} MSMs and {} FFTs", self.num_msms, self.num_ffts)
+    }
+}
+
+/// Creates a proof in the same way as [`create_proof_with`] and reports how its time splits into the witness generation, the MSMs, and the FFTs.
+///
+/// A witness-bound proof gains from cheaper arithmetic in the circuit, e.g., fewer cells per modular multiplication, while an MSM- or FFT-bound one gains from a smaller degree or fewer columns.
+/// The circuit is synthesized once more by [`MockProver`] and the MSMs and the FFTs are timed after the proof, so this takes noticeably longer and more memory than [`create_proof_with`].
+///
+/// # Return values
+/// Returns the bytes of the proof and its [`ProveTiming`], or the error of [`MockProver::run`] or [`create_proof_with`].
+pub fn create_proof_timed<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[&[Fr]],
+    transcript: TranscriptKind,
+    scheme: MultiOpenScheme,
+) -> Result<(Vec<u8>, ProveTiming), Error> {
+    let domain = pk.get_vk().get_domain();
+    let k = domain.k();
+
+    let start = Instant::now();
+    MockProver::run(
+        k,
+        &circuit,
+        instances.iter().map(|column| column.to_vec()).collect(),
+    )?;
+    let witness = start.elapsed();
+
+    let start = Instant::now();
+    let proof = create_proof_with(params, pk, circuit, instances, transcript, scheme)?;
+    let total = start.elapsed();
+
+    let cs = constraint_system::<C>();
+    let num_opening_msms = match scheme {
+        MultiOpenScheme::Gwc => num_opening_points(&cs),
+        MultiOpenScheme::Shplonk => 2,
+    };
+    let num_msms = num_commitments(&cs) + cs.num_instance_columns() + num_opening_msms;
+    // Each polynomial of the witness is converted to the coefficients and then to the extended domain, and the quotient polynomial back from the extended domain.
+    let num_polys = cs.num_advice_columns()
+        + cs.num_instance_columns()
+        + 3 * cs.lookups().len()
+        + num_permutation_chunks(&cs);
+
+    let n = 1usize << k;
+    let extended_n = n << (domain.extended_k() - k);
+    // The powers of a full-width scalar, since the MSMs skip the zero windows of small scalars.
+    let mut scalars = Vec::with_capacity(extended_n);
+    let base = -Fr::from(7);
+    let mut scalar = base;
+    for _ in 0..extended_n {
+        scalars.push(scalar);
+        scalar *= base;
+    }
+    let start = Instant::now();
+    best_multiexp(&scalars[..n], &params.get_g()[..n]);
+    let msm = start.elapsed() * num_msms as u32;
+
+    let mut values = scalars[..n].to_vec();
+    let start = Instant::now();
+    best_fft(&mut values, domain.get_omega(), k);
+    let fft_n = start.elapsed();
+    let start = Instant::now();
+    best_fft(
+        &mut scalars,
+        domain.get_extended_omega(),
+        domain.extended_k(),
+    );
+    let fft_extended = start.elapsed();
+    let fft = fft_n * num_polys as u32 + fft_extended * (num_polys + 1) as u32;
+
+    Ok((
+        proof,
+        ProveTiming {
+            total,
+            witness,
+            msm,
+            fft,
+            other: total.saturating_sub(witness + msm + fft),
+            num_msms,
+            num_ffts: 2 * num_polys + 1,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{verify_proof_with, BigUintConfig, BigUintInstructions};
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{keygen_pk, keygen_vk, ConstraintSystem},
+    };
+    use halo2_base::{
+        gates::range::{RangeConfig, RangeStrategy::Vertical},
+        gates::RangeInstructions,
+        SKIP_FIRST_PASS,
+    };
+    use num_bigint::{BigUint, RandomBits};
+    use rand::{rngs::OsRng, thread_rng, Rng};
+
+    const K: u32 = 14;
+
+    /// Multiplies 1024 bits integers modulo a 1024 bits modulus [`TestTimingCircuit::NUM_MULS`] times.
+    #[derive(Default)]
+    struct TestTimingCircuit {
+        a: BigUint,
+        b: BigUint,
+        n: BigUint,
+    }
+
+    impl TestTimingCircuit {
+        const BITS_LEN: usize = 1024;
+        const NUM_MULS: usize = 8;
+
+        fn sampled() -> Self {
+            let mut rng = thread_rng();
+            let n = rng.sample::<BigUint, _>(RandomBits::new(Self::BITS_LEN as u64))
+                | (BigUint::from(1u8) << (Self::BITS_LEN - 1));
+            let a = rng.sample::<BigUint, _>(RandomBits::new(Self::BITS_LEN as u64)) % &n;
+            let b = rng.sample::<BigUint, _>(RandomBits::new(Self::BITS_LEN as u64)) % &n;
+            Self { a, b, n }
+        }
+    }
+
+    impl Circuit<Fr> for TestTimingCircuit {
+        type Config = BigUintConfig<Fr>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let range_config = RangeConfig::configure(
+                meta,
+                Vertical,
+                &[8],
+                &[1],
+                1,
+                K as usize - 1,
+                0,
+                K as usize,
+            );
+            BigUintConfig::construct(range_config, 64)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "modular multiplications",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    let n =
+                        config.assign_integer(ctx, Value::known(self.n.clone()), Self::BITS_LEN)?;
+                    let b =
+                        config.assign_integer(ctx, Value::known(self.b.clone()), Self::BITS_LEN)?;
+                    let mut a =
+                        config.assign_integer(ctx, Value::known(self.a.clone()), Self::BITS_LEN)?;
+                    for _ in 0..Self::NUM_MULS {
+                        a = config.mul_mod(ctx, &a, &b, &n)?;
+                    }
+                    config.range().finalize(ctx);
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_create_proof_timed() {
+        let params = ParamsKZG::<Bn256>::setup(K, OsRng);
+        let vk = keygen_vk(&params, &TestTimingCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &TestTimingCircuit::default()).unwrap();
+        for scheme in [MultiOpenScheme::Gwc, MultiOpenScheme::Shplonk] {
+            let (proof, timing) = create_proof_timed(
+                &params,
+                &pk,
+                TestTimingCircuit::sampled(),
+                &[],
+                TranscriptKind::Blake2b,
+                scheme,
+            )
+            .unwrap();
+            println!("{:?}: {}", scheme, timing);
+            verify_proof_with(&params, &vk, &proof, &[], TranscriptKind::Blake2b, scheme).unwrap();
+
+            for part in [timing.total, timing.witness, timing.msm, timing.fft] {
+                assert!(!part.is_zero(), "{}", timing);
+            }
+            let cs = constraint_system::<TestTimingCircuit>();
+            assert!(timing.num_msms > cs.num_advice_columns());
+            assert!(timing.num_ffts > 2 * cs.num_advice_columns());
+            // The parts add up to the total unless the estimates exceed it, which they should not by much.
+            let parts = timing.witness + timing.msm + timing.fft + timing.other;
+            assert!(parts >= timing.total, "{}", timing);
+            assert!(parts <= 2 * timing.total, "{}", timing);
+        }
+    }
+}
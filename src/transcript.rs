@@ -0,0 +1,835 @@
+#[cfg(feature = "aggregation")]
+use crate::aggregation::PoseidonTranscript;
+use crate::{check_params_k, read_verifier_params, read_vk_checked};
+use halo2_base::halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{create_proof, verify_proof, Circuit, Error, ProvingKey, VerifyingKey},
+    poly::{
+        commitment::{Prover, Verifier},
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverGWC, ProverSHPLONK, VerifierGWC, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        },
+        VerificationStrategy,
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, EncodedChallenge, Keccak256Read, Keccak256Write,
+        TranscriptRead, TranscriptReadBuffer, TranscriptWrite, TranscriptWriterBuffer,
+    },
+};
+use rand::{rngs::OsRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "aggregation")]
+use snark_verifier::loader::native::NativeLoader;
+use std::fmt;
+use std::io::{self, Write};
+
+/// The hash function of the Fiat-Shamir transcript of a proof.
+///
+/// A proof can only be verified with the same kind of transcript as the one used to create it.
+/// [`TranscriptKind::Keccak256`] is the Keccak256 transcript of halo2, which hashes the points differently from the on-chain verifiers generated by snark-verifier.
+/// `TranscriptKind::Poseidon` of the `aggregation` feature is the Poseidon transcript of snark-verifier, whose challenges a circuit recomputes cheaply, e.g., [`crate::AggregationCircuit`].
+///
+/// A proof cannot be converted to another kind of transcript, because every challenge of the prover is derived from the transcript hash.
+/// Pick the kind at proving time: a proof to be verified on EVM must be created by `create_evm_proof` of the `evm` feature.
+/// [`verify_proof_with`] reports a proof verified with the wrong kind as [`VerifyError::TranscriptMismatch`].
+#[cfg_attr(
+    all(target_arch = "wasm32", feature = "sha256"),
+    wasm_bindgen::prelude::wasm_bindgen
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptKind {
+    /// The Blake2b transcript, which the prove and verify functions have used so far.
+    #[default]
+    Blake2b,
+    /// The Keccak256 transcript of halo2, e.g., for verifiers in other languages that implement it.
+    Keccak256,
+    /// The Poseidon transcript of snark-verifier, with which the proofs can be verified in a recursion or aggregation circuit.
+    #[cfg(feature = "aggregation")]
+    Poseidon,
+}
+
+impl TranscriptKind {
+    /// All the kinds of transcript available in this build.
+    pub const ALL: &'static [TranscriptKind] = &[
+        TranscriptKind::Blake2b,
+        TranscriptKind::Keccak256,
+        #[cfg(feature = "aggregation")]
+        TranscriptKind::Poseidon,
+    ];
+
+    /// Returns the kinds of transcript other than `self`.
+    pub fn others(self) -> impl Iterator<Item = TranscriptKind> {
+        Self::ALL.iter().copied().filter(move |kind| *kind != self)
+    }
+}
+
+/// The multi-open scheme used to open the KZG commitments of a proof.
+///
+/// Both schemes use the same params and keys, so a proving key generated once can create proofs of either scheme.
+/// A proof can only be verified with the same scheme as the one used to create it.
+#[cfg_attr(
+    all(target_arch = "wasm32", feature = "sha256"),
+    wasm_bindgen::prelude::wasm_bindgen
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MultiOpenScheme {
+    /// The scheme of Gabizon, Williamson, and Ciobotaru, which the prove and verify functions have used so far.
+    #[default]
+    Gwc,
+    /// The SHPLONK scheme of Boneh, Drake, Fisch, and Gabizon, which makes smaller proofs for circuits with many columns.
+    Shplonk,
+}
+
+/// The reason why a proof was not verified.
+#[derive(Debug)]
+pub enum VerifyError {
+    /// The params could not be read.
+    InvalidParams(io::Error),
+    /// The verifying key could not be read, was generated for another circuit, or does not match the params.
+    InvalidVerifyingKey(io::Error),
+    /// The proof ended before the verifier read all of its commitments and evaluations.
+    TruncatedProof,
+    /// The proof has bytes that encode neither a curve point nor a field element.
+    MalformedProof(io::Error),
+    /// The proof was read but failed the constraint or opening check, e.g., it was created for other instances, another verifying key, or another multi-open scheme.
+    ConstraintCheckFailed,
+    /// The number of the instance columns or rows does not match the verifying key.
+    InvalidInstances,
+    /// The proof was created with the transcript `actual` but verified with `expected`. See [`TranscriptKind`].
+    TranscriptMismatch {
+        /// The kind of transcript passed to the verifier.
+        expected: TranscriptKind,
+        /// The kind of transcript with which the proof is valid.
+        actual: TranscriptKind,
+    },
+    /// The [`crate::ProofBundle`] has a version other than [`crate::PROOF_BUNDLE_VERSION`].
+    UnsupportedBundleVersion(u32),
+    /// The [`crate::ProofBundle`] was created for the circuit `actual` but verified for `expected`.
+    CircuitMismatch {
+        /// The identifier of the circuit of the verifying key.
+        expected: String,
+        /// The identifier of the circuit recorded in the bundle.
+        actual: String,
+    },
+    /// The [`crate::ProofBundle`] was created with the verifying key whose [`crate::vk_fingerprint`] is `actual` but verified with the one whose fingerprint is `expected`.
+    VerifyingKeyMismatch {
+        /// The fingerprint of the verifying key passed to the verifier.
+        expected: [u8; 32],
+        /// The fingerprint recorded in the bundle.
+        actual: [u8; 32],
+    },
+    /// Any other error returned by the verifier.
+    Other(Error),
+}
+
+impl VerifyError {
+    /// Returns the name of the variant in snake case, e.g., `truncated_proof`, for the callers that dispatch on the reason.
+    pub fn code(&self) -> &'static str {
+        match self {
+            VerifyError::InvalidParams(_) => "invalid_params",
+            VerifyError::InvalidVerifyingKey(_) => "invalid_verifying_key",
+            VerifyError::TruncatedProof => "truncated_proof",
+            VerifyError::MalformedProof(_) => "malformed_proof",
+            VerifyError::ConstraintCheckFailed => "constraint_check_failed",
+            VerifyError::InvalidInstances => "invalid_instances",
+            VerifyError::TranscriptMismatch { .. } => "transcript_mismatch",
+            VerifyError::UnsupportedBundleVersion(_) => "unsupported_bundle_version",
+            VerifyError::CircuitMismatch { .. } => "circuit_mismatch",
+            VerifyError::VerifyingKeyMismatch { .. } => "verifying_key_mismatch",
+            VerifyError::Other(_) => "other",
+        }
+    }
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::InvalidParams(e) => write!(f, "invalid params: {}", e),
+            VerifyError::InvalidVerifyingKey(e) => write!(f, "invalid verifying key: {}", e),
+            VerifyError::TruncatedProof => write!(f, "the proof is truncated"),
+            VerifyError::MalformedProof(e) => write!(f, "malformed proof: {}", e),
+            VerifyError::ConstraintCheckFailed => {
+                write!(f, "the proof does not satisfy the constraints")
+            }
+            VerifyError::InvalidInstances => {
+                write!(f, "the instances do not match the verifying key")
+            }
+            VerifyError::TranscriptMismatch { expected, actual } => write!(
+                f,
+                "the proof was created with the {:?} transcript but verified with the {:?} transcript",
+                actual, expected
+            ),
+            VerifyError::UnsupportedBundleVersion(version) => write!(
+                f,
+                "unsupported proof bundle version {}, expected {}",
+                version,
+                crate::PROOF_BUNDLE_VERSION
+            ),
+            VerifyError::CircuitMismatch { expected, actual } => write!(
+                f,
+                "the proof bundle was created for the circuit {}, expected {}",
+                actual, expected
+            ),
+            VerifyError::VerifyingKeyMismatch { expected, actual } => write!(
+                f,
+                "the proof bundle was created with the verifying key of the fingerprint {}, expected {}",
+                crate::encode_hex(actual),
+                crate::encode_hex(expected)
+            ),
+            VerifyError::Other(e) => write!(f, "failed to verify the proof: {:?}", e),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+impl From<Error> for VerifyError {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::Transcript(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                VerifyError::TruncatedProof
+            }
+            Error::Transcript(e) => VerifyError::MalformedProof(e),
+            Error::ConstraintSystemFailure | Error::Opening => VerifyError::ConstraintCheckFailed,
+            Error::InvalidInstances => VerifyError::InvalidInstances,
+            e => VerifyError::Other(e),
+        }
+    }
+}
+
+/// Creates a proof of `circuit` with the transcript of `transcript` and the GWC multi-open scheme.
+///
+/// See [`create_proof_with`] for the arguments.
+pub fn create_proof_with_transcript<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[&[Fr]],
+    transcript: TranscriptKind,
+) -> Result<Vec<u8>, Error> {
+    create_proof_with(
+        params,
+        pk,
+        circuit,
+        instances,
+        transcript,
+        MultiOpenScheme::Gwc,
+    )
+}
+
+/// Creates a proof of `circuit` with the transcript of `transcript` and the multi-open scheme `scheme`.
+///
+/// # Arguments
+/// * params - params used to generate `pk`.
+/// * pk - a proving key of `circuit`.
+/// * circuit - a circuit with witnesses.
+/// * instances - values of the instance columns of `circuit`.
+/// * transcript - the kind of the transcript.
+/// * scheme - the multi-open scheme.
+///
+/// # Return values
+/// Returns the bytes of the proof.
+pub fn create_proof_with<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[&[Fr]],
+    transcript: TranscriptKind,
+    scheme: MultiOpenScheme,
+) -> Result<Vec<u8>, Error> {
+    create_proof_with_seed(params, pk, circuit, instances, transcript, scheme, None)
+}
+
+/// Creates a proof in the same way as [`create_proof_with`] but with the randomness derived from `seed`.
+///
+/// If `seed` is given, the blinding factors are sampled from a [`ChaCha20Rng`] seeded with it, so that the same inputs and seed always produce byte-identical proofs, e.g., for differential testing between the native and wasm builds.
+/// A seed must never be reused for proofs that should hide their witnesses.
+/// If `seed` is `None`, [`OsRng`] is used as in [`create_proof_with`].
+pub fn create_proof_with_seed<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[&[Fr]],
+    transcript: TranscriptKind,
+    scheme: MultiOpenScheme,
+    seed: Option<[u8; 32]>,
+) -> Result<Vec<u8>, Error> {
+    create_proof_to_writer(
+        params,
+        pk,
+        circuit,
+        instances,
+        transcript,
+        scheme,
+        seed,
+        vec![],
+    )
+}
+
+/// Creates a proof in the same way as [`create_proof_with_seed`] but writes the transcript into `writer` as the prover goes.
+///
+/// The proof is never held as a whole by this function, so `writer` can store it outside the memory of the prover, e.g., on the JS heap.
+///
+/// # Return values
+/// Returns `writer` after the whole proof is written.
+#[allow(clippy::too_many_arguments)]
+pub fn create_proof_to_writer<C: Circuit<Fr>, W: Write>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[&[Fr]],
+    transcript: TranscriptKind,
+    scheme: MultiOpenScheme,
+    seed: Option<[u8; 32]>,
+    writer: W,
+) -> Result<W, Error> {
+    match seed {
+        Some(seed) => create_proof_with_rng(
+            params,
+            pk,
+            circuit,
+            instances,
+            transcript,
+            scheme,
+            ChaCha20Rng::from_seed(seed),
+            writer,
+        ),
+        None => create_proof_with_rng(
+            params, pk, circuit, instances, transcript, scheme, OsRng, writer,
+        ),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn create_proof_with_rng<C: Circuit<Fr>, R: RngCore, W: Write>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[&[Fr]],
+    transcript: TranscriptKind,
+    scheme: MultiOpenScheme,
+    rng: R,
+    writer: W,
+) -> Result<W, Error> {
+    match transcript {
+        TranscriptKind::Blake2b => {
+            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(writer);
+            create_proof_with_scheme(params, pk, circuit, instances, scheme, rng, &mut transcript)?;
+            Ok(transcript.finalize())
+        }
+        TranscriptKind::Keccak256 => {
+            let mut transcript = Keccak256Write::<_, G1Affine, Challenge255<_>>::init(writer);
+            create_proof_with_scheme(params, pk, circuit, instances, scheme, rng, &mut transcript)?;
+            Ok(transcript.finalize())
+        }
+        #[cfg(feature = "aggregation")]
+        TranscriptKind::Poseidon => {
+            let mut transcript = PoseidonTranscript::<NativeLoader, _>::init(writer);
+            create_proof_with_scheme(params, pk, circuit, instances, scheme, rng, &mut transcript)?;
+            Ok(transcript.finalize())
+        }
+    }
+}
+
+fn create_proof_with_scheme<
+    C: Circuit<Fr>,
+    R: RngCore,
+    E: EncodedChallenge<G1Affine>,
+    T: TranscriptWrite<G1Affine, E>,
+>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[&[Fr]],
+    scheme: MultiOpenScheme,
+    rng: R,
+    transcript: &mut T,
+) -> Result<(), Error> {
+    match scheme {
+        MultiOpenScheme::Gwc => create_proof_with_prover::<ProverGWC<_>, _, _, _, _>(
+            params, pk, circuit, instances, rng, transcript,
+        ),
+        MultiOpenScheme::Shplonk => create_proof_with_prover::<ProverSHPLONK<_>, _, _, _, _>(
+            params, pk, circuit, instances, rng, transcript,
+        ),
+    }
+}
+
+fn create_proof_with_prover<'params, P, C, R, E, T>(
+    params: &'params ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[&[Fr]],
+    rng: R,
+    transcript: &mut T,
+) -> Result<(), Error>
+where
+    P: Prover<'params, KZGCommitmentScheme<Bn256>>,
+    C: Circuit<Fr>,
+    R: RngCore,
+    E: EncodedChallenge<G1Affine>,
+    T: TranscriptWrite<G1Affine, E>,
+{
+    create_proof::<KZGCommitmentScheme<Bn256>, P, _, _, _, _>(
+        params,
+        pk,
+        &[circuit],
+        &[instances],
+        rng,
+        transcript,
+    )
+}
+
+/// Verifies a proof created by [`create_proof_with_transcript`] with the same kind of transcript.
+///
+/// Returns an error if the proof is invalid, including the case where it was created with another kind of transcript.
+pub fn verify_proof_with_transcript(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[&[Fr]],
+    transcript: TranscriptKind,
+) -> Result<(), VerifyError> {
+    verify_proof_with(
+        params,
+        vk,
+        proof,
+        instances,
+        transcript,
+        MultiOpenScheme::Gwc,
+    )
+}
+
+/// Verifies a proof created by [`create_proof_with`] with the same kind of transcript and multi-open scheme.
+///
+/// Returns an error if the proof is invalid, including the case where it was created with another kind of transcript or another scheme.
+/// If the proof fails the check but is valid with another kind of transcript, the error is [`VerifyError::TranscriptMismatch`] instead of [`VerifyError::ConstraintCheckFailed`].
+/// The proof is then verified with every kind of [`TranscriptKind::ALL`], so an invalid proof takes that many times as long to reject.
+pub fn verify_proof_with(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[&[Fr]],
+    transcript: TranscriptKind,
+    scheme: MultiOpenScheme,
+) -> Result<(), VerifyError> {
+    match verify_proof_with_kind(params, vk, proof, instances, transcript, scheme) {
+        Err(VerifyError::ConstraintCheckFailed) => {
+            match transcript.others().find(|other| {
+                verify_proof_with_kind(params, vk, proof, instances, *other, scheme).is_ok()
+            }) {
+                Some(actual) => Err(VerifyError::TranscriptMismatch {
+                    expected: transcript,
+                    actual,
+                }),
+                None => Err(VerifyError::ConstraintCheckFailed),
+            }
+        }
+        result => result,
+    }
+}
+
+fn verify_proof_with_kind(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[&[Fr]],
+    transcript: TranscriptKind,
+    scheme: MultiOpenScheme,
+) -> Result<(), VerifyError> {
+    let result = match transcript {
+        TranscriptKind::Blake2b => {
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+            verify_proof_with_scheme(params, vk, instances, scheme, &mut transcript)
+        }
+        TranscriptKind::Keccak256 => {
+            let mut transcript = Keccak256Read::<_, _, Challenge255<_>>::init(proof);
+            verify_proof_with_scheme(params, vk, instances, scheme, &mut transcript)
+        }
+        #[cfg(feature = "aggregation")]
+        TranscriptKind::Poseidon => {
+            let mut transcript = PoseidonTranscript::<NativeLoader, _>::init(proof);
+            verify_proof_with_scheme(params, vk, instances, scheme, &mut transcript)
+        }
+    };
+    result.map_err(VerifyError::from)
+}
+
+/// Reads serialized params and a versioned verifying key of the circuit `C` and verifies a proof with them.
+///
+/// # Arguments
+/// * params - bytes of the full or trimmed params, accepted by [`read_verifier_params`].
+/// * vk - bytes of the verifying key written by [`crate::write_vk_versioned`].
+/// * min_k - the minimum degree of the circuit `C`.
+/// * proof - bytes of the proof.
+/// * instances - the values of the instance columns.
+/// * transcript - the kind of transcript used to create the proof.
+/// * scheme - the multi-open scheme used to create the proof.
+///
+/// # Return values
+/// Returns `Ok(())` if the proof is valid, and otherwise the [`VerifyError`] telling which of the params, the verifying key, and the proof is at fault.
+pub fn verify_proof_bytes<C: Circuit<Fr>>(
+    params: &[u8],
+    vk: &[u8],
+    min_k: u32,
+    proof: &[u8],
+    instances: &[&[Fr]],
+    transcript: TranscriptKind,
+    scheme: MultiOpenScheme,
+) -> Result<(), VerifyError> {
+    let params = read_verifier_params(params).map_err(VerifyError::InvalidParams)?;
+    let vk = read_vk_checked::<C>(vk).map_err(VerifyError::InvalidVerifyingKey)?;
+    check_params_k(&params, &vk, min_k).map_err(VerifyError::InvalidVerifyingKey)?;
+    verify_proof_with(&params, &vk, proof, instances, transcript, scheme)
+}
+
+fn verify_proof_with_scheme<E: EncodedChallenge<G1Affine>, T: TranscriptRead<G1Affine, E>>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    instances: &[&[Fr]],
+    scheme: MultiOpenScheme,
+    transcript: &mut T,
+) -> Result<(), Error> {
+    match scheme {
+        MultiOpenScheme::Gwc => {
+            verify_proof_with_verifier::<VerifierGWC<_>, _, _>(params, vk, instances, transcript)
+        }
+        MultiOpenScheme::Shplonk => verify_proof_with_verifier::<VerifierSHPLONK<_>, _, _>(
+            params, vk, instances, transcript,
+        ),
+    }
+}
+
+fn verify_proof_with_verifier<'params, V, E, T>(
+    params: &'params ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    instances: &[&[Fr]],
+    transcript: &mut T,
+) -> Result<(), Error>
+where
+    V: Verifier<'params, KZGCommitmentScheme<Bn256>>,
+    SingleStrategy<'params, Bn256>:
+        VerificationStrategy<'params, KZGCommitmentScheme<Bn256>, V, Output = ()>,
+    E: EncodedChallenge<G1Affine>,
+    T: TranscriptRead<G1Affine, E>,
+{
+    let strategy = SingleStrategy::new(params);
+    verify_proof::<KZGCommitmentScheme<Bn256>, V, _, _, _>(
+        params,
+        vk,
+        strategy,
+        &[instances],
+        transcript,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{BigUintConfig, BigUintInstructions};
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{keygen_pk, keygen_vk, ConstraintSystem},
+        poly::commitment::{Params, ParamsProver},
+    };
+    use halo2_base::{
+        gates::range::{RangeConfig, RangeStrategy::Vertical},
+        gates::RangeInstructions,
+        utils::PrimeField,
+        SKIP_FIRST_PASS,
+    };
+    use num_bigint::BigUint;
+
+    const K: u32 = 10;
+
+    #[derive(Default)]
+    struct TestMulCircuit {
+        a: BigUint,
+        b: BigUint,
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestMulCircuit {
+        type Config = BigUintConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config = RangeConfig::configure(
+                meta,
+                Vertical,
+                &[2],
+                &[1],
+                1,
+                K as usize - 1,
+                0,
+                K as usize,
+            );
+            BigUintConfig::construct(range_config, 64)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "mul of 128 bits integers",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    let a = config.assign_integer(ctx, Value::known(self.a.clone()), 128)?;
+                    let b = config.assign_integer(ctx, Value::known(self.b.clone()), 128)?;
+                    config.mul(ctx, &a, &b)?;
+                    config.range().finalize(ctx);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_transcript_kinds_are_not_interchangeable() {
+        let params = ParamsKZG::<Bn256>::setup(K, OsRng);
+        let vk = keygen_vk(&params, &TestMulCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &TestMulCircuit::default()).unwrap();
+        for (prove_kind, verify_kind) in [
+            (TranscriptKind::Blake2b, TranscriptKind::Keccak256),
+            (TranscriptKind::Keccak256, TranscriptKind::Blake2b),
+        ] {
+            let circuit = TestMulCircuit {
+                a: BigUint::from(3u64) << 100,
+                b: BigUint::from(5u64) << 50,
+            };
+            let proof =
+                create_proof_with_transcript(&params, &pk, circuit, &[], prove_kind).unwrap();
+            verify_proof_with_transcript(&params, &vk, &proof, &[], prove_kind).unwrap();
+            let err =
+                verify_proof_with_transcript(&params, &vk, &proof, &[], verify_kind).unwrap_err();
+            assert!(matches!(
+                err,
+                VerifyError::TranscriptMismatch { expected, actual }
+                    if expected == verify_kind && actual == prove_kind
+            ));
+            assert_eq!(err.code(), "transcript_mismatch");
+
+            // A proof invalid with both kinds is not reported as a mismatch.
+            let mut flipped = proof.clone();
+            flipped[31] ^= 0x80;
+            for kind in [prove_kind, verify_kind] {
+                assert!(matches!(
+                    verify_proof_with_transcript(&params, &vk, &flipped, &[], kind),
+                    Err(VerifyError::ConstraintCheckFailed)
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn test_estimate_proof_size() {
+        let params = ParamsKZG::<Bn256>::setup(K, OsRng);
+        let vk = keygen_vk(&params, &TestMulCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk, &TestMulCircuit::default()).unwrap();
+        let circuit = TestMulCircuit {
+            a: BigUint::from(3u64) << 100,
+            b: BigUint::from(5u64) << 50,
+        };
+        let proof = create_proof_with(
+            &params,
+            &pk,
+            circuit,
+            &[],
+            TranscriptKind::Blake2b,
+            MultiOpenScheme::Gwc,
+        )
+        .unwrap();
+        let estimate = crate::estimate_proof_size::<TestMulCircuit>();
+        println!(
+            "proof size: {} bytes, estimated {} bytes",
+            proof.len(),
+            estimate
+        );
+        assert!(estimate * 4 >= proof.len() * 3);
+        assert!(estimate * 4 <= proof.len() * 5);
+    }
+
+    #[test]
+    fn test_multi_open_schemes_share_keys() {
+        let params = ParamsKZG::<Bn256>::setup(K, OsRng);
+        let vk = keygen_vk(&params, &TestMulCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &TestMulCircuit::default()).unwrap();
+        let mut proof_sizes = vec![];
+        for (prove_scheme, verify_scheme) in [
+            (MultiOpenScheme::Gwc, MultiOpenScheme::Shplonk),
+            (MultiOpenScheme::Shplonk, MultiOpenScheme::Gwc),
+        ] {
+            let circuit = TestMulCircuit {
+                a: BigUint::from(3u64) << 100,
+                b: BigUint::from(5u64) << 50,
+            };
+            let proof = create_proof_with(
+                &params,
+                &pk,
+                circuit,
+                &[],
+                TranscriptKind::Blake2b,
+                prove_scheme,
+            )
+            .unwrap();
+            verify_proof_with(
+                &params,
+                &vk,
+                &proof,
+                &[],
+                TranscriptKind::Blake2b,
+                prove_scheme,
+            )
+            .unwrap();
+            assert!(verify_proof_with(
+                &params,
+                &vk,
+                &proof,
+                &[],
+                TranscriptKind::Blake2b,
+                verify_scheme
+            )
+            .is_err());
+            proof_sizes.push(proof.len());
+        }
+        println!(
+            "proof size: GWC {} bytes, SHPLONK {} bytes",
+            proof_sizes[0], proof_sizes[1]
+        );
+    }
+
+    #[test]
+    fn test_seeded_proofs_are_reproducible() {
+        let params = ParamsKZG::<Bn256>::setup(K, OsRng);
+        let vk = keygen_vk(&params, &TestMulCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &TestMulCircuit::default()).unwrap();
+        let prove = |seed: Option<[u8; 32]>| {
+            let circuit = TestMulCircuit {
+                a: BigUint::from(3u64) << 100,
+                b: BigUint::from(5u64) << 50,
+            };
+            create_proof_with_seed(
+                &params,
+                &pk,
+                circuit,
+                &[],
+                TranscriptKind::Blake2b,
+                MultiOpenScheme::Gwc,
+                seed,
+            )
+            .unwrap()
+        };
+        let proof = prove(Some([7u8; 32]));
+        assert_eq!(proof, prove(Some([7u8; 32])));
+        assert_ne!(proof, prove(Some([8u8; 32])));
+        assert_ne!(prove(None), prove(None));
+        verify_proof_with_transcript(&params, &vk, &proof, &[], TranscriptKind::Blake2b).unwrap();
+    }
+
+    #[test]
+    fn test_seeded_proofs_do_not_depend_on_threads() {
+        let params = ParamsKZG::<Bn256>::setup(K, OsRng);
+        let vk = keygen_vk(&params, &TestMulCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk, &TestMulCircuit::default()).unwrap();
+        // The witnesses of `mul` are precomputed on the rayon pool, so the number of its threads must not change the proof.
+        let prove = |num_threads: usize| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap();
+            pool.install(|| {
+                let circuit = TestMulCircuit {
+                    a: (BigUint::from(1u64) << 128) - 3u64,
+                    b: (BigUint::from(1u64) << 127) + 5u64,
+                };
+                create_proof_with_seed(
+                    &params,
+                    &pk,
+                    circuit,
+                    &[],
+                    TranscriptKind::Blake2b,
+                    MultiOpenScheme::Gwc,
+                    Some([7u8; 32]),
+                )
+                .unwrap()
+            })
+        };
+        assert_eq!(prove(1), prove(4));
+    }
+
+    #[test]
+    fn test_verify_errors() {
+        let params = ParamsKZG::<Bn256>::setup(K, OsRng);
+        let vk = keygen_vk(&params, &TestMulCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &TestMulCircuit::default()).unwrap();
+        let circuit = TestMulCircuit {
+            a: BigUint::from(3u64) << 100,
+            b: BigUint::from(5u64) << 50,
+        };
+        let proof =
+            create_proof_with_transcript(&params, &pk, circuit, &[], TranscriptKind::Blake2b)
+                .unwrap();
+        let mut params_bytes = vec![];
+        params.write(&mut params_bytes).unwrap();
+        let mut vk_bytes = vec![];
+        crate::write_vk_versioned::<TestMulCircuit, _>(&vk, &mut vk_bytes).unwrap();
+        let verify = |params_bytes: &[u8], vk_bytes: &[u8], proof: &[u8]| {
+            verify_proof_bytes::<TestMulCircuit>(
+                params_bytes,
+                vk_bytes,
+                K,
+                proof,
+                &[],
+                TranscriptKind::Blake2b,
+                MultiOpenScheme::Gwc,
+            )
+        };
+        verify(&params_bytes, &vk_bytes, &proof).unwrap();
+
+        let truncated = &proof[..proof.len() - 1];
+        assert!(matches!(
+            verify(&params_bytes, &vk_bytes, truncated),
+            Err(VerifyError::TruncatedProof)
+        ));
+
+        // Flipping the sign bit of the first commitment negates the point, which is still on the curve.
+        let mut flipped = proof.clone();
+        flipped[31] ^= 0x80;
+        assert!(matches!(
+            verify(&params_bytes, &vk_bytes, &flipped),
+            Err(VerifyError::ConstraintCheckFailed)
+        ));
+
+        // The verifying key generated for params of another degree.
+        let other_params = ParamsKZG::<Bn256>::setup(K + 1, OsRng);
+        let other_vk = keygen_vk(&other_params, &TestMulCircuit::default()).unwrap();
+        let mut other_vk_bytes = vec![];
+        crate::write_vk_versioned::<TestMulCircuit, _>(&other_vk, &mut other_vk_bytes).unwrap();
+        let err = verify(&params_bytes, &other_vk_bytes, &proof).unwrap_err();
+        assert!(matches!(err, VerifyError::InvalidVerifyingKey(_)));
+        assert_eq!(err.code(), "invalid_verifying_key");
+
+        assert!(matches!(
+            verify(&params_bytes[..params_bytes.len() / 2], &vk_bytes, &proof),
+            Err(VerifyError::InvalidParams(_))
+        ));
+    }
+}
@@ -1,10 +1,33 @@
+use crate::cost::params_bytes;
+use crate::params::{params_prefix_max_k, PARAMS_HEADER_PEEK_BYTES};
+#[cfg(feature = "memory-stats")]
+use crate::PeakAlloc;
+use crate::{
+    check_params_k, compress_key as compress_key_bytes, compress_params as compress_params_bytes,
+    convert_key_format as convert_key_format_bytes, create_proof_to_writer,
+    create_proof_with_transcript, decode_base64, decode_proof_hex,
+    decompress_key as decompress_key_bytes, decompress_params as decompress_params_bytes,
+    downsize_params as downsize_params_bytes, encode_base64, encode_hex, estimate_keygen_memory,
+    estimate_proof_size, estimate_proving_memory,
+    extract_verifier_params as extract_verifier_params_bytes, extract_vk_bytes, keygen_versioned,
+    normalize_signature_bytes, parse_instance, parse_instances, parse_jwk, pkcs1v15_circuit_info,
+    preflight_signature, proof_commitments_size, read_params, read_pk_checked, read_pk_with_format,
+    read_verifier_params, read_vk_checked, read_vk_with_format, verify_bundle, verify_proof_bytes,
+    verify_proof_with, vk_fingerprint as vk_fingerprint_bytes, write_pk_versioned,
+    write_vk_versioned, CircuitInfo, KeyFormat, MsgLenError, MultiOpenScheme,
+    Pkcs1v15_1024_1024EnabledBenchCircuit, Pkcs1v15_1024_128EnabledBenchCircuit,
+    Pkcs1v15_1024_64EnabledBenchCircuit, Pkcs1v15_2048_1024DisabledBenchCircuit,
+    Pkcs1v15_2048_1024EnabledBenchCircuit, Pkcs1v15_2048_128EnabledBenchCircuit,
+    Pkcs1v15_2048_64EnabledBenchCircuit, ProofBundle, TranscriptKind, VerifyError,
+    PKCS1V15_CIRCUITS, PROOF_BUNDLE_VERSION,
+};
 use crate::{
     impl_pkcs1v15_basic_circuit, AssignedBigUint, AssignedRSAPubE, AssignedRSAPublicKey,
     AssignedRSASignature, BigUintConfig, BigUintInstructions, Fresh, RSAConfig, RSAInstructions,
     RSAPubE, RSAPublicKey, RSASignature, RSASignatureVerifier,
 };
 use halo2_base::halo2_proofs::{
-    circuit::{Cell, Layouter, Region, SimpleFloorPlanner, Value},
+    circuit::{Cell, Layouter, Region, SimpleFloorPlanner, Table, Value},
     dev::MockProver,
     halo2curves::bn256::{Bn256, Fr, G1Affine},
     plonk::{
@@ -24,7 +47,6 @@ use halo2_base::halo2_proofs::{
     transcript::{
         Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
     },
-    SerdeFormat,
 };
 use halo2_base::utils::fe_to_bigint;
 use halo2_base::ContextParams;
@@ -44,107 +66,57 @@ use halo2_ecc::bigint::{
 use num_bigint::{BigInt, BigUint, Sign};
 use num_traits::{One, Signed, Zero};
 use rand::rngs::OsRng;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::marker::PhantomData;
 
 use rand::{thread_rng, Rng};
-use rsa::{traits::PaddingScheme, traits::PublicKeyParts, RsaPrivateKey, RsaPublicKey};
-use sha2::{Digest, Sha256};
+use rsa::{
+    pkcs1::DecodeRsaPublicKey, pkcs8::DecodePublicKey, traits::PublicKeyParts, Pkcs1v15Sign,
+    RsaPrivateKey, RsaPublicKey,
+};
+use sha2::{Digest, Sha256, Sha384, Sha512};
 
-use js_sys::{Array, JsString, Uint8Array};
+use base64::Engine;
+use futures::{channel::mpsc, StreamExt};
+use js_sys::{Array, Function, JsString, Object, Promise, Reflect, Uint8Array};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
 use serde_wasm_bindgen::*;
+#[cfg(feature = "memory-stats")]
+use std::alloc::System;
 use std::fs::File;
-use std::io::{BufReader, BufWriter, Write};
+use std::io::{self, BufWriter, Write};
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    Arc,
+};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::future_to_promise;
+#[cfg(feature = "web")]
 pub use wasm_bindgen_rayon::init_thread_pool;
 use web_sys::console::*;
 
+// Without the SHA256 chip, the RSA verification alone fits in a much smaller circuit, whose k is pinned by `test_no_sha2_1024_circuit_k`.
 impl_pkcs1v15_basic_circuit!(
-    Pkcs1v15_1024_64EnabledBenchConfig,
-    Pkcs1v15_1024_64EnabledBenchCircuit,
-    setup_pkcs1v15_1024_64_enabled,
-    prove_pkcs1v15_1024_64_enabled,
-    1024,
-    64,
-    1,
-    13,
-    true
-);
-
-impl_pkcs1v15_basic_circuit!(
-    Pkcs1v15_1024_128EnabledBenchConfig,
-    Pkcs1v15_1024_128EnabledBenchCircuit,
-    setup_pkcs1v15_1024_128_enabled,
-    prove_pkcs1v15_1024_128_enabled,
-    1024,
-    128,
-    1,
-    13,
-    true
-);
-
-impl_pkcs1v15_basic_circuit!(
-    Pkcs1v15_1024_1024EnabledBenchConfig,
-    Pkcs1v15_1024_1024EnabledBenchCircuit,
-    setup_pkcs1v15_1024_1024_enabled,
-    prove_pkcs1v15_1024_1024_enabled,
-    1024,
+    Pkcs1v15_1024_64WasmNoSha2Config,
+    Pkcs1v15_1024_64WasmNoSha2Circuit,
+    setup_pkcs1v15_1024_64_no_sha2,
+    prove_pkcs1v15_1024_64_no_sha2,
     1024,
-    1,
-    13,
-    true
-);
-
-impl_pkcs1v15_basic_circuit!(
-    Pkcs1v15_2048_64EnabledBenchConfig,
-    Pkcs1v15_2048_64EnabledBenchCircuit,
-    setup_pkcs1v15_2048_64_enabled,
-    prove_pkcs1v15_2048_64_enabled,
-    2048,
     64,
+    4,
     1,
-    13,
-    true
-);
-
-impl_pkcs1v15_basic_circuit!(
-    Pkcs1v15_2048_128EnabledBenchConfig,
-    Pkcs1v15_2048_128EnabledBenchCircuit,
-    setup_pkcs1v15_2048_128_enabled,
-    prove_pkcs1v15_2048_128_enabled,
-    2048,
-    128,
-    1,
-    13,
-    true
-);
-
-impl_pkcs1v15_basic_circuit!(
-    Pkcs1v15_2048_1024EnabledBenchConfig,
-    Pkcs1v15_2048_1024EnabledBenchCircuit,
-    setup_pkcs1v15_2048_1024_enabled,
-    prove_pkcs1v15_2048_1024_enabled,
-    2048,
-    1024,
+    16,
     1,
-    13,
+    15,
+    false,
     true
 );
 
-impl_pkcs1v15_basic_circuit!(
-    Pkcs1v15_2048_1024DisabledBenchConfig,
-    Pkcs1v15_2048_1024DisabledBenchCircuit,
-    setup_pkcs1v15_2048_1024_disabled,
-    prove_pkcs1v15_2048_1024_disabled,
-    2048,
-    1024,
-    1,
-    13,
-    false
-);
-
 #[wasm_bindgen]
 pub fn sample_rsa_private_key(bits_len: usize) -> JsValue {
     let mut rng = thread_rng();
@@ -152,6 +124,51 @@ pub fn sample_rsa_private_key(bits_len: usize) -> JsValue {
     serde_wasm_bindgen::to_value(&private_key).unwrap()
 }
 
+/// Samples a RSA private key of `bits_len` bits with the public exponent `e`.
+///
+/// Returns an error if `e` is even or smaller than 3.
+#[wasm_bindgen]
+pub fn sample_rsa_private_key_with_exp(bits_len: usize, e: u32) -> Result<JsValue, JsError> {
+    sample_rsa_private_key_with_e(bits_len, e)
+}
+
+/// The number of attempts of [`sample_rsa_private_key_with_e`] to find primes `p` and `q` such that `e` is coprime to `(p-1)(q-1)`.
+const SAMPLE_KEY_ATTEMPTS: usize = 16;
+
+/// Samples a RSA private key of `bits_len` bits with the public exponent `e`, e.g., 3 to exercise the non-default exponents.
+///
+/// The primes are sampled again if `e` is not coprime to the totient.
+/// Returns the private key in the same shape as [`sample_rsa_private_key`], or an error if `e` is even or smaller than 3.
+#[wasm_bindgen]
+pub fn sample_rsa_private_key_with_e(bits_len: usize, e: u32) -> Result<JsValue, JsError> {
+    if e < 3 || e % 2 == 0 {
+        return Err(JsError::new(&format!(
+            "e must be odd and at least 3, but {} was given",
+            e
+        )));
+    }
+    let mut rng = thread_rng();
+    let e = rsa::BigUint::from(e);
+    let mut last_err = None;
+    for _ in 0..SAMPLE_KEY_ATTEMPTS {
+        match RsaPrivateKey::new_with_exp(&mut rng, bits_len, &e) {
+            // `validate` checks that `d` is the inverse of `e` modulo `p-1` and `q-1`, i.e., `e` is coprime to the totient.
+            Ok(private_key) if private_key.validate().is_ok() => {
+                return Ok(serde_wasm_bindgen::to_value(&private_key)?)
+            }
+            Ok(_) => continue,
+            Err(err) => last_err = Some(err),
+        }
+    }
+    Err(JsError::new(&match last_err {
+        Some(err) => format!("failed to generate a key: {}", err),
+        None => format!(
+            "failed to find primes coprime to e in {} attempts",
+            SAMPLE_KEY_ATTEMPTS
+        ),
+    }))
+}
+
 #[wasm_bindgen]
 pub fn generate_rsa_public_key(private_key: JsValue) -> JsValue {
     let private_key: RsaPrivateKey = serde_wasm_bindgen::from_value(private_key).unwrap();
@@ -159,185 +176,3111 @@ pub fn generate_rsa_public_key(private_key: JsValue) -> JsValue {
     serde_wasm_bindgen::to_value(&public_key).unwrap()
 }
 
+/// Imports an RSA public key in the JSON Web Key format, e.g., an entry of the `keys` array of an OIDC provider's JWKS.
+///
+/// Returns the public key in the same format as [`generate_rsa_public_key`], or an error if `jwk` is not an RSA JWK with base64url `n` and odd `e`.
+/// The returned key is passed to the prove functions together with its `e` as big-endian bytes, unless `e` is 65537.
+#[wasm_bindgen]
+pub fn import_public_key_jwk(jwk: &str) -> Result<JsValue, JsError> {
+    let (n, e) = parse_jwk(jwk).map_err(|e| JsError::new(&e.to_string()))?;
+    let public_key = RsaPublicKey::new(
+        rsa::BigUint::from_bytes_be(&n.to_bytes_be()),
+        rsa::BigUint::from_bytes_be(&e.to_bytes_be()),
+    )
+    .map_err(|e| JsError::new(&format!("invalid RSA public key: {}", e)))?;
+    Ok(serde_wasm_bindgen::to_value(&public_key)?)
+}
+
+/// Returns the modulus `n` of `public_key` as a lowercase hex string without the `0x` prefix.
+#[wasm_bindgen]
+pub fn public_key_modulus_hex(public_key: JsValue) -> Result<JsString, JsError> {
+    let n = modulus_from_js(public_key)?;
+    Ok(JsString::from(n.to_str_radix(16)))
+}
+
+/// Returns the public exponent `e` of `public_key`.
+///
+/// Returns an error if `e` does not fit in 64 bits.
+#[wasm_bindgen]
+pub fn public_key_exponent(public_key: JsValue) -> Result<u64, JsError> {
+    let public_key: RsaPublicKey = serde_wasm_bindgen::from_value(public_key)?;
+    let e = BigUint::from_bytes_be(&public_key.e().to_bytes_be());
+    u64::try_from(&e).map_err(|_| JsError::new(&format!("e = {} does not fit in 64 bits", e)))
+}
+
+/// Returns the values of the instance column exposing `n` of `public_key`, i.e., its 64 bits limbs from the least significant one, as `0x`-prefixed hex strings of 32 bytes field elements.
+///
+/// The number of limbs is determined by the byte length of `n`, e.g., 32 limbs for both 2047 and 2048 bits moduli.
+#[wasm_bindgen]
+pub fn public_key_instances(public_key: JsValue) -> Result<Array, JsError> {
+    let n = modulus_from_js(public_key)?;
+    let num_limbs = ((n.bits() as usize + 7) / 8 * 8 + 63) / 64;
+    let mut limbs = n.to_u64_digits();
+    limbs.resize(num_limbs, 0);
+    Ok(limbs
+        .into_iter()
+        .map(|limb| JsValue::from_str(&format!("0x{:064x}", limb)))
+        .collect())
+}
+
+/// Returns the SHA256 digest of the big-endian bytes of `n` of `public_key` as a lowercase hex string.
+///
+/// The circuits in this crate expose `n` itself through [`public_key_instances`] rather than a commitment to it.
+/// This digest is a compact identifier of the key for comparing keys in a UI.
+#[wasm_bindgen]
+pub fn public_key_commitment(public_key: JsValue) -> Result<JsString, JsError> {
+    let n = modulus_from_js(public_key)?;
+    let digest = Sha256::digest(n.to_bytes_be());
+    Ok(JsString::from(
+        digest
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>(),
+    ))
+}
+
+fn modulus_from_js(public_key: JsValue) -> Result<BigUint, JsError> {
+    let public_key: RsaPublicKey = serde_wasm_bindgen::from_value(public_key)?;
+    Ok(BigUint::from_bytes_be(&public_key.n().to_bytes_be()))
+}
+
+/// Encodes `proof` as a lowercase hex string without the `0x` prefix, e.g., to put it in a URL.
+///
+/// The verify functions accept the returned string in place of the proof bytes.
+#[wasm_bindgen]
+pub fn proof_to_hex(proof: JsValue) -> Result<JsString, JsError> {
+    let proof = bytes_from_js(&proof)?;
+    Ok(JsString::from(encode_hex(&proof)))
+}
+
+/// Decodes a proof encoded by [`proof_to_hex`].
+///
+/// Uppercase digits and the `0x` prefix are accepted. Returns an error if the string contains whitespace or a non-hex digit, or does not decode to whole 32 bytes elements of a proof.
+#[wasm_bindgen]
+pub fn proof_from_hex(hex: &str) -> Result<Uint8Array, JsError> {
+    let proof = decode_proof_hex(hex).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(Uint8Array::from(&proof[..]))
+}
+
+/// Encodes `bytes`, e.g., a verifying key, in base64 with the standard alphabet and padding, or with the URL-safe alphabet and without padding if `url_safe` is true.
 #[wasm_bindgen]
-pub fn sign(private_key: JsValue, msg: JsValue) -> JsValue {
+pub fn bytes_to_base64(bytes: Uint8Array, url_safe: Option<bool>) -> JsString {
+    JsString::from(encode_base64(&bytes.to_vec(), url_safe.unwrap_or(false)))
+}
+
+/// Decodes a base64 string in either the standard or the URL-safe alphabet, with or without padding.
+///
+/// Returns an error if the string contains whitespace, mixes the two alphabets, or is not base64.
+/// If `expected_len` is given, e.g., 256 for the signature of a 2048 bits key, the decoded bytes must have that length.
+#[wasm_bindgen]
+pub fn bytes_from_base64(base64: &str, expected_len: Option<usize>) -> Result<Uint8Array, JsError> {
+    let bytes = decode_base64(base64, expected_len).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(Uint8Array::from(&bytes[..]))
+}
+
+#[wasm_bindgen]
+pub fn sign(private_key: JsValue, msg: JsValue) -> Uint8Array {
     let private_key: RsaPrivateKey = serde_wasm_bindgen::from_value(private_key).unwrap();
     //let msg: Vec<u8> = serde_wasm_bindgen::from_value(msg).unwrap();
     let msg: Vec<u8> = Uint8Array::new(&msg).to_vec();
     let hashed_msg = Sha256::digest(&msg).to_vec();
 
-    let padding = PaddingScheme::PKCS1v15Sign {
-        hash: Some(Hash::SHA2_256),
-    };
+    let padding = Pkcs1v15Sign::new::<Sha256>();
     let sign = private_key
         .sign(padding, &hashed_msg)
         .expect("fail to sign a hashed message.");
-    serde_wasm_bindgen::to_value(&sign).unwrap()
+    Uint8Array::from(&sign[..])
+}
+
+/// Hashes `msg` with `hash_alg`, which is one of `"sha256"`, `"sha384"`, and `"sha512"`, and signs the digest with the pkcs1v15 padding.
+///
+/// Returns an error if `hash_alg` is not supported.
+#[wasm_bindgen]
+pub fn sign_with_hash(
+    private_key: JsValue,
+    msg: Uint8Array,
+    hash_alg: &str,
+) -> Result<Uint8Array, JsError> {
+    let private_key: RsaPrivateKey = serde_wasm_bindgen::from_value(private_key)?;
+    let msg = msg.to_vec();
+    let (padding, hashed_msg) = match hash_alg {
+        "sha256" => (Pkcs1v15Sign::new::<Sha256>(), Sha256::digest(&msg).to_vec()),
+        "sha384" => (Pkcs1v15Sign::new::<Sha384>(), Sha384::digest(&msg).to_vec()),
+        "sha512" => (Pkcs1v15Sign::new::<Sha512>(), Sha512::digest(&msg).to_vec()),
+        _ => {
+            return Err(JsError::new(&format!(
+                "unsupported hash algorithm {}",
+                hash_alg
+            )))
+        }
+    };
+    let sign = private_key
+        .sign(padding, &hashed_msg)
+        .map_err(|err| JsError::new(&format!("failed to sign a hashed message: {}", err)))?;
+    Ok(Uint8Array::from(&sign[..]))
+}
+
+/// Signs a 32 bytes SHA256 digest computed elsewhere with the pkcs1v15 padding, without hashing it again.
+///
+/// Returns an error if `digest` is not 32 bytes.
+#[wasm_bindgen]
+pub fn sign_raw_digest(private_key: JsValue, digest: Uint8Array) -> Result<Uint8Array, JsError> {
+    let private_key: RsaPrivateKey = serde_wasm_bindgen::from_value(private_key)?;
+    let digest = sha256_digest_from_js(&digest)?;
+    let sign = private_key
+        .sign(Pkcs1v15Sign::new::<Sha256>(), &digest)
+        .map_err(|err| JsError::new(&format!("failed to sign the digest: {}", err)))?;
+    Ok(Uint8Array::from(&sign[..]))
+}
+
+/// Verifies a pkcs1v15 signature for a 32 bytes SHA256 digest outside the circuit.
+///
+/// Returns `false` if the signature is invalid, and an error if `digest` is not 32 bytes.
+#[wasm_bindgen]
+pub fn verify_raw_digest_native(
+    public_key: JsValue,
+    digest: Uint8Array,
+    signature: JsValue,
+) -> Result<bool, JsError> {
+    let public_key: RsaPublicKey = serde_wasm_bindgen::from_value(public_key)?;
+    let digest = sha256_digest_from_js(&digest)?;
+    let signature = bytes_from_js(&signature)?;
+    Ok(
+        normalize_signature_bytes(&signature, public_key.size() * 8).map_or(false, |signature| {
+            public_key
+                .verify(Pkcs1v15Sign::new::<Sha256>(), &digest, &signature)
+                .is_ok()
+        }),
+    )
+}
+
+/// Verifies a pkcs1v15 signature for the SHA256 hash of `msg` outside the circuit, e.g., to check the inputs before spending minutes on proving.
+///
+/// The prove functions run the same check before proving. [`verify_raw_digest_native`] is the variant taking the digest instead of the message.
+/// Returns `false` if the signature is invalid, and an error if `public_key` or `signature` cannot be read.
+#[wasm_bindgen]
+pub fn check_signature_native(
+    public_key: JsValue,
+    msg: Uint8Array,
+    signature: JsValue,
+) -> Result<bool, JsError> {
+    let public_key: RsaPublicKey = serde_wasm_bindgen::from_value(public_key)?;
+    let signature = bytes_from_js(&signature)?;
+    Ok(preflight_signature(&public_key, &msg.to_vec(), true, &signature).is_ok())
+}
+
+fn sha256_digest_from_js(digest: &Uint8Array) -> Result<Vec<u8>, JsError> {
+    let digest = digest.to_vec();
+    if digest.len() != 32 {
+        return Err(JsError::new(&format!(
+            "the digest must be 32 bytes of SHA256, but {} bytes were given",
+            digest.len()
+        )));
+    }
+    Ok(digest)
+}
+
+#[wasm_bindgen]
+pub fn sha256_msg(msg: JsValue) -> JsValue {
+    //let msg: Vec<u8> = serde_wasm_bindgen::from_value(msg).unwrap();
+    let msg: Vec<u8> = Uint8Array::new(&msg).to_vec();
+    let hashed_msg = Sha256::digest(&msg).to_vec();
+    serde_wasm_bindgen::to_value(&hashed_msg).unwrap()
+}
+
+/// Hashes a message given in chunks with SHA256 so that the whole message never has to be copied into the wasm memory at once.
+///
+/// The digest returned by [`Sha256Hasher::finalize`] can be passed as `msg` to the prove functions of the circuits without the SHA256 chip.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct Sha256Hasher {
+    hasher: Sha256,
+}
+
+#[wasm_bindgen]
+impl Sha256Hasher {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `chunk` to the hashed message.
+    pub fn update(&mut self, chunk: Uint8Array) {
+        self.hasher.update(chunk.to_vec());
+    }
+
+    /// Returns the 32 bytes digest of the chunks given so far and releases the hasher.
+    pub fn finalize(self) -> Uint8Array {
+        Uint8Array::from(&self.hasher.finalize()[..])
+    }
+}
+
+/// Reads the `msg` argument of the prove functions, which is either a `Uint8Array` or an array of `Uint8Array` chunks concatenated in order.
+///
+/// If `sha2_chip_enabled` is true, the message is hashed in the circuit, so it must fit in `msg_len` bytes together with the 9 bytes of the SHA256 padding.
+/// Otherwise, the message must be its 32 bytes SHA256 digest, e.g., the output of [`Sha256Hasher::finalize`].
+/// Panics if the message does not fit; see [`try_msg_from_js`] for the non-panicking version.
+fn msg_from_js(msg: &JsValue, sha2_chip_enabled: bool, msg_len: usize) -> Vec<u8> {
+    try_msg_from_js(msg, sha2_chip_enabled, msg_len).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Reads the `msg` argument in the same way as [`msg_from_js`] but returns the reason instead of panicking if the message does not fit.
+fn try_msg_from_js(
+    msg: &JsValue,
+    sha2_chip_enabled: bool,
+    msg_len: usize,
+) -> Result<Vec<u8>, String> {
+    let too_long = |len| {
+        MsgLenError::TooLong {
+            len,
+            max_len: msg_len - 9,
+        }
+        .to_string()
+    };
+    let msg = if Array::is_array(msg) {
+        let chunks = Array::from(msg);
+        let mut concatenated = vec![];
+        for chunk in chunks.iter() {
+            concatenated.extend_from_slice(&Uint8Array::new(&chunk).to_vec());
+            if sha2_chip_enabled && concatenated.len() + 9 > msg_len {
+                return Err(too_long(concatenated.len()));
+            }
+        }
+        concatenated
+    } else {
+        Uint8Array::new(msg).to_vec()
+    };
+    if sha2_chip_enabled && msg.len() + 9 > msg_len {
+        return Err(too_long(msg.len()));
+    }
+    if !sha2_chip_enabled && msg.len() != 32 {
+        return Err(MsgLenError::NotDigest { len: msg.len() }.to_string());
+    }
+    Ok(msg)
+}
+
+/// Reads bytes from a `Uint8Array`.
+///
+/// An array of numbers, which the previous versions used for proofs and signatures, is also accepted for compatibility.
+fn bytes_from_js(value: &JsValue) -> Result<Vec<u8>, serde_wasm_bindgen::Error> {
+    if value.is_instance_of::<Uint8Array>() {
+        return Ok(Uint8Array::new(value).to_vec());
+    }
+    serde_wasm_bindgen::from_value(value.clone())
+}
+
+/// Reads a proof given to the verify functions, either as bytes accepted by [`bytes_from_js`] or as a hex string encoded by [`proof_to_hex`].
+fn proof_from_js(value: &JsValue) -> Result<Vec<u8>, String> {
+    match value.as_string() {
+        Some(hex) => decode_proof_hex(&hex).map_err(|e| e.to_string()),
+        None => bytes_from_js(value).map_err(|e| e.to_string()),
+    }
+}
+
+/// The milestones reported to the progress callback of the prove functions, with the estimated percentages of the proving time elapsed at them.
+///
+/// halo2 does not expose hooks inside `create_proof`, so its FFTs and commitments are covered by the single `"proof"` milestone.
+const PROVE_MILESTONES: [(&str, u32); 5] = [
+    ("params", 5),
+    ("proving_key", 10),
+    ("witness", 25),
+    ("proof", 95),
+    ("transcript", 100),
+];
+
+/// Reports [`PROVE_MILESTONES`] in order to an optional JS callback as `callback(stage, percent)`.
+struct ProgressReporter {
+    callback: Option<Function>,
+    next: usize,
+}
+
+impl ProgressReporter {
+    fn new(callback: Option<Function>) -> Self {
+        Self { callback, next: 0 }
+    }
+
+    /// Reports the next milestone. An exception thrown by the callback is ignored so that it does not abort the proving.
+    fn advance(&mut self) {
+        let (stage, percent) = PROVE_MILESTONES[self.next];
+        self.next += 1;
+        if let Some(callback) = self.callback.as_ref() {
+            let _ = callback.call2(
+                &JsValue::NULL,
+                &JsValue::from_str(stage),
+                &JsValue::from(percent),
+            );
+        }
+    }
+}
+
+/// Reads a [`RSAPublicKey`] from a serialized `RsaPublicKey` and the big-endian bytes of the fixed exponent `e`, which defaults to `default_e`.
+fn public_key_from_js(
+    public_key: JsValue,
+    e: Option<Uint8Array>,
+    default_e: u128,
+) -> RSAPublicKey<Fr> {
+    let public_key: RsaPublicKey = serde_wasm_bindgen::from_value(public_key)
+        .unwrap_or_else(|e| panic!("invalid public key: {}", e));
+    RSAPublicKey::from_rsa_key(&public_key, e.map(|e| e.to_vec()).as_deref(), default_e)
+        .unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Reads a [`RSASignature`] from its big-endian bytes, with or without its leading zero bytes, for a `bits_len` bits modulus.
+fn signature_from_js(signature: &JsValue, bits_len: usize) -> RSASignature<Fr> {
+    let signature = bytes_from_js(signature).unwrap();
+    RSASignature::try_from_be_bytes(&signature, bits_len).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Checks the inputs of a prove function with the rsa crate before proving, so that a signature invalid for the message, e.g., because of a typo in the message, fails immediately with the reason instead of as an unsatisfiable circuit.
+///
+/// See [`preflight_public_key`] for `public_key`, `e`, and `default_e`, and [`preflight_signature`] for `msg` and `sha2_chip_enabled`.
+fn preflight_check(
+    public_key: &JsValue,
+    e: Option<&Uint8Array>,
+    default_e: u128,
+    msg: &[u8],
+    sha2_chip_enabled: bool,
+    signature: &JsValue,
+) -> Result<(), String> {
+    let public_key = preflight_public_key(public_key, e, default_e)?;
+    let signature = bytes_from_js(signature).map_err(|e| format!("invalid signature: {}", e))?;
+    preflight_signature(&public_key, msg, sha2_chip_enabled, &signature)
+}
+
+/// Reads the public key given to a prove function for [`preflight_signature`].
+///
+/// The circuit uses the exponent `e`, which defaults to `default_e`, instead of the one in `public_key`, so they must be the same.
+fn preflight_public_key(
+    public_key: &JsValue,
+    e: Option<&Uint8Array>,
+    default_e: u128,
+) -> Result<RsaPublicKey, String> {
+    let public_key: RsaPublicKey = serde_wasm_bindgen::from_value(public_key.clone())
+        .map_err(|e| format!("invalid public key: {}", e))?;
+    let key_e = BigUint::from_bytes_be(&public_key.e().to_bytes_be());
+    let circuit_e = e.map_or_else(
+        || BigUint::from(default_e),
+        |e| BigUint::from_bytes_be(&e.to_vec()),
+    );
+    if key_e != circuit_e {
+        return Err(format!(
+            "the public key has the exponent {}, but the circuit uses {}",
+            key_e, circuit_e
+        ));
+    }
+    Ok(public_key)
+}
+
+const ABORTED_MESSAGE: &str = "proving was aborted";
+const CANCELLED_MESSAGE: &str = "proving was cancelled";
+
+/// A token to stop the proofs of the prove functions with the suffix `_cancellable`.
+///
+/// Cancelling the token makes the running proof return the error `"proving was cancelled"` at its next check instead of running to the end.
+/// The token is shared with the thread proving on the pool, so [`CancellationToken::cancel`] may be called while the proof runs on a web worker.
+/// When the proof runs on the calling thread, e.g., without multithreading, JS cannot call `cancel` until it returns, so [`CancellationToken::cancel_after`] sets a deadline instead.
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    /// The bits of the `Date.now()` after which the token is cancelled, or zero for no deadline.
+    deadline: Arc<AtomicU64>,
+}
+
+#[wasm_bindgen]
+impl CancellationToken {
+    /// Creates a token that is not cancelled.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the proofs given this token.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Cancels the proofs given this token once `ms` milliseconds have passed, which is checked at the same points as [`CancellationToken::cancel`].
+    pub fn cancel_after(&self, ms: f64) {
+        let deadline = js_sys::Date::now() + ms;
+        self.deadline.store(deadline.to_bits(), Ordering::SeqCst);
+    }
+
+    /// Returns whether the token is cancelled, either by [`CancellationToken::cancel`] or by the deadline of [`CancellationToken::cancel_after`].
+    #[wasm_bindgen(getter)]
+    pub fn cancelled(&self) -> bool {
+        if self.cancelled.load(Ordering::SeqCst) {
+            return true;
+        }
+        let deadline = self.deadline.load(Ordering::SeqCst);
+        if deadline != 0 && js_sys::Date::now() >= f64::from_bits(deadline) {
+            self.cancel();
+            return true;
+        }
+        false
+    }
+}
+
+impl CancellationToken {
+    /// Returns the error of the cancelled proofs if the token is cancelled.
+    fn check(&self) -> Result<(), &'static str> {
+        if self.cancelled() {
+            Err(CANCELLED_MESSAGE)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Passes a proof through to `inner` but fails every write once `token` is cancelled, which makes the prover return at the next commitment or evaluation.
+struct CancellableWriter<W> {
+    inner: W,
+    token: CancellationToken,
+}
+
+impl<W: Write> CancellableWriter<W> {
+    fn new(inner: W, token: CancellationToken) -> Self {
+        Self { inner, token }
+    }
+
+    fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for CancellableWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // `write_all` retries the writes failed with `ErrorKind::Interrupted`, so another kind is returned.
+        self.token
+            .check()
+            .map_err(|message| io::Error::new(io::ErrorKind::Other, message))?;
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// The number of regions and tables assigned by [`CancellableLayouter`] between two checks of its token.
+const CANCELLATION_CHECK_REGIONS: usize = 4;
+
+/// Passes the assignments through to `inner` but checks `token` before the first region or table and every [`CANCELLATION_CHECK_REGIONS`] ones after it, failing the synthesis once it is cancelled.
+struct CancellableLayouter<'t, L> {
+    inner: L,
+    token: &'t CancellationToken,
+    num_assigned: usize,
+}
+
+impl<'t, L: Layouter<Fr>> CancellableLayouter<'t, L> {
+    fn new(inner: L, token: &'t CancellationToken) -> Self {
+        Self {
+            inner,
+            token,
+            num_assigned: 0,
+        }
+    }
+
+    fn check(&mut self) -> Result<(), Error> {
+        if self.num_assigned % CANCELLATION_CHECK_REGIONS == 0 {
+            self.token.check().map_err(|_| Error::Synthesis)?;
+        }
+        self.num_assigned += 1;
+        Ok(())
+    }
+}
+
+impl<'t, L: Layouter<Fr>> Layouter<Fr> for CancellableLayouter<'t, L> {
+    type Root = Self;
+
+    fn assign_region<A, AR, N, NR>(&mut self, name: N, assignment: A) -> Result<AR, Error>
+    where
+        A: FnMut(Region<'_, Fr>) -> Result<AR, Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        self.check()?;
+        self.inner.assign_region(name, assignment)
+    }
+
+    fn assign_table<A, N, NR>(&mut self, name: N, assignment: A) -> Result<(), Error>
+    where
+        A: FnMut(Table<'_, Fr>) -> Result<(), Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        self.check()?;
+        self.inner.assign_table(name, assignment)
+    }
+
+    fn constrain_instance(
+        &mut self,
+        cell: Cell,
+        column: Column<Instance>,
+        row: usize,
+    ) -> Result<(), Error> {
+        self.inner.constrain_instance(cell, column, row)
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.inner.get_root().push_namespace(name_fn)
+    }
+
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.inner.get_root().pop_namespace(gadget_name)
+    }
+}
+
+/// Wraps `circuit` to fail its synthesis if `token` is cancelled, so the prover stops before generating the rest of the witness.
+///
+/// The token is checked by [`CancellableLayouter`] while the regions are assigned, and once more after the synthesis.
+/// It has the same constraint system as `C`, so the proving key of `C` proves it.
+struct CancellableCircuit<C> {
+    circuit: C,
+    token: CancellationToken,
+}
+
+impl<C: Circuit<Fr>> Circuit<Fr> for CancellableCircuit<C> {
+    type Config = C::Config;
+    type FloorPlanner = C::FloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            circuit: self.circuit.without_witnesses(),
+            token: self.token.clone(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        C::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        self.circuit
+            .synthesize(config, CancellableLayouter::new(layouter, &self.token))?;
+        self.token.check().map_err(|_| Error::Synthesis)
+    }
+}
+
+/// Returns whether the page can share the wasm memory with web workers, i.e., `SharedArrayBuffer` is defined and the page is cross-origin isolated.
+///
+/// If it returns false, [`init_thread_pool_or_fallback`] sets up a single-threaded pool and the proving functions run on the calling thread, which is much slower.
+/// It always returns false without the `web` feature, e.g., in the Node.js build, which has no pool of web workers.
+#[wasm_bindgen]
+pub fn is_multithreading_available() -> bool {
+    if cfg!(not(feature = "web")) {
+        return false;
+    }
+    let global = js_sys::global();
+    let has_shared_array_buffer =
+        Reflect::has(&global, &JsValue::from_str("SharedArrayBuffer")).unwrap_or(false);
+    // `crossOriginIsolated` is not defined outside browsers, e.g., in Node.js, where `SharedArrayBuffer` is always usable.
+    let is_isolated = Reflect::get(&global, &JsValue::from_str("crossOriginIsolated"))
+        .map(|v| v.is_undefined() || v.is_truthy())
+        .unwrap_or(false);
+    has_shared_array_buffer && is_isolated
+}
+
+/// Initializes the rayon pool with `num_threads` web workers if [`is_multithreading_available`] returns true.
+/// Otherwise, it makes the calling thread the only thread of the pool instead of throwing, and the proving proceeds single-threaded.
+///
+/// The returned `Promise` must be awaited before calling the proving functions.
+#[wasm_bindgen]
+pub fn init_thread_pool_or_fallback(num_threads: usize) -> Promise {
+    if let Some(promise) = init_worker_pool(num_threads) {
+        return promise;
+    }
+    init_single_thread_pool();
+    Promise::resolve(&JsValue::UNDEFINED)
+}
+
+#[cfg(feature = "web")]
+fn init_worker_pool(num_threads: usize) -> Option<Promise> {
+    is_multithreading_available().then(|| init_thread_pool(num_threads))
+}
+
+#[cfg(not(feature = "web"))]
+fn init_worker_pool(_num_threads: usize) -> Option<Promise> {
+    None
+}
+
+/// Makes the calling thread the only thread of the rayon pool. It does nothing if the pool is already initialized.
+fn init_single_thread_pool() {
+    let _ = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .use_current_thread()
+        .build_global();
+}
+
+/// The number of threads of the pool initialized by [`init_thread_pool_with_size`], or zero before it is called.
+static THREAD_POOL_SIZE: AtomicUsize = AtomicUsize::new(0);
+/// Whether [`shutdown_thread_pool`] was called after the pool was initialized.
+static THREAD_POOL_SHUT_DOWN: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// The pool whose only thread is the calling one, on which the proofs run while the pool of web workers is shut down.
+    static CALLING_THREAD_POOL: rayon::ThreadPool = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .use_current_thread()
+        .build()
+        .expect("failed to build a pool on the calling thread");
+}
+
+/// Initializes the rayon pool with at most `num_threads` web workers, or restarts the pool shut down by [`shutdown_thread_pool`].
+///
+/// `num_threads` is capped to `navigator.hardwareConcurrency`, so passing a small number keeps low-end devices responsive.
+/// As [`init_thread_pool_or_fallback`], it falls back to a single-threaded pool if [`is_multithreading_available`] returns false.
+/// rayon cannot resize its pool once built, so the returned `Promise` is rejected if the pool was initialized with another number of threads.
+/// It must be awaited before calling the proving functions.
+#[wasm_bindgen]
+pub fn init_thread_pool_with_size(num_threads: usize) -> Promise {
+    let hardware_concurrency = Reflect::get(&js_sys::global(), &JsValue::from_str("navigator"))
+        .and_then(|navigator| Reflect::get(&navigator, &JsValue::from_str("hardwareConcurrency")))
+        .ok()
+        .and_then(|v| v.as_f64())
+        .map_or(num_threads, |v| v as usize);
+    let num_threads = num_threads.min(hardware_concurrency).max(1);
+    let num_threads = if is_multithreading_available() {
+        num_threads
+    } else {
+        1
+    };
+    let initialized =
+        THREAD_POOL_SIZE.compare_exchange(0, num_threads, Ordering::SeqCst, Ordering::SeqCst);
+    let promise = match initialized {
+        Ok(_) => init_thread_pool_or_fallback(num_threads),
+        Err(size) if size == num_threads => Promise::resolve(&JsValue::UNDEFINED),
+        Err(size) => {
+            return Promise::reject(
+                &JsError::new(&format!(
+                    "the thread pool already has {} threads and cannot be resized to {}",
+                    size, num_threads
+                ))
+                .into(),
+            )
+        }
+    };
+    THREAD_POOL_SHUT_DOWN.store(false, Ordering::SeqCst);
+    promise
+}
+
+/// Stops the proving functions from using the web workers of the pool, e.g., after proving to let the browser throttle the idle workers.
+///
+/// The proofs started afterwards run single-threaded on the calling thread until [`init_thread_pool_with_size`] is called again, instead of waiting for the workers.
+/// The workers themselves stay alive, since rayon cannot tear down the pool built by `init_thread_pool`, but they hold no memory of the finished proofs; the wasm memory shared with them never shrinks anyway.
+#[wasm_bindgen]
+pub fn shutdown_thread_pool() {
+    THREAD_POOL_SHUT_DOWN.store(true, Ordering::SeqCst);
+}
+
+/// Returns the number of threads the proving functions with the suffix `_async` run on.
+#[wasm_bindgen]
+pub fn proving_threads() -> usize {
+    if !is_multithreading_available() || THREAD_POOL_SHUT_DOWN.load(Ordering::SeqCst) {
+        1
+    } else {
+        rayon::current_num_threads()
+    }
+}
+
+/// Initializes the Node.js build: panics are written to the stderr of the process, and the proving functions run on the calling thread.
+///
+/// Call it once after loading the module instead of [`init_thread_pool_or_fallback`].
+/// The params and the keys are passed as `Buffer`s, e.g., those read by `fs.readFileSync`, since every function taking a `Uint8Array` accepts them as they are.
+#[cfg(feature = "nodejs")]
+#[wasm_bindgen]
+pub fn init_node() {
+    // Consume the `Once` of the browser hook first so that the exported functions calling `set_once` do not replace the hook below.
+    console_error_panic_hook::set_once();
+    std::panic::set_hook(Box::new(|info| write_stderr(&format!("{}\n", info))));
+    init_single_thread_pool();
+}
+
+/// Writes `message` to `process.stderr`, or to the console if the global `process` is missing.
+#[cfg(feature = "nodejs")]
+fn write_stderr(message: &str) {
+    let stderr = Reflect::get(&js_sys::global(), &JsValue::from_str("process"))
+        .and_then(|process| Reflect::get(&process, &JsValue::from_str("stderr")));
+    let written = stderr.and_then(|stderr| {
+        let write = Reflect::get(&stderr, &JsValue::from_str("write"))?;
+        write
+            .dyn_into::<Function>()?
+            .call1(&stderr, &JsValue::from_str(message))
+    });
+    if written.is_err() {
+        error_1(&JsValue::from_str(message));
+    }
+}
+
+/// Runs `prove` on the rayon pool and returns a `Promise` resolved with its output as a `Uint8Array`, or rejected with its error.
+///
+/// If `signal` is already aborted, `prove` is not run.
+/// If it is aborted later, the promise is rejected immediately, but the running `prove` cannot be interrupted and its output is discarded; use a [`CancellationToken`] to stop it.
+/// If [`is_multithreading_available`] returns false, no worker can run `prove`, so it runs on the calling thread before returning the settled `Promise`.
+/// The same holds while the pool is shut down by [`shutdown_thread_pool`], where the parallel iterators of `prove` also run on the calling thread instead of waiting for the workers.
+fn prove_on_pool<P>(prove: P, signal: Option<Object>) -> Promise
+where
+    P: FnOnce() -> Result<Vec<u8>, &'static str> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::unbounded();
+    let aborted = Arc::new(AtomicBool::new(false));
+    if let Some(signal) = signal {
+        let is_aborted = Reflect::get(&signal, &JsValue::from_str("aborted"))
+            .map(|v| v.is_truthy())
+            .unwrap_or(false);
+        if is_aborted {
+            return Promise::reject(&JsError::new(ABORTED_MESSAGE).into());
+        }
+        let abort_sender = sender.clone();
+        let abort_flag = aborted.clone();
+        let on_abort = Closure::once(move || {
+            abort_flag.store(true, Ordering::SeqCst);
+            let _ = abort_sender.unbounded_send(Err(ABORTED_MESSAGE));
+        });
+        let add_event_listener = Reflect::get(&signal, &JsValue::from_str("addEventListener"))
+            .ok()
+            .and_then(|f| f.dyn_into::<Function>().ok());
+        if let Some(add_event_listener) = add_event_listener {
+            let _ = add_event_listener.call2(
+                &signal,
+                &JsValue::from_str("abort"),
+                on_abort.as_ref().unchecked_ref(),
+            );
+        }
+        on_abort.forget();
+    }
+    if !is_multithreading_available() {
+        let _ = sender.unbounded_send(prove());
+        return proof_promise(receiver);
+    }
+    if THREAD_POOL_SHUT_DOWN.load(Ordering::SeqCst) {
+        let _ = sender.unbounded_send(CALLING_THREAD_POOL.with(|pool| pool.install(prove)));
+        return proof_promise(receiver);
+    }
+    rayon::spawn(move || {
+        if aborted.load(Ordering::SeqCst) {
+            return;
+        }
+        let _ = sender.unbounded_send(prove());
+    });
+    proof_promise(receiver)
+}
+
+/// Returns a `Promise` settled with the first result sent to `receiver`.
+fn proof_promise(mut receiver: mpsc::UnboundedReceiver<Result<Vec<u8>, &'static str>>) -> Promise {
+    future_to_promise(async move {
+        match receiver.next().await {
+            Some(Ok(proof)) => Ok(Uint8Array::from(&proof[..]).into()),
+            Some(Err(message)) => Err(JsError::new(message).into()),
+            None => Err(JsError::new("proving failed").into()),
+        }
+    })
+}
+
+/// The initial capacity of [`Uint8ArrayWriter`], which is large enough for the proofs of the pkcs1v15 circuits.
+const PROOF_WRITER_INITIAL_CAPACITY: u32 = 1 << 16;
+
+/// Writes a proof into a `Uint8Array` on the JS heap as the prover goes.
+///
+/// Compared to collecting the proof into a `Vec<u8>` and copying it into a `Uint8Array`, the proof is never held as a whole in the wasm memory, which cannot shrink once grown.
+/// The array is preallocated with [`PROOF_WRITER_INITIAL_CAPACITY`] bytes and doubled when it is full.
+struct Uint8ArrayWriter {
+    array: Uint8Array,
+    len: u32,
+}
+
+impl Uint8ArrayWriter {
+    fn new() -> Self {
+        Self::with_capacity(PROOF_WRITER_INITIAL_CAPACITY)
+    }
+
+    fn with_capacity(capacity: u32) -> Self {
+        Self {
+            array: Uint8Array::new_with_length(capacity),
+            len: 0,
+        }
+    }
+
+    /// Returns a view of the written bytes without copying them.
+    fn into_array(self) -> Uint8Array {
+        self.array.subarray(0, self.len)
+    }
+}
+
+impl Write for Uint8ArrayWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let end = self.len as usize + buf.len();
+        if end > u32::MAX as usize {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::OutOfMemory,
+                "the proof exceeds the maximum length of a Uint8Array",
+            ));
+        }
+        let end = end as u32;
+        if end > self.array.length() {
+            let capacity = end.max(self.array.length().saturating_mul(2));
+            let grown = Uint8Array::new_with_length(capacity);
+            grown.set(&self.array.subarray(0, self.len), 0);
+            self.array = grown;
+        }
+        self.array.subarray(self.len, end).copy_from(buf);
+        self.len = end;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Counts the heap allocations for [`memory_stats`].
+#[cfg(feature = "memory-stats")]
+#[global_allocator]
+static ALLOCATOR: PeakAlloc<System> = PeakAlloc::new(System);
+
+/// The number of the proving phases told apart by [`PhaseWriter`]: the assignment, the commitment, and the opening.
+const NUM_PROVE_PHASES: usize = 3;
+
+/// The peak heap bytes of each proving phase of the last proof, or zero if the phase has not ended.
+static PHASE_PEAKS: [AtomicUsize; NUM_PROVE_PHASES] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+
+/// Returns the peak heap bytes since the previous call and starts a new window, or zero without the `memory-stats` feature.
+#[cfg(feature = "memory-stats")]
+fn take_window_peak() -> usize {
+    ALLOCATOR.take_window_peak()
+}
+
+#[cfg(not(feature = "memory-stats"))]
+fn take_window_peak() -> usize {
+    0
+}
+
+/// Passes a proof through to `inner` while recording the peak heap bytes of each proving phase into [`PHASE_PEAKS`].
+///
+/// halo2 does not expose hooks inside `create_proof`, so the phases are told apart by the transcript.
+/// The prover writes nothing until the witness is assigned, and then writes the commitments, whose size is [`proof_commitments_size`], before the evaluations and the opening proof.
+/// The assignment phase starts when the writer is created, so it must be created before the witness is generated, e.g., by the `MockProver`.
+struct PhaseWriter<W> {
+    inner: W,
+    written: usize,
+    commitments_size: usize,
+    phase: usize,
+}
+
+impl<W: Write> PhaseWriter<W> {
+    fn new<C: Circuit<Fr>>(inner: W) -> Self {
+        for peak in PHASE_PEAKS.iter() {
+            peak.store(0, Ordering::SeqCst);
+        }
+        take_window_peak();
+        Self {
+            inner,
+            written: 0,
+            commitments_size: proof_commitments_size::<C>(),
+            phase: 0,
+        }
+    }
+
+    fn end_phase(&mut self) {
+        if self.phase < NUM_PROVE_PHASES {
+            PHASE_PEAKS[self.phase].store(take_window_peak(), Ordering::SeqCst);
+            self.phase += 1;
+        }
+    }
+
+    /// Ends the remaining phases and returns the inner writer after the whole proof is written.
+    fn finish(mut self) -> W {
+        while self.phase < NUM_PROVE_PHASES {
+            self.end_phase();
+        }
+        self.inner
+    }
+}
+
+impl<W: Write> Write for PhaseWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.phase == 0 {
+            self.end_phase();
+        }
+        let len = self.inner.write(buf)?;
+        self.written += len;
+        if self.phase == 1 && self.written >= self.commitments_size {
+            self.end_phase();
+        }
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// The output of [`memory_stats`].
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MemoryStatsFields {
+    linear_memory_bytes: usize,
+    heap_bytes: Option<usize>,
+    peak_heap_bytes: Option<usize>,
+    phase_peak_bytes: Option<PhasePeakFields>,
+}
+
+#[derive(Serialize)]
+struct PhasePeakFields {
+    assignment: usize,
+    commitment: usize,
+    opening: usize,
+}
+
+/// Returns the memory usage of the wasm module for choosing the circuit sizes a device can prove.
+///
+/// Returns an object `{ linearMemoryBytes, heapBytes?, peakHeapBytes?, phasePeakBytes? }`.
+/// `linearMemoryBytes` is the size of the wasm linear memory, which never shrinks, so it is also its peak.
+/// The other fields are given only if the module is built with the `memory-stats` feature: `heapBytes` and `peakHeapBytes` are the bytes currently allocated and their peak since the module was loaded, and `phasePeakBytes` is an object `{ assignment, commitment, opening }` of the peak bytes in each phase of the last proof created by the prove functions, if any.
+#[wasm_bindgen]
+pub fn memory_stats() -> Result<JsValue, JsError> {
+    let linear_memory_bytes = core::arch::wasm32::memory_size::<0>() * 65536;
+    let phase_peaks = PHASE_PEAKS
+        .iter()
+        .map(|peak| peak.load(Ordering::SeqCst))
+        .collect::<Vec<_>>();
+    #[cfg(feature = "memory-stats")]
+    let (heap_bytes, peak_heap_bytes) = (Some(ALLOCATOR.current()), Some(ALLOCATOR.peak()));
+    #[cfg(not(feature = "memory-stats"))]
+    let (heap_bytes, peak_heap_bytes) = (None, None);
+    let fields = MemoryStatsFields {
+        linear_memory_bytes,
+        heap_bytes,
+        peak_heap_bytes,
+        // The opening phase ends last, so its peak is recorded only after a whole proof.
+        phase_peak_bytes: (cfg!(feature = "memory-stats") && phase_peaks[2] > 0).then(|| {
+            PhasePeakFields {
+                assignment: phase_peaks[0],
+                commitment: phase_peaks[1],
+                opening: phase_peaks[2],
+            }
+        }),
+    };
+    Ok(serde_wasm_bindgen::to_value(&fields)?)
+}
+
+/// A deserialized artifact stored by [`load_params`] or [`load_proving_key`].
+enum LoadedArtifact {
+    Params(ParamsKZG<Bn256>),
+    /// A proving key with the type name of its circuit.
+    ProvingKey(&'static str, ProvingKey<G1Affine>),
+}
+
+/// The artifacts referred by the handles returned from [`load_params`] and [`load_proving_key`].
+#[derive(Default)]
+struct HandleRegistry {
+    next_handle: u32,
+    artifacts: HashMap<u32, LoadedArtifact>,
+}
+
+impl HandleRegistry {
+    fn insert(&mut self, artifact: LoadedArtifact) -> u32 {
+        // Handles start from one so that zero is never a valid handle.
+        self.next_handle += 1;
+        self.artifacts.insert(self.next_handle, artifact);
+        self.next_handle
+    }
+}
+
+thread_local! {
+    static HANDLE_REGISTRY: RefCell<HandleRegistry> = RefCell::new(HandleRegistry::default());
+}
+
+/// Deserializes params once and returns a handle to pass to the prove functions with the suffix `_with_handles`.
+///
+/// The params stay in the memory of the calling thread until [`free_handle`] is called with the handle.
+#[wasm_bindgen]
+pub fn load_params(bytes: Uint8Array) -> Result<u32, JsError> {
+    let bytes = bytes.to_vec();
+    let params =
+        read_params(&bytes).map_err(|e| JsError::new(&format!("invalid params: {}", e)))?;
+    Ok(HANDLE_REGISTRY
+        .with(|registry| registry.borrow_mut().insert(LoadedArtifact::Params(params))))
+}
+
+/// Deserializes a versioned proving key of the circuit `circuit_name`, one of the names in the `Pkcs1v15CircuitName` type, and returns its handle.
+///
+/// See [`load_params`] for the lifetime of the handle.
+/// `format` is the [`KeyFormat`] the key is written in, e.g., by [`convert_key_format`], and defaults to `RawBytes`.
+/// If `trusted` is true, a key in `RawBytes` is read as `RawBytesUnchecked`, i.e., the points of the key are not checked to be on the curve, which is faster but only safe for keys from a trusted source. See [`crate::read_pk_trusted`].
+#[wasm_bindgen]
+pub fn load_proving_key(
+    circuit_name: &str,
+    bytes: Uint8Array,
+    trusted: Option<bool>,
+    format: Option<KeyFormat>,
+) -> Result<u32, JsError> {
+    let bytes = bytes.to_vec();
+    let format = match format.unwrap_or_default() {
+        KeyFormat::RawBytes if trusted.unwrap_or(false) => KeyFormat::RawBytesUnchecked,
+        format => format,
+    };
+    match circuit_name {
+        "1024_64" => load_proving_key_of::<Pkcs1v15_1024_64EnabledBenchCircuit<Fr>>(&bytes, format),
+        "1024_128" => {
+            load_proving_key_of::<Pkcs1v15_1024_128EnabledBenchCircuit<Fr>>(&bytes, format)
+        }
+        "1024_1024" => {
+            load_proving_key_of::<Pkcs1v15_1024_1024EnabledBenchCircuit<Fr>>(&bytes, format)
+        }
+        "2048_64" => load_proving_key_of::<Pkcs1v15_2048_64EnabledBenchCircuit<Fr>>(&bytes, format),
+        "2048_128" => {
+            load_proving_key_of::<Pkcs1v15_2048_128EnabledBenchCircuit<Fr>>(&bytes, format)
+        }
+        "2048_1024" => {
+            load_proving_key_of::<Pkcs1v15_2048_1024EnabledBenchCircuit<Fr>>(&bytes, format)
+        }
+        "no_sha2_2048_1024" => {
+            load_proving_key_of::<Pkcs1v15_2048_1024DisabledBenchCircuit<Fr>>(&bytes, format)
+        }
+        _ => Err(unknown_circuit_error(circuit_name)),
+    }
+}
+
+fn load_proving_key_of<C: Circuit<Fr>>(bytes: &[u8], format: KeyFormat) -> Result<u32, JsError> {
+    let pk = read_pk_with_format::<C>(bytes, format)
+        .map_err(|e| JsError::new(&format!("invalid proving key: {}", e)))?;
+    Ok(HANDLE_REGISTRY.with(|registry| {
+        registry
+            .borrow_mut()
+            .insert(LoadedArtifact::ProvingKey(std::any::type_name::<C>(), pk))
+    }))
+}
+
+/// Releases the params or the proving key of `handle`.
+///
+/// Returns false if `handle` is unknown or already released.
+#[wasm_bindgen]
+pub fn free_handle(handle: u32) -> bool {
+    HANDLE_REGISTRY.with(|registry| registry.borrow_mut().artifacts.remove(&handle).is_some())
+}
+
+/// Calls `f` with the params of `params_handle` and the proving key of `pk_handle`, which must be a key of the circuit `C`.
+fn with_loaded_artifacts<C: Circuit<Fr>, T>(
+    params_handle: u32,
+    pk_handle: u32,
+    f: impl FnOnce(&ParamsKZG<Bn256>, &ProvingKey<G1Affine>) -> T,
+) -> Result<T, JsError> {
+    HANDLE_REGISTRY.with(|registry| {
+        let registry = registry.borrow();
+        let params = match registry.artifacts.get(&params_handle) {
+            Some(LoadedArtifact::Params(params)) => params,
+            Some(_) => {
+                return Err(JsError::new(&format!(
+                    "handle {} does not refer to params",
+                    params_handle
+                )))
+            }
+            None => return Err(JsError::new(&format!("unknown handle {}", params_handle))),
+        };
+        let pk = match registry.artifacts.get(&pk_handle) {
+            Some(LoadedArtifact::ProvingKey(circuit, pk))
+                if *circuit == std::any::type_name::<C>() =>
+            {
+                pk
+            }
+            Some(LoadedArtifact::ProvingKey(circuit, _)) => {
+                return Err(JsError::new(&format!(
+                    "handle {} refers to a proving key of the circuit {}, expected {}",
+                    pk_handle,
+                    circuit,
+                    std::any::type_name::<C>()
+                )))
+            }
+            Some(_) => {
+                return Err(JsError::new(&format!(
+                    "handle {} does not refer to a proving key",
+                    pk_handle
+                )))
+            }
+            None => return Err(JsError::new(&format!("unknown handle {}", pk_handle))),
+        };
+        Ok(f(params, pk))
+    })
+}
+
+/// Converts the result of [`verify_proof_bytes`] into an object `{ ok, error?, code? }`.
+fn verify_result_to_js(result: Result<(), VerifyError>) -> Object {
+    let output = Object::new();
+    let set = |key: &str, value: JsValue| {
+        Reflect::set(&output, &JsValue::from_str(key), &value)
+            .expect("setting a property of a plain object never fails");
+    };
+    set("ok", JsValue::from_bool(result.is_ok()));
+    if let Err(e) = result {
+        set("error", JsValue::from_str(&e.to_string()));
+        set("code", JsValue::from_str(e.code()));
+    }
+    output
+}
+
+/// Returns true if `result` made by [`verify_result_to_js`] is ok and panics with its error otherwise, as the verify functions returning bool have done.
+fn verify_or_panic(result: &Object) -> bool {
+    let get = |key: &str| Reflect::get(result, &JsValue::from_str(key)).unwrap();
+    if get("ok").is_truthy() {
+        return true;
+    }
+    panic!(
+        "proof invalid: {}",
+        get("error").as_string().unwrap_or_default()
+    );
+}
+
+/// Reads the public inputs given to a verify function as the instance columns of a circuit exposing `num_instances` values in its only instance column, or no column if zero.
+///
+/// `instances` is an array of the values or an array of one such array, where each value is a `0x`-prefixed hex string or a decimal string of a field element, e.g., one returned by [`public_key_instances`].
+/// Omitted instances are the same as an empty array.
+fn instances_from_js(
+    instances: Option<Array>,
+    num_instances: usize,
+) -> Result<Vec<Vec<Fr>>, String> {
+    let values = match instances {
+        Some(instances) if instances.length() == 1 && Array::is_array(&instances.get(0)) => {
+            Array::from(&instances.get(0)).to_vec()
+        }
+        Some(instances) => instances.to_vec(),
+        None => vec![],
+    };
+    let values = values
+        .iter()
+        .map(|value| {
+            value
+                .as_string()
+                .ok_or_else(|| "an instance must be a hex or decimal string".to_string())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    parse_instances(&values, num_instances).map_err(|e| e.to_string())
+}
+
+/// Reads the public inputs given to [`verify_pkcs1v15_batch`] as those of `num_proofs` proofs, each of which is read by [`instances_from_js`].
+///
+/// Omitted instances are the same as the omitted instances of every proof.
+fn batch_instances_from_js(
+    instances: Option<Array>,
+    num_proofs: usize,
+    num_instances: usize,
+) -> Result<Vec<Vec<Vec<Fr>>>, String> {
+    let instances = match instances {
+        Some(instances) => instances,
+        None => return Ok(vec![instances_from_js(None, num_instances)?; num_proofs]),
+    };
+    if instances.length() as usize != num_proofs {
+        return Err(format!(
+            "{} proofs and {} instances were given",
+            num_proofs,
+            instances.length()
+        ));
+    }
+    instances
+        .iter()
+        .enumerate()
+        .map(|(i, proof_instances)| {
+            if !Array::is_array(&proof_instances) {
+                return Err(format!("the instances of the proof {} must be an array", i));
+            }
+            instances_from_js(Some(Array::from(&proof_instances)), num_instances)
+                .map_err(|e| format!("the instances of the proof {}: {}", i, e))
+        })
+        .collect()
+}
+
+#[macro_export]
+macro_rules! impl_pkcs1v15_wasm_functions {
+    ($circuit_name:ident, $prove_fn_name:ident, $prove_async_fn_name:ident, $prove_cancellable_fn_name:ident, $prove_handles_fn_name:ident, $prove_keccak_fn_name:ident, $prove_shplonk_fn_name:ident, $prove_seeded_fn_name:ident, $verify_fn_name:ident, $verify_keccak_fn_name:ident, $verify_shplonk_fn_name:ident, $verify_detailed_fn_name:ident, $msg_len:expr, $k:expr, $sha2_chip_enabled:expr) => {
+        #[wasm_bindgen]
+        pub fn $prove_fn_name(
+            params: JsValue,
+            pk: JsValue,
+            public_key: JsValue,
+            msg: JsValue,
+            signature: JsValue,
+            e: Option<Uint8Array>,
+            progress: Option<Function>,
+        ) -> Uint8Array {
+            console_error_panic_hook::set_once();
+            let mut progress = ProgressReporter::new(progress);
+            let msg = msg_from_js(&msg, $sha2_chip_enabled, $msg_len);
+            preflight_check(
+                &public_key,
+                e.as_ref(),
+                $circuit_name::<Fr>::DEFAULT_E,
+                &msg,
+                $sha2_chip_enabled,
+                &signature,
+            )
+            .unwrap_or_else(|e| panic!("{}", e));
+
+            let params = Uint8Array::new(&params).to_vec();
+            let params = read_params(&params).unwrap();
+            progress.advance();
+
+            let pk: Vec<u8> = Uint8Array::new(&pk).to_vec();
+            let pk = read_pk_checked::<$circuit_name<Fr>>(&pk).unwrap_or_else(|e| panic!("{}", e));
+            check_params_k(&params, pk.get_vk(), $circuit_name::<Fr>::min_k())
+                .unwrap_or_else(|e| panic!("{}", e));
+            progress.advance();
+
+            let public_key = public_key_from_js(public_key, e, $circuit_name::<Fr>::DEFAULT_E);
+            let signature = signature_from_js(&signature, $circuit_name::<Fr>::BITS_LEN);
+
+            let instances = $circuit_name::<Fr>::instances(&msg);
+            let circuit = $circuit_name::<Fr> {
+                signature,
+                public_key,
+                msg,
+                _f: PhantomData,
+            };
+
+            let writer = PhaseWriter::new::<$circuit_name<Fr>>(Uint8ArrayWriter::new());
+            let prover = match MockProver::run($k, &circuit, instances.clone()) {
+                Ok(prover) => prover,
+                Err(e) => panic!("{:#?}", e),
+            };
+            prover.verify().unwrap();
+            progress.advance();
+
+            let proof = create_proof_to_writer(
+                &params,
+                &pk,
+                circuit,
+                &instances.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+                TranscriptKind::Blake2b,
+                MultiOpenScheme::Gwc,
+                None,
+                writer,
+            )
+            .unwrap();
+            progress.advance();
+            progress.advance();
+            proof.finish().into_array()
+        }
+
+        /// Proves in the same way as the function without the suffix `_with_handles` but with the params and the proving key loaded by [`load_params`] and [`load_proving_key`].
+        ///
+        /// Nothing is deserialized per call, so repeated proofs skip parsing the params and the proving key.
+        #[wasm_bindgen]
+        pub fn $prove_handles_fn_name(
+            params_handle: u32,
+            pk_handle: u32,
+            public_key: JsValue,
+            msg: JsValue,
+            signature: JsValue,
+            e: Option<Uint8Array>,
+        ) -> Result<Uint8Array, JsError> {
+            console_error_panic_hook::set_once();
+            let msg = msg_from_js(&msg, $sha2_chip_enabled, $msg_len);
+            preflight_check(
+                &public_key,
+                e.as_ref(),
+                $circuit_name::<Fr>::DEFAULT_E,
+                &msg,
+                $sha2_chip_enabled,
+                &signature,
+            )
+            .map_err(|e| JsError::new(&e))?;
+
+            let public_key = public_key_from_js(public_key, e, $circuit_name::<Fr>::DEFAULT_E);
+            let signature = signature_from_js(&signature, $circuit_name::<Fr>::BITS_LEN);
+
+            let instances = $circuit_name::<Fr>::instances(&msg);
+            let circuit = $circuit_name::<Fr> {
+                signature,
+                public_key,
+                msg,
+                _f: PhantomData,
+            };
+
+            let proof = with_loaded_artifacts::<$circuit_name<Fr>, _>(
+                params_handle,
+                pk_handle,
+                |params, pk| {
+                    check_params_k(params, pk.get_vk(), $circuit_name::<Fr>::min_k())
+                        .map_err(|e| JsError::new(&e.to_string()))?;
+                    let writer = PhaseWriter::new::<$circuit_name<Fr>>(Uint8ArrayWriter::new());
+                    let prover = match MockProver::run($k, &circuit, instances.clone()) {
+                        Ok(prover) => prover,
+                        Err(e) => panic!("{:#?}", e),
+                    };
+                    prover.verify().unwrap();
+                    create_proof_to_writer(
+                        params,
+                        pk,
+                        circuit,
+                        &instances.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+                        TranscriptKind::Blake2b,
+                        MultiOpenScheme::Gwc,
+                        None,
+                        writer,
+                    )
+                    .map_err(|e| JsError::new(&format!("failed to create a proof: {:?}", e)))
+                },
+            )??;
+            Ok(proof.finish().into_array())
+        }
+
+        /// Proves in the same way as the function without the suffix `_keccak` but with the Keccak256 transcript of [`TranscriptKind::Keccak256`].
+        #[wasm_bindgen]
+        pub fn $prove_keccak_fn_name(
+            params: JsValue,
+            pk: JsValue,
+            public_key: JsValue,
+            msg: JsValue,
+            signature: JsValue,
+            e: Option<Uint8Array>,
+        ) -> Uint8Array {
+            console_error_panic_hook::set_once();
+            let msg = msg_from_js(&msg, $sha2_chip_enabled, $msg_len);
+            preflight_check(
+                &public_key,
+                e.as_ref(),
+                $circuit_name::<Fr>::DEFAULT_E,
+                &msg,
+                $sha2_chip_enabled,
+                &signature,
+            )
+            .unwrap_or_else(|e| panic!("{}", e));
+
+            let params = Uint8Array::new(&params).to_vec();
+            let params = read_params(&params).unwrap();
+            let pk: Vec<u8> = Uint8Array::new(&pk).to_vec();
+            let pk = read_pk_checked::<$circuit_name<Fr>>(&pk).unwrap_or_else(|e| panic!("{}", e));
+            check_params_k(&params, pk.get_vk(), $circuit_name::<Fr>::min_k())
+                .unwrap_or_else(|e| panic!("{}", e));
+            let public_key = public_key_from_js(public_key, e, $circuit_name::<Fr>::DEFAULT_E);
+            let signature = signature_from_js(&signature, $circuit_name::<Fr>::BITS_LEN);
+
+            let instances = $circuit_name::<Fr>::instances(&msg);
+            let circuit = $circuit_name::<Fr> {
+                signature,
+                public_key,
+                msg,
+                _f: PhantomData,
+            };
+
+            let writer = PhaseWriter::new::<$circuit_name<Fr>>(Uint8ArrayWriter::new());
+            let prover = match MockProver::run($k, &circuit, instances.clone()) {
+                Ok(prover) => prover,
+                Err(e) => panic!("{:#?}", e),
+            };
+            prover.verify().unwrap();
+            let proof = create_proof_to_writer(
+                &params,
+                &pk,
+                circuit,
+                &instances.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+                TranscriptKind::Keccak256,
+                MultiOpenScheme::Gwc,
+                None,
+                writer,
+            )
+            .unwrap();
+            proof.finish().into_array()
+        }
+
+        /// Proves in the same way as the function without the suffix `_shplonk` but opens the commitments with the SHPLONK multi-open scheme instead of GWC.
+        ///
+        /// The proving key is the same as the one of the function without the suffix; only the opening proof differs, so the proof is usually smaller.
+        #[wasm_bindgen]
+        pub fn $prove_shplonk_fn_name(
+            params: JsValue,
+            pk: JsValue,
+            public_key: JsValue,
+            msg: JsValue,
+            signature: JsValue,
+            e: Option<Uint8Array>,
+        ) -> Uint8Array {
+            console_error_panic_hook::set_once();
+            let msg = msg_from_js(&msg, $sha2_chip_enabled, $msg_len);
+            preflight_check(
+                &public_key,
+                e.as_ref(),
+                $circuit_name::<Fr>::DEFAULT_E,
+                &msg,
+                $sha2_chip_enabled,
+                &signature,
+            )
+            .unwrap_or_else(|e| panic!("{}", e));
+
+            let params = Uint8Array::new(&params).to_vec();
+            let params = read_params(&params).unwrap();
+            let pk: Vec<u8> = Uint8Array::new(&pk).to_vec();
+            let pk = read_pk_checked::<$circuit_name<Fr>>(&pk).unwrap_or_else(|e| panic!("{}", e));
+            check_params_k(&params, pk.get_vk(), $circuit_name::<Fr>::min_k())
+                .unwrap_or_else(|e| panic!("{}", e));
+            let public_key = public_key_from_js(public_key, e, $circuit_name::<Fr>::DEFAULT_E);
+            let signature = signature_from_js(&signature, $circuit_name::<Fr>::BITS_LEN);
+
+            let instances = $circuit_name::<Fr>::instances(&msg);
+            let circuit = $circuit_name::<Fr> {
+                signature,
+                public_key,
+                msg,
+                _f: PhantomData,
+            };
+
+            let writer = PhaseWriter::new::<$circuit_name<Fr>>(Uint8ArrayWriter::new());
+            let prover = match MockProver::run($k, &circuit, instances.clone()) {
+                Ok(prover) => prover,
+                Err(e) => panic!("{:#?}", e),
+            };
+            prover.verify().unwrap();
+            let proof = create_proof_to_writer(
+                &params,
+                &pk,
+                circuit,
+                &instances.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+                TranscriptKind::Blake2b,
+                MultiOpenScheme::Shplonk,
+                None,
+                writer,
+            )
+            .unwrap();
+            proof.finish().into_array()
+        }
+
+        /// Proves in the same way as the function without the suffix `_seeded` but samples the randomness of the prover from a `ChaCha20Rng` seeded with `seed`.
+        ///
+        /// The same inputs and `seed` always produce the same proof bytes, which is meant for reproducible tests and not for proofs hiding their witnesses.
+        /// Returns an error if `seed` is not 32 bytes.
+        #[wasm_bindgen]
+        pub fn $prove_seeded_fn_name(
+            params: JsValue,
+            pk: JsValue,
+            public_key: JsValue,
+            msg: JsValue,
+            signature: JsValue,
+            e: Option<Uint8Array>,
+            seed: Uint8Array,
+        ) -> Result<Uint8Array, JsError> {
+            console_error_panic_hook::set_once();
+            let msg = msg_from_js(&msg, $sha2_chip_enabled, $msg_len);
+            preflight_check(
+                &public_key,
+                e.as_ref(),
+                $circuit_name::<Fr>::DEFAULT_E,
+                &msg,
+                $sha2_chip_enabled,
+                &signature,
+            )
+            .map_err(|e| JsError::new(&e))?;
+
+            let seed: [u8; 32] = seed.to_vec().try_into().map_err(|seed: Vec<u8>| {
+                JsError::new(&format!(
+                    "the seed must be 32 bytes, but {} bytes were given",
+                    seed.len()
+                ))
+            })?;
+            let params = Uint8Array::new(&params).to_vec();
+            let params = read_params(&params).unwrap();
+            let pk: Vec<u8> = Uint8Array::new(&pk).to_vec();
+            let pk = read_pk_checked::<$circuit_name<Fr>>(&pk).unwrap_or_else(|e| panic!("{}", e));
+            check_params_k(&params, pk.get_vk(), $circuit_name::<Fr>::min_k())
+                .unwrap_or_else(|e| panic!("{}", e));
+            let public_key = public_key_from_js(public_key, e, $circuit_name::<Fr>::DEFAULT_E);
+            let signature = signature_from_js(&signature, $circuit_name::<Fr>::BITS_LEN);
+
+            let instances = $circuit_name::<Fr>::instances(&msg);
+            let circuit = $circuit_name::<Fr> {
+                signature,
+                public_key,
+                msg,
+                _f: PhantomData,
+            };
+
+            let writer = PhaseWriter::new::<$circuit_name<Fr>>(Uint8ArrayWriter::new());
+            let prover = match MockProver::run($k, &circuit, instances.clone()) {
+                Ok(prover) => prover,
+                Err(e) => panic!("{:#?}", e),
+            };
+            prover.verify().unwrap();
+            let proof = create_proof_to_writer(
+                &params,
+                &pk,
+                circuit,
+                &instances.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+                TranscriptKind::Blake2b,
+                MultiOpenScheme::Gwc,
+                Some(seed),
+                writer,
+            )
+            .map_err(|e| JsError::new(&format!("failed to create a proof: {:?}", e)))?;
+            Ok(proof.finish().into_array())
+        }
+
+        /// Proves on the rayon pool without blocking the calling thread and returns a `Promise` resolved with the proof as a `Uint8Array`.
+        ///
+        /// The promise is rejected as soon as `signal`, an `AbortSignal` or an object of the same shape, is aborted.
+        ///
+        /// The promise returned by `init_thread_pool` or [`init_thread_pool_or_fallback`] must be awaited before calling it.
+        /// With a pool of web workers, the proof runs on a worker and the calling thread, e.g., the UI thread, stays responsive until the promise is settled.
+        /// Without multithreading, the proof runs on the calling thread before the promise is returned, which blocks it as long as the synchronous function does.
+        #[wasm_bindgen]
+        pub fn $prove_async_fn_name(
+            params: JsValue,
+            pk: JsValue,
+            public_key: JsValue,
+            msg: JsValue,
+            signature: JsValue,
+            e: Option<Uint8Array>,
+            signal: Option<Object>,
+        ) -> Promise {
+            console_error_panic_hook::set_once();
+            let msg = msg_from_js(&msg, $sha2_chip_enabled, $msg_len);
+            if let Err(message) = preflight_check(
+                &public_key,
+                e.as_ref(),
+                $circuit_name::<Fr>::DEFAULT_E,
+                &msg,
+                $sha2_chip_enabled,
+                &signature,
+            ) {
+                return Promise::reject(&JsError::new(&message).into());
+            }
+
+            let params = Uint8Array::new(&params).to_vec();
+            let params = read_params(&params).unwrap();
+            let pk: Vec<u8> = Uint8Array::new(&pk).to_vec();
+            let pk = read_pk_checked::<$circuit_name<Fr>>(&pk).unwrap_or_else(|e| panic!("{}", e));
+            check_params_k(&params, pk.get_vk(), $circuit_name::<Fr>::min_k())
+                .unwrap_or_else(|e| panic!("{}", e));
+            let public_key = public_key_from_js(public_key, e, $circuit_name::<Fr>::DEFAULT_E);
+            let signature = signature_from_js(&signature, $circuit_name::<Fr>::BITS_LEN);
+
+            let instances = $circuit_name::<Fr>::instances(&msg);
+            let circuit = $circuit_name::<Fr> {
+                signature,
+                public_key,
+                msg,
+                _f: PhantomData,
+            };
+
+            prove_on_pool(
+                move || {
+                    let writer = PhaseWriter::new::<$circuit_name<Fr>>(vec![]);
+                    let prover = match MockProver::run($k, &circuit, instances.clone()) {
+                        Ok(prover) => prover,
+                        Err(e) => panic!("{:#?}", e),
+                    };
+                    prover.verify().unwrap();
+                    let proof = create_proof_to_writer(
+                        &params,
+                        &pk,
+                        circuit,
+                        &instances.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+                        TranscriptKind::Blake2b,
+                        MultiOpenScheme::Gwc,
+                        None,
+                        writer,
+                    )
+                    .unwrap();
+                    Ok(proof.finish())
+                },
+                signal,
+            )
+        }
+
+        /// Proves in the same way as the function with the suffix `_async` but stops as soon as `token` is cancelled, rejecting the promise with the error `"proving was cancelled"`.
+        ///
+        /// The token is checked before the witness is synthesized, every [`CANCELLATION_CHECK_REGIONS`] regions assigned during the synthesis, after the synthesis, and whenever the prover writes a commitment or an evaluation to the transcript.
+        /// Most of the witness of the circuit is assigned in a single region, so a proof cancelled during that region stops when the region ends.
+        /// The promise is also rejected, instead of panicking, if the params or the proving key cannot be read or do not fit the circuit.
+        /// Unlike the function with the suffix `_async`, the witness is not checked by the `MockProver` first, since the signature is already verified natively.
+        #[wasm_bindgen]
+        pub fn $prove_cancellable_fn_name(
+            token: &CancellationToken,
+            params: JsValue,
+            pk: JsValue,
+            public_key: JsValue,
+            msg: JsValue,
+            signature: JsValue,
+            e: Option<Uint8Array>,
+        ) -> Promise {
+            console_error_panic_hook::set_once();
+            let msg = msg_from_js(&msg, $sha2_chip_enabled, $msg_len);
+            if let Err(message) = preflight_check(
+                &public_key,
+                e.as_ref(),
+                $circuit_name::<Fr>::DEFAULT_E,
+                &msg,
+                $sha2_chip_enabled,
+                &signature,
+            ) {
+                return Promise::reject(&JsError::new(&message).into());
+            }
+
+            let params = Uint8Array::new(&params).to_vec();
+            let params = match read_params(&params) {
+                Ok(params) => params,
+                Err(e) => return Promise::reject(&JsError::new(&e.to_string()).into()),
+            };
+            let pk: Vec<u8> = Uint8Array::new(&pk).to_vec();
+            let pk = match read_pk_checked::<$circuit_name<Fr>>(&pk) {
+                Ok(pk) => pk,
+                Err(e) => return Promise::reject(&JsError::new(&e.to_string()).into()),
+            };
+            if let Err(e) = check_params_k(&params, pk.get_vk(), $circuit_name::<Fr>::min_k()) {
+                return Promise::reject(&JsError::new(&e.to_string()).into());
+            }
+            let public_key = public_key_from_js(public_key, e, $circuit_name::<Fr>::DEFAULT_E);
+            let signature = signature_from_js(&signature, $circuit_name::<Fr>::BITS_LEN);
+
+            let instances = $circuit_name::<Fr>::instances(&msg);
+            let circuit = CancellableCircuit {
+                circuit: $circuit_name::<Fr> {
+                    signature,
+                    public_key,
+                    msg,
+                    _f: PhantomData,
+                },
+                token: token.clone(),
+            };
+            let token = token.clone();
+            prove_on_pool(
+                move || {
+                    token.check()?;
+                    let writer = PhaseWriter::new::<$circuit_name<Fr>>(CancellableWriter::new(
+                        vec![],
+                        token.clone(),
+                    ));
+                    let proof = create_proof_to_writer(
+                        &params,
+                        &pk,
+                        circuit,
+                        &instances.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+                        TranscriptKind::Blake2b,
+                        MultiOpenScheme::Gwc,
+                        None,
+                        writer,
+                    )
+                    .map_err(|_| token.check().err().unwrap_or("failed to create a proof"))?;
+                    Ok(proof.finish().into_inner())
+                },
+                None,
+            )
+        }
+
+        /// Verifies a proof created by the prove function with the given kind of transcript and multi-open scheme, which default to Blake2b and GWC.
+        ///
+        /// `proof` is either a `Uint8Array` or a hex string encoded by [`proof_to_hex`].
+        /// `instances` are the public inputs read by [`instances_from_js`], which must be empty or omitted if the circuit has no public instances.
+        /// Returns an object `{ ok, error?, code? }` instead of throwing, where `error` is the message of [`VerifyError`] and `code` is its [`VerifyError::code`], e.g., `"truncated_proof"`.
+        #[wasm_bindgen]
+        pub fn $verify_detailed_fn_name(
+            params: JsValue,
+            vk: JsValue,
+            proof: JsValue,
+            transcript: Option<TranscriptKind>,
+            scheme: Option<MultiOpenScheme>,
+            instances: Option<Array>,
+        ) -> Object {
+            console_error_panic_hook::set_once();
+
+            let instances =
+                match instances_from_js(instances, $circuit_name::<Fr>::INFO.num_instances) {
+                    Ok(instances) => instances,
+                    Err(_) => return verify_result_to_js(Err(VerifyError::InvalidInstances)),
+                };
+            let instance_refs = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+            let result = proof_from_js(&proof)
+                .map_err(|e| {
+                    VerifyError::MalformedProof(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        e.to_string(),
+                    ))
+                })
+                .and_then(|proof| {
+                    verify_proof_bytes::<$circuit_name<Fr>>(
+                        &Uint8Array::new(&params).to_vec(),
+                        &Uint8Array::new(&vk).to_vec(),
+                        $circuit_name::<Fr>::min_k(),
+                        &proof,
+                        &instance_refs,
+                        transcript.unwrap_or_default(),
+                        scheme.unwrap_or_default(),
+                    )
+                });
+            verify_result_to_js(result)
+        }
+
+        #[wasm_bindgen]
+        pub fn $verify_fn_name(params: JsValue, vk: JsValue, proof: JsValue) -> bool {
+            verify_or_panic(&$verify_detailed_fn_name(
+                params, vk, proof, None, None, None,
+            ))
+        }
+
+        /// Verifies a proof created by the prove function with the suffix `_keccak`.
+        #[wasm_bindgen]
+        pub fn $verify_keccak_fn_name(params: JsValue, vk: JsValue, proof: JsValue) -> bool {
+            verify_or_panic(&$verify_detailed_fn_name(
+                params,
+                vk,
+                proof,
+                Some(TranscriptKind::Keccak256),
+                None,
+                None,
+            ))
+        }
+
+        /// Verifies a proof created by the prove function with the suffix `_shplonk`.
+        #[wasm_bindgen]
+        pub fn $verify_shplonk_fn_name(params: JsValue, vk: JsValue, proof: JsValue) -> bool {
+            verify_or_panic(&$verify_detailed_fn_name(
+                params,
+                vk,
+                proof,
+                None,
+                Some(MultiOpenScheme::Shplonk),
+                None,
+            ))
+        }
+
+        impl $circuit_name<Fr> {
+            /// Returns the circuits proving the pairs of `msgs` and `signatures` for [`prove_pkcs1v15_batch`] with their instances, each of which is the reason instead if the pair does not fit the circuit.
+            fn batch_circuits(
+                public_key: JsValue,
+                e: Option<Uint8Array>,
+                msgs: &Array,
+                signatures: &Array,
+            ) -> Result<Vec<Result<(Self, Vec<Vec<Fr>>), String>>, JsError> {
+                if msgs.length() != signatures.length() {
+                    return Err(JsError::new(&format!(
+                        "{} messages and {} signatures were given",
+                        msgs.length(),
+                        signatures.length()
+                    )));
+                }
+                let native_key = preflight_public_key(&public_key, e.as_ref(), Self::DEFAULT_E)
+                    .map_err(|e| JsError::new(&e))?;
+                let public_key = public_key_from_js(public_key, e, Self::DEFAULT_E);
+                Ok(msgs
+                    .iter()
+                    .zip(signatures.iter())
+                    .map(|(msg, signature)| {
+                        let msg = try_msg_from_js(&msg, $sha2_chip_enabled, $msg_len)?;
+                        let signature = bytes_from_js(&signature)
+                            .map_err(|e| format!("invalid signature: {}", e))?;
+                        if signature.len() > Self::BITS_LEN / 8 {
+                            return Err(format!(
+                                "the signature must be at most {} bytes, but {} bytes were given",
+                                Self::BITS_LEN / 8,
+                                signature.len()
+                            ));
+                        }
+                        preflight_signature(&native_key, &msg, $sha2_chip_enabled, &signature)?;
+                        let instances = Self::instances(&msg);
+                        let circuit = Self {
+                            signature: RSASignature::new(Value::known(BigUint::from_bytes_be(
+                                &signature,
+                            ))),
+                            public_key: public_key.clone(),
+                            msg,
+                            _f: PhantomData,
+                        };
+                        Ok((circuit, instances))
+                    })
+                    .collect())
+            }
+        }
+    };
+}
+
+impl_pkcs1v15_wasm_functions!(
+    Pkcs1v15_1024_64EnabledBenchCircuit,
+    prove_pkcs1v15_1024_64_circuit,
+    prove_pkcs1v15_1024_64_circuit_async,
+    prove_pkcs1v15_1024_64_circuit_cancellable,
+    prove_pkcs1v15_1024_64_circuit_with_handles,
+    prove_pkcs1v15_1024_64_circuit_keccak,
+    prove_pkcs1v15_1024_64_circuit_shplonk,
+    prove_pkcs1v15_1024_64_circuit_seeded,
+    verify_pkcs1v15_1024_64_circuit,
+    verify_pkcs1v15_1024_64_circuit_keccak,
+    verify_pkcs1v15_1024_64_circuit_shplonk,
+    verify_pkcs1v15_1024_64_circuit_detailed,
+    64,
+    19,
+    true
+);
+
+impl_pkcs1v15_wasm_functions!(
+    Pkcs1v15_1024_128EnabledBenchCircuit,
+    prove_pkcs1v15_1024_128_circuit,
+    prove_pkcs1v15_1024_128_circuit_async,
+    prove_pkcs1v15_1024_128_circuit_cancellable,
+    prove_pkcs1v15_1024_128_circuit_with_handles,
+    prove_pkcs1v15_1024_128_circuit_keccak,
+    prove_pkcs1v15_1024_128_circuit_shplonk,
+    prove_pkcs1v15_1024_128_circuit_seeded,
+    verify_pkcs1v15_1024_128_circuit,
+    verify_pkcs1v15_1024_128_circuit_keccak,
+    verify_pkcs1v15_1024_128_circuit_shplonk,
+    verify_pkcs1v15_1024_128_circuit_detailed,
+    128,
+    19,
+    true
+);
+
+impl_pkcs1v15_wasm_functions!(
+    Pkcs1v15_1024_1024EnabledBenchCircuit,
+    prove_pkcs1v15_1024_1024_circuit,
+    prove_pkcs1v15_1024_1024_circuit_async,
+    prove_pkcs1v15_1024_1024_circuit_cancellable,
+    prove_pkcs1v15_1024_1024_circuit_with_handles,
+    prove_pkcs1v15_1024_1024_circuit_keccak,
+    prove_pkcs1v15_1024_1024_circuit_shplonk,
+    prove_pkcs1v15_1024_1024_circuit_seeded,
+    verify_pkcs1v15_1024_1024_circuit,
+    verify_pkcs1v15_1024_1024_circuit_keccak,
+    verify_pkcs1v15_1024_1024_circuit_shplonk,
+    verify_pkcs1v15_1024_1024_circuit_detailed,
+    1024,
+    18,
+    true
+);
+
+impl_pkcs1v15_wasm_functions!(
+    Pkcs1v15_2048_64EnabledBenchCircuit,
+    prove_pkcs1v15_2048_64_circuit,
+    prove_pkcs1v15_2048_64_circuit_async,
+    prove_pkcs1v15_2048_64_circuit_cancellable,
+    prove_pkcs1v15_2048_64_circuit_with_handles,
+    prove_pkcs1v15_2048_64_circuit_keccak,
+    prove_pkcs1v15_2048_64_circuit_shplonk,
+    prove_pkcs1v15_2048_64_circuit_seeded,
+    verify_pkcs1v15_2048_64_circuit,
+    verify_pkcs1v15_2048_64_circuit_keccak,
+    verify_pkcs1v15_2048_64_circuit_shplonk,
+    verify_pkcs1v15_2048_64_circuit_detailed,
+    64,
+    19,
+    true
+);
+
+impl_pkcs1v15_wasm_functions!(
+    Pkcs1v15_2048_128EnabledBenchCircuit,
+    prove_pkcs1v15_2048_128_circuit,
+    prove_pkcs1v15_2048_128_circuit_async,
+    prove_pkcs1v15_2048_128_circuit_cancellable,
+    prove_pkcs1v15_2048_128_circuit_with_handles,
+    prove_pkcs1v15_2048_128_circuit_keccak,
+    prove_pkcs1v15_2048_128_circuit_shplonk,
+    prove_pkcs1v15_2048_128_circuit_seeded,
+    verify_pkcs1v15_2048_128_circuit,
+    verify_pkcs1v15_2048_128_circuit_keccak,
+    verify_pkcs1v15_2048_128_circuit_shplonk,
+    verify_pkcs1v15_2048_128_circuit_detailed,
+    128,
+    19,
+    true
+);
+
+impl_pkcs1v15_wasm_functions!(
+    Pkcs1v15_2048_1024EnabledBenchCircuit,
+    prove_pkcs1v15_2048_1024_circuit,
+    prove_pkcs1v15_2048_1024_circuit_async,
+    prove_pkcs1v15_2048_1024_circuit_cancellable,
+    prove_pkcs1v15_2048_1024_circuit_with_handles,
+    prove_pkcs1v15_2048_1024_circuit_keccak,
+    prove_pkcs1v15_2048_1024_circuit_shplonk,
+    prove_pkcs1v15_2048_1024_circuit_seeded,
+    verify_pkcs1v15_2048_1024_circuit,
+    verify_pkcs1v15_2048_1024_circuit_keccak,
+    verify_pkcs1v15_2048_1024_circuit_shplonk,
+    verify_pkcs1v15_2048_1024_circuit_detailed,
+    1024,
+    19,
+    true
+);
+
+impl_pkcs1v15_wasm_functions!(
+    Pkcs1v15_2048_1024DisabledBenchCircuit,
+    prove_pkcs1v15_no_sha2_2048_1024_circuit,
+    prove_pkcs1v15_no_sha2_2048_1024_circuit_async,
+    prove_pkcs1v15_no_sha2_2048_1024_circuit_cancellable,
+    prove_pkcs1v15_no_sha2_2048_1024_circuit_with_handles,
+    prove_pkcs1v15_no_sha2_2048_1024_circuit_keccak,
+    prove_pkcs1v15_no_sha2_2048_1024_circuit_shplonk,
+    prove_pkcs1v15_no_sha2_2048_1024_circuit_seeded,
+    verify_pkcs1v15_no_sha2_2048_1024_circuit,
+    verify_pkcs1v15_no_sha2_2048_1024_circuit_keccak,
+    verify_pkcs1v15_no_sha2_2048_1024_circuit_shplonk,
+    verify_pkcs1v15_no_sha2_2048_1024_circuit_detailed,
+    1024,
+    19,
+    false
+);
+
+#[macro_export]
+macro_rules! impl_pkcs1v15_digest_wasm_functions {
+    ($circuit_name:ident, $prove_fn_name:ident, $verify_fn_name:ident, $verify_detailed_fn_name:ident) => {
+        /// Proves a pkcs1v15 signature for the 32 bytes SHA256 `digest` of a message hashed outside the circuit, e.g., by `crypto.subtle.digest`.
+        ///
+        /// The digest is the public input of the proof, so the verifier must be given the same digest.
+        /// `public_key`, `signature`, and `e` are the same as those of the other prove functions.
+        /// Returns an error if the digest is not 32 bytes, the signature does not verify for it, or the params or the proving key are invalid.
+        #[wasm_bindgen]
+        pub fn $prove_fn_name(
+            params: JsValue,
+            pk: JsValue,
+            public_key: JsValue,
+            digest: Uint8Array,
+            signature: JsValue,
+            e: Option<Uint8Array>,
+        ) -> Result<Uint8Array, JsError> {
+            console_error_panic_hook::set_once();
+            let digest = sha256_digest_from_js(&digest)?;
+            preflight_check(
+                &public_key,
+                e.as_ref(),
+                $circuit_name::<Fr>::DEFAULT_E,
+                &digest,
+                false,
+                &signature,
+            )
+            .map_err(|e| JsError::new(&e))?;
+
+            let params = read_params(&Uint8Array::new(&params).to_vec())
+                .map_err(|e| JsError::new(&format!("invalid params: {}", e)))?;
+            let pk = read_pk_checked::<$circuit_name<Fr>>(&Uint8Array::new(&pk).to_vec())
+                .map_err(|e| JsError::new(&e.to_string()))?;
+            check_params_k(&params, pk.get_vk(), $circuit_name::<Fr>::min_k())
+                .map_err(|e| JsError::new(&e.to_string()))?;
+            let public_key = public_key_from_js(public_key, e, $circuit_name::<Fr>::DEFAULT_E);
+            let signature = signature_from_js(&signature, $circuit_name::<Fr>::BITS_LEN);
+
+            let instances = $circuit_name::<Fr>::instances(&digest);
+            let instance_refs = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+            let circuit = $circuit_name::<Fr> {
+                signature,
+                public_key,
+                msg: digest,
+                _f: PhantomData,
+            };
+
+            let writer = PhaseWriter::new::<$circuit_name<Fr>>(Uint8ArrayWriter::new());
+            let proof = create_proof_to_writer(
+                &params,
+                &pk,
+                circuit,
+                &instance_refs,
+                TranscriptKind::Blake2b,
+                MultiOpenScheme::Gwc,
+                None,
+                writer,
+            )
+            .map_err(|e| JsError::new(&format!("failed to create a proof: {:?}", e)))?;
+            Ok(proof.finish().into_array())
+        }
+
+        /// Verifies a proof created by the prove function for the 32 bytes SHA256 `digest`, with the given kind of transcript and multi-open scheme, which default to Blake2b and GWC.
+        ///
+        /// `proof` is either a `Uint8Array` or a hex string encoded by [`proof_to_hex`].
+        /// Returns the same object `{ ok, error?, code? }` as the other verify functions with the suffix `_detailed`, where a digest that is not 32 bytes results in the code `"invalid_instances"`.
+        #[wasm_bindgen]
+        pub fn $verify_detailed_fn_name(
+            params: JsValue,
+            vk: JsValue,
+            proof: JsValue,
+            digest: Uint8Array,
+            transcript: Option<TranscriptKind>,
+            scheme: Option<MultiOpenScheme>,
+        ) -> Object {
+            console_error_panic_hook::set_once();
+
+            let digest = digest.to_vec();
+            if digest.len() != 32 {
+                return verify_result_to_js(Err(VerifyError::InvalidInstances));
+            }
+            let instances = $circuit_name::<Fr>::instances(&digest);
+            let instance_refs = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+            let result = proof_from_js(&proof)
+                .map_err(|e| {
+                    VerifyError::MalformedProof(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        e.to_string(),
+                    ))
+                })
+                .and_then(|proof| {
+                    verify_proof_bytes::<$circuit_name<Fr>>(
+                        &Uint8Array::new(&params).to_vec(),
+                        &Uint8Array::new(&vk).to_vec(),
+                        $circuit_name::<Fr>::min_k(),
+                        &proof,
+                        &instance_refs,
+                        transcript.unwrap_or_default(),
+                        scheme.unwrap_or_default(),
+                    )
+                });
+            verify_result_to_js(result)
+        }
+
+        /// Verifies a proof created by the prove function for the 32 bytes SHA256 `digest`.
+        #[wasm_bindgen]
+        pub fn $verify_fn_name(
+            params: JsValue,
+            vk: JsValue,
+            proof: JsValue,
+            digest: Uint8Array,
+        ) -> bool {
+            verify_or_panic(&$verify_detailed_fn_name(
+                params, vk, proof, digest, None, None,
+            ))
+        }
+    };
+}
+
+impl_pkcs1v15_digest_wasm_functions!(
+    Pkcs1v15_1024_64WasmNoSha2Circuit,
+    prove_pkcs1v15_no_sha2_1024_circuit,
+    verify_pkcs1v15_no_sha2_1024_circuit,
+    verify_pkcs1v15_no_sha2_1024_circuit_detailed
+);
+
+#[wasm_bindgen(typescript_custom_section)]
+const PROVE_PKCS1V15_TS: &'static str = r#"
+/** The names of the circuits accepted by `prove_pkcs1v15`. */
+export type Pkcs1v15CircuitName =
+    | "1024_64"
+    | "1024_128"
+    | "1024_1024"
+    | "2048_64"
+    | "2048_128"
+    | "2048_1024"
+    | "no_sha2_2048_1024";
+
+/** The input of `prove_pkcs1v15`. */
+export interface ProvePkcs1v15Input {
+    circuit: Pkcs1v15CircuitName;
+    params: Uint8Array;
+    provingKey: Uint8Array;
+    /** A PEM-encoded RSA public key in either the SPKI or the PKCS#1 format. */
+    publicKeyPem: string;
+    /** The signed message, or its SHA256 digest for the circuits without the SHA256 chip. */
+    msg: Uint8Array;
+    /** The base64-encoded pkcs1v15 signature. */
+    signatureBase64: string;
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "ProvePkcs1v15Input")]
+    pub type ProvePkcs1v15Input;
+}
+
+/// The input of [`prove_pkcs1v15`], whose TypeScript definition is `ProvePkcs1v15Input`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct ProvePkcs1v15Fields {
+    circuit: String,
+    params: ByteBuf,
+    proving_key: ByteBuf,
+    public_key_pem: String,
+    msg: ByteBuf,
+    signature_base64: String,
+}
+
+/// Proves a pkcs1v15 signature with the circuit named in `input`, which is an object of the `ProvePkcs1v15Input` type.
+///
+/// Every field is validated before proving: an unknown circuit name, a missing or unknown field, an invalid PEM or base64 string, or a key, message, or signature that does not fit the circuit results in an error naming the cause.
+/// Returns the proof as a `Uint8Array`.
+#[wasm_bindgen]
+pub fn prove_pkcs1v15(input: ProvePkcs1v15Input) -> Result<Uint8Array, JsError> {
+    let input: ProvePkcs1v15Fields = serde_wasm_bindgen::from_value(input.into())
+        .map_err(|e| JsError::new(&format!("invalid prove input: {}", e)))?;
+    let info = pkcs1v15_circuit_info(&input.circuit)
+        .ok_or_else(|| unknown_circuit_error(&input.circuit))?;
+    let CircuitInfo {
+        bits_len,
+        sha2_chip_enabled,
+        ..
+    } = info;
+
+    let public_key = RsaPublicKey::from_public_key_pem(&input.public_key_pem)
+        .or_else(|_| RsaPublicKey::from_pkcs1_pem(&input.public_key_pem))
+        .map_err(|e| JsError::new(&format!("invalid publicKeyPem: {}", e)))?;
+    // A modulus with leading zero bits, e.g., 2047 bits, is accepted as long as its signatures have the same length.
+    if public_key.n().bits() > bits_len || public_key.size() != bits_len / 8 {
+        return Err(JsError::new(&format!(
+            "publicKeyPem must be a {} bits key for the circuit {}, but a {} bits key was given",
+            bits_len,
+            input.circuit,
+            public_key.n().bits()
+        )));
+    }
+    let signature = base64::engine::general_purpose::STANDARD
+        .decode(input.signature_base64.trim())
+        .map_err(|e| JsError::new(&format!("invalid signatureBase64: {}", e)))?;
+    // A signature whose leading zero bytes were stripped by its signer is left-padded.
+    let signature =
+        normalize_signature_bytes(&signature, public_key.size() * 8).ok_or_else(|| {
+            JsError::new(&format!(
+                "signatureBase64 must decode to at most {} bytes, but {} bytes were given",
+                public_key.size(),
+                signature.len()
+            ))
+        })?;
+    info.check_msg_len(&input.msg).map_err(|e| {
+        JsError::new(&format!(
+            "invalid msg for the circuit {}: {}",
+            input.circuit, e
+        ))
+    })?;
+
+    preflight_signature(&public_key, &input.msg, sha2_chip_enabled, &signature)
+        .map_err(|e| JsError::new(&e))?;
+
+    let params = JsValue::from(Uint8Array::from(&input.params[..]));
+    let pk = JsValue::from(Uint8Array::from(&input.proving_key[..]));
+    let e = Some(Uint8Array::from(&public_key.e().to_bytes_be()[..]));
+    let public_key = serde_wasm_bindgen::to_value(&public_key)?;
+    let msg = JsValue::from(Uint8Array::from(&input.msg[..]));
+    let signature = JsValue::from(Uint8Array::from(&signature[..]));
+    let proof = match input.circuit.as_str() {
+        "1024_64" => {
+            prove_pkcs1v15_1024_64_circuit(params, pk, public_key, msg, signature, e, None)
+        }
+        "1024_128" => {
+            prove_pkcs1v15_1024_128_circuit(params, pk, public_key, msg, signature, e, None)
+        }
+        "1024_1024" => {
+            prove_pkcs1v15_1024_1024_circuit(params, pk, public_key, msg, signature, e, None)
+        }
+        "2048_64" => {
+            prove_pkcs1v15_2048_64_circuit(params, pk, public_key, msg, signature, e, None)
+        }
+        "2048_128" => {
+            prove_pkcs1v15_2048_128_circuit(params, pk, public_key, msg, signature, e, None)
+        }
+        "2048_1024" => {
+            prove_pkcs1v15_2048_1024_circuit(params, pk, public_key, msg, signature, e, None)
+        }
+        _ => prove_pkcs1v15_no_sha2_2048_1024_circuit(
+            params, pk, public_key, msg, signature, e, None,
+        ),
+    };
+    Ok(proof)
+}
+
+/// The verifying keys embedded in the binary with the `builtin-vks` feature, keyed by the names in the `Pkcs1v15CircuitName` type.
+///
+/// Each file in `vks/` is the `vk` returned by the setup function with the suffix `_from_params` for the params of the trusted setup, so the keys verify proofs only with those params.
+/// The keys are versioned by [`write_vk_versioned`], so they must be regenerated whenever the circuits or the crate version change.
+#[cfg(feature = "builtin-vks")]
+const BUILTIN_VKS: &[(&str, &[u8])] = &[
+    (
+        "1024_64",
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/vks/1024_64.vk")),
+    ),
+    (
+        "1024_128",
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/vks/1024_128.vk")),
+    ),
+    (
+        "1024_1024",
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/vks/1024_1024.vk")),
+    ),
+    (
+        "2048_64",
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/vks/2048_64.vk")),
+    ),
+    (
+        "2048_128",
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/vks/2048_128.vk")),
+    ),
+    (
+        "2048_1024",
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/vks/2048_1024.vk")),
+    ),
+    (
+        "no_sha2_2048_1024",
+        include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/vks/no_sha2_2048_1024.vk"
+        )),
+    ),
+];
+#[cfg(not(feature = "builtin-vks"))]
+const BUILTIN_VKS: &[(&str, &[u8])] = &[];
+
+/// Returns the verifying key of the circuit `circuit_name` embedded in the binary, or `undefined` if the binary is built without the `builtin-vks` feature.
+#[wasm_bindgen]
+pub fn builtin_vk(circuit_name: &str) -> Option<Uint8Array> {
+    BUILTIN_VKS
+        .iter()
+        .find(|(name, _)| *name == circuit_name)
+        .map(|(_, vk)| Uint8Array::from(*vk))
+}
+
+/// Verifies a proof of the circuit `circuit_name`, one of the names in the `Pkcs1v15CircuitName` type, with the verifying key embedded in the binary.
+///
+/// The verifier thus needs to fetch only the params, which must be those of the trusted setup the embedded keys were generated for.
+/// `instances` are the public inputs read by [`instances_from_js`], which must be empty or omitted for the circuits without public instances.
+/// Returns the same object `{ ok, error?, code? }` as the verify functions with the suffix `_detailed`, or an error if `circuit_name` is unknown, no key is embedded, or `instances` do not fit the circuit.
+#[wasm_bindgen]
+pub fn verify_pkcs1v15_builtin(
+    circuit_name: &str,
+    params: Uint8Array,
+    proof: Uint8Array,
+    instances: Option<Array>,
+) -> Result<Object, JsError> {
+    console_error_panic_hook::set_once();
+
+    let info =
+        pkcs1v15_circuit_info(circuit_name).ok_or_else(|| unknown_circuit_error(circuit_name))?;
+    let vk = builtin_vk(circuit_name).ok_or_else(|| {
+        JsError::new(&format!(
+            "no verifying key of the circuit {} is embedded; build with the builtin-vks feature",
+            circuit_name
+        ))
+    })?;
+    instances_from_js(instances.clone(), info.num_instances).map_err(|e| {
+        JsError::new(&format!(
+            "invalid instances of the circuit {}: {}",
+            circuit_name, e
+        ))
+    })?;
+
+    let (params, vk, proof) = (params.into(), vk.into(), proof.into());
+    let result = match circuit_name {
+        "1024_64" => {
+            verify_pkcs1v15_1024_64_circuit_detailed(params, vk, proof, None, None, instances)
+        }
+        "1024_128" => {
+            verify_pkcs1v15_1024_128_circuit_detailed(params, vk, proof, None, None, instances)
+        }
+        "1024_1024" => {
+            verify_pkcs1v15_1024_1024_circuit_detailed(params, vk, proof, None, None, instances)
+        }
+        "2048_64" => {
+            verify_pkcs1v15_2048_64_circuit_detailed(params, vk, proof, None, None, instances)
+        }
+        "2048_128" => {
+            verify_pkcs1v15_2048_128_circuit_detailed(params, vk, proof, None, None, instances)
+        }
+        "2048_1024" => {
+            verify_pkcs1v15_2048_1024_circuit_detailed(params, vk, proof, None, None, instances)
+        }
+        _ => verify_pkcs1v15_no_sha2_2048_1024_circuit_detailed(
+            params, vk, proof, None, None, instances,
+        ),
+    };
+    Ok(result)
+}
+
+/// Proves a pkcs1v15 signature in the same way as [`prove_pkcs1v15`] but returns the proof wrapped in a [`ProofBundle`] encoded by [`ProofBundle::to_bytes`].
+///
+/// The bundle records the circuit, the fingerprint of the verifying key, the Blake2b transcript, and the GWC scheme of the proof, so [`verify_proof_bundle`] needs no circuit name.
+#[wasm_bindgen]
+pub fn prove_pkcs1v15_bundle(input: ProvePkcs1v15Input) -> Result<Uint8Array, JsError> {
+    let get = |key: &str| Reflect::get(&input, &JsValue::from_str(key)).ok();
+    let circuit = get("circuit")
+        .and_then(|circuit| circuit.as_string())
+        .unwrap_or_default();
+    // The fields are validated by `prove_pkcs1v15`, which fails first if the proving key is missing.
+    let pk = get("provingKey")
+        .map(|pk| Uint8Array::new(&pk).to_vec())
+        .unwrap_or_default();
+    let proof = prove_pkcs1v15(input)?;
+    let circuit_id = circuit_id_of_name(&circuit).ok_or_else(|| unknown_circuit_error(&circuit))?;
+    let vk_fingerprint = vk_fingerprint_of_name(&circuit, &pk)?;
+    let bundle = ProofBundle {
+        version: PROOF_BUNDLE_VERSION,
+        circuit_id: circuit_id.to_string(),
+        vk_fingerprint,
+        transcript: TranscriptKind::Blake2b,
+        scheme: MultiOpenScheme::Gwc,
+        instances: vec![],
+        proof: proof.to_vec(),
+    };
+    let bytes = bundle
+        .to_bytes()
+        .map_err(|e| JsError::new(&format!("failed to encode the proof bundle: {}", e)))?;
+    Ok(Uint8Array::from(&bytes[..]))
+}
+
+/// Verifies a proof bundle encoded by [`ProofBundle::to_bytes`], e.g., the one returned by [`prove_pkcs1v15_bundle`], for the circuit recorded in it.
+///
+/// `vk` must be the verifying key of that circuit, whose id is checked against the header of `vk`.
+/// Returns the same object `{ ok, error?, code? }` as the verify functions with the suffix `_detailed`, where an unknown version or a circuit of another verifying key results in `ok: false`.
+#[wasm_bindgen]
+pub fn verify_proof_bundle(params: Uint8Array, vk: Uint8Array, bundle: Uint8Array) -> Object {
+    console_error_panic_hook::set_once();
+
+    let (params, vk) = (params.to_vec(), vk.to_vec());
+    let bundle = match ProofBundle::from_bytes(&bundle.to_vec()) {
+        Ok(bundle) => bundle,
+        Err(e) => return verify_result_to_js(Err(VerifyError::MalformedProof(e))),
+    };
+    macro_rules! verify_for {
+        ($($circuit_name:ident),*) => {
+            $(
+                if bundle.circuit_id == ProofBundle::circuit_id_of::<$circuit_name<Fr>>() {
+                    return verify_result_to_js(verify_bundle_bytes::<$circuit_name<Fr>>(
+                        &params,
+                        &vk,
+                        $circuit_name::<Fr>::min_k(),
+                        &bundle,
+                    ));
+                }
+            )*
+        };
+    }
+    verify_for!(
+        Pkcs1v15_1024_64EnabledBenchCircuit,
+        Pkcs1v15_1024_128EnabledBenchCircuit,
+        Pkcs1v15_1024_1024EnabledBenchCircuit,
+        Pkcs1v15_2048_64EnabledBenchCircuit,
+        Pkcs1v15_2048_128EnabledBenchCircuit,
+        Pkcs1v15_2048_1024EnabledBenchCircuit,
+        Pkcs1v15_2048_1024DisabledBenchCircuit
+    );
+    verify_result_to_js(Err(VerifyError::CircuitMismatch {
+        expected: "one of the pkcs1v15 circuits".to_string(),
+        actual: bundle.circuit_id,
+    }))
+}
+
+/// Returns the id given by [`ProofBundle::circuit_id_of`] to the circuit named `circuit_name` in the `Pkcs1v15CircuitName` type.
+fn circuit_id_of_name(circuit_name: &str) -> Option<&'static str> {
+    let circuit_id = match circuit_name {
+        "1024_64" => ProofBundle::circuit_id_of::<Pkcs1v15_1024_64EnabledBenchCircuit<Fr>>(),
+        "1024_128" => ProofBundle::circuit_id_of::<Pkcs1v15_1024_128EnabledBenchCircuit<Fr>>(),
+        "1024_1024" => ProofBundle::circuit_id_of::<Pkcs1v15_1024_1024EnabledBenchCircuit<Fr>>(),
+        "2048_64" => ProofBundle::circuit_id_of::<Pkcs1v15_2048_64EnabledBenchCircuit<Fr>>(),
+        "2048_128" => ProofBundle::circuit_id_of::<Pkcs1v15_2048_128EnabledBenchCircuit<Fr>>(),
+        "2048_1024" => ProofBundle::circuit_id_of::<Pkcs1v15_2048_1024EnabledBenchCircuit<Fr>>(),
+        "no_sha2_2048_1024" => {
+            ProofBundle::circuit_id_of::<Pkcs1v15_2048_1024DisabledBenchCircuit<Fr>>()
+        }
+        _ => return None,
+    };
+    Some(circuit_id)
+}
+
+/// Returns the fingerprint of the verifying key in the proving key `pk` of the circuit named `circuit_name` in the `Pkcs1v15CircuitName` type.
+fn vk_fingerprint_of_name(circuit_name: &str, pk: &[u8]) -> Result<[u8; 32], JsError> {
+    let vk = match circuit_name {
+        "1024_64" => extract_vk_bytes::<Pkcs1v15_1024_64EnabledBenchCircuit<Fr>>(pk),
+        "1024_128" => extract_vk_bytes::<Pkcs1v15_1024_128EnabledBenchCircuit<Fr>>(pk),
+        "1024_1024" => extract_vk_bytes::<Pkcs1v15_1024_1024EnabledBenchCircuit<Fr>>(pk),
+        "2048_64" => extract_vk_bytes::<Pkcs1v15_2048_64EnabledBenchCircuit<Fr>>(pk),
+        "2048_128" => extract_vk_bytes::<Pkcs1v15_2048_128EnabledBenchCircuit<Fr>>(pk),
+        "2048_1024" => extract_vk_bytes::<Pkcs1v15_2048_1024EnabledBenchCircuit<Fr>>(pk),
+        "no_sha2_2048_1024" => extract_vk_bytes::<Pkcs1v15_2048_1024DisabledBenchCircuit<Fr>>(pk),
+        _ => return Err(unknown_circuit_error(circuit_name)),
+    }
+    .map_err(|e| JsError::new(&format!("invalid provingKey: {}", e)))?;
+    Ok(vk_fingerprint_bytes(&vk))
+}
+
+/// Reads the params and the verifying key of the circuit `C` and verifies `bundle` for [`verify_proof_bundle`].
+fn verify_bundle_bytes<C: Circuit<Fr>>(
+    params: &[u8],
+    vk: &[u8],
+    min_k: u32,
+    bundle: &ProofBundle,
+) -> Result<(), VerifyError> {
+    let params = read_verifier_params(params).map_err(VerifyError::InvalidParams)?;
+    let vk = read_vk_checked::<C>(vk).map_err(VerifyError::InvalidVerifyingKey)?;
+    check_params_k(&params, &vk, min_k).map_err(VerifyError::InvalidVerifyingKey)?;
+    verify_bundle::<C>(&params, &vk, bundle)
+}
+
+/// Proves the pairs of messages and signatures in `msgs` and `signatures` with the circuit `circuit_name` for the same `public_key`, one of the names in the `Pkcs1v15CircuitName` type.
+///
+/// The params and the proving key are deserialized once for the whole batch, and the proofs are created in parallel if the thread pool is initialized with multiple workers.
+/// `public_key` and `e` are the same as those of the prove functions, and each message may be given in chunks as well.
+/// Returns an array whose `i`-th element is the proof of the `i`-th pair as a `Uint8Array`, or an `Error` if the pair cannot be proved, e.g., its signature is invalid, so one bad pair does not fail the others.
+/// Returns an error for the whole batch only if the shared arguments are invalid.
+#[wasm_bindgen]
+pub fn prove_pkcs1v15_batch(
+    circuit_name: &str,
+    params: Uint8Array,
+    pk: Uint8Array,
+    public_key: JsValue,
+    msgs: Array,
+    signatures: Array,
+    e: Option<Uint8Array>,
+    pk_format: Option<KeyFormat>,
+) -> Result<Array, JsError> {
+    console_error_panic_hook::set_once();
+
+    let (params, pk) = (params.to_vec(), pk.to_vec());
+    match circuit_name {
+        "1024_64" => prove_batch(
+            &params,
+            &pk,
+            pk_format.unwrap_or_default(),
+            Pkcs1v15_1024_64EnabledBenchCircuit::<Fr>::min_k(),
+            Pkcs1v15_1024_64EnabledBenchCircuit::batch_circuits(public_key, e, &msgs, &signatures)?,
+        ),
+        "1024_128" => prove_batch(
+            &params,
+            &pk,
+            pk_format.unwrap_or_default(),
+            Pkcs1v15_1024_128EnabledBenchCircuit::<Fr>::min_k(),
+            Pkcs1v15_1024_128EnabledBenchCircuit::batch_circuits(
+                public_key,
+                e,
+                &msgs,
+                &signatures,
+            )?,
+        ),
+        "1024_1024" => prove_batch(
+            &params,
+            &pk,
+            pk_format.unwrap_or_default(),
+            Pkcs1v15_1024_1024EnabledBenchCircuit::<Fr>::min_k(),
+            Pkcs1v15_1024_1024EnabledBenchCircuit::batch_circuits(
+                public_key,
+                e,
+                &msgs,
+                &signatures,
+            )?,
+        ),
+        "2048_64" => prove_batch(
+            &params,
+            &pk,
+            pk_format.unwrap_or_default(),
+            Pkcs1v15_2048_64EnabledBenchCircuit::<Fr>::min_k(),
+            Pkcs1v15_2048_64EnabledBenchCircuit::batch_circuits(public_key, e, &msgs, &signatures)?,
+        ),
+        "2048_128" => prove_batch(
+            &params,
+            &pk,
+            pk_format.unwrap_or_default(),
+            Pkcs1v15_2048_128EnabledBenchCircuit::<Fr>::min_k(),
+            Pkcs1v15_2048_128EnabledBenchCircuit::batch_circuits(
+                public_key,
+                e,
+                &msgs,
+                &signatures,
+            )?,
+        ),
+        "2048_1024" => prove_batch(
+            &params,
+            &pk,
+            pk_format.unwrap_or_default(),
+            Pkcs1v15_2048_1024EnabledBenchCircuit::<Fr>::min_k(),
+            Pkcs1v15_2048_1024EnabledBenchCircuit::batch_circuits(
+                public_key,
+                e,
+                &msgs,
+                &signatures,
+            )?,
+        ),
+        "no_sha2_2048_1024" => prove_batch(
+            &params,
+            &pk,
+            pk_format.unwrap_or_default(),
+            Pkcs1v15_2048_1024DisabledBenchCircuit::<Fr>::min_k(),
+            Pkcs1v15_2048_1024DisabledBenchCircuit::batch_circuits(
+                public_key,
+                e,
+                &msgs,
+                &signatures,
+            )?,
+        ),
+        _ => Err(unknown_circuit_error(circuit_name)),
+    }
+}
+
+/// Verifies `proofs` created by the prove functions of the circuit `circuit_name` with the Blake2b transcript and the GWC multi-open scheme.
+///
+/// The params and the verifying key are deserialized once for the whole batch, and the proofs are verified in parallel if the thread pool is initialized with multiple workers.
+/// `instances` is an array whose `i`-th element is the public inputs of the `i`-th proof read by [`instances_from_js`], which may be omitted for the circuits without public instances.
+/// Returns an array whose `i`-th element is whether the `i`-th proof is valid; a proof that cannot be read is just invalid.
+/// Each proof is either a `Uint8Array` or a hex string encoded by [`proof_to_hex`].
+/// Returns an error only if `circuit_name`, the params, the verifying key, or `instances` is invalid.
+#[wasm_bindgen]
+pub fn verify_pkcs1v15_batch(
+    circuit_name: &str,
+    params: Uint8Array,
+    vk: Uint8Array,
+    proofs: Array,
+    instances: Option<Array>,
+    vk_format: Option<KeyFormat>,
+) -> Result<Array, JsError> {
+    console_error_panic_hook::set_once();
+
+    let info =
+        pkcs1v15_circuit_info(circuit_name).ok_or_else(|| unknown_circuit_error(circuit_name))?;
+    let num_proofs = proofs.length() as usize;
+    let instances =
+        batch_instances_from_js(instances, num_proofs, info.num_instances).map_err(|e| {
+            JsError::new(&format!(
+                "invalid instances of the circuit {}: {}",
+                circuit_name, e
+            ))
+        })?;
+    let (params, vk) = (params.to_vec(), vk.to_vec());
+    match circuit_name {
+        "1024_64" => verify_batch::<Pkcs1v15_1024_64EnabledBenchCircuit<Fr>>(
+            &params,
+            &vk,
+            vk_format.unwrap_or_default(),
+            Pkcs1v15_1024_64EnabledBenchCircuit::<Fr>::min_k(),
+            &proofs,
+            &instances,
+        ),
+        "1024_128" => verify_batch::<Pkcs1v15_1024_128EnabledBenchCircuit<Fr>>(
+            &params,
+            &vk,
+            vk_format.unwrap_or_default(),
+            Pkcs1v15_1024_128EnabledBenchCircuit::<Fr>::min_k(),
+            &proofs,
+            &instances,
+        ),
+        "1024_1024" => verify_batch::<Pkcs1v15_1024_1024EnabledBenchCircuit<Fr>>(
+            &params,
+            &vk,
+            vk_format.unwrap_or_default(),
+            Pkcs1v15_1024_1024EnabledBenchCircuit::<Fr>::min_k(),
+            &proofs,
+            &instances,
+        ),
+        "2048_64" => verify_batch::<Pkcs1v15_2048_64EnabledBenchCircuit<Fr>>(
+            &params,
+            &vk,
+            vk_format.unwrap_or_default(),
+            Pkcs1v15_2048_64EnabledBenchCircuit::<Fr>::min_k(),
+            &proofs,
+            &instances,
+        ),
+        "2048_128" => verify_batch::<Pkcs1v15_2048_128EnabledBenchCircuit<Fr>>(
+            &params,
+            &vk,
+            vk_format.unwrap_or_default(),
+            Pkcs1v15_2048_128EnabledBenchCircuit::<Fr>::min_k(),
+            &proofs,
+            &instances,
+        ),
+        "2048_1024" => verify_batch::<Pkcs1v15_2048_1024EnabledBenchCircuit<Fr>>(
+            &params,
+            &vk,
+            vk_format.unwrap_or_default(),
+            Pkcs1v15_2048_1024EnabledBenchCircuit::<Fr>::min_k(),
+            &proofs,
+            &instances,
+        ),
+        "no_sha2_2048_1024" => verify_batch::<Pkcs1v15_2048_1024DisabledBenchCircuit<Fr>>(
+            &params,
+            &vk,
+            vk_format.unwrap_or_default(),
+            Pkcs1v15_2048_1024DisabledBenchCircuit::<Fr>::min_k(),
+            &proofs,
+            &instances,
+        ),
+        _ => Err(unknown_circuit_error(circuit_name)),
+    }
+}
+
+/// Proves each of `circuits` with its instances for [`prove_pkcs1v15_batch`] with the params and the proving key deserialized once.
+fn prove_batch<C: Circuit<Fr> + Send>(
+    params: &[u8],
+    pk: &[u8],
+    pk_format: KeyFormat,
+    min_k: u32,
+    circuits: Vec<Result<(C, Vec<Vec<Fr>>), String>>,
+) -> Result<Array, JsError> {
+    let params =
+        read_params(params).map_err(|e| JsError::new(&format!("invalid params: {}", e)))?;
+    let pk = read_pk_with_format::<C>(pk, pk_format).map_err(|e| JsError::new(&e.to_string()))?;
+    check_params_k(&params, pk.get_vk(), min_k).map_err(|e| JsError::new(&e.to_string()))?;
+
+    let prove = |circuit: Result<(C, Vec<Vec<Fr>>), String>| -> Result<Vec<u8>, String> {
+        let (circuit, instances) = circuit?;
+        let prover =
+            MockProver::run(min_k, &circuit, instances.clone()).map_err(|e| format!("{:?}", e))?;
+        prover.verify().map_err(|_| {
+            "the signature is invalid for the message and the public key".to_string()
+        })?;
+        let instance_refs = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        create_proof_with_transcript(
+            &params,
+            &pk,
+            circuit,
+            &instance_refs,
+            TranscriptKind::Blake2b,
+        )
+        .map_err(|e| format!("failed to create a proof: {:?}", e))
+    };
+    let results: Vec<_> = if is_multithreading_available() {
+        circuits.into_par_iter().map(prove).collect()
+    } else {
+        circuits.into_iter().map(prove).collect()
+    };
+    Ok(results
+        .into_iter()
+        .map(|result| match result {
+            Ok(proof) => JsValue::from(Uint8Array::from(&proof[..])),
+            Err(message) => JsValue::from(js_sys::Error::new(&message)),
+        })
+        .collect())
+}
+
+/// Verifies each of `proofs` with the instances of the same index for [`verify_pkcs1v15_batch`] with the params and the verifying key deserialized once.
+fn verify_batch<C: Circuit<Fr>>(
+    params: &[u8],
+    vk: &[u8],
+    vk_format: KeyFormat,
+    min_k: u32,
+    proofs: &Array,
+    instances: &[Vec<Vec<Fr>>],
+) -> Result<Array, JsError> {
+    let params = read_verifier_params(params)
+        .map_err(|e| JsError::new(&format!("invalid params: {}", e)))?;
+    let vk = read_vk_with_format::<C>(vk, vk_format).map_err(|e| JsError::new(&e.to_string()))?;
+    check_params_k(&params, &vk, min_k).map_err(|e| JsError::new(&e.to_string()))?;
+
+    if instances.len() != proofs.length() as usize {
+        return Err(JsError::new(&format!(
+            "{} proofs and {} instances were given",
+            proofs.length(),
+            instances.len()
+        )));
+    }
+    let proofs = proofs
+        .iter()
+        .map(|proof| proof_from_js(&proof).ok())
+        .zip(instances.iter())
+        .collect::<Vec<_>>();
+    let verify = |(proof, instances): &(Option<Vec<u8>>, &Vec<Vec<Fr>>)| {
+        proof.as_ref().map_or(false, |proof| {
+            verify_proof_with(
+                &params,
+                &vk,
+                proof,
+                &instances.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+                TranscriptKind::Blake2b,
+                MultiOpenScheme::Gwc,
+            )
+            .is_ok()
+        })
+    };
+    let results: Vec<_> = if is_multithreading_available() {
+        proofs.par_iter().map(verify).collect()
+    } else {
+        proofs.iter().map(verify).collect()
+    };
+    Ok(results.into_iter().map(JsValue::from_bool).collect())
+}
+
+/// The object returned by [`circuit_info`].
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CircuitInfoFields {
+    k: u32,
+    bits_len: usize,
+    msg_len: usize,
+    num_instances: usize,
+    sha2_chip_enabled: bool,
+    proof_size_estimate: usize,
+    proving_mem_estimate_mb: usize,
+    setup_mem_estimate_mb: usize,
+}
+
+/// Returns the names of the circuits in the `Pkcs1v15CircuitName` type.
+#[wasm_bindgen]
+pub fn list_circuits() -> Array {
+    PKCS1V15_CIRCUITS
+        .iter()
+        .map(|(name, _)| JsValue::from_str(name))
+        .collect()
+}
+
+/// Returns the metadata of the circuit `circuit_name` for choosing the params to download and warning about the memory usage.
+///
+/// Returns an object `{ k, bitsLen, msgLen, numInstances, sha2ChipEnabled, proofSizeEstimate, provingMemEstimateMb, setupMemEstimateMb }`.
+/// `k` is the minimum degree of the params, and `msgLen` is the maximum message length including the 9 bytes of the SHA256 padding, or 32 for the circuits taking SHA256 digests.
+/// The estimates in bytes and MiB are computed by [`estimate_proof_size`], [`estimate_proving_memory`], and [`estimate_keygen_memory`] from the constraint system of the circuit.
+#[wasm_bindgen]
+pub fn circuit_info(circuit_name: &str) -> Result<JsValue, JsError> {
+    match circuit_name {
+        "1024_64" => circuit_info_of::<Pkcs1v15_1024_64EnabledBenchCircuit<Fr>>(
+            Pkcs1v15_1024_64EnabledBenchCircuit::<Fr>::INFO,
+        ),
+        "1024_128" => circuit_info_of::<Pkcs1v15_1024_128EnabledBenchCircuit<Fr>>(
+            Pkcs1v15_1024_128EnabledBenchCircuit::<Fr>::INFO,
+        ),
+        "1024_1024" => circuit_info_of::<Pkcs1v15_1024_1024EnabledBenchCircuit<Fr>>(
+            Pkcs1v15_1024_1024EnabledBenchCircuit::<Fr>::INFO,
+        ),
+        "2048_64" => circuit_info_of::<Pkcs1v15_2048_64EnabledBenchCircuit<Fr>>(
+            Pkcs1v15_2048_64EnabledBenchCircuit::<Fr>::INFO,
+        ),
+        "2048_128" => circuit_info_of::<Pkcs1v15_2048_128EnabledBenchCircuit<Fr>>(
+            Pkcs1v15_2048_128EnabledBenchCircuit::<Fr>::INFO,
+        ),
+        "2048_1024" => circuit_info_of::<Pkcs1v15_2048_1024EnabledBenchCircuit<Fr>>(
+            Pkcs1v15_2048_1024EnabledBenchCircuit::<Fr>::INFO,
+        ),
+        "no_sha2_2048_1024" => circuit_info_of::<Pkcs1v15_2048_1024DisabledBenchCircuit<Fr>>(
+            Pkcs1v15_2048_1024DisabledBenchCircuit::<Fr>::INFO,
+        ),
+        _ => Err(unknown_circuit_error(circuit_name)),
+    }
+}
+
+fn circuit_info_of<C: Circuit<Fr>>(info: CircuitInfo) -> Result<JsValue, JsError> {
+    let fields = CircuitInfoFields {
+        k: info.k,
+        bits_len: info.bits_len,
+        msg_len: info.msg_len,
+        num_instances: info.num_instances,
+        sha2_chip_enabled: info.sha2_chip_enabled,
+        proof_size_estimate: estimate_proof_size::<C>(),
+        proving_mem_estimate_mb: (estimate_proving_memory::<C>(info.k) + (1 << 20) - 1) >> 20,
+        setup_mem_estimate_mb: (estimate_keygen_memory::<C>(info.k) + (1 << 20) - 1) >> 20,
+    };
+    Ok(serde_wasm_bindgen::to_value(&fields)?)
+}
+
+/// Returns the error for a circuit name not in [`PKCS1V15_CIRCUITS`].
+fn unknown_circuit_error(circuit_name: &str) -> JsError {
+    let names = PKCS1V15_CIRCUITS
+        .iter()
+        .map(|(name, ..)| *name)
+        .collect::<Vec<_>>();
+    JsError::new(&format!(
+        "unknown circuit {:?}, expected one of {}",
+        circuit_name,
+        names.join(", ")
+    ))
+}
+
+/// Trims the given params to the part used by the verify functions.
+///
+/// The verify functions accept both the full params and the returned params.
+/// `num_instances` is the maximum number of rows used in an instance column of the verified circuit and defaults to zero.
+#[wasm_bindgen]
+pub fn extract_verifier_params(
+    params: JsValue,
+    num_instances: Option<usize>,
+) -> Result<Uint8Array, JsError> {
+    let params = Uint8Array::new(&params).to_vec();
+    let trimmed = extract_verifier_params_bytes(&params, num_instances.unwrap_or(0))
+        .map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(Uint8Array::from(&trimmed[..]))
+}
+
+/// Derives the verifying key of the circuit `circuit_name`, one of the names in the `Pkcs1v15CircuitName` type, from its proving key `pk`.
+///
+/// The returned key is the same as the `vk` returned by the setup function with `pk`, so the holders of only `pk` can verify proofs without running the setup again.
+#[wasm_bindgen]
+pub fn extract_verifying_key(circuit_name: &str, pk: Uint8Array) -> Result<Uint8Array, JsError> {
+    let pk = pk.to_vec();
+    let vk = match circuit_name {
+        "1024_64" => extract_vk_bytes::<Pkcs1v15_1024_64EnabledBenchCircuit<Fr>>(&pk),
+        "1024_128" => extract_vk_bytes::<Pkcs1v15_1024_128EnabledBenchCircuit<Fr>>(&pk),
+        "1024_1024" => extract_vk_bytes::<Pkcs1v15_1024_1024EnabledBenchCircuit<Fr>>(&pk),
+        "2048_64" => extract_vk_bytes::<Pkcs1v15_2048_64EnabledBenchCircuit<Fr>>(&pk),
+        "2048_128" => extract_vk_bytes::<Pkcs1v15_2048_128EnabledBenchCircuit<Fr>>(&pk),
+        "2048_1024" => extract_vk_bytes::<Pkcs1v15_2048_1024EnabledBenchCircuit<Fr>>(&pk),
+        "no_sha2_2048_1024" => extract_vk_bytes::<Pkcs1v15_2048_1024DisabledBenchCircuit<Fr>>(&pk),
+        _ => return Err(unknown_circuit_error(circuit_name)),
+    };
+    let vk = vk.map_err(|e| JsError::new(&format!("invalid proving key: {}", e)))?;
+    Ok(Uint8Array::from(&vk[..]))
+}
+
+/// Converts a proving key or a verifying key of the circuit `circuit_name`, one of the names in the `Pkcs1v15CircuitName` type, from the format `from` to `to`.
+///
+/// `from` defaults to `RawBytes`, the format of the keys returned by the setup functions. The converted key must be read with `to`, e.g., by [`load_proving_key`].
+#[wasm_bindgen]
+pub fn convert_key_format(
+    circuit_name: &str,
+    key: Uint8Array,
+    from: Option<KeyFormat>,
+    to: KeyFormat,
+) -> Result<Uint8Array, JsError> {
+    let (key, from) = (key.to_vec(), from.unwrap_or_default());
+    let converted = match circuit_name {
+        "1024_64" => {
+            convert_key_format_bytes::<Pkcs1v15_1024_64EnabledBenchCircuit<Fr>>(&key, from, to)
+        }
+        "1024_128" => {
+            convert_key_format_bytes::<Pkcs1v15_1024_128EnabledBenchCircuit<Fr>>(&key, from, to)
+        }
+        "1024_1024" => {
+            convert_key_format_bytes::<Pkcs1v15_1024_1024EnabledBenchCircuit<Fr>>(&key, from, to)
+        }
+        "2048_64" => {
+            convert_key_format_bytes::<Pkcs1v15_2048_64EnabledBenchCircuit<Fr>>(&key, from, to)
+        }
+        "2048_128" => {
+            convert_key_format_bytes::<Pkcs1v15_2048_128EnabledBenchCircuit<Fr>>(&key, from, to)
+        }
+        "2048_1024" => {
+            convert_key_format_bytes::<Pkcs1v15_2048_1024EnabledBenchCircuit<Fr>>(&key, from, to)
+        }
+        "no_sha2_2048_1024" => {
+            convert_key_format_bytes::<Pkcs1v15_2048_1024DisabledBenchCircuit<Fr>>(&key, from, to)
+        }
+        _ => return Err(unknown_circuit_error(circuit_name)),
+    };
+    let converted = converted.map_err(|e| JsError::new(&format!("invalid key: {}", e)))?;
+    Ok(Uint8Array::from(&converted[..]))
+}
+
+/// Returns the maximum degree `k` of the given params by parsing only their header, e.g., to reject params smaller than `k` of [`circuit_info`] before loading them.
+///
+/// Only the first bytes of `params` are copied into the wasm memory, so it is cheap even for params of many megabytes.
+#[wasm_bindgen]
+pub fn params_max_k(params: Uint8Array) -> Result<u32, JsError> {
+    let peek_len = params.length().min(PARAMS_HEADER_PEEK_BYTES as u32);
+    let prefix = params.subarray(0, peek_len).to_vec();
+    params_prefix_max_k(&prefix, params.length() as u64)
+        .map_err(|e| JsError::new(&format!("invalid params: {}", e)))
+}
+
+/// Compresses the given params for transfer. All functions taking params accept the compressed params as they are.
+#[wasm_bindgen]
+pub fn compress_params(params: Uint8Array) -> Result<Uint8Array, JsError> {
+    let compressed =
+        compress_params_bytes(&params.to_vec()).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(Uint8Array::from(&compressed[..]))
+}
+
+/// Restores the params compressed by [`compress_params`].
+#[wasm_bindgen]
+pub fn decompress_params(bytes: Uint8Array) -> Result<Uint8Array, JsError> {
+    let params =
+        decompress_params_bytes(&bytes.to_vec()).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(Uint8Array::from(&params[..]))
+}
+
+/// Downsizes the params of a larger ceremony, e.g., an `.srs` file of the Axiom or the PSE ceremony, to `target_k`. See [`crate::downsize_params`].
+///
+/// The whole ceremony file is deserialized, so its params must fit in the memory of the wasm module; downsize them natively otherwise.
+#[wasm_bindgen]
+pub fn downsize_params(params: Uint8Array, target_k: u32) -> Result<Uint8Array, JsError> {
+    let downsized = downsize_params_bytes(&params.to_vec(), target_k)
+        .map_err(|e| JsError::new(&format!("invalid params: {}", e)))?;
+    Ok(Uint8Array::from(&downsized[..]))
+}
+
+/// Compresses the given proving key or verifying key for transfer. All functions taking keys accept the compressed keys as they are.
+#[wasm_bindgen]
+pub fn compress_key(key: Uint8Array) -> Result<Uint8Array, JsError> {
+    let compressed = compress_key_bytes(&key.to_vec()).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(Uint8Array::from(&compressed[..]))
+}
+
+/// Restores the key compressed by [`compress_key`].
+#[wasm_bindgen]
+pub fn decompress_key(bytes: Uint8Array) -> Result<Uint8Array, JsError> {
+    let key = decompress_key_bytes(&bytes.to_vec()).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(Uint8Array::from(&key[..]))
 }
 
+/// Returns the 32 bytes fingerprint of the versioned verifying key `vk`, which may be compressed by [`compress_key`].
+///
+/// It is the fingerprint recorded in the bundles of [`prove_pkcs1v15_bundle`], and its hex string is the `vkDigest` returned by the setup functions, so the verifying key a verifier holds can be matched against either of them.
 #[wasm_bindgen]
-pub fn sha256_msg(msg: JsValue) -> JsValue {
-    //let msg: Vec<u8> = serde_wasm_bindgen::from_value(msg).unwrap();
-    let msg: Vec<u8> = Uint8Array::new(&msg).to_vec();
-    let hashed_msg = Sha256::digest(&msg).to_vec();
-    serde_wasm_bindgen::to_value(&hashed_msg).unwrap()
+pub fn vk_fingerprint(vk: Uint8Array) -> Result<Uint8Array, JsError> {
+    let vk = decompress_key_bytes(&vk.to_vec()).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(Uint8Array::from(&vk_fingerprint_bytes(&vk)[..]))
 }
 
-#[macro_export]
-macro_rules! impl_pkcs1v15_wasm_functions {
-    ($circuit_name:ident, $prove_fn_name:ident, $verify_fn_name:ident, $msg_len:expr, $k:expr, $sha2_chip_enabled:expr) => {
-        #[wasm_bindgen]
-        pub fn $prove_fn_name(
-            params: JsValue,
-            pk: JsValue,
-            public_key: JsValue,
-            msg: JsValue,
-            signature: JsValue,
-        ) -> JsValue {
-            console_error_panic_hook::set_once();
-
-            let params = Uint8Array::new(&params).to_vec();
-            let params = ParamsKZG::<Bn256>::read(&mut BufReader::new(&params[..])).unwrap();
-
-            let pk: Vec<u8> = Uint8Array::new(&pk).to_vec();
-            let pk = ProvingKey::<G1Affine>::read::<_, $circuit_name<Fr>>(
-                &mut BufReader::new(&pk[..]),
-                SerdeFormat::RawBytes,
-            )
-            .unwrap();
+/// Checks that `bytes` bytes can still be allocated so that a setup that cannot fit in the wasm memory fails with an error instead of aborting the instance.
+///
+/// The setup functions pass the peak of the key generation estimated by [`estimate_keygen_memory`], not only the params, because [`keygen_pk`] allocates the fixed and permutation polynomials over the extended domain after the params and takes several times more.
+/// For k = 19 the params take 64 MiB, and the estimated peak of each circuit is reported as `setupMemEstimateMb` by [`circuit_info`].
+fn ensure_allocatable(bytes: usize) -> Result<(), JsError> {
+    let mut probe: Vec<u8> = Vec::new();
+    probe.try_reserve_exact(bytes).map_err(|_| {
+        JsError::new(&format!(
+            "failed to allocate {} bytes for the setup; try a smaller circuit",
+            bytes
+        ))
+    })
+}
 
-            let public_key: RsaPublicKey = serde_wasm_bindgen::from_value(public_key).unwrap();
-            let n_big =
-                BigUint::from_radix_le(&public_key.n().clone().to_radix_le(16), 16).unwrap();
-            let e_fix = RSAPubE::Fix(BigUint::from($circuit_name::<Fr>::DEFAULT_E));
-            let public_key = RSAPublicKey::new(Value::known(n_big), e_fix);
+fn set_bytes(object: &Object, key: &str, bytes: &[u8]) -> Result<(), JsError> {
+    Reflect::set(object, &JsValue::from_str(key), &Uint8Array::from(bytes))
+        .map_err(|_| JsError::new(&format!("failed to set {} to the output object", key)))?;
+    Ok(())
+}
 
-            let msg: Vec<u8> = Uint8Array::new(&msg).to_vec();
-            let mut signature: Vec<u8> = serde_wasm_bindgen::from_value(signature).unwrap();
+/// Generates the keys of `circuit` for `params` and returns an object `{ params, pk, vk, vkDigest }`.
+///
+/// `params_bytes` must be the serialization of `params`. `vkDigest` is the hex-encoded [`vk_fingerprint`] of the serialized verifying key, i.e., its SHA256 digest.
+fn keygen_to_js<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    params_bytes: &[u8],
+    circuit: &C,
+) -> Result<JsValue, JsError> {
+    let (pk_bytes, vk_bytes) =
+        keygen_versioned(params, circuit).map_err(|e| JsError::new(&e.to_string()))?;
+    let vk_digest = encode_hex(&vk_fingerprint_bytes(&vk_bytes));
 
-            signature.reverse();
-            let sign_big = BigUint::from_bytes_le(&signature);
-            let signature = RSASignature::new(Value::known(sign_big));
+    let output = Object::new();
+    set_bytes(&output, "params", params_bytes)?;
+    set_bytes(&output, "pk", &pk_bytes)?;
+    set_bytes(&output, "vk", &vk_bytes)?;
+    Reflect::set(
+        &output,
+        &JsValue::from_str("vkDigest"),
+        &JsValue::from_str(&vk_digest),
+    )
+    .map_err(|_| JsError::new("failed to set vkDigest to the output object"))?;
+    Ok(output.into())
+}
 
-            let circuit = $circuit_name::<Fr> {
-                signature,
-                public_key,
-                msg,
-                _f: PhantomData,
-            };
+#[macro_export]
+macro_rules! impl_pkcs1v15_wasm_setup {
+    ($circuit_name:ident, $setup_fn_name:ident, $setup_from_params_fn_name:ident, $k:expr) => {
+        /// Generates params, a proving key, and a verifying key in the browser.
+        ///
+        /// `k` defaults to the degree of the circuit and must not be smaller than it.
+        /// `e` is the big-endian bytes of the fixed exponent, which defaults to 65537 and must be odd.
+        /// The keys are bound to `e`, so the same `e` must be passed to the prove function.
+        /// Returns an object `{ params, pk, vk, vkDigest }` whose first three values are `Uint8Array`s of the serialized artifacts, or an error if `k` is invalid or the peak memory of the key generation estimated by [`estimate_keygen_memory`] cannot be allocated.
+        /// The params are sampled from fresh randomness, so the keys differ per call; use the function with the suffix `_from_params` to reproduce the keys of a verifier.
+        #[wasm_bindgen]
+        pub fn $setup_fn_name(k: Option<u32>, e: Option<Uint8Array>) -> Result<JsValue, JsError> {
+            console_error_panic_hook::set_once();
 
-            let prover = match MockProver::run($k, &circuit, vec![]) {
-                Ok(prover) => prover,
-                Err(e) => panic!("{:#?}", e),
-            };
-            prover.verify().unwrap();
+            let k = k.unwrap_or($k);
+            if k < $k {
+                return Err(JsError::new(&format!(
+                    "k must be at least {}, but {} was given",
+                    $k, k
+                )));
+            }
+            ensure_allocatable(estimate_keygen_memory::<$circuit_name<Fr>>(k))?;
 
-            let proof = {
-                let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
-                create_proof::<KZGCommitmentScheme<_>, ProverGWC<_>, _, _, _, _>(
-                    &params,
-                    &pk,
-                    &[circuit],
-                    &[&[]],
-                    OsRng,
-                    &mut transcript,
-                )
-                .unwrap();
-                transcript.finalize()
-            };
-            serde_wasm_bindgen::to_value(&proof).unwrap()
+            let circuit = $circuit_name::<Fr>::setup_circuit(e)?;
+            let params = ParamsKZG::<Bn256>::setup(k, OsRng);
+            let mut params_bytes = vec![];
+            params
+                .write(&mut params_bytes)
+                .map_err(|e| JsError::new(&format!("failed to serialize params: {}", e)))?;
+            keygen_to_js(&params, &params_bytes, &circuit)
         }
 
+        /// Generates a proving key and a verifying key in the browser for the given params, e.g., those downloaded once from the ceremony, instead of downloading the keys.
+        ///
+        /// The key generation is deterministic, so the same params and `e` always yield byte-identical keys across sessions and machines.
+        /// The returned object `{ params, pk, vk, vkDigest }` holds only `Uint8Array`s and a string, so it can be stored in IndexedDB as it is.
+        /// `vkDigest` can be compared with the digest of the verifying key used by the verifier to detect a stale cache.
+        /// The params may be compressed by [`compress_params`]; the returned params are always uncompressed.
+        /// Like the function without the suffix, it returns an error if the peak memory of the key generation cannot be allocated.
         #[wasm_bindgen]
-        pub fn $verify_fn_name(params: JsValue, vk: JsValue, proof: JsValue) -> bool {
+        pub fn $setup_from_params_fn_name(
+            params: Uint8Array,
+            e: Option<Uint8Array>,
+        ) -> Result<JsValue, JsError> {
             console_error_panic_hook::set_once();
 
-            let params = Uint8Array::new(&params).to_vec();
-            let params = ParamsKZG::<Bn256>::read(&mut BufReader::new(&params[..])).unwrap();
-            let vk: Vec<u8> = Uint8Array::new(&vk).to_vec();
-            let vk = VerifyingKey::<G1Affine>::read::<_, $circuit_name<Fr>>(
-                &mut BufReader::new(&vk[..]),
-                SerdeFormat::RawBytes,
-            )
-            .unwrap();
+            let params_bytes = decompress_params_bytes(&params.to_vec())
+                .map_err(|e| JsError::new(&format!("invalid params: {}", e)))?;
+            let params = read_params(&params_bytes)
+                .map_err(|e| JsError::new(&format!("invalid params: {}", e)))?;
+            if params.k() < $k {
+                return Err(JsError::new(&format!(
+                    "params for k = {} were given, but the circuit requires k >= {}",
+                    params.k(),
+                    $k
+                )));
+            }
+            // The params are already allocated, so only the rest of the peak is probed.
+            ensure_allocatable(
+                estimate_keygen_memory::<$circuit_name<Fr>>(params.k()) - params_bytes(params.k()),
+            )?;
+            let circuit = $circuit_name::<Fr>::setup_circuit(e)?;
+            keygen_to_js(&params, &params_bytes, &circuit)
+        }
 
-            let strategy = SingleStrategy::new(&params);
-            let proof: Vec<u8> = serde_wasm_bindgen::from_value(proof).unwrap();
-            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
-            verify_proof::<_, VerifierGWC<_>, _, _, _>(
-                &params,
-                &vk,
-                strategy,
-                &[&[]],
-                &mut transcript,
-            )
-            .expect("proof invalid");
-            true
+        impl<F: PrimeField> $circuit_name<F> {
+            /// Returns the circuit without witnesses for the exponent `e` given to the setup functions.
+            fn setup_circuit(e: Option<Uint8Array>) -> Result<Self, JsError> {
+                match e {
+                    Some(e) => match RSAPubE::fix_from_be_bytes(&e.to_vec()) {
+                        Some(RSAPubE::Fix(e)) => Ok(Self::without_witness_with_e(e)),
+                        _ => Err(JsError::new("e must be odd")),
+                    },
+                    None => Ok(Self::default()),
+                }
+            }
         }
     };
 }
 
-impl_pkcs1v15_wasm_functions!(
+impl_pkcs1v15_wasm_setup!(
     Pkcs1v15_1024_64EnabledBenchCircuit,
-    prove_pkcs1v15_1024_64_circuit,
-    verify_pkcs1v15_1024_64_circuit,
-    64,
-    13,
-    true
+    setup_pkcs1v15_1024_64,
+    setup_pkcs1v15_1024_64_from_params,
+    19
 );
 
-impl_pkcs1v15_wasm_functions!(
+impl_pkcs1v15_wasm_setup!(
     Pkcs1v15_1024_128EnabledBenchCircuit,
-    prove_pkcs1v15_1024_128_circuit,
-    verify_pkcs1v15_1024_128_circuit,
-    128,
-    13,
-    true
+    setup_pkcs1v15_1024_128,
+    setup_pkcs1v15_1024_128_from_params,
+    19
 );
 
-impl_pkcs1v15_wasm_functions!(
+impl_pkcs1v15_wasm_setup!(
     Pkcs1v15_1024_1024EnabledBenchCircuit,
-    prove_pkcs1v15_1024_1024_circuit,
-    verify_pkcs1v15_1024_1024_circuit,
-    1024,
-    13,
-    true
+    setup_pkcs1v15_1024_1024,
+    setup_pkcs1v15_1024_1024_from_params,
+    18
 );
 
-impl_pkcs1v15_wasm_functions!(
+impl_pkcs1v15_wasm_setup!(
     Pkcs1v15_2048_64EnabledBenchCircuit,
-    prove_pkcs1v15_2048_64_circuit,
-    verify_pkcs1v15_2048_64_circuit,
-    64,
-    13,
-    true
+    setup_pkcs1v15_2048_64,
+    setup_pkcs1v15_2048_64_from_params,
+    19
 );
 
-impl_pkcs1v15_wasm_functions!(
+impl_pkcs1v15_wasm_setup!(
     Pkcs1v15_2048_128EnabledBenchCircuit,
-    prove_pkcs1v15_2048_128_circuit,
-    verify_pkcs1v15_2048_128_circuit,
-    128,
-    13,
-    true
+    setup_pkcs1v15_2048_128,
+    setup_pkcs1v15_2048_128_from_params,
+    19
 );
 
-impl_pkcs1v15_wasm_functions!(
+impl_pkcs1v15_wasm_setup!(
     Pkcs1v15_2048_1024EnabledBenchCircuit,
-    prove_pkcs1v15_2048_1024_circuit,
-    verify_pkcs1v15_2048_1024_circuit,
-    1024,
-    13,
-    true
+    setup_pkcs1v15_2048_1024,
+    setup_pkcs1v15_2048_1024_from_params,
+    19
 );
 
-impl_pkcs1v15_wasm_functions!(
+impl_pkcs1v15_wasm_setup!(
     Pkcs1v15_2048_1024DisabledBenchCircuit,
-    prove_pkcs1v15_no_sha2_2048_1024_circuit,
-    verify_pkcs1v15_no_sha2_2048_1024_circuit,
-    1024,
-    13,
-    false
+    setup_pkcs1v15_no_sha2_2048_1024,
+    setup_pkcs1v15_no_sha2_2048_1024_from_params,
+    19
+);
+
+impl_pkcs1v15_wasm_setup!(
+    Pkcs1v15_1024_64WasmNoSha2Circuit,
+    setup_pkcs1v15_no_sha2_1024,
+    setup_pkcs1v15_no_sha2_1024_from_params,
+    15
 );
 
 #[macro_export]
@@ -354,22 +3297,16 @@ macro_rules! impl_pkcs1v15_wasm_multi_exec_bench {
             times: usize,
         ) -> Array {
             let params = Uint8Array::new(&params).to_vec();
-            let params = ParamsKZG::<Bn256>::read(&mut BufReader::new(&params[..])).unwrap();
+            let params = read_params(&params).unwrap();
             let pk = Uint8Array::new(&pk).to_vec();
-            let pk = ProvingKey::<G1Affine>::read::<_, $circuit_name<Fr>>(
-                &mut BufReader::new(&pk[..]),
-                SerdeFormat::RawBytes,
-            )
-            .unwrap();
+            let pk = read_pk_checked::<$circuit_name<Fr>>(&pk).unwrap_or_else(|e| panic!("{}", e));
+            check_params_k(&params, pk.get_vk(), $circuit_name::<Fr>::min_k())
+                .unwrap_or_else(|e| panic!("{}", e));
             let vk = Uint8Array::new(&vk).to_vec();
-            let vk = VerifyingKey::<G1Affine>::read::<_, $circuit_name<Fr>>(
-                &mut BufReader::new(&vk[..]),
-                SerdeFormat::RawBytes,
-            )
-            .unwrap();
+            let vk = read_vk_checked::<$circuit_name<Fr>>(&vk).unwrap_or_else(|e| panic!("{}", e));
             let public_key: RsaPublicKey = serde_wasm_bindgen::from_value(public_key).unwrap();
             let msg: Vec<u8> = Uint8Array::new(&msg).to_vec();
-            let mut signature: Vec<u8> = serde_wasm_bindgen::from_value(signature).unwrap();
+            let mut signature = bytes_from_js(&signature).unwrap();
             signature.reverse();
 
             let (sum, square_sum) = (0..times)
@@ -453,6 +3390,1688 @@ macro_rules! impl_pkcs1v15_wasm_multi_exec_bench {
 
 impl_pkcs1v15_wasm_multi_exec_bench!(
     Pkcs1v15_2048_1024EnabledBenchCircuit,
-    13,
+    19,
     multi_bench_2048_1024_circuit
 );
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::big_uint::decompose_biguint;
+    use crate::{create_proof_with_seed, recommend_k_with_advice};
+    use halo2_base::halo2_proofs::plonk::Advice;
+    use rsa::pkcs8::EncodePublicKey;
+    use std::rc::Rc;
+    use wasm_bindgen_futures::JsFuture;
+    use wasm_bindgen_test::*;
+
+    // The Node.js build runs the tests under `wasm-pack test --node`.
+    #[cfg(not(feature = "nodejs"))]
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn test_single_threaded_fallback() {
+        // The test runner serves the page without the COOP/COEP headers.
+        assert!(!is_multithreading_available());
+        JsFuture::from(init_thread_pool_or_fallback(2))
+            .await
+            .unwrap();
+        let proof = JsFuture::from(prove_on_pool(|| Ok(vec![1, 2, 3]), None))
+            .await
+            .unwrap();
+        assert_eq!(Uint8Array::new(&proof).to_vec(), vec![1, 2, 3]);
+        let sum: u64 = (0..1000u64).into_par_iter().sum();
+        assert_eq!(sum, 499500);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sha256_hasher_byte_chunks() {
+        let mut msg = vec![0u8; 1000];
+        thread_rng().fill(&mut msg[..]);
+        let mut hasher = Sha256Hasher::new();
+        for byte in msg.iter() {
+            hasher.update(Uint8Array::from(&[*byte][..]));
+        }
+        let digest = hasher.finalize().to_vec();
+        let one_shot: Vec<u8> =
+            serde_wasm_bindgen::from_value(sha256_msg(Uint8Array::from(&msg[..]).into())).unwrap();
+        assert_eq!(digest, one_shot);
+        assert_eq!(
+            msg_from_js(&Uint8Array::from(&digest[..]).into(), false, 1024),
+            digest
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_msg_from_js_chunks() {
+        let chunks = Array::new();
+        chunks.push(&Uint8Array::from(&b"hello "[..]));
+        chunks.push(&Uint8Array::from(&b"world"[..]));
+        assert_eq!(
+            msg_from_js(&chunks.into(), true, 64),
+            b"hello world".to_vec()
+        );
+    }
+
+    #[wasm_bindgen_test]
+    #[should_panic]
+    fn test_msg_from_js_too_long() {
+        let chunks = Array::new();
+        chunks.push(&Uint8Array::from(&[0u8; 40][..]));
+        chunks.push(&Uint8Array::from(&[0u8; 40][..]));
+        msg_from_js(&chunks.into(), true, 64);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_uint8array_writer_grows() {
+        let mut bytes = vec![0u8; 1000];
+        thread_rng().fill(&mut bytes[..]);
+        let mut writer = Uint8ArrayWriter::with_capacity(16);
+        for chunk in bytes.chunks(32) {
+            writer.write_all(chunk).unwrap();
+        }
+        let array = writer.into_array();
+        assert_eq!(array.length(), 1000);
+        assert_eq!(array.to_vec(), bytes);
+    }
+
+    const FIXTURE_PEM: &str = "-----BEGIN PUBLIC KEY-----
+MIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQKBgQCwf+SdPyvzbTqa+/yt2nLZtR1/
+aZCz3ypXByiHyOZibjRU1QtqHgOZ4JLf+klhvT4C6wgB2jpEia0SAPao9GVBAOH1
+BU8IpviPI53pS9VGtjIIaRdkjqpg1CF10LXkGnkKAhrRkuEcO6/Chr7jiFXzTGRI
+pQ5YXPUOrTzJH7CFuwIDAQAB
+-----END PUBLIC KEY-----";
+    const FIXTURE_MODULUS_HEX: &str = "b07fe49d3f2bf36d3a9afbfcadda72d9b51d7f6990b3df2a57072887c8e6626e3454d50b6a1e0399e092dffa4961bd3e02eb0801da3a4489ad1200f6a8f4654100e1f5054f08a6f88f239de94bd546b632086917648eaa60d42175d0b5e41a790a021ad192e11c3bafc286bee38855f34c6448a50e585cf50ead3cc91fb085bb";
+
+    #[wasm_bindgen_test]
+    fn test_public_key_helpers_with_pem_fixture() {
+        let public_key = RsaPublicKey::from_public_key_pem(FIXTURE_PEM).unwrap();
+        let public_key = serde_wasm_bindgen::to_value(&public_key).unwrap();
+        assert_eq!(
+            String::from(public_key_modulus_hex(public_key.clone()).unwrap()),
+            FIXTURE_MODULUS_HEX
+        );
+        assert_eq!(public_key_exponent(public_key.clone()).unwrap(), 65537);
+        assert_eq!(
+            String::from(public_key_commitment(public_key.clone()).unwrap()),
+            "0fa8548883bc88899060fcca016f86c85fef960526ba777b14d341dadacf12b1"
+        );
+
+        let instances = public_key_instances(public_key).unwrap();
+        assert_eq!(instances.length(), 16);
+        assert_eq!(
+            instances.get(0).as_string().unwrap(),
+            format!("0x{:0>64}", "0ead3cc91fb085bb")
+        );
+        assert_eq!(
+            instances.get(15).as_string().unwrap(),
+            format!("0x{:0>64}", "b07fe49d3f2bf36d")
+        );
+        // The limbs are the same values as the instances of the circuits.
+        let n = BigUint::parse_bytes(FIXTURE_MODULUS_HEX.as_bytes(), 16).unwrap();
+        let n_fes = decompose_biguint::<Fr>(&n, 16, 64);
+        for (i, fe) in n_fes.iter().enumerate() {
+            let hex = fe_to_biguint(fe).to_str_radix(16);
+            assert_eq!(
+                instances.get(i as u32).as_string().unwrap(),
+                format!("0x{:0>64}", hex)
+            );
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_import_public_key_jwk() {
+        let n = BigUint::parse_bytes(FIXTURE_MODULUS_HEX.as_bytes(), 16).unwrap();
+        let jwk = format!(
+            r#"{{"kty":"RSA","alg":"RS256","use":"sig","kid":"fixture","n":"{}","e":"AQAB"}}"#,
+            base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(n.to_bytes_be())
+        );
+        let public_key = import_public_key_jwk(&jwk).unwrap();
+        assert_eq!(
+            String::from(public_key_modulus_hex(public_key.clone()).unwrap()),
+            FIXTURE_MODULUS_HEX
+        );
+        assert_eq!(public_key_exponent(public_key).unwrap(), 65537);
+        assert!(import_public_key_jwk(r#"{"kty":"RSA","n":"AQAB"}"#).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_proof_bytes_roundtrip() {
+        let mut proof = vec![0u8; 1 << 16];
+        thread_rng().fill(&mut proof[..]);
+        let typed = JsValue::from(Uint8Array::from(&proof[..]));
+        assert_eq!(bytes_from_js(&typed).unwrap(), proof);
+        let legacy = serde_wasm_bindgen::to_value(&proof).unwrap();
+        assert_eq!(bytes_from_js(&legacy).unwrap(), proof);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_real_proof_survives_bytes_from_js() {
+        let artifacts = setup_pkcs1v15_1024_64(None, None).unwrap();
+        let get = |key: &str| Reflect::get(&artifacts, &JsValue::from_str(key)).unwrap();
+        let private_key = sample_rsa_private_key(1024);
+        let public_key = generate_rsa_public_key(private_key.clone());
+        let msg = Uint8Array::from(&b"hello world"[..]);
+        let signature = sign(private_key, msg.clone().into());
+        let proof = prove_pkcs1v15_1024_64_circuit(
+            get("params"),
+            get("pk"),
+            public_key,
+            msg.into(),
+            signature.into(),
+            None,
+            None,
+        )
+        .to_vec();
+
+        // Both the `Uint8Array` and the legacy array of numbers are read back to the same bytes, which still verify.
+        let typed = JsValue::from(Uint8Array::from(&proof[..]));
+        let legacy = serde_wasm_bindgen::to_value(&proof).unwrap();
+        for value in [typed, legacy] {
+            assert_eq!(bytes_from_js(&value).unwrap(), proof);
+            assert!(verify_pkcs1v15_1024_64_circuit(
+                get("params"),
+                get("vk"),
+                value
+            ));
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_proof_hex_and_base64_roundtrip() {
+        let mut proof = vec![0u8; 32 * 40];
+        thread_rng().fill(&mut proof[..]);
+        let hex = String::from(proof_to_hex(Uint8Array::from(&proof[..]).into()).unwrap());
+        assert_eq!(hex, hex.to_lowercase());
+        for hex in [hex.clone(), hex.to_uppercase(), format!("0x{}", hex)] {
+            assert_eq!(proof_from_hex(&hex).unwrap().to_vec(), proof);
+        }
+        assert!(proof_from_hex(&hex[..hex.len() - 2]).is_err());
+        assert!(proof_from_hex(&hex[1..]).is_err());
+        assert!(proof_from_hex(&format!("{}\n", hex)).is_err());
+
+        // 34 bytes are padded with `=` only in the standard encoding.
+        let bytes = Uint8Array::from(&proof[..34]);
+        for url_safe in [None, Some(false), Some(true)] {
+            let encoded = String::from(bytes_to_base64(bytes.clone(), url_safe));
+            assert_eq!(url_safe != Some(true), encoded.ends_with('='));
+            let decoded = bytes_from_base64(&encoded, Some(34)).unwrap();
+            assert_eq!(decoded.to_vec(), bytes.to_vec());
+            assert!(bytes_from_base64(&encoded, Some(32)).is_err());
+        }
+        let url_safe = String::from(bytes_to_base64(
+            Uint8Array::from(&[0xfb, 0xff][..]),
+            Some(true),
+        ));
+        assert_eq!(url_safe, "-_8");
+        assert_eq!(
+            bytes_from_base64(&url_safe, None).unwrap().to_vec(),
+            vec![0xfb, 0xff]
+        );
+        assert!(bytes_from_base64("+_8=", None).is_err());
+        assert!(bytes_from_base64("AQ AB", None).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sign_roundtrip() {
+        let private_key = sample_rsa_private_key(1024);
+        let public_key: RsaPublicKey =
+            serde_wasm_bindgen::from_value(generate_rsa_public_key(private_key.clone())).unwrap();
+        let msg = Uint8Array::from(&b"hello world"[..]);
+        let signature = sign(private_key, msg.into());
+        let signature = bytes_from_js(&signature.into()).unwrap();
+        let hashed_msg = Sha256::digest(b"hello world");
+        public_key
+            .verify(Pkcs1v15Sign::new::<Sha256>(), &hashed_msg, &signature)
+            .unwrap();
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sign_with_hash_e3_sha512() {
+        let private_key = sample_rsa_private_key_with_exp(1024, 3).unwrap();
+        let public_key: RsaPublicKey =
+            serde_wasm_bindgen::from_value(generate_rsa_public_key(private_key.clone())).unwrap();
+        assert_eq!(public_key.e(), &rsa::BigUint::from(3u32));
+        let msg = b"hello world";
+        let signature = sign_with_hash(private_key, Uint8Array::from(&msg[..]), "sha512")
+            .unwrap()
+            .to_vec();
+        public_key
+            .verify(
+                Pkcs1v15Sign::new::<Sha512>(),
+                &Sha512::digest(msg),
+                &signature,
+            )
+            .unwrap();
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sample_rsa_private_key_with_e3() {
+        let private_key = sample_rsa_private_key_with_e(1024, 3).unwrap();
+        let private_key: RsaPrivateKey = serde_wasm_bindgen::from_value(private_key).unwrap();
+        assert_eq!(private_key.e(), &rsa::BigUint::from(3u32));
+        assert_eq!(private_key.n().bits(), 1024);
+        private_key.validate().unwrap();
+        assert!(sample_rsa_private_key_with_e(1024, 4).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sample_rsa_private_key_with_invalid_exp() {
+        assert!(sample_rsa_private_key_with_exp(1024, 1).is_err());
+        assert!(sample_rsa_private_key_with_exp(1024, 65536).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sign_with_unsupported_hash() {
+        let private_key = sample_rsa_private_key(1024);
+        let msg = Uint8Array::from(&b"hello world"[..]);
+        assert!(sign_with_hash(private_key, msg, "md5").is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sign_raw_digest() {
+        let private_key = sample_rsa_private_key(1024);
+        let public_key = generate_rsa_public_key(private_key.clone());
+        let msg = b"hello world";
+        let digest = Uint8Array::from(&Sha256::digest(msg)[..]);
+        let signature = sign_raw_digest(private_key.clone(), digest.clone()).unwrap();
+        // The same signature as signing the message itself.
+        let expected = sign(private_key, Uint8Array::from(&msg[..]).into());
+        assert_eq!(signature.to_vec(), expected.to_vec());
+        assert!(
+            verify_raw_digest_native(public_key.clone(), digest.clone(), signature.into()).unwrap()
+        );
+        let mut tampered = digest.to_vec();
+        tampered[0] ^= 1;
+        assert!(!verify_raw_digest_native(
+            public_key,
+            Uint8Array::from(&tampered[..]),
+            expected.into()
+        )
+        .unwrap());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sign_raw_digest_wrong_length() {
+        let private_key = sample_rsa_private_key(1024);
+        let digest = Uint8Array::from(&[0u8; 31][..]);
+        assert!(sign_raw_digest(private_key, digest).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_check_signature_native_without_leading_zero_byte() {
+        let private_key = sample_rsa_private_key(1024);
+        let public_key = generate_rsa_public_key(private_key.clone());
+        // About one in 256 signatures starts with a zero byte.
+        let (msg, signature) = (0u32..)
+            .map(|i| {
+                let msg = i.to_be_bytes().to_vec();
+                let signature = sign(private_key.clone(), Uint8Array::from(&msg[..]).into());
+                (msg, signature.to_vec())
+            })
+            .find(|(_, signature)| signature[0] == 0)
+            .unwrap();
+        let check = |signature: &[u8]| {
+            check_signature_native(
+                public_key.clone(),
+                Uint8Array::from(&msg[..]),
+                Uint8Array::from(signature).into(),
+            )
+            .unwrap()
+        };
+        assert!(check(&signature));
+        assert!(check(&signature[1..]));
+        let mut too_long = vec![0];
+        too_long.extend_from_slice(&signature);
+        assert!(!check(&too_long));
+    }
+
+    fn prove_input_error(input: &Object) -> String {
+        let input = JsValue::from(input.clone()).unchecked_into::<ProvePkcs1v15Input>();
+        let err = JsValue::from(prove_pkcs1v15(input).unwrap_err());
+        Reflect::get(&err, &JsValue::from_str("message"))
+            .unwrap()
+            .as_string()
+            .unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_prove_pkcs1v15_input_errors() {
+        let input = Object::new();
+        let set = |key: &str, value: JsValue| {
+            Reflect::set(&input, &JsValue::from_str(key), &value).unwrap();
+        };
+        set("circuit", JsValue::from_str("1024_64"));
+        set("params", Uint8Array::new_with_length(0).into());
+        set("provingKey", Uint8Array::new_with_length(0).into());
+        set("msg", Uint8Array::from(&b"hello world"[..]).into());
+        assert!(prove_input_error(&input).contains("publicKeyPem"));
+
+        let private_key: RsaPrivateKey =
+            serde_wasm_bindgen::from_value(sample_rsa_private_key(1024)).unwrap();
+        let pem = RsaPublicKey::from(&private_key)
+            .to_public_key_pem(rsa::pkcs8::LineEnding::LF)
+            .unwrap();
+        set("publicKeyPem", JsValue::from_str(&pem));
+        set("signatureBase64", JsValue::from_str("not base64!"));
+        assert!(prove_input_error(&input).contains("invalid signatureBase64"));
+
+        set("circuit", JsValue::from_str("4096_64"));
+        assert!(prove_input_error(&input).starts_with("unknown circuit \"4096_64\""));
+
+        set("circuit", JsValue::from_str("2048_64"));
+        assert!(prove_input_error(&input).contains("2048 bits key"));
+    }
+
+    fn js_error_message(err: JsError) -> String {
+        Reflect::get(&JsValue::from(err), &JsValue::from_str("message"))
+            .unwrap()
+            .as_string()
+            .unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_prove_fails_early_for_flipped_message() {
+        let private_key = sample_rsa_private_key(1024);
+        let public_key = generate_rsa_public_key(private_key.clone());
+        let msg = b"hello world".to_vec();
+        let signature = sign(private_key.clone(), Uint8Array::from(&msg[..]).into());
+        let mut flipped = msg.clone();
+        flipped[0] ^= 1;
+        assert!(check_signature_native(
+            public_key.clone(),
+            Uint8Array::from(&msg[..]),
+            signature.clone().into()
+        )
+        .unwrap());
+        assert!(!check_signature_native(
+            public_key.clone(),
+            Uint8Array::from(&flipped[..]),
+            signature.clone().into()
+        )
+        .unwrap());
+
+        // The params and the proving key are empty, so the prove functions fail before reading them.
+        let empty = || JsValue::from(Uint8Array::new_with_length(0));
+        let prove = |msg: &[u8], e: Option<Uint8Array>| {
+            prove_pkcs1v15_1024_64_circuit_seeded(
+                empty(),
+                empty(),
+                public_key.clone(),
+                Uint8Array::from(msg).into(),
+                signature.clone().into(),
+                e,
+                Uint8Array::from(&[0u8; 32][..]),
+            )
+            .map_err(js_error_message)
+        };
+        assert!(prove(&flipped, None)
+            .unwrap_err()
+            .contains("the signature does not verify for the message"));
+        assert!(prove(&msg, Some(Uint8Array::from(&[3u8][..])))
+            .unwrap_err()
+            .contains("the public key has the exponent 65537, but the circuit uses 3"));
+        let rejected = JsFuture::from(prove_pkcs1v15_1024_64_circuit_async(
+            empty(),
+            empty(),
+            public_key.clone(),
+            Uint8Array::from(&flipped[..]).into(),
+            signature.clone().into(),
+            None,
+            None,
+        ))
+        .await;
+        assert!(rejected.is_err());
+
+        let private_key: RsaPrivateKey = serde_wasm_bindgen::from_value(private_key).unwrap();
+        let pem = RsaPublicKey::from(&private_key)
+            .to_public_key_pem(rsa::pkcs8::LineEnding::LF)
+            .unwrap();
+        let input = Object::new();
+        for (key, value) in [
+            ("circuit", JsValue::from_str("1024_64")),
+            ("params", empty()),
+            ("provingKey", empty()),
+            ("publicKeyPem", JsValue::from_str(&pem)),
+            ("msg", Uint8Array::from(&flipped[..]).into()),
+            (
+                "signatureBase64",
+                JsValue::from_str(
+                    &base64::engine::general_purpose::STANDARD.encode(signature.to_vec()),
+                ),
+            ),
+        ] {
+            Reflect::set(&input, &JsValue::from_str(key), &value).unwrap();
+        }
+        assert!(prove_input_error(&input).contains("the signature does not verify"));
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_prove_async_verifies() {
+        JsFuture::from(init_thread_pool_or_fallback(2))
+            .await
+            .unwrap();
+        let artifacts = setup_pkcs1v15_1024_64(None, None).unwrap();
+        let get = |key: &str| Reflect::get(&artifacts, &JsValue::from_str(key)).unwrap();
+        let private_key = sample_rsa_private_key(1024);
+        let public_key = generate_rsa_public_key(private_key.clone());
+        let msg = Uint8Array::from(&b"hello world"[..]);
+        let signature = sign(private_key, msg.clone().into());
+        let proof = JsFuture::from(prove_pkcs1v15_1024_64_circuit_async(
+            get("params"),
+            get("pk"),
+            public_key,
+            msg.into(),
+            signature.into(),
+            None,
+            None,
+        ))
+        .await
+        .unwrap();
+        assert!(verify_pkcs1v15_1024_64_circuit(
+            get("params"),
+            get("vk"),
+            proof
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_prove_after_shutdown_thread_pool() {
+        JsFuture::from(init_thread_pool_with_size(2)).await.unwrap();
+        // The test runner serves the page without the COOP/COEP headers, so the pool falls back to a single thread.
+        let expected_threads = if is_multithreading_available() { 2 } else { 1 };
+        assert_eq!(proving_threads(), expected_threads);
+        if is_multithreading_available() {
+            let resized = JsFuture::from(init_thread_pool_with_size(expected_threads + 1)).await;
+            assert!(resized.is_err());
+        }
+
+        let artifacts = setup_pkcs1v15_1024_64(None, None).unwrap();
+        let get = |key: &str| Reflect::get(&artifacts, &JsValue::from_str(key)).unwrap();
+        let private_key = sample_rsa_private_key(1024);
+        let public_key = generate_rsa_public_key(private_key.clone());
+        let msg = Uint8Array::from(&b"hello world"[..]);
+        let signature = sign(private_key, msg.clone().into());
+        let prove = || {
+            JsFuture::from(prove_pkcs1v15_1024_64_circuit_async(
+                get("params"),
+                get("pk"),
+                public_key.clone(),
+                msg.clone().into(),
+                signature.clone().into(),
+                None,
+                None,
+            ))
+        };
+        let proof = prove().await.unwrap();
+        assert!(verify_pkcs1v15_1024_64_circuit(
+            get("params"),
+            get("vk"),
+            proof
+        ));
+
+        shutdown_thread_pool();
+        assert_eq!(proving_threads(), 1);
+        let proof = prove().await.unwrap();
+        assert!(verify_pkcs1v15_1024_64_circuit(
+            get("params"),
+            get("vk"),
+            proof
+        ));
+
+        JsFuture::from(init_thread_pool_with_size(expected_threads))
+            .await
+            .unwrap();
+        assert_eq!(proving_threads(), expected_threads);
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_prove_cancellable_stops_early() {
+        let artifacts = setup_pkcs1v15_1024_64(None, None).unwrap();
+        let get = |key: &str| Reflect::get(&artifacts, &JsValue::from_str(key)).unwrap();
+        let private_key = sample_rsa_private_key(1024);
+        let public_key = generate_rsa_public_key(private_key.clone());
+        let msg = Uint8Array::from(&b"hello world"[..]);
+        let signature = sign(private_key, msg.clone().into());
+        let prove_cancellable = |token: &CancellationToken| {
+            JsFuture::from(prove_pkcs1v15_1024_64_circuit_cancellable(
+                token,
+                get("params"),
+                get("pk"),
+                public_key.clone(),
+                msg.clone().into(),
+                signature.clone().into(),
+                None,
+            ))
+        };
+        let rejected_message = |result: Result<JsValue, JsValue>| {
+            Reflect::get(&result.unwrap_err(), &JsValue::from_str("message"))
+                .unwrap()
+                .as_string()
+                .unwrap()
+        };
+
+        let start = js_sys::Date::now();
+        prove_pkcs1v15_1024_64_circuit(
+            get("params"),
+            get("pk"),
+            public_key.clone(),
+            msg.clone().into(),
+            signature.clone().into(),
+            None,
+            None,
+        )
+        .unwrap();
+        let full_time = js_sys::Date::now() - start;
+
+        // A token cancelled before proving rejects without proving.
+        let token = CancellationToken::new();
+        token.cancel();
+        let start = js_sys::Date::now();
+        let result = prove_cancellable(&token).await;
+        assert_eq!(rejected_message(result), CANCELLED_MESSAGE);
+        assert!(js_sys::Date::now() - start < full_time / 2.0);
+
+        // A token cancelled while proving stops the proof at the next check.
+        let token = CancellationToken::new();
+        token.cancel_after(full_time / 4.0);
+        let start = js_sys::Date::now();
+        let result = prove_cancellable(&token).await;
+        assert_eq!(rejected_message(result), CANCELLED_MESSAGE);
+        assert!(token.cancelled());
+        assert!(js_sys::Date::now() - start < full_time);
+
+        // A proving key that cannot be read rejects the promise instead of panicking.
+        let result = JsFuture::from(prove_pkcs1v15_1024_64_circuit_cancellable(
+            &CancellationToken::new(),
+            get("params"),
+            Uint8Array::from(&[0u8; 16][..]).into(),
+            public_key.clone(),
+            msg.clone().into(),
+            signature.clone().into(),
+            None,
+        ))
+        .await;
+        assert!(result.is_err());
+
+        // A token that is never cancelled gives a valid proof.
+        let proof = prove_cancellable(&CancellationToken::new()).await.unwrap();
+        assert!(verify_pkcs1v15_1024_64_circuit(
+            get("params"),
+            get("vk"),
+            proof
+        ));
+    }
+
+    /// A circuit assigning `num_regions` regions of one cell, which cancels `token` in the first one and records the number of the regions it entered.
+    #[derive(Clone)]
+    struct CancelInRegionCircuit {
+        token: CancellationToken,
+        num_regions: usize,
+        entered: Rc<std::cell::Cell<usize>>,
+    }
+
+    impl Circuit<Fr> for CancelInRegionCircuit {
+        type Config = Column<Advice>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            meta.advice_column()
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            for i in 0..self.num_regions {
+                layouter.assign_region(
+                    || "cell",
+                    |mut region| {
+                        self.token.cancel();
+                        self.entered.set(i + 1);
+                        region.assign_advice(
+                            || "cell",
+                            config,
+                            0,
+                            || Value::known(Fr::from(i as u64)),
+                        )?;
+                        Ok(())
+                    },
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_cancellable_circuit_checks_between_regions() {
+        let token = CancellationToken::new();
+        let entered = Rc::new(std::cell::Cell::new(0));
+        let circuit = CancellableCircuit {
+            circuit: CancelInRegionCircuit {
+                token: token.clone(),
+                num_regions: 3 * CANCELLATION_CHECK_REGIONS,
+                entered: entered.clone(),
+            },
+            token,
+        };
+        assert!(MockProver::run(6, &circuit, vec![]).is_err());
+        // The synthesis stops at the first check after the token is cancelled.
+        assert_eq!(entered.get(), CANCELLATION_CHECK_REGIONS);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_prove_twice_with_handles() {
+        let artifacts = setup_pkcs1v15_1024_64(None, None).unwrap();
+        let get = |key: &str| {
+            Uint8Array::new(&Reflect::get(&artifacts, &JsValue::from_str(key)).unwrap())
+        };
+        let params_handle = load_params(get("params")).unwrap();
+        let pk_handle = load_proving_key("1024_64", get("pk"), None, None).unwrap();
+        assert!(load_proving_key("4096_64", get("pk"), None, None).is_err());
+
+        let private_key = sample_rsa_private_key(1024);
+        let public_key = generate_rsa_public_key(private_key.clone());
+        for msg in [&b"hello world"[..], &b"hello again"[..]] {
+            let signature = sign(private_key.clone(), Uint8Array::from(msg).into());
+            let proof = prove_pkcs1v15_1024_64_circuit_with_handles(
+                params_handle,
+                pk_handle,
+                public_key.clone(),
+                Uint8Array::from(msg).into(),
+                signature.into(),
+                None,
+            )
+            .unwrap();
+            assert!(verify_pkcs1v15_1024_64_circuit(
+                get("params").into(),
+                get("vk").into(),
+                proof.into()
+            ));
+        }
+
+        // The params handle does not refer to a proving key.
+        let msg = Uint8Array::from(&b"hello world"[..]);
+        let signature = sign(private_key, msg.clone().into());
+        assert!(prove_pkcs1v15_1024_64_circuit_with_handles(
+            params_handle,
+            params_handle,
+            public_key,
+            msg.into(),
+            signature.into(),
+            None,
+        )
+        .is_err());
+        assert!(free_handle(params_handle));
+        assert!(free_handle(pk_handle));
+        assert!(!free_handle(pk_handle));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_params_max_k() {
+        let artifacts = setup_pkcs1v15_1024_64(None, None).unwrap();
+        let params =
+            Uint8Array::new(&Reflect::get(&artifacts, &JsValue::from_str("params")).unwrap());
+        let k = read_params(&params.to_vec()).unwrap().k();
+        assert_eq!(params_max_k(params.clone()).unwrap(), k);
+        let compressed = compress_params(params.clone()).unwrap();
+        assert_eq!(params_max_k(compressed).unwrap(), k);
+        let truncated = params.subarray(0, params.length() - 1);
+        assert!(params_max_k(truncated).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_extract_verifying_key() {
+        let artifacts = setup_pkcs1v15_1024_64(None, None).unwrap();
+        let get = |key: &str| {
+            Uint8Array::new(&Reflect::get(&artifacts, &JsValue::from_str(key)).unwrap())
+        };
+        let vk = extract_verifying_key("1024_64", get("pk")).unwrap();
+        assert_eq!(vk.to_vec(), get("vk").to_vec());
+        assert!(extract_verifying_key("1024_128", get("pk")).is_err());
+        assert!(extract_verifying_key("4096_64", get("pk")).is_err());
+
+        let private_key = sample_rsa_private_key(1024);
+        let public_key = generate_rsa_public_key(private_key.clone());
+        let msg = Uint8Array::from(&b"hello world"[..]);
+        let signature = sign(private_key, msg.clone().into());
+        let proof = prove_pkcs1v15_1024_64_circuit(
+            get("params").into(),
+            get("pk").into(),
+            public_key,
+            msg.into(),
+            signature.into(),
+            None,
+            None,
+        );
+        assert!(verify_pkcs1v15_1024_64_circuit(
+            get("params").into(),
+            vk.into(),
+            proof.into()
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_no_sha2_1024_circuit_k() {
+        let k = Pkcs1v15_1024_64WasmNoSha2Circuit::<Fr>::min_k();
+        assert_eq!(k, 15);
+        let private_key =
+            RsaPrivateKey::new(&mut thread_rng(), 1024).expect("failed to generate a key");
+        let digest = Sha256::digest(b"hashed off-circuit").to_vec();
+        let sign = private_key
+            .sign(Pkcs1v15Sign::new::<Sha256>(), &digest)
+            .unwrap();
+        let n = BigUint::from_bytes_be(&private_key.n().to_bytes_be());
+        let circuit = Pkcs1v15_1024_64WasmNoSha2Circuit::<Fr> {
+            signature: RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+            public_key: RSAPublicKey::new(Value::known(n), RSAPubE::Fix(BigUint::from(65537u32))),
+            msg: digest.clone(),
+            _f: PhantomData,
+        };
+        let instances = Pkcs1v15_1024_64WasmNoSha2Circuit::<Fr>::instances(&digest);
+        assert_eq!(
+            instances[0].len(),
+            Pkcs1v15_1024_64WasmNoSha2Circuit::<Fr>::INFO.num_instances
+        );
+        let prover = MockProver::run(k, &circuit, instances).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // The digest is bound to the public instances.
+        let other = Sha256::digest(b"another message").to_vec();
+        let instances = Pkcs1v15_1024_64WasmNoSha2Circuit::<Fr>::instances(&other);
+        let prover = MockProver::run(k, &circuit, instances).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_prove_no_sha2_1024_with_public_digest() {
+        let artifacts = setup_pkcs1v15_no_sha2_1024(None, None).unwrap();
+        let get = |key: &str| {
+            Uint8Array::new(&Reflect::get(&artifacts, &JsValue::from_str(key)).unwrap())
+        };
+        let private_key = sample_rsa_private_key(1024);
+        let public_key = generate_rsa_public_key(private_key.clone());
+        let digest = Uint8Array::from(&Sha256::digest(b"hello world")[..]);
+        let signature = sign_raw_digest(private_key, digest.clone()).unwrap();
+        let proof = prove_pkcs1v15_no_sha2_1024_circuit(
+            get("params").into(),
+            get("pk").into(),
+            public_key.clone(),
+            digest.clone(),
+            signature.clone().into(),
+            None,
+        )
+        .unwrap();
+        assert!(verify_pkcs1v15_no_sha2_1024_circuit(
+            get("params").into(),
+            get("vk").into(),
+            proof.clone().into(),
+            digest.clone(),
+        ));
+
+        let verify_detailed = |digest: &[u8]| {
+            verify_pkcs1v15_no_sha2_1024_circuit_detailed(
+                get("params").into(),
+                get("vk").into(),
+                proof.clone().into(),
+                Uint8Array::from(digest),
+                None,
+                None,
+            )
+        };
+        let code = |result: &Object| {
+            Reflect::get(result, &JsValue::from_str("code"))
+                .unwrap()
+                .as_string()
+        };
+        // The proof is invalid for another digest.
+        let result = verify_detailed(&Sha256::digest(b"hello again"));
+        assert_eq!(code(&result), Some("constraint_check_failed".to_string()));
+        let result = verify_detailed(&[0u8; 31]);
+        assert_eq!(code(&result), Some("invalid_instances".to_string()));
+
+        // A digest of another message does not pass the preflight check.
+        let other = Uint8Array::from(&Sha256::digest(b"hello again")[..]);
+        assert!(prove_pkcs1v15_no_sha2_1024_circuit(
+            get("params").into(),
+            get("pk").into(),
+            public_key,
+            other,
+            signature.into(),
+            None,
+        )
+        .is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_verify_batch_with_js_instances() {
+        type NoSha2Circuit = Pkcs1v15_1024_64WasmNoSha2Circuit<Fr>;
+        let artifacts = setup_pkcs1v15_no_sha2_1024(None, None).unwrap();
+        let get = |key: &str| {
+            Uint8Array::new(&Reflect::get(&artifacts, &JsValue::from_str(key)).unwrap()).to_vec()
+        };
+        let private_key = sample_rsa_private_key(1024);
+        let public_key = generate_rsa_public_key(private_key.clone());
+        let digest = Sha256::digest(b"hello world").to_vec();
+        let signature = sign_raw_digest(private_key, Uint8Array::from(&digest[..])).unwrap();
+        let proof = prove_pkcs1v15_no_sha2_1024_circuit(
+            Uint8Array::from(&get("params")[..]).into(),
+            Uint8Array::from(&get("pk")[..]).into(),
+            public_key,
+            Uint8Array::from(&digest[..]),
+            signature.into(),
+            None,
+        )
+        .unwrap();
+
+        let to_js = |digest: &[u8], format: fn(BigUint) -> String| -> Array {
+            NoSha2Circuit::instances(digest)[0]
+                .iter()
+                .map(|value| JsValue::from_str(&format(fe_to_biguint(value))))
+                .collect()
+        };
+        let hex = |value: BigUint| format!("0x{:064x}", value);
+        let decimal = |value: BigUint| value.to_string();
+        // Returns whether the proof is valid for `instances`, or none if they are rejected before verifying.
+        let verify = |instances: Option<Array>| {
+            let instances = instances_from_js(instances, NoSha2Circuit::INFO.num_instances).ok()?;
+            let results = verify_batch::<NoSha2Circuit>(
+                &get("params"),
+                &get("vk"),
+                KeyFormat::RawBytes,
+                NoSha2Circuit::min_k(),
+                &Array::of1(&proof),
+                &[instances],
+            )
+            .ok()?;
+            results.get(0).as_bool()
+        };
+        assert_eq!(verify(Some(to_js(&digest, hex))), Some(true));
+        assert_eq!(verify(Some(to_js(&digest, decimal))), Some(true));
+        assert_eq!(verify(Some(Array::of1(&to_js(&digest, hex)))), Some(true));
+
+        // The proof does not verify for the instances of another digest.
+        let other = Sha256::digest(b"hello again").to_vec();
+        assert_eq!(verify(Some(to_js(&other, hex))), Some(false));
+        let mut tampered = digest.clone();
+        tampered[31] ^= 1;
+        assert_eq!(verify(Some(to_js(&tampered, decimal))), Some(false));
+
+        // The instances must be as many strings of field elements as the circuit exposes.
+        assert_eq!(verify(None), None);
+        let truncated = to_js(&digest, hex);
+        truncated.pop();
+        assert_eq!(verify(Some(truncated)), None);
+        let numbers = to_js(&digest, hex);
+        numbers.set(0, JsValue::from_f64(1.0));
+        assert_eq!(verify(Some(numbers)), None);
+        let overflowed = to_js(&digest, hex);
+        overflowed.set(0, JsValue::from_str(&hex(modulus::<Fr>())));
+        assert_eq!(verify(Some(overflowed)), None);
+
+        // The instances of a batch are given per proof.
+        let num_instances = NoSha2Circuit::INFO.num_instances;
+        let batch = Array::of2(&to_js(&digest, hex), &to_js(&other, decimal));
+        assert_eq!(
+            batch_instances_from_js(Some(batch.clone()), 2, num_instances).unwrap(),
+            vec![
+                NoSha2Circuit::instances(&digest),
+                NoSha2Circuit::instances(&other)
+            ]
+        );
+        assert!(batch_instances_from_js(Some(batch), 3, num_instances).is_err());
+        assert!(batch_instances_from_js(None, 2, num_instances).is_err());
+        assert!(batch_instances_from_js(Some(to_js(&digest, hex)), 32, num_instances).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_batch_with_public_digests() {
+        type NoSha2Circuit = Pkcs1v15_1024_64WasmNoSha2Circuit<Fr>;
+        let artifacts = setup_pkcs1v15_no_sha2_1024(None, None).unwrap();
+        let get = |key: &str| {
+            Uint8Array::new(&Reflect::get(&artifacts, &JsValue::from_str(key)).unwrap()).to_vec()
+        };
+        let private_key =
+            RsaPrivateKey::new(&mut thread_rng(), 1024).expect("failed to generate a key");
+        let n = BigUint::from_bytes_be(&private_key.n().to_bytes_be());
+        let digests =
+            [&b"hello world"[..], &b"hello again"[..]].map(|msg| Sha256::digest(msg).to_vec());
+        let circuits = digests
+            .iter()
+            .map(|digest| {
+                let sign = private_key
+                    .sign(Pkcs1v15Sign::new::<Sha256>(), digest)
+                    .unwrap();
+                let circuit = NoSha2Circuit {
+                    signature: RSASignature::new(Value::known(BigUint::from_bytes_be(&sign))),
+                    public_key: RSAPublicKey::new(
+                        Value::known(n.clone()),
+                        RSAPubE::Fix(BigUint::from(65537u32)),
+                    ),
+                    msg: digest.clone(),
+                    _f: PhantomData,
+                };
+                Ok((circuit, NoSha2Circuit::instances(digest)))
+            })
+            .collect();
+        let proofs = prove_batch::<NoSha2Circuit>(
+            &get("params"),
+            &get("pk"),
+            KeyFormat::RawBytes,
+            NoSha2Circuit::min_k(),
+            circuits,
+        )
+        .unwrap();
+        assert!(proofs
+            .iter()
+            .all(|proof| proof.is_instance_of::<Uint8Array>()));
+
+        let verify = |instances: &[Vec<Vec<Fr>>]| {
+            verify_batch::<NoSha2Circuit>(
+                &get("params"),
+                &get("vk"),
+                KeyFormat::RawBytes,
+                NoSha2Circuit::min_k(),
+                &proofs,
+                instances,
+            )
+            .map(|results| {
+                results
+                    .iter()
+                    .map(|result| result.as_bool().unwrap())
+                    .collect::<Vec<_>>()
+            })
+        };
+        let instances = digests
+            .iter()
+            .map(|digest| NoSha2Circuit::instances(digest))
+            .collect::<Vec<_>>();
+        assert_eq!(verify(&instances).unwrap(), vec![true, true]);
+        // Each proof is bound to its own digest, so the instances of the other proof are invalid for it.
+        let swapped = instances.iter().rev().cloned().collect::<Vec<_>>();
+        assert_eq!(verify(&swapped).unwrap(), vec![false, false]);
+        // One instance vector shared by the whole batch is rejected.
+        assert!(verify(&instances[..1]).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_seeded_proof_matches_native_proof() {
+        let artifacts = setup_pkcs1v15_1024_64(None, None).unwrap();
+        let get = |key: &str| {
+            Uint8Array::new(&Reflect::get(&artifacts, &JsValue::from_str(key)).unwrap())
+        };
+        let private_key = sample_rsa_private_key(1024);
+        let public_key = generate_rsa_public_key(private_key.clone());
+        let msg = Uint8Array::from(&b"hello world"[..]);
+        let signature = sign(private_key, msg.clone().into());
+        let seed = [7u8; 32];
+
+        let proof = prove_pkcs1v15_1024_64_circuit_seeded(
+            get("params").into(),
+            get("pk").into(),
+            public_key.clone(),
+            msg.clone().into(),
+            signature.clone().into(),
+            None,
+            Uint8Array::from(&seed[..]),
+        )
+        .unwrap()
+        .to_vec();
+
+        let params = get("params").to_vec();
+        let params = read_params(&params).unwrap();
+        let pk = get("pk").to_vec();
+        let pk = read_pk_checked::<Pkcs1v15_1024_64EnabledBenchCircuit<Fr>>(&pk).unwrap();
+        let circuit = Pkcs1v15_1024_64EnabledBenchCircuit::<Fr> {
+            signature: signature_from_js(&signature.clone().into(), 1024),
+            public_key: public_key_from_js(
+                public_key.clone(),
+                None,
+                Pkcs1v15_1024_64EnabledBenchCircuit::<Fr>::DEFAULT_E,
+            ),
+            msg: msg_from_js(&msg.clone().into(), true, 64),
+            _f: PhantomData,
+        };
+        let native_proof = create_proof_with_seed(
+            &params,
+            &pk,
+            circuit,
+            &[],
+            TranscriptKind::Blake2b,
+            MultiOpenScheme::Gwc,
+            Some(seed),
+        )
+        .unwrap();
+        assert_eq!(proof, native_proof);
+        assert!(verify_pkcs1v15_1024_64_circuit(
+            get("params").into(),
+            get("vk").into(),
+            Uint8Array::from(&native_proof[..]).into()
+        ));
+
+        assert!(prove_pkcs1v15_1024_64_circuit_seeded(
+            get("params").into(),
+            get("pk").into(),
+            public_key,
+            msg.into(),
+            signature.into(),
+            None,
+            Uint8Array::from(&seed[..31]),
+        )
+        .is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_setup_from_params_is_deterministic() {
+        let artifacts = setup_pkcs1v15_1024_64(None, None).unwrap();
+        let get =
+            |object: &JsValue, key: &str| Reflect::get(object, &JsValue::from_str(key)).unwrap();
+        let params = Uint8Array::new(&get(&artifacts, "params"));
+        let bundle =
+            setup_pkcs1v15_1024_64_from_params(compress_params(params.clone()).unwrap(), None)
+                .unwrap();
+        for key in ["params", "pk", "vk"] {
+            assert_eq!(
+                Uint8Array::new(&get(&bundle, key)).to_vec(),
+                Uint8Array::new(&get(&artifacts, key)).to_vec()
+            );
+        }
+        assert_eq!(get(&bundle, "vkDigest"), get(&artifacts, "vkDigest"));
+
+        // The keys depend on e.
+        let e3 = Uint8Array::from(&[3u8][..]);
+        let bundle_e3 = setup_pkcs1v15_1024_64_from_params(params.clone(), Some(e3)).unwrap();
+        assert_ne!(get(&bundle_e3, "vkDigest"), get(&artifacts, "vkDigest"));
+
+        let e2 = Uint8Array::from(&[2u8][..]);
+        assert!(setup_pkcs1v15_1024_64_from_params(params, Some(e2)).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_verify_detailed_reports_reasons() {
+        let artifacts = setup_pkcs1v15_1024_64(None, None).unwrap();
+        let get =
+            |object: &JsValue, key: &str| Reflect::get(object, &JsValue::from_str(key)).unwrap();
+        let private_key = sample_rsa_private_key(1024);
+        let public_key = generate_rsa_public_key(private_key.clone());
+        let msg = Uint8Array::from(&b"hello world"[..]);
+        let signature = sign(private_key, msg.clone().into());
+        let proof = prove_pkcs1v15_1024_64_circuit(
+            get(&artifacts, "params"),
+            get(&artifacts, "pk"),
+            public_key,
+            msg.into(),
+            signature.into(),
+            None,
+            None,
+        )
+        .to_vec();
+        let verify = |vk: JsValue, proof: &[u8]| {
+            let result: JsValue = verify_pkcs1v15_1024_64_circuit_detailed(
+                get(&artifacts, "params"),
+                vk,
+                Uint8Array::from(proof).into(),
+                None,
+                None,
+                None,
+            )
+            .into();
+            (
+                get(&result, "ok").as_bool().unwrap(),
+                get(&result, "code").as_string(),
+            )
+        };
+        assert_eq!(verify(get(&artifacts, "vk"), &proof), (true, None));
+        assert_eq!(
+            verify(get(&artifacts, "vk"), &proof[..proof.len() - 1]),
+            (false, Some("truncated_proof".to_string()))
+        );
+        let mut flipped = proof.clone();
+        flipped[31] ^= 0x80;
+        assert_eq!(
+            verify(get(&artifacts, "vk"), &flipped),
+            (false, Some("constraint_check_failed".to_string()))
+        );
+        // The keys for e = 3 are generated for the same params and circuit.
+        let e3_artifacts = setup_pkcs1v15_1024_64_from_params(
+            Uint8Array::new(&get(&artifacts, "params")),
+            Some(Uint8Array::from(&[3u8][..])),
+        )
+        .unwrap();
+        assert_eq!(
+            verify(get(&e3_artifacts, "vk"), &proof),
+            (false, Some("constraint_check_failed".to_string()))
+        );
+        assert_eq!(
+            verify(Uint8Array::from(&b"not a key"[..]).into(), &proof),
+            (false, Some("invalid_verifying_key".to_string()))
+        );
+
+        // The proof is also accepted as a hex string.
+        let verify_hex = |hex: String| {
+            let result: JsValue = verify_pkcs1v15_1024_64_circuit_detailed(
+                get(&artifacts, "params"),
+                get(&artifacts, "vk"),
+                JsValue::from_str(&hex),
+                None,
+                None,
+                None,
+            )
+            .into();
+            (
+                get(&result, "ok").as_bool().unwrap(),
+                get(&result, "code").as_string(),
+            )
+        };
+        let hex = String::from(proof_to_hex(Uint8Array::from(&proof[..]).into()).unwrap());
+        assert_eq!(verify_hex(hex.clone()), (true, None));
+        assert_eq!(
+            verify_hex(format!("0x{}", hex.to_uppercase())),
+            (true, None)
+        );
+        assert_eq!(
+            verify_hex(hex[..hex.len() - 2].to_string()),
+            (false, Some("malformed_proof".to_string()))
+        );
+        assert_eq!(
+            verify_hex(format!("{} ", hex)),
+            (false, Some("malformed_proof".to_string()))
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_proof_bundle_roundtrip() {
+        let artifacts = setup_pkcs1v15_1024_64(None, None).unwrap();
+        let get = |key: &str| Reflect::get(&artifacts, &JsValue::from_str(key)).unwrap();
+        let private_key: RsaPrivateKey =
+            serde_wasm_bindgen::from_value(sample_rsa_private_key(1024)).unwrap();
+        let pem = RsaPublicKey::from(&private_key)
+            .to_public_key_pem(rsa::pkcs8::LineEnding::LF)
+            .unwrap();
+        let msg = Uint8Array::from(&b"hello world"[..]);
+        let signature = sign(
+            serde_wasm_bindgen::to_value(&private_key).unwrap(),
+            msg.clone().into(),
+        );
+        let input = Object::new();
+        for (key, value) in [
+            ("circuit", JsValue::from_str("1024_64")),
+            ("params", get("params")),
+            ("provingKey", get("pk")),
+            ("publicKeyPem", JsValue::from_str(&pem)),
+            ("msg", msg.into()),
+            (
+                "signatureBase64",
+                JsValue::from_str(
+                    &base64::engine::general_purpose::STANDARD.encode(signature.to_vec()),
+                ),
+            ),
+        ] {
+            Reflect::set(&input, &JsValue::from_str(key), &value).unwrap();
+        }
+        let bundle =
+            prove_pkcs1v15_bundle(JsValue::from(input).unchecked_into::<ProvePkcs1v15Input>())
+                .unwrap();
+        let verify = |bundle: &[u8]| {
+            let result: JsValue = verify_proof_bundle(
+                Uint8Array::new(&get("params")),
+                Uint8Array::new(&get("vk")),
+                Uint8Array::from(bundle),
+            )
+            .into();
+            Reflect::get(&result, &JsValue::from_str("code"))
+                .unwrap()
+                .as_string()
+        };
+        assert_eq!(verify(&bundle.to_vec()), None);
+
+        // The proof in the bundle is still accepted by the legacy verify functions.
+        let decoded = ProofBundle::from_bytes(&bundle.to_vec()).unwrap();
+        assert!(verify_pkcs1v15_1024_64_circuit(
+            get("params"),
+            get("vk"),
+            Uint8Array::from(&decoded.proof[..]).into(),
+        ));
+
+        let mut other_circuit = decoded.clone();
+        other_circuit.circuit_id = circuit_id_of_name("2048_64").unwrap().to_string();
+        assert_eq!(
+            verify(&other_circuit.to_bytes().unwrap()),
+            Some("invalid_verifying_key".to_string())
+        );
+        let mut unknown_circuit = decoded.clone();
+        unknown_circuit.circuit_id = "unknown".to_string();
+        assert_eq!(
+            verify(&unknown_circuit.to_bytes().unwrap()),
+            Some("circuit_mismatch".to_string())
+        );
+        let mut tampered = decoded;
+        tampered.instances = vec![vec![Fr::from(1)]];
+        assert_eq!(
+            verify(&tampered.to_bytes().unwrap()),
+            Some("invalid_instances".to_string())
+        );
+        assert_eq!(
+            verify(&bundle.to_vec()[1..]),
+            Some("malformed_proof".to_string())
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_verify_builtin_errors() {
+        let empty = Uint8Array::new_with_length(0);
+        assert!(verify_pkcs1v15_builtin("4096_64", empty.clone(), empty.clone(), None).is_err());
+        if builtin_vk("1024_64").is_none() {
+            assert!(verify_pkcs1v15_builtin("1024_64", empty.clone(), empty, None).is_err());
+            return;
+        }
+        let instances = Array::of1(&JsValue::from_str("0x1"));
+        assert!(verify_pkcs1v15_builtin("1024_64", empty.clone(), empty, Some(instances)).is_err());
+    }
+
+    /// Guards the embedded keys against drift from the circuits by regenerating them for the params they were generated for.
+    ///
+    /// The params of the trusted setup are too large to embed, so they are fetched from `HALO2_RSA_BUILTIN_PARAMS_URL` set at build time.
+    #[cfg(feature = "builtin-vks")]
+    #[wasm_bindgen_test]
+    async fn test_builtin_vks_match_keygen() {
+        let url = option_env!("HALO2_RSA_BUILTIN_PARAMS_URL").expect(
+            "set HALO2_RSA_BUILTIN_PARAMS_URL to the params the builtin vks were generated for",
+        );
+        let window = web_sys::window().unwrap();
+        let response: web_sys::Response = JsFuture::from(window.fetch_with_str(url))
+            .await
+            .unwrap()
+            .dyn_into()
+            .unwrap();
+        let params = Uint8Array::new(
+            &JsFuture::from(response.array_buffer().unwrap())
+                .await
+                .unwrap(),
+        );
+
+        let setups: [(
+            &str,
+            fn(Uint8Array, Option<Uint8Array>) -> Result<JsValue, JsError>,
+        ); 7] = [
+            ("1024_64", setup_pkcs1v15_1024_64_from_params),
+            ("1024_128", setup_pkcs1v15_1024_128_from_params),
+            ("1024_1024", setup_pkcs1v15_1024_1024_from_params),
+            ("2048_64", setup_pkcs1v15_2048_64_from_params),
+            ("2048_128", setup_pkcs1v15_2048_128_from_params),
+            ("2048_1024", setup_pkcs1v15_2048_1024_from_params),
+            (
+                "no_sha2_2048_1024",
+                setup_pkcs1v15_no_sha2_2048_1024_from_params,
+            ),
+        ];
+        assert_eq!(setups.len(), BUILTIN_VKS.len());
+        for (name, setup) in setups {
+            let artifacts = setup(params.clone(), None).unwrap();
+            let vk = Uint8Array::new(&Reflect::get(&artifacts, &JsValue::from_str("vk")).unwrap());
+            assert_eq!(
+                builtin_vk(name).unwrap().to_vec(),
+                vk.to_vec(),
+                "the embedded vk of the circuit {} is stale",
+                name
+            );
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_batch_with_corrupted_signature() {
+        let artifacts = setup_pkcs1v15_1024_64(None, None).unwrap();
+        let get = |key: &str| {
+            Uint8Array::new(&Reflect::get(&artifacts, &JsValue::from_str(key)).unwrap())
+        };
+        let private_key = sample_rsa_private_key(1024);
+        let public_key = generate_rsa_public_key(private_key.clone());
+        let msgs = Array::new();
+        let signatures = Array::new();
+        for msg in [&b"hello world"[..], &b"hello again"[..], &b"goodbye"[..]] {
+            let msg = Uint8Array::from(msg);
+            signatures.push(&sign(private_key.clone(), msg.clone().into()));
+            msgs.push(&msg);
+        }
+        let mut corrupted = Uint8Array::new(&signatures.get(1)).to_vec();
+        corrupted[0] ^= 1;
+        signatures.set(1, Uint8Array::from(&corrupted[..]).into());
+
+        let proofs = prove_pkcs1v15_batch(
+            "1024_64",
+            get("params"),
+            get("pk"),
+            public_key.clone(),
+            msgs.clone(),
+            signatures.clone(),
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(proofs.length(), 3);
+        assert!(proofs.get(0).is_instance_of::<Uint8Array>());
+        assert!(proofs.get(1).is_instance_of::<js_sys::Error>());
+        assert!(proofs.get(2).is_instance_of::<Uint8Array>());
+
+        // The error in place of the proof is verified as an invalid proof.
+        let verify = |vk: Uint8Array, vk_format: Option<KeyFormat>, proofs: Array| {
+            verify_pkcs1v15_batch("1024_64", get("params"), vk, proofs, None, vk_format)
+                .unwrap()
+                .iter()
+                .map(|result| result.as_bool().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            verify(get("vk"), None, proofs.clone()),
+            vec![true, false, true]
+        );
+
+        // The keys converted to another format create and verify the same proofs as the raw keys.
+        let processed = |key: Uint8Array| {
+            convert_key_format("1024_64", key, None, KeyFormat::Processed).unwrap()
+        };
+        assert_eq!(
+            verify(processed(get("vk")), Some(KeyFormat::Processed), proofs),
+            vec![true, false, true]
+        );
+        let processed_proofs = prove_pkcs1v15_batch(
+            "1024_64",
+            get("params"),
+            processed(get("pk")),
+            public_key.clone(),
+            msgs.clone(),
+            signatures.clone(),
+            None,
+            Some(KeyFormat::Processed),
+        )
+        .unwrap();
+        assert_eq!(
+            verify(get("vk"), None, processed_proofs),
+            vec![true, false, true]
+        );
+        assert!(prove_pkcs1v15_batch(
+            "1024_64",
+            get("params"),
+            processed(get("pk")),
+            public_key.clone(),
+            msgs.clone(),
+            signatures.clone(),
+            None,
+            None,
+        )
+        .is_err());
+
+        signatures.pop();
+        assert!(prove_pkcs1v15_batch(
+            "1024_64",
+            get("params"),
+            get("pk"),
+            public_key,
+            msgs,
+            signatures,
+            None,
+            None,
+        )
+        .is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_circuit_info_matches_macro_arguments() {
+        let get =
+            |object: &JsValue, key: &str| Reflect::get(object, &JsValue::from_str(key)).unwrap();
+        macro_rules! check_circuit {
+            ($name:expr, $circuit_name:ident) => {{
+                let info = circuit_info($name).unwrap();
+                let k = get(&info, "k").as_f64().unwrap() as u32;
+                assert_eq!(k, $circuit_name::<Fr>::min_k());
+                assert_eq!(k as usize, $circuit_name::<Fr>::LOOKUP_BITS + 1);
+                assert_eq!(
+                    get(&info, "bitsLen").as_f64().unwrap() as usize,
+                    $circuit_name::<Fr>::BITS_LEN
+                );
+                let sha2_chip_enabled = get(&info, "sha2ChipEnabled").as_bool().unwrap();
+                if sha2_chip_enabled {
+                    assert_eq!(
+                        get(&info, "msgLen").as_f64().unwrap() as usize,
+                        $circuit_name::<Fr>::MSG_LEN
+                    );
+                    let recommended = recommend_k_with_advice(
+                        $circuit_name::<Fr>::BITS_LEN,
+                        $circuit_name::<Fr>::MSG_LEN,
+                        $circuit_name::<Fr>::NUM_ADVICE,
+                    );
+                    assert!(recommended <= k);
+                }
+                assert!(get(&info, "proofSizeEstimate").as_f64().unwrap() > 0.0);
+                // The params alone take 2^k G1 points of both bases, and the key generation allocates the proving key besides them.
+                let setup_mem = get(&info, "setupMemEstimateMb").as_f64().unwrap() as usize;
+                assert!(setup_mem > params_bytes(k) >> 20);
+                assert!(
+                    get(&info, "provingMemEstimateMb").as_f64().unwrap() as usize
+                        >= params_bytes(k) >> 20
+                );
+            }};
+        }
+        check_circuit!("1024_64", Pkcs1v15_1024_64EnabledBenchCircuit);
+        check_circuit!("1024_128", Pkcs1v15_1024_128EnabledBenchCircuit);
+        check_circuit!("1024_1024", Pkcs1v15_1024_1024EnabledBenchCircuit);
+        check_circuit!("2048_64", Pkcs1v15_2048_64EnabledBenchCircuit);
+        check_circuit!("2048_128", Pkcs1v15_2048_128EnabledBenchCircuit);
+        check_circuit!("2048_1024", Pkcs1v15_2048_1024EnabledBenchCircuit);
+        check_circuit!("no_sha2_2048_1024", Pkcs1v15_2048_1024DisabledBenchCircuit);
+
+        let names = list_circuits()
+            .iter()
+            .map(|name| name.as_string().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names.len(), 7);
+        assert_eq!(names[0], "1024_64");
+        assert!(circuit_info("4096_64").is_err());
+    }
+
+    #[cfg(feature = "memory-stats")]
+    fn stat(stats: &JsValue, key: &str) -> usize {
+        Reflect::get(stats, &JsValue::from_str(key))
+            .unwrap()
+            .as_f64()
+            .unwrap() as usize
+    }
+
+    #[cfg(feature = "memory-stats")]
+    /// Samples the linear memory bytes and the peak heap bytes whenever the prover writes the transcript.
+    struct MemorySampler(Vec<(usize, usize)>);
+
+    #[cfg(feature = "memory-stats")]
+    impl Write for MemorySampler {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let stats = memory_stats();
+            self.0.push((
+                stat(&stats, "linearMemoryBytes"),
+                stat(&stats, "peakHeapBytes"),
+            ));
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "memory-stats")]
+    #[wasm_bindgen_test]
+    fn test_memory_stats_are_monotone_during_proof() {
+        let artifacts = setup_pkcs1v15_1024_64(None, None).unwrap();
+        let get = |key: &str| {
+            Uint8Array::new(&Reflect::get(&artifacts, &JsValue::from_str(key)).unwrap()).to_vec()
+        };
+        let params = read_params(&get("params")).unwrap();
+        let pk = read_pk_checked::<Pkcs1v15_1024_64EnabledBenchCircuit<Fr>>(&get("pk")).unwrap();
+        let private_key = sample_rsa_private_key(1024);
+        let public_key = generate_rsa_public_key(private_key.clone());
+        let msg = Uint8Array::from(&b"hello world"[..]);
+        let signature = sign(private_key, msg.clone().into());
+        let circuit = Pkcs1v15_1024_64EnabledBenchCircuit::<Fr> {
+            signature: signature_from_js(&signature.into(), 1024),
+            public_key: public_key_from_js(
+                public_key,
+                None,
+                Pkcs1v15_1024_64EnabledBenchCircuit::<Fr>::DEFAULT_E,
+            ),
+            msg: msg_from_js(&msg.into(), true, 64),
+            _f: PhantomData,
+        };
+
+        let before = memory_stats();
+        let first_sample = (
+            stat(&before, "linearMemoryBytes"),
+            stat(&before, "peakHeapBytes"),
+        );
+        let sampler = MemorySampler(vec![first_sample]);
+        let writer = PhaseWriter::new::<Pkcs1v15_1024_64EnabledBenchCircuit<Fr>>(sampler);
+        let samples = create_proof_to_writer(
+            &params,
+            &pk,
+            circuit,
+            &[],
+            TranscriptKind::Blake2b,
+            MultiOpenScheme::Gwc,
+            None,
+            writer,
+        )
+        .unwrap()
+        .finish()
+        .0;
+        assert!(samples.len() > 2);
+        for pair in samples.windows(2) {
+            assert!(pair[0].0 <= pair[1].0);
+            assert!(pair[0].1 <= pair[1].1);
+        }
+
+        let after = memory_stats();
+        let peak = stat(&after, "peakHeapBytes");
+        assert!(stat(&after, "heapBytes") <= peak);
+        assert!(peak <= stat(&after, "linearMemoryBytes"));
+        let phases = Reflect::get(&after, &JsValue::from_str("phasePeakBytes")).unwrap();
+        for phase in ["assignment", "commitment", "opening"] {
+            let phase_peak = stat(&phases, phase);
+            assert!(phase_peak > 0);
+            assert!(phase_peak <= peak);
+        }
+    }
+
+    #[cfg(feature = "memory-stats")]
+    #[wasm_bindgen_test]
+    fn test_setup_peak_is_within_keygen_estimate() {
+        let artifacts = setup_pkcs1v15_1024_64(None, None).unwrap();
+        let params =
+            Uint8Array::new(&Reflect::get(&artifacts, &JsValue::from_str("params")).unwrap());
+        let k = read_params(&params.to_vec()).unwrap().k();
+        drop(artifacts);
+
+        let heap_before = stat(&memory_stats(), "heapBytes");
+        take_window_peak();
+        setup_pkcs1v15_1024_64_from_params(params, None).unwrap();
+        let measured = take_window_peak() - heap_before;
+        // The proving key takes more than the params alone, which the setup used to probe.
+        assert!(measured > params_bytes(k));
+        assert!(
+            measured <= estimate_keygen_memory::<Pkcs1v15_1024_64EnabledBenchCircuit<Fr>>(k),
+            "the setup peaked at {} bytes",
+            measured
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_progress_reporter_order() {
+        let calls = Array::new();
+        let callback = Function::new_with_args("stage, percent", "this.push([stage, percent])");
+        let callback = callback.bind(&calls);
+        let mut progress = ProgressReporter::new(Some(callback));
+        for _ in 0..PROVE_MILESTONES.len() {
+            progress.advance();
+        }
+        assert_eq!(calls.length() as usize, PROVE_MILESTONES.len());
+        let mut last_percent = 0;
+        for (i, (stage, percent)) in PROVE_MILESTONES.iter().enumerate() {
+            let call = Array::from(&calls.get(i as u32));
+            assert_eq!(call.get(0).as_string().unwrap(), *stage);
+            let reported = call.get(1).as_f64().unwrap() as u32;
+            assert_eq!(reported, *percent);
+            assert!(reported > last_percent);
+            last_percent = reported;
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_prove_reports_milestones_in_order() {
+        let artifacts = setup_pkcs1v15_1024_64(None, None).unwrap();
+        let get = |key: &str| Reflect::get(&artifacts, &JsValue::from_str(key)).unwrap();
+        let private_key = sample_rsa_private_key(1024);
+        let public_key = generate_rsa_public_key(private_key.clone());
+        let msg = Uint8Array::from(&b"hello world"[..]);
+        let signature = sign(private_key, msg.clone().into());
+        let calls = Array::new();
+        let callback = Function::new_with_args("stage, percent", "this.push([stage, percent])");
+        let proof = prove_pkcs1v15_1024_64_circuit(
+            get("params"),
+            get("pk"),
+            public_key,
+            msg.into(),
+            signature.into(),
+            None,
+            Some(callback.bind(&calls)),
+        );
+
+        assert_eq!(calls.length() as usize, PROVE_MILESTONES.len());
+        for (i, (stage, percent)) in PROVE_MILESTONES.iter().enumerate() {
+            let call = Array::from(&calls.get(i as u32));
+            assert_eq!(call.get(0).as_string().unwrap(), *stage);
+            assert_eq!(call.get(1).as_f64().unwrap() as u32, *percent);
+        }
+        assert!(verify_pkcs1v15_1024_64_circuit(
+            get("params"),
+            get("vk"),
+            proof.into()
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_proof_promise_resolves_with_proof() {
+        let (sender, receiver) = mpsc::unbounded();
+        let promise = proof_promise(receiver);
+        sender.unbounded_send(Ok(vec![1, 2, 3])).unwrap();
+        let proof = JsFuture::from(promise).await.unwrap();
+        assert_eq!(Uint8Array::new(&proof).to_vec(), vec![1, 2, 3]);
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_proof_promise_rejects_on_abort() {
+        let (sender, receiver) = mpsc::unbounded();
+        let promise = proof_promise(receiver);
+        let abort_sender = sender.clone();
+        abort_sender.unbounded_send(Err(ABORTED_MESSAGE)).unwrap();
+        sender.unbounded_send(Ok(vec![1, 2, 3])).unwrap();
+        assert!(JsFuture::from(promise).await.is_err());
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_prove_on_pool_with_aborted_signal() {
+        let signal = Object::new();
+        Reflect::set(&signal, &JsValue::from_str("aborted"), &JsValue::TRUE).unwrap();
+        let promise = prove_on_pool(|| unreachable!(), Some(signal));
+        assert!(JsFuture::from(promise).await.is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_progress_reporter_without_callback() {
+        let mut progress = ProgressReporter::new(None);
+        for _ in 0..PROVE_MILESTONES.len() {
+            progress.advance();
+        }
+    }
+}
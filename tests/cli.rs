@@ -0,0 +1,164 @@
+//! Runs the `halo2-rsa` binary through its subcommands on a temporary directory.
+//!
+//! The round trip sets up a circuit of k = 19, so it is ignored by default:
+//! `cargo test --release --test cli -- --ignored`.
+
+use rsa::{
+    pkcs1v15::SigningKey,
+    pkcs8::{EncodePublicKey, LineEnding},
+    signature::{SignatureEncoding, Signer},
+    RsaPrivateKey, RsaPublicKey,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn halo2_rsa(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_halo2-rsa"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("failed to run halo2-rsa")
+}
+
+/// Creates an empty directory for a test, which is removed before the test if left by a previous run.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("halo2-rsa-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn assert_exit(output: &Output, code: i32) {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(code), "stderr: {}", stderr);
+    if code != 0 {
+        assert!(stderr.starts_with("error: "), "stderr: {}", stderr);
+    }
+}
+
+#[test]
+fn test_cli_rejects_invalid_arguments() {
+    let dir = temp_dir("args");
+    assert_exit(&halo2_rsa(&dir, &[]), 2);
+    assert_exit(&halo2_rsa(&dir, &["keygen", "--circuit", "1024_64"]), 2);
+    assert_exit(
+        &halo2_rsa(&dir, &["setup", "--circuit", "1024_32", "--out-dir", "."]),
+        2,
+    );
+    assert_exit(&halo2_rsa(&dir, &["setup", "--circuit", "1024_64"]), 2);
+    assert_exit(
+        &halo2_rsa(&dir, &["verify", "--circuit", "1024_64", "--proof"]),
+        2,
+    );
+    assert_exit(
+        &halo2_rsa(
+            &dir,
+            &[
+                "verify",
+                "--circuit",
+                "1024_64",
+                "--proof",
+                "a",
+                "--proof",
+                "b",
+            ],
+        ),
+        2,
+    );
+    // The arguments are valid, but the files do not exist.
+    assert_exit(
+        &halo2_rsa(
+            &dir,
+            &["verify", "--circuit", "1024_64", "--proof", "proof.bin"],
+        ),
+        3,
+    );
+    assert_exit(&halo2_rsa(&dir, &["--help"]), 0);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+#[ignore = "sets up and proves a circuit of k = 19"]
+fn test_cli_setup_prove_verify() {
+    let dir = temp_dir("roundtrip");
+    let mut rng = rand::thread_rng();
+    let private_key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+    let public_key = RsaPublicKey::from(&private_key);
+    let msg = b"hello from the command line".to_vec();
+    let signature = SigningKey::<rsa::sha2::Sha256>::new(private_key)
+        .sign(&msg)
+        .to_vec();
+    fs::write(
+        dir.join("key.pem"),
+        public_key.to_public_key_pem(LineEnding::LF).unwrap(),
+    )
+    .unwrap();
+    fs::write(dir.join("msg.bin"), &msg).unwrap();
+    fs::write(dir.join("sig.bin"), &signature).unwrap();
+
+    let setup = halo2_rsa(
+        &dir,
+        &["setup", "--circuit", "1024_64", "--out-dir", "artifacts"],
+    );
+    assert_exit(&setup, 0);
+    assert_eq!(String::from_utf8(setup.stdout).unwrap().trim().len(), 64);
+    for file in ["params.bin", "pk.bin", "vk.bin"] {
+        assert!(dir.join("artifacts").join(file).exists());
+    }
+
+    let prove_args = [
+        "prove",
+        "--circuit",
+        "1024_64",
+        "--key",
+        "key.pem",
+        "--msg",
+        "msg.bin",
+        "--sig",
+        "sig.bin",
+        "--out",
+        "proof.bin",
+        "--instances-out",
+        "instances.json",
+    ];
+    assert_exit(&halo2_rsa(&dir, &prove_args), 0);
+    assert_eq!(
+        fs::read_to_string(dir.join("instances.json")).unwrap(),
+        "[]"
+    );
+
+    let verify_args = [
+        "verify",
+        "--circuit",
+        "1024_64",
+        "--proof",
+        "proof.bin",
+        "--instances",
+        "instances.json",
+    ];
+    let verify = halo2_rsa(&dir, &verify_args);
+    assert_exit(&verify, 0);
+    assert_eq!(String::from_utf8(verify.stdout).unwrap().trim(), "valid");
+
+    // The keys are bound to the circuit.
+    assert_exit(
+        &halo2_rsa(
+            &dir,
+            &["verify", "--circuit", "1024_128", "--proof", "proof.bin"],
+        ),
+        3,
+    );
+
+    let mut proof = fs::read(dir.join("proof.bin")).unwrap();
+    let last = proof.len() - 1;
+    proof[last] ^= 1;
+    fs::write(dir.join("proof.bin"), &proof).unwrap();
+    assert_exit(&halo2_rsa(&dir, &verify_args), 1);
+
+    // A message other than the signed one fails before proving.
+    fs::write(dir.join("msg.bin"), b"hello from the browser").unwrap();
+    assert_exit(&halo2_rsa(&dir, &prove_args), 3);
+
+    fs::remove_dir_all(dir).unwrap();
+}
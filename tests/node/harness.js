@@ -0,0 +1,49 @@
+// Exercises the Node.js build of the wasm module built by `tests/nodejs.rs`.
+//
+// Usage: node harness.js <pkg dir> [params file]
+// If the params file for k = 19 is given, it also proves and verifies a signature with the 1024_64 circuit.
+const assert = require("assert");
+const fs = require("fs");
+const path = require("path");
+
+const pkg = require(path.resolve(process.argv[2]));
+const paramsPath = process.argv[3];
+
+pkg.init_node();
+assert.strictEqual(pkg.is_multithreading_available(), false);
+
+assert.ok(pkg.list_circuits().includes("1024_64"));
+assert.strictEqual(pkg.circuit_info("1024_64").k, 19);
+
+const privateKey = pkg.sample_rsa_private_key(1024);
+const publicKey = pkg.generate_rsa_public_key(privateKey);
+const msg = Buffer.from("hello from node");
+const signature = pkg.sign(privateKey, msg);
+const digest = Buffer.from(pkg.sha256_msg(msg));
+assert.strictEqual(pkg.verify_raw_digest_native(publicKey, digest, signature), true);
+assert.strictEqual(pkg.check_signature_native(publicKey, msg, signature), true);
+signature[0] ^= 1;
+assert.strictEqual(pkg.verify_raw_digest_native(publicKey, digest, signature), false);
+signature[0] ^= 1;
+
+// Errors are thrown as exceptions rather than written to stderr.
+assert.throws(() => pkg.circuit_info("4096_64"), /unknown circuit/);
+
+if (paramsPath) {
+  // The params are passed as a Buffer without opening any file in wasm.
+  const params = fs.readFileSync(paramsPath);
+  const artifacts = pkg.setup_pkcs1v15_1024_64_from_params(params);
+  const proof = pkg.prove_pkcs1v15_1024_64_circuit(
+    params,
+    artifacts.pk,
+    publicKey,
+    msg,
+    signature
+  );
+  assert.strictEqual(
+    pkg.verify_pkcs1v15_1024_64_circuit(params, artifacts.vk, proof),
+    true
+  );
+}
+
+console.log("node harness passed");
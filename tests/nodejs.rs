@@ -0,0 +1,33 @@
+//! Builds the wasm module for Node.js and runs `tests/node/harness.js` against it.
+//!
+//! It needs `wasm-pack` and Node.js 18+ on the `PATH`, so it is ignored by default:
+//! `cargo test --test nodejs -- --ignored`.
+//! Set `HALO2_RSA_NODE_PARAMS` to a params file for k = 19 to prove and verify a signature as well.
+
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+#[ignore = "requires wasm-pack and Node.js 18+"]
+fn test_nodejs_build() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let out_dir = root.join("target").join("pkg-node");
+    let status = Command::new("wasm-pack")
+        .current_dir(root)
+        .args(["build", "--target", "nodejs", "--out-dir"])
+        .arg(&out_dir)
+        .args(["--", "--no-default-features", "--features", "sha256,nodejs"])
+        .status()
+        .expect("failed to run wasm-pack");
+    assert!(status.success(), "wasm-pack build failed");
+
+    let mut harness = Command::new("node");
+    harness
+        .arg(root.join("tests").join("node").join("harness.js"))
+        .arg(&out_dir);
+    if let Some(params) = std::env::var_os("HALO2_RSA_NODE_PARAMS") {
+        harness.arg(params);
+    }
+    let status = harness.status().expect("failed to run node");
+    assert!(status.success(), "the node harness failed");
+}
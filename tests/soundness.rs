@@ -0,0 +1,169 @@
+//! Runs the pkcs1v15 circuits with systematically corrupted witnesses, which must fail a constraint of [`MockProver`] instead of being accepted or panicking.
+//!
+//! Each case documents a relation the circuit enforces:
+//! the signature, the message, and the modulus are bound to the verified relation, the signature is smaller than the modulus, and the whole EM block `s^e mod n` has the exact pkcs1v15 layout.
+//! The 2048 bits circuit takes a few minutes without `--release`: `cargo test --release --test soundness`.
+
+use halo2_base::halo2_proofs::{circuit::Value, dev::MockProver, halo2curves::bn256::Fr};
+use halo2_rsa::{Pkcs1v15Circuit, RSAPubE, RSAPublicKey, RSASignature, SHA256_OID};
+use num_bigint::BigUint;
+use rand::thread_rng;
+use rsa::{
+    pkcs1v15::SigningKey,
+    signature::{SignatureEncoding, Signer},
+    traits::{PrivateKeyParts, PublicKeyParts},
+    RsaPrivateKey,
+};
+use sha2::{Digest, Sha256};
+use std::panic::{self, AssertUnwindSafe};
+
+/// The maximum message length of the circuits including the 9 bytes of the SHA256 padding.
+const MSG_LEN: usize = 64;
+const E: u32 = 65537;
+
+type TestCircuit<const BITS_LEN: usize> = Pkcs1v15Circuit<Fr, BITS_LEN, MSG_LEN, true>;
+
+/// A witness of the circuit, which is corrupted field by field.
+#[derive(Clone)]
+struct Witness {
+    signature: BigUint,
+    n: BigUint,
+    msg: Vec<u8>,
+}
+
+impl Witness {
+    /// Signs `msg` with `private_key`.
+    fn sign(private_key: &RsaPrivateKey, msg: &[u8]) -> Self {
+        let signing_key = SigningKey::<rsa::sha2::Sha256>::new(private_key.clone());
+        Self {
+            signature: BigUint::from_bytes_be(&signing_key.sign(msg).to_vec()),
+            n: modulus_of(private_key),
+            msg: msg.to_vec(),
+        }
+    }
+
+    /// Runs [`MockProver`] and returns whether the constraints are satisfied.
+    ///
+    /// # Panics
+    /// Panics if the synthesis panics or fails, which would hide the constraint that rejects a corrupted witness.
+    fn verify<const BITS_LEN: usize>(&self, case: &str) -> bool {
+        let circuit = TestCircuit::<BITS_LEN>::try_new(
+            RSASignature::new(Value::known(self.signature.clone())),
+            RSAPublicKey::new(Value::known(self.n.clone()), RSAPubE::Fix(BigUint::from(E))),
+            self.msg.clone(),
+        )
+        .unwrap();
+        let instances = TestCircuit::<BITS_LEN>::instances(&self.msg);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            MockProver::run(TestCircuit::<BITS_LEN>::K, &circuit, instances)
+                .map(|prover| prover.verify())
+        }));
+        match result {
+            Ok(Ok(Ok(()))) => true,
+            Ok(Ok(Err(failures))) => {
+                assert!(!failures.is_empty());
+                false
+            }
+            Ok(Err(e)) => panic!("{} bits, {}: the synthesis failed: {:?}", BITS_LEN, case, e),
+            Err(_) => panic!(
+                "{} bits, {}: the circuit panicked instead of failing a constraint",
+                BITS_LEN, case
+            ),
+        }
+    }
+
+    fn assert_rejected<const BITS_LEN: usize>(&self, case: &str) {
+        assert!(
+            !self.verify::<BITS_LEN>(case),
+            "{} bits, {}: the corrupted witness is accepted",
+            BITS_LEN,
+            case
+        );
+    }
+}
+
+fn modulus_of(private_key: &RsaPrivateKey) -> BigUint {
+    BigUint::from_bytes_be(&private_key.n().to_bytes_be())
+}
+
+/// Returns the signature whose EM block `s^e mod n` is `em`, computed with the private exponent of `private_key`.
+fn forge_em_signature(private_key: &RsaPrivateKey, em: &[u8]) -> BigUint {
+    let d = BigUint::from_bytes_be(&private_key.d().to_bytes_be());
+    BigUint::from_bytes_be(em).modpow(&d, &modulus_of(private_key))
+}
+
+/// Returns the pkcs1v15 EM block of `bits_len` bits for `msg` whose run of 0xFF bytes is one byte shorter, followed by a trailing garbage byte.
+///
+/// A verifier parsing the block from its head would find the DigestInfo and the digest of `msg` in it.
+fn em_with_short_padding(bits_len: usize, msg: &[u8]) -> Vec<u8> {
+    let mut digest_info = vec![0x30, 0x31, 0x30, 0x0d, 0x06, 0x09];
+    digest_info.extend_from_slice(&SHA256_OID);
+    digest_info.extend_from_slice(&[0x05, 0x00, 0x04, 0x20]);
+    digest_info.extend_from_slice(&Sha256::digest(msg));
+    let em_len = bits_len / 8;
+    let mut em = vec![0x00, 0x01];
+    em.resize(em_len - digest_info.len() - 2, 0xff);
+    em.push(0x00);
+    em.extend(digest_info);
+    em.push(0xab);
+    assert_eq!(em.len(), em_len);
+    em
+}
+
+fn test_corrupted_witnesses<const BITS_LEN: usize>() {
+    let mut rng = thread_rng();
+    let private_key = RsaPrivateKey::new(&mut rng, BITS_LEN).expect("failed to generate a key");
+    let other_key = RsaPrivateKey::new(&mut rng, BITS_LEN).expect("failed to generate a key");
+    // `s + n` must fit in the limbs of the signature to be assigned as it is, so the message is chosen for a small `s`.
+    let valid = (0u32..)
+        .map(|i| {
+            Witness::sign(
+                &private_key,
+                format!("soundness test message {}", i).as_bytes(),
+            )
+        })
+        .find(|witness| (&witness.signature + &witness.n).bits() <= BITS_LEN as u64)
+        .unwrap();
+    assert!(valid.verify::<BITS_LEN>("valid witness"));
+
+    // The signature is bound by `s^e mod n`.
+    let mut corrupted = valid.clone();
+    corrupted.signature ^= BigUint::from(1u8) << (BITS_LEN / 2);
+    corrupted.assert_rejected::<BITS_LEN>("flipped signature bit");
+
+    // The message is bound by its SHA256 digest in the EM block.
+    let mut corrupted = valid.clone();
+    corrupted.msg[0] ^= 1;
+    corrupted.assert_rejected::<BITS_LEN>("flipped message byte");
+
+    // The modulus is bound limb by limb. The second limb is altered, which keeps `n` odd.
+    let mut corrupted = valid.clone();
+    corrupted.n ^= BigUint::from(0xffu8) << 64;
+    corrupted.assert_rejected::<BITS_LEN>("altered modulus limb");
+
+    // A signature valid for another key does not verify with this key.
+    let mut corrupted = Witness::sign(&other_key, &valid.msg);
+    corrupted.n = valid.n.clone();
+    corrupted.assert_rejected::<BITS_LEN>("signature of a different key");
+
+    // `s + n` has the same `s^e mod n`, so only the constraint `s < n` rejects it.
+    let mut corrupted = valid.clone();
+    corrupted.signature += &valid.n;
+    corrupted.assert_rejected::<BITS_LEN>("signature plus modulus");
+
+    // Every byte of the EM block is checked, not only the DigestInfo and the digest following the first zero byte.
+    let mut corrupted = valid.clone();
+    corrupted.signature =
+        forge_em_signature(&private_key, &em_with_short_padding(BITS_LEN, &valid.msg));
+    corrupted.assert_rejected::<BITS_LEN>("shortened 0xFF run");
+}
+
+#[test]
+fn test_corrupted_witnesses_1024() {
+    test_corrupted_witnesses::<1024>();
+}
+
+#[test]
+fn test_corrupted_witnesses_2048() {
+    test_corrupted_witnesses::<2048>();
+}